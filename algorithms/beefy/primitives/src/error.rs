@@ -83,6 +83,10 @@ pub enum BeefyClientError {
 		/// The location of the error
 		location: &'static str,
 	},
+	/// Commitments don't prove an equivocation of the authority set
+	#[from(ignore)]
+	#[display(fmt = "InvalidEquivocation: {}", _0)]
+	InvalidEquivocation(&'static str),
 	/// Invalid authority proof
 	InvalidAuthorityProof,
 	/// Invalid merkle proof
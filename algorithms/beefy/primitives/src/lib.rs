@@ -77,6 +77,15 @@ pub struct SignedCommitment {
 	pub signatures: Vec<SignatureWithAuthorityIndex>,
 }
 
+#[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
+/// Signed commitment with proof
+pub struct SignedCommitmentWithProof {
+	/// Signed commitment
+	pub signed_commitment: SignedCommitment,
+	/// Proof for the signatories in the authority set that signed the commitment
+	pub authority_proof: Vec<Hash>,
+}
+
 #[derive(sp_std::fmt::Debug, Clone, PartialEq, Eq, Encode, Decode)]
 /// Mmr Update with proof
 pub struct MmrUpdateProof {
@@ -29,7 +29,7 @@ pub mod runtime;
 
 use beefy_light_client_primitives::{
 	ClientState, HostFunctions, MerkleHasher, MmrUpdateProof, ParachainHeader, PartialMmrLeaf,
-	SignedCommitment,
+	SignedCommitment, SignedCommitmentWithProof,
 };
 use beefy_primitives::{
 	known_payloads::MMR_ROOT_ID,
@@ -43,7 +43,7 @@ use helpers::{
 };
 use hex_literal::hex;
 use pallet_mmr_primitives::Proof;
-use relay_chain_queries::fetch_beefy_justification;
+use relay_chain_queries::{fetch_beefy_justification, fetch_beefy_justification_at};
 use sp_core::{hexdisplay::AsBytesRef, keccak_256, H256};
 use sp_io::crypto;
 use sp_runtime::traits::BlakeTwo256;
@@ -361,6 +361,50 @@ where
 		})
 	}
 
+	/// Fetch the signed commitment in the beefy justification of the given relay chain block, if
+	/// it has one, along with the proof of its signatories in the authority set.
+	pub async fn fetch_signed_commitment_with_proof(
+		&self,
+		block_number: u32,
+	) -> Result<Option<SignedCommitmentWithProof>, Error> {
+		let subxt_block_number: subxt::rpc::types::BlockNumber = block_number.into();
+		let block_hash =
+			self.relay_client.rpc().block_hash(Some(subxt_block_number)).await?.ok_or_else(
+				|| Error::Custom(format!("No block hash found for block {block_number}")),
+			)?;
+		let signed_commitment =
+			match fetch_beefy_justification_at(&self.relay_client, block_hash).await? {
+				Some(signed_commitment) => signed_commitment,
+				None => return Ok(None),
+			};
+
+		let current_authorities = {
+			let key = runtime::api::storage().beefy().authorities();
+			self.relay_client
+				.storage()
+				.at(Some(block_hash))
+				.await
+				.expect("Storage client")
+				.fetch(&key)
+				.await?
+				.ok_or_else(|| Error::Custom(format!("No beefy authorities found!")))?
+				.0
+		};
+		let authority_address_hashes = hash_authority_addresses(
+			current_authorities.into_iter().map(|x| x.encode()).collect(),
+		)?;
+		let AuthorityProofWithSignatures { authority_proof, signatures } =
+			prove_authority_set(&signed_commitment, authority_address_hashes)?;
+
+		Ok(Some(SignedCommitmentWithProof {
+			signed_commitment: SignedCommitment {
+				commitment: signed_commitment.commitment,
+				signatures,
+			},
+			authority_proof,
+		}))
+	}
+
 	/// Construct a beefy client state to be submitted to the counterparty chain
 	pub async fn construct_beefy_client_state(&self) -> Result<ClientState, Error> {
 		let (signed_commitment, latest_beefy_finalized) =
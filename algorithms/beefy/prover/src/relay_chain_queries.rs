@@ -166,6 +166,34 @@ pub async fn fetch_beefy_justification<T: Config>(
 	Ok((signed_commitment, latest_beefy_finalized))
 }
 
+/// Get the beefy justification stored in the given block, only blocks that end a session are
+/// guaranteed to have one.
+pub async fn fetch_beefy_justification_at<T: Config>(
+	client: &OnlineClient<T>,
+	block_hash: T::Hash,
+) -> Result<Option<SignedCommitment<u32, beefy_primitives::crypto::Signature>>, Error> {
+	let beefy_justification = client
+		.rpc()
+		.block(Some(block_hash))
+		.await?
+		.and_then(|block| block.justifications)
+		.and_then(|justifications| {
+			justifications.into_iter().find_map(|justfication| {
+				(justfication.0 == beefy_primitives::BEEFY_ENGINE_ID).then(|| justfication.1)
+			})
+		});
+	let beefy_justification = match beefy_justification {
+		Some(beefy_justification) => beefy_justification,
+		None => return Ok(None),
+	};
+	let VersionedFinalityProof::V1(signed_commitment) = VersionedFinalityProof::<
+		u32,
+		beefy_primitives::crypto::Signature,
+	>::decode(&mut &*beefy_justification)?;
+
+	Ok(Some(signed_commitment))
+}
+
 /// Query a mmr  proof
 pub async fn fetch_mmr_proof<T: Config>(
 	client: &OnlineClient<T>,
@@ -24,8 +24,9 @@ extern crate alloc;
 mod tests;
 
 use beefy_light_client_primitives::{
-	error::BeefyClientError, BeefyNextAuthoritySet, ClientState, HostFunctions, MerkleHasher,
-	MmrUpdateProof, NodesUtils, ParachainsUpdateProof, SignatureWithAuthorityIndex, HASH_LENGTH,
+	error::BeefyClientError, BeefyNextAuthoritySet, ClientState, Hash, HostFunctions, MerkleHasher,
+	MmrUpdateProof, NodesUtils, ParachainsUpdateProof, SignatureWithAuthorityIndex,
+	SignedCommitment, SignedCommitmentWithProof, HASH_LENGTH,
 };
 use beefy_primitives::{known_payloads::MMR_ROOT_ID, mmr::MmrLeaf};
 use codec::{Decode, Encode};
@@ -52,6 +53,7 @@ where
 	let next_authority_set = &trusted_client_state.next_authorities;
 	let signatures_len = mmr_update.signed_commitment.signatures.len();
 	let validator_set_id = mmr_update.signed_commitment.commitment.validator_set_id;
+	let commitment_block_number = mmr_update.signed_commitment.commitment.block_number;
 
 	// If signature threshold is not satisfied, return
 	if !validate_sigs_against_threshold(current_authority_set, signatures_len) &&
@@ -86,56 +88,23 @@ where
 
 	let mmr_root_hash = H256::from_slice(&*mmr_root_vec);
 
-	// Beefy validators sign the keccak_256 hash of the scale encoded commitment
-	let encoded_commitment = mmr_update.signed_commitment.commitment.encode();
-	let commitment_hash = H::keccak_256(&*encoded_commitment);
-
-	let mut authority_indices = Vec::new();
-	let authority_leaves = mmr_update
-		.signed_commitment
-		.signatures
-		.into_iter()
-		.map(|SignatureWithAuthorityIndex { index, signature }| {
-			H::secp256k1_ecdsa_recover_compressed(&signature, &commitment_hash)
-				.and_then(|public_key_bytes| {
-					beefy_primitives::crypto::AuthorityId::from_slice(&public_key_bytes).ok()
-				})
-				.map(|pub_key| {
-					authority_indices.push(index as usize);
-					H::keccak_256(&beefy_mmr::BeefyEcdsaToEthereum::convert(pub_key))
-				})
-				.ok_or(BeefyClientError::InvalidSignature)
-		})
-		.collect::<Result<Vec<_>, BeefyClientError>>()?;
-
-	let mut authorities_changed = false;
-
-	let authorities_merkle_proof =
-		rs_merkle::MerkleProof::<MerkleHasher<H>>::new(mmr_update.authority_proof);
-	// Verify mmr_update.authority_proof against store root hash
-	match validator_set_id {
+	// Verify the signatures against the authority set that signed the commitment
+	let authorities_changed = match validator_set_id {
 		id if id == current_authority_set.id => {
-			let root_hash = current_authority_set.root;
-			if !authorities_merkle_proof.verify(
-				root_hash.into(),
-				&authority_indices,
-				&authority_leaves,
-				current_authority_set.len as usize,
-			) {
-				return Err(BeefyClientError::InvalidAuthorityProof)
-			}
+			verify_commitment_signatures::<H>(
+				current_authority_set,
+				&mmr_update.signed_commitment,
+				&mmr_update.authority_proof,
+			)?;
+			false
 		},
 		id if id == next_authority_set.id => {
-			let root_hash = next_authority_set.root;
-			if !authorities_merkle_proof.verify(
-				root_hash.into(),
-				&authority_indices,
-				&authority_leaves,
-				next_authority_set.len as usize,
-			) {
-				return Err(BeefyClientError::InvalidAuthorityProof)
-			}
-			authorities_changed = true;
+			verify_commitment_signatures::<H>(
+				next_authority_set,
+				&mmr_update.signed_commitment,
+				&mmr_update.authority_proof,
+			)?;
+			true
 		},
 		_ =>
 			return Err(BeefyClientError::AuthoritySetMismatch {
@@ -143,11 +112,10 @@ where
 				next_set_id: next_authority_set.id,
 				commitment_set_id: validator_set_id,
 			}),
-	}
+	};
 
 	let latest_beefy_height = trusted_client_state.latest_beefy_height;
 
-	let commitment_block_number = mmr_update.signed_commitment.commitment.block_number;
 	if commitment_block_number <= latest_beefy_height {
 		return Err(BeefyClientError::OutdatedCommitment {
 			latest_beefy_height,
@@ -181,7 +149,7 @@ where
 		})
 	}
 
-	trusted_client_state.latest_beefy_height = mmr_update.signed_commitment.commitment.block_number;
+	trusted_client_state.latest_beefy_height = commitment_block_number;
 	trusted_client_state.mmr_root_hash = mmr_root_hash;
 
 	if authorities_changed {
@@ -191,6 +159,55 @@ where
 	Ok(trusted_client_state)
 }
 
+/// Verifies that two different commitments for the same block were both signed by a super
+/// majority of the same authority set known to the light client, proving that the authority set
+/// equivocated.
+pub fn verify_equivocation<H>(
+	trusted_client_state: &ClientState,
+	first: &SignedCommitmentWithProof,
+	second: &SignedCommitmentWithProof,
+) -> Result<(), BeefyClientError>
+where
+	H: HostFunctions + Clone,
+{
+	let first_commitment = &first.signed_commitment.commitment;
+	let second_commitment = &second.signed_commitment.commitment;
+	if first_commitment.block_number != second_commitment.block_number {
+		return Err(BeefyClientError::InvalidEquivocation("commitments are for different blocks"))
+	}
+	if first_commitment.validator_set_id != second_commitment.validator_set_id {
+		return Err(BeefyClientError::InvalidEquivocation(
+			"commitments are signed by different authority sets",
+		))
+	}
+	if first_commitment == second_commitment {
+		return Err(BeefyClientError::InvalidEquivocation("commitments are identical"))
+	}
+
+	let validator_set_id = first_commitment.validator_set_id;
+	let authority_set = match validator_set_id {
+		id if id == trusted_client_state.current_authorities.id =>
+			&trusted_client_state.current_authorities,
+		id if id == trusted_client_state.next_authorities.id =>
+			&trusted_client_state.next_authorities,
+		_ =>
+			return Err(BeefyClientError::AuthoritySetMismatch {
+				current_set_id: trusted_client_state.current_authorities.id,
+				next_set_id: trusted_client_state.next_authorities.id,
+				commitment_set_id: validator_set_id,
+			}),
+	};
+
+	for SignedCommitmentWithProof { signed_commitment, authority_proof } in [first, second] {
+		if !validate_sigs_against_threshold(authority_set, signed_commitment.signatures.len()) {
+			return Err(BeefyClientError::IncompleteSignatureThreshold)
+		}
+		verify_commitment_signatures::<H>(authority_set, signed_commitment, authority_proof)?;
+	}
+
+	Ok(())
+}
+
 /// Takes the updated client state and parachains headers update proof
 /// and verifies inclusion in mmr
 pub fn verify_parachain_headers<H>(
@@ -271,6 +288,50 @@ where
 	Ok(())
 }
 
+/// Recovers the signatories of the commitment and verifies their membership in the authority set.
+fn verify_commitment_signatures<H>(
+	authority_set: &BeefyNextAuthoritySet<H256>,
+	signed_commitment: &SignedCommitment,
+	authority_proof: &[Hash],
+) -> Result<(), BeefyClientError>
+where
+	H: HostFunctions + Clone,
+{
+	// Beefy validators sign the keccak_256 hash of the scale encoded commitment
+	let encoded_commitment = signed_commitment.commitment.encode();
+	let commitment_hash = H::keccak_256(&*encoded_commitment);
+
+	let mut authority_indices = Vec::new();
+	let authority_leaves = signed_commitment
+		.signatures
+		.iter()
+		.map(|SignatureWithAuthorityIndex { index, signature }| {
+			H::secp256k1_ecdsa_recover_compressed(signature, &commitment_hash)
+				.and_then(|public_key_bytes| {
+					beefy_primitives::crypto::AuthorityId::from_slice(&public_key_bytes).ok()
+				})
+				.map(|pub_key| {
+					authority_indices.push(*index as usize);
+					H::keccak_256(&beefy_mmr::BeefyEcdsaToEthereum::convert(pub_key))
+				})
+				.ok_or(BeefyClientError::InvalidSignature)
+		})
+		.collect::<Result<Vec<_>, BeefyClientError>>()?;
+
+	let authorities_merkle_proof =
+		rs_merkle::MerkleProof::<MerkleHasher<H>>::new(authority_proof.to_vec());
+	if !authorities_merkle_proof.verify(
+		authority_set.root.into(),
+		&authority_indices,
+		&authority_leaves,
+		authority_set.len as usize,
+	) {
+		return Err(BeefyClientError::InvalidAuthorityProof)
+	}
+
+	Ok(())
+}
+
 /// Validate signatures against threshold
 fn validate_sigs_against_threshold(set: &BeefyNextAuthoritySet<H256>, sigs_len: usize) -> bool {
 	let threshold = ((2 * set.len) / 3) + 1;
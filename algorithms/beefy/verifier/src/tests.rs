@@ -15,7 +15,7 @@
 
 use beefy_light_client_primitives::{
 	error::BeefyClientError, EncodedVersionedFinalityProof, MmrUpdateProof, ParachainsUpdateProof,
-	SignatureWithAuthorityIndex, SignedCommitment,
+	SignatureWithAuthorityIndex, SignedCommitment, SignedCommitmentWithProof,
 };
 use beefy_primitives::{
 	known_payloads::MMR_ROOT_ID,
@@ -205,6 +205,32 @@ async fn should_fail_with_invalid_validator_set_id() {
 	}
 }
 
+#[tokio::test]
+async fn should_fail_equivocation_with_identical_commitments() {
+	let signed_commitment = SignedCommitmentWithProof {
+		signed_commitment: SignedCommitment {
+			commitment: beefy_primitives::Commitment {
+				payload: Payload::from_single_entry(MMR_ROOT_ID, vec![0u8; 32]),
+				block_number: 10,
+				validator_set_id: 0,
+			},
+			signatures: vec![SignatureWithAuthorityIndex { index: 0, signature: [0u8; 65] }; 5],
+		},
+		authority_proof: vec![],
+	};
+
+	let res = crate::verify_equivocation::<Crypto>(
+		&Prover::<PolkadotConfig>::get_initial_client_state(None).await,
+		&signed_commitment,
+		&signed_commitment,
+	);
+	match res {
+		Err(BeefyClientError::InvalidEquivocation(_)) => {},
+		Err(err) => panic!("Expected InvalidEquivocation found {:?}", err),
+		Ok(val) => panic!("Found {:?}", val),
+	}
+}
+
 #[tokio::test]
 #[ignore]
 async fn verify_parachain_headers() {
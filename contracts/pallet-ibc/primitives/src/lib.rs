@@ -6,7 +6,7 @@ use alloc::string::{String, ToString};
 use codec::{Decode, Encode};
 use frame_support::{weights::Weight, RuntimeDebug};
 use ibc::{
-	applications::transfer::{error::Error as Ics20Error, PrefixedCoin, VERSION},
+	applications::transfer::{error::Error as Ics20Error, PrefixedCoin, VERSION, VERSION_V2},
 	core::{
 		ics04_channel::{
 			channel::{ChannelEnd, Order},
@@ -217,6 +217,22 @@ pub struct QueryChannelResponse {
 	pub trie_key: Vec<u8>,
 }
 
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+pub struct QueryChannelUpgradeResponse {
+	/// Protobuf encoded `ibc::core::ics04_channel::upgrade::Upgrade`
+	pub upgrade: Vec<u8>,
+	pub height: u64,
+	pub trie_key: Vec<u8>,
+}
+
+#[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
+pub struct QueryUpgradeErrorResponse {
+	/// Protobuf encoded `ibc::core::ics04_channel::upgrade::ErrorReceipt`
+	pub error_receipt: Vec<u8>,
+	pub height: u64,
+	pub trie_key: Vec<u8>,
+}
+
 #[derive(Clone, codec::Encode, codec::Decode, PartialEq, Eq, Ord, PartialOrd)]
 pub struct QueryChannelsResponse {
 	pub channels: Vec<IdentifiedChannel>,
@@ -509,6 +525,23 @@ pub fn get_channel_escrow_address(
 	hex_string.parse::<Signer>().map_err(Ics20Error::signer)
 }
 
+/// Account that holds the tokens received over a channel while they are forwarded to their next
+/// hop, it's derived like the escrow address of the channel.
+pub fn get_channel_forward_address(
+	port_id: &PortId,
+	channel_id: ChannelId,
+) -> Result<Signer, Ics20Error> {
+	let contents = format!("forward/{}/{}", port_id, channel_id);
+	let mut data = VERSION_V2.as_bytes().to_vec();
+	data.extend_from_slice(&[0]);
+	data.extend_from_slice(contents.as_bytes());
+
+	let hash = sp_io::hashing::sha2_256(&data).to_vec();
+	let mut hex_string = hex::encode_upper(hash);
+	hex_string.insert_str(0, "0x");
+	hex_string.parse::<Signer>().map_err(Ics20Error::signer)
+}
+
 // This is needed because Ics20 traits require an implementation of TryFrom<Signer> for AccountId
 // associated type
 #[derive(Clone)]
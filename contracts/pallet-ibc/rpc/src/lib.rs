@@ -25,6 +25,7 @@ use ibc_proto::{
 				QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementResponse,
 				QueryPacketAcknowledgementsResponse, QueryPacketCommitmentResponse,
 				QueryPacketCommitmentsResponse, QueryPacketReceiptResponse,
+				QueryUpgradeErrorResponse, QueryUpgradeResponse,
 			},
 			client::v1::{
 				Height, IdentifiedClientState, QueryClientStateResponse,
@@ -252,6 +253,24 @@ where
 		port_id: String,
 	) -> Result<QueryChannelResponse>;
 
+	/// Query the upgrade proposed for a channel
+	#[method(name = "ibc_queryChannelUpgrade")]
+	fn query_channel_upgrade(
+		&self,
+		height: u32,
+		channel_id: String,
+		port_id: String,
+	) -> Result<QueryUpgradeResponse>;
+
+	/// Query the error receipt of the last aborted upgrade of a channel
+	#[method(name = "ibc_queryChannelUpgradeError")]
+	fn query_channel_upgrade_error(
+		&self,
+		height: u32,
+		channel_id: String,
+		port_id: String,
+	) -> Result<QueryUpgradeErrorResponse>;
+
 	/// Query client state for channel and port id
 	#[method(name = "ibc_queryChannelClient")]
 	fn query_channel_client(
@@ -1000,6 +1019,101 @@ where
 		})
 	}
 
+	fn query_channel_upgrade(
+		&self,
+		height: u32,
+		channel_id: String,
+		port_id: String,
+	) -> Result<QueryUpgradeResponse> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::Number(height.into());
+		let hash_at = self
+			.client
+			.block_hash_from_id(&at)
+			.map_err(|_| RpcError::Custom("Unknown block".into()))?
+			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
+		let para_id = api
+			.para_id(&at)
+			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
+		let result: ibc_primitives::QueryChannelUpgradeResponse = api
+			.channel_upgrade(&at, channel_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
+			.ok()
+			.flatten()
+			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch channel upgrade"))?;
+		let upgrade = ibc::core::ics04_channel::upgrade::Upgrade::decode_vec(&result.upgrade)
+			.map_err(|_| runtime_error_into_rpc_error("Failed to decode channel upgrade"))?;
+		let mut keys = vec![result.trie_key];
+		let child_trie_key = api
+			.child_trie_key(&at)
+			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
+		let child_info = ChildInfo::new_default(&child_trie_key);
+		let proof = self
+			.client
+			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
+			.map_err(runtime_error_into_rpc_error)?
+			.iter_nodes()
+			.collect::<Vec<_>>()
+			.encode();
+		Ok(QueryUpgradeResponse {
+			upgrade: Some(upgrade.into()),
+			proof,
+			proof_height: Some(ibc_proto::ibc::core::client::v1::Height {
+				revision_number: para_id.into(),
+				revision_height: result.height,
+			}),
+		})
+	}
+
+	fn query_channel_upgrade_error(
+		&self,
+		height: u32,
+		channel_id: String,
+		port_id: String,
+	) -> Result<QueryUpgradeErrorResponse> {
+		let api = self.client.runtime_api();
+
+		let at = BlockId::Number(height.into());
+		let hash_at = self
+			.client
+			.block_hash_from_id(&at)
+			.map_err(|_| RpcError::Custom("Unknown block".into()))?
+			.ok_or_else(|| RpcError::Custom("Unknown block".into()))?;
+		let para_id = api
+			.para_id(&at)
+			.map_err(|_| runtime_error_into_rpc_error("Error getting para id"))?;
+		let result: ibc_primitives::QueryUpgradeErrorResponse = api
+			.channel_upgrade_error(&at, channel_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
+			.ok()
+			.flatten()
+			.ok_or_else(|| runtime_error_into_rpc_error("Failed to fetch upgrade error receipt"))?;
+		let error_receipt =
+			ibc::core::ics04_channel::upgrade::ErrorReceipt::decode_vec(&result.error_receipt)
+				.map_err(|_| {
+					runtime_error_into_rpc_error("Failed to decode upgrade error receipt")
+				})?;
+		let mut keys = vec![result.trie_key];
+		let child_trie_key = api
+			.child_trie_key(&at)
+			.map_err(|_| runtime_error_into_rpc_error("Failed to get child trie key"))?;
+		let child_info = ChildInfo::new_default(&child_trie_key);
+		let proof = self
+			.client
+			.read_child_proof(hash_at, &child_info, &mut keys.iter_mut().map(|nodes| &nodes[..]))
+			.map_err(runtime_error_into_rpc_error)?
+			.iter_nodes()
+			.collect::<Vec<_>>()
+			.encode();
+		Ok(QueryUpgradeErrorResponse {
+			error_receipt: Some(error_receipt.into()),
+			proof,
+			proof_height: Some(ibc_proto::ibc::core::client::v1::Height {
+				revision_number: para_id.into(),
+				revision_height: result.height,
+			}),
+		})
+	}
+
 	fn query_channel_client(
 		&self,
 		height: u32,
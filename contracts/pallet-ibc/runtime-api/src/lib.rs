@@ -45,6 +45,12 @@ sp_api::decl_runtime_apis! {
 
 		fn channel(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryChannelResponse>;
 
+		/// Returns the upgrade proposed for the channel while an upgrade handshake is in progress
+		fn channel_upgrade(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryChannelUpgradeResponse>;
+
+		/// Returns the error receipt of the last aborted upgrade of the channel
+		fn channel_upgrade_error(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<QueryUpgradeErrorResponse>;
+
 		/// Should return the client state for the client supporting this channel
 		fn channel_client(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<IdentifiedClientState>;
 
@@ -7,7 +7,10 @@ use crate::{
 		grandpa_benchmark_utils::{generate_finality_proof, GRANDPA_UPDATE_TIMESTAMP},
 		tendermint_benchmark_utils::*,
 	},
-	ics20::IbcModule,
+	ics20::{
+		rate_limit::{Quota, RateLimit},
+		IbcModule,
+	},
 	ics23::client_states::ClientStates,
 	light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState},
 	Any, Config,
@@ -19,7 +22,7 @@ use frame_benchmarking::{benchmarks, whitelisted_caller};
 use frame_support::traits::fungibles::{Inspect, Mutate};
 use frame_system::RawOrigin;
 use ibc_primitives::IbcHandler;
-use sp_runtime::traits::IdentifyAccount;
+use sp_runtime::{traits::IdentifyAccount, Percent};
 
 use crate::routing::Context;
 use ibc::{
@@ -903,6 +906,59 @@ benchmarks! {
 		}.into())
 	}
 
+	set_rate_limit {
+		let channel_id = ChannelId::new(0).to_string().as_bytes().to_vec();
+		let denom = "transfer/channel-15/uatom".to_string();
+		let asset_id = <T as Config>::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom).unwrap();
+		let rate_limit = RateLimit {
+			max_inflow: Some(Quota::Absolute((1000 * MILLIS).into())),
+			max_outflow: Some(Quota::Percent(Percent::from_percent(10))),
+			period: 24 * 60 * 60,
+		};
+	}:_(RawOrigin::Root, channel_id.clone(), asset_id, Some(rate_limit))
+	verify {
+		assert_last_event::<T>(Event::<T>::RateLimitUpdated {
+			channel_id,
+			asset_id,
+			rate_limit: Some(rate_limit)
+		}.into())
+	}
+
+	recover_client {
+		let mut ctx = routing::Context::<T>::new();
+		let now: <T as pallet_timestamp::Config>::Moment = TENDERMINT_TIMESTAMP.saturating_mul(1000).saturating_add(1_000_000);
+		pallet_timestamp::Pallet::<T>::set_timestamp(now);
+		let time = core::time::Duration::from_millis(TENDERMINT_TIMESTAMP.saturating_mul(1000));
+		let time = Timestamp::from_nanoseconds(time.as_nanos() as u64).unwrap();
+		// The subject client is frozen at height 1, the substitute client of the same chain is active at height 2
+		let (mock_client_state, mock_cs_state) = create_mock_state();
+		let mut subject_client_state = mock_client_state.clone();
+		subject_client_state.frozen_height = Some(Height::new(0, 1));
+		let mut substitute_client_state = mock_client_state;
+		substitute_client_state.latest_height = Height::new(0, 2);
+		let subject_client_id = ClientId::new("07-tendermint", 0).unwrap();
+		let substitute_client_id = ClientId::new("07-tendermint", 1).unwrap();
+		for (client_id, client_state, height) in [
+			(subject_client_id.clone(), subject_client_state, Height::new(0, 1)),
+			(substitute_client_id.clone(), substitute_client_state, Height::new(0, 2)),
+		] {
+			let client_state = AnyClientState::Tendermint(client_state);
+			ctx.store_client_type(client_id.clone(), client_state.client_type()).unwrap();
+			ctx.store_client_state(client_id.clone(), client_state).unwrap();
+			ctx.store_consensus_state(client_id.clone(), height, AnyConsensusState::Tendermint(mock_cs_state.clone())).unwrap();
+			ctx.store_update_time(client_id.clone(), height, time).unwrap();
+			ctx.store_update_height(client_id, height, Height::new(0, 1)).unwrap();
+		}
+	}:_(RawOrigin::Root, subject_client_id.as_bytes().to_vec(), substitute_client_id.as_bytes().to_vec())
+	verify {
+		assert_last_event::<T>(Event::<T>::ClientRecovered {
+			subject_client_id: subject_client_id.as_bytes().to_vec(),
+			substitute_client_id: substitute_client_id.as_bytes().to_vec(),
+			height: 2,
+			revision_number: 0,
+		}.into())
+	}
+
 	on_chan_open_init {
 		let mut output = HandlerOutputBuilder::new();
 		let port_id = PortId::transfer();
@@ -7,9 +7,14 @@ use sp_core::crypto::AccountId32;
 
 use crate::{
 	ics23::{
-		acknowledgements::Acknowledgements, channels::Channels, next_seq_ack::NextSequenceAck,
-		next_seq_recv::NextSequenceRecv, next_seq_send::NextSequenceSend,
-		packet_commitments::PacketCommitment, receipts::PacketReceipt,
+		acknowledgements::Acknowledgements,
+		channel_upgrades::{ChannelUpgradeErrors, ChannelUpgrades, CounterpartyUpgrades},
+		channels::Channels,
+		next_seq_ack::NextSequenceAck,
+		next_seq_recv::NextSequenceRecv,
+		next_seq_send::NextSequenceSend,
+		packet_commitments::PacketCommitment,
+		receipts::PacketReceipt,
 	},
 	impls::host_height,
 	routing::Context,
@@ -24,6 +29,7 @@ use ibc::{
 			error::Error as ICS04Error,
 			msgs::acknowledgement::Acknowledgement,
 			packet::{Receipt, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 	},
@@ -196,6 +202,61 @@ where
 		}
 	}
 
+	fn channel_upgrade(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, ICS04Error> {
+		let data = <ChannelUpgrades<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+			.ok_or_else(|| {
+				ICS04Error::upgrade_not_found(port_channel_id.0.clone(), port_channel_id.1)
+			})?;
+		let upgrade = Upgrade::decode_vec(&data).map_err(|e| {
+			ICS04Error::implementation_specific(format!(
+				"[channel_upgrade]: error decoding upgrade: {}",
+				e
+			))
+		})?;
+		log::trace!(target: "pallet_ibc", "in channel : [channel_upgrade] >> upgrade = {:?}", upgrade);
+		Ok(upgrade)
+	}
+
+	fn counterparty_upgrade(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, ICS04Error> {
+		let data = <CounterpartyUpgrades<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+			.ok_or_else(|| {
+				ICS04Error::upgrade_not_found(port_channel_id.0.clone(), port_channel_id.1)
+			})?;
+		Upgrade::decode_vec(&data).map_err(|e| {
+			ICS04Error::implementation_specific(format!(
+				"[counterparty_upgrade]: error decoding upgrade: {}",
+				e
+			))
+		})
+	}
+
+	fn upgrade_error_receipt(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, ICS04Error> {
+		let data = <ChannelUpgradeErrors<T>>::get(port_channel_id.0.clone(), port_channel_id.1)
+			.ok_or_else(ICS04Error::missing_error_receipt)?;
+		ErrorReceipt::decode_vec(&data).map_err(|e| {
+			ICS04Error::implementation_specific(format!(
+				"[upgrade_error_receipt]: error decoding error receipt: {}",
+				e
+			))
+		})
+	}
+
+	fn inflight_packet_count(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<u64, ICS04Error> {
+		Ok(<PacketCommitment<T>>::count(port_channel_id.0.clone(), port_channel_id.1))
+	}
+
 	/// A hashing function for packet commitments
 	fn hash(&self, value: Vec<u8>) -> Vec<u8> {
 		sp_io::hashing::sha2_256(&value).to_vec()
@@ -438,6 +499,49 @@ where
 		Ok(())
 	}
 
+	fn store_channel_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: &Upgrade,
+	) -> Result<(), ICS04Error> {
+		<ChannelUpgrades<T>>::insert(port_channel_id.0, port_channel_id.1, upgrade);
+		Ok(())
+	}
+
+	fn delete_channel_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+	) -> Result<(), ICS04Error> {
+		<ChannelUpgrades<T>>::remove(port_channel_id.0, port_channel_id.1);
+		Ok(())
+	}
+
+	fn store_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: &Upgrade,
+	) -> Result<(), ICS04Error> {
+		<CounterpartyUpgrades<T>>::insert(port_channel_id.0, port_channel_id.1, upgrade);
+		Ok(())
+	}
+
+	fn delete_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+	) -> Result<(), ICS04Error> {
+		<CounterpartyUpgrades<T>>::remove(port_channel_id.0, port_channel_id.1);
+		Ok(())
+	}
+
+	fn store_upgrade_error_receipt(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), ICS04Error> {
+		<ChannelUpgradeErrors<T>>::insert(port_channel_id.0, port_channel_id.1, error_receipt);
+		Ok(())
+	}
+
 	fn store_next_sequence_send(
 		&mut self,
 		port_channel_id: (PortId, ChannelId),
@@ -290,6 +290,14 @@ where
 		Ok(consensus_state)
 	}
 
+	fn decode_client_state(&self, value: &[u8]) -> Result<AnyClientState, ICS02Error> {
+		AnyClientState::decode_vec(value).map_err(ICS02Error::decode_raw_client_state)
+	}
+
+	fn decode_consensus_state(&self, value: &[u8]) -> Result<AnyConsensusState, ICS02Error> {
+		AnyConsensusState::decode_vec(value).map_err(ICS02Error::decode_raw_consensus_state)
+	}
+
 	fn client_counter(&self) -> Result<u64, ICS02Error> {
 		let count = ClientCounter::<T>::get();
 		log::trace!(target: "pallet_ibc", "in client : [client_counter] >> client_counter: {:?}", count);
@@ -214,6 +214,96 @@ pub enum IbcEvent {
 	ChainError,
 	/// App module
 	AppModule { kind: Vec<u8>, module_id: Vec<u8> },
+	/// Channel upgrade init
+	UpgradeInitChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
+	/// Channel upgrade try
+	UpgradeTryChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
+	/// Channel upgrade ack
+	UpgradeAckChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
+	/// Channel upgrade confirm
+	UpgradeConfirmChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
+	/// Channel upgrade open
+	UpgradeOpenChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
+	/// Channel upgrade timeout
+	UpgradeTimeoutChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
+	/// Channel upgrade cancelled
+	UpgradeCancelChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
+	/// Channel upgrade error
+	UpgradeErrorChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
+	/// Channel flush complete
+	FlushCompleteChannel {
+		revision_height: u64,
+		revision_number: u64,
+		port_id: Vec<u8>,
+		channel_id: Vec<u8>,
+		counterparty_port_id: Vec<u8>,
+		counterparty_channel_id: Option<Vec<u8>>,
+		upgrade_sequence: u64,
+	},
 }
 
 impl From<RawIbcEvent> for IbcEvent {
@@ -433,6 +523,105 @@ impl From<RawIbcEvent> for IbcEvent {
 				kind: ev.kind.as_bytes().to_vec(),
 				module_id: ev.module_name.to_string().as_bytes().to_vec(),
 			},
+			RawIbcEvent::UpgradeInitChannel(ev) => IbcEvent::UpgradeInitChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
+			RawIbcEvent::UpgradeTryChannel(ev) => IbcEvent::UpgradeTryChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
+			RawIbcEvent::UpgradeAckChannel(ev) => IbcEvent::UpgradeAckChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
+			RawIbcEvent::UpgradeConfirmChannel(ev) => IbcEvent::UpgradeConfirmChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
+			RawIbcEvent::UpgradeOpenChannel(ev) => IbcEvent::UpgradeOpenChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
+			RawIbcEvent::UpgradeTimeoutChannel(ev) => IbcEvent::UpgradeTimeoutChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
+			RawIbcEvent::UpgradeCancelChannel(ev) => IbcEvent::UpgradeCancelChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
+			RawIbcEvent::UpgradeErrorChannel(ev) => IbcEvent::UpgradeErrorChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
+			RawIbcEvent::FlushCompleteChannel(ev) => IbcEvent::FlushCompleteChannel {
+				revision_height: ev.height().revision_height,
+				revision_number: ev.height().revision_number,
+				port_id: ev.port_id().as_bytes().to_vec(),
+				channel_id: ev.channel_id().to_string().as_bytes().to_vec(),
+				counterparty_port_id: ev.counterparty_port_id.as_bytes().to_vec(),
+				counterparty_channel_id: ev
+					.counterparty_channel_id
+					.map(|val| val.to_string().as_bytes().to_vec()),
+				upgrade_sequence: ev.upgrade_sequence,
+			},
 		}
 	}
 }
@@ -1039,6 +1228,195 @@ impl TryFrom<IbcEvent> for RawIbcEvent {
 				.map_err(|_| ERROR_STR)?,
 				attributes: Default::default(),
 			})),
+			IbcEvent::UpgradeInitChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::UpgradeInitChannel(ChannelEvents::UpgradeInit::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
+			IbcEvent::UpgradeTryChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::UpgradeTryChannel(ChannelEvents::UpgradeTry::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
+			IbcEvent::UpgradeAckChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::UpgradeAckChannel(ChannelEvents::UpgradeAck::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
+			IbcEvent::UpgradeConfirmChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::UpgradeConfirmChannel(ChannelEvents::UpgradeConfirm::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
+			IbcEvent::UpgradeOpenChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::UpgradeOpenChannel(ChannelEvents::UpgradeOpen::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
+			IbcEvent::UpgradeTimeoutChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::UpgradeTimeoutChannel(ChannelEvents::UpgradeTimeout::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
+			IbcEvent::UpgradeCancelChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::UpgradeCancelChannel(ChannelEvents::UpgradeCancel::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
+			IbcEvent::UpgradeErrorChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::UpgradeErrorChannel(ChannelEvents::UpgradeError::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
+			IbcEvent::FlushCompleteChannel {
+				revision_height,
+				revision_number,
+				port_id,
+				channel_id,
+				counterparty_port_id,
+				counterparty_channel_id,
+				upgrade_sequence,
+			} => Ok(RawIbcEvent::FlushCompleteChannel(ChannelEvents::FlushComplete::from(
+				upgrade_attributes(
+					Height::new(revision_number, revision_height),
+					port_id,
+					channel_id,
+					counterparty_port_id,
+					counterparty_channel_id,
+					upgrade_sequence,
+				)?,
+			))),
 		}
 	}
 }
+
+fn upgrade_attributes(
+	height: Height,
+	port_id: Vec<u8>,
+	channel_id: Vec<u8>,
+	counterparty_port_id: Vec<u8>,
+	counterparty_channel_id: Option<Vec<u8>>,
+	upgrade_sequence: u64,
+) -> Result<ChannelEvents::UpgradeAttributes, &'static str> {
+	Ok(ChannelEvents::UpgradeAttributes {
+		height,
+		port_id: PortId::from_str(&String::from_utf8(port_id).map_err(|_| ERROR_STR)?)
+			.map_err(|_| ERROR_STR)?,
+		channel_id: ChannelId::from_str(&String::from_utf8(channel_id).map_err(|_| ERROR_STR)?)
+			.map_err(|_| ERROR_STR)?,
+		counterparty_port_id: PortId::from_str(
+			&String::from_utf8(counterparty_port_id).map_err(|_| ERROR_STR)?,
+		)
+		.map_err(|_| ERROR_STR)?,
+		counterparty_channel_id: counterparty_channel_id.and_then(|channel_id| {
+			String::from_utf8(channel_id)
+				.ok()
+				.and_then(|channel_id| ChannelId::from_str(&channel_id).ok())
+		}),
+		upgrade_sequence,
+	})
+}
@@ -0,0 +1,770 @@
+use crate::{
+	ics20::{
+		atomically, decode_transfer_data, full_ibc_denom, rate_limit::FlowDirection,
+		receive_transfer,
+	},
+	routing::Context,
+	Config, DenomToAssetId, Event, InFlightForwards, Pallet, Timeout,
+};
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use codec::{Decode, Encode};
+use core::{fmt::Debug, str::FromStr};
+use ibc::{
+	applications::transfer::{
+		acknowledgement::{Acknowledgement as Ics20Acknowledgement, ACK_ERR_STR},
+		context::{BankKeeper, Ics20Reader},
+		is_receiver_chain_source,
+		packet::{Forwarding, PacketDataV2},
+		PrefixedCoin, PrefixedDenom, TracePrefix,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			context::ChannelReader,
+			error::Error,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics05_port::context::PortReader,
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{
+			Ics26Context, Module, ModuleCallbackContext, ModuleOutputBuilder, Router,
+		},
+	},
+	signer::Signer,
+};
+use ibc_primitives::{get_channel_forward_address, HandlerMessage, IbcHandler, PacketInfo};
+use scale_info::TypeInfo;
+use sp_core::crypto::AccountId32;
+use sp_runtime::RuntimeDebug;
+
+/// Timeout of forwarded transfers in seconds, if the memo doesn't set one
+pub const DEFAULT_FORWARD_TIMEOUT: u64 = 10 * 60;
+
+/// Instructions to forward a received transfer to another chain, given in the memo of the transfer
+/// as `{"forward": {"receiver": .., "port": .., "channel": .., "timeout": .., "retries": ..,
+/// "next": ..}}`, or by the forwarding hops of an `ics20-2` packet.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq)]
+pub struct Forward {
+	/// Receiver of the tokens on the next chain
+	pub receiver: String,
+	/// Port the tokens are forwarded from, only the transfer port is supported
+	pub port: PortId,
+	/// Channel the tokens are forwarded over
+	pub channel: ChannelId,
+	/// Timeout of the forwarded transfer in seconds, relative to the latest timestamp of the next
+	/// chain
+	pub timeout: u64,
+	/// Number of times the forwarded transfer is sent again if it fails or times out
+	pub retries: u8,
+	/// Memo of the forwarded transfer, it can itself hold forward instructions for the next chain
+	pub next: String,
+	/// Remaining hops of the forwarded transfer
+	pub forwarding: Forwarding,
+}
+
+impl Forward {
+	/// Parses the forward instructions of a memo, returns `None` if the memo doesn't ask for the
+	/// transfer to be forwarded.
+	pub fn from_memo(memo: &str) -> Result<Option<Self>, Error> {
+		let memo = match serde_json::from_str::<serde_json::Value>(memo) {
+			Ok(memo) => memo,
+			Err(_) => return Ok(None),
+		};
+		let forward = match memo.get("forward") {
+			Some(forward) => forward,
+			None => return Ok(None),
+		};
+		let invalid = |field: &str| {
+			Error::implementation_specific(format!("Invalid {} in forward memo", field))
+		};
+		let field = |name: &str| forward.get(name).and_then(|value| value.as_str());
+
+		let receiver = field("receiver").ok_or_else(|| invalid("receiver"))?.to_string();
+		let port = field("port")
+			.and_then(|port| PortId::from_str(port).ok())
+			.filter(|port| *port == PortId::transfer())
+			.ok_or_else(|| invalid("port"))?;
+		let channel = field("channel")
+			.and_then(|channel| ChannelId::from_str(channel).ok())
+			.ok_or_else(|| invalid("channel"))?;
+		let timeout = match forward.get("timeout") {
+			Some(timeout) => timeout.as_u64().ok_or_else(|| invalid("timeout"))?,
+			None => DEFAULT_FORWARD_TIMEOUT,
+		};
+		let retries = match forward.get("retries") {
+			Some(retries) => retries
+				.as_u64()
+				.and_then(|retries| u8::try_from(retries).ok())
+				.ok_or_else(|| invalid("retries"))?,
+			None => 0,
+		};
+		let next = match forward.get("next") {
+			None | Some(serde_json::Value::Null) => String::new(),
+			Some(serde_json::Value::String(next)) => next.clone(),
+			Some(next) => next.to_string(),
+		};
+		Ok(Some(Self {
+			receiver,
+			port,
+			channel,
+			timeout,
+			retries,
+			next,
+			forwarding: Forwarding::default(),
+		}))
+	}
+
+	/// Takes the next hop of a forwarded `ics20-2` packet, the destination memo is only passed
+	/// along by the last hop.
+	pub fn from_hops(packet_data: &PacketDataV2) -> Result<Self, Error> {
+		let (hop, hops) =
+			packet_data.forwarding.hops.split_first().ok_or_else(|| {
+				Error::implementation_specific("Packet is not forwarded".to_string())
+			})?;
+		if hop.port_id != PortId::transfer() {
+			return Err(Error::implementation_specific("Invalid port in forwarding hop".to_string()))
+		}
+		let destination_memo = packet_data.forwarding.destination_memo.clone();
+		let (next, forwarding) = if hops.is_empty() {
+			(destination_memo, Forwarding::default())
+		} else {
+			(String::new(), Forwarding { hops: hops.to_vec(), destination_memo })
+		};
+		Ok(Self {
+			receiver: packet_data.receiver.to_string(),
+			port: hop.port_id.clone(),
+			channel: hop.channel_id,
+			timeout: DEFAULT_FORWARD_TIMEOUT,
+			retries: 0,
+			next,
+			forwarding,
+		})
+	}
+}
+
+/// A received transfer that was forwarded to the next chain, its acknowledgement is written once
+/// the forwarded transfer is acknowledged or has failed.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Clone)]
+pub struct InFlightForward {
+	/// The received packet
+	pub packet: PacketInfo,
+	/// Timeout of the forwarded transfer in seconds
+	pub timeout: u64,
+	/// Number of times the forwarded transfer can still be sent again
+	pub retries: u8,
+	/// Relayer of the received packet, which is paid the fees of its acknowledgement
+	pub relayer: Vec<u8>,
+}
+
+impl InFlightForward {
+	fn relayer(&self) -> Result<Signer, Error> {
+		String::from_utf8(self.relayer.clone())
+			.ok()
+			.and_then(|relayer| Signer::from_str(&relayer).ok())
+			.ok_or_else(|| {
+				Error::implementation_specific("Invalid relayer of forwarded packet".to_string())
+			})
+	}
+}
+
+/// This middleware forwards received transfers whose memo holds [`Forward`] instructions, or
+/// `ics20-2` transfers with forwarding hops, which lets users route tokens through this chain to
+/// another one in a single transfer. Tokens forwarded through hops are held by the forward address
+/// of the channel they were received on until the forwarded transfer succeeds.
+/// The acknowledgement of the received packet is held until the forwarded transfer is
+/// acknowledged, if it fails or times out (after the retries) the tokens are taken back and an
+/// error acknowledgement is written so that the original sender is refunded.
+/// Forwarded transfers are received without going through the inner module, so this middleware
+/// should directly wrap the ics20 module.
+/// USAGE:
+/// pub struct Router {
+/// 	ics20: crate::ics20::memo::Memo<
+/// 		Runtime,
+/// 		crate::ics20_fee::Ics20ServiceCharge<
+/// 			Runtime,
+/// 			crate::ics20::forward::PacketForward<Runtime, crate::ics20::IbcModule<Runtime>>,
+/// 		>,
+/// 	>,
+/// }
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PacketForward<T: Config, S: Module + Clone + Default + PartialEq + Eq + Debug> {
+	inner: S,
+	_phantom: core::marker::PhantomData<T>,
+}
+
+impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Default
+	for PacketForward<T, S>
+{
+	fn default() -> Self {
+		Self { inner: S::default(), _phantom: Default::default() }
+	}
+}
+
+impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Module
+	for PacketForward<T, S>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_chan_open_init(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			relayer,
+		)
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_open_try(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			version,
+			counterparty_version,
+			relayer,
+		)
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner
+			.on_chan_open_ack(ctx, output, port_id, channel_id, counterparty_version, relayer)
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_chan_open_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_chan_close_init(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			counterparty_version,
+		)
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Error> {
+		self.inner
+			.on_chan_upgrade_ack(ctx, output, port_id, channel_id, counterparty_version)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Error> {
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
+	fn on_recv_packet(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Error> {
+		let packet_data = match decode_transfer_data::<T>(
+			&packet.destination_port,
+			&packet.destination_channel,
+			&packet.data,
+		) {
+			Ok(packet_data) => packet_data,
+			Err(_) => return self.inner.on_recv_packet(ctx, output, packet, relayer),
+		};
+		let forward = if packet_data.is_forwarded() {
+			Forward::from_hops(&packet_data)
+		} else {
+			match Forward::from_memo(&packet_data.memo) {
+				Ok(Some(forward)) => Ok(forward),
+				Ok(None) => return self.inner.on_recv_packet(ctx, output, packet, relayer),
+				Err(e) => Err(e),
+			}
+		};
+		let forward = match forward {
+			Ok(forward) => forward,
+			Err(e) => return Ok(error_acknowledgement(e)),
+		};
+
+		// The tokens are only kept if they could be forwarded
+		let mut exceeded = None;
+		let result = atomically(|| {
+			let sender = Pallet::<T>::forwarding_receiver(packet, &packet_data)?;
+			let received = PacketDataV2 {
+				receiver: sender.clone(),
+				forwarding: Forwarding::default(),
+				..packet_data.clone()
+			};
+			let inflows = receive_transfer::<T>(output, packet, received, &mut exceeded)?;
+			let tokens = packet_data
+				.tokens
+				.iter()
+				.map(|token| {
+					let denom = full_ibc_denom(packet, token.clone());
+					Ok(PrefixedCoin {
+						denom: PrefixedDenom::from_str(&denom).map_err(|_| {
+							Error::implementation_specific(
+								"Failed to parse token denom".to_string(),
+							)
+						})?,
+						amount: token.amount,
+					})
+				})
+				.collect::<Result<Vec<_>, Error>>()?;
+			let receiver = Signer::from_str(&forward.receiver).map_err(|_| {
+				Error::implementation_specific("Invalid receiver in forward memo".to_string())
+			})?;
+			let forwarded_data = PacketDataV2 {
+				tokens,
+				sender,
+				receiver,
+				memo: forward.next,
+				forwarding: forward.forwarding,
+			};
+			Pallet::<T>::forward_transfer(
+				packet,
+				forward.channel,
+				forwarded_data,
+				forward.timeout,
+				forward.retries,
+				relayer,
+				&mut exceeded,
+			)?;
+			// The received tokens are taken back if the forwarded transfer fails
+			Pallet::<T>::track_packet_flows(
+				&packet.destination_channel,
+				packet.sequence.into(),
+				FlowDirection::Inflow,
+				inflows,
+			);
+			Ok(())
+		});
+		match result {
+			Ok(()) => Ok(Acknowledgement::from_bytes(Vec::new())),
+			Err(e) => {
+				if let Some(event) = exceeded {
+					Pallet::<T>::deposit_event(event);
+				}
+				Ok(error_acknowledgement(e))
+			},
+		}
+	}
+
+	fn on_write_acknowledgement(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		packet: &Packet,
+		acknowledgement: Acknowledgement,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Error> {
+		self.inner.on_write_acknowledgement(ctx, packet, acknowledgement, relayer)
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner
+			.on_acknowledgement_packet(ctx, output, packet, acknowledgement, relayer)?;
+		let forward = match InFlightForwards::<T>::take(
+			(
+				packet.source_port.as_bytes().to_vec(),
+				packet.source_channel.to_string().as_bytes().to_vec(),
+			),
+			u64::from(packet.sequence),
+		) {
+			Some(forward) => forward,
+			None => return Ok(()),
+		};
+		let ack = serde_json::from_slice::<Ics20Acknowledgement>(acknowledgement.as_ref())
+			.map_err(|e| {
+				Error::implementation_specific(format!(
+					"Failed to decode acknowledgement data {:?}",
+					e
+				))
+			})?;
+		match ack.into_result() {
+			Ok(_) => Pallet::<T>::write_forward_acknowledgement(
+				&forward.packet.clone().into(),
+				Ics20Acknowledgement::success().to_string().into_bytes(),
+				&forward.relayer()?,
+			),
+			Err(e) => Pallet::<T>::retry_or_refund_forward(packet, forward, e),
+		}
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		self.inner.on_timeout_packet(ctx, output, packet, relayer)?;
+		match InFlightForwards::<T>::take(
+			(
+				packet.source_port.as_bytes().to_vec(),
+				packet.source_channel.to_string().as_bytes().to_vec(),
+			),
+			u64::from(packet.sequence),
+		) {
+			Some(forward) => Pallet::<T>::retry_or_refund_forward(
+				packet,
+				forward,
+				"forwarded transfer timed out".to_string(),
+			),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<T: Config + Send + Sync> Pallet<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	/// Sends the forwarded transfer of a received packet over the channel, and tracks it until it's
+	/// acknowledged or times out. If the transfer exceeds a rate limit, `exceeded` is set to the
+	/// event to deposit once the transfer is rolled back.
+	fn forward_transfer(
+		packet: &Packet,
+		channel_id: ChannelId,
+		packet_data: PacketDataV2,
+		timeout: u64,
+		retries: u8,
+		relayer: &Signer,
+		exceeded: &mut Option<Event<T>>,
+	) -> Result<(), Error> {
+		let port_id = PortId::transfer();
+		let ctx = Context::<T>::default();
+		let sequence = ctx.get_next_sequence_send(&(port_id.clone(), channel_id))?;
+		let (timeout_height, timeout_timestamp) = Pallet::<T>::timeout_height_and_timestamp(
+			&port_id,
+			&channel_id,
+			Timeout::Offset { timestamp: Some(timeout), height: None },
+		)
+		.map_err(|e| Error::implementation_specific(format!("{:?}", e)))?;
+		let mut outflows = Vec::new();
+		for token in &packet_data.tokens {
+			if let Ok(asset_id) =
+				T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&token.denom.to_string())
+			{
+				let amount: T::Balance = u128::try_from(token.amount.as_u256())
+					.map_err(|_| {
+						Error::implementation_specific("Forwarded amount overflows".to_string())
+					})?
+					.into();
+				outflows.extend(
+					Pallet::<T>::record_transfer_flow(
+						&channel_id,
+						asset_id,
+						amount,
+						FlowDirection::Outflow,
+					)
+					.map_err(|_| {
+						*exceeded = Some(Pallet::<T>::rate_limit_exceeded(
+							&channel_id,
+							asset_id,
+							amount,
+							FlowDirection::Outflow,
+						));
+						Error::implementation_specific(
+							"Transfer exceeds the rate limit of the channel".to_string(),
+						)
+					})?,
+				);
+			}
+		}
+		Pallet::<T>::send_tokens(channel_id, packet_data, timeout_height, timeout_timestamp)
+			.map_err(|e| Error::implementation_specific(format!("{:?}", e)))?;
+		Pallet::<T>::track_packet_flows(
+			&channel_id,
+			sequence.into(),
+			FlowDirection::Outflow,
+			outflows,
+		);
+		InFlightForwards::<T>::insert(
+			(port_id.as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec()),
+			u64::from(sequence),
+			InFlightForward {
+				packet: packet.clone().into(),
+				timeout,
+				retries,
+				relayer: relayer.to_string().into_bytes(),
+			},
+		);
+		Ok(())
+	}
+
+	/// Sends a failed forwarded transfer again if it has retries left, otherwise the tokens of the
+	/// received packet are taken back and an error acknowledgement is written for it.
+	fn retry_or_refund_forward(
+		packet: &Packet,
+		forward: InFlightForward,
+		reason: String,
+	) -> Result<(), Error> {
+		let relayer = forward.relayer()?;
+		let received: Packet = forward.packet.into();
+		if forward.retries > 0 {
+			let packet_data = decode_transfer_data::<T>(
+				&packet.source_port,
+				&packet.source_channel,
+				&packet.data,
+			)?;
+			let mut exceeded = None;
+			let retried = atomically(|| {
+				Pallet::<T>::forward_transfer(
+					&received,
+					packet.source_channel,
+					packet_data,
+					forward.timeout,
+					forward.retries - 1,
+					&relayer,
+					&mut exceeded,
+				)
+			});
+			match retried {
+				Ok(()) => return Ok(()),
+				Err(e) => {
+					log::trace!(target: "pallet_ibc", "[forward]: retry failed: {:?}", e);
+					if let Some(event) = exceeded {
+						Pallet::<T>::deposit_event(event);
+					}
+				},
+			}
+		}
+
+		// The forwarded tokens have been refunded to the receiver of the packet at this point
+		Pallet::<T>::undo_receive(&received)?;
+		let ack = Ics20Acknowledgement::Error(format!("{}: {}", ACK_ERR_STR, reason));
+		Pallet::<T>::write_forward_acknowledgement(
+			&received,
+			ack.to_string().into_bytes(),
+			&relayer,
+		)
+	}
+
+	/// Returns the account that receives the tokens of a forwarded packet on this chain, packets
+	/// forwarded through hops are received by the forward address of the channel.
+	fn forwarding_receiver(packet: &Packet, packet_data: &PacketDataV2) -> Result<Signer, Error> {
+		if packet_data.is_forwarded() {
+			get_channel_forward_address(&packet.destination_port, packet.destination_channel)
+				.map_err(|e| Error::implementation_specific(e.to_string()))
+		} else {
+			Ok(packet_data.receiver.clone())
+		}
+	}
+
+	/// Burns or escrows back the tokens minted or unescrowed for a received packet.
+	fn undo_receive(packet: &Packet) -> Result<(), Error> {
+		let mut ctx = Context::<T>::default();
+		let packet_data = decode_transfer_data::<T>(
+			&packet.destination_port,
+			&packet.destination_channel,
+			&packet.data,
+		)?;
+		let receiver = <T as Config>::AccountIdConversion::try_from(
+			Pallet::<T>::forwarding_receiver(packet, &packet_data)?,
+		)
+		.map_err(|_| {
+			Error::implementation_specific("Failed to parse receiver account".to_string())
+		})?;
+		for mut coin in packet_data.tokens {
+			let result = if is_receiver_chain_source(
+				packet.source_port.clone(),
+				packet.source_channel,
+				&coin.denom,
+			) {
+				coin.denom.remove_trace_prefix(&TracePrefix::new(
+					packet.source_port.clone(),
+					packet.source_channel,
+				));
+				ctx.get_channel_escrow_address(&packet.destination_port, packet.destination_channel)
+					.and_then(|escrow_address| ctx.send_coins(&receiver, &escrow_address, &coin))
+			} else {
+				coin.denom.add_trace_prefix(TracePrefix::new(
+					packet.destination_port.clone(),
+					packet.destination_channel,
+				));
+				ctx.burn_coins(&receiver, &coin)
+			};
+			result.map_err(|e| Error::implementation_specific(e.to_string()))?;
+		}
+		Pallet::<T>::settle_packet_flows(
+			&packet.destination_channel,
+			packet.sequence.into(),
+			FlowDirection::Inflow,
+			true,
+		);
+		Ok(())
+	}
+
+	/// Writes the held acknowledgement of a forwarded packet, after it went through the modules
+	/// routed to the port the packet was received on, e.g. to be wrapped by the fee middleware.
+	fn write_forward_acknowledgement(
+		packet: &Packet,
+		ack: Vec<u8>,
+		relayer: &Signer,
+	) -> Result<(), Error> {
+		// The received tokens are kept once the acknowledgement is written, unless they were
+		// taken back already
+		Pallet::<T>::settle_packet_flows(
+			&packet.destination_channel,
+			packet.sequence.into(),
+			FlowDirection::Inflow,
+			false,
+		);
+		let mut ctx = Context::<T>::default();
+		let module_id =
+			ctx.lookup_module_by_port(&packet.destination_port).map_err(Error::ics05_port)?;
+		let ctx_clone = ctx.clone();
+		let ack = ctx
+			.router_mut()
+			.get_route_mut(&module_id)
+			.ok_or_else(Error::route_not_found)?
+			.on_write_acknowledgement(&ctx_clone, packet, ack.into(), relayer)?
+			.into_bytes();
+		Pallet::<T>::handle_message(HandlerMessage::WriteAck { packet: packet.clone(), ack })
+			.map_err(|e| Error::implementation_specific(format!("[forward] {:#?}", e)))
+	}
+}
+
+fn error_acknowledgement(err: Error) -> Acknowledgement {
+	Acknowledgement::from_bytes(
+		Ics20Acknowledgement::Error(format!("{}: {:?}", ACK_ERR_STR, err))
+			.to_string()
+			.into_bytes(),
+	)
+}
@@ -2,10 +2,11 @@ use crate::{ics20::HandleMemo, Config};
 use alloc::format;
 use core::fmt::Debug;
 use ibc::{
-	applications::transfer::packet::PacketData,
+	applications::transfer::packet::{decode_packet_data, PacketData},
 	core::{
 		ics04_channel::{
 			channel::{Counterparty, Order},
+			context::ChannelReader,
 			error::Error,
 			msgs::acknowledgement::{Acknowledgement as GenericAcknowledgement, Acknowledgement},
 			packet::Packet,
@@ -145,6 +146,81 @@ impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Deb
 		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)
 	}
 
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Error> {
+		self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			counterparty_version,
+		)
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Error> {
+		self.inner
+			.on_chan_upgrade_ack(ctx, output, port_id, channel_id, counterparty_version)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Error> {
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
 	fn on_recv_packet(
 		&self,
 		ctx: &dyn ModuleCallbackContext,
@@ -153,10 +229,27 @@ impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Deb
 		relayer: &Signer,
 	) -> Result<Acknowledgement, Error> {
 		let ack = self.inner.on_recv_packet(ctx, output, packet, relayer)?;
-		let packet_data: PacketData =
-			serde_json::from_slice(packet.data.as_slice()).map_err(|e| {
+		// The tokens of transfers whose acknowledgement is held have been forwarded
+		if ack.as_ref().is_empty() {
+			return Ok(ack)
+		}
+		let channel_end =
+			ctx.channel_end(&(packet.destination_port.clone(), packet.destination_channel))?;
+		let packet_data = decode_packet_data(channel_end.version(), packet.data.as_slice())
+			.map_err(|e| {
 				Error::implementation_specific(format!("Failed to decode packet data {:?}", e))
 			})?;
+		let has_memo = !packet_data.memo.is_empty();
+		let packet_data = match PacketData::try_from(packet_data) {
+			Ok(packet_data) => packet_data,
+			// Memos can only be executed for transfers of a single token
+			Err(_) if !has_memo => return Ok(ack),
+			Err(e) =>
+				return Err(Error::implementation_specific(format!(
+					"Failed to execute memo {:?}",
+					e
+				))),
+		};
 		let receiver = <T as Config>::AccountIdConversion::try_from(packet_data.receiver.clone())
 			.map_err(|_| {
 				Error::implementation_specific(format!("Failed to parse receiver account"))
@@ -168,6 +261,16 @@ impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Deb
 		Ok(ack)
 	}
 
+	fn on_write_acknowledgement(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		packet: &Packet,
+		acknowledgement: GenericAcknowledgement,
+		relayer: &Signer,
+	) -> Result<GenericAcknowledgement, Error> {
+		self.inner.on_write_acknowledgement(ctx, packet, acknowledgement, relayer)
+	}
+
 	fn on_acknowledgement_packet(
 		&mut self,
 		ctx: &dyn ModuleCallbackContext,
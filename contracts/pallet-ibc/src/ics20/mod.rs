@@ -1,24 +1,36 @@
 pub mod context;
+pub mod forward;
 pub mod memo;
+pub mod rate_limit;
 
-use crate::{routing::Context, ChannelIds, Config, DenomToAssetId, Event, Pallet, WeightInfo};
+use crate::{
+	ics20::rate_limit::{FlowDirection, PacketFlow},
+	routing::Context,
+	ChannelIds, Config, DenomToAssetId, Event, Pallet, WeightInfo,
+};
 use alloc::{
 	format,
 	str::FromStr,
 	string::{String, ToString},
+	vec::Vec,
 };
 
-use frame_support::weights::Weight;
+use frame_support::{
+	dispatch::DispatchError,
+	storage::{with_transaction, TransactionOutcome},
+	weights::Weight,
+};
 pub use ibc::applications::transfer::{MODULE_ID_STR, PORT_ID_STR};
 use ibc::{
 	applications::transfer::{
 		acknowledgement::{Acknowledgement as Ics20Acknowledgement, ACK_ERR_STR},
 		context::{
 			on_chan_close_confirm, on_chan_close_init, on_chan_open_ack, on_chan_open_confirm,
-			on_chan_open_init, on_chan_open_try,
+			on_chan_open_init, on_chan_open_try, on_chan_upgrade_ack, on_chan_upgrade_init,
+			on_chan_upgrade_try,
 		},
 		is_receiver_chain_source, is_sender_chain_source,
-		packet::PacketData,
+		packet::{decode_packet_data, PacketData, PacketDataV2},
 		relay::{
 			on_ack_packet::process_ack_packet, on_recv_packet::process_recv_packet,
 			on_timeout_packet::process_timeout_packet,
@@ -28,6 +40,7 @@ use ibc::{
 	core::{
 		ics04_channel::{
 			channel::{Counterparty, Order},
+			context::ChannelReader,
 			error::Error as Ics04Error,
 			msgs::acknowledgement::Acknowledgement,
 			packet::Packet,
@@ -193,6 +206,57 @@ where
 			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
 	}
 
+	fn on_chan_upgrade_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<Version, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		on_chan_upgrade_init(&mut ctx, output, port_id, channel_id, order, connection_hops, version)
+			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		on_chan_upgrade_try(
+			&mut ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			counterparty_version,
+		)
+		.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Ics04Error> {
+		let mut ctx = Context::<T>::default();
+		on_chan_upgrade_ack(&mut ctx, output, port_id, channel_id, counterparty_version)
+			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))
+	}
+
 	fn on_recv_packet(
 		&self,
 		_ctx: &dyn ModuleCallbackContext,
@@ -200,71 +264,29 @@ where
 		packet: &mut Packet,
 		_relayer: &Signer,
 	) -> Result<Acknowledgement, Ics04Error> {
-		let mut ctx = Context::<T>::default();
-		let result = serde_json::from_slice(packet.data.as_slice())
-			.map_err(|e| {
-				Ics04Error::implementation_specific(format!("Failed to decode packet data {:?}", e))
-			})
-			.and_then(|packet_data: PacketData| {
-				// We need to reject transaction amounts that are larger than u128 since we expect
-				// the balance type of the runtime to be a u128; For a U256 to be converted to a
-				// u128 without truncating, the last two words should be zero
-				let amount = packet_data.token.amount.as_u256();
-				u128::try_from(amount)
-					.map_err(|e| Ics04Error::implementation_specific(format!("{:?}", e)))?;
-				process_recv_packet(&mut ctx, output, packet, packet_data.clone())
-					.map(|_| packet_data)
-					.map_err(|e| {
-						log::trace!(target: "pallet_ibc", "[on_recv_packet]: {:?}", e);
-						Ics04Error::implementation_specific(e.to_string())
-					})
-			});
-
-		let ack = match result {
+		let mut exceeded = None;
+		let received = decode_transfer_data::<T>(
+			&packet.destination_port,
+			&packet.destination_channel,
+			&packet.data,
+		)
+		.and_then(|packet_data| receive_transfer::<T>(output, packet, packet_data, &mut exceeded));
+		let ack = match received {
 			Err(err) => {
-				let ack = Ics20Acknowledgement::Error(format!("{}: {:?}", ACK_ERR_STR, err))
+				if let Some(event) = exceeded {
+					Pallet::<T>::deposit_event(event);
+				}
+				Ics20Acknowledgement::Error(format!("{}: {:?}", ACK_ERR_STR, err))
 					.to_string()
-					.into_bytes();
-				Pallet::<T>::handle_message(HandlerMessage::WriteAck {
-					packet: packet.clone(),
-					ack: ack.clone(),
-				})
-				.map_err(|e| {
-					Ics04Error::implementation_specific(format!("[on_recv_packet] {:#?}", e))
-				})?;
-				ack
-			},
-			Ok(packet_data) => {
-				let denom = full_ibc_denom(packet, packet_data.token.clone());
-				let prefixed_denom = PrefixedDenom::from_str(&denom).map_err(|_| {
-					Ics04Error::implementation_specific("Failed to parse token denom".to_string())
-				})?;
-				Pallet::<T>::deposit_event(Event::<T>::TokenReceived {
-					from: packet_data.sender.to_string().as_bytes().to_vec(),
-					to: packet_data.receiver.to_string().as_bytes().to_vec(),
-					ibc_denom: denom.as_bytes().to_vec(),
-					local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom)
-						.ok(),
-					amount: packet_data.token.amount.as_u256().as_u128().into(),
-					is_receiver_source: is_receiver_chain_source(
-						packet.source_port.clone(),
-						packet.source_channel.clone(),
-						&prefixed_denom,
-					),
-					source_channel: packet.source_channel.to_string().as_bytes().to_vec(),
-					destination_channel: packet.destination_channel.to_string().as_bytes().to_vec(),
-				});
-				let packet = packet.clone();
-				Pallet::<T>::handle_message(HandlerMessage::WriteAck {
-					packet,
-					ack: Ics20Acknowledgement::success().to_string().into_bytes(),
-				})
-				.map_err(|e| {
-					Ics04Error::implementation_specific(format!("[on_recv_packet] {:#?}", e))
-				})?;
-				Ics20Acknowledgement::success().to_string().into_bytes()
+					.into_bytes()
 			},
+			Ok(_) => Ics20Acknowledgement::success().to_string().into_bytes(),
 		};
+		Pallet::<T>::handle_message(HandlerMessage::WriteAck {
+			packet: packet.clone(),
+			ack: ack.clone(),
+		})
+		.map_err(|e| Ics04Error::implementation_specific(format!("[on_recv_packet] {:#?}", e)))?;
 		Ok(Acknowledgement::from_bytes(ack))
 	}
 
@@ -277,10 +299,8 @@ where
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
 		let mut ctx = Context::<T>::default();
-		let packet_data: PacketData =
-			serde_json::from_slice(packet.data.as_slice()).map_err(|e| {
-				Ics04Error::implementation_specific(format!("Failed to decode packet data {:?}", e))
-			})?;
+		let packet_data =
+			decode_transfer_data::<T>(&packet.source_port, &packet.source_channel, &packet.data)?;
 		let ack = serde_json::from_slice::<Ics20Acknowledgement>(&acknowledgement.as_ref())
 			.map_err(|e| {
 				Ics04Error::implementation_specific(format!(
@@ -290,47 +310,60 @@ where
 			})?;
 		process_ack_packet(&mut ctx, packet, &packet_data, &ack)
 			.map_err(|e| Ics04Error::implementation_specific(e.to_string()))?;
-		match ack.into_result() {
-			Ok(_) => Pallet::<T>::deposit_event(Event::<T>::TokenTransferCompleted {
-				from: packet_data.sender.to_string().as_bytes().to_vec(),
-				to: packet_data.receiver.to_string().as_bytes().to_vec(),
-				ibc_denom: packet_data.token.denom.to_string().as_bytes().to_vec(),
-				local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
-					&packet_data.token.denom.to_string(),
-				)
-				.ok(),
-				amount: packet_data.token.amount.as_u256().as_u128().into(),
-				is_sender_source: is_sender_chain_source(
-					packet.source_port.clone(),
-					packet.source_channel.clone(),
-					&packet_data.token.denom,
-				),
-				source_channel: packet.source_channel.to_string().as_bytes().to_vec(),
-				destination_channel: packet.destination_channel.to_string().as_bytes().to_vec(),
-			}),
-			Err(e) => {
-				log::trace!(
-					target: "pallet_ibc",
-					"[transfer] error: acknowledgement error: {e}",
-				);
-				Pallet::<T>::deposit_event(Event::<T>::TokenTransferFailed {
-					from: packet_data.sender.to_string().as_bytes().to_vec(),
-					to: packet_data.receiver.to_string().as_bytes().to_vec(),
-					ibc_denom: packet_data.token.denom.to_string().as_bytes().to_vec(),
-					local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
-						&packet_data.token.denom.to_string(),
-					)
-					.ok(),
-					amount: packet_data.token.amount.as_u256().as_u128().into(),
-					is_sender_source: is_sender_chain_source(
-						packet.source_port.clone(),
-						packet.source_channel.clone(),
-						&packet_data.token.denom,
-					),
-					source_channel: packet.source_channel.to_string().as_bytes().to_vec(),
-					destination_channel: packet.destination_channel.to_string().as_bytes().to_vec(),
-				})
-			},
+		let result = ack.into_result();
+		if let Err(e) = &result {
+			log::trace!(
+				target: "pallet_ibc",
+				"[transfer] error: acknowledgement error: {e}",
+			);
+		}
+		// The tokens of a failed packet have been refunded, they no longer count towards the
+		// outflow of the channel
+		Pallet::<T>::settle_packet_flows(
+			&packet.source_channel,
+			packet.sequence.into(),
+			FlowDirection::Outflow,
+			result.is_err(),
+		);
+		for token in &packet_data.tokens {
+			let from = packet_data.sender.to_string().as_bytes().to_vec();
+			let to = packet_data.receiver.to_string().as_bytes().to_vec();
+			let ibc_denom = token.denom.to_string().as_bytes().to_vec();
+			let local_asset_id =
+				T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&token.denom.to_string())
+					.ok();
+			let amount = u128::try_from(token.amount.as_u256())
+				.map_err(|e| Ics04Error::implementation_specific(format!("{:?}", e)))?
+				.into();
+			let is_sender_source = is_sender_chain_source(
+				packet.source_port.clone(),
+				packet.source_channel.clone(),
+				&token.denom,
+			);
+			let source_channel = packet.source_channel.to_string().as_bytes().to_vec();
+			let destination_channel = packet.destination_channel.to_string().as_bytes().to_vec();
+			Pallet::<T>::deposit_event(match result {
+				Ok(_) => Event::<T>::TokenTransferCompleted {
+					from,
+					to,
+					ibc_denom,
+					local_asset_id,
+					amount,
+					is_sender_source,
+					source_channel,
+					destination_channel,
+				},
+				Err(_) => Event::<T>::TokenTransferFailed {
+					from,
+					to,
+					ibc_denom,
+					local_asset_id,
+					amount,
+					is_sender_source,
+					source_channel,
+					destination_channel,
+				},
+			});
 		}
 
 		Ok(())
@@ -344,10 +377,16 @@ where
 		_relayer: &Signer,
 	) -> Result<(), Ics04Error> {
 		let mut ctx = Context::<T>::default();
-		let packet_data: PacketData = serde_json::from_slice(packet.data.as_slice())
-			.map_err(|e| Ics04Error::app_module(format!("Failed to decode packet data {:?}", e)))?;
+		let packet_data =
+			decode_transfer_data::<T>(&packet.source_port, &packet.source_channel, &packet.data)?;
 		process_timeout_packet(&mut ctx, packet, &packet_data)
 			.map_err(|e| Ics04Error::app_module(e.to_string()))?;
+		Pallet::<T>::settle_packet_flows(
+			&packet.source_channel,
+			packet.sequence.into(),
+			FlowDirection::Outflow,
+			true,
+		);
 
 		Ok(())
 	}
@@ -403,6 +442,102 @@ impl<T: Config> CallbackWeight for WeightHandler<T> {
 	}
 }
 
+/// Decodes the data of a transfer packet according to the version of the channel it was sent or
+/// received on.
+pub(crate) fn decode_transfer_data<T: Config + Send + Sync>(
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	data: &[u8],
+) -> Result<PacketDataV2, Ics04Error>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	let ctx = Context::<T>::default();
+	let channel_end = ctx.channel_end(&(port_id.clone(), *channel_id))?;
+	decode_packet_data(channel_end.version(), data).map_err(|e| {
+		Ics04Error::implementation_specific(format!("Failed to decode packet data {:?}", e))
+	})
+}
+
+/// Mints or unescrows the tokens of a received transfer, either all of them are received or none.
+/// Returns the transfers recorded in the inflows of the channel's rate limits. If the packet
+/// exceeds a rate limit, `exceeded` is set to the event to deposit once the receive is rolled back.
+pub(crate) fn receive_transfer<T: Config + Send + Sync>(
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	packet_data: PacketDataV2,
+	exceeded: &mut Option<Event<T>>,
+) -> Result<Vec<PacketFlow<T::AssetId, T::Balance>>, Ics04Error>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	atomically(|| {
+		let mut ctx = Context::<T>::default();
+		let mut received = Vec::with_capacity(packet_data.tokens.len());
+		let mut flows = Vec::new();
+		for token in &packet_data.tokens {
+			// We need to reject transaction amounts that are larger than u128 since we expect
+			// the balance type of the runtime to be a u128; For a U256 to be converted to a
+			// u128 without truncating, the last two words should be zero
+			let amount = u128::try_from(token.amount.as_u256())
+				.map_err(|e| Ics04Error::implementation_specific(format!("{:?}", e)))?;
+			let denom = full_ibc_denom(packet, token.clone());
+			let prefixed_denom = PrefixedDenom::from_str(&denom).map_err(|_| {
+				Ics04Error::implementation_specific("Failed to parse token denom".to_string())
+			})?;
+			// Packets that exceed the rate limit of the channel are rejected before any tokens
+			// are minted or unescrowed
+			let asset_id = T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(&denom).ok();
+			if let Some(asset_id) = asset_id {
+				flows.extend(
+					Pallet::<T>::record_transfer_flow(
+						&packet.destination_channel,
+						asset_id,
+						amount.into(),
+						FlowDirection::Inflow,
+					)
+					.map_err(|_| {
+						*exceeded = Some(Pallet::<T>::rate_limit_exceeded(
+							&packet.destination_channel,
+							asset_id,
+							amount.into(),
+							FlowDirection::Inflow,
+						));
+						Ics04Error::implementation_specific(
+							"Transfer exceeds the rate limit of the channel".to_string(),
+						)
+					})?,
+				);
+			}
+			received.push((denom, prefixed_denom, asset_id, amount));
+		}
+		process_recv_packet(&mut ctx, output, packet, packet_data.clone()).map_err(|e| {
+			log::trace!(target: "pallet_ibc", "[on_recv_packet]: {:?}", e);
+			Ics04Error::implementation_specific(e.to_string())
+		})?;
+
+		for (denom, prefixed_denom, local_asset_id, amount) in received {
+			Pallet::<T>::deposit_event(Event::<T>::TokenReceived {
+				from: packet_data.sender.to_string().as_bytes().to_vec(),
+				to: packet_data.receiver.to_string().as_bytes().to_vec(),
+				ibc_denom: denom.as_bytes().to_vec(),
+				local_asset_id,
+				amount: amount.into(),
+				is_receiver_source: is_receiver_chain_source(
+					packet.source_port.clone(),
+					packet.source_channel.clone(),
+					&prefixed_denom,
+				),
+				source_channel: packet.source_channel.to_string().as_bytes().to_vec(),
+				destination_channel: packet.destination_channel.to_string().as_bytes().to_vec(),
+			});
+		}
+		Ok(flows)
+	})
+}
+
 pub fn full_ibc_denom(packet: &Packet, mut token: PrefixedCoin) -> String {
 	if is_receiver_chain_source(packet.source_port.clone(), packet.source_channel, &token.denom) {
 		let prefix = TracePrefix::new(packet.source_port.clone(), packet.source_channel);
@@ -417,6 +552,15 @@ pub fn full_ibc_denom(packet: &Packet, mut token: PrefixedCoin) -> String {
 	}
 }
 
+/// Runs `f` in a storage transaction that is rolled back if it fails.
+pub(crate) fn atomically<R>(f: impl FnOnce() -> Result<R, Ics04Error>) -> Result<R, Ics04Error> {
+	with_transaction(|| match f() {
+		Ok(result) => TransactionOutcome::Commit(Ok(Ok(result))),
+		Err(e) => TransactionOutcome::Rollback(Ok(Err(e))),
+	})
+	.map_err(|e: DispatchError| Ics04Error::implementation_specific(format!("{:?}", e)))?
+}
+
 use ibc::applications::transfer::error::Error as Ics20Error;
 
 pub trait HandleMemo<T: Config> {
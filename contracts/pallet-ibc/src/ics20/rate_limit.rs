@@ -0,0 +1,224 @@
+use crate::{Config, Error, Event, PacketFlows, Pallet, RateLimitFlows, RateLimits};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{fungibles::Inspect, Currency, Get, UnixTime},
+	weights::Weight,
+};
+use ibc::core::ics24_host::identifier::ChannelId;
+use scale_info::{prelude::string::ToString, TypeInfo};
+use sp_runtime::{traits::Saturating, Percent, RuntimeDebug};
+
+/// Maximum amount of an asset that can be transferred over a channel in a period.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Copy, Clone)]
+pub enum Quota<Balance> {
+	/// Absolute amount of the asset
+	Absolute(Balance),
+	/// Share of the total issuance of the asset at the start of the period
+	Percent(Percent),
+}
+
+/// Rate limit of ICS-20 transfers of an asset over a channel.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Copy, Clone)]
+pub struct RateLimit<Balance> {
+	/// Quota of tokens received over the channel, no limit if unset
+	pub max_inflow: Option<Quota<Balance>>,
+	/// Quota of tokens sent over the channel, no limit if unset
+	pub max_outflow: Option<Quota<Balance>>,
+	/// Length of the period in seconds, the flows are reset once it has elapsed. Periods follow
+	/// each other from the moment the rate limit is set
+	pub period: u64,
+}
+
+/// Amounts transferred over a channel in the current period of its rate limit.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Copy, Clone, Default)]
+pub struct RateLimitFlow<Balance> {
+	pub inflow: Balance,
+	pub outflow: Balance,
+	/// Total issuance of the asset at the start of the period
+	pub issuance: Balance,
+	/// Start of the period as a unix timestamp in seconds
+	pub period_start: u64,
+}
+
+/// Transfer of a pending packet recorded in the flow of a rate limit.
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Clone)]
+pub struct PacketFlow<AssetId, Balance> {
+	pub asset_id: AssetId,
+	pub amount: Balance,
+	/// Start of the period the transfer was recorded in
+	pub period_start: u64,
+}
+
+#[derive(RuntimeDebug, PartialEq, Eq, TypeInfo, Encode, Decode, Copy, Clone)]
+pub enum FlowDirection {
+	/// Tokens received over the channel
+	Inflow,
+	/// Tokens sent over the channel
+	Outflow,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Records the transfer of `amount` tokens over `channel_id` in the flow of the channel's rate
+	/// limit for the asset, the transfer is rejected if it would exceed the quota of the period.
+	/// Returns the recorded transfer if the asset is rate limited on the channel.
+	pub(crate) fn record_transfer_flow(
+		channel_id: &ChannelId,
+		asset_id: T::AssetId,
+		amount: T::Balance,
+		direction: FlowDirection,
+	) -> Result<Option<PacketFlow<T::AssetId, T::Balance>>, Error<T>> {
+		let channel = channel_id.to_string().as_bytes().to_vec();
+		let rate_limit = match RateLimits::<T>::get(&channel, asset_id) {
+			Some(rate_limit) => rate_limit,
+			None => return Ok(None),
+		};
+		let now = T::TimeProvider::now().as_secs();
+		let mut flow = Self::current_flow(
+			RateLimitFlows::<T>::get(&channel, asset_id),
+			asset_id,
+			rate_limit.period,
+			now,
+		);
+
+		let (quota, total) = match direction {
+			FlowDirection::Inflow => {
+				flow.inflow = flow.inflow.saturating_add(amount);
+				(rate_limit.max_inflow, flow.inflow)
+			},
+			FlowDirection::Outflow => {
+				flow.outflow = flow.outflow.saturating_add(amount);
+				(rate_limit.max_outflow, flow.outflow)
+			},
+		};
+		let max = match quota {
+			Some(Quota::Absolute(max)) => Some(max),
+			Some(Quota::Percent(percent)) => Some(percent * flow.issuance),
+			None => None,
+		};
+		if matches!(max, Some(max) if total > max) {
+			log::trace!(target: "pallet_ibc", "[rate_limit]: {:?} of {:?} over {} exceeds the quota", direction, amount, channel_id);
+			return Err(Error::<T>::RateLimitExceeded)
+		}
+
+		let period_start = flow.period_start;
+		RateLimitFlows::<T>::insert(&channel, asset_id, flow);
+		Ok(Some(PacketFlow { asset_id, amount, period_start }))
+	}
+
+	/// Starts a new period for every rate limit whose period has elapsed, so that `Percent` quotas
+	/// are measured against the total issuance of the asset at the start of the period rather than
+	/// at the first transfer in it. Called at the start of every block.
+	pub(crate) fn roll_over_rate_limit_flows() -> Weight {
+		let now = T::TimeProvider::now().as_secs();
+		let (mut reads, mut writes) = (1, 0);
+		for (channel, asset_id, rate_limit) in RateLimits::<T>::iter() {
+			let stored = RateLimitFlows::<T>::get(&channel, asset_id);
+			reads += 2;
+			let flow = Self::current_flow(stored, asset_id, rate_limit.period, now);
+			if stored != Some(flow) {
+				RateLimitFlows::<T>::insert(&channel, asset_id, flow);
+				reads += 1;
+				writes += 1;
+			}
+		}
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+
+	/// Flow of a period starting at `period_start`, the flows are reset and the total issuance of
+	/// the asset is snapshotted.
+	pub(crate) fn new_period(asset_id: T::AssetId, period_start: u64) -> RateLimitFlow<T::Balance> {
+		RateLimitFlow {
+			inflow: Default::default(),
+			outflow: Default::default(),
+			issuance: Self::total_issuance(asset_id),
+			period_start,
+		}
+	}
+
+	/// Flow of the period `now` falls in, a new period starts at the last period boundary before
+	/// `now`.
+	fn current_flow(
+		flow: Option<RateLimitFlow<T::Balance>>,
+		asset_id: T::AssetId,
+		period: u64,
+		now: u64,
+	) -> RateLimitFlow<T::Balance> {
+		match flow {
+			Some(flow) if now < flow.period_start.saturating_add(period) => flow,
+			Some(flow) => Self::new_period(
+				asset_id,
+				now - (now - flow.period_start).checked_rem(period).unwrap_or_default(),
+			),
+			None => Self::new_period(asset_id, now),
+		}
+	}
+
+	/// Event of a transfer rejected by [`Self::record_transfer_flow`]. It must be deposited after
+	/// the storage changes of the rejected packet are rolled back, or it's rolled back with them.
+	pub(crate) fn rate_limit_exceeded(
+		channel_id: &ChannelId,
+		asset_id: T::AssetId,
+		amount: T::Balance,
+		direction: FlowDirection,
+	) -> Event<T> {
+		Event::<T>::RateLimitExceeded {
+			channel_id: channel_id.to_string().as_bytes().to_vec(),
+			asset_id,
+			amount,
+			is_inflow: direction == FlowDirection::Inflow,
+		}
+	}
+
+	/// Keeps the transfers of a packet recorded in the flows of the channel until the packet is
+	/// settled, so that they can be removed from the flows if its tokens are refunded.
+	pub(crate) fn track_packet_flows(
+		channel_id: &ChannelId,
+		sequence: u64,
+		direction: FlowDirection,
+		flows: Vec<PacketFlow<T::AssetId, T::Balance>>,
+	) {
+		if !flows.is_empty() {
+			let channel = channel_id.to_string().as_bytes().to_vec();
+			PacketFlows::<T>::insert(&channel, (sequence, direction), flows);
+		}
+	}
+
+	/// Stops tracking the transfers of a packet, if its tokens were refunded the transfers are
+	/// removed from the flows of the channel. Transfers recorded in a period that has since ended
+	/// are left alone, they no longer count towards the quota.
+	pub(crate) fn settle_packet_flows(
+		channel_id: &ChannelId,
+		sequence: u64,
+		direction: FlowDirection,
+		refunded: bool,
+	) {
+		let channel = channel_id.to_string().as_bytes().to_vec();
+		let flows = PacketFlows::<T>::take(&channel, (sequence, direction));
+		if !refunded {
+			return
+		}
+		for PacketFlow { asset_id, amount, period_start } in flows {
+			RateLimitFlows::<T>::mutate_exists(&channel, asset_id, |flow| {
+				if let Some(flow) = flow.as_mut().filter(|flow| flow.period_start == period_start) {
+					match direction {
+						FlowDirection::Inflow => flow.inflow = flow.inflow.saturating_sub(amount),
+						FlowDirection::Outflow =>
+							flow.outflow = flow.outflow.saturating_sub(amount),
+					}
+				}
+			});
+		}
+	}
+
+	fn total_issuance(asset_id: T::AssetId) -> T::Balance {
+		if asset_id == T::NativeAssetId::get() {
+			<T::NativeCurrency as Currency<<T as frame_system::Config>::AccountId>>::total_issuance(
+			)
+		} else {
+			<T::Fungibles as Inspect<<T as frame_system::Config>::AccountId>>::total_issuance(
+				asset_id,
+			)
+		}
+	}
+}
@@ -3,13 +3,17 @@ use alloc::{format, string::ToString};
 use core::fmt::Debug;
 use ibc::{
 	applications::transfer::{
-		acknowledgement::Acknowledgement as Ics20Ack, context::BankKeeper,
-		is_receiver_chain_source, packet::PacketData, TracePrefix,
+		acknowledgement::Acknowledgement as Ics20Ack,
+		context::BankKeeper,
+		is_receiver_chain_source,
+		packet::{decode_packet_data, encode_packet_data},
+		TracePrefix,
 	},
 	bigint::U256,
 	core::{
 		ics04_channel::{
 			channel::{Counterparty, Order},
+			context::ChannelReader,
 			error::Error as Ics04Error,
 			msgs::acknowledgement::Acknowledgement,
 			packet::Packet,
@@ -200,6 +204,81 @@ where
 		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)
 	}
 
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<Version, Ics04Error> {
+		self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			counterparty_version,
+		)
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Ics04Error> {
+		self.inner
+			.on_chan_upgrade_ack(ctx, output, port_id, channel_id, counterparty_version)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			version,
+		)
+	}
+
 	fn on_recv_packet(
 		&self,
 		_ctx: &dyn ModuleCallbackContext,
@@ -210,7 +289,9 @@ where
 		// Module ModuleCallbackContext does not have the ics20 context as part of its trait bounds
 		// so we define a new context
 		let mut ctx = Context::<T>::default();
-		let mut packet_data = serde_json::from_slice::<PacketData>(packet.data.as_slice())
+		let channel_end =
+			ctx.channel_end(&(packet.destination_port.clone(), packet.destination_channel))?;
+		let mut packet_data = decode_packet_data(channel_end.version(), packet.data.as_slice())
 			.map_err(|e| {
 				Ics04Error::implementation_specific(format!("Failed to decode packet data {:?}", e))
 			})?;
@@ -219,49 +300,65 @@ where
 		let ack = self.inner.on_recv_packet(&mut ctx, output, packet, relayer)?;
 		// We only take the fee charge if the acknowledgement is not an error
 		if ack.as_ref() == Ics20Ack::success().to_string().as_bytes() {
-			// We have ensured that token amounts larger than the max value for a u128 are rejected
-			// in the ics20 on_recv_packet callback so we can multiply safely.
-			// Percent does Non-Overflowing multiplication so this is infallible
-			let fee = percent * packet_data.token.amount.as_u256().low_u128();
 			let receiver =
 				<T as crate::Config>::AccountIdConversion::try_from(packet_data.receiver.clone())
 					.map_err(|_| {
 					Ics04Error::implementation_specific("Failed to receiver account".to_string())
 				})?;
 			let pallet_account = Pallet::<T>::account_id();
-			let mut prefixed_coin = if is_receiver_chain_source(
-				packet.source_port.clone(),
-				packet.source_channel,
-				&packet_data.token.denom,
-			) {
-				let prefix = TracePrefix::new(packet.source_port.clone(), packet.source_channel);
-				let mut c = packet_data.token.clone();
-				c.denom.remove_trace_prefix(&prefix);
-				c
-			} else {
-				let prefix =
-					TracePrefix::new(packet.destination_port.clone(), packet.destination_channel);
-				let mut c = packet_data.token.clone();
-				c.denom.add_trace_prefix(prefix);
-				c
-			};
-			prefixed_coin.amount = fee.into();
-			// Now we proceed to send the service fee from the receiver's account to the pallet
-			// account
-			ctx.send_coins(&receiver, &pallet_account, &prefixed_coin)
-				.map_err(|e| Ics04Error::app_module(e.to_string()))?;
-			// We modify the packet data to remove the fee so any other middleware has access to the
-			// correct amount deposited in the receiver's account
-			packet_data.token.amount =
-				(packet_data.token.amount.as_u256() - U256::from(fee)).into();
-			packet.data = serde_json::to_vec(&packet_data).map_err(|_| {
-				Ics04Error::implementation_specific("Failed to receiver account".to_string())
+			for token in packet_data.tokens.iter_mut() {
+				// We have ensured that token amounts larger than the max value for a u128 are
+				// rejected in the ics20 on_recv_packet callback so we can multiply safely.
+				// Percent does Non-Overflowing multiplication so this is infallible
+				let fee = percent * token.amount.as_u256().low_u128();
+				let mut prefixed_coin = if is_receiver_chain_source(
+					packet.source_port.clone(),
+					packet.source_channel,
+					&token.denom,
+				) {
+					let prefix =
+						TracePrefix::new(packet.source_port.clone(), packet.source_channel);
+					let mut c = token.clone();
+					c.denom.remove_trace_prefix(&prefix);
+					c
+				} else {
+					let prefix = TracePrefix::new(
+						packet.destination_port.clone(),
+						packet.destination_channel,
+					);
+					let mut c = token.clone();
+					c.denom.add_trace_prefix(prefix);
+					c
+				};
+				prefixed_coin.amount = fee.into();
+				// Now we proceed to send the service fee from the receiver's account to the
+				// pallet account
+				ctx.send_coins(&receiver, &pallet_account, &prefixed_coin)
+					.map_err(|e| Ics04Error::app_module(e.to_string()))?;
+				// We modify the packet data to remove the fee so any other middleware has access
+				// to the correct amount deposited in the receiver's account
+				token.amount = (token.amount.as_u256() - U256::from(fee)).into();
+				Pallet::<T>::deposit_event(Event::<T>::IbcTransferFeeCollected {
+					amount: fee.into(),
+				})
+			}
+			packet.data = encode_packet_data(channel_end.version(), packet_data).map_err(|_| {
+				Ics04Error::implementation_specific("Failed to encode packet data".to_string())
 			})?;
-			Pallet::<T>::deposit_event(Event::<T>::IbcTransferFeeCollected { amount: fee.into() })
 		}
 		Ok(ack)
 	}
 
+	fn on_write_acknowledgement(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		packet: &Packet,
+		acknowledgement: Acknowledgement,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		self.inner.on_write_acknowledgement(ctx, packet, acknowledgement, relayer)
+	}
+
 	fn on_acknowledgement_packet(
 		&mut self,
 		ctx: &dyn ModuleCallbackContext,
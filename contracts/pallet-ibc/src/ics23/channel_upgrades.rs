@@ -0,0 +1,95 @@
+use crate::{format, Config};
+use frame_support::storage::{child, child::ChildInfo};
+use ibc::core::{
+	ics04_channel::upgrade::{ErrorReceipt, Upgrade},
+	ics24_host::{
+		identifier::{ChannelId, PortId},
+		path::{ChannelUpgradeErrorsPath, ChannelUpgradesPath},
+	},
+};
+use ibc_primitives::apply_prefix;
+use sp_std::{marker::PhantomData, prelude::*};
+use tendermint_proto::Protobuf;
+
+/// (port_id, channel_id) => Upgrade
+/// trie key path: "channelUpgrades/upgrades/ports/{port_id}/channels/{channel_id}"
+pub struct ChannelUpgrades<T>(PhantomData<T>);
+
+impl<T: Config> ChannelUpgrades<T> {
+	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<Vec<u8>> {
+		let upgrade_path = format!("{}", ChannelUpgradesPath(port_id, channel_id));
+		let upgrade_key = apply_prefix(T::PALLET_PREFIX, vec![upgrade_path]);
+		child::get(&ChildInfo::new_default(T::PALLET_PREFIX), &upgrade_key)
+	}
+
+	pub fn insert(port_id: PortId, channel_id: ChannelId, upgrade: &Upgrade) {
+		let upgrade_path = format!("{}", ChannelUpgradesPath(port_id, channel_id));
+		let upgrade_key = apply_prefix(T::PALLET_PREFIX, vec![upgrade_path]);
+		child::put(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&upgrade_key,
+			&upgrade.encode_vec().expect("encode channel upgrade"),
+		);
+	}
+
+	pub fn remove(port_id: PortId, channel_id: ChannelId) {
+		let upgrade_path = format!("{}", ChannelUpgradesPath(port_id, channel_id));
+		let upgrade_key = apply_prefix(T::PALLET_PREFIX, vec![upgrade_path]);
+		child::kill(&ChildInfo::new_default(T::PALLET_PREFIX), &upgrade_key)
+	}
+}
+
+/// (port_id, channel_id) => Upgrade
+/// trie key path: "channelUpgrades/counterpartyUpgrade/ports/{port_id}/channels/{channel_id}"
+///
+/// The counterparty upgrade is only needed by this chain to check incoming packets while the
+/// channel is flushing, it is not part of the ICS24 provable paths.
+pub struct CounterpartyUpgrades<T>(PhantomData<T>);
+
+impl<T: Config> CounterpartyUpgrades<T> {
+	fn key(port_id: PortId, channel_id: ChannelId) -> Vec<u8> {
+		let upgrade_path = format!(
+			"channelUpgrades/counterpartyUpgrade/ports/{}/channels/{}",
+			port_id, channel_id
+		);
+		apply_prefix(T::PALLET_PREFIX, vec![upgrade_path])
+	}
+
+	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<Vec<u8>> {
+		child::get(&ChildInfo::new_default(T::PALLET_PREFIX), &Self::key(port_id, channel_id))
+	}
+
+	pub fn insert(port_id: PortId, channel_id: ChannelId, upgrade: &Upgrade) {
+		child::put(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&Self::key(port_id, channel_id),
+			&upgrade.encode_vec().expect("encode counterparty upgrade"),
+		);
+	}
+
+	pub fn remove(port_id: PortId, channel_id: ChannelId) {
+		child::kill(&ChildInfo::new_default(T::PALLET_PREFIX), &Self::key(port_id, channel_id))
+	}
+}
+
+/// (port_id, channel_id) => ErrorReceipt
+/// trie key path: "channelUpgrades/upgradeError/ports/{port_id}/channels/{channel_id}"
+pub struct ChannelUpgradeErrors<T>(PhantomData<T>);
+
+impl<T: Config> ChannelUpgradeErrors<T> {
+	pub fn get(port_id: PortId, channel_id: ChannelId) -> Option<Vec<u8>> {
+		let error_path = format!("{}", ChannelUpgradeErrorsPath(port_id, channel_id));
+		let error_key = apply_prefix(T::PALLET_PREFIX, vec![error_path]);
+		child::get(&ChildInfo::new_default(T::PALLET_PREFIX), &error_key)
+	}
+
+	pub fn insert(port_id: PortId, channel_id: ChannelId, error_receipt: &ErrorReceipt) {
+		let error_path = format!("{}", ChannelUpgradeErrorsPath(port_id, channel_id));
+		let error_key = apply_prefix(T::PALLET_PREFIX, vec![error_path]);
+		child::put(
+			&ChildInfo::new_default(T::PALLET_PREFIX),
+			&error_key,
+			&error_receipt.encode_vec().expect("encode upgrade error receipt"),
+		);
+	}
+}
@@ -52,6 +52,14 @@ impl<T: Config> PacketCommitment<T> {
 		child::exists(&ChildInfo::new_default(T::PALLET_PREFIX), &commitment_key)
 	}
 
+	/// Number of packet commitments stored for the given channel.
+	pub fn count(port_id: PortId, channel_id: ChannelId) -> u64 {
+		let prefix = format!("commitments/ports/{}/channels/{}/sequences/", port_id, channel_id);
+		let prefix_key = apply_prefix(T::PALLET_PREFIX, vec![prefix]);
+		ChildTriePrefixIterator::with_prefix(&ChildInfo::new_default(T::PALLET_PREFIX), &prefix_key)
+			.count() as u64
+	}
+
 	// WARNING: too expensive to be called from an on-chain context, only here for rpc layer.
 	pub fn iter() -> impl Iterator<Item = ((PortId, ChannelId, Sequence), Vec<u8>)> {
 		let prefix = "commitments/ports/".to_string();
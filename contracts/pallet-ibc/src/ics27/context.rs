@@ -0,0 +1,185 @@
+use super::super::*;
+use crate::routing::Context;
+use frame_support::{
+	dispatch::{DispatchError, PostDispatchInfo},
+	storage::{with_transaction, TransactionOutcome},
+	traits::{CallMetadata, GetCallMetadata},
+};
+use ibc::{
+	applications::interchain_accounts::{
+		context::{IcaContext, IcaControllerContext, IcaHostContext},
+		error::Error as IcaError,
+	},
+	core::ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+};
+use ibc_primitives::channel_id_from_bytes;
+use ibc_proto::google::protobuf::Any as RawAny;
+use sp_core::crypto::{AccountId32, ByteArray};
+use sp_runtime::traits::{Dispatchable, TrailingZeroInput};
+
+impl<T: Config + Send + Sync> IcaContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId> {
+		IcaActiveChannels::<T>::get(connection_id.as_bytes().to_vec(), port_id.as_bytes().to_vec())
+			.and_then(|channel_id| channel_id_from_bytes(channel_id).ok())
+	}
+
+	fn set_active_channel(
+		&mut self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+	) -> Result<(), IcaError> {
+		IcaActiveChannels::<T>::insert(
+			connection_id.as_bytes().to_vec(),
+			port_id.as_bytes().to_vec(),
+			channel_id.to_string().as_bytes().to_vec(),
+		);
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> IcaHostContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn is_host_enabled(&self) -> bool {
+		IcaParams::<T>::get().host_enabled
+	}
+
+	fn interchain_account_address(
+		&self,
+		connection_id: &ConnectionId,
+		controller_port_id: &PortId,
+	) -> Option<String> {
+		InterchainAccounts::<T>::get(
+			connection_id.as_bytes().to_vec(),
+			controller_port_id.as_bytes().to_vec(),
+		)
+		.map(account_address::<T>)
+	}
+
+	fn register_interchain_account(
+		&mut self,
+		connection_id: &ConnectionId,
+		controller_port_id: &PortId,
+	) -> Result<String, IcaError> {
+		// the account is derived from the connection and port so it can't be controlled by anyone
+		// but the owner of the controller port.
+		let entropy = (b"ibc/ica", connection_id.as_bytes(), controller_port_id.as_bytes())
+			.using_encoded(sp_io::hashing::blake2_256);
+		let account = <T as frame_system::Config>::AccountId::decode(&mut TrailingZeroInput::new(
+			entropy.as_ref(),
+		))
+		.map_err(|e| IcaError::implementation_specific(format!("{:?}", e)))?;
+		InterchainAccounts::<T>::insert(
+			connection_id.as_bytes().to_vec(),
+			controller_port_id.as_bytes().to_vec(),
+			account.clone(),
+		);
+		frame_system::Pallet::<T>::inc_providers(&account);
+		Ok(account_address::<T>(account))
+	}
+
+	fn execute_tx(
+		&mut self,
+		connection_id: &ConnectionId,
+		controller_port_id: &PortId,
+		messages: Vec<RawAny>,
+	) -> Result<Vec<u8>, IcaError> {
+		let account = InterchainAccounts::<T>::get(
+			connection_id.as_bytes().to_vec(),
+			controller_port_id.as_bytes().to_vec(),
+		)
+		.ok_or_else(|| {
+			IcaError::account_not_found(connection_id.clone(), controller_port_id.clone())
+		})?;
+		let calls = messages.iter().map(decode_runtime_call::<T>).collect::<Result<Vec<_>, _>>()?;
+		let allow_messages = IcaParams::<T>::get().allow_messages;
+		if let Some(call) = calls.iter().find(|call| !is_call_allowed(&allow_messages, *call)) {
+			let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+			return Err(IcaError::message_not_allowed(format!("{}.{}", pallet_name, function_name)))
+		}
+
+		// the messages are executed atomically
+		with_transaction(|| {
+			let mut results = Vec::<PostDispatchInfo>::new();
+			for call in calls {
+				let origin = frame_system::RawOrigin::Signed(account.clone()).into();
+				match call.dispatch(origin) {
+					Ok(info) => results.push(info),
+					Err(e) => return TransactionOutcome::Rollback(Err(e.error)),
+				}
+			}
+			TransactionOutcome::Commit(Ok::<_, DispatchError>(results.encode()))
+		})
+		.map_err(|e| IcaError::execute_tx(format!("{:?}", e)))
+	}
+}
+
+impl<T: Config + Send + Sync> IcaControllerContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn is_controller_enabled(&self) -> bool {
+		IcaParams::<T>::get().controller_enabled
+	}
+
+	fn set_interchain_account_address(
+		&mut self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+		address: String,
+	) -> Result<(), IcaError> {
+		IcaControllerAddresses::<T>::insert(
+			connection_id.as_bytes().to_vec(),
+			port_id.as_bytes().to_vec(),
+			address.as_bytes().to_vec(),
+		);
+		Ok(())
+	}
+}
+
+/// Interchain accounts are addressed by the hex encoding of their account id
+pub fn account_address<T: Config>(account: <T as frame_system::Config>::AccountId) -> String
+where
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	let account_id_32: AccountId32 = account.into();
+	let mut hex_string = hex::encode(account_id_32.to_raw_vec());
+	hex_string.insert_str(0, "0x");
+	hex_string
+}
+
+/// Returns true if the call matches one of the `<pallet>.<call>`, `<pallet>.*` or `*` entries of
+/// the host's allow list.
+pub fn is_call_allowed<C: GetCallMetadata>(allow_messages: &[Vec<u8>], call: &C) -> bool {
+	let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+	allow_messages.iter().any(|allowed| match allowed.as_slice() {
+		b"*" => true,
+		allowed => match allowed.strip_suffix(b".*") {
+			Some(pallet) => pallet == pallet_name.as_bytes(),
+			None => allowed == format!("{}.{}", pallet_name, function_name).as_bytes(),
+		},
+	})
+}
+
+/// Decodes a runtime call sent by a controller, calls are scale encoded in messages with the
+/// [`super::RUNTIME_CALL_TYPE_URL`] type url.
+pub fn decode_runtime_call<T: Config>(
+	msg: &RawAny,
+) -> Result<<T as Config>::RuntimeCall, IcaError> {
+	if msg.type_url != super::RUNTIME_CALL_TYPE_URL {
+		return Err(IcaError::implementation_specific(format!(
+			"unsupported message type url {}",
+			msg.type_url
+		)))
+	}
+	<T as Config>::RuntimeCall::decode(&mut msg.value.as_slice())
+		.map_err(|e| IcaError::implementation_specific(format!("{:?}", e)))
+}
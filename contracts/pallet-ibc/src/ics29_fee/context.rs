@@ -0,0 +1,150 @@
+use super::super::*;
+use crate::routing::Context;
+use ibc::{
+	applications::fee::{
+		context::{FeeContext, FeeKeeper, FeeReader},
+		error::Error as FeeError,
+		fee::PacketFee,
+		VERSION,
+	},
+	core::{
+		ics04_channel::packet::Sequence,
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	signer::Signer,
+};
+use sp_core::crypto::AccountId32;
+use tendermint_proto::Protobuf;
+
+fn escrow_key(port_id: &PortId, channel_id: &ChannelId) -> (Vec<u8>, Vec<u8>) {
+	(port_id.as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec())
+}
+
+impl<T: Config + Send + Sync> FeeReader for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	type AccountId = T::AccountIdConversion;
+
+	fn get_fee_escrow_address(&self) -> Result<<Self as FeeReader>::AccountId, FeeError> {
+		let mut data = VERSION.as_bytes().to_vec();
+		data.extend_from_slice(&[0]);
+		data.extend_from_slice(b"fee-escrow");
+		let hash = sp_io::hashing::sha2_256(&data).to_vec();
+		let mut hex_string = hex::encode_upper(hash);
+		hex_string.insert_str(0, "0x");
+		let signer = hex_string.parse::<Signer>().map_err(FeeError::signer)?;
+		signer.try_into().map_err(|_| {
+			log::trace!(target: "pallet_ibc", "Failed to get fee escrow address");
+			FeeError::parse_account_failure()
+		})
+	}
+
+	fn is_fee_enabled(&self, port_id: &PortId, channel_id: &ChannelId) -> bool {
+		let (port_id, channel_id) = escrow_key(port_id, channel_id);
+		FeeEnabledChannels::<T>::contains_key(port_id, channel_id)
+	}
+
+	fn fees_in_escrow(
+		&self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Vec<PacketFee> {
+		FeesInEscrow::<T>::get(escrow_key(port_id, channel_id), u64::from(sequence))
+			.into_iter()
+			.filter_map(|fee| PacketFee::decode_vec(&fee).ok())
+			.collect()
+	}
+
+	fn sequences_with_fees(&self, port_id: &PortId, channel_id: &ChannelId) -> Vec<Sequence> {
+		FeesInEscrow::<T>::iter_key_prefix(escrow_key(port_id, channel_id))
+			.map(Sequence::from)
+			.collect()
+	}
+
+	fn payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer> {
+		Payees::<T>::get(
+			channel_id.to_string().as_bytes().to_vec(),
+			relayer.as_ref().as_bytes().to_vec(),
+		)
+		.and_then(|payee| String::from_utf8(payee).ok())
+		.and_then(|payee| Signer::from_str(&payee).ok())
+	}
+
+	fn counterparty_payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<String> {
+		CounterpartyPayees::<T>::get(
+			channel_id.to_string().as_bytes().to_vec(),
+			relayer.as_ref().as_bytes().to_vec(),
+		)
+		.and_then(|payee| String::from_utf8(payee).ok())
+	}
+}
+
+impl<T: Config + Send + Sync> FeeKeeper for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	type AccountId = T::AccountIdConversion;
+
+	fn set_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId) {
+		let (port_id, channel_id) = escrow_key(port_id, channel_id);
+		FeeEnabledChannels::<T>::insert(port_id, channel_id, ());
+	}
+
+	fn delete_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId) {
+		let (port_id, channel_id) = escrow_key(port_id, channel_id);
+		FeeEnabledChannels::<T>::remove(port_id, channel_id);
+	}
+
+	fn store_fees_in_escrow(
+		&mut self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		fees: Vec<PacketFee>,
+	) {
+		let fees = fees.into_iter().filter_map(|fee| fee.encode_vec().ok()).collect::<Vec<_>>();
+		FeesInEscrow::<T>::insert(escrow_key(port_id, channel_id), u64::from(sequence), fees);
+	}
+
+	fn delete_fees_in_escrow(
+		&mut self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) {
+		FeesInEscrow::<T>::remove(escrow_key(port_id, channel_id), u64::from(sequence));
+	}
+
+	fn store_payee(&mut self, relayer: &Signer, channel_id: &ChannelId, payee: &Signer) {
+		Payees::<T>::insert(
+			channel_id.to_string().as_bytes().to_vec(),
+			relayer.as_ref().as_bytes().to_vec(),
+			payee.as_ref().as_bytes().to_vec(),
+		);
+	}
+
+	fn store_counterparty_payee(
+		&mut self,
+		relayer: &Signer,
+		channel_id: &ChannelId,
+		counterparty_payee: &str,
+	) {
+		CounterpartyPayees::<T>::insert(
+			channel_id.to_string().as_bytes().to_vec(),
+			relayer.as_ref().as_bytes().to_vec(),
+			counterparty_payee.as_bytes().to_vec(),
+		);
+	}
+}
+
+impl<T: Config + Send + Sync> FeeContext for Context<T>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	type AccountId = T::AccountIdConversion;
+}
@@ -0,0 +1,388 @@
+use crate::{ics23::acknowledgements::Acknowledgements, routing::Context, Config, Pallet};
+use alloc::{string::ToString, vec::Vec};
+use core::fmt::Debug;
+use ibc::{
+	applications::fee::{context::FeeKeeper, metadata::Metadata, relay},
+	core::{
+		ics02_client::context::ClientReader,
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			context::ChannelReader,
+			error::Error as Ics04Error,
+			events::WriteAcknowledgement,
+			msgs::acknowledgement::Acknowledgement,
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::{Module, ModuleCallbackContext, ModuleOutputBuilder},
+	},
+	events::IbcEvent,
+	signer::Signer,
+};
+use sp_core::crypto::AccountId32;
+
+pub mod context;
+
+/// ICS-29 fee middleware, escrows the fees paid for the packets sent by the wrapped application
+/// and pays them out to the relayers of the packets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ics29Fee<T: Config, S: Module + Clone + Default + PartialEq + Eq + Debug> {
+	inner: S,
+	_phantom: core::marker::PhantomData<T>,
+}
+
+impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Default
+	for Ics29Fee<T, S>
+{
+	fn default() -> Self {
+		Self { inner: S::default(), _phantom: Default::default() }
+	}
+}
+
+fn fee_error(e: impl ToString) -> Ics04Error {
+	Ics04Error::implementation_specific(e.to_string())
+}
+
+impl<T: Config + Send + Sync, S: Module + Clone + Default + PartialEq + Eq + Debug> Module
+	for Ics29Fee<T, S>
+where
+	u32: From<<T as frame_system::Config>::BlockNumber>,
+	AccountId32: From<<T as frame_system::Config>::AccountId>,
+{
+	fn on_chan_open_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let metadata = Metadata::from_version(version).map_err(fee_error)?;
+		let app_version = metadata.as_ref().map(Metadata::app_version);
+		self.inner.on_chan_open_init(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			app_version.as_ref().unwrap_or(version),
+			relayer,
+		)?;
+		if metadata.is_some() {
+			Context::<T>::default().set_fee_enabled(port_id, channel_id);
+		}
+		Ok(())
+	}
+
+	fn on_chan_open_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty: &Counterparty,
+		version: &Version,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<Version, Ics04Error> {
+		let metadata = match Metadata::from_version(counterparty_version).map_err(fee_error)? {
+			Some(metadata) => metadata,
+			None =>
+				return self.inner.on_chan_open_try(
+					ctx,
+					output,
+					order,
+					connection_hops,
+					port_id,
+					channel_id,
+					counterparty,
+					version,
+					counterparty_version,
+					relayer,
+				),
+		};
+		let version = Metadata::from_version(version)
+			.map_err(fee_error)?
+			.map(|metadata| metadata.app_version())
+			.unwrap_or_else(|| version.clone());
+		let app_version = self.inner.on_chan_open_try(
+			ctx,
+			output,
+			order,
+			connection_hops,
+			port_id,
+			channel_id,
+			counterparty,
+			&version,
+			&metadata.app_version(),
+			relayer,
+		)?;
+		Context::<T>::default().set_fee_enabled(port_id, channel_id);
+		Ok(Metadata::new(&app_version).to_version())
+	}
+
+	fn on_chan_open_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		match Metadata::from_version(counterparty_version).map_err(fee_error)? {
+			Some(metadata) => self.inner.on_chan_open_ack(
+				ctx,
+				output,
+				port_id,
+				channel_id,
+				&metadata.app_version(),
+				relayer,
+			),
+			None => {
+				// The counterparty doesn't support the fee middleware
+				Context::<T>::default().delete_fee_enabled(port_id, channel_id);
+				self.inner.on_chan_open_ack(
+					ctx,
+					output,
+					port_id,
+					channel_id,
+					counterparty_version,
+					relayer,
+				)
+			},
+		}
+	}
+
+	fn on_chan_open_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_open_confirm(ctx, output, port_id, channel_id, relayer)
+	}
+
+	fn on_chan_close_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_close_init(ctx, output, port_id, channel_id, relayer)?;
+		let mut ctx = Context::<T>::default();
+		relay::refund_fees_on_channel_closure(&mut ctx, output, port_id, channel_id)
+			.map_err(fee_error)
+	}
+
+	fn on_chan_close_confirm(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		self.inner.on_chan_close_confirm(ctx, output, port_id, channel_id, relayer)?;
+		let mut ctx = Context::<T>::default();
+		relay::refund_fees_on_channel_closure(&mut ctx, output, port_id, channel_id)
+			.map_err(fee_error)
+	}
+
+	fn on_chan_upgrade_init(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<Version, Ics04Error> {
+		let metadata = Metadata::from_version(version).map_err(fee_error)?;
+		let app_version = metadata.as_ref().map(Metadata::app_version);
+		let app_version = self.inner.on_chan_upgrade_init(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			app_version.as_ref().unwrap_or(version),
+		)?;
+		match metadata {
+			Some(_) => Ok(Metadata::new(&app_version).to_version()),
+			None => Ok(app_version),
+		}
+	}
+
+	fn on_chan_upgrade_try(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		counterparty_version: &Version,
+	) -> Result<Version, Ics04Error> {
+		let metadata = Metadata::from_version(counterparty_version).map_err(fee_error)?;
+		let app_version = metadata.as_ref().map(Metadata::app_version);
+		let app_version = self.inner.on_chan_upgrade_try(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			app_version.as_ref().unwrap_or(counterparty_version),
+		)?;
+		match metadata {
+			Some(_) => Ok(Metadata::new(&app_version).to_version()),
+			None => Ok(app_version),
+		}
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		counterparty_version: &Version,
+	) -> Result<(), Ics04Error> {
+		let app_version = Metadata::from_version(counterparty_version)
+			.map_err(fee_error)?
+			.map(|metadata| metadata.app_version());
+		self.inner.on_chan_upgrade_ack(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			app_version.as_ref().unwrap_or(counterparty_version),
+		)
+	}
+
+	fn on_chan_upgrade_open(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		order: Order,
+		connection_hops: &[ConnectionId],
+		version: &Version,
+	) -> Result<(), Ics04Error> {
+		let metadata = Metadata::from_version(version).map_err(fee_error)?;
+		let app_version = metadata.as_ref().map(Metadata::app_version);
+		self.inner.on_chan_upgrade_open(
+			ctx,
+			output,
+			port_id,
+			channel_id,
+			order,
+			connection_hops,
+			app_version.as_ref().unwrap_or(version),
+		)?;
+		// The fees are enabled or disabled on the channel by the version it was upgraded to
+		let mut fee_ctx = Context::<T>::default();
+		match metadata {
+			Some(_) => fee_ctx.set_fee_enabled(port_id, channel_id),
+			None => fee_ctx.delete_fee_enabled(port_id, channel_id),
+		}
+		Ok(())
+	}
+
+	fn on_recv_packet(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let app_ack = self.inner.on_recv_packet(ctx, output, packet, relayer)?.into_bytes();
+		let ctx = Context::<T>::default();
+		let ack = relay::on_recv_packet(&ctx, packet, app_ack.clone(), relayer);
+		if ack == app_ack {
+			return Ok(ack.into())
+		}
+
+		let key = (packet.destination_port.clone(), packet.destination_channel, packet.sequence);
+		if ctx.get_packet_acknowledgement(&key).is_err() {
+			return Ok(ack.into())
+		}
+
+		// The application has already written its acknowledgement, it's replaced in place by the
+		// incentivized acknowledgement and an empty acknowledgement is returned so that it isn't
+		// written again
+		Acknowledgements::<T>::insert(key.clone(), ctx.ack_commitment(ack.clone().into()));
+		Pallet::<T>::store_raw_acknowledgement(key, ack.clone()).map_err(|_| {
+			Ics04Error::implementation_specific("Error storing acknowledgement".to_string())
+		})?;
+		output.emit(IbcEvent::WriteAcknowledgement(WriteAcknowledgement {
+			height: ctx.host_height(),
+			packet: packet.clone(),
+			ack,
+		}));
+		Ok(Acknowledgement::from_bytes(Vec::new()))
+	}
+
+	fn on_write_acknowledgement(
+		&self,
+		ctx: &dyn ModuleCallbackContext,
+		packet: &Packet,
+		acknowledgement: Acknowledgement,
+		relayer: &Signer,
+	) -> Result<Acknowledgement, Ics04Error> {
+		let acknowledgement =
+			self.inner.on_write_acknowledgement(ctx, packet, acknowledgement, relayer)?;
+		let ctx = Context::<T>::default();
+		Ok(relay::on_recv_packet(&ctx, packet, acknowledgement.into_bytes(), relayer).into())
+	}
+
+	fn on_acknowledgement_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		acknowledgement: &Acknowledgement,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		let app_ack = relay::on_acknowledgement_packet(
+			&mut fee_ctx,
+			output,
+			packet,
+			acknowledgement.as_ref(),
+			relayer,
+		)
+		.map_err(fee_error)?;
+		self.inner
+			.on_acknowledgement_packet(ctx, output, packet, &app_ack.into(), relayer)
+	}
+
+	fn on_timeout_packet(
+		&mut self,
+		ctx: &dyn ModuleCallbackContext,
+		output: &mut ModuleOutputBuilder,
+		packet: &mut Packet,
+		relayer: &Signer,
+	) -> Result<(), Ics04Error> {
+		let mut fee_ctx = Context::<T>::default();
+		relay::on_timeout_packet(&mut fee_ctx, output, packet, relayer).map_err(fee_error)?;
+		self.inner.on_timeout_packet(ctx, output, packet, relayer)
+	}
+}
@@ -8,9 +8,14 @@ use core::time::Duration;
 
 use crate::{
 	ics23::{
-		acknowledgements::Acknowledgements, channels::Channels, client_states::ClientStates,
-		connections::Connections, consensus_states::ConsensusStates,
-		next_seq_recv::NextSequenceRecv, packet_commitments::PacketCommitment,
+		acknowledgements::Acknowledgements,
+		channel_upgrades::{ChannelUpgradeErrors, ChannelUpgrades},
+		channels::Channels,
+		client_states::ClientStates,
+		connections::Connections,
+		consensus_states::ConsensusStates,
+		next_seq_recv::NextSequenceRecv,
+		packet_commitments::PacketCommitment,
 		receipts::PacketReceipt,
 	},
 	light_clients::AnyClientState,
@@ -20,14 +25,23 @@ use crate::{
 	IbcAssets, Pallet, Params, MODULE_ID,
 };
 use codec::{Decode, Encode};
-use frame_support::traits::Currency;
+use frame_support::{ensure, traits::Currency};
 use ibc::{
-	applications::transfer::{
-		msgs::transfer::MsgTransfer, relay::send_transfer::send_transfer, PrefixedCoin,
+	applications::{
+		fee::{msgs::FeeMsg, relay as fee_relay},
+		interchain_accounts::controller::{send_tx, MsgSendTx},
+		transfer::{
+			msgs::transfer::MsgTransfer,
+			packet::PacketDataV2,
+			relay::send_transfer::{send_tokens, send_transfer},
+			PrefixedCoin,
+		},
 	},
 	core::{
 		ics02_client::{
-			client_consensus::ConsensusState, client_state::ClientState, context::ClientReader,
+			client_consensus::ConsensusState,
+			client_state::ClientState,
+			context::{ClientKeeper, ClientReader},
 		},
 		ics03_connection::context::ConnectionReader,
 		ics04_channel::{
@@ -40,8 +54,9 @@ use ibc::{
 		ics24_host::{
 			identifier::*,
 			path::{
-				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
-				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+				AcksPath, ChannelEndsPath, ChannelUpgradeErrorsPath, ChannelUpgradesPath,
+				ClientConsensusStatePath, ClientStatePath, CommitmentsPath, ConnectionsPath,
+				ReceiptsPath, SeqRecvsPath,
 			},
 		},
 		ics26_routing::handler::MsgReceipt,
@@ -56,12 +71,12 @@ use ibc_primitives::{
 	apply_prefix, channel_id_from_bytes, client_id_from_bytes, connection_id_from_bytes,
 	get_channel_escrow_address, port_id_from_bytes, runtime_interface, ConnectionHandshake,
 	Error as IbcHandlerError, HandlerMessage, IbcHandler, IdentifiedChannel, IdentifiedClientState,
-	IdentifiedConnection, PacketInfo, PacketState, QueryChannelResponse, QueryChannelsResponse,
-	QueryClientStateResponse, QueryConnectionResponse, QueryConnectionsResponse,
-	QueryConsensusStateResponse, QueryNextSequenceReceiveResponse,
-	QueryPacketAcknowledgementResponse, QueryPacketAcknowledgementsResponse,
-	QueryPacketCommitmentResponse, QueryPacketCommitmentsResponse, QueryPacketReceiptResponse,
-	Timeout,
+	IdentifiedConnection, PacketInfo, PacketState, QueryChannelResponse,
+	QueryChannelUpgradeResponse, QueryChannelsResponse, QueryClientStateResponse,
+	QueryConnectionResponse, QueryConnectionsResponse, QueryConsensusStateResponse,
+	QueryNextSequenceReceiveResponse, QueryPacketAcknowledgementResponse,
+	QueryPacketAcknowledgementsResponse, QueryPacketCommitmentResponse,
+	QueryPacketCommitmentsResponse, QueryPacketReceiptResponse, QueryUpgradeErrorResponse, Timeout,
 };
 use scale_info::prelude::string::ToString;
 use sp_core::{crypto::AccountId32, offchain::StorageKind};
@@ -108,6 +123,137 @@ where
 			Self::deposit_event(events.into())
 		};
 	}
+
+	/// Executes the ICS-29 fee messages, these are signed by the account that pays the fee or by
+	/// the relayer registering a payee, which must be the sender of the extrinsic.
+	pub(crate) fn execute_fee_messages(
+		ctx: &mut Context<T>,
+		sender: &<T as frame_system::Config>::AccountId,
+		messages: Vec<ibc_proto::google::protobuf::Any>,
+	) -> Result<(), Error<T>> {
+		let mut events = vec![];
+		for msg in messages {
+			let msg = FeeMsg::try_from(msg).map_err(|_| Error::<T>::DecodingError)?;
+			let signer = T::AccountIdConversion::try_from(msg.signer().clone())
+				.map_err(|_| Error::<T>::AccessDenied)?;
+			ensure!(&signer.into_account() == sender, Error::<T>::AccessDenied);
+			match fee_relay::dispatch(ctx, msg) {
+				Ok(output) => events.extend(output.events.into_iter().map(Ok)),
+				Err(e) => log::trace!(target: "pallet_ibc", "fee message execution error: {}", e),
+			}
+		}
+
+		if !events.is_empty() {
+			Self::deposit_event(events.into())
+		};
+		Ok(())
+	}
+
+	/// Replaces the client state and latest consensus state of a frozen or expired client with
+	/// those of an active substitute client, modelled on ibc-go's `MsgRecoverClient`. The client
+	/// parameters that can't change over the lifetime of a client must match. Returns the height
+	/// the subject client was recovered at.
+	pub(crate) fn recover_client_state(
+		subject_client_id: &ClientId,
+		substitute_client_id: &ClientId,
+	) -> Result<Height, Error<T>> {
+		ensure!(subject_client_id != substitute_client_id, Error::<T>::InvalidSubstituteClient);
+		let mut ctx = Context::<T>::default();
+		let subject_client_state = ctx
+			.client_state(subject_client_id)
+			.map_err(|_| Error::<T>::ClientStateNotFound)?;
+		let substitute_client_state = ctx
+			.client_state(substitute_client_id)
+			.map_err(|_| Error::<T>::ClientStateNotFound)?;
+		ensure!(
+			!Self::is_client_active(&ctx, subject_client_id, &subject_client_state),
+			Error::<T>::ClientStillActive
+		);
+		ensure!(
+			Self::is_client_active(&ctx, substitute_client_id, &substitute_client_state),
+			Error::<T>::InvalidSubstituteClient
+		);
+		let height = substitute_client_state.latest_height();
+		ensure!(height > subject_client_state.latest_height(), Error::<T>::InvalidSubstituteClient);
+
+		let client_state = match (subject_client_state, substitute_client_state) {
+			(AnyClientState::Tendermint(subject), AnyClientState::Tendermint(substitute)) => {
+				// the chain id and trusting period may differ, e.g. to recover a client after a
+				// chain restart.
+				ensure!(
+					subject.trust_level == substitute.trust_level &&
+						subject.unbonding_period == substitute.unbonding_period &&
+						subject.max_clock_drift == substitute.max_clock_drift &&
+						subject.proof_specs == substitute.proof_specs &&
+						subject.upgrade_path == substitute.upgrade_path,
+					Error::<T>::InvalidSubstituteClient
+				);
+				AnyClientState::Tendermint(ics07_tendermint::client_state::ClientState {
+					frozen_height: None,
+					..substitute
+				})
+			},
+			(AnyClientState::Grandpa(subject), AnyClientState::Grandpa(substitute)) => {
+				ensure!(
+					subject.relay_chain == substitute.relay_chain &&
+						subject.para_id == substitute.para_id,
+					Error::<T>::InvalidSubstituteClient
+				);
+				AnyClientState::Grandpa(ics10_grandpa::client_state::ClientState {
+					frozen_height: None,
+					..substitute
+				})
+			},
+			#[cfg(test)]
+			(AnyClientState::Mock(_), AnyClientState::Mock(mut substitute)) => {
+				substitute.frozen_height = None;
+				AnyClientState::Mock(substitute)
+			},
+			_ => return Err(Error::<T>::InvalidSubstituteClient),
+		};
+
+		let consensus_state = ctx
+			.consensus_state(substitute_client_id, height)
+			.map_err(|_| Error::<T>::ConsensusStateNotFound)?;
+		let update_time = ctx
+			.client_update_time(substitute_client_id, height)
+			.map_err(|_| Error::<T>::ClientUpdateNotFound)?;
+		let update_height = ctx
+			.client_update_height(substitute_client_id, height)
+			.map_err(|_| Error::<T>::ClientUpdateNotFound)?;
+
+		ctx.store_client_state(subject_client_id.clone(), client_state)
+			.map_err(|_| Error::<T>::ClientRecoveryFailed)?;
+		ctx.store_consensus_state(subject_client_id.clone(), height, consensus_state)
+			.map_err(|_| Error::<T>::ClientRecoveryFailed)?;
+		ctx.store_update_time(subject_client_id.clone(), height, update_time)
+			.map_err(|_| Error::<T>::ClientRecoveryFailed)?;
+		ctx.store_update_height(subject_client_id.clone(), height, update_height)
+			.map_err(|_| Error::<T>::ClientRecoveryFailed)?;
+
+		Ok(height)
+	}
+
+	/// A client is active if it's not frozen and its latest consensus state is within the
+	/// trusting period.
+	fn is_client_active(
+		ctx: &Context<T>,
+		client_id: &ClientId,
+		client_state: &AnyClientState,
+	) -> bool {
+		if client_state.frozen_height().is_some() {
+			return false
+		}
+		let consensus_state = match ctx.consensus_state(client_id, client_state.latest_height()) {
+			Ok(consensus_state) => consensus_state,
+			Err(_) => return false,
+		};
+		let elapsed = ctx
+			.host_timestamp()
+			.duration_since(&consensus_state.timestamp())
+			.unwrap_or_default();
+		!client_state.expired(elapsed)
+	}
 }
 
 impl<T: Config> Pallet<T>
@@ -133,6 +279,38 @@ where
 		Ok(QueryChannelResponse { channel, trie_key: key, height: host_height::<T>() })
 	}
 
+	/// Get the upgrade proposed for a channel
+	pub fn channel_upgrade(
+		channel_id: Vec<u8>,
+		port_id: Vec<u8>,
+	) -> Result<QueryChannelUpgradeResponse, Error<T>> {
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::DecodingError)?;
+		let channel_id =
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::DecodingError)?;
+		let upgrade = ChannelUpgrades::<T>::get(port_id.clone(), channel_id)
+			.ok_or(Error::<T>::ChannelUpgradeNotFound)?;
+		let upgrade_path = format!("{}", ChannelUpgradesPath(port_id, channel_id));
+		let key = apply_prefix(T::PALLET_PREFIX, vec![upgrade_path]);
+
+		Ok(QueryChannelUpgradeResponse { upgrade, trie_key: key, height: host_height::<T>() })
+	}
+
+	/// Get the error receipt of the last aborted upgrade of a channel
+	pub fn channel_upgrade_error(
+		channel_id: Vec<u8>,
+		port_id: Vec<u8>,
+	) -> Result<QueryUpgradeErrorResponse, Error<T>> {
+		let port_id = port_id_from_bytes(port_id).map_err(|_| Error::<T>::DecodingError)?;
+		let channel_id =
+			channel_id_from_bytes(channel_id).map_err(|_| Error::<T>::DecodingError)?;
+		let error_receipt = ChannelUpgradeErrors::<T>::get(port_id.clone(), channel_id)
+			.ok_or(Error::<T>::ChannelUpgradeNotFound)?;
+		let error_path = format!("{}", ChannelUpgradeErrorsPath(port_id, channel_id));
+		let key = apply_prefix(T::PALLET_PREFIX, vec![error_path]);
+
+		Ok(QueryUpgradeErrorResponse { error_receipt, trie_key: key, height: host_height::<T>() })
+	}
+
 	/// Get a connection state
 	pub fn connection(connection_id: Vec<u8>) -> Result<QueryConnectionResponse, Error<T>> {
 		let connection_id =
@@ -1092,6 +1270,79 @@ where
 		Ok(())
 	}
 
+	/// Sends several tokens in a single transfer packet over the transfer port.
+	pub(crate) fn send_tokens(
+		source_channel: ChannelId,
+		data: PacketDataV2,
+		timeout_height: Height,
+		timeout_timestamp: Timestamp,
+	) -> Result<(), IbcHandlerError> {
+		let mut ctx = Context::<T>::default();
+		let mut handler_output = HandlerOutputBuilder::default();
+		send_tokens(
+			&mut ctx,
+			&mut handler_output,
+			PortId::transfer(),
+			source_channel,
+			data,
+			timeout_height,
+			timeout_timestamp,
+		)
+		.map_err(|e| IbcHandlerError::SendTransferError { msg: Some(e.to_string()) })?;
+		let result = handler_output.with_result(());
+		Self::deposit_event(result.events.into());
+		Ok(())
+	}
+
+	pub(crate) fn send_interchain_tx(msg: MsgSendTx) -> Result<Sequence, IbcHandlerError> {
+		let mut ctx = Context::<T>::default();
+		let mut handler_output = HandlerOutputBuilder::default();
+		let sequence = send_tx(&mut ctx, &mut handler_output, msg)
+			.map_err(|e| IbcHandlerError::SendPacketError { msg: Some(e.to_string()) })?;
+		let result = handler_output.with_result(());
+		Self::deposit_event(result.events.into());
+		Ok(sequence)
+	}
+
+	/// Converts the timeout into the timeout height and timestamp of a packet sent over the
+	/// channel, offsets are relative to the latest height and timestamp of the counterparty client.
+	pub(crate) fn timeout_height_and_timestamp(
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		timeout: Timeout,
+	) -> Result<(Height, Timestamp), Error<T>> {
+		let (latest_height, latest_timestamp) =
+			Pallet::<T>::latest_height_and_timestamp(port_id, channel_id)
+				.map_err(|_| Error::<T>::TimestampAndHeightNotFound)?;
+
+		let timeout = match timeout {
+			Timeout::Offset { timestamp, height } => {
+				let timestamp = timestamp
+					.map(|offset| (latest_timestamp + Duration::from_secs(offset)))
+					.transpose()
+					.map_err(|_| Error::<T>::InvalidTimestamp)?
+					.map_err(|_| Error::<T>::InvalidTimestamp)?
+					.unwrap_or_default();
+				let height = height.map(|offset| latest_height.add(offset)).unwrap_or_default();
+				(height, timestamp)
+			},
+			Timeout::Absolute { timestamp, height } => {
+				let timestamp = timestamp
+					.map(Timestamp::from_nanoseconds)
+					.transpose()
+					.map_err(|_| Error::<T>::InvalidTimestamp)?
+					.unwrap_or_default();
+				let height = height
+					.map(|revision_height| {
+						Height::new(latest_height.revision_number, revision_height)
+					})
+					.unwrap_or_default();
+				(height, timestamp)
+			},
+		};
+		Ok(timeout)
+	}
+
 	fn close_channel(port_id: PortId, channel_id: ChannelId) -> Result<(), IbcHandlerError> {
 		let mut ctx = crate::routing::Context::<T>::new();
 		// Signer does not matter in this case
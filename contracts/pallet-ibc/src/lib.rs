@@ -52,6 +52,8 @@ pub mod errors;
 pub mod events;
 pub mod ics20;
 mod ics23;
+pub mod ics27;
+pub mod ics29_fee;
 pub mod light_clients;
 mod port;
 pub mod routing;
@@ -90,6 +92,18 @@ pub struct PalletParams {
 	pub receive_enabled: bool,
 }
 
+/// Params used to enable the interchain accounts host and controller
+#[derive(
+	frame_support::RuntimeDebug, PartialEq, Eq, scale_info::TypeInfo, Encode, Decode, Clone, Default,
+)]
+pub struct InterchainAccountParams {
+	pub host_enabled: bool,
+	pub controller_enabled: bool,
+	/// Calls the host executes on behalf of interchain accounts, as `<pallet>.<call>` names of
+	/// the runtime. `<pallet>.*` allows every call of a pallet and `*` allows every call.
+	pub allow_messages: Vec<Vec<u8>>,
+}
+
 /// Params needed to upgrade clients for all connected chains.
 #[derive(
 	frame_support::RuntimeDebug, PartialEq, Eq, scale_info::TypeInfo, Encode, Decode, Clone,
@@ -144,15 +158,13 @@ pub use weight::WeightInfo;
 pub mod pallet {
 	use super::*;
 
-	use core::time::Duration;
-
 	use frame_support::{
-		dispatch::DispatchResult,
+		dispatch::{DispatchResult, GetDispatchInfo, PostDispatchInfo},
 		pallet_prelude::*,
 		traits::{
 			fungibles::{Inspect, Mutate, Transfer},
 			tokens::{AssetId, Balance},
-			Contains, ReservableCurrency, UnixTime,
+			Contains, GetCallMetadata, ReservableCurrency, UnixTime,
 		},
 	};
 	use frame_system::pallet_prelude::*;
@@ -160,26 +172,43 @@ pub mod pallet {
 	use sp_core::crypto::ByteArray;
 
 	use crate::{
-		ics20::HandleMemo,
+		ics20::{
+			forward::InFlightForward,
+			rate_limit::{FlowDirection, PacketFlow, RateLimit, RateLimitFlow},
+			HandleMemo,
+		},
+		ics27::context::account_address,
 		routing::{Context, ModuleRouter},
 	};
 	use ibc::{
-		applications::transfer::{
-			is_sender_chain_source, msgs::transfer::MsgTransfer, Amount, PrefixedCoin,
-			PrefixedDenom,
+		applications::{
+			fee::msgs::FeeMsg,
+			interchain_accounts::{
+				context::IcaContext,
+				controller::{self, MsgSendTx},
+				controller_port_id,
+				packet::InterchainAccountPacketData,
+			},
+			transfer::{
+				is_sender_chain_source,
+				packet::{Forwarding, PacketDataV2},
+				Amount, PrefixedCoin, PrefixedDenom,
+			},
 		},
 		core::{
 			ics02_client::context::{ClientKeeper, ClientReader},
 			ics04_channel::context::ChannelReader,
 			ics24_host::identifier::{ChannelId, PortId},
 		},
-		timestamp::Timestamp,
 		Height,
 	};
-	use ibc_primitives::{client_id_from_bytes, get_channel_escrow_address, IbcHandler};
+	use ibc_primitives::{
+		channel_id_from_bytes, client_id_from_bytes, connection_id_from_bytes,
+		get_channel_escrow_address, HandlerMessage, IbcHandler,
+	};
 	use light_clients::AnyClientState;
 	use sp_runtime::{
-		traits::{IdentifyAccount, Saturating},
+		traits::{Dispatchable, IdentifyAccount, Saturating},
 		AccountId32, BoundedBTreeSet,
 	};
 	#[cfg(feature = "std")]
@@ -262,6 +291,13 @@ pub mod pallet {
 			+ scale_info::TypeInfo
 			+ Clone
 			+ Eq;
+		/// Runtime calls that can be executed by interchain accounts hosted on this chain
+		type RuntimeCall: Parameter
+			+ Dispatchable<
+				RuntimeOrigin = <Self as frame_system::Config>::RuntimeOrigin,
+				PostInfo = PostDispatchInfo,
+			> + GetDispatchInfo
+			+ GetCallMetadata;
 	}
 
 	#[pallet::pallet]
@@ -354,6 +390,148 @@ pub mod pallet {
 	pub type EscrowAddresses<T: Config> =
 		StorageValue<_, BTreeSet<<T as frame_system::Config>::AccountId>, ValueQuery>;
 
+	#[pallet::storage]
+	#[allow(clippy::disallowed_types)]
+	/// Params used to enable the interchain accounts host and controller
+	pub type IcaParams<T: Config> = StorageValue<_, InterchainAccountParams, ValueQuery>;
+
+	#[pallet::storage]
+	/// (connection_id, port_id) => channel_id
+	/// Active interchain account channels, the port is the controller port on both chains
+	pub type IcaActiveChannels<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// (connection_id, controller_port_id) => AccountId
+	/// Interchain accounts hosted on this chain
+	pub type InterchainAccounts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		<T as frame_system::Config>::AccountId,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// (connection_id, controller_port_id) => address
+	/// Addresses of the interchain accounts registered by this chain on counterparty hosts
+	pub type IcaControllerAddresses<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// (port_id, channel_id) => ()
+	/// Channels the fee middleware was negotiated on
+	pub type FeeEnabledChannels<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, Vec<u8>, (), OptionQuery>;
+
+	#[pallet::storage]
+	/// ((port_id, channel_id), sequence) => Vec<PacketFee>
+	/// Fees escrowed for packets, each fee is stored protobuf encoded
+	pub type FeesInEscrow<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(Vec<u8>, Vec<u8>),
+		Blake2_128Concat,
+		u64,
+		Vec<Vec<u8>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	/// (channel_id, relayer) => payee
+	/// Addresses relayers registered to be paid their acknowledgement and timeout fees to
+	pub type Payees<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// (channel_id, relayer) => counterparty_payee
+	/// Addresses on the counterparty chain relayers registered to be paid their receive fees to
+	pub type CounterpartyPayees<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		Vec<u8>,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// (channel_id, asset_id) => RateLimit
+	/// Rate limits of ICS-20 transfers over channels of this chain
+	pub type RateLimits<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Twox64Concat,
+		T::AssetId,
+		RateLimit<T::Balance>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// (channel_id, asset_id) => RateLimitFlow
+	/// Amounts transferred in the current period of the rate limits
+	pub type RateLimitFlows<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Twox64Concat,
+		T::AssetId,
+		RateLimitFlow<T::Balance>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// (channel_id, (sequence, direction)) => [PacketFlow]
+	/// Transfers of pending packets recorded in the flows of the rate limits
+	pub type PacketFlows<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Twox64Concat,
+		(u64, FlowDirection),
+		Vec<PacketFlow<T::AssetId, T::Balance>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	/// ((port_id, channel_id), sequence) => InFlightForward
+	/// Received transfers forwarded over the channel whose acknowledgement is held until the
+	/// forwarded packet is acknowledged
+	pub type InFlightForwards<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		(Vec<u8>, Vec<u8>),
+		Blake2_128Concat,
+		u64,
+		InFlightForward,
+		OptionQuery,
+	>;
+
 	#[pallet::storage]
 	#[allow(clippy::disallowed_types)]
 	/// Consensus heights
@@ -458,8 +636,33 @@ pub mod pallet {
 		ClientUpgradeSet,
 		/// Client has been frozen
 		ClientFrozen { client_id: Vec<u8>, height: u64, revision_number: u64 },
+		/// Client has been recovered with the state of a substitute client
+		ClientRecovered {
+			subject_client_id: Vec<u8>,
+			substitute_client_id: Vec<u8>,
+			height: u64,
+			revision_number: u64,
+		},
 		/// Asset Admin Account Updated
 		AssetAdminUpdated { admin_account: <T as frame_system::Config>::AccountId },
+		/// Interchain accounts params updated
+		IcaParamsUpdated { host_enabled: bool, controller_enabled: bool },
+		/// A transaction has been sent to an interchain account
+		InterchainTxSent { connection_id: Vec<u8>, port_id: Vec<u8>, sequence: u64 },
+		/// Rate limit of transfers of an asset over a channel has been set or removed
+		RateLimitUpdated {
+			channel_id: Vec<u8>,
+			asset_id: T::AssetId,
+			rate_limit: Option<RateLimit<T::Balance>>,
+		},
+		/// A received or forwarded transfer was rejected because it exceeds the rate limit of the
+		/// channel, transfers sent by extrinsics fail with `Error::RateLimitExceeded` instead
+		RateLimitExceeded {
+			channel_id: Vec<u8>,
+			asset_id: T::AssetId,
+			amount: T::Balance,
+			is_inflow: bool,
+		},
 	}
 
 	/// Errors inform users that something went wrong.
@@ -527,8 +730,22 @@ pub mod pallet {
 		ClientUpdateNotFound,
 		/// Error Freezing client
 		ClientFreezeFailed,
+		/// Client is neither frozen nor expired and can't be recovered
+		ClientStillActive,
+		/// Substitute client is not active or doesn't track the same chain as the subject client
+		InvalidSubstituteClient,
+		/// Error recovering client
+		ClientRecoveryFailed,
 		/// Access denied
 		AccessDenied,
+		/// The channel of the interchain account could not be opened
+		RegisterInterchainAccountFailed,
+		/// The transaction could not be sent to the interchain account
+		InterchainTxFailed,
+		/// The transfer exceeds the rate limit of the channel
+		RateLimitExceeded,
+		/// No upgrade or upgrade error receipt stored for the channel
+		ChannelUpgradeNotFound,
 	}
 
 	#[pallet::hooks]
@@ -538,6 +755,20 @@ pub mod pallet {
 		T: Send + Sync,
 		AccountId32: From<<T as frame_system::Config>::AccountId>,
 	{
+		fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+			let weight = Pallet::<T>::roll_over_rate_limit_flows();
+			// the localhost client and its connection are created once, every later block only
+			// pays for reading the client state
+			let mut ctx = routing::Context::<T>::new();
+			if ctx.client_state(&ibc::core::ics09_localhost::client_id()).is_ok() {
+				return weight.saturating_add(T::DbWeight::get().reads(1))
+			}
+			if let Err(e) = ibc::core::ics09_localhost::initialize(&mut ctx) {
+				log::error!(target: "pallet_ibc", "Failed to initialize the localhost client: {:?}", e);
+			}
+			weight.saturating_add(T::DbWeight::get().reads_writes(2, 7))
+		}
+
 		fn offchain_worker(_n: BlockNumberFor<T>) {
 			let _ = Pallet::<T>::packet_cleanup();
 		}
@@ -566,10 +797,20 @@ pub mod pallet {
 			// so people don't spam our chain with useless clients.
 			let mut ctx = routing::Context::<T>::new();
 			let mut reserve_count = 0u128;
+			let mut fee_messages = vec![];
 			let messages = messages
 				.into_iter()
 				.filter_map(|message| {
 					let type_url = String::from_utf8(message.type_url.clone()).ok()?;
+					// Fee messages are not ibc core messages, they're handled by the fee
+					// middleware
+					if FeeMsg::is_fee_msg(&type_url) {
+						fee_messages.push(ibc_proto::google::protobuf::Any {
+							type_url,
+							value: message.value,
+						});
+						return None
+					}
 					if matches!(
 						type_url.as_str(),
 						create_client::TYPE_URL | conn_open_init::TYPE_URL
@@ -588,6 +829,7 @@ pub mod pallet {
 				>>::reserve(&sender, reserve_amt.into())?;
 			}
 			Self::execute_ibc_messages(&mut ctx, messages);
+			Self::execute_fee_messages(&mut ctx, &sender, fee_messages)?;
 
 			Ok(())
 		}
@@ -603,128 +845,7 @@ pub mod pallet {
 			memo: Option<T::MemoMessage>,
 		) -> DispatchResult {
 			let origin = ensure_signed(origin)?;
-			// Ensure that the signer is whitelisted
-			ensure!(T::Whitelist::contains(&origin), Error::<T>::AccessDenied);
-			let denom = T::IbcDenomToAssetIdConversion::from_asset_id_to_denom(asset_id)
-				.ok_or_else(|| Error::<T>::InvalidAssetId)?;
-
-			let account_id_32: AccountId32 = origin.into();
-			let from = {
-				let mut hex_string = hex::encode(account_id_32.to_raw_vec());
-				hex_string.insert_str(0, "0x");
-				hex_string
-			};
-
-			let to = match params.to {
-				MultiAddress::Id(id) => {
-					// we convert id to hex string instead of ss58 because destination chain could
-					// have a different ss58 prefix from source chain
-					let account_id_32: AccountId32 = id.into();
-					let mut hex_string = hex::encode(account_id_32.to_raw_vec());
-					hex_string.insert_str(0, "0x");
-					hex_string
-				},
-				MultiAddress::Raw(bytes) =>
-					String::from_utf8(bytes).map_err(|_| Error::<T>::Utf8Error)?,
-			};
-			let denom = PrefixedDenom::from_str(&denom).map_err(|_| Error::<T>::InvalidIbcDenom)?;
-			let ibc_amount = Amount::from_str(&format!("{:?}", amount))
-				.map_err(|_| Error::<T>::InvalidAmount)?;
-			let coin = PrefixedCoin { denom, amount: ibc_amount };
-			let source_channel = ChannelId::new(params.source_channel);
-			let source_port = PortId::transfer();
-			let (latest_height, latest_timestamp) =
-				Pallet::<T>::latest_height_and_timestamp(&source_port, &source_channel)
-					.map_err(|_| Error::<T>::TimestampAndHeightNotFound)?;
-
-			let (timeout_height, timeout_timestamp) = match params.timeout {
-				Timeout::Offset { timestamp, height } => {
-					let timestamp = timestamp
-						.map(|offset| (latest_timestamp + Duration::from_secs(offset)))
-						.transpose()
-						.map_err(|_| Error::<T>::InvalidTimestamp)?
-						.unwrap_or_default();
-					let height = height.map(|offset| latest_height.add(offset)).unwrap_or_default();
-					(height, timestamp)
-				},
-				Timeout::Absolute { timestamp, height } => {
-					let timestamp = timestamp
-						.map(Timestamp::from_nanoseconds)
-						.transpose()
-						.map_err(|_| Error::<T>::InvalidTimestamp)?
-						.unwrap_or_default();
-					let height = height
-						.map(|revision_height| {
-							Height::new(latest_height.revision_number, revision_height)
-						})
-						.unwrap_or_default();
-					(height, timestamp)
-				},
-			};
-
-			let msg = MsgTransfer {
-				source_port,
-				source_channel: source_channel.clone(),
-				token: coin.clone(),
-				sender: Signer::from_str(&from).map_err(|_| Error::<T>::Utf8Error)?,
-				receiver: Signer::from_str(&to).map_err(|_| Error::<T>::Utf8Error)?,
-				timeout_height,
-				timeout_timestamp,
-				memo: memo.map(|memo| memo.to_string()).unwrap_or_default(),
-			};
-			let is_sender_source = is_sender_chain_source(
-				msg.source_port.clone(),
-				msg.source_channel,
-				&msg.token.denom,
-			);
-
-			if is_sender_source {
-				// Store escrow address
-				let escrow_address =
-					get_channel_escrow_address(&msg.source_port, msg.source_channel)
-						.map_err(|_| Error::<T>::ChannelEscrowAddress)?;
-				let account_id = T::AccountIdConversion::try_from(escrow_address)
-					.map_err(|_| Error::<T>::ChannelEscrowAddress)?
-					.into_account();
-				let _ = EscrowAddresses::<T>::try_mutate::<_, &'static str, _>(|addresses| {
-					if !addresses.contains(&account_id) {
-						addresses.insert(account_id);
-						Ok(())
-					} else {
-						Err("Address already exists")
-					}
-				});
-			}
-
-			Pallet::<T>::send_transfer(msg).map_err(|e| {
-				log::trace!(target: "pallet_ibc", "[transfer]: error: {:?}", e);
-				Error::<T>::TransferFailed
-			})?;
-			let ctx = Context::<T>::default();
-			let channel_end = ctx
-				.channel_end(&(PortId::transfer(), source_channel))
-				.map_err(|_| Error::<T>::ChannelNotFound)?;
-
-			Self::deposit_event(Event::<T>::TokenTransferInitiated {
-				from: from.as_bytes().to_vec(),
-				to: to.as_bytes().to_vec(),
-				amount,
-				local_asset_id: T::IbcDenomToAssetIdConversion::from_denom_to_asset_id(
-					&coin.denom.to_string(),
-				)
-				.ok(),
-				ibc_denom: coin.denom.to_string().as_bytes().to_vec(),
-				is_sender_source,
-				source_channel: source_channel.to_string().as_bytes().to_vec(),
-				destination_channel: channel_end
-					.counterparty()
-					.channel_id
-					.ok_or_else(|| Error::<T>::ChannelNotFound)?
-					.to_string()
-					.as_bytes()
-					.to_vec(),
-			});
-			Ok(())
+			Self::do_transfer(origin, params, vec![(asset_id, amount)], memo)
 		}
 
 		#[pallet::call_index(2)]
@@ -796,6 +917,19 @@ pub mod pallet {
 							.map_err(|_| Error::<T>::ClientFreezeFailed)?,
 					)
 				},
+				AnyClientState::Near(near) => {
+					let latest_height = near.latest_height();
+					AnyClientState::wrap(
+						&near
+							.with_frozen_height(Height::new(latest_height.revision_number, height))
+							.map_err(|_| Error::<T>::ClientFreezeFailed)?,
+					)
+				},
+				// solo machines are frozen at their current sequence
+				AnyClientState::Solomachine(solomachine) =>
+					AnyClientState::wrap(&solomachine.frozen()),
+				// the localhost client only reads the host's own state, there is nothing to freeze
+				AnyClientState::Localhost(_) => return Err(Error::<T>::ClientFreezeFailed.into()),
 				#[cfg(test)]
 				AnyClientState::Mock(mut ms) => {
 					ms.frozen_height =
@@ -816,6 +950,322 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Register an interchain account on the host chain of the connection, the account is
+		/// owned by the caller and controlled through the `icacontroller-{owner}` port.
+		#[pallet::call_index(5)]
+		#[pallet::weight(<T as Config>::WeightInfo::on_chan_open_init())]
+		pub fn register_interchain_account(
+			origin: OriginFor<T>,
+			connection_id: Vec<u8>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let owner = Signer::from_str(&account_address::<T>(owner))
+				.map_err(|_| Error::<T>::Utf8Error)?;
+			let connection_id =
+				connection_id_from_bytes(connection_id).map_err(|_| Error::<T>::DecodingError)?;
+			let ctx = Context::<T>::default();
+			let (port_id, channel_end) = controller::register_account(&ctx, &owner, &connection_id)
+				.map_err(|e| {
+					log::trace!(target: "pallet_ibc", "[register_interchain_account]: error: {:?}", e);
+					Error::<T>::RegisterInterchainAccountFailed
+				})?;
+			Pallet::<T>::handle_message(HandlerMessage::OpenChannel { port_id, channel_end })
+				.map_err(|e| {
+					log::trace!(target: "pallet_ibc", "[register_interchain_account]: error: {:?}", e);
+					Error::<T>::ChannelInitError
+				})?;
+			Ok(())
+		}
+
+		/// Send messages to be executed by the interchain account of the caller on the host chain
+		/// of the connection.
+		#[pallet::call_index(6)]
+		#[frame_support::transactional]
+		#[pallet::weight(<T as Config>::WeightInfo::transfer())]
+		pub fn send_interchain_tx(
+			origin: OriginFor<T>,
+			connection_id: Vec<u8>,
+			messages: Vec<Any>,
+			memo: Vec<u8>,
+			timeout: Timeout,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let owner = Signer::from_str(&account_address::<T>(owner))
+				.map_err(|_| Error::<T>::Utf8Error)?;
+			let connection_id =
+				connection_id_from_bytes(connection_id).map_err(|_| Error::<T>::DecodingError)?;
+			let port_id =
+				controller_port_id(owner.as_ref()).map_err(|_| Error::<T>::InvalidPortId)?;
+			let ctx = Context::<T>::default();
+			let channel_id = ctx
+				.active_channel(&connection_id, &port_id)
+				.ok_or_else(|| Error::<T>::ChannelNotFound)?;
+			let (timeout_height, timeout_timestamp) =
+				Pallet::<T>::timeout_height_and_timestamp(&port_id, &channel_id, timeout)?;
+
+			let messages = messages
+				.into_iter()
+				.map(|message| {
+					let type_url =
+						String::from_utf8(message.type_url).map_err(|_| Error::<T>::Utf8Error)?;
+					Ok(ibc_proto::google::protobuf::Any { type_url, value: message.value })
+				})
+				.collect::<Result<Vec<_>, Error<T>>>()?;
+			let memo = String::from_utf8(memo).map_err(|_| Error::<T>::Utf8Error)?;
+			let msg = MsgSendTx {
+				owner,
+				connection_id: connection_id.clone(),
+				packet_data: InterchainAccountPacketData::new(messages, memo),
+				timeout_height,
+				timeout_timestamp,
+			};
+			let sequence = Pallet::<T>::send_interchain_tx(msg).map_err(|e| {
+				log::trace!(target: "pallet_ibc", "[send_interchain_tx]: error: {:?}", e);
+				Error::<T>::InterchainTxFailed
+			})?;
+
+			Self::deposit_event(Event::<T>::InterchainTxSent {
+				connection_id: connection_id.as_bytes().to_vec(),
+				port_id: port_id.as_bytes().to_vec(),
+				sequence: sequence.into(),
+			});
+			Ok(())
+		}
+
+		#[pallet::call_index(7)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_params())]
+		pub fn set_ica_params(
+			origin: OriginFor<T>,
+			params: InterchainAccountParams,
+		) -> DispatchResult {
+			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			Self::deposit_event(Event::<T>::IcaParamsUpdated {
+				host_enabled: params.host_enabled,
+				controller_enabled: params.controller_enabled,
+			});
+			<IcaParams<T>>::put(params);
+			Ok(())
+		}
+
+		/// Recover a frozen or expired client by replacing its client state and latest consensus
+		/// state with those of an active substitute client of the same chain.
+		#[pallet::call_index(8)]
+		#[pallet::weight(<T as Config>::WeightInfo::recover_client())]
+		pub fn recover_client(
+			origin: OriginFor<T>,
+			subject_client_id: Vec<u8>,
+			substitute_client_id: Vec<u8>,
+		) -> DispatchResult {
+			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			let subject_client_id =
+				client_id_from_bytes(subject_client_id).map_err(|_| Error::<T>::DecodingError)?;
+			let substitute_client_id = client_id_from_bytes(substitute_client_id)
+				.map_err(|_| Error::<T>::DecodingError)?;
+			let height = Self::recover_client_state(&subject_client_id, &substitute_client_id)?;
+
+			Self::deposit_event(Event::<T>::ClientRecovered {
+				subject_client_id: subject_client_id.as_bytes().to_vec(),
+				substitute_client_id: substitute_client_id.as_bytes().to_vec(),
+				height: height.revision_height,
+				revision_number: height.revision_number,
+			});
+
+			Ok(())
+		}
+
+		/// Set the rate limit of ICS-20 transfers of an asset over a channel, or remove it if
+		/// `rate_limit` is `None`. The flows of the current period are reset.
+		#[pallet::call_index(9)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_rate_limit())]
+		pub fn set_rate_limit(
+			origin: OriginFor<T>,
+			channel_id: Vec<u8>,
+			asset_id: T::AssetId,
+			rate_limit: Option<RateLimit<T::Balance>>,
+		) -> DispatchResult {
+			<T as Config>::AdminOrigin::ensure_origin(origin)?;
+			let channel_id = channel_id_from_bytes(channel_id)
+				.map_err(|_| Error::<T>::InvalidChannelId)?
+				.to_string()
+				.as_bytes()
+				.to_vec();
+			match rate_limit {
+				Some(rate_limit) => {
+					ensure!(rate_limit.period > 0, Error::<T>::InvalidParams);
+					RateLimits::<T>::insert(&channel_id, asset_id, rate_limit);
+					// the first period starts now, `Percent` quotas are measured against the
+					// issuance of the asset at this point
+					let now = T::TimeProvider::now().as_secs();
+					RateLimitFlows::<T>::insert(
+						&channel_id,
+						asset_id,
+						Self::new_period(asset_id, now),
+					);
+				},
+				None => {
+					RateLimits::<T>::remove(&channel_id, asset_id);
+					RateLimitFlows::<T>::remove(&channel_id, asset_id);
+				},
+			}
+			Self::deposit_event(Event::<T>::RateLimitUpdated { channel_id, asset_id, rate_limit });
+			Ok(())
+		}
+
+		/// Transfer several assets to the same receiver in a single `ics20-2` packet. Channels
+		/// that negotiated `ics20-1` only accept a single asset.
+		#[pallet::call_index(10)]
+		#[frame_support::transactional]
+		#[pallet::weight(
+			<T as Config>::WeightInfo::transfer().saturating_mul(assets.len() as u64)
+		)]
+		pub fn transfer_multi(
+			origin: OriginFor<T>,
+			params: TransferParams<<T as frame_system::Config>::AccountId>,
+			assets: Vec<(T::AssetId, T::Balance)>,
+			memo: Option<T::MemoMessage>,
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			Self::do_transfer(origin, params, assets, memo)
+		}
+	}
+
+	impl<T: Config> Pallet<T>
+	where
+		T: Send + Sync,
+		AccountId32: From<<T as frame_system::Config>::AccountId>,
+		u32: From<<T as frame_system::Config>::BlockNumber>,
+	{
+		/// Sends `assets` from `origin` to the receiver of `params` in a single transfer packet,
+		/// which is encoded according to the version of the source channel.
+		fn do_transfer(
+			origin: <T as frame_system::Config>::AccountId,
+			params: TransferParams<<T as frame_system::Config>::AccountId>,
+			assets: Vec<(T::AssetId, T::Balance)>,
+			memo: Option<T::MemoMessage>,
+		) -> DispatchResult {
+			// Ensure that the signer is whitelisted
+			ensure!(T::Whitelist::contains(&origin), Error::<T>::AccessDenied);
+			ensure!(!assets.is_empty(), Error::<T>::InvalidParams);
+
+			let account_id_32: AccountId32 = origin.into();
+			let from = {
+				let mut hex_string = hex::encode(account_id_32.to_raw_vec());
+				hex_string.insert_str(0, "0x");
+				hex_string
+			};
+
+			let to = match params.to {
+				MultiAddress::Id(id) => {
+					// we convert id to hex string instead of ss58 because destination chain could
+					// have a different ss58 prefix from source chain
+					let account_id_32: AccountId32 = id.into();
+					let mut hex_string = hex::encode(account_id_32.to_raw_vec());
+					hex_string.insert_str(0, "0x");
+					hex_string
+				},
+				MultiAddress::Raw(bytes) =>
+					String::from_utf8(bytes).map_err(|_| Error::<T>::Utf8Error)?,
+			};
+			let source_channel = ChannelId::new(params.source_channel);
+			let source_port = PortId::transfer();
+			let (timeout_height, timeout_timestamp) = Pallet::<T>::timeout_height_and_timestamp(
+				&source_port,
+				&source_channel,
+				params.timeout,
+			)?;
+
+			let mut tokens = Vec::with_capacity(assets.len());
+			for (asset_id, amount) in assets.iter() {
+				let denom = T::IbcDenomToAssetIdConversion::from_asset_id_to_denom(*asset_id)
+					.ok_or_else(|| Error::<T>::InvalidAssetId)?;
+				let denom =
+					PrefixedDenom::from_str(&denom).map_err(|_| Error::<T>::InvalidIbcDenom)?;
+				let amount = Amount::from_str(&format!("{:?}", amount))
+					.map_err(|_| Error::<T>::InvalidAmount)?;
+				tokens.push(PrefixedCoin { denom, amount });
+			}
+
+			if tokens.iter().any(|token| {
+				is_sender_chain_source(source_port.clone(), source_channel, &token.denom)
+			}) {
+				// Store escrow address
+				let escrow_address = get_channel_escrow_address(&source_port, source_channel)
+					.map_err(|_| Error::<T>::ChannelEscrowAddress)?;
+				let account_id = T::AccountIdConversion::try_from(escrow_address)
+					.map_err(|_| Error::<T>::ChannelEscrowAddress)?
+					.into_account();
+				let _ = EscrowAddresses::<T>::try_mutate::<_, &'static str, _>(|addresses| {
+					if !addresses.contains(&account_id) {
+						addresses.insert(account_id);
+						Ok(())
+					} else {
+						Err("Address already exists")
+					}
+				});
+			}
+
+			let ctx = Context::<T>::default();
+			let sequence = ctx
+				.get_next_sequence_send(&(PortId::transfer(), source_channel))
+				.map_err(|_| Error::<T>::ChannelNotFound)?;
+			let mut flows = Vec::with_capacity(assets.len());
+			for (asset_id, amount) in assets.iter() {
+				flows.extend(Self::record_transfer_flow(
+					&source_channel,
+					*asset_id,
+					*amount,
+					FlowDirection::Outflow,
+				)?);
+			}
+
+			let data = PacketDataV2 {
+				tokens: tokens.clone(),
+				sender: Signer::from_str(&from).map_err(|_| Error::<T>::Utf8Error)?,
+				receiver: Signer::from_str(&to).map_err(|_| Error::<T>::Utf8Error)?,
+				memo: memo.map(|memo| memo.to_string()).unwrap_or_default(),
+				forwarding: Forwarding::default(),
+			};
+			Pallet::<T>::send_tokens(source_channel, data, timeout_height, timeout_timestamp)
+				.map_err(|e| {
+					log::trace!(target: "pallet_ibc", "[transfer]: error: {:?}", e);
+					Error::<T>::TransferFailed
+				})?;
+			Self::track_packet_flows(
+				&source_channel,
+				sequence.into(),
+				FlowDirection::Outflow,
+				flows,
+			);
+			let channel_end = ctx
+				.channel_end(&(PortId::transfer(), source_channel))
+				.map_err(|_| Error::<T>::ChannelNotFound)?;
+			let destination_channel = channel_end
+				.counterparty()
+				.channel_id
+				.ok_or_else(|| Error::<T>::ChannelNotFound)?
+				.to_string()
+				.as_bytes()
+				.to_vec();
+
+			for (token, (asset_id, amount)) in tokens.into_iter().zip(assets) {
+				Self::deposit_event(Event::<T>::TokenTransferInitiated {
+					from: from.as_bytes().to_vec(),
+					to: to.as_bytes().to_vec(),
+					amount,
+					local_asset_id: Some(asset_id),
+					ibc_denom: token.denom.to_string().as_bytes().to_vec(),
+					is_sender_source: is_sender_chain_source(
+						source_port.clone(),
+						source_channel,
+						&token.denom,
+					),
+					source_channel: source_channel.to_string().as_bytes().to_vec(),
+					destination_channel: destination_channel.clone(),
+				});
+			}
+			Ok(())
+		}
 	}
 }
 
@@ -1,4 +1,5 @@
 use alloc::{borrow::ToOwned, format, string::ToString, vec::Vec};
+use codec::Encode;
 use frame_support::{
 	pallet_prelude::{StorageValue, ValueQuery},
 	traits::StorageInstance,
@@ -6,10 +7,20 @@ use frame_support::{
 use ibc::core::{
 	ics02_client,
 	ics02_client::{client_consensus::ConsensusState, client_state::ClientState},
+	ics09_localhost::{
+		client_message::LOCALHOST_CLIENT_MESSAGE_TYPE_URL,
+		client_state::LOCALHOST_CLIENT_STATE_TYPE_URL,
+		consensus_state::LOCALHOST_CONSENSUS_STATE_TYPE_URL,
+	},
 };
 use ibc_derive::{ClientDef, ClientMessage, ClientState, ConsensusState, Protobuf};
 use ibc_primitives::runtime_interface;
 use ibc_proto::google::protobuf::Any;
+use ics06_solomachine::{
+	client_message::SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL,
+	client_state::SOLOMACHINE_CLIENT_STATE_TYPE_URL,
+	consensus_state::SOLOMACHINE_CONSENSUS_STATE_TYPE_URL,
+};
 use ics10_grandpa::{
 	client_message::{RelayChainHeader, GRANDPA_CLIENT_MESSAGE_TYPE_URL},
 	client_state::GRANDPA_CLIENT_STATE_TYPE_URL,
@@ -19,6 +30,10 @@ use ics11_beefy::{
 	client_message::BEEFY_CLIENT_MESSAGE_TYPE_URL, client_state::BEEFY_CLIENT_STATE_TYPE_URL,
 	consensus_state::BEEFY_CONSENSUS_STATE_TYPE_URL,
 };
+use ics13_near::{
+	client_state::NEAR_CLIENT_STATE_TYPE_URL, consensus_state::NEAR_CONSENSUS_STATE_TYPE_URL,
+	header::NEAR_CLIENT_MESSAGE_TYPE_URL,
+};
 use sp_core::{crypto::ByteArray, ed25519, H256};
 use sp_runtime::{
 	app_crypto::RuntimePublic,
@@ -192,11 +207,103 @@ impl beefy_client_primitives::HostFunctions for HostFunctionsManager {
 	}
 }
 
+impl ics13_near::client_def::HostFunctions for HostFunctionsManager {
+	fn keccak_256(input: &[u8]) -> [u8; 32] {
+		sp_io::hashing::keccak_256(input)
+	}
+
+	fn secp256k1_ecdsa_recover_compressed(signature: &[u8; 65], msg: &[u8; 32]) -> Option<Vec<u8>> {
+		sp_io::crypto::secp256k1_ecdsa_recover_compressed(signature, msg)
+			.ok()
+			.map(|pub_key| pub_key.to_vec())
+	}
+
+	fn ed25519_verify(signature: &[u8; 64], msg: &[u8], pubkey: &[u8]) -> bool {
+		match ed25519::Public::from_slice(pubkey) {
+			Ok(pubkey) => sp_io::crypto::ed25519_verify(
+				&ed25519::Signature::from_raw(*signature),
+				msg,
+				&pubkey,
+			),
+			Err(_) => false,
+		}
+	}
+
+	fn verify_timestamp_extrinsic(
+		root: &[u8; 32],
+		proof: &[Vec<u8>],
+		value: &[u8],
+	) -> Result<(), ics02_client::error::Error> {
+		let key = codec::Compact(0u32).encode();
+		sp_io::trie::blake2_256_verify_proof(
+			H256::from_slice(root),
+			proof,
+			&key,
+			value,
+			sp_core::storage::StateVersion::V0,
+		)
+		.then(|| ())
+		.ok_or_else(|| {
+			ics02_client::error::Error::implementation_specific(
+				"timestamp verification failed".to_string(),
+			)
+		})
+	}
+
+	fn sha256_digest(data: &[u8]) -> [u8; 32] {
+		sp_io::hashing::sha2_256(data)
+	}
+
+	fn sha2_256(message: &[u8]) -> [u8; 32] {
+		sp_io::hashing::sha2_256(message)
+	}
+
+	fn sha2_512(message: &[u8]) -> [u8; 64] {
+		runtime_interface::sha2_512(message)
+	}
+
+	fn sha2_512_truncated(message: &[u8]) -> [u8; 32] {
+		runtime_interface::sha2_512_truncated(message)
+	}
+
+	fn sha3_512(message: &[u8]) -> [u8; 64] {
+		runtime_interface::sha3_512(message)
+	}
+
+	fn ripemd160(message: &[u8]) -> [u8; 20] {
+		runtime_interface::ripemd160(message)
+	}
+}
+
+impl ics13_near::client_def::HostFunctionsTrait for HostFunctionsManager {}
+
+impl ics06_solomachine::HostFunctionsProvider for HostFunctionsManager {
+	fn ed25519_verify(signature: &[u8; 64], msg: &[u8], pubkey: &[u8]) -> bool {
+		<Self as ics13_near::client_def::HostFunctions>::ed25519_verify(signature, msg, pubkey)
+	}
+
+	fn secp256k1_verify(signature: &[u8; 64], msg: &[u8], pubkey: &[u8]) -> bool {
+		let hash = sp_io::hashing::sha2_256(msg);
+		let mut recoverable = [0u8; 65];
+		recoverable[..64].copy_from_slice(signature);
+		// the solo machine only provides the compact signature, try both recovery ids
+		(0..2).any(|recovery_id| {
+			recoverable[64] = recovery_id;
+			sp_io::crypto::secp256k1_ecdsa_recover_compressed(&recoverable, &hash)
+				.map(|recovered| recovered.as_slice() == pubkey)
+				.unwrap_or(false)
+		})
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, ClientDef)]
 pub enum AnyClient {
 	Grandpa(ics10_grandpa::client_def::GrandpaClient<HostFunctionsManager>),
 	Beefy(ics11_beefy::client_def::BeefyClient<HostFunctionsManager>),
 	Tendermint(ics07_tendermint::client_def::TendermintClient<HostFunctionsManager>),
+	Near(ics13_near::client_def::NearClient<HostFunctionsManager>),
+	Solomachine(ics06_solomachine::client_def::SolomachineClient<HostFunctionsManager>),
+	Localhost(ibc::core::ics09_localhost::client_def::LocalhostClient),
 	#[cfg(test)]
 	Mock(ibc::mock::client_def::MockClient),
 }
@@ -206,6 +313,9 @@ pub enum AnyUpgradeOptions {
 	Grandpa(ics10_grandpa::client_state::UpgradeOptions),
 	Beefy(ics11_beefy::client_state::UpgradeOptions),
 	Tendermint(ics07_tendermint::client_state::UpgradeOptions),
+	Near(ics13_near::client_state::NearUpgradeOptions),
+	Solomachine(()),
+	Localhost(()),
 	#[cfg(test)]
 	Mock(()),
 }
@@ -218,6 +328,12 @@ pub enum AnyClientState {
 	Beefy(ics11_beefy::client_state::ClientState<HostFunctionsManager>),
 	#[ibc(proto_url = "TENDERMINT_CLIENT_STATE_TYPE_URL")]
 	Tendermint(ics07_tendermint::client_state::ClientState<HostFunctionsManager>),
+	#[ibc(proto_url = "NEAR_CLIENT_STATE_TYPE_URL")]
+	Near(ics13_near::client_state::NearClientState<HostFunctionsManager>),
+	#[ibc(proto_url = "SOLOMACHINE_CLIENT_STATE_TYPE_URL")]
+	Solomachine(ics06_solomachine::client_state::ClientState<HostFunctionsManager>),
+	#[ibc(proto_url = "LOCALHOST_CLIENT_STATE_TYPE_URL")]
+	Localhost(ibc::core::ics09_localhost::client_state::ClientState),
 	#[cfg(test)]
 	#[ibc(proto_url = "MOCK_CLIENT_STATE_TYPE_URL")]
 	Mock(ibc::mock::client_state::MockClientState),
@@ -231,6 +347,12 @@ pub enum AnyConsensusState {
 	Beefy(ics11_beefy::consensus_state::ConsensusState),
 	#[ibc(proto_url = "TENDERMINT_CONSENSUS_STATE_TYPE_URL")]
 	Tendermint(ics07_tendermint::consensus_state::ConsensusState),
+	#[ibc(proto_url = "NEAR_CONSENSUS_STATE_TYPE_URL")]
+	Near(ics13_near::consensus_state::ConsensusState),
+	#[ibc(proto_url = "SOLOMACHINE_CONSENSUS_STATE_TYPE_URL")]
+	Solomachine(ics06_solomachine::consensus_state::ConsensusState),
+	#[ibc(proto_url = "LOCALHOST_CONSENSUS_STATE_TYPE_URL")]
+	Localhost(ibc::core::ics09_localhost::consensus_state::ConsensusState),
 	#[cfg(test)]
 	#[ibc(proto_url = "MOCK_CONSENSUS_STATE_TYPE_URL")]
 	Mock(ibc::mock::client_state::MockConsensusState),
@@ -245,6 +367,12 @@ pub enum AnyClientMessage {
 	Beefy(ics11_beefy::client_message::ClientMessage),
 	#[ibc(proto_url = "TENDERMINT_CLIENT_MESSAGE_TYPE_URL")]
 	Tendermint(ics07_tendermint::client_message::ClientMessage),
+	#[ibc(proto_url = "NEAR_CLIENT_MESSAGE_TYPE_URL")]
+	Near(ics13_near::header::NearClientMessage),
+	#[ibc(proto_url = "SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL")]
+	Solomachine(ics06_solomachine::client_message::ClientMessage),
+	#[ibc(proto_url = "LOCALHOST_CLIENT_MESSAGE_TYPE_URL")]
+	Localhost(ibc::core::ics09_localhost::client_message::ClientMessage),
 	#[cfg(test)]
 	#[ibc(proto_url = "MOCK_CLIENT_MESSAGE_TYPE_URL")]
 	Mock(ibc::mock::header::MockClientMessage),
@@ -269,6 +397,16 @@ impl TryFrom<Any> for AnyClientMessage {
 				ics07_tendermint::client_message::ClientMessage::decode_vec(&value.value)
 					.map_err(ics02_client::error::Error::decode_raw_header)?,
 			)),
+			NEAR_CLIENT_MESSAGE_TYPE_URL => Ok(Self::Near(
+				ics13_near::header::NearClientMessage::decode_vec(&value.value)
+					.map_err(ics02_client::error::Error::decode_raw_header)?,
+			)),
+			SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL => Ok(Self::Solomachine(
+				ics06_solomachine::client_message::ClientMessage::decode_vec(&value.value)
+					.map_err(ics02_client::error::Error::decode_raw_header)?,
+			)),
+			LOCALHOST_CLIENT_MESSAGE_TYPE_URL =>
+				Ok(Self::Localhost(ibc::core::ics09_localhost::client_message::ClientMessage)),
 			_ => Err(ics02_client::error::Error::unknown_consensus_state_type(value.type_url)),
 		}
 	}
@@ -289,6 +427,15 @@ impl From<AnyClientMessage> for Any {
 				type_url: TENDERMINT_CLIENT_MESSAGE_TYPE_URL.to_string(),
 				value: msg.encode_vec().expect("Tendermint client message is always serializable"),
 			},
+			AnyClientMessage::Near(msg) => Any {
+				type_url: NEAR_CLIENT_MESSAGE_TYPE_URL.to_string(),
+				value: msg.encode_vec().expect("Near client message is always serializable"),
+			},
+			AnyClientMessage::Solomachine(msg) => Any {
+				type_url: SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL.to_string(),
+				value: msg.encode_vec().expect("Solomachine client message is always serializable"),
+			},
+			AnyClientMessage::Localhost(msg) => msg.into(),
 			#[cfg(test)]
 			AnyClientMessage::Mock(_msg) => panic!("MockHeader can't be serialized"),
 		}
@@ -193,6 +193,7 @@ impl Config for Test {
 	type Whitelist = AllowAll;
 	type HandleMemo = ();
 	type MemoMessage = MemoMessage;
+	type RuntimeCall = RuntimeCall;
 }
 
 parameter_types! {
@@ -291,7 +292,13 @@ pub struct Router {
 	ibc_ping: pallet_ibc_ping::IbcModule<Test>,
 	ics20: crate::ics20::memo::Memo<
 		Test,
-		crate::ics20_fee::Ics20ServiceCharge<Test, crate::ics20::IbcModule<Test>>,
+		crate::ics29_fee::Ics29Fee<
+			Test,
+			crate::ics20_fee::Ics20ServiceCharge<
+				Test,
+				crate::ics20::forward::PacketForward<Test, crate::ics20::IbcModule<Test>>,
+			>,
+		>,
 	>,
 }
 
@@ -2,8 +2,12 @@ use super::*;
 
 use crate::routing::{Context, ModuleRouter};
 use ibc::{
-	applications::transfer::{
-		MODULE_ID_STR as TRANSFER_MODULE_ID, PORT_ID_STR as TRANSFER_PORT_ID,
+	applications::{
+		interchain_accounts::{
+			is_controller_port, CONTROLLER_MODULE_ID_STR as ICA_CONTROLLER_MODULE_ID,
+			HOST_MODULE_ID_STR as ICA_HOST_MODULE_ID, HOST_PORT_ID_STR as ICA_HOST_PORT_ID,
+		},
+		transfer::{MODULE_ID_STR as TRANSFER_MODULE_ID, PORT_ID_STR as TRANSFER_PORT_ID},
 	},
 	core::{
 		ics05_port::{context::PortReader, error::Error as ICS05Error},
@@ -22,6 +26,10 @@ impl<T: Config + Sync + Send> PortReader for Context<T> {
 		match port_id.as_str() {
 			TRANSFER_PORT_ID => Ok(ModuleId::from_str(TRANSFER_MODULE_ID)
 				.map_err(|_| ICS05Error::module_not_found(port_id.clone()))?),
+			ICA_HOST_PORT_ID => Ok(ModuleId::from_str(ICA_HOST_MODULE_ID)
+				.map_err(|_| ICS05Error::module_not_found(port_id.clone()))?),
+			_ if is_controller_port(port_id) => Ok(ModuleId::from_str(ICA_CONTROLLER_MODULE_ID)
+				.map_err(|_| ICS05Error::module_not_found(port_id.clone()))?),
 			_ => Err(ICS05Error::module_not_found(port_id.clone())),
 		}
 	}
@@ -1,7 +1,13 @@
 use super::*;
 use core::fmt::Debug;
 use ibc::{
-	applications::transfer::MODULE_ID_STR as IBC_TRANSFER_MODULE_ID,
+	applications::{
+		interchain_accounts::{
+			CONTROLLER_MODULE_ID_STR as ICA_CONTROLLER_MODULE_ID,
+			HOST_MODULE_ID_STR as ICA_HOST_MODULE_ID,
+		},
+		transfer::MODULE_ID_STR as IBC_TRANSFER_MODULE_ID,
+	},
 	core::{
 		ics24_host::identifier::PortId,
 		ics26_routing::context::{
@@ -30,15 +36,24 @@ impl<T: Config + Send + Sync> Context<T> {
 	}
 }
 
+/// Routes packets to the modules of the pallet, transfers go through the fee and forward
+/// middlewares unless the runtime router defines its own transfer route.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct IbcRouter<T: Config> {
-	ibc_transfer: ics20::IbcModule<T>,
+	ibc_transfer: ics29_fee::Ics29Fee<T, ics20::forward::PacketForward<T, ics20::IbcModule<T>>>,
+	ica_host: ics27::IcaHostModule<T>,
+	ica_controller: ics27::IcaControllerModule<T>,
 	sub_router: T::Router,
 }
 
-impl<T: Config> Default for IbcRouter<T> {
+impl<T: Config + Send + Sync> Default for IbcRouter<T> {
 	fn default() -> Self {
-		Self { ibc_transfer: ics20::IbcModule::<T>::default(), sub_router: Default::default() }
+		Self {
+			ibc_transfer: Default::default(),
+			ica_host: ics27::IcaHostModule::<T>::default(),
+			ica_controller: ics27::IcaControllerModule::<T>::default(),
+			sub_router: Default::default(),
+		}
 	}
 }
 
@@ -65,6 +80,8 @@ where
 
 		match module_id.as_ref() {
 			IBC_TRANSFER_MODULE_ID => Some(&mut self.ibc_transfer),
+			ICA_HOST_MODULE_ID => Some(&mut self.ica_host),
+			ICA_CONTROLLER_MODULE_ID => Some(&mut self.ica_controller),
 			&_ => None,
 		}
 	}
@@ -75,7 +92,10 @@ where
 			return true
 		}
 
-		matches!(module_id.to_string().as_str(), IBC_TRANSFER_MODULE_ID,)
+		matches!(
+			module_id.to_string().as_str(),
+			IBC_TRANSFER_MODULE_ID | ICA_HOST_MODULE_ID | ICA_CONTROLLER_MODULE_ID
+		)
 	}
 }
 
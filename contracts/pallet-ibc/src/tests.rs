@@ -8,7 +8,7 @@ use crate::{
 };
 use core::time::Duration;
 use frame_support::{
-	assert_ok,
+	assert_noop, assert_ok,
 	traits::{
 		fungibles::{Inspect, Mutate},
 		Len,
@@ -644,3 +644,1382 @@ fn test_next_and_previous_consensus_state_for_other_client_types() {
 		assert!(ctx.next_consensus_state(&client_id, Height::new(0, 400)).unwrap().is_some());
 	})
 }
+
+#[test]
+fn should_register_payee_signed_by_the_relayer() {
+	use ibc::applications::fee::{
+		context::{FeeKeeper, FeeReader},
+		msgs::register_payee::{MsgRegisterPayee, TYPE_URL as REGISTER_PAYEE_TYPE_URL},
+	};
+
+	new_test_ext().execute_with(|| {
+		let relayer = AccountId32::new([1; 32]);
+		let relayer_signer = Signer::from_str(&format!("0x{}", hex::encode([1u8; 32]))).unwrap();
+		let payee = Signer::from_str(&format!("0x{}", hex::encode([3u8; 32]))).unwrap();
+		let channel_id = ChannelId::new(0);
+		let mut ctx = Context::<Test>::default();
+		ctx.set_fee_enabled(&PortId::transfer(), &channel_id);
+
+		let msg = MsgRegisterPayee {
+			port_id: PortId::transfer(),
+			channel_id,
+			relayer: relayer_signer.clone(),
+			payee: payee.clone(),
+		};
+		let msg = Any {
+			type_url: REGISTER_PAYEE_TYPE_URL.as_bytes().to_vec(),
+			value: msg.encode_vec().unwrap(),
+		};
+
+		// Only the relayer can register its payee
+		assert_noop!(
+			Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([2; 32])), vec![msg.clone()]),
+			crate::Error::<Test>::AccessDenied
+		);
+		assert_eq!(ctx.payee(&relayer_signer, &channel_id), None);
+
+		assert_ok!(Ibc::deliver(RuntimeOrigin::signed(relayer), vec![msg]));
+		assert_eq!(ctx.payee(&relayer_signer, &channel_id), Some(payee));
+	})
+}
+
+#[test]
+fn should_unwrap_the_fee_version_on_channel_upgrades() {
+	use crate::{ics20::IbcModule, ics29_fee::Ics29Fee};
+	use ibc::{
+		applications::fee::{context::FeeReader, metadata::Metadata},
+		core::ics26_routing::context::{Module, ModuleOutputBuilder},
+	};
+
+	new_test_ext().execute_with(|| {
+		let port_id = PortId::transfer();
+		let channel_id = ChannelId::new(0);
+		let connection_hops = vec![ConnectionId::new(0)];
+		let ctx = Context::<Test>::default();
+		let mut output = ModuleOutputBuilder::new();
+		let mut module = Ics29Fee::<Test, IbcModule<Test>>::default();
+		let app_version = ChanVersion::new(VERSION.to_string());
+		let fee_version = Metadata::new(&app_version).to_version();
+
+		// The transfer module only sees its own version, which is wrapped again in the metadata
+		let version = module
+			.on_chan_upgrade_init(
+				&ctx,
+				&mut output,
+				&port_id,
+				&channel_id,
+				Order::Unordered,
+				&connection_hops,
+				&fee_version,
+			)
+			.unwrap();
+		assert_eq!(version, fee_version);
+		let version = module
+			.on_chan_upgrade_try(
+				&ctx,
+				&mut output,
+				&port_id,
+				&channel_id,
+				Order::Unordered,
+				&connection_hops,
+				&fee_version,
+			)
+			.unwrap();
+		assert_eq!(version, fee_version);
+		assert_ok!(module.on_chan_upgrade_ack(
+			&ctx,
+			&mut output,
+			&port_id,
+			&channel_id,
+			&fee_version
+		));
+		assert!(!ctx.is_fee_enabled(&port_id, &channel_id));
+		assert_ok!(module.on_chan_upgrade_open(
+			&ctx,
+			&mut output,
+			&port_id,
+			&channel_id,
+			Order::Unordered,
+			&connection_hops,
+			&fee_version,
+		));
+		assert!(ctx.is_fee_enabled(&port_id, &channel_id));
+
+		// Upgrading to the version of the transfer module disables the fees
+		let version = module
+			.on_chan_upgrade_init(
+				&ctx,
+				&mut output,
+				&port_id,
+				&channel_id,
+				Order::Unordered,
+				&connection_hops,
+				&app_version,
+			)
+			.unwrap();
+		assert_eq!(version, app_version);
+		assert_ok!(module.on_chan_upgrade_open(
+			&ctx,
+			&mut output,
+			&port_id,
+			&channel_id,
+			Order::Unordered,
+			&connection_hops,
+			&app_version,
+		));
+		assert!(!ctx.is_fee_enabled(&port_id, &channel_id));
+	})
+}
+
+#[test]
+fn should_upgrade_a_transfer_channel_to_a_fee_enabled_channel() {
+	use ibc::{
+		applications::fee::{context::FeeReader, metadata::Metadata},
+		core::ics04_channel::{
+			context::ChannelReader,
+			msgs::{
+				chan_upgrade_ack::MsgChannelUpgradeAck,
+				chan_upgrade_confirm::MsgChannelUpgradeConfirm,
+				chan_upgrade_init::MsgChannelUpgradeInit, chan_upgrade_open::MsgChannelUpgradeOpen,
+				chan_upgrade_try::MsgChannelUpgradeTry,
+			},
+			upgrade::UpgradeFields,
+		},
+	};
+
+	new_test_ext().execute_with(|| {
+		Timestamp::set_timestamp(3600 * 1000);
+		setup_client_and_consensus_state(PortId::transfer());
+
+		// Both ends of the channel are on this chain, their proofs are accepted by the mock client
+		let port_id = PortId::transfer();
+		let connection_id = ConnectionId::new(0);
+		let (channel_a, channel_b) = (ChannelId::new(0), ChannelId::new(1));
+		let mut ctx = Context::<Test>::default();
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Unordered,
+			ChanCounterParty::new(port_id.clone(), Some(channel_a)),
+			vec![connection_id.clone()],
+			ChanVersion::new(VERSION.to_string()),
+		);
+		ctx.store_channel((port_id.clone(), channel_b), &channel_end).unwrap();
+		ctx.store_next_sequence_send((port_id.clone(), channel_b), 1.into()).unwrap();
+		ctx.store_next_sequence_recv((port_id.clone(), channel_b), 1.into()).unwrap();
+
+		let fee_version = Metadata::new(&ChanVersion::new(VERSION.to_string())).to_version();
+		let fields =
+			UpgradeFields::new(Order::Unordered, vec![connection_id.clone()], fee_version.clone());
+		let signer = Signer::from_str(MODULE_ID).unwrap();
+		let proofs = || {
+			Proofs::new(
+				vec![1u8; 32].try_into().unwrap(),
+				None,
+				None,
+				Some(vec![1u8; 32].try_into().unwrap()),
+				Height::new(0, 1),
+			)
+			.unwrap()
+		};
+		let deliver = |msg: ibc_proto::google::protobuf::Any| {
+			let msg = Any { type_url: msg.type_url.as_bytes().to_vec(), value: msg.value };
+			assert_ok!(Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]));
+		};
+		let channel = |channel_id| ctx.channel_end(&(port_id.clone(), channel_id)).unwrap();
+		let upgrade = |channel_id| ctx.channel_upgrade(&(port_id.clone(), channel_id)).unwrap();
+
+		deliver(
+			MsgChannelUpgradeInit {
+				port_id: port_id.clone(),
+				channel_id: channel_a,
+				fields: fields.clone(),
+				signer: signer.clone(),
+			}
+			.to_any(),
+		);
+		assert_eq!(channel(channel_a).upgrade_sequence(), 1);
+		assert_eq!(upgrade(channel_a).fields.version, fee_version);
+
+		deliver(
+			MsgChannelUpgradeTry {
+				port_id: port_id.clone(),
+				channel_id: channel_b,
+				proposed_upgrade_connection_hops: vec![connection_id],
+				counterparty_upgrade_fields: fields,
+				counterparty_upgrade_sequence: 1,
+				proofs: proofs(),
+				signer: signer.clone(),
+			}
+			.to_any(),
+		);
+		assert_eq!(channel(channel_b).state, State::Flushing);
+		assert_eq!(upgrade(channel_b).fields.version, fee_version);
+
+		deliver(
+			MsgChannelUpgradeAck {
+				port_id: port_id.clone(),
+				channel_id: channel_a,
+				counterparty_upgrade: upgrade(channel_b),
+				proofs: proofs(),
+				signer: signer.clone(),
+			}
+			.to_any(),
+		);
+		// There are no packets in flight, so the channel is done flushing right away
+		assert_eq!(channel(channel_a).state, State::FlushComplete);
+
+		deliver(
+			MsgChannelUpgradeConfirm {
+				port_id: port_id.clone(),
+				channel_id: channel_b,
+				counterparty_channel_state: State::FlushComplete,
+				counterparty_upgrade: upgrade(channel_a),
+				proofs: proofs(),
+				signer: signer.clone(),
+			}
+			.to_any(),
+		);
+		assert_eq!(channel(channel_b).state, State::Open);
+		assert_eq!(channel(channel_b).version(), &fee_version);
+		assert!(ctx.is_fee_enabled(&port_id, &channel_b));
+		assert!(!ctx.is_fee_enabled(&port_id, &channel_a));
+
+		deliver(
+			MsgChannelUpgradeOpen {
+				port_id: port_id.clone(),
+				channel_id: channel_a,
+				counterparty_channel_state: State::Open,
+				counterparty_upgrade_sequence: 1,
+				proofs: proofs(),
+				signer,
+			}
+			.to_any(),
+		);
+		assert_eq!(channel(channel_a).state, State::Open);
+		assert_eq!(channel(channel_a).version(), &fee_version);
+		assert!(ctx.is_fee_enabled(&port_id, &channel_a));
+		assert!(ctx.channel_upgrade(&(port_id.clone(), channel_a)).is_err());
+		assert!(ctx.channel_upgrade(&(port_id.clone(), channel_b)).is_err());
+	})
+}
+
+#[test]
+fn should_close_ordered_channels_on_timeout() {
+	use ibc::core::ics04_channel::{context::ChannelReader, msgs::timeout::MsgTimeout};
+
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let raw_user = ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let ss58_address = String::from_utf8(raw_user).unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+		let port_id = PortId::transfer();
+		let channel_id = ChannelId::new(0);
+		let sender = AccountId32::new([0; 32]);
+		let amount = 1000 * MILLIS;
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				&"PICA".to_string(),
+			)
+			.unwrap();
+		let channel_escrow_address = get_channel_escrow_address(&port_id, channel_id).unwrap();
+		let channel_escrow_address =
+			<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+				.map_err(|_| ())
+				.unwrap()
+				.into_account();
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &channel_escrow_address, 2 * amount)
+		.unwrap();
+
+		let mut ctx = Context::<Test>::default();
+		ctx.store_channel(
+			(port_id.clone(), channel_id),
+			&ChannelEnd::new(
+				State::Open,
+				Order::Ordered,
+				ChanCounterParty::new(port_id.clone(), Some(ChannelId::new(1))),
+				vec![ConnectionId::new(0)],
+				ChanVersion::new(VERSION.to_string()),
+			),
+		)
+		.unwrap();
+
+		// Packets that time out at the latest height of the counterparty's client
+		let packet = |sequence: u64| {
+			let packet = Packet {
+				sequence: sequence.into(),
+				source_port: port_id.clone(),
+				source_channel: channel_id,
+				destination_port: port_id.clone(),
+				destination_channel: ChannelId::new(1),
+				data: serde_json::to_vec(&PacketData {
+					token: Coin {
+						denom: PrefixedDenom::from_str("PICA").unwrap(),
+						amount: ibc::applications::transfer::Amount::from_str(&format!(
+							"{:?}",
+							amount
+						))
+						.unwrap(),
+					},
+					sender: Signer::from_str(&format!("0x{}", hex::encode([0; 32]))).unwrap(),
+					receiver: Signer::from_str(&ss58_address).unwrap(),
+					memo: "".to_string(),
+				})
+				.unwrap(),
+				timeout_height: Height::new(0, 1),
+				timeout_timestamp: Default::default(),
+			};
+			let commitment = ctx.packet_commitment(
+				packet.data.clone(),
+				packet.timeout_height,
+				packet.timeout_timestamp,
+			);
+			Context::<Test>::default()
+				.store_packet_commitment((port_id.clone(), channel_id, packet.sequence), commitment)
+				.unwrap();
+			packet
+		};
+		let timeout = |packet: Packet| {
+			let msg = MsgTimeout {
+				packet,
+				next_sequence_recv: 1.into(),
+				proofs: Proofs::new(
+					vec![0u8; 32].try_into().unwrap(),
+					None,
+					None,
+					None,
+					Height::new(0, 1),
+				)
+				.unwrap(),
+				signer: Signer::from_str(MODULE_ID).unwrap(),
+			};
+			let msg = Any {
+				type_url: msg.type_url().as_bytes().to_vec(),
+				value: msg.encode_vec().unwrap(),
+			};
+			assert_ok!(Ibc::deliver(RuntimeOrigin::signed(sender.clone()), vec![msg]));
+		};
+		let (first, second) = (packet(1), packet(2));
+
+		// The timeout of the first packet refunds it and closes the channel
+		timeout(first);
+		assert_eq!(ctx.channel_end(&(port_id.clone(), channel_id)).unwrap().state, State::Closed);
+		assert!(ctx.get_packet_commitment(&(port_id.clone(), channel_id, 1.into())).is_err());
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(asset_id, &sender), amount);
+
+		// Later packets can't time out on the closed channel
+		timeout(second);
+		assert!(ctx.get_packet_commitment(&(port_id.clone(), channel_id, 2.into())).is_ok());
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(asset_id, &sender), amount);
+	})
+}
+
+#[test]
+fn should_recover_frozen_client_with_substitute() {
+	new_test_ext().execute_with(|| {
+		let create_client = |height: u64| {
+			let client_state = MockClientState::new(MockClientMessage::from(MockHeader::new(
+				Height::new(0, height),
+			)));
+			let consensus_state = MockConsensusState::new(MockHeader::new(Height::new(0, height)));
+			let msg = MsgCreateAnyClient::<Context<Test>>::new(
+				AnyClientState::Mock(client_state),
+				AnyConsensusState::Mock(consensus_state),
+				Signer::from_str(MODULE_ID).unwrap(),
+			)
+			.unwrap()
+			.encode_vec()
+			.unwrap();
+			let msg = Any { type_url: TYPE_URL.to_string().as_bytes().to_vec(), value: msg };
+			assert_ok!(Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]));
+		};
+		create_client(1);
+		create_client(5);
+		let subject_client_id = ClientId::new("9999-mock", 0).unwrap();
+		let substitute_client_id = ClientId::new("9999-mock", 1).unwrap();
+		let ctx = Context::<Test>::default();
+
+		// An active client can't be recovered
+		assert_noop!(
+			Ibc::recover_client(
+				RuntimeOrigin::root(),
+				subject_client_id.as_bytes().to_vec(),
+				substitute_client_id.as_bytes().to_vec()
+			),
+			crate::Error::<Test>::ClientStillActive
+		);
+
+		assert_ok!(Ibc::freeze_client(
+			RuntimeOrigin::root(),
+			subject_client_id.as_bytes().to_vec(),
+			1
+		));
+		assert!(ctx.client_state(&subject_client_id).unwrap().frozen_height().is_some());
+
+		// Only the frozen client can be recovered
+		assert_noop!(
+			Ibc::recover_client(
+				RuntimeOrigin::root(),
+				substitute_client_id.as_bytes().to_vec(),
+				subject_client_id.as_bytes().to_vec()
+			),
+			crate::Error::<Test>::ClientStillActive
+		);
+
+		assert_ok!(Ibc::recover_client(
+			RuntimeOrigin::root(),
+			subject_client_id.as_bytes().to_vec(),
+			substitute_client_id.as_bytes().to_vec()
+		));
+
+		let client_state = ctx.client_state(&subject_client_id).unwrap();
+		assert_eq!(client_state.latest_height(), Height::new(0, 5));
+		assert_eq!(client_state.frozen_height(), None);
+		assert!(ctx.consensus_state(&subject_client_id, Height::new(0, 5)).is_ok());
+	})
+}
+
+#[test]
+fn should_reject_transfers_exceeding_the_rate_limit() {
+	use crate::ics20::rate_limit::{Quota, RateLimit};
+
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let raw_user = ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let ss58_address = String::from_utf8(raw_user).unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+		let balance = 100000 * MILLIS;
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				&"PICA".to_string(),
+			)
+			.unwrap();
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &AccountId32::new([0; 32]), balance).unwrap();
+
+		Ibc::set_params(
+			RuntimeOrigin::root(),
+			PalletParams { send_enabled: true, receive_enabled: true },
+		)
+		.unwrap();
+		assert_ok!(Ibc::set_rate_limit(
+			RuntimeOrigin::root(),
+			ChannelId::new(0).to_string().as_bytes().to_vec(),
+			asset_id,
+			Some(RateLimit {
+				max_inflow: None,
+				max_outflow: Some(Quota::Absolute(balance / 2)),
+				period: 3600,
+			}),
+		));
+
+		let transfer = |amount| {
+			Ibc::transfer(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				TransferParams {
+					to: MultiAddress::Raw(ss58_address.as_bytes().to_vec()),
+					source_channel: 0,
+					timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+				},
+				asset_id,
+				amount,
+				None,
+			)
+		};
+
+		assert_noop!(transfer(balance), crate::Error::<Test>::RateLimitExceeded);
+		assert_ok!(transfer(balance / 2));
+		assert_noop!(transfer(1), crate::Error::<Test>::RateLimitExceeded);
+	})
+}
+
+#[test]
+fn should_refund_the_outflow_of_failed_transfers_in_the_same_period() {
+	use crate::ics20::{
+		rate_limit::{Quota, RateLimit},
+		IbcModule,
+	};
+	use ibc::{
+		applications::transfer::acknowledgement::Acknowledgement as Ics20Acknowledgement,
+		core::{
+			ics04_channel::msgs::acknowledgement::Acknowledgement,
+			ics26_routing::context::{Module, ModuleOutputBuilder},
+		},
+	};
+
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let raw_user = ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let ss58_address = String::from_utf8(raw_user).unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+		let balance = 100000 * MILLIS;
+		let sender = AccountId32::new([0; 32]);
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				&"PICA".to_string(),
+			)
+			.unwrap();
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &sender, balance).unwrap();
+
+		Ibc::set_params(
+			RuntimeOrigin::root(),
+			PalletParams { send_enabled: true, receive_enabled: true },
+		)
+		.unwrap();
+		assert_ok!(Ibc::set_rate_limit(
+			RuntimeOrigin::root(),
+			ChannelId::new(0).to_string().as_bytes().to_vec(),
+			asset_id,
+			Some(RateLimit {
+				max_inflow: None,
+				max_outflow: Some(Quota::Absolute(balance / 2)),
+				period: 3600,
+			}),
+		));
+
+		let transfer = |amount| {
+			Ibc::transfer(
+				RuntimeOrigin::signed(sender.clone()),
+				TransferParams {
+					to: MultiAddress::Raw(ss58_address.as_bytes().to_vec()),
+					source_channel: 0,
+					timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+				},
+				asset_id,
+				amount,
+				None,
+			)
+		};
+		let sent_packet = |sequence: u64| Packet {
+			sequence: sequence.into(),
+			source_port: PortId::transfer(),
+			source_channel: ChannelId::new(0),
+			destination_port: PortId::transfer(),
+			destination_channel: ChannelId::new(1),
+			data: serde_json::to_vec(&PacketData {
+				token: Coin {
+					denom: PrefixedDenom::from_str("PICA").unwrap(),
+					amount: ibc::applications::transfer::Amount::from_str(&format!(
+						"{:?}",
+						balance / 2
+					))
+					.unwrap(),
+				},
+				sender: Signer::from_str(&format!("0x{}", hex::encode([0; 32]))).unwrap(),
+				receiver: Signer::from_str(&ss58_address).unwrap(),
+				memo: "".to_string(),
+			})
+			.unwrap(),
+			timeout_height: Default::default(),
+			timeout_timestamp: Default::default(),
+		};
+		let error_ack = Acknowledgement::from_bytes(
+			Ics20Acknowledgement::Error("failed".to_string()).to_string().into_bytes(),
+		);
+		let ctx = Context::<Test>::default();
+		let relayer = Signer::from_str("relayer").unwrap();
+
+		// The tokens refunded by an error acknowledgement can be sent again
+		assert_ok!(transfer(balance / 2));
+		assert_noop!(transfer(1), crate::Error::<Test>::RateLimitExceeded);
+		IbcModule::<Test>::default()
+			.on_acknowledgement_packet(
+				&ctx,
+				&mut ModuleOutputBuilder::new(),
+				&mut sent_packet(1),
+				&error_ack,
+				&relayer,
+			)
+			.unwrap();
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(asset_id, &sender), balance);
+
+		// The tokens refunded by a timeout can be sent again
+		assert_ok!(transfer(balance / 2));
+		assert_noop!(transfer(1), crate::Error::<Test>::RateLimitExceeded);
+		IbcModule::<Test>::default()
+			.on_timeout_packet(&ctx, &mut ModuleOutputBuilder::new(), &mut sent_packet(2), &relayer)
+			.unwrap();
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(asset_id, &sender), balance);
+		assert_ok!(transfer(balance / 2));
+
+		// A refund of a transfer sent in a previous period doesn't free the quota of the current
+		// one
+		Timestamp::set_timestamp(3600 * 1000);
+		assert_ok!(transfer(balance / 2));
+		IbcModule::<Test>::default()
+			.on_acknowledgement_packet(
+				&ctx,
+				&mut ModuleOutputBuilder::new(),
+				&mut sent_packet(3),
+				&error_ack,
+				&relayer,
+			)
+			.unwrap();
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(asset_id, &sender), balance / 2);
+		assert_noop!(transfer(1), crate::Error::<Test>::RateLimitExceeded);
+		// Only the transfer of the last packet is still pending
+		assert_eq!(crate::PacketFlows::<Test>::iter().count(), 1);
+	})
+}
+
+#[test]
+fn should_measure_percent_quotas_against_the_issuance_at_the_start_of_the_period() {
+	use crate::{
+		ics20::{
+			rate_limit::{Quota, RateLimit, RateLimitFlow},
+			IbcModule,
+		},
+		RateLimitFlows,
+	};
+	use frame_support::traits::Hooks;
+	use ibc::{
+		applications::transfer::acknowledgement::Acknowledgement as Ics20Acknowledgement,
+		core::{
+			ics04_channel::msgs::acknowledgement::Acknowledgement,
+			ics26_routing::context::{Module, ModuleOutputBuilder},
+		},
+	};
+	use sp_runtime::Percent;
+
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let raw_user = ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let ss58_address = String::from_utf8(raw_user).unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+		let balance = 100000 * MILLIS;
+		let sender = AccountId32::new([0; 32]);
+		let channel_id = ChannelId::new(0);
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				&"PICA".to_string(),
+			)
+			.unwrap();
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &sender, balance).unwrap();
+
+		Ibc::set_params(
+			RuntimeOrigin::root(),
+			PalletParams { send_enabled: true, receive_enabled: true },
+		)
+		.unwrap();
+		assert_ok!(Ibc::set_rate_limit(
+			RuntimeOrigin::root(),
+			channel_id.to_string().as_bytes().to_vec(),
+			asset_id,
+			Some(RateLimit {
+				max_inflow: None,
+				max_outflow: Some(Quota::Percent(Percent::from_percent(10))),
+				period: 3600,
+			}),
+		));
+
+		let transfer = |amount| {
+			Ibc::transfer(
+				RuntimeOrigin::signed(sender.clone()),
+				TransferParams {
+					to: MultiAddress::Raw(ss58_address.as_bytes().to_vec()),
+					source_channel: 0,
+					timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+				},
+				asset_id,
+				amount,
+				None,
+			)
+		};
+		let refund = |sequence: u64, amount: u128| {
+			let mut packet = Packet {
+				sequence: sequence.into(),
+				source_port: PortId::transfer(),
+				source_channel: channel_id,
+				destination_port: PortId::transfer(),
+				destination_channel: ChannelId::new(1),
+				data: serde_json::to_vec(&PacketData {
+					token: Coin {
+						denom: PrefixedDenom::from_str("PICA").unwrap(),
+						amount: ibc::applications::transfer::Amount::from_str(&format!(
+							"{:?}",
+							amount
+						))
+						.unwrap(),
+					},
+					sender: Signer::from_str(&format!("0x{}", hex::encode([0; 32]))).unwrap(),
+					receiver: Signer::from_str(&ss58_address).unwrap(),
+					memo: "".to_string(),
+				})
+				.unwrap(),
+				timeout_height: Default::default(),
+				timeout_timestamp: Default::default(),
+			};
+			IbcModule::<Test>::default()
+				.on_acknowledgement_packet(
+					&Context::<Test>::default(),
+					&mut ModuleOutputBuilder::new(),
+					&mut packet,
+					&Acknowledgement::from_bytes(
+						Ics20Acknowledgement::Error("failed".to_string()).to_string().into_bytes(),
+					),
+					&Signer::from_str("relayer").unwrap(),
+				)
+				.unwrap();
+		};
+		let flow = || RateLimitFlows::<Test>::get(channel_id.to_string().as_bytes(), asset_id);
+
+		// The issuance is snapshotted when the rate limit is set, tokens minted later in the
+		// period don't raise the quota
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &AccountId32::new([1; 32]), balance)
+		.unwrap();
+		assert_ok!(transfer(balance / 10));
+		assert_noop!(transfer(1), crate::Error::<Test>::RateLimitExceeded);
+
+		// The next period starts at the period boundary with the issuance of that block
+		Timestamp::set_timestamp(5400 * 1000);
+		Ibc::on_initialize(2);
+		assert_eq!(
+			flow(),
+			Some(RateLimitFlow {
+				inflow: 0,
+				outflow: 0,
+				issuance: 2 * balance,
+				period_start: 3600,
+			})
+		);
+
+		// Refunding the transfer of the previous period leaves the flow of this one alone
+		refund(1, balance / 10);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(asset_id, &sender), balance);
+		assert_eq!(flow().unwrap().outflow, 0);
+
+		// The quota of the new period is measured against its snapshot, and refunds in it free
+		// the quota again
+		assert_ok!(transfer(balance / 5));
+		assert_noop!(transfer(1), crate::Error::<Test>::RateLimitExceeded);
+		refund(2, balance / 5);
+		assert_eq!(flow().unwrap().outflow, 0);
+		assert_ok!(transfer(balance / 5));
+		assert_eq!(crate::PacketFlows::<Test>::iter().count(), 1);
+	})
+}
+
+#[test]
+fn should_reject_received_transfers_exceeding_the_inflow_limit() {
+	use crate::{
+		ics20::rate_limit::{Quota, RateLimit},
+		RateLimitFlows,
+	};
+	use ibc::core::ics04_channel::context::ChannelReader;
+
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ss58_address_bytes =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let ss58_address = String::from_utf8(ss58_address_bytes).unwrap();
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				&"PICA".to_string(),
+			)
+			.unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+
+		let channel_id = ChannelId::new(0);
+		let balance = 100000 * MILLIS;
+		let channel_escrow_address =
+			get_channel_escrow_address(&PortId::transfer(), channel_id).unwrap();
+		let channel_escrow_address =
+			<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+				.map_err(|_| ())
+				.unwrap()
+				.into_account();
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &channel_escrow_address, balance)
+		.unwrap();
+
+		Ibc::set_params(
+			RuntimeOrigin::root(),
+			PalletParams { send_enabled: true, receive_enabled: true },
+		)
+		.unwrap();
+		let max_inflow = balance / 10;
+		assert_ok!(Ibc::set_rate_limit(
+			RuntimeOrigin::root(),
+			channel_id.to_string().as_bytes().to_vec(),
+			asset_id,
+			Some(RateLimit {
+				max_inflow: Some(Quota::Absolute(max_inflow)),
+				max_outflow: None,
+				period: 3600,
+			}),
+		));
+
+		let receive = |sequence: u64, amount: u128| {
+			let packet = Packet {
+				sequence: sequence.into(),
+				source_port: PortId::transfer(),
+				source_channel: ChannelId::new(1),
+				destination_port: PortId::transfer(),
+				destination_channel: channel_id,
+				data: serde_json::to_vec(&PacketData {
+					token: Coin {
+						denom: PrefixedDenom::from_str("transfer/channel-1/PICA").unwrap(),
+						amount: ibc::applications::transfer::Amount::from_str(&format!(
+							"{:?}",
+							amount
+						))
+						.unwrap(),
+					},
+					sender: Signer::from_str("alice").unwrap(),
+					receiver: Signer::from_str(&ss58_address).unwrap(),
+					memo: "".to_string(),
+				})
+				.unwrap(),
+				timeout_height: Height::new(2000, 5),
+				timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+					1690894363u64.saturating_mul(1000000000),
+				)
+				.unwrap(),
+			};
+			let msg = MsgRecvPacket {
+				packet,
+				proofs: Proofs::new(
+					vec![0u8; 32].try_into().unwrap(),
+					None,
+					None,
+					None,
+					Height::new(0, 1),
+				)
+				.unwrap(),
+				signer: Signer::from_str(MODULE_ID).unwrap(),
+			};
+			let msg = Any {
+				type_url: msg.type_url().as_bytes().to_vec(),
+				value: msg.encode_vec().unwrap(),
+			};
+			Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]).unwrap();
+		};
+
+		let rate_limit_events = || {
+			System::events()
+				.into_iter()
+				.filter_map(|record| match record.event {
+					RuntimeEvent::Ibc(event @ crate::Event::RateLimitExceeded { .. }) =>
+						Some(event),
+					_ => None,
+				})
+				.collect::<Vec<_>>()
+		};
+
+		let receiver = AccountId32::new(pair.public().0);
+		let ctx = Context::<Test>::default();
+		receive(1, max_inflow);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(asset_id, &receiver), max_inflow);
+		assert!(rate_limit_events().is_empty());
+
+		// The packet exceeding the quota is acknowledged with an error and no tokens are received
+		receive(2, 1);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(asset_id, &receiver), max_inflow);
+		let ack = ctx.get_packet_acknowledgement(&(PortId::transfer(), channel_id, 2.into()));
+		assert!(ack.is_ok());
+		assert_ne!(
+			ack.unwrap(),
+			ctx.get_packet_acknowledgement(&(PortId::transfer(), channel_id, 1.into()))
+				.unwrap()
+		);
+		// The event outlives the rollback of the rejected packet
+		assert_eq!(
+			rate_limit_events(),
+			vec![crate::Event::RateLimitExceeded {
+				channel_id: channel_id.to_string().as_bytes().to_vec(),
+				asset_id,
+				amount: 1,
+				is_inflow: true,
+			}]
+		);
+		let flow = RateLimitFlows::<Test>::get(channel_id.to_string().as_bytes(), asset_id);
+		assert_eq!(flow.unwrap().inflow, max_inflow);
+		// Received transfers that aren't forwarded can't be refunded and aren't tracked
+		assert_eq!(crate::PacketFlows::<Test>::iter().count(), 0);
+	})
+}
+
+#[test]
+fn should_forward_transfers_and_refund_failed_forwards() {
+	use crate::{
+		ics20::{forward::PacketForward, IbcModule},
+		InFlightForwards,
+	};
+	use ibc::{
+		applications::{
+			fee::{acknowledgement::IncentivizedAcknowledgement, context::FeeKeeper},
+			transfer::acknowledgement::Acknowledgement as Ics20Acknowledgement,
+		},
+		core::{
+			ics04_channel::{context::ChannelReader, msgs::acknowledgement::Acknowledgement},
+			ics26_routing::context::{Module, ModuleOutputBuilder},
+		},
+	};
+
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ss58_address_bytes =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let ss58_address = String::from_utf8(ss58_address_bytes).unwrap();
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				&"PICA".to_string(),
+			)
+			.unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+
+		let channel_id = ChannelId::new(0);
+		let balance = 100000 * MILLIS;
+		let channel_escrow_address =
+			get_channel_escrow_address(&PortId::transfer(), channel_id).unwrap();
+		let channel_escrow_address =
+			<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+				.map_err(|_| ())
+				.unwrap()
+				.into_account();
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &channel_escrow_address, balance)
+		.unwrap();
+
+		Ibc::set_params(
+			RuntimeOrigin::root(),
+			PalletParams { send_enabled: true, receive_enabled: true },
+		)
+		.unwrap();
+
+		// The tokens are sent back to their source chain and forwarded to another chain from there
+		let amt = 1000 * MILLIS;
+		let packet_data = PacketData {
+			token: Coin {
+				denom: PrefixedDenom::from_str("transfer/channel-1/PICA").unwrap(),
+				amount: ibc::applications::transfer::Amount::from_str(&format!("{:?}", amt))
+					.unwrap(),
+			},
+			sender: Signer::from_str("alice").unwrap(),
+			receiver: Signer::from_str(&ss58_address).unwrap(),
+			memo: r#"{"forward": {"receiver": "bob", "port": "transfer", "channel": "channel-0"}}"#
+				.to_string(),
+		};
+		let packet = Packet {
+			sequence: 1u64.into(),
+			source_port: PortId::transfer(),
+			source_channel: ChannelId::new(1),
+			destination_port: PortId::transfer(),
+			destination_channel: channel_id,
+			data: serde_json::to_vec(&packet_data).unwrap(),
+			timeout_height: Height::new(2000, 5),
+			timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+				1690894363u64.saturating_mul(1000000000),
+			)
+			.unwrap(),
+		};
+		let msg = MsgRecvPacket {
+			packet: packet.clone(),
+			proofs: Proofs::new(
+				vec![0u8; 32].try_into().unwrap(),
+				None,
+				None,
+				None,
+				Height::new(0, 1),
+			)
+			.unwrap(),
+			signer: Signer::from_str(MODULE_ID).unwrap(),
+		};
+		let msg =
+			Any { type_url: msg.type_url().as_bytes().to_vec(), value: msg.encode_vec().unwrap() };
+		Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]).unwrap();
+
+		let receiver = AccountId32::new(pair.public().0);
+		let ack_key = (PortId::transfer(), channel_id, packet.sequence);
+		let forward_key =
+			(PortId::transfer().as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec());
+		// The tokens were forwarded and the acknowledgement is held until the forward completes
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &receiver), 0);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &channel_escrow_address), balance);
+		assert!(Context::<Test>::default().get_packet_acknowledgement(&ack_key).is_err());
+		assert_eq!(
+			InFlightForwards::<Test>::get(&forward_key, 1).unwrap().relayer,
+			MODULE_ID.as_bytes().to_vec()
+		);
+
+		let mut forwarded_packet = Packet {
+			sequence: 1u64.into(),
+			source_port: PortId::transfer(),
+			source_channel: channel_id,
+			destination_port: PortId::transfer(),
+			destination_channel: ChannelId::new(1),
+			data: serde_json::to_vec(&PacketData {
+				token: Coin {
+					denom: PrefixedDenom::from_str("PICA").unwrap(),
+					amount: packet_data.token.amount,
+				},
+				sender: packet_data.receiver.clone(),
+				receiver: Signer::from_str("bob").unwrap(),
+				memo: "".to_string(),
+			})
+			.unwrap(),
+			timeout_height: Default::default(),
+			timeout_timestamp: Default::default(),
+		};
+		let ack = Acknowledgement::from_bytes(
+			Ics20Acknowledgement::Error("failed".to_string()).to_string().into_bytes(),
+		);
+		// The held acknowledgement is wrapped by the fee middleware for the relayer of the received
+		// packet, not the relayer of the acknowledgement
+		let mut ctx = Context::<Test>::default();
+		ctx.set_fee_enabled(&PortId::transfer(), &channel_id);
+		ctx.store_counterparty_payee(&Signer::from_str(MODULE_ID).unwrap(), &channel_id, "payee");
+		PacketForward::<Test, IbcModule<Test>>::default()
+			.on_acknowledgement_packet(
+				&ctx,
+				&mut ModuleOutputBuilder::new(),
+				&mut forwarded_packet,
+				&ack,
+				&Signer::from_str("relayer").unwrap(),
+			)
+			.unwrap();
+
+		// The forward failed, the tokens are escrowed again and the original sender gets refunded
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &receiver), 0);
+		assert_eq!(<Assets as Inspect<AccountId>>::balance(2, &channel_escrow_address), balance);
+		assert!(!InFlightForwards::<Test>::contains_key(&forward_key, 1));
+		let ack = ctx.get_packet_acknowledgement(&ack_key).unwrap();
+		let error_ack = Ics20Acknowledgement::Error(format!(
+			"{}: failed",
+			ibc::applications::transfer::acknowledgement::ACK_ERR_STR
+		))
+		.to_string()
+		.into_bytes();
+		let fee_ack = IncentivizedAcknowledgement::new(error_ack, "payee".to_string()).encode();
+		assert_eq!(ack, ctx.ack_commitment(fee_ack.into()));
+	})
+}
+
+#[test]
+fn should_deposit_the_rate_limit_event_of_rejected_forwards() {
+	use crate::ics20::rate_limit::{Quota, RateLimit};
+	use ibc::core::ics04_channel::context::ChannelReader;
+
+	new_test_ext().execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let ss58_address_bytes =
+			ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let ss58_address = String::from_utf8(ss58_address_bytes).unwrap();
+		frame_system::Pallet::<Test>::set_block_number(1u32);
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				&"PICA".to_string(),
+			)
+			.unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+
+		let channel_id = ChannelId::new(0);
+		let balance = 100000 * MILLIS;
+		let channel_escrow_address =
+			get_channel_escrow_address(&PortId::transfer(), channel_id).unwrap();
+		let channel_escrow_address =
+			<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+				.map_err(|_| ())
+				.unwrap()
+				.into_account();
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &channel_escrow_address, balance)
+		.unwrap();
+
+		Ibc::set_params(
+			RuntimeOrigin::root(),
+			PalletParams { send_enabled: true, receive_enabled: true },
+		)
+		.unwrap();
+		let amt = 1000 * MILLIS;
+		assert_ok!(Ibc::set_rate_limit(
+			RuntimeOrigin::root(),
+			channel_id.to_string().as_bytes().to_vec(),
+			asset_id,
+			Some(RateLimit {
+				max_inflow: Some(Quota::Absolute(amt - 1)),
+				max_outflow: None,
+				period: 3600,
+			}),
+		));
+
+		let packet = Packet {
+			sequence: 1u64.into(),
+			source_port: PortId::transfer(),
+			source_channel: ChannelId::new(1),
+			destination_port: PortId::transfer(),
+			destination_channel: channel_id,
+			data: serde_json::to_vec(&PacketData {
+				token: Coin {
+					denom: PrefixedDenom::from_str("transfer/channel-1/PICA").unwrap(),
+					amount: ibc::applications::transfer::Amount::from_str(&format!("{:?}", amt))
+						.unwrap(),
+				},
+				sender: Signer::from_str("alice").unwrap(),
+				receiver: Signer::from_str(&ss58_address).unwrap(),
+				memo: r#"{"forward": {"receiver": "bob", "port": "transfer", "channel": "channel-0"}}"#
+					.to_string(),
+			})
+			.unwrap(),
+			timeout_height: Height::new(2000, 5),
+			timeout_timestamp: ibc::timestamp::Timestamp::from_nanoseconds(
+				1690894363u64.saturating_mul(1000000000),
+			)
+			.unwrap(),
+		};
+		let msg = MsgRecvPacket {
+			packet: packet.clone(),
+			proofs: Proofs::new(
+				vec![0u8; 32].try_into().unwrap(),
+				None,
+				None,
+				None,
+				Height::new(0, 1),
+			)
+			.unwrap(),
+			signer: Signer::from_str(MODULE_ID).unwrap(),
+		};
+		let msg =
+			Any { type_url: msg.type_url().as_bytes().to_vec(), value: msg.encode_vec().unwrap() };
+		Ibc::deliver(RuntimeOrigin::signed(AccountId32::new([0; 32])), vec![msg]).unwrap();
+
+		// The packet is rejected before it's forwarded, both the receive and the forward are
+		// rolled back but the event is kept
+		let ack_key = (PortId::transfer(), channel_id, packet.sequence);
+		assert!(Context::<Test>::default().get_packet_acknowledgement(&ack_key).is_ok());
+		assert_eq!(
+			<Assets as Inspect<AccountId>>::balance(asset_id, &channel_escrow_address),
+			balance
+		);
+		assert!(System::events().into_iter().any(|record| record.event ==
+			RuntimeEvent::Ibc(crate::Event::RateLimitExceeded {
+				channel_id: channel_id.to_string().as_bytes().to_vec(),
+				asset_id,
+				amount: amt,
+				is_inflow: true,
+			})));
+	})
+}
+
+#[test]
+fn should_transfer_several_assets_in_one_packet() {
+	use ibc::{
+		applications::transfer::packet::PacketDataV2, core::ics04_channel::context::ChannelReader,
+	};
+
+	let mut ext = new_test_ext();
+	let balance = 100000 * MILLIS;
+	ext.execute_with(|| {
+		let pair = sp_core::sr25519::Pair::from_seed(b"12345678901234567890123456789012");
+		let raw_user = ibc_primitives::runtime_interface::account_id_to_ss58(pair.public().0, 49);
+		let ss58_address = String::from_utf8(raw_user).unwrap();
+		setup_client_and_consensus_state(PortId::transfer());
+		let asset_id =
+			<<Test as Config>::IbcDenomToAssetIdConversion as DenomToAssetId<Test>>::from_denom_to_asset_id(
+				&"PICA".to_string(),
+			)
+			.unwrap();
+		<<Test as Config>::Fungibles as Mutate<
+			<Test as frame_system::Config>::AccountId,
+		>>::mint_into(asset_id, &AccountId32::new([0; 32]), balance).unwrap();
+
+		Ibc::set_params(
+			RuntimeOrigin::root(),
+			PalletParams { send_enabled: true, receive_enabled: true },
+		)
+		.unwrap();
+
+		let transfer_multi = || {
+			Ibc::transfer_multi(
+				RuntimeOrigin::signed(AccountId32::new([0; 32])),
+				TransferParams {
+					to: MultiAddress::Raw(ss58_address.as_bytes().to_vec()),
+					source_channel: 0,
+					timeout: Timeout::Offset { timestamp: Some(1000), height: Some(5) },
+				},
+				vec![(asset_id, balance / 4), (asset_id, balance / 2)],
+				None,
+			)
+		};
+
+		// Several tokens can't be sent over an `ics20-1` channel
+		assert_noop!(transfer_multi(), crate::Error::<Test>::TransferFailed);
+
+		let mut ctx = Context::<Test>::default();
+		let channel_id = ChannelId::new(0);
+		let mut channel_end = ctx.channel_end(&(PortId::transfer(), channel_id)).unwrap();
+		channel_end.version = ChanVersion::ics20_v2();
+		ctx.store_channel((PortId::transfer(), channel_id), &channel_end).unwrap();
+		assert_ok!(transfer_multi());
+
+		let channel_escrow_address =
+			get_channel_escrow_address(&PortId::transfer(), channel_id).unwrap();
+		let channel_escrow_address =
+			<Test as Config>::AccountIdConversion::try_from(channel_escrow_address)
+				.map_err(|_| ())
+				.unwrap()
+				.into_account();
+		assert_eq!(
+			<Assets as Inspect<AccountId>>::balance(asset_id, &AccountId32::new([0; 32])),
+			balance / 4
+		);
+		assert_eq!(
+			<Assets as Inspect<AccountId>>::balance(asset_id, &channel_escrow_address),
+			balance / 4 + balance / 2
+		);
+	});
+
+	ext.persist_offchain_overlay();
+
+	ext.execute_with(|| {
+		let packet_info = Pallet::<Test>::get_send_packet_info(
+			ChannelId::new(0).to_string().as_bytes().to_vec(),
+			PortId::transfer().as_bytes().to_vec(),
+			vec![1],
+		)
+		.unwrap()
+		.get(0)
+		.unwrap()
+		.clone();
+		let packet_data: PacketDataV2 = serde_json::from_slice(&packet_info.data).unwrap();
+		assert_eq!(packet_data.tokens.len(), 2);
+		assert_eq!(packet_data.tokens[1].amount.as_u256().as_u128(), balance / 2);
+	})
+}
+
+/// Opens two host channels for the same controller port, the first one is the account's active
+/// channel. Only `System.remark` may be executed by interchain accounts.
+fn setup_ica_host() -> PortId {
+	use crate::{IcaParams, InterchainAccountParams};
+	use ibc::applications::interchain_accounts::{
+		context::{IcaContext, IcaHostContext},
+		host_port_id, VERSION as ICA_VERSION,
+	};
+
+	let mut ctx = Context::<Test>::default();
+	IcaParams::<Test>::put(InterchainAccountParams {
+		host_enabled: true,
+		controller_enabled: false,
+		allow_messages: vec![b"System.remark".to_vec()],
+	});
+
+	let client_id = ClientId::new(&MockClientState::client_type(), 0).unwrap();
+	let counterparty_client_id = ClientId::new(&MockClientState::client_type(), 1).unwrap();
+	let commitment_prefix: CommitmentPrefix =
+		<Test as Config>::PALLET_PREFIX.to_vec().try_into().unwrap();
+	let connection_end = ConnectionEnd::new(
+		ConnState::Open,
+		client_id,
+		Counterparty::new(counterparty_client_id, Some(ConnectionId::new(1)), commitment_prefix),
+		vec![ConnVersion::default()],
+		Duration::from_nanos(0),
+	);
+	ctx.store_connection(ConnectionId::new(0), &connection_end).unwrap();
+
+	let controller_port = PortId::from_str("icacontroller-alice").unwrap();
+	for channel in 0..2 {
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			ChanCounterParty::new(controller_port.clone(), Some(ChannelId::new(channel))),
+			vec![ConnectionId::new(0)],
+			ChanVersion::new(ICA_VERSION.to_string()),
+		);
+		ctx.store_channel((host_port_id(), ChannelId::new(channel)), &channel_end)
+			.unwrap();
+	}
+	ctx.set_active_channel(&ConnectionId::new(0), &controller_port, &ChannelId::new(0))
+		.unwrap();
+	ctx.register_interchain_account(&ConnectionId::new(0), &controller_port)
+		.unwrap();
+	controller_port
+}
+
+fn ica_message(call: RuntimeCall) -> ibc_proto::google::protobuf::Any {
+	use codec::Encode;
+	ibc_proto::google::protobuf::Any {
+		type_url: crate::ics27::RUNTIME_CALL_TYPE_URL.to_string(),
+		value: call.encode(),
+	}
+}
+
+#[test]
+fn ica_host_rejects_messages_not_in_allow_list() {
+	use crate::ics27::context::is_call_allowed;
+	use ibc::applications::interchain_accounts::context::IcaHostContext;
+
+	new_test_ext().execute_with(|| {
+		let controller_port = setup_ica_host();
+		let mut ctx = Context::<Test>::default();
+		let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![1] });
+		let remark_with_event =
+			RuntimeCall::System(frame_system::Call::remark_with_event { remark: vec![1] });
+
+		assert!(ctx
+			.execute_tx(&ConnectionId::new(0), &controller_port, vec![ica_message(remark.clone())])
+			.is_ok());
+		// a transaction with a message that isn't allowed is rejected as a whole.
+		let err = ctx
+			.execute_tx(
+				&ConnectionId::new(0),
+				&controller_port,
+				vec![ica_message(remark.clone()), ica_message(remark_with_event.clone())],
+			)
+			.unwrap_err();
+		assert!(err.to_string().contains("System.remark_with_event"));
+
+		assert!(is_call_allowed(&[b"System.*".to_vec()], &remark_with_event));
+		assert!(is_call_allowed(&[b"*".to_vec()], &remark_with_event));
+		assert!(!is_call_allowed(&[b"Tokens.*".to_vec()], &remark));
+		assert!(!is_call_allowed(&[], &remark));
+	})
+}
+
+#[test]
+fn ica_host_rejects_packets_on_inactive_channel() {
+	use ibc::{
+		applications::interchain_accounts::{
+			host::on_recv_packet, host_port_id, packet::InterchainAccountPacketData,
+		},
+		core::ics26_routing::context::ModuleOutputBuilder,
+	};
+
+	new_test_ext().execute_with(|| {
+		let controller_port = setup_ica_host();
+		let mut ctx = Context::<Test>::default();
+		let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![1] });
+		let packet = |channel| Packet {
+			sequence: 1.into(),
+			source_port: controller_port.clone(),
+			source_channel: ChannelId::new(channel),
+			destination_port: host_port_id(),
+			destination_channel: ChannelId::new(channel),
+			data: InterchainAccountPacketData::new(
+				vec![ica_message(remark.clone())],
+				String::new(),
+			)
+			.encode(),
+			timeout_height: Height::new(0, 100),
+			timeout_timestamp: Default::default(),
+		};
+
+		let ack = on_recv_packet(&mut ctx, &mut ModuleOutputBuilder::new(), &packet(1));
+		assert!(!ack.is_successful());
+
+		let ack = on_recv_packet(&mut ctx, &mut ModuleOutputBuilder::new(), &packet(0));
+		assert!(ack.is_successful());
+	})
+}
@@ -31,6 +31,8 @@ pub trait WeightInfo {
 	fn ack_packet_tendermint(i: u32, j: u32) -> Weight;
 	fn timeout_packet_tendermint(i: u32) -> Weight;
 	fn set_params() -> Weight;
+	fn set_rate_limit() -> Weight;
+	fn recover_client() -> Weight;
 	fn transfer() -> Weight;
 	fn on_chan_open_init() -> Weight;
 	fn on_chan_open_try() -> Weight;
@@ -109,6 +111,14 @@ impl WeightInfo for () {
 		Weight::from_ref_time(0)
 	}
 
+	fn set_rate_limit() -> Weight {
+		Weight::from_ref_time(0)
+	}
+
+	fn recover_client() -> Weight {
+		Weight::from_ref_time(0)
+	}
+
 	fn transfer() -> Weight {
 		Weight::from_ref_time(0)
 	}
@@ -161,6 +171,9 @@ impl<T: Config> WeightRouter<T> {
 		match port_id {
 			ibc::applications::transfer::PORT_ID_STR =>
 				Some(Box::new(ics20::WeightHandler::<T>::default())),
+			ics27::HOST_PORT_ID_STR => Some(Box::new(ics27::WeightHandler::<T>::default())),
+			port_id if port_id.starts_with(ics27::CONTROLLER_PORT_PREFIX) =>
+				Some(Box::new(ics27::WeightHandler::<T>::default())),
 			_ => None,
 		}
 	}
@@ -450,6 +463,14 @@ where
 						};
 						cb_weight.saturating_add(lc_verification_weight)
 					},
+					// todo: benchmark channel upgrade handshake
+					ChannelMsg::ChannelUpgradeInit(_) |
+					ChannelMsg::ChannelUpgradeTry(_) |
+					ChannelMsg::ChannelUpgradeAck(_) |
+					ChannelMsg::ChannelUpgradeConfirm(_) |
+					ChannelMsg::ChannelUpgradeOpen(_) |
+					ChannelMsg::ChannelUpgradeTimeout(_) |
+					ChannelMsg::ChannelUpgradeCancel(_) => Weight::default(),
 				},
 				Ics26Envelope::Ics4PacketMsg(msgs) => match msgs {
 					PacketMsg::RecvPacket(packet_msg) => {
@@ -0,0 +1,583 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP api to monitor the relayer and control its paths at runtime, served alongside the
+//! prometheus endpoint.
+
+use std::{
+	collections::{HashMap, HashSet},
+	net::SocketAddr,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex,
+	},
+};
+
+use anyhow::anyhow;
+use hyper::{
+	header::AUTHORIZATION,
+	http::StatusCode,
+	server::Server,
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response,
+};
+use ibc::{
+	core::ics24_host::identifier::{ChannelId, PortId},
+	Height,
+};
+use pallet_ibc::light_clients::AnyClientState;
+use primitives::{query_undelivered_acks, query_undelivered_sequences, Chain, IbcProvider};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::chain::AnyChain;
+
+/// Runtime controls of a relay path, shared between the api and the path's relay loop.
+#[derive(Default)]
+pub struct PathControl {
+	paused: AtomicBool,
+	/// Chains whose channels are swept for pending packets on their next finality notification.
+	clear_requests: Mutex<HashSet<String>>,
+	/// Channel whitelists of the chains that were changed through the api, they replace the
+	/// configured whitelists until the relayer is restarted.
+	channel_whitelists: Mutex<HashMap<String, Vec<(ChannelId, PortId)>>>,
+}
+
+impl PathControl {
+	/// Returns true if the path is paused, finality notifications are ignored while it is.
+	pub fn is_paused(&self) -> bool {
+		self.paused.load(Ordering::SeqCst)
+	}
+
+	pub fn set_paused(&self, paused: bool) {
+		self.paused.store(paused, Ordering::SeqCst);
+	}
+
+	/// Requests a sweep of the channels of `chain` for pending packets.
+	pub fn request_clear(&self, chain: &str) {
+		self.clear_requests.lock().unwrap().insert(chain.to_string());
+	}
+
+	/// Returns true if a sweep of the channels of `chain` was requested, and resets the request.
+	pub fn take_clear_request(&self, chain: &str) -> bool {
+		self.clear_requests.lock().unwrap().remove(chain)
+	}
+
+	pub fn set_channel_whitelist(&self, chain: &str, channel_whitelist: Vec<(ChannelId, PortId)>) {
+		self.channel_whitelists
+			.lock()
+			.unwrap()
+			.insert(chain.to_string(), channel_whitelist);
+	}
+
+	/// Returns the channel whitelist of `chain` if it was changed through the api.
+	pub fn channel_whitelist(&self, chain: &str) -> Option<Vec<(ChannelId, PortId)>> {
+		self.channel_whitelists.lock().unwrap().get(chain).cloned()
+	}
+
+	/// Replaces the channel whitelist of `chain` with the one set through the api, if it differs.
+	/// The relay loop calls this before handling each finality notification of `chain`.
+	pub fn apply_channel_whitelist(&self, chain: &mut impl Chain) {
+		if let Some(whitelist) = self.channel_whitelist(chain.name()) {
+			if whitelist != chain.channel_whitelist() {
+				log::info!("Updating channel whitelist of {} to {:?}", chain.name(), whitelist);
+				chain.set_channel_whitelist(whitelist);
+			}
+		}
+	}
+}
+
+/// A relay path exposed through the api.
+#[derive(Clone)]
+pub struct ApiPath {
+	pub name: String,
+	pub chain_a: AnyChain,
+	pub chain_b: AnyChain,
+	pub control: Arc<PathControl>,
+}
+
+impl ApiPath {
+	fn channel_whitelist(&self, chain: &AnyChain) -> Vec<(ChannelId, PortId)> {
+		self.control
+			.channel_whitelist(chain.name())
+			.unwrap_or_else(|| chain.channel_whitelist())
+	}
+}
+
+/// Body of `POST /paths/{id}/clear`, both chains are cleared if no chain is given.
+#[derive(Deserialize, Default)]
+struct ClearRequest {
+	chain: Option<String>,
+}
+
+/// Body of `PUT /paths/{id}/channels`.
+#[derive(Deserialize)]
+struct ChannelWhitelistRequest {
+	chain: String,
+	channels: Vec<(ChannelId, PortId)>,
+}
+
+/// The paths served by the api and who may control them.
+struct Api {
+	paths: Vec<ApiPath>,
+	/// Bearer token the requests controlling the paths must carry.
+	token: Option<String>,
+	/// Whether the api is only reachable from this host, the paths can then be controlled
+	/// without a token.
+	local: bool,
+}
+
+impl Api {
+	/// Checks that a request is allowed to control the paths.
+	fn authorize(&self, req: &Request<Body>) -> Result<(), (StatusCode, String)> {
+		match self.token.as_ref() {
+			Some(token) => {
+				let bearer = req
+					.headers()
+					.get(AUTHORIZATION)
+					.and_then(|value| value.to_str().ok())
+					.and_then(|value| value.strip_prefix("Bearer "));
+				if bearer != Some(token.as_str()) {
+					return Err((StatusCode::UNAUTHORIZED, "Invalid api token.".to_string()))
+				}
+			},
+			None if !self.local =>
+				return Err((
+					StatusCode::FORBIDDEN,
+					"Paths can only be controlled with an api token when the api isn't bound to a loopback address."
+						.to_string(),
+				)),
+			None => {},
+		}
+		Ok(())
+	}
+}
+
+/// Starts an HTTP server serving the api for the given paths. Requests that control the paths
+/// must carry `token` as a bearer token, without a token they're only accepted if `addr` is a
+/// loopback address.
+pub async fn serve(
+	addr: SocketAddr,
+	token: Option<String>,
+	paths: Vec<ApiPath>,
+) -> Result<(), anyhow::Error> {
+	let listener = tokio::net::TcpListener::bind(&addr)
+		.await
+		.map_err(|e| anyhow!("Failed to bind api to {}: {}", addr, e))?;
+	let listener = hyper::server::conn::AddrIncoming::from_listener(listener)?;
+	if token.is_none() && !addr.ip().is_loopback() {
+		log::warn!(target: "hyperspace",
+			"No api token is configured, the paths can't be controlled through the api on {}", addr);
+	}
+	let api = Arc::new(Api { paths, token, local: addr.ip().is_loopback() });
+
+	let service = make_service_fn(move |_| {
+		let api = api.clone();
+
+		async move {
+			Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+				handle_request(req, api.clone())
+			}))
+		}
+	});
+
+	log::info!(target: "hyperspace", "Serving api on {}", addr);
+	Server::builder(listener).serve(service).await?;
+	Ok(())
+}
+
+async fn handle_request(
+	req: Request<Body>,
+	api: Arc<Api>,
+) -> Result<Response<Body>, hyper::http::Error> {
+	let method = req.method().clone();
+	// anything but reading the state of the relayer controls the paths
+	if method != Method::GET {
+		if let Err((status, error)) = api.authorize(&req) {
+			return respond(status, json!({ "error": error }))
+		}
+	}
+	let paths = &api.paths;
+	let segments = req
+		.uri()
+		.path()
+		.split('/')
+		.filter(|segment| !segment.is_empty())
+		.map(|segment| segment.to_string())
+		.collect::<Vec<_>>();
+	let body = match hyper::body::to_bytes(req.into_body()).await {
+		Ok(body) => body,
+		Err(err) => return respond(StatusCode::BAD_REQUEST, json!({ "error": err.to_string() })),
+	};
+	let segments = segments.iter().map(|segment| segment.as_str()).collect::<Vec<_>>();
+
+	let result = match (&method, segments.as_slice()) {
+		(&Method::GET, ["health"]) => Ok(json!({ "status": "ok" })),
+		(&Method::GET, ["version"]) => Ok(json!({ "version": env!("CARGO_PKG_VERSION") })),
+		(&Method::GET, ["chains"]) => chains(paths).await.map_err(internal_error),
+		(&Method::GET, ["paths"]) => Ok(paths
+			.iter()
+			.map(|path| {
+				json!({
+					"name": path.name,
+					"chain_a": path.chain_a.name(),
+					"chain_b": path.chain_b.name(),
+					"paused": path.control.is_paused(),
+				})
+			})
+			.collect()),
+		(_, ["paths", name, action]) => match paths.iter().find(|path| path.name == *name) {
+			Some(path) => path_action(&method, path, action, &body).await,
+			None => Err((StatusCode::NOT_FOUND, format!("Unknown path {}", name))),
+		},
+		_ => Err((StatusCode::NOT_FOUND, "Not found.".to_string())),
+	};
+
+	match result {
+		Ok(value) => respond(StatusCode::OK, value),
+		Err((status, error)) => respond(status, json!({ "error": error })),
+	}
+}
+
+async fn path_action(
+	method: &Method,
+	path: &ApiPath,
+	action: &str,
+	body: &[u8],
+) -> Result<Value, (StatusCode, String)> {
+	match (method, action) {
+		(&Method::GET, "pending") => pending(path).await.map_err(internal_error),
+		(&Method::POST, "pause") => {
+			log::info!(target: "hyperspace", "Pausing relay path {}", path.name);
+			path.control.set_paused(true);
+			Ok(json!({ "paused": true }))
+		},
+		(&Method::POST, "resume") => {
+			log::info!(target: "hyperspace", "Resuming relay path {}", path.name);
+			path.control.set_paused(false);
+			// pick up the packets sent while the path was paused
+			path.control.request_clear(path.chain_a.name());
+			path.control.request_clear(path.chain_b.name());
+			Ok(json!({ "paused": false }))
+		},
+		(&Method::POST, "clear") => {
+			let request = if body.is_empty() {
+				ClearRequest::default()
+			} else {
+				serde_json::from_slice::<ClearRequest>(body).map_err(bad_request)?
+			};
+			let chains = match request.chain {
+				Some(chain) => vec![path_chain(path, &chain)?.name().to_string()],
+				None => vec![path.chain_a.name().to_string(), path.chain_b.name().to_string()],
+			};
+			for chain in &chains {
+				log::info!(target: "hyperspace", "Requesting packet clearing of {} on path {}", chain, path.name);
+				path.control.request_clear(chain);
+			}
+			Ok(json!({ "cleared": chains }))
+		},
+		(&Method::PUT, "channels") => {
+			let request =
+				serde_json::from_slice::<ChannelWhitelistRequest>(body).map_err(bad_request)?;
+			let chain = path_chain(path, &request.chain)?;
+			log::info!(target: "hyperspace",
+				"Setting channel whitelist of {} on path {} to {:?}", chain.name(), path.name, request.channels);
+			path.control.set_channel_whitelist(chain.name(), request.channels.clone());
+			Ok(json!({ "chain": chain.name(), "channels": request.channels }))
+		},
+		_ => Err((StatusCode::NOT_FOUND, "Not found.".to_string())),
+	}
+}
+
+fn path_chain<'a>(path: &'a ApiPath, chain: &str) -> Result<&'a AnyChain, (StatusCode, String)> {
+	[&path.chain_a, &path.chain_b]
+		.into_iter()
+		.find(|end| end.name() == chain)
+		.ok_or_else(|| {
+			(StatusCode::BAD_REQUEST, format!("Chain {} is not on path {}", chain, path.name))
+		})
+}
+
+/// Latest height of every chain and the latest heights of the clients on it.
+async fn chains(paths: &[ApiPath]) -> Result<Value, anyhow::Error> {
+	let mut chains = HashMap::new();
+	let mut clients = HashMap::<String, Vec<Value>>::new();
+	for path in paths {
+		for (chain, counterparty) in
+			[(&path.chain_a, &path.chain_b), (&path.chain_b, &path.chain_a)]
+		{
+			if !chains.contains_key(chain.name()) {
+				let (height, _) = chain.latest_height_and_timestamp().await?;
+				chains.insert(chain.name().to_string(), height);
+			}
+			// the client of the counterparty on this chain
+			let client_id = counterparty.client_id();
+			let response =
+				chain.query_client_state(chains[chain.name()], client_id.clone()).await?;
+			let client_height = response
+				.client_state
+				.map(AnyClientState::try_from)
+				.transpose()
+				.map_err(|_| anyhow!("Invalid client state {} on {}", client_id, chain.name()))?
+				.map(|client_state| height_json(client_state.latest_height()));
+			clients.entry(chain.name().to_string()).or_default().push(json!({
+				"path": path.name,
+				"client_id": client_id.to_string(),
+				"counterparty": counterparty.name(),
+				"latest_height": client_height,
+			}));
+		}
+	}
+
+	let mut names = chains.keys().cloned().collect::<Vec<_>>();
+	names.sort();
+	Ok(names
+		.into_iter()
+		.map(|name| {
+			json!({
+				"name": name,
+				"latest_height": height_json(chains[&name]),
+				"clients": clients.remove(&name).unwrap_or_default(),
+			})
+		})
+		.collect())
+}
+
+/// Undelivered packets and acknowledgements on the whitelisted channels of both ends of a path.
+async fn pending(path: &ApiPath) -> Result<Value, anyhow::Error> {
+	let mut channels = vec![];
+	for (source, sink) in [(&path.chain_a, &path.chain_b), (&path.chain_b, &path.chain_a)] {
+		let (source_height, _) = source.latest_height_and_timestamp().await?;
+		let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+		for (channel_id, port_id) in path.channel_whitelist(source) {
+			let packets = query_undelivered_sequences(
+				source_height,
+				sink_height,
+				channel_id,
+				port_id.clone(),
+				source,
+				sink,
+			)
+			.await?;
+			let acks = query_undelivered_acks(
+				source_height,
+				sink_height,
+				channel_id,
+				port_id.clone(),
+				source,
+				sink,
+			)
+			.await?;
+			channels.push(json!({
+				"chain": source.name(),
+				"channel_id": channel_id.to_string(),
+				"port_id": port_id.to_string(),
+				"packets": packets,
+				"acks": acks,
+			}));
+		}
+	}
+	Ok(json!({ "path": path.name, "paused": path.control.is_paused(), "channels": channels }))
+}
+
+fn height_json(height: Height) -> Value {
+	json!({
+		"revision_number": height.revision_number,
+		"revision_height": height.revision_height,
+	})
+}
+
+fn internal_error(err: anyhow::Error) -> (StatusCode, String) {
+	(StatusCode::INTERNAL_SERVER_ERROR, format!("{:?}", err))
+}
+
+fn bad_request(err: serde_json::Error) -> (StatusCode, String) {
+	(StatusCode::BAD_REQUEST, err.to_string())
+}
+
+fn respond(status: StatusCode, value: Value) -> Result<Response<Body>, hyper::http::Error> {
+	Response::builder()
+		.status(status)
+		.header("Content-Type", "application/json")
+		.body(Body::from(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cosmos::{CosmosClient, CosmosClientConfig};
+
+	async fn chain(name: &str) -> AnyChain {
+		let config = CosmosClientConfig {
+			name: name.to_string(),
+			// never queried by the handlers under test
+			rpc_url: "http://127.0.0.1:1".to_string(),
+			grpc_url: "http://127.0.0.1:1".to_string(),
+			websocket_url: "ws://127.0.0.1:1/websocket".to_string(),
+			chain_id: format!("{}-1", name),
+			client_id: None,
+			connection_id: None,
+			account_prefix: "cosmos".to_string(),
+			fee_denom: "stake".to_string(),
+			fee_amount: "4000".to_string(),
+			gas_limit: None,
+			store_prefix: "ibc".to_string(),
+			max_tx_size: None,
+			mnemonic: Some(
+				"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+					.to_string(),
+			),
+			key_name: None,
+			keystore_path: None,
+			channel_whitelist: vec![],
+			min_relay_fees: vec![],
+			trusted_rpc_urls: vec![],
+		};
+		AnyChain::Cosmos(CosmosClient::new(config).await.unwrap())
+	}
+
+	async fn api(token: Option<&str>, local: bool) -> Arc<Api> {
+		let path = ApiPath {
+			name: "a-b".to_string(),
+			chain_a: chain("a").await,
+			chain_b: chain("b").await,
+			control: Arc::new(PathControl::default()),
+		};
+		Arc::new(Api { paths: vec![path], token: token.map(str::to_string), local })
+	}
+
+	async fn request(
+		api: &Arc<Api>,
+		method: Method,
+		uri: &str,
+		token: Option<&str>,
+		body: &str,
+	) -> (StatusCode, Value) {
+		let mut req = Request::builder().method(method).uri(uri);
+		if let Some(token) = token {
+			req = req.header(AUTHORIZATION, format!("Bearer {}", token));
+		}
+		let response = handle_request(req.body(Body::from(body.to_string())).unwrap(), api.clone())
+			.await
+			.unwrap();
+		let status = response.status();
+		let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+		(status, serde_json::from_slice(&body).unwrap())
+	}
+
+	#[tokio::test]
+	async fn controlling_a_path_requires_the_api_token() {
+		let api = api(Some("secret"), false).await;
+		let control = &api.paths[0].control;
+
+		for token in [None, Some("wrong")] {
+			let (status, _) = request(&api, Method::POST, "/paths/a-b/pause", token, "").await;
+			assert_eq!(status, StatusCode::UNAUTHORIZED);
+			assert!(!control.is_paused());
+		}
+		let (status, body) =
+			request(&api, Method::POST, "/paths/a-b/pause", Some("secret"), "").await;
+		assert_eq!((status, body), (StatusCode::OK, json!({ "paused": true })));
+		assert!(control.is_paused());
+
+		// reading the state doesn't need the token
+		let (status, body) = request(&api, Method::GET, "/paths", None, "").await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			body,
+			json!([{ "name": "a-b", "chain_a": "a", "chain_b": "b", "paused": true }])
+		);
+		let (status, body) = request(&api, Method::GET, "/health", None, "").await;
+		assert_eq!((status, body), (StatusCode::OK, json!({ "status": "ok" })));
+
+		// resuming sweeps both chains for the packets sent in the meantime
+		let (status, _) =
+			request(&api, Method::POST, "/paths/a-b/resume", Some("secret"), "").await;
+		assert_eq!(status, StatusCode::OK);
+		assert!(!control.is_paused());
+		assert!(control.take_clear_request("a"));
+		assert!(control.take_clear_request("b"));
+	}
+
+	#[tokio::test]
+	async fn paths_are_only_controlled_without_a_token_on_loopback() {
+		let remote = api(None, false).await;
+		let (status, _) = request(&remote, Method::POST, "/paths/a-b/pause", None, "").await;
+		assert_eq!(status, StatusCode::FORBIDDEN);
+		assert!(!remote.paths[0].control.is_paused());
+
+		let local = api(None, true).await;
+		let (status, _) = request(&local, Method::POST, "/paths/a-b/pause", None, "").await;
+		assert_eq!(status, StatusCode::OK);
+		assert!(local.paths[0].control.is_paused());
+	}
+
+	#[tokio::test]
+	async fn control_requests_are_validated() {
+		let api = api(Some("secret"), false).await;
+		let control = &api.paths[0].control;
+		let token = Some("secret");
+
+		let (status, _) = request(&api, Method::POST, "/paths/a-c/pause", token, "").await;
+		assert_eq!(status, StatusCode::NOT_FOUND);
+		let (status, _) =
+			request(&api, Method::POST, "/paths/a-b/clear", token, r#"{"chain": "c"}"#).await;
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		let (status, body) =
+			request(&api, Method::POST, "/paths/a-b/clear", token, r#"{"chain": "b"}"#).await;
+		assert_eq!((status, body), (StatusCode::OK, json!({ "cleared": ["b"] })));
+		assert!(!control.take_clear_request("a"));
+		assert!(control.take_clear_request("b"));
+
+		let (status, _) =
+			request(&api, Method::PUT, "/paths/a-b/channels", token, r#"{"chain": "a"}"#).await;
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		assert_eq!(control.channel_whitelist("a"), None);
+		let whitelist = r#"{"chain": "a", "channels": [["channel-0", "transfer"]]}"#;
+		let (status, _) = request(&api, Method::PUT, "/paths/a-b/channels", token, whitelist).await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			control.channel_whitelist("a"),
+			Some(vec![(ChannelId::new(0), PortId::transfer())])
+		);
+	}
+
+	#[tokio::test]
+	async fn relaying_follows_the_channel_whitelist_set_through_the_api() {
+		let api = api(Some("secret"), false).await;
+		let control = &api.paths[0].control;
+		let (mut chain_a, mut chain_b) =
+			(api.paths[0].chain_a.clone(), api.paths[0].chain_b.clone());
+
+		let whitelist = r#"{"chain": "a", "channels": [["channel-0", "transfer"]]}"#;
+		let (status, _) =
+			request(&api, Method::PUT, "/paths/a-b/channels", Some("secret"), whitelist).await;
+		assert_eq!(status, StatusCode::OK);
+
+		// the relay loop applies the whitelist to the chain it relays from, which the packets are
+		// then queried for
+		control.apply_channel_whitelist(&mut chain_a);
+		control.apply_channel_whitelist(&mut chain_b);
+		assert_eq!(chain_a.channel_whitelist(), vec![(ChannelId::new(0), PortId::transfer())]);
+		assert_eq!(chain_b.channel_whitelist(), vec![]);
+
+		// channels removed from the whitelist are no longer relayed
+		let whitelist = r#"{"chain": "a", "channels": []}"#;
+		let (status, _) =
+			request(&api, Method::PUT, "/paths/a-b/channels", Some("secret"), whitelist).await;
+		assert_eq!(status, StatusCode::OK);
+		control.apply_channel_whitelist(&mut chain_a);
+		assert_eq!(chain_a.channel_whitelist(), vec![]);
+	}
+}
@@ -20,7 +20,7 @@ use futures::Stream;
 #[cfg(any(test, feature = "testing"))]
 use ibc::applications::transfer::msgs::transfer::MsgTransfer;
 use ibc::{
-	applications::transfer::PrefixedCoin,
+	applications::{fee::fee::PacketFee, transfer::PrefixedCoin},
 	core::{
 		ics02_client::client_state::ClientType,
 		ics23_commitment::commitment::CommitmentPrefix,
@@ -38,7 +38,7 @@ use ibc_proto::{
 		channel::v1::{
 			QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
 			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
-			QueryPacketReceiptResponse,
+			QueryPacketReceiptResponse, QueryUpgradeErrorResponse, QueryUpgradeResponse,
 		},
 		client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
 		connection::v1::{IdentifiedConnection, QueryConnectionResponse},
@@ -50,13 +50,16 @@ use pallet_ibc::Timeout;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use cosmos::{CosmosClient, CosmosClientConfig};
 use ibc::core::ics02_client::events::UpdateClient;
+use near::{NearClient, NearClientConfig};
 use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState};
 use parachain::{config, ParachainClient};
 use primitives::{
+	store::{SharedStateStore, SledStore, TransactionStatus},
 	Chain, IbcProvider, KeyProvider, LightClientSync, MisbehaviourHandler, UpdateType,
 };
-use std::{pin::Pin, time::Duration};
+use std::{pin::Pin, sync::Arc, time::Duration};
 #[cfg(feature = "dali")]
 use subxt::config::substrate::{
 	SubstrateExtrinsicParams as ParachainExtrinsicParams,
@@ -115,30 +118,98 @@ pub struct Config {
 	pub core: CoreConfig,
 }
 
+/// Config for relaying many paths from a single process, chains are declared once and can be
+/// shared between any number of paths.
+#[derive(Serialize, Deserialize)]
+pub struct MultiPathConfig {
+	pub chains: Vec<AnyConfig>,
+	pub paths: Vec<PathConfig>,
+	pub core: CoreConfig,
+}
+
+/// A relay path between two of the chains in a [`MultiPathConfig`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PathConfig {
+	/// Name of the path, used in logs and as the `path` label of its metrics.
+	pub name: String,
+	pub chain_a: PathEndConfig,
+	pub chain_b: PathEndConfig,
+	/// Names of the intermediate chains of a multi-hop path, ordered from `chain_a`. The channels
+	/// of a multi-hop path are relayed with proofs through these chains, the ids of its ends are
+	/// those of the connections to the adjacent chains.
+	#[serde(default)]
+	pub hops: Vec<String>,
+}
+
+/// One end of a [`PathConfig`], the ids are those of the light client, connection and channels
+/// on the counterparty chain of this path.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PathEndConfig {
+	/// Name of the chain, must match the name of one of the configured chains.
+	pub chain: String,
+	pub client_id: ClientId,
+	pub connection_id: ConnectionId,
+	#[serde(default)]
+	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum AnyConfig {
 	Parachain(parachain::ParachainClientConfig),
+	Cosmos(CosmosClientConfig),
+	Near(NearClientConfig),
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct CoreConfig {
 	pub prometheus_endpoint: Option<String>,
+	/// Address the HTTP api for monitoring and controlling the relay paths is served on, the api
+	/// is disabled if unset.
+	pub api_endpoint: Option<String>,
+	/// Bearer token required by the api requests that control the relay paths. Without a token
+	/// the paths can only be controlled if `api_endpoint` is a loopback address.
+	pub api_token: Option<String>,
+	/// Path of the database the relayer persists its progress to, so that it can resume from
+	/// where it left off after a restart. Nothing is persisted if unset.
+	pub state_store_path: Option<String>,
+	/// Number of blocks of a chain between sweeps of its channels for pending packets and
+	/// acknowledgements. The channels are swept on every finality notification if unset.
+	pub clear_interval: Option<u64>,
+	/// Fraction of the trusting period of a light client after which it is updated even if there
+	/// are no packets to relay, e.g. `0.66`. Clients are only updated along with packets if unset.
+	pub refresh_threshold: Option<f64>,
+}
+
+impl CoreConfig {
+	/// Opens the configured state store, if any.
+	pub fn state_store(&self) -> anyhow::Result<Option<SharedStateStore>> {
+		Ok(match self.state_store_path.as_ref() {
+			Some(path) => Some(Arc::new(SledStore::open(path)?)),
+			None => None,
+		})
+	}
 }
 
 #[derive(Clone)]
 pub enum AnyChain {
 	Parachain(ParachainClient<DefaultConfig>),
+	Cosmos(CosmosClient),
+	Near(NearClient),
 }
 
-#[derive(From)]
+#[derive(From, Clone)]
 pub enum AnyFinalityEvent {
 	Parachain(parachain::finality_protocol::FinalityEvent),
+	Cosmos(cosmos::provider::FinalityEvent),
+	Near(near::provider::FinalityEvent),
 }
 
-#[derive(From)]
+#[derive(From, Debug)]
 pub enum AnyTransactionId {
 	Parachain(parachain::provider::TransactionId<sp_core::H256>),
+	Cosmos(tendermint::Hash),
+	Near(near::CryptoHash),
 }
 
 #[derive(Error, Debug)]
@@ -146,6 +217,10 @@ pub enum AnyError {
 	#[error("{0}")]
 	Parachain(#[from] parachain::error::Error),
 	#[error("{0}")]
+	Cosmos(#[from] cosmos::error::Error),
+	#[error("{0}")]
+	Near(#[from] near::error::Error),
+	#[error("{0}")]
 	Other(String),
 }
 
@@ -177,6 +252,20 @@ impl IbcProvider for AnyChain {
 					chain.query_latest_ibc_events(finality_event, counterparty).await?;
 				Ok((client_msg, events, update_type))
 			},
+			AnyChain::Cosmos(chain) => {
+				let finality_event = ibc::downcast!(finality_event => AnyFinalityEvent::Cosmos)
+					.ok_or_else(|| AnyError::Other("Invalid finality event type".to_owned()))?;
+				let (client_msg, events, update_type) =
+					chain.query_latest_ibc_events(finality_event, counterparty).await?;
+				Ok((client_msg, events, update_type))
+			},
+			AnyChain::Near(chain) => {
+				let finality_event = ibc::downcast!(finality_event => AnyFinalityEvent::Near)
+					.ok_or_else(|| AnyError::Other("Invalid finality event type".to_owned()))?;
+				let (client_msg, events, update_type) =
+					chain.query_latest_ibc_events(finality_event, counterparty).await?;
+				Ok((client_msg, events, update_type))
+			},
 			_ => unreachable!(),
 		}
 	}
@@ -184,6 +273,8 @@ impl IbcProvider for AnyChain {
 	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
 		match self {
 			Self::Parachain(chain) => chain.ibc_events().await,
+			Self::Cosmos(chain) => chain.ibc_events().await,
+			Self::Near(chain) => chain.ibc_events().await,
 			_ => unreachable!(),
 		}
 	}
@@ -199,6 +290,14 @@ impl IbcProvider for AnyChain {
 				.query_client_consensus(at, client_id, consensus_height)
 				.await
 				.map_err(Into::into),
+			AnyChain::Cosmos(chain) => chain
+				.query_client_consensus(at, client_id, consensus_height)
+				.await
+				.map_err(Into::into),
+			AnyChain::Near(chain) => chain
+				.query_client_consensus(at, client_id, consensus_height)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -211,6 +310,10 @@ impl IbcProvider for AnyChain {
 		match self {
 			AnyChain::Parachain(chain) =>
 				chain.query_client_state(at, client_id).await.map_err(Into::into),
+			AnyChain::Cosmos(chain) =>
+				chain.query_client_state(at, client_id).await.map_err(Into::into),
+			AnyChain::Near(chain) =>
+				chain.query_client_state(at, client_id).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -223,6 +326,10 @@ impl IbcProvider for AnyChain {
 		match self {
 			AnyChain::Parachain(chain) =>
 				chain.query_connection_end(at, connection_id).await.map_err(Into::into),
+			AnyChain::Cosmos(chain) =>
+				chain.query_connection_end(at, connection_id).await.map_err(Into::into),
+			AnyChain::Near(chain) =>
+				chain.query_connection_end(at, connection_id).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -236,6 +343,50 @@ impl IbcProvider for AnyChain {
 		match self {
 			AnyChain::Parachain(chain) =>
 				chain.query_channel_end(at, channel_id, port_id).await.map_err(Into::into),
+			AnyChain::Cosmos(chain) =>
+				chain.query_channel_end(at, channel_id, port_id).await.map_err(Into::into),
+			AnyChain::Near(chain) =>
+				chain.query_channel_end(at, channel_id, port_id).await.map_err(Into::into),
+			_ => unreachable!(),
+		}
+	}
+
+	async fn query_channel_upgrade(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeResponse, Self::Error> {
+		match self {
+			AnyChain::Parachain(chain) =>
+				chain.query_channel_upgrade(at, channel_id, port_id).await.map_err(Into::into),
+			AnyChain::Cosmos(chain) =>
+				chain.query_channel_upgrade(at, channel_id, port_id).await.map_err(Into::into),
+			AnyChain::Near(chain) =>
+				chain.query_channel_upgrade(at, channel_id, port_id).await.map_err(Into::into),
+			_ => unreachable!(),
+		}
+	}
+
+	async fn query_channel_upgrade_error(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeErrorResponse, Self::Error> {
+		match self {
+			AnyChain::Parachain(chain) => chain
+				.query_channel_upgrade_error(at, channel_id, port_id)
+				.await
+				.map_err(Into::into),
+			AnyChain::Cosmos(chain) => chain
+				.query_channel_upgrade_error(at, channel_id, port_id)
+				.await
+				.map_err(Into::into),
+			AnyChain::Near(chain) => chain
+				.query_channel_upgrade_error(at, channel_id, port_id)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -243,6 +394,8 @@ impl IbcProvider for AnyChain {
 	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
 		match self {
 			AnyChain::Parachain(chain) => chain.query_proof(at, keys).await.map_err(Into::into),
+			AnyChain::Cosmos(chain) => chain.query_proof(at, keys).await.map_err(Into::into),
+			AnyChain::Near(chain) => chain.query_proof(at, keys).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -259,6 +412,14 @@ impl IbcProvider for AnyChain {
 				.query_packet_commitment(at, port_id, channel_id, seq)
 				.await
 				.map_err(Into::into),
+			AnyChain::Cosmos(chain) => chain
+				.query_packet_commitment(at, port_id, channel_id, seq)
+				.await
+				.map_err(Into::into),
+			AnyChain::Near(chain) => chain
+				.query_packet_commitment(at, port_id, channel_id, seq)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -275,6 +436,14 @@ impl IbcProvider for AnyChain {
 				.query_packet_acknowledgement(at, port_id, channel_id, seq)
 				.await
 				.map_err(Into::into),
+			AnyChain::Cosmos(chain) => chain
+				.query_packet_acknowledgement(at, port_id, channel_id, seq)
+				.await
+				.map_err(Into::into),
+			AnyChain::Near(chain) => chain
+				.query_packet_acknowledgement(at, port_id, channel_id, seq)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -290,6 +459,14 @@ impl IbcProvider for AnyChain {
 				.query_next_sequence_recv(at, port_id, channel_id)
 				.await
 				.map_err(Into::into),
+			AnyChain::Cosmos(chain) => chain
+				.query_next_sequence_recv(at, port_id, channel_id)
+				.await
+				.map_err(Into::into),
+			AnyChain::Near(chain) => chain
+				.query_next_sequence_recv(at, port_id, channel_id)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -306,6 +483,14 @@ impl IbcProvider for AnyChain {
 				.query_packet_receipt(at, port_id, channel_id, seq)
 				.await
 				.map_err(Into::into),
+			AnyChain::Cosmos(chain) => chain
+				.query_packet_receipt(at, port_id, channel_id, seq)
+				.await
+				.map_err(Into::into),
+			AnyChain::Near(chain) => chain
+				.query_packet_receipt(at, port_id, channel_id, seq)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -314,6 +499,9 @@ impl IbcProvider for AnyChain {
 		match self {
 			AnyChain::Parachain(chain) =>
 				chain.latest_height_and_timestamp().await.map_err(Into::into),
+			AnyChain::Cosmos(chain) =>
+				chain.latest_height_and_timestamp().await.map_err(Into::into),
+			AnyChain::Near(chain) => chain.latest_height_and_timestamp().await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -329,6 +517,14 @@ impl IbcProvider for AnyChain {
 				.query_packet_commitments(at, channel_id, port_id)
 				.await
 				.map_err(Into::into),
+			Self::Cosmos(chain) => chain
+				.query_packet_commitments(at, channel_id, port_id)
+				.await
+				.map_err(Into::into),
+			Self::Near(chain) => chain
+				.query_packet_commitments(at, channel_id, port_id)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -344,6 +540,14 @@ impl IbcProvider for AnyChain {
 				.query_packet_acknowledgements(at, channel_id, port_id)
 				.await
 				.map_err(Into::into),
+			Self::Cosmos(chain) => chain
+				.query_packet_acknowledgements(at, channel_id, port_id)
+				.await
+				.map_err(Into::into),
+			Self::Near(chain) => chain
+				.query_packet_acknowledgements(at, channel_id, port_id)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -360,6 +564,14 @@ impl IbcProvider for AnyChain {
 				.query_unreceived_packets(at, channel_id, port_id, seqs)
 				.await
 				.map_err(Into::into),
+			Self::Cosmos(chain) => chain
+				.query_unreceived_packets(at, channel_id, port_id, seqs)
+				.await
+				.map_err(Into::into),
+			Self::Near(chain) => chain
+				.query_unreceived_packets(at, channel_id, port_id, seqs)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -376,6 +588,14 @@ impl IbcProvider for AnyChain {
 				.query_unreceived_acknowledgements(at, channel_id, port_id, seqs)
 				.await
 				.map_err(Into::into),
+			Self::Cosmos(chain) => chain
+				.query_unreceived_acknowledgements(at, channel_id, port_id, seqs)
+				.await
+				.map_err(Into::into),
+			Self::Near(chain) => chain
+				.query_unreceived_acknowledgements(at, channel_id, port_id, seqs)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -383,6 +603,43 @@ impl IbcProvider for AnyChain {
 	fn channel_whitelist(&self) -> Vec<(ChannelId, PortId)> {
 		match self {
 			Self::Parachain(chain) => chain.channel_whitelist(),
+			Self::Cosmos(chain) => chain.channel_whitelist(),
+			Self::Near(chain) => chain.channel_whitelist(),
+			_ => unreachable!(),
+		}
+	}
+
+	fn set_channel_whitelist(&mut self, channel_whitelist: Vec<(ChannelId, PortId)>) {
+		match self {
+			Self::Parachain(chain) => chain.set_channel_whitelist(channel_whitelist),
+			Self::Cosmos(chain) => chain.set_channel_whitelist(channel_whitelist),
+			Self::Near(chain) => chain.set_channel_whitelist(channel_whitelist),
+			_ => unreachable!(),
+		}
+	}
+
+	async fn query_packet_fees(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error> {
+		match self {
+			Self::Parachain(chain) =>
+				chain.query_packet_fees(port_id, channel_id, sequence).await.map_err(Into::into),
+			Self::Cosmos(chain) =>
+				chain.query_packet_fees(port_id, channel_id, sequence).await.map_err(Into::into),
+			Self::Near(chain) =>
+				chain.query_packet_fees(port_id, channel_id, sequence).await.map_err(Into::into),
+			_ => unreachable!(),
+		}
+	}
+
+	fn min_relay_fees(&self) -> Vec<PrefixedCoin> {
+		match self {
+			Self::Parachain(chain) => chain.min_relay_fees(),
+			Self::Cosmos(chain) => chain.min_relay_fees(),
+			Self::Near(chain) => chain.min_relay_fees(),
 			_ => unreachable!(),
 		}
 	}
@@ -395,6 +652,10 @@ impl IbcProvider for AnyChain {
 		match self {
 			Self::Parachain(chain) =>
 				chain.query_connection_channels(at, connection_id).await.map_err(Into::into),
+			Self::Cosmos(chain) =>
+				chain.query_connection_channels(at, connection_id).await.map_err(Into::into),
+			Self::Near(chain) =>
+				chain.query_connection_channels(at, connection_id).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -408,6 +669,10 @@ impl IbcProvider for AnyChain {
 		match self {
 			Self::Parachain(chain) =>
 				chain.query_send_packets(channel_id, port_id, seqs).await.map_err(Into::into),
+			Self::Cosmos(chain) =>
+				chain.query_send_packets(channel_id, port_id, seqs).await.map_err(Into::into),
+			Self::Near(chain) =>
+				chain.query_send_packets(channel_id, port_id, seqs).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -421,6 +686,10 @@ impl IbcProvider for AnyChain {
 		match self {
 			Self::Parachain(chain) =>
 				chain.query_recv_packets(channel_id, port_id, seqs).await.map_err(Into::into),
+			Self::Cosmos(chain) =>
+				chain.query_recv_packets(channel_id, port_id, seqs).await.map_err(Into::into),
+			Self::Near(chain) =>
+				chain.query_recv_packets(channel_id, port_id, seqs).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -428,6 +697,8 @@ impl IbcProvider for AnyChain {
 	fn expected_block_time(&self) -> Duration {
 		match self {
 			Self::Parachain(chain) => chain.expected_block_time(),
+			Self::Cosmos(chain) => chain.expected_block_time(),
+			Self::Near(chain) => chain.expected_block_time(),
 			_ => unreachable!(),
 		}
 	}
@@ -442,6 +713,14 @@ impl IbcProvider for AnyChain {
 				.query_client_update_time_and_height(client_id, client_height)
 				.await
 				.map_err(Into::into),
+			Self::Cosmos(chain) => chain
+				.query_client_update_time_and_height(client_id, client_height)
+				.await
+				.map_err(Into::into),
+			Self::Near(chain) => chain
+				.query_client_update_time_and_height(client_id, client_height)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -453,6 +732,10 @@ impl IbcProvider for AnyChain {
 		match self {
 			AnyChain::Parachain(chain) =>
 				chain.query_host_consensus_state_proof(height).await.map_err(Into::into),
+			AnyChain::Cosmos(chain) =>
+				chain.query_host_consensus_state_proof(height).await.map_err(Into::into),
+			AnyChain::Near(chain) =>
+				chain.query_host_consensus_state_proof(height).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -460,6 +743,8 @@ impl IbcProvider for AnyChain {
 	async fn query_ibc_balance(&self) -> Result<Vec<PrefixedCoin>, Self::Error> {
 		match self {
 			Self::Parachain(chain) => chain.query_ibc_balance().await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.query_ibc_balance().await.map_err(Into::into),
+			Self::Near(chain) => chain.query_ibc_balance().await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -467,6 +752,8 @@ impl IbcProvider for AnyChain {
 	fn connection_prefix(&self) -> CommitmentPrefix {
 		match self {
 			AnyChain::Parachain(chain) => chain.connection_prefix(),
+			AnyChain::Cosmos(chain) => chain.connection_prefix(),
+			AnyChain::Near(chain) => chain.connection_prefix(),
 			_ => unreachable!(),
 		}
 	}
@@ -474,6 +761,8 @@ impl IbcProvider for AnyChain {
 	fn client_id(&self) -> ClientId {
 		match self {
 			AnyChain::Parachain(chain) => chain.client_id(),
+			AnyChain::Cosmos(chain) => chain.client_id(),
+			AnyChain::Near(chain) => chain.client_id(),
 			_ => unreachable!(),
 		}
 	}
@@ -481,6 +770,8 @@ impl IbcProvider for AnyChain {
 	fn connection_id(&self) -> ConnectionId {
 		match self {
 			AnyChain::Parachain(chain) => chain.connection_id(),
+			AnyChain::Cosmos(chain) => chain.connection_id(),
+			AnyChain::Near(chain) => chain.connection_id(),
 			_ => unreachable!(),
 		}
 	}
@@ -488,6 +779,8 @@ impl IbcProvider for AnyChain {
 	fn client_type(&self) -> ClientType {
 		match self {
 			AnyChain::Parachain(chain) => chain.client_type(),
+			AnyChain::Cosmos(chain) => chain.client_type(),
+			AnyChain::Near(chain) => chain.client_type(),
 			_ => unreachable!(),
 		}
 	}
@@ -496,6 +789,8 @@ impl IbcProvider for AnyChain {
 		match self {
 			Self::Parachain(chain) =>
 				chain.query_timestamp_at(block_number).await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.query_timestamp_at(block_number).await.map_err(Into::into),
+			Self::Near(chain) => chain.query_timestamp_at(block_number).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -503,6 +798,8 @@ impl IbcProvider for AnyChain {
 	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
 		match self {
 			Self::Parachain(chain) => chain.query_clients().await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.query_clients().await.map_err(Into::into),
+			Self::Near(chain) => chain.query_clients().await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -510,6 +807,8 @@ impl IbcProvider for AnyChain {
 	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
 		match self {
 			Self::Parachain(chain) => chain.query_channels().await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.query_channels().await.map_err(Into::into),
+			Self::Near(chain) => chain.query_channels().await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -522,6 +821,10 @@ impl IbcProvider for AnyChain {
 		match self {
 			Self::Parachain(chain) =>
 				chain.query_connection_using_client(height, client_id).await.map_err(Into::into),
+			Self::Cosmos(chain) =>
+				chain.query_connection_using_client(height, client_id).await.map_err(Into::into),
+			Self::Near(chain) =>
+				chain.query_connection_using_client(height, client_id).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -536,6 +839,14 @@ impl IbcProvider for AnyChain {
 				.is_update_required(latest_height, latest_client_height_on_counterparty)
 				.await
 				.map_err(Into::into),
+			Self::Cosmos(chain) => chain
+				.is_update_required(latest_height, latest_client_height_on_counterparty)
+				.await
+				.map_err(Into::into),
+			Self::Near(chain) => chain
+				.is_update_required(latest_height, latest_client_height_on_counterparty)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -544,6 +855,23 @@ impl IbcProvider for AnyChain {
 	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
 		match self {
 			Self::Parachain(chain) => chain.initialize_client_state().await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.initialize_client_state().await.map_err(Into::into),
+			Self::Near(chain) => chain.initialize_client_state().await.map_err(Into::into),
+			_ => unreachable!(),
+		}
+	}
+
+	async fn query_client_upgrade_messages<T>(
+		&self,
+		counterparty: &T,
+	) -> Result<Vec<Any>, anyhow::Error>
+	where
+		T: Chain,
+	{
+		match self {
+			Self::Parachain(chain) => chain.query_client_upgrade_messages(counterparty).await,
+			Self::Cosmos(chain) => chain.query_client_upgrade_messages(counterparty).await,
+			Self::Near(chain) => chain.query_client_upgrade_messages(counterparty).await,
 			_ => unreachable!(),
 		}
 	}
@@ -560,6 +888,20 @@ impl IbcProvider for AnyChain {
 				)
 				.await
 				.map_err(Into::into),
+			Self::Cosmos(chain) => chain
+				.query_client_id_from_tx_hash(
+					downcast!(tx_id => AnyTransactionId::Cosmos)
+						.expect("Should be cosmos transaction id"),
+				)
+				.await
+				.map_err(Into::into),
+			Self::Near(chain) => chain
+				.query_client_id_from_tx_hash(
+					downcast!(tx_id => AnyTransactionId::Near)
+						.expect("Should be near transaction id"),
+				)
+				.await
+				.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -575,6 +917,9 @@ impl MisbehaviourHandler for AnyChain {
 		match self {
 			AnyChain::Parachain(parachain) =>
 				parachain.check_for_misbehaviour(counterparty, client_message).await,
+			AnyChain::Cosmos(cosmos) =>
+				cosmos.check_for_misbehaviour(counterparty, client_message).await,
+			AnyChain::Near(near) => near.check_for_misbehaviour(counterparty, client_message).await,
 			_ => unreachable!(),
 		}
 	}
@@ -584,6 +929,8 @@ impl KeyProvider for AnyChain {
 	fn account_id(&self) -> Signer {
 		match self {
 			AnyChain::Parachain(parachain) => parachain.account_id(),
+			AnyChain::Cosmos(cosmos) => cosmos.account_id(),
+			AnyChain::Near(near) => near.account_id(),
 			_ => unreachable!(),
 		}
 	}
@@ -594,6 +941,8 @@ impl Chain for AnyChain {
 	fn name(&self) -> &str {
 		match self {
 			Self::Parachain(chain) => chain.name(),
+			Self::Cosmos(chain) => chain.name(),
+			Self::Near(chain) => chain.name(),
 			_ => unreachable!(),
 		}
 	}
@@ -601,6 +950,8 @@ impl Chain for AnyChain {
 	fn block_max_weight(&self) -> u64 {
 		match self {
 			Self::Parachain(chain) => chain.block_max_weight(),
+			Self::Cosmos(chain) => chain.block_max_weight(),
+			Self::Near(chain) => chain.block_max_weight(),
 			_ => unreachable!(),
 		}
 	}
@@ -608,6 +959,8 @@ impl Chain for AnyChain {
 	async fn estimate_weight(&self, msg: Vec<Any>) -> Result<u64, Self::Error> {
 		match self {
 			Self::Parachain(chain) => chain.estimate_weight(msg).await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.estimate_weight(msg).await.map_err(Into::into),
+			Self::Near(chain) => chain.estimate_weight(msg).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -620,6 +973,14 @@ impl Chain for AnyChain {
 				use futures::StreamExt;
 				Box::pin(chain.finality_notifications().await.map(|x| x.into()))
 			},
+			Self::Cosmos(chain) => {
+				use futures::StreamExt;
+				Box::pin(chain.finality_notifications().await.map(|x| x.into()))
+			},
+			Self::Near(chain) => {
+				use futures::StreamExt;
+				Box::pin(chain.finality_notifications().await.map(|x| x.into()))
+			},
 			_ => unreachable!(),
 		}
 	}
@@ -631,6 +992,48 @@ impl Chain for AnyChain {
 				.await
 				.map_err(Into::into)
 				.map(|id| AnyTransactionId::Parachain(id)),
+			Self::Cosmos(chain) => chain
+				.submit(messages)
+				.await
+				.map_err(Into::into)
+				.map(|id| AnyTransactionId::Cosmos(id)),
+			Self::Near(chain) => chain
+				.submit(messages)
+				.await
+				.map_err(Into::into)
+				.map(|id| AnyTransactionId::Near(id)),
+			_ => unreachable!(),
+		}
+	}
+
+	async fn broadcast(&self, messages: Vec<Any>) -> Result<String, Self::Error> {
+		match self {
+			Self::Parachain(chain) => chain.broadcast(messages).await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.broadcast(messages).await.map_err(Into::into),
+			Self::Near(chain) => chain.broadcast(messages).await.map_err(Into::into),
+			_ => unreachable!(),
+		}
+	}
+
+	async fn wait_for_transaction(&self, tx_hash: &str) -> Result<(), Self::Error> {
+		match self {
+			Self::Parachain(chain) => chain.wait_for_transaction(tx_hash).await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.wait_for_transaction(tx_hash).await.map_err(Into::into),
+			Self::Near(chain) => chain.wait_for_transaction(tx_hash).await.map_err(Into::into),
+			_ => unreachable!(),
+		}
+	}
+
+	async fn query_transaction_status(
+		&self,
+		tx_hash: &str,
+	) -> Result<TransactionStatus, Self::Error> {
+		match self {
+			Self::Parachain(chain) =>
+				chain.query_transaction_status(tx_hash).await.map_err(Into::into),
+			Self::Cosmos(chain) =>
+				chain.query_transaction_status(tx_hash).await.map_err(Into::into),
+			Self::Near(chain) => chain.query_transaction_status(tx_hash).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -641,6 +1044,17 @@ impl Chain for AnyChain {
 	) -> Result<AnyClientMessage, Self::Error> {
 		match self {
 			Self::Parachain(chain) => chain.query_client_message(update).await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.query_client_message(update).await.map_err(Into::into),
+			Self::Near(chain) => chain.query_client_message(update).await.map_err(Into::into),
+			_ => unreachable!(),
+		}
+	}
+
+	async fn reconnect(&mut self) -> Result<(), Self::Error> {
+		match self {
+			Self::Parachain(chain) => chain.reconnect().await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.reconnect().await.map_err(Into::into),
+			Self::Near(chain) => chain.reconnect().await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -651,6 +1065,8 @@ impl LightClientSync for AnyChain {
 	async fn is_synced<C: Chain>(&self, counterparty: &C) -> Result<bool, anyhow::Error> {
 		match self {
 			Self::Parachain(chain) => chain.is_synced(counterparty).await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.is_synced(counterparty).await.map_err(Into::into),
+			Self::Near(chain) => chain.is_synced(counterparty).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -662,6 +1078,10 @@ impl LightClientSync for AnyChain {
 		match self {
 			Self::Parachain(chain) =>
 				chain.fetch_mandatory_updates(counterparty).await.map_err(Into::into),
+			Self::Cosmos(chain) =>
+				chain.fetch_mandatory_updates(counterparty).await.map_err(Into::into),
+			Self::Near(chain) =>
+				chain.fetch_mandatory_updates(counterparty).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -673,6 +1093,8 @@ impl primitives::TestProvider for AnyChain {
 	async fn send_transfer(&self, params: MsgTransfer<PrefixedCoin>) -> Result<(), Self::Error> {
 		match self {
 			Self::Parachain(chain) => chain.send_transfer(params).await.map_err(Into::into),
+			Self::Cosmos(chain) => chain.send_transfer(params).await.map_err(Into::into),
+			Self::Near(chain) => chain.send_transfer(params).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -685,6 +1107,10 @@ impl primitives::TestProvider for AnyChain {
 		match self {
 			Self::Parachain(chain) =>
 				chain.send_ordered_packet(channel_id, timeout).await.map_err(Into::into),
+			Self::Cosmos(chain) =>
+				chain.send_ordered_packet(channel_id, timeout).await.map_err(Into::into),
+			Self::Near(chain) =>
+				chain.send_ordered_packet(channel_id, timeout).await.map_err(Into::into),
 			_ => unreachable!(),
 		}
 	}
@@ -692,23 +1118,51 @@ impl primitives::TestProvider for AnyChain {
 	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
 		match self {
 			Self::Parachain(chain) => chain.subscribe_blocks().await,
+			Self::Cosmos(chain) => chain.subscribe_blocks().await,
+			Self::Near(chain) => chain.subscribe_blocks().await,
 			_ => unreachable!(),
 		}
 	}
+}
 
-	fn set_channel_whitelist(&mut self, channel_whitelist: Vec<(ChannelId, PortId)>) {
+impl AnyChain {
+	/// Configure this chain for the given end of a relay path.
+	pub fn set_path_end(&mut self, path_end: &PathEndConfig) {
 		match self {
-			Self::Parachain(chain) => chain.set_channel_whitelist(channel_whitelist),
-			_ => unreachable!(),
+			Self::Parachain(chain) => {
+				chain.client_id = Some(path_end.client_id.clone());
+				chain.connection_id = Some(path_end.connection_id.clone());
+				chain.channel_whitelist = path_end.channel_whitelist.clone();
+			},
+			Self::Cosmos(chain) => {
+				chain.client_id = Some(path_end.client_id.clone());
+				chain.connection_id = Some(path_end.connection_id.clone());
+				chain.channel_whitelist = path_end.channel_whitelist.clone();
+			},
+			Self::Near(chain) => {
+				chain.client_id = Some(path_end.client_id.clone());
+				chain.connection_id = Some(path_end.connection_id.clone());
+				chain.channel_whitelist = path_end.channel_whitelist.clone();
+			},
 		}
 	}
 }
 
 impl AnyConfig {
+	pub fn name(&self) -> &str {
+		match self {
+			Self::Parachain(config) => &config.name,
+			Self::Cosmos(config) => &config.name,
+			Self::Near(config) => &config.name,
+		}
+	}
+
 	pub async fn into_client(self) -> anyhow::Result<AnyChain> {
 		Ok(match self {
 			AnyConfig::Parachain(config) =>
 				AnyChain::Parachain(ParachainClient::new(config).await?),
+			AnyConfig::Cosmos(config) => AnyChain::Cosmos(CosmosClient::new(config).await?),
+			AnyConfig::Near(config) => AnyChain::Near(NearClient::new(config).await?),
 		})
 	}
 
@@ -717,6 +1171,12 @@ impl AnyConfig {
 			Self::Parachain(chain) => {
 				chain.client_id.replace(client_id);
 			},
+			Self::Cosmos(chain) => {
+				chain.client_id.replace(client_id);
+			},
+			Self::Near(chain) => {
+				chain.client_id.replace(client_id);
+			},
 		}
 	}
 
@@ -725,6 +1185,12 @@ impl AnyConfig {
 			Self::Parachain(chain) => {
 				chain.connection_id.replace(connection_id);
 			},
+			Self::Cosmos(chain) => {
+				chain.connection_id.replace(connection_id);
+			},
+			Self::Near(chain) => {
+				chain.connection_id.replace(connection_id);
+			},
 		}
 	}
 
@@ -733,6 +1199,12 @@ impl AnyConfig {
 			Self::Parachain(chain) => {
 				chain.channel_whitelist.push((channel_id, port_id));
 			},
+			Self::Cosmos(chain) => {
+				chain.channel_whitelist.push((channel_id, port_id));
+			},
+			Self::Near(chain) => {
+				chain.channel_whitelist.push((channel_id, port_id));
+			},
 		}
 	}
 }
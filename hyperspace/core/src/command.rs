@@ -12,19 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Parser;
 use primitives::Chain;
 use prometheus::Registry;
-use std::{path::PathBuf, str::FromStr, time::Duration};
+use std::{ops::RangeInclusive, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 
-use crate::{chain::Config, fish, relay, Mode};
-use ibc::core::{ics04_channel::channel::Order, ics24_host::identifier::PortId};
+use crate::{
+	api::{self, ApiPath, PathControl},
+	chain::{AnyChain, Config, MultiPathConfig},
+	fish,
+	packets::query_ready_and_timed_out_packets_on_channels,
+	queue, relay, supervisor, Mode,
+};
+use cosmos::key_provider::KeyEntry;
+use ibc::{
+	core::{
+		ics04_channel::channel::Order,
+		ics24_host::identifier::{ChannelId, ClientId, PortId},
+	},
+	signer::Signer,
+};
 use metrics::{data::Metrics, handler::MetricsHandler, init_prometheus};
 use primitives::{
-	utils::{create_channel, create_clients, create_connection},
+	keystore::{self, KeyStore, KeyType},
+	utils::{
+		create_channel, create_clients, create_connection, create_substitute_client,
+		register_counterparty_payee, register_payee,
+	},
 	IbcProvider,
 };
+use sp_core::{ecdsa, ed25519, sr25519, Pair};
 
 #[derive(Debug, Parser)]
 pub struct Cli {
@@ -37,6 +55,11 @@ pub struct Cli {
 pub enum Subcommand {
 	#[clap(name = "relay", about = "Start relaying messages between two chains")]
 	Relay(Cmd),
+	#[clap(
+		name = "relay-paths",
+		about = "Start relaying messages on all the paths listed in a multi-path config"
+	)]
+	RelayPaths(Cmd),
 	#[clap(
 		name = "fish",
 		about = "Start the relayer in fishing mode (catching malicious transactions)"
@@ -48,6 +71,23 @@ pub enum Subcommand {
 	CreateConnection(Cmd),
 	#[clap(name = "create-channel", about = "Creates a channel on the specified port")]
 	CreateChannel(Cmd),
+	#[clap(
+		name = "register-payee",
+		about = "Registers the addresses the relayer is paid its fees to on a channel of chain A"
+	)]
+	RegisterPayee(Cmd),
+	#[clap(
+		name = "recover-client",
+		about = "Creates a substitute client on chain A and prepares the governance proposal to recover a frozen or expired client with it"
+	)]
+	RecoverClient(Cmd),
+	#[clap(
+		name = "clear-packets",
+		about = "Relays the pending packets and acknowledgements of a channel of one of the chains"
+	)]
+	ClearPackets(Cmd),
+	#[clap(name = "keys", about = "Manages the signing keys in the hyperspace keystore")]
+	Keys(KeysCmd),
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -68,6 +108,27 @@ pub struct Cmd {
 	/// Channel version
 	#[clap(long)]
 	version: Option<String>,
+	/// Channel id for payee registration
+	#[clap(long)]
+	channel_id: Option<String>,
+	/// Address on chain A the acknowledgement and timeout fees are paid to
+	#[clap(long)]
+	payee: Option<String>,
+	/// Address on chain B the receive fees are paid to
+	#[clap(long)]
+	counterparty_payee: Option<String>,
+	/// Frozen or expired client on chain A to recover, defaults to chain B's client id
+	#[clap(long)]
+	subject_client_id: Option<String>,
+	/// Name of the chain whose channel is cleared of pending packets
+	#[clap(long)]
+	chain: Option<String>,
+	/// First packet sequence to clear
+	#[clap(long)]
+	start_sequence: Option<u64>,
+	/// Last packet sequence to clear
+	#[clap(long)]
+	end_sequence: Option<u64>,
 	/// New config path to avoid overriding existing configuration
 	#[clap(long)]
 	pub new_config: Option<String>,
@@ -82,6 +143,9 @@ impl Cmd {
 		let config: Config = toml::from_str(&file_content)?;
 		let any_chain_a = config.chain_a.into_client().await?;
 		let any_chain_b = config.chain_b.into_client().await?;
+		let store = config.core.state_store()?;
+		let clear_interval = config.core.clear_interval;
+		let refresh_threshold = config.core.refresh_threshold;
 
 		let registry =
 			Registry::new_custom(None, None).expect("this can only fail if the prefix is empty");
@@ -95,8 +159,38 @@ impl Cmd {
 			tokio::spawn(init_prometheus(addr, registry.clone()));
 		}
 
-		relay(any_chain_a, any_chain_b, Some(metrics_handler_a), Some(metrics_handler_b), None)
-			.await
+		let control = Arc::new(PathControl::default());
+		if let Some(addr) = config.core.api_endpoint.map(|s| s.parse().ok()).flatten() {
+			let path = ApiPath {
+				name: format!("{}-{}", any_chain_a.name(), any_chain_b.name()),
+				chain_a: any_chain_a.clone(),
+				chain_b: any_chain_b.clone(),
+				control: control.clone(),
+			};
+			tokio::spawn(api::serve(addr, config.core.api_token.clone(), vec![path]));
+		}
+
+		relay(
+			any_chain_a,
+			any_chain_b,
+			Some(metrics_handler_a),
+			Some(metrics_handler_b),
+			None,
+			store,
+			clear_interval,
+			refresh_threshold,
+			Some(control),
+		)
+		.await
+	}
+
+	/// Run the relayer on every path of a multi-path config
+	pub async fn run_paths(&self) -> Result<()> {
+		let path: PathBuf = self.config.parse()?;
+		let file_content = tokio::fs::read_to_string(path).await?;
+		let config: MultiPathConfig = toml::from_str(&file_content)?;
+
+		supervisor::supervise(config).await
 	}
 
 	/// Run fisherman
@@ -152,9 +246,19 @@ impl Cmd {
 		let any_chain_a_clone = any_chain_a.clone();
 		let any_chain_b_clone = any_chain_b.clone();
 		let handle = tokio::task::spawn(async move {
-			relay(any_chain_a_clone, any_chain_b_clone, None, None, Some(Mode::Light))
-				.await
-				.unwrap();
+			relay(
+				any_chain_a_clone,
+				any_chain_b_clone,
+				None,
+				None,
+				Some(Mode::Light),
+				None,
+				None,
+				None,
+				None,
+			)
+			.await
+			.unwrap();
 		});
 
 		let (connection_id_a, connection_id_b) =
@@ -192,9 +296,19 @@ impl Cmd {
 		let any_chain_a_clone = any_chain_a.clone();
 		let any_chain_b_clone = any_chain_b.clone();
 		let handle = tokio::task::spawn(async move {
-			relay(any_chain_a_clone, any_chain_b_clone, None, None, Some(Mode::Light))
-				.await
-				.unwrap();
+			relay(
+				any_chain_a_clone,
+				any_chain_b_clone,
+				None,
+				None,
+				Some(Mode::Light),
+				None,
+				None,
+				None,
+				None,
+			)
+			.await
+			.unwrap();
 		});
 
 		let order = Order::from_str(order).expect("Expected one of 'ordered' or 'unordered'");
@@ -216,4 +330,325 @@ impl Cmd {
 
 		Ok(config)
 	}
+
+	pub async fn register_payee(&self) -> Result<()> {
+		let port_id = PortId::from_str(
+			self.port_id
+				.as_ref()
+				.expect("port_id must be specified when registering a payee")
+				.as_str(),
+		)
+		.expect("Port id was invalid");
+		let channel_id = ChannelId::from_str(
+			self.channel_id
+				.as_ref()
+				.expect("channel_id must be specified when registering a payee")
+				.as_str(),
+		)
+		.expect("Channel id was invalid");
+		let path: PathBuf = self.config.parse()?;
+		let file_content = tokio::fs::read_to_string(path).await?;
+		let config: Config = toml::from_str(&file_content)?;
+		let any_chain_a = config.chain_a.into_client().await?;
+
+		if let Some(payee) = self.payee.as_ref() {
+			let payee = Signer::from_str(payee).expect("Payee was invalid");
+			register_payee(&any_chain_a, port_id.clone(), channel_id, payee).await?;
+			log::info!("Registered payee on Chain {}", any_chain_a.name());
+		}
+		if let Some(counterparty_payee) = self.counterparty_payee.clone() {
+			register_counterparty_payee(&any_chain_a, port_id, channel_id, counterparty_payee)
+				.await?;
+			log::info!("Registered counterparty payee on Chain {}", any_chain_a.name());
+		}
+
+		Ok(())
+	}
+
+	/// Creates a substitute client of chain B on chain A and prints the proposal that recovers
+	/// the subject client with it, to be submitted through the admin origin of a parachain or the
+	/// governance module of a cosmos chain.
+	pub async fn recover_client(&self) -> Result<()> {
+		let path: PathBuf = self.config.parse()?;
+		let file_content = tokio::fs::read_to_string(path).await?;
+		let config: Config = toml::from_str(&file_content)?;
+		let any_chain_a = config.chain_a.into_client().await?;
+		let any_chain_b = config.chain_b.into_client().await?;
+		if let AnyChain::Near(_) = any_chain_a {
+			anyhow::bail!("Client recovery is not supported on Chain {}", any_chain_a.name())
+		}
+
+		let subject_client_id = match self.subject_client_id.as_ref() {
+			Some(client_id) =>
+				ClientId::from_str(client_id).expect("Subject client id was invalid"),
+			None => any_chain_b.client_id(),
+		};
+		let substitute_client_id = create_substitute_client(&any_chain_a, &any_chain_b).await?;
+		log::info!(
+			"Substitute ClientId for Chain {} on Chain {}: {}",
+			any_chain_b.name(),
+			any_chain_a.name(),
+			substitute_client_id
+		);
+
+		let proposal = match &any_chain_a {
+			AnyChain::Parachain(_) => format!(
+				"Dispatch `ibc.recoverClient` through the admin origin with\n\
+				subject_client_id: 0x{}\nsubstitute_client_id: 0x{}",
+				hex::encode(subject_client_id.as_bytes()),
+				hex::encode(substitute_client_id.as_bytes())
+			),
+			AnyChain::Cosmos(chain) => format!(
+				r#"{{
+  "messages": [
+    {{
+      "@type": "/ibc.core.client.v1.MsgRecoverClient",
+      "subject_client_id": "{subject_client_id}",
+      "substitute_client_id": "{substitute_client_id}",
+      "signer": "{}"
+    }}
+  ],
+  "metadata": "",
+  "deposit": "",
+  "title": "Recover client {subject_client_id}",
+  "summary": "Replace the state of client {subject_client_id} with that of client {substitute_client_id}"
+}}"#,
+				chain.module_address("gov")?
+			),
+			AnyChain::Near(_) => unreachable!(),
+		};
+		log::info!("Recovery proposal for Chain {}:\n{}", any_chain_a.name(), proposal);
+
+		Ok(())
+	}
+	/// Relays the packets sent and the acknowledgements written on a channel of the given chain
+	/// that haven't been delivered to its counterparty yet, and the timeouts of its packets.
+	pub async fn clear_packets(&self) -> Result<()> {
+		let (chain, channel_id, port_id, sequences) = self.packets_to_clear()?;
+		let path: PathBuf = self.config.parse()?;
+		let file_content = tokio::fs::read_to_string(path).await?;
+		let config: Config = toml::from_str(&file_content)?;
+		let any_chain_a = config.chain_a.into_client().await?;
+		let any_chain_b = config.chain_b.into_client().await?;
+
+		let (source, sink) = if any_chain_a.name() == chain {
+			(any_chain_a, any_chain_b)
+		} else if any_chain_b.name() == chain {
+			(any_chain_b, any_chain_a)
+		} else {
+			anyhow::bail!(
+				"Unknown chain {}, expected one of {} or {}",
+				chain,
+				any_chain_a.name(),
+				any_chain_b.name()
+			)
+		};
+		let (messages, timeouts) = query_ready_and_timed_out_packets_on_channels(
+			&source,
+			&sink,
+			None,
+			vec![(channel_id, port_id)],
+			sequences,
+		)
+		.await?;
+		if messages.is_empty() && timeouts.is_empty() {
+			log::info!("No pending packets on channel {} of Chain {}", channel_id, source.name());
+			return Ok(())
+		}
+		if !timeouts.is_empty() {
+			log::info!("Submitting {} timeout messages to {}", timeouts.len(), source.name());
+			queue::flush_message_batch(timeouts, None, &source, None).await?;
+		}
+		if !messages.is_empty() {
+			log::info!("Submitting {} packet messages to {}", messages.len(), sink.name());
+			queue::flush_message_batch(messages, None, &sink, None).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Returns the chain, channel and range of sequences selected by the arguments of the
+	/// `clear-packets` command.
+	fn packets_to_clear(&self) -> Result<(&str, ChannelId, PortId, Option<RangeInclusive<u64>>)> {
+		let chain = self
+			.chain
+			.as_deref()
+			.ok_or_else(|| anyhow!("chain must be specified when clearing packets"))?;
+		let channel_id = self
+			.channel_id
+			.as_deref()
+			.ok_or_else(|| anyhow!("channel_id must be specified when clearing packets"))?;
+		let channel_id = ChannelId::from_str(channel_id)
+			.map_err(|e| anyhow!("Channel id {} was invalid: {}", channel_id, e))?;
+		let port_id = self
+			.port_id
+			.as_deref()
+			.ok_or_else(|| anyhow!("port_id must be specified when clearing packets"))?;
+		let port_id = PortId::from_str(port_id)
+			.map_err(|e| anyhow!("Port id {} was invalid: {}", port_id, e))?;
+		let sequences = match (self.start_sequence, self.end_sequence) {
+			(None, None) => None,
+			(start, end) => {
+				let sequences = start.unwrap_or(1)..=end.unwrap_or(u64::MAX);
+				if sequences.is_empty() {
+					anyhow::bail!(
+						"start_sequence {} is greater than end_sequence {}",
+						sequences.start(),
+						sequences.end()
+					)
+				}
+				Some(sequences)
+			},
+		};
+		Ok((chain, channel_id, port_id, sequences))
+	}
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct KeysCmd {
+	/// Keystore directory, defaults to `$HYPERSPACE_KEYSTORE` or `~/.hyperspace/keys`. Chains
+	/// signing with a key from another keystore must set its directory as their `keystore_path`.
+	#[clap(long)]
+	keystore: Option<String>,
+	#[clap(subcommand)]
+	subcommand: KeysSubcommand,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub enum KeysSubcommand {
+	#[clap(name = "add", about = "Generates a new key and stores it in the keystore")]
+	Add(KeyArgs),
+	#[clap(
+		name = "import",
+		about = "Imports a key from a BIP-39 mnemonic, or a secret uri for substrate key types"
+	)]
+	Import(KeyArgs),
+	#[clap(name = "list", about = "Lists the keys in the keystore")]
+	List,
+	#[clap(name = "delete", about = "Deletes a key from the keystore")]
+	Delete {
+		/// Name of the key
+		#[clap(long)]
+		name: String,
+	},
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct KeyArgs {
+	/// Name of the key, as referred to by the `key_name` of chain configs
+	#[clap(long)]
+	name: String,
+	/// Key type, one of sr25519, ed25519, ecdsa or secp256k1 (cosmos)
+	#[clap(long, default_value = "sr25519")]
+	key_type: String,
+}
+
+impl KeysCmd {
+	/// Run the command
+	pub fn run(&self) -> Result<()> {
+		let keystore = KeyStore::open_or_default(self.keystore.as_ref())?;
+		match &self.subcommand {
+			KeysSubcommand::Add(args) => {
+				let key_type = KeyType::from_str(&args.key_type)?;
+				// a BIP-39 mnemonic is a valid secret for all key types
+				let (_, phrase, _) = sr25519::Pair::generate_with_phrase(None);
+				let public_key = public_key(key_type, &phrase)?;
+				let passphrase = keystore::passphrase(true)?;
+				keystore.add(&args.name, key_type, &public_key, &phrase, &passphrase)?;
+				println!("Added {} key {}: 0x{}", key_type, args.name, hex::encode(public_key));
+				println!("Mnemonic, write it down and keep it safe:\n{}", phrase);
+			},
+			KeysSubcommand::Import(args) => {
+				let key_type = KeyType::from_str(&args.key_type)?;
+				let secret = keystore::prompt_secret("Mnemonic or secret uri: ")?;
+				let public_key = public_key(key_type, secret.trim())?;
+				let passphrase = keystore::passphrase(true)?;
+				keystore.add(&args.name, key_type, &public_key, secret.trim(), &passphrase)?;
+				println!("Imported {} key {}: 0x{}", key_type, args.name, hex::encode(public_key));
+			},
+			KeysSubcommand::List =>
+				for key in keystore.list()? {
+					println!("{}\t{}\t0x{}", key.name, key.key_type, key.public_key);
+				},
+			KeysSubcommand::Delete { name } => {
+				keystore.delete(name)?;
+				println!("Deleted key {}", name);
+			},
+		}
+		Ok(())
+	}
+}
+
+/// Derives the public key of the secret, which also checks that it's valid for the key type.
+fn public_key(key_type: KeyType, secret: &str) -> Result<Vec<u8>> {
+	let invalid = || anyhow!("Invalid secret for a {} key", key_type);
+	Ok(match key_type {
+		KeyType::Sr25519 => sr25519::Pair::from_string(secret, None)
+			.map_err(|_| invalid())?
+			.public()
+			.0
+			.to_vec(),
+		KeyType::Ed25519 => ed25519::Pair::from_string(secret, None)
+			.map_err(|_| invalid())?
+			.public()
+			.0
+			.to_vec(),
+		KeyType::Ecdsa => ecdsa::Pair::from_string(secret, None)
+			.map_err(|_| invalid())?
+			.public()
+			.0
+			.to_vec(),
+		// the account prefix doesn't affect the public key
+		KeyType::Secp256k1 => KeyEntry::from_mnemonic(secret, "cosmos")?.public_key,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn clear_packets_cmd(args: &[&str]) -> Cmd {
+		Cmd::try_parse_from(["clear-packets", "--config", "config.toml"].iter().chain(args))
+			.unwrap()
+	}
+
+	#[test]
+	fn clear_packets_rejects_missing_and_invalid_arguments() {
+		let (chain, channel_id, port_id, sequences) = clear_packets_cmd(&[
+			"--chain",
+			"centauri",
+			"--channel-id",
+			"channel-3",
+			"--port-id",
+			"transfer",
+			"--start-sequence",
+			"5",
+		])
+		.packets_to_clear()
+		.unwrap();
+		assert_eq!(chain, "centauri");
+		assert_eq!(channel_id, ChannelId::new(3));
+		assert_eq!(port_id, PortId::transfer());
+		assert_eq!(sequences, Some(5..=u64::MAX));
+
+		let selected =
+			["--chain", "centauri", "--channel-id", "channel-3", "--port-id", "transfer"];
+		assert_eq!(clear_packets_cmd(&selected).packets_to_clear().unwrap().3, None);
+		for missing in [0, 2, 4] {
+			let mut args = selected.to_vec();
+			args.drain(missing..missing + 2);
+			assert!(clear_packets_cmd(&args).packets_to_clear().is_err(), "{:?}", args);
+		}
+
+		let mut invalid_channel = selected;
+		invalid_channel[3] = "channel";
+		assert!(clear_packets_cmd(&invalid_channel).packets_to_clear().is_err());
+		let mut invalid_port = selected;
+		invalid_port[5] = "a";
+		assert!(clear_packets_cmd(&invalid_port).packets_to_clear().is_err());
+
+		let mut empty_range = selected.to_vec();
+		empty_range.extend(["--start-sequence", "5", "--end-sequence", "4"]);
+		assert!(clear_packets_cmd(&empty_range).packets_to_clear().is_err());
+	}
 }
@@ -27,14 +27,21 @@ use ibc::{
 			},
 		},
 		ics04_channel::{
-			channel::{ChannelEnd, Counterparty as ChannelCounterparty},
+			channel::{ChannelEnd, Counterparty as ChannelCounterparty, State},
+			events::{FlushComplete, UpgradeConfirm, UpgradeOpen},
 			msgs::{
 				acknowledgement::MsgAcknowledgement, chan_close_confirm::MsgChannelCloseConfirm,
 				chan_open_ack::MsgChannelOpenAck, chan_open_confirm::MsgChannelOpenConfirm,
-				chan_open_try::MsgChannelOpenTry, recv_packet::MsgRecvPacket,
+				chan_open_try::MsgChannelOpenTry, chan_upgrade_ack::MsgChannelUpgradeAck,
+				chan_upgrade_cancel::MsgChannelUpgradeCancel,
+				chan_upgrade_confirm::MsgChannelUpgradeConfirm,
+				chan_upgrade_open::MsgChannelUpgradeOpen, chan_upgrade_try::MsgChannelUpgradeTry,
+				recv_packet::MsgRecvPacket,
 			},
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes},
+		ics24_host::identifier::{ChannelId, PortId},
 	},
 	events::{IbcEvent, IbcEventType},
 	proofs::{ConsensusProof, Proofs},
@@ -43,7 +50,7 @@ use ibc::{
 };
 use ibc_proto::google::protobuf::Any;
 use pallet_ibc::light_clients::AnyClientState;
-use primitives::{error::Error, mock::LocalClientTypes, Chain};
+use primitives::{error::Error, mock::LocalClientTypes, store::StateStore, Chain};
 use tendermint_proto::Protobuf;
 
 /// Connection proof type
@@ -56,11 +63,13 @@ pub struct ConnectionProof {
 /// This parses events coming from a source chain
 /// Returns a tuple of messages, with the first item being packets that are ready to be sent to the
 /// sink chain. And the second item being packet timeouts that should be sent to the source.
+#[tracing::instrument(skip_all, fields(chain = source.name(), counterparty = sink.name()))]
 pub async fn parse_events(
 	source: &mut impl Chain,
 	sink: &mut impl Chain,
 	events: Vec<IbcEvent>,
 	mode: Option<Mode>,
+	store: Option<&dyn StateStore>,
 ) -> Result<(Vec<Any>, Vec<Any>), anyhow::Error> {
 	let mut messages = vec![];
 	// 1. translate events to messages
@@ -289,6 +298,10 @@ pub async fn parse_events(
 							))
 						})?)
 						.expect("Channel end decoding should not fail");
+					// multi-hop channels are relayed by the multi-hop path they belong to
+					if channel_end.connection_hops.len() > 1 {
+						continue
+					}
 					let counterparty = channel_end.counterparty();
 					// Construct the channel end as we expect it to be constructed on the
 					// receiving chain
@@ -335,6 +348,9 @@ pub async fn parse_events(
 							))
 						})?)
 						.expect("Channel end decoding should not fail");
+					if channel_end.connection_hops.len() > 1 {
+						continue
+					}
 					let counterparty = channel_end.counterparty();
 					let channel_proof = CommitmentProofBytes::try_from(channel_response.proof)?;
 
@@ -370,6 +386,9 @@ pub async fn parse_events(
 								channel_id, open_ack.port_id
 							))
 						})?)?;
+					if channel_end.connection_hops.len() > 1 {
+						continue
+					}
 					let counterparty = channel_end.counterparty();
 					let channel_proof = CommitmentProofBytes::try_from(channel_response.proof)?;
 
@@ -402,6 +421,9 @@ pub async fn parse_events(
 							channel_id, close_init.port_id
 						))
 					})?)?;
+				if channel_end.connection_hops.len() > 1 {
+					continue
+				}
 				let counterparty = channel_end.counterparty();
 				let channel_proof = CommitmentProofBytes::try_from(channel_response.proof)?;
 
@@ -422,6 +444,207 @@ pub async fn parse_events(
 				let msg = Any { value, type_url: msg.type_url() };
 				messages.push(msg)
 			},
+			IbcEvent::UpgradeInitChannel(upgrade_init) => {
+				let (channel_end, upgrade, proofs) = query_channel_upgrade_with_proofs(
+					source,
+					upgrade_init.height(),
+					upgrade_init.port_id.clone(),
+					upgrade_init.channel_id,
+				)
+				.await?;
+				// The counterparty proposes its own end of the connection the channel moves to
+				let connection_id =
+					upgrade.fields.connection_hops.get(0).cloned().ok_or_else(|| {
+						Error::Custom("Channel upgrade missing connection id".to_string())
+					})?;
+				let connection_response = source
+					.query_connection_end(upgrade_init.height(), connection_id.clone())
+					.await?;
+				let connection_end =
+					ConnectionEnd::try_from(connection_response.connection.ok_or_else(|| {
+						Error::Custom(format!(
+							"[get_messages_for_events - upgrade_chan_init] ConnectionEnd not found for {:?}",
+							connection_id
+						))
+					})?)?;
+				let counterparty_connection_id =
+					connection_end.counterparty().connection_id().cloned().ok_or_else(|| {
+						Error::Custom("Expected counterparty connection id to be set".to_string())
+					})?;
+				let counterparty = channel_end.counterparty();
+
+				let msg = MsgChannelUpgradeTry {
+					port_id: counterparty.port_id.clone(),
+					channel_id: counterparty.channel_id.expect("Expect channel id to be set"),
+					proposed_upgrade_connection_hops: vec![counterparty_connection_id],
+					counterparty_upgrade_fields: upgrade.fields,
+					counterparty_upgrade_sequence: channel_end.upgrade_sequence(),
+					proofs,
+					signer: sink.account_id(),
+				};
+
+				let value = msg.encode_vec()?;
+				let msg = Any { value, type_url: msg.type_url() };
+				messages.push(msg)
+			},
+			IbcEvent::UpgradeTryChannel(upgrade_try) => {
+				let (channel_end, upgrade, proofs) = query_channel_upgrade_with_proofs(
+					source,
+					upgrade_try.height(),
+					upgrade_try.port_id.clone(),
+					upgrade_try.channel_id,
+				)
+				.await?;
+				let counterparty = channel_end.counterparty();
+
+				let msg = MsgChannelUpgradeAck {
+					port_id: counterparty.port_id.clone(),
+					channel_id: counterparty.channel_id.expect("Expect channel id to be set"),
+					counterparty_upgrade: upgrade,
+					proofs,
+					signer: sink.account_id(),
+				};
+
+				let value = msg.encode_vec()?;
+				let msg = Any { value, type_url: msg.type_url() };
+				messages.push(msg)
+			},
+			IbcEvent::UpgradeAckChannel(upgrade_ack) => {
+				let (channel_end, upgrade, proofs) = query_channel_upgrade_with_proofs(
+					source,
+					upgrade_ack.height(),
+					upgrade_ack.port_id.clone(),
+					upgrade_ack.channel_id,
+				)
+				.await?;
+				let counterparty = channel_end.counterparty();
+
+				let msg = MsgChannelUpgradeConfirm {
+					port_id: counterparty.port_id.clone(),
+					channel_id: counterparty.channel_id.expect("Expect channel id to be set"),
+					counterparty_channel_state: channel_end.state,
+					counterparty_upgrade: upgrade,
+					proofs,
+					signer: sink.account_id(),
+				};
+
+				let value = msg.encode_vec()?;
+				let msg = Any { value, type_url: msg.type_url() };
+				messages.push(msg)
+			},
+			IbcEvent::UpgradeConfirmChannel(UpgradeConfirm {
+				height, port_id, channel_id, ..
+			}) |
+			IbcEvent::FlushCompleteChannel(FlushComplete {
+				height, port_id, channel_id, ..
+			}) |
+			IbcEvent::UpgradeOpenChannel(UpgradeOpen { height, port_id, channel_id, .. }) => {
+				let channel_response =
+					source.query_channel_end(height, channel_id, port_id.clone()).await?;
+				let channel_end =
+					ChannelEnd::try_from(channel_response.channel.ok_or_else(|| {
+						Error::Custom(format!(
+							"[get_messages_for_events - upgrade_chan_open] ChannelEnd not found for {:?}/{:?}",
+							channel_id, port_id
+						))
+					})?)?;
+				if !matches!(channel_end.state, State::FlushComplete | State::Open) {
+					continue
+				}
+				let counterparty = channel_end.counterparty();
+				let counterparty_channel_id =
+					counterparty.channel_id.expect("Expect channel id to be set");
+				// The counterparty can only open the upgraded channel once it flushed its own
+				// in-flight packets
+				let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+				let sink_channel_response = sink
+					.query_channel_end(
+						sink_height,
+						counterparty_channel_id,
+						counterparty.port_id.clone(),
+					)
+					.await?;
+				let sink_channel_state = sink_channel_response
+					.channel
+					.map(ChannelEnd::try_from)
+					.transpose()?
+					.map(|channel| channel.state);
+				if sink_channel_state != Some(State::FlushComplete) {
+					continue
+				}
+				let channel_proof = CommitmentProofBytes::try_from(channel_response.proof)?;
+
+				let proof_height =
+					channel_response.proof_height.expect("Proof height should be present");
+				let proof_height =
+					Height::new(proof_height.revision_number, proof_height.revision_height);
+
+				let msg = MsgChannelUpgradeOpen {
+					port_id: counterparty.port_id.clone(),
+					channel_id: counterparty_channel_id,
+					counterparty_channel_state: channel_end.state,
+					counterparty_upgrade_sequence: channel_end.upgrade_sequence(),
+					proofs: Proofs::new(channel_proof, None, None, None, proof_height)?,
+					signer: sink.account_id(),
+				};
+
+				let value = msg.encode_vec()?;
+				let msg = Any { value, type_url: msg.type_url() };
+				messages.push(msg)
+			},
+			IbcEvent::UpgradeErrorChannel(upgrade_error) => {
+				let counterparty_port_id = upgrade_error.counterparty_port_id.clone();
+				let counterparty_channel_id = match upgrade_error.counterparty_channel_id {
+					Some(channel_id) => channel_id,
+					None => continue,
+				};
+				// Nothing to cancel if the counterparty has no upgrade in progress
+				let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+				let has_upgrade = sink
+					.query_channel_upgrade(
+						sink_height,
+						counterparty_channel_id,
+						counterparty_port_id.clone(),
+					)
+					.await
+					.map(|response| response.upgrade.is_some())
+					.unwrap_or(false);
+				if !has_upgrade {
+					continue
+				}
+				let error_response = source
+					.query_channel_upgrade_error(
+						upgrade_error.height(),
+						upgrade_error.channel_id,
+						upgrade_error.port_id.clone(),
+					)
+					.await?;
+				let error_receipt =
+					ErrorReceipt::from(error_response.error_receipt.ok_or_else(|| {
+						Error::Custom(format!(
+							"[get_messages_for_events - upgrade_chan_error] Error receipt not found for {:?}/{:?}",
+							upgrade_error.channel_id, upgrade_error.port_id
+						))
+					})?);
+				let error_proof = CommitmentProofBytes::try_from(error_response.proof)?;
+
+				let proof_height =
+					error_response.proof_height.expect("Proof height should be present");
+				let proof_height =
+					Height::new(proof_height.revision_number, proof_height.revision_height);
+
+				let msg = MsgChannelUpgradeCancel {
+					port_id: counterparty_port_id,
+					channel_id: counterparty_channel_id,
+					error_receipt,
+					proofs: Proofs::new(error_proof, None, None, None, proof_height)?,
+					signer: sink.account_id(),
+				};
+
+				let value = msg.encode_vec()?;
+				let msg = Any { value, type_url: msg.type_url() };
+				messages.push(msg)
+			},
 			IbcEvent::SendPacket(send_packet) => {
 				#[cfg(feature = "testing")]
 				if !packet_relay_status() {
@@ -442,6 +665,9 @@ pub async fn parse_events(
 							"Failed to convert to concrete channel end from raw channel end",
 						))
 					})?)?;
+				if channel_end.connection_hops.len() > 1 {
+					continue
+				}
 				let connection_id = channel_end
 					.connection_hops
 					.get(0)
@@ -475,6 +701,13 @@ pub async fn parse_events(
 					proofs: Proofs::new(commitment_proof, None, None, None, proof_height)?,
 					signer: sink.account_id(),
 				};
+				tracing::info!(
+					channel = %channel_id,
+					port = %port_id,
+					sequence = seq,
+					"Relaying packet sent on {}",
+					source.name()
+				);
 
 				let value = msg.encode_vec()?;
 				let msg = Any { value, type_url: msg.type_url() };
@@ -492,6 +725,9 @@ pub async fn parse_events(
 							"Failed to convert to concrete channel end from raw channel end",
 						))
 					})?)?;
+				if channel_end.connection_hops.len() > 1 {
+					continue
+				}
 				let connection_id = channel_end
 					.connection_hops
 					.get(0)
@@ -528,6 +764,13 @@ pub async fn parse_events(
 
 					signer: sink.account_id(),
 				};
+				tracing::info!(
+					channel = %channel_id,
+					port = %port_id,
+					sequence = seq,
+					"Relaying acknowledgement written on {}",
+					source.name()
+				);
 
 				let value = msg.encode_vec()?;
 				let msg = Any { value, type_url: msg.type_url() };
@@ -544,7 +787,7 @@ pub async fn parse_events(
 
 	// 2. query packets that can now be sent, at this sink height because of connection delay.
 	let (ready_packets, timed_out_packets) =
-		query_ready_and_timed_out_packets(source, sink).await?;
+		query_ready_and_timed_out_packets(source, sink, store).await?;
 	messages.extend(ready_packets);
 
 	Ok((messages, timed_out_packets))
@@ -567,6 +810,35 @@ async fn query_host_consensus_state_proof(
 	Ok(host_consensus_state_proof)
 }
 
+/// Query the channel end and the upgrade proposed for it, with the proofs of both at the same
+/// height.
+async fn query_channel_upgrade_with_proofs(
+	source: &impl Chain,
+	at: Height,
+	port_id: PortId,
+	channel_id: ChannelId,
+) -> Result<(ChannelEnd, Upgrade, Proofs), anyhow::Error> {
+	let channel_response = source.query_channel_end(at, channel_id, port_id.clone()).await?;
+	let channel_end = ChannelEnd::try_from(channel_response.channel.ok_or_else(|| {
+		Error::Custom(format!("ChannelEnd not found for {:?}/{:?}", channel_id, port_id))
+	})?)?;
+	let upgrade_response = source.query_channel_upgrade(at, channel_id, port_id.clone()).await?;
+	let upgrade = Upgrade::try_from(upgrade_response.upgrade.ok_or_else(|| {
+		Error::Custom(format!("Channel upgrade not found for {:?}/{:?}", channel_id, port_id))
+	})?)?;
+
+	let proof_height = channel_response.proof_height.expect("Proof height should be present");
+	let proofs = Proofs::new(
+		CommitmentProofBytes::try_from(channel_response.proof)?,
+		None,
+		None,
+		Some(CommitmentProofBytes::try_from(upgrade_response.proof)?),
+		Height::new(proof_height.revision_number, proof_height.revision_height),
+	)?;
+
+	Ok((channel_end, upgrade, proofs))
+}
+
 pub fn has_packet_events(event_types: &[IbcEventType]) -> bool {
 	event_types
 		.into_iter()
@@ -15,19 +15,32 @@
 #![warn(unused_variables)]
 
 use futures::{future::ready, StreamExt};
-use primitives::Chain;
+use primitives::{
+	store::{SharedStateStore, StateStore},
+	Chain,
+};
 
+pub mod api;
 pub mod chain;
 pub mod command;
 pub mod events;
 pub mod logging;
 mod macros;
+pub mod multihop;
 pub mod packets;
 pub mod queue;
+pub mod recovery;
+pub mod refresh;
+pub mod retry;
+pub mod supervisor;
 
+use api::PathControl;
 use events::{has_packet_events, parse_events};
 use ibc::events::IbcEvent;
 use metrics::handler::MetricsHandler;
+use retry::{Backoff, Retry};
+use std::sync::Arc;
+use tracing::Instrument;
 
 #[derive(Copy, Debug, Clone)]
 pub enum Mode {
@@ -43,60 +56,151 @@ pub async fn relay<A, B>(
 	mut chain_a_metrics: Option<MetricsHandler>,
 	mut chain_b_metrics: Option<MetricsHandler>,
 	mode: Option<Mode>,
+	store: Option<SharedStateStore>,
+	clear_interval: Option<u64>,
+	refresh_threshold: Option<f64>,
+	control: Option<Arc<PathControl>>,
 ) -> Result<(), anyhow::Error>
 where
 	A: Chain,
 	B: Chain,
 {
+	if let Some(store) = store.as_ref() {
+		for chain in [chain_a.name(), chain_b.name()] {
+			if let Some(height) = store.last_processed_height(chain)? {
+				log::info!(target: "hyperspace", "Resuming {} from height {}", chain, height);
+			}
+		}
+		// submissions interrupted by a previous shutdown
+		recovery::resolve_pending_transactions(&chain_a, &**store).await?;
+		recovery::resolve_pending_transactions(&chain_b, &**store).await?;
+	}
+
 	let (mut chain_a_finality, mut chain_b_finality) =
 		(chain_a.finality_notifications().await, chain_b.finality_notifications().await);
 
+	let (mut chain_a_backoff, mut chain_b_backoff) = (Backoff::default(), Backoff::default());
+
 	// If light clients on both chains are not synced then send the old updates and events before
 	// listening for new events
-	if !chain_a.is_synced(&chain_b).await? {
-		let (mut messages, events) = chain_a.fetch_mandatory_updates(&chain_b).await?;
-		// we use light mode because channel state will be queried during the full relay operation
-		let (parsed_messages, ..) =
-			parse_events(&mut chain_a, &mut chain_b, events, Some(Mode::Light)).await?;
-		messages.extend(parsed_messages);
-		log::info!(target: "hyperspace",
-			"Syncing Chain {}'s light client on chain {} {:#?}",
-			chain_a.name(),
-				chain_b.name(),
-			messages.iter().map(|msg| &msg.type_url).collect::<Vec<_>>()
-		);
-		queue::flush_message_batch(messages, chain_a_metrics.as_ref(), &chain_b).await?;
+	while let Err(err) =
+		sync_light_client(&mut chain_a, &mut chain_b, chain_a_metrics.as_ref(), store.as_deref())
+			.await
+	{
+		if !chain_a_backoff.on_error(chain_a.name(), err)? {
+			chain_a_backoff.schedule();
+		}
+		chain_a_backoff.elapsed().await;
+	}
+	chain_a_backoff.reset();
+	while let Err(err) =
+		sync_light_client(&mut chain_b, &mut chain_a, chain_b_metrics.as_ref(), store.as_deref())
+			.await
+	{
+		if !chain_b_backoff.on_error(chain_b.name(), err)? {
+			chain_b_backoff.schedule();
+		}
+		chain_b_backoff.elapsed().await;
 	}
+	chain_b_backoff.reset();
 
-	if !chain_b.is_synced(&chain_a).await? {
-		let (mut messages, events) = chain_b.fetch_mandatory_updates(&chain_a).await?;
-		// we use light mode because channel state will be queried during the full relay operation
-		let (parsed_messages, ..) =
-			parse_events(&mut chain_b, &mut chain_a, events, Some(Mode::Light)).await?;
-		messages.extend(parsed_messages);
-		log::info!(target: "hyperspace",
-			"Syncing Chain {}'s light client on chain {} {:#?}",
-			chain_b.name(),
-				chain_a.name(),
-			messages.iter().map(|msg| &msg.type_url).collect::<Vec<_>>()
-		);
-		queue::flush_message_batch(messages, chain_b_metrics.as_ref(), &chain_a).await?;
+	if let Some(store) = store.as_deref() {
+		recovery::resume_in_flight_packets(&chain_a, &chain_b, store, chain_a_metrics.as_ref())
+			.await?;
+		recovery::resume_in_flight_packets(&chain_b, &chain_a, store, chain_b_metrics.as_ref())
+			.await?;
 	}
 
+	// heights at which the channels of each chain were last swept for pending packets
+	let (mut chain_a_cleared_at, mut chain_b_cleared_at) = (None, None);
+	// work on each chain that's waiting for its backoff to elapse
+	let (mut chain_a_retry, mut chain_b_retry) = (None, None);
+
 	// loop forever
 	loop {
 		tokio::select! {
 			// new finality event from chain A
-			result  = chain_a_finality.next() => {
-				process_finality_event!(chain_a, chain_b, chain_a_metrics, mode, result)
+			result = chain_a_finality.next(), if !matches!(chain_a_retry, Some(Retry::Resubscribe)) => {
+				match result {
+					// stream closed, most likely because the connection to the chain was lost
+					None => {
+						log::warn!("Finality notifications from {} ended, re-subscribing in {:?}", chain_a.name(), chain_a_backoff.schedule());
+						chain_a_retry = Some(Retry::Resubscribe);
+					},
+					// a newer event covers the one waiting to be retried
+					Some(finality_event) if chain_a_backoff.is_scheduled() => chain_a_retry = Some(Retry::FinalityEvent(finality_event)),
+					Some(finality_event) => process_finality_event!(chain_a, chain_b, chain_a_metrics, mode, store, clear_interval, refresh_threshold, chain_a_cleared_at, control, chain_a_backoff, chain_a_retry, finality_event),
+				}
 			}
 			// new finality event from chain B
-			result = chain_b_finality.next() => {
-				process_finality_event!(chain_b, chain_a, chain_b_metrics, mode, result)
+			result = chain_b_finality.next(), if !matches!(chain_b_retry, Some(Retry::Resubscribe)) => {
+				match result {
+					None => {
+						log::warn!("Finality notifications from {} ended, re-subscribing in {:?}", chain_b.name(), chain_b_backoff.schedule());
+						chain_b_retry = Some(Retry::Resubscribe);
+					},
+					Some(finality_event) if chain_b_backoff.is_scheduled() => chain_b_retry = Some(Retry::FinalityEvent(finality_event)),
+					Some(finality_event) => process_finality_event!(chain_b, chain_a, chain_b_metrics, mode, store, clear_interval, refresh_threshold, chain_b_cleared_at, control, chain_b_backoff, chain_b_retry, finality_event),
+				}
+			}
+			// backoff of chain A elapsed
+			_ = chain_a_backoff.elapsed(), if chain_a_retry.is_some() => {
+				match chain_a_retry.take() {
+					Some(Retry::Resubscribe) => {
+						if let Err(err) = chain_a.reconnect().await {
+							log::error!("Failed to reconnect to {} {:?}", chain_a.name(), err);
+						}
+						chain_a_finality = chain_a.finality_notifications().await;
+					},
+					Some(Retry::FinalityEvent(finality_event)) => process_finality_event!(chain_a, chain_b, chain_a_metrics, mode, store, clear_interval, refresh_threshold, chain_a_cleared_at, control, chain_a_backoff, chain_a_retry, finality_event),
+					None => {},
+				}
+			}
+			// backoff of chain B elapsed
+			_ = chain_b_backoff.elapsed(), if chain_b_retry.is_some() => {
+				match chain_b_retry.take() {
+					Some(Retry::Resubscribe) => {
+						if let Err(err) = chain_b.reconnect().await {
+							log::error!("Failed to reconnect to {} {:?}", chain_b.name(), err);
+						}
+						chain_b_finality = chain_b.finality_notifications().await;
+					},
+					Some(Retry::FinalityEvent(finality_event)) => process_finality_event!(chain_b, chain_a, chain_b_metrics, mode, store, clear_interval, refresh_threshold, chain_b_cleared_at, control, chain_b_backoff, chain_b_retry, finality_event),
+					None => {},
+				}
 			}
 		}
 	}
+}
 
+/// Sends the light client updates and events the sink has missed to bring the light client of
+/// the source on the sink up to date.
+#[tracing::instrument(skip_all, fields(chain = source.name(), counterparty = sink.name()))]
+async fn sync_light_client<A, B>(
+	source: &mut A,
+	sink: &mut B,
+	metrics: Option<&MetricsHandler>,
+	store: Option<&dyn StateStore>,
+) -> Result<(), anyhow::Error>
+where
+	A: Chain,
+	B: Chain,
+{
+	if source.is_synced(sink).await? {
+		return Ok(())
+	}
+	let (mut messages, events) = source.fetch_mandatory_updates(sink).await?;
+	// we use light mode because channel state will be queried during the full relay operation
+	let (parsed_messages, ..) =
+		parse_events(source, sink, events, Some(Mode::Light), store).await?;
+	messages.extend(parsed_messages);
+	log::info!(target: "hyperspace",
+		"Syncing Chain {}'s light client on chain {} {:?}",
+		source.name(),
+		sink.name(),
+		messages.iter().map(|msg| &msg.type_url).collect::<Vec<_>>()
+	);
+	queue::flush_message_batch(messages, metrics, sink, store).await?;
 	Ok(())
 }
 
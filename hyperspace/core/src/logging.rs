@@ -12,11 +12,133 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use log::LevelFilter;
+//! The relayer is instrumented with `tracing` spans, logs of crates using `log` are forwarded to
+//! the same subscriber.
 
+use tracing_subscriber::{
+	filter::EnvFilter, fmt, fmt::MakeWriter, prelude::*, registry::LookupSpan, Layer,
+};
+
+/// Environment variable selecting the log format, either `text` (the default) or `json`.
+pub const LOG_FORMAT_ENV: &str = "HYPERSPACE_LOG_FORMAT";
+/// Environment variable with the endpoint of an OpenTelemetry collector that spans are exported
+/// to, only used with the `otlp` feature.
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Installs the global subscriber, log levels are configured with `RUST_LOG`.
 pub fn setup_logging() {
-	env_logger::builder()
-		.filter_module("hyper", LevelFilter::Info)
-		.format_module_path(false)
+	let filter = EnvFilter::try_from_default_env()
+		.unwrap_or_else(|_| EnvFilter::new("error"))
+		.add_directive("hyper=info".parse().expect("directive is valid; qed"));
+	let json = matches!(std::env::var(LOG_FORMAT_ENV).as_deref(), Ok("json"));
+
+	tracing_subscriber::registry()
+		.with(fmt_layer(json, std::io::stdout))
+		.with(otlp_layer())
+		.with(filter)
 		.init();
 }
+
+/// Writes events as JSON lines carrying the fields of their spans if `json` is set, as text
+/// otherwise.
+fn fmt_layer<S, W>(json: bool, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+	S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+	W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+	if json {
+		fmt::layer()
+			.json()
+			.with_current_span(true)
+			.with_span_list(true)
+			.with_writer(writer)
+			.boxed()
+	} else {
+		fmt::layer().with_target(false).with_writer(writer).boxed()
+	}
+}
+
+/// Exports spans to the collector at `$OTEL_EXPORTER_OTLP_ENDPOINT`, if set.
+#[cfg(feature = "otlp")]
+fn otlp_layer<S>() -> Option<impl Layer<S>>
+where
+	S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+	use opentelemetry::{
+		sdk::{trace, Resource},
+		KeyValue,
+	};
+	use opentelemetry_otlp::WithExportConfig;
+
+	let endpoint = std::env::var(OTLP_ENDPOINT_ENV).ok()?;
+	let tracer = opentelemetry_otlp::new_pipeline()
+		.tracing()
+		.with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+		.with_trace_config(
+			trace::config()
+				.with_resource(Resource::new(vec![KeyValue::new("service.name", "hyperspace")])),
+		)
+		.install_batch(opentelemetry::runtime::Tokio)
+		.map_err(|e| eprintln!("Failed to set up the OTLP exporter: {:?}", e))
+		.ok()?;
+	Some(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+#[cfg(not(feature = "otlp"))]
+fn otlp_layer<S: tracing::Subscriber>() -> Option<impl Layer<S>> {
+	None::<tracing_subscriber::layer::Identity>
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{
+		io,
+		sync::{Arc, Mutex},
+	};
+
+	#[derive(Clone, Default)]
+	struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+	impl io::Write for Buffer {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().extend_from_slice(buf);
+			Ok(buf.len())
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	fn log_packet(json: bool) -> String {
+		let buffer = Buffer::default();
+		let writer = buffer.clone();
+		let subscriber =
+			tracing_subscriber::registry().with(fmt_layer(json, move || writer.clone()));
+		tracing::subscriber::with_default(subscriber, || {
+			let span = tracing::info_span!("flush_message_batch", chain = "sink", messages = 2);
+			let _guard = span.enter();
+			tracing::info!(sequence = 1, "Relaying packet sent on {}", "source");
+		});
+		let output = buffer.0.lock().unwrap().clone();
+		String::from_utf8(output).unwrap()
+	}
+
+	#[test]
+	fn json_logs_carry_the_fields_of_relay_spans() {
+		let output = log_packet(true);
+		let line: serde_json::Value = serde_json::from_str(output.trim_end()).unwrap();
+		assert_eq!(line["level"], "INFO");
+		assert_eq!(line["fields"]["message"], "Relaying packet sent on source");
+		assert_eq!(line["fields"]["sequence"], 1);
+		assert_eq!(line["span"]["name"], "flush_message_batch");
+		assert_eq!(line["span"]["chain"], "sink");
+		assert_eq!(line["spans"][0]["messages"], 2);
+
+		let output = log_packet(false);
+		assert!(serde_json::from_str::<serde_json::Value>(output.trim_end()).is_err());
+		assert!(output.contains("flush_message_batch"), "{}", output);
+		assert!(output.contains("Relaying packet sent on source"), "{}", output);
+	}
+}
@@ -14,42 +14,127 @@
 
 #[macro_export]
 macro_rules! process_finality_event {
-	($source:ident, $sink:ident, $metrics:expr, $mode:ident, $result:ident) => {
-		match $result {
-			// stream closed
-			None => break,
-			Some(finality_event) => {
+	($source:ident, $sink:ident, $metrics:expr, $mode:ident, $store:ident, $clear_interval:ident, $refresh_threshold:ident, $cleared_at:ident, $control:ident, $backoff:ident, $retry:ident, $finality_event:ident) => {
+		{
+			// errors are handled below so that a single failure doesn't stop the relayer
+			let result: Result<(), anyhow::Error> = async {
+				if let Some(control) = $control.as_ref() {
+					control.apply_channel_whitelist(&mut $source);
+					if control.is_paused() {
+						log::info!("Skipping finality notification for {}, relaying is paused", $source.name());
+						return Ok(())
+					}
+				}
 				log::info!("=======================================================");
 				log::info!("Received finality notification from {}", $source.name());
-				let (mut msg_update_client, events, update_type) =
-					match $source.query_latest_ibc_events(finality_event, &$sink).await {
-						Ok(resp) => resp,
-						Err(err) => {
-							log::error!(
-								"Failed to fetch IBC events for finality event for {} {:?}",
+				// the height the chain was at when processing this finality event began, anything
+				// up to it has been handled once the event has been processed.
+				let processed_height = match $store.as_ref() {
+					Some(store) => {
+						let height = $source.latest_height_and_timestamp().await?.0;
+						if matches!(store.last_processed_height($source.name())?, Some(last) if height <= last) {
+							log::info!(
+								"Skipping finality notification for {}, {} was already processed",
 								$source.name(),
-								err
+								height
 							);
-							continue
-						},
-					};
+							return Ok(())
+						}
+						Some(height)
+					},
+					None => None,
+				};
+				// A planned upgrade of the source chain must be followed before its light client is
+				// updated past the upgrade height.
+				match $source.query_client_upgrade_messages(&$sink).await {
+					Ok(messages) if !messages.is_empty() => {
+						log::info!(
+							"Upgrading light client of {} on {}",
+							$source.name(),
+							$sink.name()
+						);
+						queue::flush_message_batch(
+							messages,
+							$metrics.as_ref(),
+							&$sink,
+							$store.as_deref(),
+						)
+						.await?;
+						return Ok(())
+					},
+					Ok(_) => {},
+					Err(err) => log::error!(
+						"Failed to query client upgrade for {} {:?}",
+						$source.name(),
+						err
+					),
+				}
+				let (mut msg_update_client, events, update_type) =
+					$source.query_latest_ibc_events($finality_event.clone(), &$sink).await?;
 				if let Some(metrics) = $metrics.as_mut() {
 					if let Err(e) = metrics.handle_events(events.as_slice()).await {
 						log::error!("Failed to handle metrics for {} {:?}", $source.name(), e);
 					}
 				}
 				let event_types = events.iter().map(|ev| ev.event_type()).collect::<Vec<_>>();
+				// only sweep the channels for pending packets once every `clear_interval` blocks or
+				// when requested, packets emitted in between are still relayed from their events.
+				let clear_requested = $control
+					.as_ref()
+					.map(|control| control.take_clear_request($source.name()))
+					.unwrap_or(false);
+				let mode = match $clear_interval {
+					Some(interval) => {
+						let height = $source.latest_height_and_timestamp().await?.0.revision_height;
+						if !clear_requested &&
+							matches!($cleared_at, Some(cleared_at) if height < cleared_at + interval)
+						{
+							Some(Mode::Light)
+						} else {
+							$cleared_at = Some(height);
+							$mode
+						}
+					},
+					None => $mode,
+				};
 				let (mut messages, timeouts) =
-					parse_events(&mut $source, &mut $sink, events, $mode).await?;
-				if !timeouts.is_empty() {
+					parse_events(&mut $source, &mut $sink, events, mode, $store.as_deref()).await?;
+				// timeouts that can't be delivered shouldn't hold back the messages to the sink,
+				// the failure is reported once those have been submitted.
+				let timeouts_result = if !timeouts.is_empty() {
 					if let Some(metrics) = $metrics.as_ref() {
 						metrics.handle_timeouts(timeouts.as_slice()).await;
 					}
 					let type_urls =
 						timeouts.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>();
-					log::info!("Submitting timeout messages to {}: {type_urls:#?}", $source.name());
-					queue::flush_message_batch(timeouts, $metrics.as_ref(), &$source).await?;
-				}
+					log::info!("Submitting timeout messages to {}: {type_urls:?}", $source.name());
+					queue::flush_message_batch(timeouts, $metrics.as_ref(), &$source, $store.as_deref())
+						.await
+				} else {
+					Ok(())
+				};
+				let refresh_client = if $refresh_threshold.is_some() || $metrics.is_some() {
+					match refresh::query_client_expiry(&$source, &$sink).await {
+						Ok(Some(expiry)) => {
+							if let Some(metrics) = $metrics.as_ref() {
+								metrics.handle_client_expiry(expiry.time_left());
+							}
+							matches!($refresh_threshold, Some(threshold) if expiry.needs_refresh(threshold))
+						},
+						Ok(None) => false,
+						Err(err) => {
+							log::error!(
+								"Failed to query expiry of the light client of {} on {} {:?}",
+								$source.name(),
+								$sink.name(),
+								err
+							);
+							false
+						},
+					}
+				} else {
+					false
+				};
 				// We want to send client update if packet messages exist but where not sent due to
 				// a connection delay even if client update message is optional
 				match (
@@ -57,18 +142,27 @@ macro_rules! process_finality_event {
 					has_packet_events(&event_types),
 					messages.is_empty(),
 				) {
-					(true, false, true) => {
+					(true, false, true) if !refresh_client || msg_update_client.is_empty() => {
 						// skip sending ibc messages if no new events
 						log::info!(
 							"Skipping finality notification for {}, No new events",
 							$source.name()
 						);
-						continue
+						timeouts_result?;
+						if let (Some(store), Some(height)) = ($store.as_ref(), processed_height) {
+							store.set_last_processed_height($source.name(), height)?;
+						}
+						return Ok(())
 					},
 					(false, _, true) =>
 						log::info!("Sending mandatory client update message for {}", $source.name()),
+					(true, false, true) => log::info!(
+						"Refreshing light client of {} on {} before it expires",
+						$source.name(),
+						$sink.name()
+					),
 					_ => log::info!(
-						"Received finalized events from: {} {event_types:#?}",
+						"Received finalized events from: {} {event_types:?}",
 						$source.name()
 					),
 				};
@@ -81,9 +175,35 @@ macro_rules! process_finality_event {
 				}
 				let type_urls =
 					msg_update_client.iter().map(|msg| msg.type_url.as_str()).collect::<Vec<_>>();
-				log::info!("Submitting messages to {}: {type_urls:#?}", $sink.name());
-				queue::flush_message_batch(msg_update_client, $metrics.as_ref(), &$sink).await?;
-			},
+				log::info!("Submitting messages to {}: {type_urls:?}", $sink.name());
+				queue::flush_message_batch(
+					msg_update_client,
+					$metrics.as_ref(),
+					&$sink,
+					$store.as_deref(),
+				)
+				.await?;
+				timeouts_result?;
+				if let (Some(store), Some(height)) = ($store.as_ref(), processed_height) {
+					store.set_last_processed_height($source.name(), height)?;
+				}
+				Ok(())
+			}
+			.instrument(tracing::info_span!(
+				"finality_event",
+				chain = $source.name(),
+				client_id = %$source.client_id(),
+				counterparty = $sink.name(),
+			))
+			.await;
+			match result {
+				Ok(()) => $backoff.reset(),
+				// the event is processed again once the backoff has elapsed
+				Err(err) =>
+					if $backoff.on_error($source.name(), err)? {
+						$retry = Some(Retry::FinalityEvent($finality_event));
+					},
+			}
 		}
 	};
 }
@@ -0,0 +1,537 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relaying of channels that span several connection hops. The sink of a message on such a
+//! channel only has a light client of the adjacent chain, the proofs of the source state are
+//! therefore bundled with the proofs of the connection ends, client and consensus states stored by
+//! the intermediate chains. The light clients along the path are kept up to date by the paths relaying
+//! between adjacent chains.
+
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use futures::StreamExt;
+use ibc::{
+	core::{
+		ics02_client::client_state::ClientState as ClientStateT,
+		ics03_connection::connection::ConnectionEnd,
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty as ChannelCounterparty, Order, State},
+			msgs::{
+				acknowledgement::MsgAcknowledgement, chan_open_ack::MsgChannelOpenAck,
+				chan_open_confirm::MsgChannelOpenConfirm, chan_open_try::MsgChannelOpenTry,
+				recv_packet::MsgRecvPacket,
+			},
+			multihop::{MultihopProof, MultihopProofs},
+		},
+		ics23_commitment::commitment::CommitmentProofBytes,
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			path::{ChannelEndsPath, ClientConsensusStatePath, ClientStatePath, ConnectionsPath},
+		},
+	},
+	proofs::Proofs,
+	tx_msg::Msg,
+	Height,
+};
+use ibc_proto::google::protobuf::Any;
+use pallet_ibc::light_clients::AnyClientState;
+use primitives::{
+	apply_prefix, error::Error, packet_info_to_packet, query_undelivered_acks,
+	query_undelivered_sequences, Chain,
+};
+use prost::Message;
+use tendermint_proto::Protobuf;
+
+use crate::{
+	api::PathControl,
+	chain::AnyChain,
+	packets::utils::{get_key_path, in_order, KeyPathType},
+};
+
+/// Proofs of the connection ends, client and consensus states stored by the intermediate chains of
+/// a multi-hop channel, completed with the proof of an object on the source by
+/// [`HopProofs::prove`].
+pub struct HopProofs {
+	/// Height of the client of the first hop on the sink, the proofs are verified at it.
+	pub proof_height: Height,
+	/// Height of the source the object proof is queried at.
+	pub source_height: Height,
+	connection_proofs: Vec<MultihopProof>,
+	consensus_proofs: Vec<MultihopProof>,
+	client_state_proofs: Vec<MultihopProof>,
+}
+
+impl HopProofs {
+	/// Queries the proofs for the channel end on `sink` with the given `connection_hops`, `hops`
+	/// are the intermediate chains ordered from the sink. Every chain is proven at the latest
+	/// height of its light client on the previous chain.
+	pub async fn query(
+		sink: &impl Chain,
+		hops: &[AnyChain],
+		connection_hops: &[ConnectionId],
+	) -> Result<Self, anyhow::Error> {
+		if connection_hops.len() != hops.len() + 1 {
+			return Err(anyhow!(
+				"Channel on {} has {} connection hops, the path has {}",
+				sink.name(),
+				connection_hops.len(),
+				hops.len() + 1
+			))
+		}
+		let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+		let connection_end =
+			query_connection_end(sink, sink_height, connection_hops[0].clone()).await?;
+		let proof_height =
+			query_client_height(sink, sink_height, connection_end.client_id().clone()).await?;
+
+		let mut height = proof_height;
+		let mut connection_proofs = vec![];
+		let mut consensus_proofs = vec![];
+		let mut client_state_proofs = vec![];
+		for (chain, connection_id) in hops.iter().zip(&connection_hops[1..]) {
+			let prefix = chain.connection_prefix().into_vec();
+			let response = chain.query_connection_end(height, connection_id.clone()).await?;
+			let connection = response.connection.ok_or_else(|| {
+				Error::Custom(format!("ConnectionEnd not found for {:?}", connection_id))
+			})?;
+			let client_id = ConnectionEnd::try_from(connection.clone())?.client_id().clone();
+			connection_proofs.push(MultihopProof {
+				proof: CommitmentProofBytes::try_from(response.proof)?,
+				value: connection.encode_to_vec(),
+				prefixed_key: prefixed_key(
+					&prefix,
+					ConnectionsPath(connection_id.clone()).to_string(),
+				),
+			});
+
+			let response = chain.query_client_state(height, client_id.clone()).await?;
+			let client_state = response.client_state.ok_or_else(|| {
+				Error::Custom(format!("Client state {} not found on {}", client_id, chain.name()))
+			})?;
+			let client_height = AnyClientState::try_from(client_state.clone())
+				.map_err(|_| {
+					Error::Custom(format!("Invalid client state {} on {}", client_id, chain.name()))
+				})?
+				.latest_height();
+			client_state_proofs.push(MultihopProof {
+				proof: CommitmentProofBytes::try_from(response.proof)?,
+				value: client_state.encode_to_vec(),
+				prefixed_key: prefixed_key(&prefix, ClientStatePath(client_id.clone()).to_string()),
+			});
+
+			let response =
+				chain.query_client_consensus(height, client_id.clone(), client_height).await?;
+			let consensus_state = response.consensus_state.ok_or_else(|| {
+				Error::Custom(format!(
+					"Consensus state {} of {} not found on {}",
+					client_height,
+					client_id,
+					chain.name()
+				))
+			})?;
+			let path = ClientConsensusStatePath {
+				client_id,
+				epoch: client_height.revision_number,
+				height: client_height.revision_height,
+			};
+			consensus_proofs.push(MultihopProof {
+				proof: CommitmentProofBytes::try_from(response.proof)?,
+				value: consensus_state.encode_to_vec(),
+				prefixed_key: prefixed_key(&prefix, path.to_string()),
+			});
+			height = client_height;
+		}
+		// the proofs are verified from the first hop but ordered from the counterparty
+		connection_proofs.reverse();
+		consensus_proofs.reverse();
+		client_state_proofs.reverse();
+
+		Ok(Self {
+			proof_height,
+			source_height: height,
+			connection_proofs,
+			consensus_proofs,
+			client_state_proofs,
+		})
+	}
+
+	/// Adds the proof of the value stored under `path` on `source` and returns the proofs encoded
+	/// as the object proof of a message.
+	pub async fn prove(
+		&self,
+		source: &impl Chain,
+		path: String,
+	) -> Result<CommitmentProofBytes, anyhow::Error> {
+		let prefix = source.connection_prefix().into_vec();
+		let proof = source
+			.query_proof(self.source_height, vec![apply_prefix(prefix.clone(), path.clone())])
+			.await?;
+		let proofs = MultihopProofs {
+			key_proof: MultihopProof {
+				proof: CommitmentProofBytes::try_from(proof)?,
+				value: vec![],
+				prefixed_key: prefixed_key(&prefix, path),
+			},
+			connection_proofs: self.connection_proofs.clone(),
+			consensus_proofs: self.consensus_proofs.clone(),
+			client_state_proofs: self.client_state_proofs.clone(),
+		};
+		Ok(CommitmentProofBytes::try_from(proofs.encode_vec()?)?)
+	}
+}
+
+fn prefixed_key(prefix: &[u8], path: String) -> Vec<String> {
+	vec![String::from_utf8_lossy(prefix).to_string(), path]
+}
+
+async fn query_connection_end(
+	chain: &impl Chain,
+	at: Height,
+	connection_id: ConnectionId,
+) -> Result<ConnectionEnd, anyhow::Error> {
+	let response = chain.query_connection_end(at, connection_id.clone()).await?;
+	let connection_end = ConnectionEnd::try_from(response.connection.ok_or_else(|| {
+		Error::Custom(format!(
+			"ConnectionEnd not found for {:?} on {}",
+			connection_id,
+			chain.name()
+		))
+	})?)?;
+	Ok(connection_end)
+}
+
+async fn query_client_height(
+	chain: &impl Chain,
+	at: Height,
+	client_id: ClientId,
+) -> Result<Height, anyhow::Error> {
+	let response = chain.query_client_state(at, client_id.clone()).await?;
+	let client_state = AnyClientState::try_from(response.client_state.ok_or_else(|| {
+		Error::Custom(format!("Client state {} not found on {}", client_id, chain.name()))
+	})?)
+	.map_err(|_| {
+		Error::Custom(format!("Invalid client state {} on {}", client_id, chain.name()))
+	})?;
+	Ok(client_state.latest_height())
+}
+
+async fn query_channel(
+	chain: &impl Chain,
+	at: Height,
+	channel_id: ChannelId,
+	port_id: PortId,
+) -> Result<Option<ChannelEnd>, anyhow::Error> {
+	let response = chain.query_channel_end(at, channel_id, port_id).await?;
+	Ok(response.channel.map(ChannelEnd::try_from).transpose()?)
+}
+
+/// Returns the connection hops of the counterparty of a channel end on `source` with the given
+/// `connection_hops`, `hops` are the intermediate chains ordered from the source.
+pub async fn counterparty_connection_hops(
+	source: &impl Chain,
+	hops: &[AnyChain],
+	connection_hops: &[ConnectionId],
+) -> Result<Vec<ConnectionId>, anyhow::Error> {
+	if connection_hops.len() != hops.len() + 1 {
+		return Err(anyhow!(
+			"Channel on {} has {} connection hops, the path has {}",
+			source.name(),
+			connection_hops.len(),
+			hops.len() + 1
+		))
+	}
+	let (height, _) = source.latest_height_and_timestamp().await?;
+	let mut connection_ends =
+		vec![query_connection_end(source, height, connection_hops[0].clone()).await?];
+	for (chain, connection_id) in hops.iter().zip(&connection_hops[1..]) {
+		let (height, _) = chain.latest_height_and_timestamp().await?;
+		connection_ends.push(query_connection_end(chain, height, connection_id.clone()).await?);
+	}
+	connection_ends
+		.iter()
+		.rev()
+		.map(|connection_end| {
+			connection_end.counterparty().connection_id().cloned().ok_or_else(|| {
+				anyhow!("Connection hop on {} without a counterparty", source.name())
+			})
+		})
+		.collect()
+}
+
+/// Returns the messages that advance the handshake of a multi-hop channel on `sink`, or relay its
+/// packets and acknowledgements once it's open. `hops` are the intermediate chains ordered from
+/// the source.
+async fn query_channel_messages(
+	source: &impl Chain,
+	sink: &impl Chain,
+	hops: &[AnyChain],
+	channel_id: ChannelId,
+	port_id: PortId,
+) -> Result<Vec<Any>, anyhow::Error> {
+	let sink_hops = hops.iter().rev().cloned().collect::<Vec<_>>();
+	let (source_height, _) = source.latest_height_and_timestamp().await?;
+	let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+	let channel_end = query_channel(source, source_height, channel_id, port_id.clone())
+		.await?
+		.ok_or_else(|| {
+			Error::Custom(format!("ChannelEnd not found for {:?}/{:?}", channel_id, port_id))
+		})?;
+	if channel_end.connection_hops.len() <= 1 {
+		return Ok(vec![])
+	}
+	let counterparty = channel_end.counterparty().clone();
+	let counterparty_end = match counterparty.channel_id {
+		Some(counterparty_channel_id) =>
+			query_channel(sink, sink_height, counterparty_channel_id, counterparty.port_id.clone())
+				.await?,
+		None => None,
+	};
+	let channel_path = ChannelEndsPath(port_id.clone(), channel_id).to_string();
+
+	let msg = match (channel_end.state, counterparty_end) {
+		(State::Init, None) => {
+			if has_counterparty_channel(source, sink, channel_id, &port_id).await? {
+				return Ok(vec![])
+			}
+			let sink_connection_hops =
+				counterparty_connection_hops(source, hops, &channel_end.connection_hops).await?;
+			let hop_proofs = HopProofs::query(sink, &sink_hops, &sink_connection_hops).await?;
+			let proof = hop_proofs.prove(source, channel_path).await?;
+			let channel = ChannelEnd::new(
+				channel_end.state,
+				channel_end.ordering,
+				ChannelCounterparty::new(port_id, Some(channel_id)),
+				sink_connection_hops,
+				channel_end.version.clone(),
+			);
+			let msg = MsgChannelOpenTry {
+				port_id: counterparty.port_id,
+				channel,
+				counterparty_version: channel_end.version,
+				proofs: Proofs::new(proof, None, None, None, hop_proofs.proof_height)?,
+				signer: sink.account_id(),
+			};
+			Any { value: msg.encode_vec()?, type_url: msg.type_url() }
+		},
+		(State::TryOpen, Some(sink_channel_end)) if sink_channel_end.state == State::Init => {
+			let hop_proofs =
+				HopProofs::query(sink, &sink_hops, &sink_channel_end.connection_hops).await?;
+			let proof = hop_proofs.prove(source, channel_path).await?;
+			let msg = MsgChannelOpenAck {
+				port_id: counterparty.port_id,
+				channel_id: counterparty.channel_id.expect("Counterparty channel end was found"),
+				counterparty_channel_id: channel_id,
+				counterparty_version: channel_end.version,
+				proofs: Proofs::new(proof, None, None, None, hop_proofs.proof_height)?,
+				signer: sink.account_id(),
+			};
+			Any { value: msg.encode_vec()?, type_url: msg.type_url() }
+		},
+		(State::Open, Some(sink_channel_end)) if sink_channel_end.state == State::TryOpen => {
+			let hop_proofs =
+				HopProofs::query(sink, &sink_hops, &sink_channel_end.connection_hops).await?;
+			let proof = hop_proofs.prove(source, channel_path).await?;
+			let msg = MsgChannelOpenConfirm {
+				port_id: counterparty.port_id,
+				channel_id: counterparty.channel_id.expect("Counterparty channel end was found"),
+				proofs: Proofs::new(proof, None, None, None, hop_proofs.proof_height)?,
+				signer: sink.account_id(),
+			};
+			Any { value: msg.encode_vec()?, type_url: msg.type_url() }
+		},
+		(State::Open, Some(sink_channel_end)) if sink_channel_end.state == State::Open =>
+			return query_packet_messages(
+				source,
+				sink,
+				&sink_hops,
+				&sink_channel_end,
+				channel_id,
+				port_id,
+				channel_end.ordering,
+			)
+			.await,
+		_ => return Ok(vec![]),
+	};
+	Ok(vec![msg])
+}
+
+/// Returns true if a channel end on `sink` already has the channel on `source` as counterparty,
+/// i.e. the channel open try was relayed but not answered yet.
+async fn has_counterparty_channel(
+	source: &impl Chain,
+	sink: &impl Chain,
+	channel_id: ChannelId,
+	port_id: &PortId,
+) -> Result<bool, anyhow::Error> {
+	let (sink_height, _) = sink.latest_height_and_timestamp().await?;
+	for (sink_channel_id, sink_port_id) in sink.query_channels().await? {
+		let sink_channel_end =
+			match query_channel(sink, sink_height, sink_channel_id, sink_port_id).await? {
+				Some(channel_end) => channel_end,
+				None => continue,
+			};
+		let counterparty = sink_channel_end.counterparty();
+		if counterparty.channel_id == Some(channel_id) && counterparty.port_id == *port_id {
+			log::debug!(target: "hyperspace", "Channel {}/{} on {} already has a counterparty on {}", channel_id, port_id, source.name(), sink.name());
+			return Ok(true)
+		}
+	}
+	Ok(false)
+}
+
+/// Returns the receive and acknowledgement messages for the packets on an open multi-hop channel
+/// that were not delivered to `sink` yet. Timed out packets are not relayed.
+async fn query_packet_messages(
+	source: &impl Chain,
+	sink: &impl Chain,
+	sink_hops: &[AnyChain],
+	sink_channel_end: &ChannelEnd,
+	channel_id: ChannelId,
+	port_id: PortId,
+	ordering: Order,
+) -> Result<Vec<Any>, anyhow::Error> {
+	let (sink_height, sink_timestamp) = sink.latest_height_and_timestamp().await?;
+	let hop_proofs = HopProofs::query(sink, sink_hops, &sink_channel_end.connection_hops).await?;
+	let source_height = hop_proofs.source_height;
+
+	let seqs = query_undelivered_sequences(
+		source_height,
+		sink_height,
+		channel_id,
+		port_id.clone(),
+		source,
+		sink,
+	)
+	.await?;
+	let mut recv_messages = vec![];
+	for send_packet in source.query_send_packets(channel_id, port_id.clone(), seqs.clone()).await? {
+		let packet = packet_info_to_packet(&send_packet);
+		if packet.timed_out(&sink_timestamp, sink_height) ||
+			send_packet.height > source_height.revision_height
+		{
+			continue
+		}
+		let sequence = u64::from(packet.sequence);
+		let path = get_key_path(KeyPathType::CommitmentPath, &packet);
+		let proof = hop_proofs.prove(source, path).await?;
+		let msg = MsgRecvPacket {
+			packet,
+			proofs: Proofs::new(proof, None, None, None, hop_proofs.proof_height)?,
+			signer: sink.account_id(),
+		};
+		recv_messages.push((sequence, Any { value: msg.encode_vec()?, type_url: msg.type_url() }));
+	}
+	let mut messages = match ordering {
+		Order::Ordered => in_order(&seqs, recv_messages),
+		_ => recv_messages.into_iter().map(|(_, msg)| msg).collect(),
+	};
+
+	let seqs = query_undelivered_acks(
+		source_height,
+		sink_height,
+		channel_id,
+		port_id.clone(),
+		source,
+		sink,
+	)
+	.await?;
+	for recv_packet in source.query_recv_packets(channel_id, port_id, seqs).await? {
+		let ack = match recv_packet.ack.clone() {
+			Some(ack) if recv_packet.height <= source_height.revision_height => ack,
+			_ => continue,
+		};
+		let packet = packet_info_to_packet(&recv_packet);
+		let path = get_key_path(KeyPathType::AcksPath, &packet);
+		let proof = hop_proofs.prove(source, path).await?;
+		let msg = MsgAcknowledgement {
+			packet,
+			proofs: Proofs::new(proof, None, None, None, hop_proofs.proof_height)?,
+			acknowledgement: ack.into(),
+			signer: sink.account_id(),
+		};
+		messages.push(Any { value: msg.encode_vec()?, type_url: msg.type_url() });
+	}
+
+	Ok(messages)
+}
+
+/// Relays the messages of the whitelisted multi-hop channels of `source` to `sink`.
+#[tracing::instrument(skip_all, fields(chain = source.name(), counterparty = sink.name()))]
+async fn relay_channels(
+	source: &impl Chain,
+	sink: &impl Chain,
+	hops: &[AnyChain],
+) -> Result<(), anyhow::Error> {
+	let mut messages = vec![];
+	for (channel_id, port_id) in source.channel_whitelist() {
+		// a failure on one channel shouldn't prevent relaying the others
+		match query_channel_messages(source, sink, hops, channel_id, port_id.clone()).await {
+			Ok(msgs) => messages.extend(msgs),
+			Err(err) => log::error!(target: "hyperspace",
+				"Failed to relay multi-hop channel {}/{} of {}: {:?}",
+				channel_id,
+				port_id,
+				source.name(),
+				err
+			),
+		}
+	}
+	if messages.is_empty() {
+		return Ok(())
+	}
+	log::info!(target: "hyperspace",
+		"Submitting multi-hop messages to {}: {:?}",
+		sink.name(),
+		messages.iter().map(|msg| &msg.type_url).collect::<Vec<_>>()
+	);
+	sink.submit(messages).await?;
+	Ok(())
+}
+
+/// Relays the multi-hop channels between `chain_a` and `chain_b`, `hops` are the intermediate
+/// chains ordered from `chain_a`. The channel state is checked on every finality notification of
+/// the source, packet timeouts and channel closing are not relayed.
+pub async fn relay<A, B>(
+	chain_a: A,
+	chain_b: B,
+	hops: Vec<AnyChain>,
+	control: Option<Arc<PathControl>>,
+) -> Result<(), anyhow::Error>
+where
+	A: Chain,
+	B: Chain,
+{
+	let reversed_hops = hops.iter().rev().cloned().collect::<Vec<_>>();
+	let (mut chain_a_finality, mut chain_b_finality) =
+		(chain_a.finality_notifications().await, chain_b.finality_notifications().await);
+
+	loop {
+		let result = tokio::select! {
+			result = chain_a_finality.next() => match result {
+				Some(_) if control.as_ref().map(|c| c.is_paused()).unwrap_or(false) => Ok(()),
+				Some(_) => relay_channels(&chain_a, &chain_b, &hops).await,
+				None => return Err(anyhow!("Finality notifications from {} ended", chain_a.name())),
+			},
+			result = chain_b_finality.next() => match result {
+				Some(_) if control.as_ref().map(|c| c.is_paused()).unwrap_or(false) => Ok(()),
+				Some(_) => relay_channels(&chain_b, &chain_a, &reversed_hops).await,
+				None => return Err(anyhow!("Finality notifications from {} ended", chain_b.name())),
+			},
+		};
+		if let Err(err) = result {
+			log::error!(target: "hyperspace", "Failed to relay multi-hop channels: {:?}", err);
+		}
+	}
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::packets::connection_delay::has_delay_elapsed;
+use anyhow::anyhow;
 use ibc::{
 	core::{
 		ics02_client::client_state::ClientState as ClientStateT,
@@ -20,14 +21,16 @@ use ibc::{
 			channel::{ChannelEnd, Order, State},
 			context::calculate_block_delay,
 			msgs::{
-				acknowledgement::MsgAcknowledgement, recv_packet::MsgRecvPacket,
-				timeout::MsgTimeout, timeout_on_close::MsgTimeoutOnClose,
+				acknowledgement::MsgAcknowledgement, chan_close_confirm::MsgChannelCloseConfirm,
+				recv_packet::MsgRecvPacket, timeout::MsgTimeout,
+				timeout_on_close::MsgTimeoutOnClose,
 			},
 			packet::{Packet, TimeoutVariant},
 		},
 		ics23_commitment::commitment::CommitmentProofBytes,
-		ics24_host::path::{
-			AcksPath, ChannelEndsPath, CommitmentsPath, ReceiptsPath, SeqRecvsPath,
+		ics24_host::{
+			identifier::{ChannelId, PortId},
+			path::{AcksPath, ChannelEndsPath, CommitmentsPath, ReceiptsPath, SeqRecvsPath},
 		},
 	},
 	proofs::Proofs,
@@ -197,6 +200,15 @@ pub async fn verify_delay_passed(
 	}
 }
 
+#[tracing::instrument(
+	skip_all,
+	fields(
+		chain = source.name(),
+		channel = %packet.source_channel,
+		port = %packet.source_port,
+		sequence = u64::from(packet.sequence),
+	)
+)]
 pub async fn construct_timeout_message(
 	source: &impl Chain,
 	sink: &impl Chain,
@@ -215,6 +227,19 @@ pub async fn construct_timeout_message(
 
 	let proof_unreceived = sink.query_proof(proof_height, vec![key]).await?;
 	let proof_unreceived = CommitmentProofBytes::try_from(proof_unreceived)?;
+	// on ordered channels the proof is of the next sequence to be received, its value must be
+	// the one at the proof height.
+	let next_sequence_recv = if sink_channel_end.ordering == Order::Ordered {
+		sink.query_next_sequence_recv(
+			proof_height,
+			&packet.destination_port,
+			&packet.destination_channel,
+		)
+		.await?
+		.next_sequence_receive
+	} else {
+		next_sequence_recv
+	};
 	let msg = if sink_channel_end.state == State::Closed {
 		let path = get_key_path(KeyPathType::ChannelPath, &packet);
 		let channel_key = apply_prefix(sink.connection_prefix().into_vec(), path);
@@ -242,6 +267,58 @@ pub async fn construct_timeout_message(
 	Ok(msg)
 }
 
+/// Constructs the message closing the sink's end of a channel that was closed on the source, e.g.
+/// by the timeout of a packet on an ordered channel. The channel is proven at `proof_height`, a
+/// height the source's client on the sink has a consensus state for, `None` is returned if the
+/// channel wasn't closed yet at that height.
+#[tracing::instrument(
+	skip_all,
+	fields(chain = source.name(), channel = %channel_id, port = %port_id)
+)]
+pub async fn construct_close_confirm_message(
+	source: &impl Chain,
+	sink: &impl Chain,
+	channel_id: ChannelId,
+	port_id: PortId,
+	proof_height: Height,
+) -> Result<Option<Any>, anyhow::Error> {
+	let channel_response =
+		source.query_channel_end(proof_height, channel_id, port_id.clone()).await?;
+	let channel_end = ChannelEnd::try_from(
+		channel_response
+			.channel
+			.ok_or_else(|| anyhow!("ChannelEnd not found for {}/{}", port_id, channel_id))?,
+	)?;
+	if channel_end.state != State::Closed {
+		return Ok(None)
+	}
+	let counterparty = channel_end.counterparty();
+	let channel_proof = CommitmentProofBytes::try_from(channel_response.proof)?;
+	let proof_height = channel_response
+		.proof_height
+		.ok_or_else(|| anyhow!("Proof height not found in channel end response"))?;
+	let proof_height = Height::new(proof_height.revision_number, proof_height.revision_height);
+	let msg = MsgChannelCloseConfirm {
+		port_id: counterparty.port_id.clone(),
+		channel_id: counterparty.channel_id.ok_or_else(|| {
+			anyhow!("Channel {} is missing its counterparty channel id", channel_id)
+		})?,
+		proofs: Proofs::new(channel_proof, None, None, None, proof_height)?,
+		signer: sink.account_id(),
+	};
+	let value = msg.encode_vec()?;
+	Ok(Some(Any { value, type_url: msg.type_url() }))
+}
+
+#[tracing::instrument(
+	skip_all,
+	fields(
+		chain = source.name(),
+		channel = %packet.source_channel,
+		port = %packet.source_port,
+		sequence = u64::from(packet.sequence),
+	)
+)]
 pub async fn construct_recv_message(
 	source: &impl Chain,
 	sink: &impl Chain,
@@ -263,6 +340,15 @@ pub async fn construct_recv_message(
 	Ok(msg)
 }
 
+#[tracing::instrument(
+	skip_all,
+	fields(
+		chain = source.name(),
+		channel = %packet.source_channel,
+		port = %packet.source_port,
+		sequence = u64::from(packet.sequence),
+	)
+)]
 pub async fn construct_ack_message(
 	source: &impl Chain,
 	sink: &impl Chain,
@@ -343,3 +429,49 @@ pub fn get_key_path(key_path_type: KeyPathType, packet: &Packet) -> String {
 		},
 	}
 }
+
+/// Returns the messages of the longest run of consecutive `sequences` (sorted in ascending order)
+/// that there's a message for, in sequence order. Used for ordered channels, where a packet or
+/// acknowledgement can only be delivered once all the ones before it were.
+pub fn in_order(sequences: &[u64], mut messages: Vec<(u64, Any)>) -> Vec<Any> {
+	messages.sort_by_key(|(sequence, _)| *sequence);
+	sequences
+		.iter()
+		.zip(messages)
+		.take_while(|(expected, (sequence, _))| *expected == sequence)
+		.map(|(_, (_, msg))| msg)
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::undelivered_ordered_sequences;
+
+	fn msg(sequence: u64) -> Any {
+		Any { type_url: "recv".to_string(), value: sequence.to_be_bytes().to_vec() }
+	}
+
+	fn msgs(sequences: &[u64]) -> Vec<(u64, Any)> {
+		sequences.iter().map(|sequence| (*sequence, msg(*sequence))).collect()
+	}
+
+	#[test]
+	fn ordered_packets_are_relayed_from_the_next_sequence_to_be_received() {
+		// sequences 1 and 2 were received already, their commitments may still be around
+		let sequences = undelivered_ordered_sequences(vec![5, 2, 4, 1, 3], 3);
+		assert_eq!(sequences, vec![3, 4, 5]);
+
+		assert_eq!(in_order(&sequences, msgs(&[5, 3, 4])), vec![msg(3), msg(4), msg(5)]);
+		// a packet that isn't ready holds back the packets after it
+		assert_eq!(in_order(&sequences, msgs(&[5, 3])), vec![msg(3)]);
+		// nothing is relayed until the next sequence to be received is ready
+		assert!(in_order(&sequences, msgs(&[4, 5])).is_empty());
+	}
+
+	#[test]
+	fn ordered_acknowledgements_are_relayed_in_sequence() {
+		assert_eq!(in_order(&[7, 8, 9], msgs(&[9, 7, 8])), vec![msg(7), msg(8), msg(9)]);
+		assert_eq!(in_order(&[7, 8, 9], msgs(&[7, 9])), vec![msg(7)]);
+	}
+}
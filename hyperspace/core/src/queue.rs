@@ -12,47 +12,468 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use ibc::{
+	core::ics04_channel::{
+		msgs::{
+			acknowledgement::{self, MsgAcknowledgement},
+			recv_packet::{self, MsgRecvPacket},
+			timeout::{self, MsgTimeout},
+			timeout_on_close::{self, MsgTimeoutOnClose},
+		},
+		packet::Packet,
+	},
+	Height,
+};
 use ibc_proto::google::protobuf::Any;
 use metrics::handler::MetricsHandler;
-use primitives::Chain;
+use primitives::{
+	store::{PendingTransaction, StateStore},
+	Chain,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tendermint_proto::Protobuf;
 
-/// This sends messages to the sink chain in a gas-aware manner.
+/// Returned by [`flush_message_batch`] when some of the messages could not be delivered, the
+/// other messages of the batch were submitted.
+#[derive(Debug, thiserror::Error)]
+#[error("{} of {total} messages could not be delivered to {chain}: {type_urls:?}", type_urls.len())]
+pub struct UndeliveredMessages {
+	/// Name of the sink chain.
+	pub chain: String,
+	/// Type urls of the messages that failed.
+	pub type_urls: Vec<String>,
+	/// Number of messages in the batch.
+	pub total: usize,
+}
+
+/// The operations of a chain that are needed to submit batches of messages to it, implemented
+/// for every [`Chain`].
+#[async_trait::async_trait]
+pub trait MessageSink: Send + Sync {
+	/// Name of the chain, used in logs.
+	fn name(&self) -> &str;
+
+	/// Max weight of the transactions in a block.
+	fn block_max_weight(&self) -> u64;
+
+	/// Estimates the weight of a batch of messages.
+	async fn estimate_weight(&self, msgs: Vec<Any>) -> Result<u64, anyhow::Error>;
+
+	/// Submits the messages and waits for the transaction to be included, returns its id.
+	async fn submit(&self, msgs: Vec<Any>) -> Result<String, anyhow::Error>;
+
+	/// Broadcasts the messages without waiting for the transaction to be included, returns its
+	/// hash.
+	async fn broadcast(&self, msgs: Vec<Any>) -> Result<String, anyhow::Error>;
+
+	/// Waits for a broadcast transaction to be included.
+	async fn wait_for_transaction(&self, tx_hash: &str) -> Result<(), anyhow::Error>;
+
+	/// Removes the messages whose effect has already been applied to the chain.
+	async fn drop_delivered(&self, msgs: Vec<Any>) -> Vec<Any>;
+}
+
+#[async_trait::async_trait]
+impl<C: Chain> MessageSink for C {
+	fn name(&self) -> &str {
+		Chain::name(self)
+	}
+
+	fn block_max_weight(&self) -> u64 {
+		Chain::block_max_weight(self)
+	}
+
+	async fn estimate_weight(&self, msgs: Vec<Any>) -> Result<u64, anyhow::Error> {
+		Ok(Chain::estimate_weight(self, msgs).await?)
+	}
+
+	async fn submit(&self, msgs: Vec<Any>) -> Result<String, anyhow::Error> {
+		Ok(format!("{:?}", Chain::submit(self, msgs).await?))
+	}
+
+	async fn broadcast(&self, msgs: Vec<Any>) -> Result<String, anyhow::Error> {
+		Ok(Chain::broadcast(self, msgs).await?)
+	}
+
+	async fn wait_for_transaction(&self, tx_hash: &str) -> Result<(), anyhow::Error> {
+		Ok(Chain::wait_for_transaction(self, tx_hash).await?)
+	}
+
+	async fn drop_delivered(&self, msgs: Vec<Any>) -> Vec<Any> {
+		drop_delivered(msgs, self).await
+	}
+}
+
+/// This sends messages to the sink chain in a gas-aware manner. Batches that exceed the block
+/// max weight are split into chunks, a message that can't be delivered doesn't prevent the other
+/// messages from being submitted but is reported as an [`UndeliveredMessages`] error.
+#[tracing::instrument(skip_all, fields(chain = sink.name(), messages = msgs.len()))]
 pub async fn flush_message_batch(
 	msgs: Vec<Any>,
 	metrics: Option<&MetricsHandler>,
-	sink: &impl Chain,
+	sink: &impl MessageSink,
+	store: Option<&dyn StateStore>,
 ) -> Result<(), anyhow::Error> {
-	let block_max_weight = sink.block_max_weight();
-	let batch_weight = sink.estimate_weight(msgs.clone()).await?;
+	if msgs.is_empty() {
+		return Ok(())
+	}
+	let block_max_weight = sink.block_max_weight().max(1);
+	let batch_weight = sink.estimate_weight(msgs.clone()).await.map_err(|err| {
+		err.context(format!("Failed to estimate weight of messages for {}", sink.name()))
+	})?;
 
 	if let Some(metrics) = metrics {
 		metrics.handle_transaction_costs(batch_weight, &msgs).await;
 	}
 
+	let total = msgs.len();
 	let ratio = (batch_weight / block_max_weight) as usize;
-	if ratio == 0 {
-		sink.submit(msgs).await?;
-		return Ok(())
-	}
-
-	// whelp our batch exceeds the block max weight.
-	let chunk = if ratio == 1 {
-		// split the batch into ratio * 2
-		ratio * 2
+	let failed = if ratio == 0 {
+		submit_with_retry(msgs, sink, store).await
 	} else {
-		// split the batch into ratio + 2
-		ratio + 2
+		// whelp our batch exceeds the block max weight.
+		let chunks = if ratio == 1 {
+			// split the batch into ratio * 2
+			ratio * 2
+		} else {
+			// split the batch into ratio + 2
+			ratio + 2
+		};
+
+		log::info!(
+			"Outgoing messages weight: {} exceeds the block max weight: {}. Chunking {} messages into {} chunks",
+			batch_weight, block_max_weight, msgs.len(), chunks,
+		);
+		let chunk_size = (msgs.len() + chunks - 1) / chunks;
+		let mut failed = vec![];
+		for batch in msgs.chunks(chunk_size.max(1)) {
+			// send out batches.
+			failed.extend(submit_with_retry(batch.to_vec(), sink, store).await);
+		}
+		failed
 	};
 
-	log::info!(
-		"Outgoing messages weight: {} exceeds the block max weight: {}. Chunking {} messages into {} chunks",
-        batch_weight, block_max_weight, msgs.len(), chunk,
-	);
-	// TODO: return number of failed messages and record it to metrics
-	for batch in msgs.chunks(chunk) {
-		// send out batches.
-		sink.submit(batch.to_vec()).await?;
+	if !failed.is_empty() {
+		if let Some(metrics) = metrics {
+			metrics.handle_failed_messages(failed.len() as u64);
+		}
+		return Err(UndeliveredMessages {
+			chain: sink.name().to_string(),
+			type_urls: failed.into_iter().map(|msg| msg.type_url).collect(),
+			total,
+		}
+		.into())
+	}
+
+	Ok(())
+}
+
+/// Submits the batch, if the transaction fails the batch is split in two and both halves are
+/// resubmitted, after dropping messages that someone else has already delivered. A message that
+/// fails on its own is dropped, the relayer will pick it up again on the next finality event if
+/// it's still relevant. Returns the messages that were dropped because they failed.
+async fn submit_with_retry(
+	msgs: Vec<Any>,
+	sink: &impl MessageSink,
+	store: Option<&dyn StateStore>,
+) -> Vec<Any> {
+	let mut failed = vec![];
+	// batches are popped from the back, so the first half of a split batch goes last in order
+	// to preserve the order of messages, eg client updates must come before packets.
+	let mut batches = vec![msgs];
+	while let Some(batch) = batches.pop() {
+		let err = match submit_tracked(batch.clone(), sink, store).await {
+			Ok(()) => continue,
+			Err(err) => err,
+		};
+
+		if batch.len() == 1 {
+			log::error!("Failed to submit {} to {}: {:?}", batch[0].type_url, sink.name(), err);
+			failed.extend(batch);
+			continue
+		}
+
+		log::warn!(
+			"Failed to submit batch of {} messages to {}, splitting it: {:?}",
+			batch.len(),
+			sink.name(),
+			err
+		);
+		let batch = sink.drop_delivered(batch).await;
+		let (first, second) = batch.split_at(batch.len() / 2);
+		for half in [second, first] {
+			if !half.is_empty() {
+				batches.push(half.to_vec());
+			}
+		}
 	}
 
+	failed
+}
+
+/// Submits the messages to the sink, the transaction is recorded as pending in the store from
+/// the moment it's broadcast until the sink has confirmed it. Entries left behind by a crash are
+/// resolved on the next start.
+#[tracing::instrument(
+	skip_all,
+	fields(chain = sink.name(), messages = msgs.len(), tx_hash = tracing::field::Empty)
+)]
+async fn submit_tracked(
+	msgs: Vec<Any>,
+	sink: &impl MessageSink,
+	store: Option<&dyn StateStore>,
+) -> Result<(), anyhow::Error> {
+	let store = match store {
+		Some(store) => store,
+		None => {
+			let tx_hash = sink.submit(msgs).await?;
+			tracing::Span::current().record("tx_hash", &tracing::field::display(&tx_hash));
+			return Ok(())
+		},
+	};
+
+	let type_urls = msgs.iter().map(|msg| msg.type_url.clone()).collect();
+	let tx_hash = sink.broadcast(msgs).await?;
+	tracing::Span::current().record("tx_hash", &tracing::field::display(&tx_hash));
+	let pending = PendingTransaction {
+		tx_id: tx_hash.clone(),
+		type_urls,
+		submitted_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+	};
+	store.add_pending_transaction(sink.name(), pending)?;
+	let result = sink.wait_for_transaction(&tx_hash).await;
+	store.remove_pending_transaction(sink.name(), &tx_hash)?;
+	result?;
+
 	Ok(())
 }
+
+/// Removes packet messages that have already been delivered to the sink, most likely by another
+/// relayer.
+async fn drop_delivered(msgs: Vec<Any>, sink: &impl Chain) -> Vec<Any> {
+	let sink_height = match sink.latest_height_and_timestamp().await {
+		Ok((height, _)) => height,
+		Err(err) => {
+			log::warn!("Failed to query latest height of {}: {:?}", Chain::name(sink), err);
+			return msgs
+		},
+	};
+
+	let mut undelivered = Vec::with_capacity(msgs.len());
+	for msg in msgs {
+		match is_delivered(&msg, sink, sink_height).await {
+			Ok(true) => log::info!(
+				"Dropping {} that was already delivered to {}",
+				msg.type_url,
+				Chain::name(sink)
+			),
+			Ok(false) => undelivered.push(msg),
+			Err(err) => {
+				log::warn!("Failed to check if {} was delivered: {:?}", msg.type_url, err);
+				undelivered.push(msg)
+			},
+		}
+	}
+
+	undelivered
+}
+
+/// Checks the sink's state for whether the effect of the message has already been applied.
+async fn is_delivered(
+	msg: &Any,
+	sink: &impl Chain,
+	sink_height: Height,
+) -> Result<bool, anyhow::Error> {
+	match msg.type_url.as_str() {
+		recv_packet::TYPE_URL => {
+			let Packet { sequence, destination_port, destination_channel, .. } =
+				MsgRecvPacket::decode_vec(&msg.value)?.packet;
+			// ordered channels don't write receipts
+			let next_sequence_recv = sink
+				.query_next_sequence_recv(sink_height, &destination_port, &destination_channel)
+				.await?;
+			if next_sequence_recv.next_sequence_receive > u64::from(sequence) {
+				return Ok(true)
+			}
+			let receipt = sink
+				.query_packet_receipt(
+					sink_height,
+					&destination_port,
+					&destination_channel,
+					sequence.into(),
+				)
+				.await?;
+			Ok(receipt.received)
+		},
+		acknowledgement::TYPE_URL =>
+			is_commitment_cleared(
+				MsgAcknowledgement::decode_vec(&msg.value)?.packet,
+				sink,
+				sink_height,
+			)
+			.await,
+		timeout::TYPE_URL =>
+			is_commitment_cleared(MsgTimeout::decode_vec(&msg.value)?.packet, sink, sink_height)
+				.await,
+		timeout_on_close::TYPE_URL =>
+			is_commitment_cleared(
+				MsgTimeoutOnClose::decode_vec(&msg.value)?.packet,
+				sink,
+				sink_height,
+			)
+			.await,
+		_ => Ok(false),
+	}
+}
+
+/// The packet commitment on the packet's source chain is deleted once the packet has been
+/// acknowledged or timed out.
+async fn is_commitment_cleared(
+	packet: Packet,
+	sink: &impl Chain,
+	sink_height: Height,
+) -> Result<bool, anyhow::Error> {
+	let commitment = sink
+		.query_packet_commitment(
+			sink_height,
+			&packet.source_port,
+			&packet.source_channel,
+			packet.sequence.into(),
+		)
+		.await?;
+	Ok(commitment.commitment.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::anyhow;
+	use primitives::store::InMemoryStore;
+	use std::{collections::HashSet, sync::Mutex};
+
+	/// Sink whose transactions fail if they contain an invalid message.
+	#[derive(Default)]
+	struct MockSink {
+		block_max_weight: u64,
+		weight_per_message: u64,
+		estimate_fails: bool,
+		invalid: HashSet<String>,
+		delivered: HashSet<String>,
+		transactions: Mutex<Vec<Vec<String>>>,
+	}
+
+	impl MockSink {
+		fn execute(&self, msgs: Vec<Any>) -> Result<String, anyhow::Error> {
+			let type_urls = msgs.into_iter().map(|msg| msg.type_url).collect::<Vec<_>>();
+			if let Some(invalid) = type_urls.iter().find(|url| self.invalid.contains(*url)) {
+				return Err(anyhow!("invalid message {}", invalid))
+			}
+			let mut transactions = self.transactions.lock().unwrap();
+			transactions.push(type_urls);
+			Ok(format!("tx-{}", transactions.len()))
+		}
+
+		fn transactions(&self) -> Vec<Vec<String>> {
+			self.transactions.lock().unwrap().clone()
+		}
+	}
+
+	#[async_trait::async_trait]
+	impl MessageSink for MockSink {
+		fn name(&self) -> &str {
+			"mock"
+		}
+
+		fn block_max_weight(&self) -> u64 {
+			self.block_max_weight
+		}
+
+		async fn estimate_weight(&self, msgs: Vec<Any>) -> Result<u64, anyhow::Error> {
+			if self.estimate_fails {
+				return Err(anyhow!("estimation failed"))
+			}
+			Ok(msgs.len() as u64 * self.weight_per_message)
+		}
+
+		async fn submit(&self, msgs: Vec<Any>) -> Result<String, anyhow::Error> {
+			self.execute(msgs)
+		}
+
+		async fn broadcast(&self, msgs: Vec<Any>) -> Result<String, anyhow::Error> {
+			self.execute(msgs)
+		}
+
+		async fn wait_for_transaction(&self, _tx_hash: &str) -> Result<(), anyhow::Error> {
+			Ok(())
+		}
+
+		async fn drop_delivered(&self, msgs: Vec<Any>) -> Vec<Any> {
+			msgs.into_iter().filter(|msg| !self.delivered.contains(&msg.type_url)).collect()
+		}
+	}
+
+	fn messages(count: usize) -> Vec<Any> {
+		(0..count)
+			.map(|i| Any { type_url: format!("/msg{}", i), value: vec![] })
+			.collect()
+	}
+
+	fn urls(indices: &[usize]) -> Vec<String> {
+		indices.iter().map(|i| format!("/msg{}", i)).collect()
+	}
+
+	#[tokio::test]
+	async fn propagates_weight_estimation_errors() {
+		let sink = MockSink { block_max_weight: 100, estimate_fails: true, ..Default::default() };
+
+		let err = flush_message_batch(messages(2), None, &sink, None).await.unwrap_err();
+
+		assert!(format!("{:?}", err).contains("estimation failed"));
+		assert!(sink.transactions().is_empty());
+	}
+
+	#[tokio::test]
+	async fn chunks_batches_exceeding_the_block_max_weight() {
+		let sink = MockSink { block_max_weight: 20, weight_per_message: 10, ..Default::default() };
+		let store = InMemoryStore::default();
+
+		// 60 / 20 = 3, so the batch is split into 5 chunks of at most 2 messages.
+		flush_message_batch(messages(6), None, &sink, Some(&store)).await.unwrap();
+
+		assert_eq!(sink.transactions(), vec![urls(&[0, 1]), urls(&[2, 3]), urls(&[4, 5])]);
+		assert!(store.pending_transactions("mock").unwrap().is_empty());
+	}
+
+	#[tokio::test]
+	async fn delivers_the_rest_of_a_partially_failing_batch() {
+		let sink = MockSink {
+			block_max_weight: 100,
+			weight_per_message: 1,
+			invalid: HashSet::from(["/msg2".to_string()]),
+			..Default::default()
+		};
+
+		let err = flush_message_batch(messages(4), None, &sink, None).await.unwrap_err();
+
+		// the batch is halved until the failing message is isolated, order is preserved.
+		assert_eq!(sink.transactions(), vec![urls(&[0, 1]), urls(&[3])]);
+		let undelivered = err.downcast::<UndeliveredMessages>().unwrap();
+		assert_eq!(undelivered.type_urls, urls(&[2]));
+		assert_eq!(undelivered.total, 4);
+	}
+
+	#[tokio::test]
+	async fn drops_messages_delivered_by_another_relayer() {
+		let sink = MockSink {
+			block_max_weight: 100,
+			weight_per_message: 1,
+			invalid: HashSet::from(["/msg1".to_string()]),
+			delivered: HashSet::from(["/msg1".to_string()]),
+			..Default::default()
+		};
+
+		flush_message_batch(messages(3), None, &sink, None).await.unwrap();
+
+		assert_eq!(sink.transactions(), vec![urls(&[0]), urls(&[2])]);
+	}
+}
@@ -0,0 +1,180 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Picks up the work a previous run of the relayer left unfinished, from the state it recorded
+//! in the [`StateStore`].
+
+use std::{future::Future, ops::RangeInclusive};
+
+use ibc::core::ics24_host::identifier::{ChannelId, PortId};
+use metrics::handler::MetricsHandler;
+use primitives::{
+	store::{StateStore, TransactionStatus},
+	Chain,
+};
+
+use crate::{packets::query_ready_and_timed_out_packets_on_channels, queue};
+
+/// Resolves the transactions that were still waiting for confirmation on the chain when the
+/// relayer stopped, every one of them is removed from the store once its status is known.
+pub async fn resolve_pending_transactions(
+	chain: &impl Chain,
+	store: &dyn StateStore,
+) -> Result<(), anyhow::Error> {
+	resolve_pending_with(chain.name(), store, |tx_hash| async move {
+		chain.query_transaction_status(&tx_hash).await.map_err(anyhow::Error::from)
+	})
+	.await
+}
+
+/// Relays the packets that were in flight on the source's channels when the relayer stopped.
+/// Packets that have been delivered in the meantime are skipped, the others are submitted again
+/// without waiting for a finality event.
+pub async fn resume_in_flight_packets(
+	source: &impl Chain,
+	sink: &impl Chain,
+	store: &dyn StateStore,
+	metrics: Option<&MetricsHandler>,
+) -> Result<(), anyhow::Error> {
+	for (channel_id, port_id, sequences) in
+		in_flight_ranges(source.name(), store, source.channel_whitelist())?
+	{
+		log::info!(target: "hyperspace",
+			"Resuming packets {:?} in flight from {} on {}/{}", sequences, source.name(), port_id, channel_id);
+		let (messages, timeouts) = query_ready_and_timed_out_packets_on_channels(
+			source,
+			sink,
+			Some(store),
+			vec![(channel_id, port_id)],
+			Some(sequences),
+		)
+		.await?;
+		if !messages.is_empty() {
+			queue::flush_message_batch(messages, metrics, sink, Some(store)).await?;
+		}
+		if !timeouts.is_empty() {
+			queue::flush_message_batch(timeouts, None, source, Some(store)).await?;
+		}
+	}
+	Ok(())
+}
+
+async fn resolve_pending_with<F, Fut>(
+	chain: &str,
+	store: &dyn StateStore,
+	query_status: F,
+) -> Result<(), anyhow::Error>
+where
+	F: Fn(String) -> Fut,
+	Fut: Future<Output = Result<TransactionStatus, anyhow::Error>>,
+{
+	for tx in store.pending_transactions(chain)? {
+		let status = query_status(tx.tx_id.clone()).await.unwrap_or_else(|err| {
+			log::warn!(target: "hyperspace", "Failed to query transaction {} on {}: {:?}", tx.tx_id, chain, err);
+			TransactionStatus::Unknown
+		});
+		match status {
+			TransactionStatus::Included => log::info!(target: "hyperspace",
+				"Transaction {} submitted to {} by a previous run was included", tx.tx_id, chain),
+			TransactionStatus::Failed(err) => log::warn!(target: "hyperspace",
+				"Transaction {} submitted to {} by a previous run failed: {}", tx.tx_id, chain, err),
+			// its packets are still recorded as in flight and are submitted again.
+			TransactionStatus::Unknown => log::warn!(target: "hyperspace",
+				"Transaction {} submitted to {} by a previous run wasn't confirmed: {:?}", tx.tx_id, chain, tx.type_urls),
+		}
+		store.remove_pending_transaction(chain, &tx.tx_id)?;
+	}
+	Ok(())
+}
+
+/// Returns the range of the sequences recorded as in flight on each of the channels, channels
+/// without packets in flight are left out.
+fn in_flight_ranges(
+	chain: &str,
+	store: &dyn StateStore,
+	channels: Vec<(ChannelId, PortId)>,
+) -> Result<Vec<(ChannelId, PortId, RangeInclusive<u64>)>, anyhow::Error> {
+	let mut ranges = vec![];
+	for (channel_id, port_id) in channels {
+		let sequences = store.in_flight_sequences(chain, &channel_id, &port_id)?;
+		if let (Some(first), Some(last)) = (sequences.iter().min(), sequences.iter().max()) {
+			ranges.push((channel_id, port_id, *first..=*last));
+		}
+	}
+	Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::anyhow;
+	use primitives::store::{InMemoryStore, PendingTransaction};
+
+	fn pending(tx_id: &str) -> PendingTransaction {
+		PendingTransaction {
+			tx_id: tx_id.to_string(),
+			type_urls: vec!["/ibc.core.channel.v1.MsgRecvPacket".to_string()],
+			submitted_at: 0,
+		}
+	}
+
+	#[tokio::test]
+	async fn restart_resolves_state_left_by_a_crash() {
+		// state left behind by a relayer that crashed while waiting for its transactions.
+		let store = InMemoryStore::default();
+		for tx_id in ["included", "failed", "dropped", "unreachable"] {
+			store.add_pending_transaction("sink", pending(tx_id)).unwrap();
+		}
+		store.add_pending_transaction("other", pending("other")).unwrap();
+		let port_id = PortId::transfer();
+		store
+			.set_in_flight_sequences("source", &ChannelId::new(0), &port_id, vec![7, 3, 5])
+			.unwrap();
+		store
+			.set_in_flight_sequences("source", &ChannelId::new(1), &port_id, vec![])
+			.unwrap();
+
+		let queried = std::sync::Mutex::new(vec![]);
+		resolve_pending_with("sink", &store, |tx_hash| {
+			queried.lock().unwrap().push(tx_hash.clone());
+			async move {
+				match tx_hash.as_str() {
+					"included" => Ok(TransactionStatus::Included),
+					"failed" => Ok(TransactionStatus::Failed("out of gas".to_string())),
+					"dropped" => Ok(TransactionStatus::Unknown),
+					_ => Err(anyhow!("connection refused")),
+				}
+			}
+		})
+		.await
+		.unwrap();
+
+		let mut queried = queried.into_inner().unwrap();
+		queried.sort();
+		assert_eq!(queried, vec!["dropped", "failed", "included", "unreachable"]);
+		assert!(store.pending_transactions("sink").unwrap().is_empty());
+		// other chains are resolved against their own rpc.
+		assert_eq!(store.pending_transactions("other").unwrap(), vec![pending("other")]);
+
+		let channels = vec![
+			(ChannelId::new(0), port_id.clone()),
+			(ChannelId::new(1), port_id.clone()),
+			(ChannelId::new(2), port_id.clone()),
+		];
+		assert_eq!(
+			in_flight_ranges("source", &store, channels).unwrap(),
+			vec![(ChannelId::new(0), port_id, 3..=7)]
+		);
+	}
+}
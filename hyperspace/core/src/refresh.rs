@@ -0,0 +1,97 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Optional light client updates are only sent along with packets, so the client of a chain
+//! without traffic would expire once its trusting period has passed. Clients are refreshed when a
+//! configured fraction of their trusting period has elapsed since their last update.
+
+use std::time::Duration;
+
+use ibc::core::ics02_client::client_state::ClientState as ClientStateT;
+use pallet_ibc::light_clients::AnyClientState;
+use primitives::{error::Error, Chain};
+
+/// Time elapsed since the last update of a light client, relative to its trusting period.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientExpiry {
+	pub trusting_period: Duration,
+	/// Time since the last update of the client, as measured by the host chain.
+	pub elapsed: Duration,
+}
+
+impl ClientExpiry {
+	/// Time left before the client expires.
+	pub fn time_left(&self) -> Duration {
+		self.trusting_period.saturating_sub(self.elapsed)
+	}
+
+	/// Returns true once `threshold`, a fraction of the trusting period, has elapsed.
+	pub fn needs_refresh(&self, threshold: f64) -> bool {
+		self.elapsed >= self.trusting_period.mul_f64(threshold)
+	}
+}
+
+/// Queries how long ago the light client of `source` on `sink` was last updated, returns `None` if
+/// the client doesn't expire.
+pub async fn query_client_expiry(
+	source: &impl Chain,
+	sink: &impl Chain,
+) -> Result<Option<ClientExpiry>, anyhow::Error> {
+	let (sink_height, sink_timestamp) = sink.latest_height_and_timestamp().await?;
+	let response = sink.query_client_state(sink_height, source.client_id()).await?;
+	let client_state = AnyClientState::try_from(response.client_state.ok_or_else(|| {
+		Error::Custom(format!("Client state for {} should exist on {}", source.name(), sink.name()))
+	})?)
+	.map_err(|_| {
+		Error::Custom(format!(
+			"Invalid client state for {} found on {}",
+			source.name(),
+			sink.name()
+		))
+	})?;
+	let trusting_period = match client_state {
+		AnyClientState::Grandpa(ref client_state) => client_state.relay_chain.trusting_period(),
+		AnyClientState::Beefy(ref client_state) => client_state.relay_chain.trusting_period(),
+		AnyClientState::Tendermint(ref client_state) => client_state.trusting_period,
+		AnyClientState::Near(_) | AnyClientState::Solomachine(_) | AnyClientState::Localhost(_) =>
+			return Ok(None),
+	};
+	let (_, updated_at) = sink
+		.query_client_update_time_and_height(source.client_id(), client_state.latest_height())
+		.await?;
+	let elapsed = sink_timestamp.duration_since(&updated_at).unwrap_or_default();
+	Ok(Some(ClientExpiry { trusting_period, elapsed }))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+	#[test]
+	fn clients_are_refreshed_once_the_threshold_of_their_trusting_period_has_elapsed() {
+		let expiry = |elapsed| ClientExpiry { trusting_period: DAY * 14, elapsed };
+
+		assert!(!expiry(Duration::ZERO).needs_refresh(0.5));
+		assert!(!expiry(DAY * 7 - Duration::from_secs(1)).needs_refresh(0.5));
+		assert!(expiry(DAY * 7).needs_refresh(0.5));
+		assert!(!expiry(DAY * 7).needs_refresh(0.75));
+		assert_eq!(expiry(DAY * 7).time_left(), DAY * 7);
+
+		// past its trusting period, the client has no time left.
+		assert!(expiry(DAY * 15).needs_refresh(0.5));
+		assert_eq!(expiry(DAY * 15).time_left(), Duration::ZERO);
+	}
+}
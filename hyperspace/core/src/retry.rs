@@ -0,0 +1,240 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{chain::AnyError, queue::UndeliveredMessages};
+use futures::future;
+use primitives::error::{Error, ErrorKind};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Delay before the first retry of a failed operation.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Maximum delay between retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(120);
+
+/// Classifies an error returned while relaying, by looking for a known error type in its chain
+/// of causes. Unknown errors are assumed to be transient.
+pub fn classify(err: &anyhow::Error) -> ErrorKind {
+	for cause in err.chain() {
+		if let Some(err) = cause.downcast_ref::<AnyError>() {
+			return match err {
+				AnyError::Parachain(err) => err.kind(),
+				AnyError::Cosmos(err) => err.kind(),
+				AnyError::Near(err) => err.kind(),
+				AnyError::Other(_) => ErrorKind::Retryable,
+			}
+		}
+		if let Some(err) = cause.downcast_ref::<parachain::error::Error>() {
+			return err.kind()
+		}
+		if let Some(err) = cause.downcast_ref::<cosmos::error::Error>() {
+			return err.kind()
+		}
+		if let Some(err) = cause.downcast_ref::<near::error::Error>() {
+			return err.kind()
+		}
+		if let Some(err) = cause.downcast_ref::<Error>() {
+			return err.kind()
+		}
+		if cause.is::<UndeliveredMessages>() {
+			return ErrorKind::Rejected
+		}
+	}
+	ErrorKind::Retryable
+}
+
+/// Exponential backoff between retries of an operation that keeps failing. The next attempt is
+/// scheduled rather than waited for, so that a relay loop keeps handling its other events while
+/// one of them is backing off.
+#[derive(Debug)]
+pub struct Backoff {
+	delay: Duration,
+	retry_at: Option<Instant>,
+}
+
+impl Default for Backoff {
+	fn default() -> Self {
+		Self { delay: INITIAL_BACKOFF, retry_at: None }
+	}
+}
+
+impl Backoff {
+	/// The operation succeeded, the next failure is retried after the initial delay.
+	pub fn reset(&mut self) {
+		self.delay = INITIAL_BACKOFF;
+		self.retry_at = None;
+	}
+
+	/// Schedules the next attempt after the current delay, doubling the delay up to the maximum.
+	/// Returns the delay until the attempt.
+	pub fn schedule(&mut self) -> Duration {
+		let delay = self.delay;
+		self.retry_at = Some(Instant::now() + delay);
+		self.delay = (delay * 2).min(MAX_BACKOFF);
+		delay
+	}
+
+	/// Returns true if an attempt is scheduled and not yet due.
+	pub fn is_scheduled(&self) -> bool {
+		self.retry_at.is_some()
+	}
+
+	/// Completes once the scheduled attempt is due, never if there's none. This is cancel safe,
+	/// the attempt stays scheduled if the future is dropped before completing.
+	pub async fn elapsed(&mut self) {
+		match self.retry_at {
+			Some(retry_at) => {
+				tokio::time::sleep_until(retry_at).await;
+				self.retry_at = None;
+			},
+			None => future::pending().await,
+		}
+	}
+
+	/// Schedules the next attempt and waits until it's due.
+	pub async fn wait(&mut self) {
+		self.schedule();
+		self.elapsed().await;
+	}
+
+	/// Handles an error of an operation on `chain`: fatal errors are returned, rejected
+	/// transactions are logged and retryable errors schedule another attempt. Returns true if an
+	/// attempt was scheduled.
+	pub fn on_error(&mut self, chain: &str, err: anyhow::Error) -> Result<bool, anyhow::Error> {
+		match classify(&err) {
+			ErrorKind::Fatal => {
+				log::error!(target: "hyperspace", "Fatal error relaying {}: {:?}", chain, err);
+				Err(err)
+			},
+			ErrorKind::Rejected => {
+				log::error!(target: "hyperspace", "Transaction rejected relaying {}: {:?}", chain, err);
+				Ok(false)
+			},
+			ErrorKind::Retryable => {
+				let delay = self.schedule();
+				log::warn!(target: "hyperspace",
+					"Error relaying {}, retrying in {:?}: {:?}", chain, delay, err);
+				Ok(true)
+			},
+		}
+	}
+}
+
+/// Work of a relay loop postponed until the backoff of its chain has elapsed.
+pub enum Retry<T> {
+	/// The finality notifications stream ended, the chain should be reconnected to and
+	/// subscribed to again.
+	Resubscribe,
+	/// Processing the finality event failed, it's processed again. A newer finality event
+	/// received in the meantime replaces it.
+	FinalityEvent(T),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use anyhow::anyhow;
+
+	#[test]
+	fn errors_are_classified_by_their_causes() {
+		let cases = [
+			(anyhow!("connection reset"), ErrorKind::Retryable),
+			(anyhow::Error::new(Error::Custom("rpc timeout".into())), ErrorKind::Retryable),
+			(anyhow::Error::new(Error::Keystore("bad passphrase".into())), ErrorKind::Fatal),
+			(
+				anyhow::Error::new(AnyError::Parachain(parachain::error::Error::PalletNotFound(
+					"Ibc",
+				))),
+				ErrorKind::Fatal,
+			),
+			(
+				anyhow::Error::new(parachain::error::Error::ExtrinsicFailed("bad proof".into())),
+				ErrorKind::Rejected,
+			),
+			(
+				anyhow::Error::new(cosmos::error::Error::TransactionFailed {
+					tx_hash: "ABCD".into(),
+					code: 5,
+					log: "insufficient funds".into(),
+				})
+				.context("failed to submit transaction"),
+				ErrorKind::Rejected,
+			),
+			(
+				anyhow::Error::new(UndeliveredMessages {
+					chain: "sink".into(),
+					type_urls: vec!["/ibc.core.channel.v1.MsgRecvPacket".into()],
+					total: 2,
+				})
+				.context("failed to relay packets"),
+				ErrorKind::Rejected,
+			),
+		];
+		for (err, kind) in cases {
+			assert_eq!(classify(&err), kind, "{:?}", err);
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn backoff_doubles_up_to_the_maximum_and_resets() {
+		let mut backoff = Backoff::default();
+		assert!(!backoff.is_scheduled());
+
+		let start = Instant::now();
+		assert_eq!(backoff.schedule(), INITIAL_BACKOFF);
+		assert!(backoff.is_scheduled());
+		backoff.elapsed().await;
+		assert_eq!(start.elapsed(), INITIAL_BACKOFF);
+		assert!(!backoff.is_scheduled());
+
+		assert_eq!(backoff.schedule(), INITIAL_BACKOFF * 2);
+		assert_eq!(backoff.schedule(), INITIAL_BACKOFF * 4);
+		for _ in 0..10 {
+			backoff.schedule();
+		}
+		assert_eq!(backoff.schedule(), MAX_BACKOFF);
+
+		backoff.reset();
+		assert!(!backoff.is_scheduled());
+		assert_eq!(backoff.schedule(), INITIAL_BACKOFF);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn backoff_is_only_scheduled_for_retryable_errors() {
+		let mut backoff = Backoff::default();
+		assert!(backoff.on_error("chain", anyhow!("connection reset")).unwrap());
+		assert!(backoff.is_scheduled());
+
+		// a scheduled attempt that's cancelled is still due afterwards.
+		let start = Instant::now();
+		tokio::select! {
+			_ = backoff.elapsed() => unreachable!("the attempt isn't due yet"),
+			_ = tokio::time::sleep(INITIAL_BACKOFF / 2) => {},
+		}
+		assert!(backoff.is_scheduled());
+		backoff.elapsed().await;
+		assert_eq!(start.elapsed(), INITIAL_BACKOFF);
+
+		let rejected = anyhow::Error::new(UndeliveredMessages {
+			chain: "sink".into(),
+			type_urls: vec![],
+			total: 1,
+		});
+		assert!(!backoff.on_error("chain", rejected).unwrap());
+		assert!(!backoff.is_scheduled());
+		let fatal = anyhow::Error::new(Error::Keystore("bad passphrase".into()));
+		assert!(backoff.on_error("chain", fatal).is_err());
+		assert!(!backoff.is_scheduled());
+	}
+}
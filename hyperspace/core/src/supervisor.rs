@@ -0,0 +1,290 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Relays many paths from a single process, chain clients (and with them rpc connections and
+//! signers) are shared between all paths that relay to or from the same chain.
+
+use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use futures::{
+	future::{self, BoxFuture},
+	stream::FuturesUnordered,
+	FutureExt, StreamExt,
+};
+use metrics::{data::Metrics, handler::MetricsHandler, init_prometheus};
+use primitives::{
+	store::{PrefixedStore, SharedStateStore},
+	Chain,
+};
+use prometheus::Registry;
+use tokio::task::JoinHandle;
+
+use crate::{
+	api::{self, ApiPath, PathControl},
+	chain::{AnyChain, MultiPathConfig, PathConfig, PathEndConfig},
+	multihop, relay,
+};
+
+/// Delay before a failed relay path is started again.
+const RESTART_DELAY: Duration = Duration::from_secs(10);
+
+/// Starts a relay path, called again every time the path is restarted.
+type PathTask = Box<dyn Fn() -> BoxFuture<'static, Result<(), anyhow::Error>> + Send + Sync>;
+
+/// Spawns a relay task for every path in the config and supervises them until ctrl-c is
+/// received. A failing path is logged and restarted without affecting the other paths.
+pub async fn supervise(config: MultiPathConfig) -> Result<(), anyhow::Error> {
+	let mut chains = HashMap::new();
+	for chain_config in config.chains {
+		let name = chain_config.name().to_string();
+		let chain = chain_config.into_client().await?;
+		if chains.insert(name.clone(), chain).is_some() {
+			return Err(anyhow!("Chain {} is configured more than once", name))
+		}
+	}
+
+	let store = config.core.state_store()?;
+	let clear_interval = config.core.clear_interval;
+	let refresh_threshold = config.core.refresh_threshold;
+
+	let registry =
+		Registry::new_custom(None, None).expect("this can only fail if the prefix is empty");
+	if let Some(addr) = config.core.prometheus_endpoint.map(|s| s.parse().ok()).flatten() {
+		tokio::spawn(init_prometheus(addr, registry.clone()));
+	}
+
+	let mut tasks = Vec::<(String, PathTask)>::new();
+	let mut api_paths = vec![];
+	for path in config.paths {
+		let chain_a = path_end_client(&chains, &path.chain_a)?;
+		let chain_b = path_end_client(&chains, &path.chain_b)?;
+		let (metrics_a, metrics_b) = path_metrics(&path, &chain_a, &chain_b, &registry)?;
+
+		log::info!(target: "hyperspace", "Starting relay path {}: {} <-> {}", path.name, chain_a.name(), chain_b.name());
+		// paths share chains, their progress is tracked separately.
+		let path_store = store.clone().map(|store| {
+			Arc::new(PrefixedStore::new(path.name.clone(), store)) as SharedStateStore
+		});
+		let control = Arc::new(PathControl::default());
+		api_paths.push(ApiPath {
+			name: path.name.clone(),
+			chain_a: chain_a.clone(),
+			chain_b: chain_b.clone(),
+			control: control.clone(),
+		});
+		let task: PathTask = if path.hops.is_empty() {
+			Box::new(move || {
+				relay(
+					chain_a.clone(),
+					chain_b.clone(),
+					Some(metrics_a.clone()),
+					Some(metrics_b.clone()),
+					None,
+					path_store.clone(),
+					clear_interval,
+					refresh_threshold,
+					Some(control.clone()),
+				)
+				.boxed()
+			})
+		} else {
+			let hops = path
+				.hops
+				.iter()
+				.map(|hop| path_hop_client(&chains, hop))
+				.collect::<Result<Vec<_>, _>>()?;
+			Box::new(move || {
+				multihop::relay(
+					chain_a.clone(),
+					chain_b.clone(),
+					hops.clone(),
+					Some(control.clone()),
+				)
+				.boxed()
+			})
+		};
+		tasks.push((path.name, task));
+	}
+
+	if let Some(addr) = config.core.api_endpoint.map(|s| s.parse().ok()).flatten() {
+		tokio::spawn(api::serve(addr, config.core.api_token.clone(), api_paths));
+	}
+
+	let shutdown = async {
+		if let Err(err) = tokio::signal::ctrl_c().await {
+			log::error!(target: "hyperspace", "Failed to listen for the shutdown signal: {:?}", err);
+			future::pending::<()>().await
+		}
+	};
+	run_paths(tasks, RESTART_DELAY, shutdown).await;
+
+	Ok(())
+}
+
+/// Runs every relay path until `shutdown` resolves or all paths have exited. A path that fails
+/// or panics is logged and started again after `restart_delay`, a path that exits cleanly is not
+/// restarted. Paths that are still running on shutdown are aborted.
+async fn run_paths(
+	paths: Vec<(String, PathTask)>,
+	restart_delay: Duration,
+	shutdown: impl Future<Output = ()>,
+) {
+	let start_path = |index: usize, delay: Duration| {
+		let task = &paths[index].1;
+		async move {
+			tokio::time::sleep(delay).await;
+			let mut handle = AbortOnDrop(tokio::spawn(task()));
+			(index, (&mut handle.0).await)
+		}
+	};
+	let mut tasks = (0..paths.len())
+		.map(|index| start_path(index, Duration::ZERO))
+		.collect::<FuturesUnordered<_>>();
+	futures::pin_mut!(shutdown);
+
+	while !tasks.is_empty() {
+		tokio::select! {
+			_ = &mut shutdown => {
+				log::info!(target: "hyperspace", "Shutting down {} relay paths", tasks.len());
+				return
+			},
+			Some((index, result)) = tasks.next() => {
+				let name = &paths[index].0;
+				match result {
+					Ok(Ok(())) => {
+						log::info!(target: "hyperspace", "Relay path {} exited", name);
+						continue
+					},
+					Ok(Err(err)) =>
+						log::error!(target: "hyperspace", "Relay path {} failed: {:?}", name, err),
+					Err(err) =>
+						log::error!(target: "hyperspace", "Relay path {} panicked: {:?}", name, err),
+				}
+				log::info!(target: "hyperspace", "Restarting relay path {} in {:?}", name, restart_delay);
+				tasks.push(start_path(index, restart_delay));
+			},
+		}
+	}
+}
+
+/// Aborts the spawned relay task when dropped, so that no path outlives the supervisor.
+struct AbortOnDrop(JoinHandle<Result<(), anyhow::Error>>);
+
+impl Drop for AbortOnDrop {
+	fn drop(&mut self) {
+		self.0.abort();
+	}
+}
+
+/// Returns a handle to the chain at one end of a path, configured with the path's ids.
+fn path_end_client(
+	chains: &HashMap<String, AnyChain>,
+	path_end: &PathEndConfig,
+) -> Result<AnyChain, anyhow::Error> {
+	let mut chain = chains
+		.get(&path_end.chain)
+		.ok_or_else(|| anyhow!("Unknown chain {} in path config", path_end.chain))?
+		.clone();
+	chain.set_path_end(path_end);
+	Ok(chain)
+}
+
+/// Returns a handle to an intermediate chain of a multi-hop path.
+fn path_hop_client(
+	chains: &HashMap<String, AnyChain>,
+	hop: &str,
+) -> Result<AnyChain, anyhow::Error> {
+	chains
+		.get(hop)
+		.cloned()
+		.ok_or_else(|| anyhow!("Unknown chain {} in path config", hop))
+}
+
+/// Registers the metrics of both ends of a path, labelled with the path's name.
+fn path_metrics(
+	path: &PathConfig,
+	chain_a: &AnyChain,
+	chain_b: &AnyChain,
+	registry: &Registry,
+) -> Result<(MetricsHandler, MetricsHandler), anyhow::Error> {
+	let labels = HashMap::from([("path".to_string(), path.name.clone())]);
+	let metrics_a = Metrics::register_with_labels(chain_a.name(), labels.clone(), registry)?;
+	let metrics_b = Metrics::register_with_labels(chain_b.name(), labels, registry)?;
+	let mut metrics_handler_a = MetricsHandler::new(registry.clone(), metrics_a);
+	let mut metrics_handler_b = MetricsHandler::new(registry.clone(), metrics_b);
+	metrics_handler_a.link_with_counterparty(&mut metrics_handler_b);
+	Ok((metrics_handler_a, metrics_handler_b))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use tokio::sync::oneshot;
+
+	#[tokio::test]
+	async fn restarts_failed_and_panicked_paths() {
+		let starts = Arc::new(AtomicUsize::new(0));
+		let task_starts = starts.clone();
+		let task: PathTask = Box::new(move || {
+			let run = task_starts.fetch_add(1, Ordering::SeqCst);
+			async move {
+				match run {
+					0 => Err(anyhow!("connection lost")),
+					1 => panic!("relay task panicked"),
+					_ => Ok(()),
+				}
+			}
+			.boxed()
+		});
+
+		run_paths(vec![("path".into(), task)], Duration::from_millis(1), future::pending()).await;
+
+		// failed, panicked, then exited cleanly and wasn't restarted again.
+		assert_eq!(starts.load(Ordering::SeqCst), 3);
+	}
+
+	#[tokio::test]
+	async fn aborts_running_paths_on_shutdown() {
+		let (started, running) = oneshot::channel::<()>();
+		let (guard, released) = oneshot::channel::<()>();
+		let channels = std::sync::Mutex::new(Some((started, guard)));
+		let task: PathTask = Box::new(move || {
+			let (started, guard) = channels.lock().unwrap().take().expect("started only once");
+			async move {
+				let _guard = guard;
+				started.send(()).unwrap();
+				future::pending().await
+			}
+			.boxed()
+		});
+		let (shutdown, signal) = oneshot::channel::<()>();
+		let supervisor = tokio::spawn(run_paths(
+			vec![("path".into(), task)],
+			Duration::from_millis(1),
+			signal.map(|_| ()),
+		));
+
+		running.await.unwrap();
+		shutdown.send(()).unwrap();
+		tokio::time::timeout(Duration::from_secs(5), supervisor)
+			.await
+			.expect("supervisor should stop on shutdown")
+			.unwrap();
+		// the aborted task drops its guard without sending on it.
+		let released = tokio::time::timeout(Duration::from_secs(5), released).await;
+		assert!(matches!(released, Ok(Err(_))));
+	}
+}
@@ -0,0 +1,364 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{pin::Pin, time::Duration};
+
+use anyhow::anyhow;
+use futures::{Stream, StreamExt};
+use ibc::{
+	core::ics02_client::{events::UpdateClient, msgs::update_client::MsgUpdateAnyClient},
+	tx_msg::Msg,
+};
+use ibc_proto::{
+	cosmos::{
+		base::v1beta1::Coin,
+		tx::{
+			signing::v1beta1::SignMode,
+			v1beta1::{
+				mode_info::{Single, Sum},
+				service_client::ServiceClient,
+				AuthInfo, Fee, ModeInfo, SignDoc, SignerInfo, SimulateRequest, TxBody, TxRaw,
+			},
+		},
+	},
+	google::protobuf::Any,
+};
+use ics07_tendermint::client_message::{ClientMessage, Header, Misbehaviour};
+use pallet_ibc::light_clients::AnyClientMessage;
+use primitives::{
+	mock::LocalClientTypes, store::TransactionStatus, Chain, IbcProvider, MisbehaviourHandler,
+};
+use prost::Message;
+use tendermint::Hash;
+use tendermint_rpc::{
+	event::EventData,
+	query::{EventType, Query},
+	Client, Order, SubscriptionClient, WebSocketClient,
+};
+use tokio::time::sleep;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::{error::Error, fetch_tendermint_header, provider::FinalityEvent, to_any, CosmosClient};
+
+/// Type url of secp256k1 public keys in the cosmos-sdk
+const SECP256K1_PUB_KEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+
+/// How long we wait for a broadcasted transaction to be included in a block
+const TX_COMMIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Protobuf representation of a cosmos-sdk secp256k1 public key
+#[derive(Clone, PartialEq, Message)]
+struct Secp256k1PubKey {
+	#[prost(bytes = "vec", tag = "1")]
+	key: Vec<u8>,
+}
+
+/// Parses a transaction hash returned by [`Chain::broadcast`].
+fn parse_tx_hash(tx_hash: &str) -> Result<Hash, Error> {
+	tx_hash
+		.parse()
+		.map_err(|e| Error::from(format!("Invalid transaction hash {}: {}", tx_hash, e)))
+}
+
+#[async_trait::async_trait]
+impl Chain for CosmosClient {
+	fn name(&self) -> &str {
+		&*self.name
+	}
+
+	fn block_max_weight(&self) -> u64 {
+		self.gas_limit
+	}
+
+	async fn estimate_weight(&self, messages: Vec<Any>) -> Result<u64, Self::Error> {
+		let tx_bytes = self.build_signed_tx(messages).await?;
+		let mut client = ServiceClient::connect(self.grpc_url.clone()).await?;
+		#[allow(deprecated)]
+		let request = tonic::Request::new(SimulateRequest { tx: None, tx_bytes });
+		let gas_info = client
+			.simulate(request)
+			.await?
+			.into_inner()
+			.gas_info
+			.ok_or_else(|| Error::Custom("Simulation returned no gas info".to_string()))?;
+		Ok(gas_info.gas_used)
+	}
+
+	async fn finality_notifications(
+		&self,
+	) -> Pin<Box<dyn Stream<Item = <Self as IbcProvider>::FinalityEvent> + Send + Sync>> {
+		let (tx, rx) = tokio::sync::mpsc::channel(32);
+		// the relayer re-subscribes once the stream ends
+		let (client, driver) = match WebSocketClient::new(self.websocket_url.clone()).await {
+			Ok(client) => client,
+			Err(err) => {
+				log::error!("Failed to connect to tendermint websocket: {}", err);
+				return Box::pin(futures::stream::empty())
+			},
+		};
+		tokio::spawn(driver.run());
+		let mut subscription = match client.subscribe(EventType::NewBlock.into()).await {
+			Ok(subscription) => subscription,
+			Err(err) => {
+				log::error!("Failed to subscribe to new blocks: {}", err);
+				return Box::pin(futures::stream::empty())
+			},
+		};
+
+		tokio::spawn(async move {
+			// keep the client alive for as long as the subscription is.
+			let _client = client;
+			while let Some(event) = subscription.next().await {
+				let block = match event.map(|event| event.data) {
+					Ok(EventData::NewBlock { block: Some(block), .. }) => block,
+					Ok(_) => continue,
+					Err(err) => {
+						log::error!("Failed to fetch new block: {}", err);
+						continue
+					},
+				};
+				if tx.send(FinalityEvent::Tendermint(block.header)).await.is_err() {
+					break
+				}
+			}
+		});
+
+		Box::pin(ReceiverStream::new(rx))
+	}
+
+	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Error> {
+		let tx_hash = self.broadcast(messages).await?;
+		self.wait_for_transaction(&tx_hash).await?;
+		parse_tx_hash(&tx_hash)
+	}
+
+	async fn broadcast(&self, messages: Vec<Any>) -> Result<String, Error> {
+		let tx_bytes = self.build_signed_tx(messages).await?;
+		let response = self.rpc_client.broadcast_tx_sync(tx_bytes).await?;
+		if response.code.is_err() {
+			return Err(Error::TransactionFailed {
+				tx_hash: response.hash.to_string(),
+				code: response.code.value(),
+				log: response.log.to_string(),
+			})
+		}
+
+		Ok(response.hash.to_string())
+	}
+
+	async fn wait_for_transaction(&self, tx_hash: &str) -> Result<(), Error> {
+		self.wait_for_tx(parse_tx_hash(tx_hash)?).await
+	}
+
+	async fn query_transaction_status(&self, tx_hash: &str) -> Result<TransactionStatus, Error> {
+		match self.rpc_client.tx(parse_tx_hash(tx_hash)?, false).await {
+			Ok(response) if response.tx_result.code.is_err() =>
+				Ok(TransactionStatus::Failed(response.tx_result.log.to_string())),
+			Ok(_) => Ok(TransactionStatus::Included),
+			// the node reports unknown transactions as an error
+			Err(err) => {
+				log::debug!(target: "hyperspace", "Transaction {} not found: {}", tx_hash, err);
+				Ok(TransactionStatus::Unknown)
+			},
+		}
+	}
+
+	async fn query_client_message(&self, update: UpdateClient) -> Result<AnyClientMessage, Error> {
+		let header = match update.header {
+			Some(header) => header,
+			None => {
+				// the event didn't include the header, search for the transaction that emitted it.
+				let query = Query::eq("update_client.client_id", update.client_id().to_string())
+					.and_eq(
+						"update_client.consensus_height",
+						update.consensus_height().to_string(),
+					);
+				let response =
+					self.rpc_client.tx_search(query, false, 1, 1, Order::Ascending).await?;
+				response
+					.txs
+					.into_iter()
+					.flat_map(|tx| tx.tx_result.events)
+					.filter(|event| event.kind == "update_client")
+					.flat_map(|event| event.attributes)
+					.find(|attr| attr.key == "header")
+					.map(|attr| hex::decode(attr.value))
+					.transpose()?
+					.ok_or_else(|| Error::from("No client message found".to_owned()))?
+			},
+		};
+
+		let any = Any::decode(&*header)?;
+		AnyClientMessage::try_from(any)
+			.map_err(|e| Error::from(format!("Failed to decode client message: {:?}", e)))
+	}
+
+	async fn reconnect(&mut self) -> Result<(), Error> {
+		// the rpc and grpc clients connect on every request, the websocket used for finality
+		// notifications is opened when subscribing.
+		Ok(())
+	}
+}
+
+#[async_trait::async_trait]
+impl MisbehaviourHandler for CosmosClient {
+	async fn check_for_misbehaviour<C: Chain>(
+		&self,
+		counterparty: &C,
+		client_message: AnyClientMessage,
+	) -> Result<(), anyhow::Error> {
+		let header = match client_message {
+			AnyClientMessage::Tendermint(ClientMessage::Header(header)) => header,
+			_ => return Ok(()),
+		};
+		if let Some(misbehaviour) = self.query_misbehaviour(&header).await? {
+			counterparty
+				.submit(vec![MsgUpdateAnyClient::<LocalClientTypes>::new(
+					self.client_id(),
+					AnyClientMessage::Tendermint(ClientMessage::Misbehaviour(misbehaviour)),
+					counterparty.account_id(),
+				)
+				.to_any()])
+				.await
+				.map_err(|e| anyhow!("Failed to submit misbehaviour report: {:?}", e))?;
+		}
+		Ok(())
+	}
+}
+
+impl CosmosClient {
+	/// Checks a header submitted to the light client of this chain against the same block on the
+	/// trusted nodes, returns the misbehaviour to report if one of them has a different header.
+	pub async fn query_misbehaviour(&self, header: &Header) -> Result<Option<Misbehaviour>, Error> {
+		let target_height = self.to_ibc_height(header.signed_header.header.height);
+		let rpc_clients = if self.trusted_rpc_clients.is_empty() {
+			std::slice::from_ref(&self.rpc_client)
+		} else {
+			&self.trusted_rpc_clients[..]
+		};
+		for rpc_client in rpc_clients {
+			let trusted_header =
+				match fetch_tendermint_header(rpc_client, header.trusted_height, target_height)
+					.await
+				{
+					Ok(trusted_header) => trusted_header,
+					Err(err) => {
+						log::warn!(
+							"Failed to fetch header {} of {} from a trusted node: {:?}",
+							target_height,
+							self.name,
+							err
+						);
+						continue
+					},
+				};
+			let (hash, trusted_hash) =
+				(header.signed_header.header.hash(), trusted_header.signed_header.header.hash());
+			if hash != trusted_hash {
+				log::warn!(
+					"Found misbehaviour on client {}: {:?} != {:?}",
+					self.client_id
+						.as_ref()
+						.map(|x| x.as_str().to_owned())
+						.unwrap_or_else(|| "{unknown}".to_owned()),
+					hash,
+					trusted_hash
+				);
+				return Ok(Some(Misbehaviour {
+					client_id: self.client_id(),
+					header1: header.clone(),
+					header2: trusted_header,
+				}))
+			}
+		}
+		Ok(None)
+	}
+
+	/// Build a transaction containing the given messages and sign it with the relayer key using
+	/// `SIGN_MODE_DIRECT`, returns the encoded [`TxRaw`].
+	pub async fn build_signed_tx(&self, messages: Vec<Any>) -> Result<Vec<u8>, Error> {
+		let account = self.query_account().await?;
+
+		let body = TxBody {
+			messages,
+			memo: String::new(),
+			timeout_height: 0,
+			extension_options: vec![],
+			non_critical_extension_options: vec![],
+		};
+		let public_key = Secp256k1PubKey { key: self.keybase.public_key.clone() };
+		let signer_info = SignerInfo {
+			public_key: Some(to_any(SECP256K1_PUB_KEY_TYPE_URL, &public_key)),
+			mode_info: Some(ModeInfo {
+				sum: Some(Sum::Single(Single { mode: SignMode::Direct as i32 })),
+			}),
+			sequence: account.sequence,
+		};
+		let fee = Fee {
+			amount: vec![Coin { denom: self.fee_denom.clone(), amount: self.fee_amount.clone() }],
+			gas_limit: self.gas_limit,
+			payer: String::new(),
+			granter: String::new(),
+		};
+		let auth_info = AuthInfo { signer_infos: vec![signer_info], fee: Some(fee), tip: None };
+
+		let body_bytes = body.encode_to_vec();
+		let auth_info_bytes = auth_info.encode_to_vec();
+		let sign_doc = SignDoc {
+			body_bytes: body_bytes.clone(),
+			auth_info_bytes: auth_info_bytes.clone(),
+			chain_id: self.chain_id.to_string(),
+			account_number: account.account_number,
+		};
+		let signature = self.keybase.sign(&sign_doc.encode_to_vec());
+
+		let tx_raw = TxRaw { body_bytes, auth_info_bytes, signatures: vec![signature] };
+		let tx_bytes = tx_raw.encode_to_vec();
+		if tx_bytes.len() as u64 > self.max_tx_size {
+			return Err(Error::Custom(format!(
+				"Transaction size {} exceeds the max tx size {}",
+				tx_bytes.len(),
+				self.max_tx_size
+			)))
+		}
+
+		Ok(tx_bytes)
+	}
+
+	/// Wait for the transaction with the given hash to be committed, returns an error if the
+	/// transaction failed.
+	async fn wait_for_tx(&self, tx_hash: Hash) -> Result<(), Error> {
+		let now = std::time::Instant::now();
+		loop {
+			match self.rpc_client.tx(tx_hash, false).await {
+				Ok(response) if response.tx_result.code.is_err() =>
+					return Err(Error::TransactionFailed {
+						tx_hash: tx_hash.to_string(),
+						code: response.tx_result.code.value(),
+						log: response.tx_result.log.to_string(),
+					}),
+				Ok(_) => return Ok(()),
+				Err(err) => {
+					if now.elapsed() > TX_COMMIT_TIMEOUT {
+						return Err(Error::from(format!(
+							"Timeout while waiting for transaction {}: {}",
+							tx_hash, err
+						)))
+					}
+					sleep(Duration::from_millis(500)).await;
+				},
+			}
+		}
+	}
+}
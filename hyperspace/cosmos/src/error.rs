@@ -0,0 +1,87 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::{core::ics02_client, timestamp::ParseTimestampError};
+use primitives::error::ErrorKind;
+use std::num::ParseIntError;
+use thiserror::Error;
+
+/// Error definition for the cosmos client
+#[derive(Error, Debug)]
+pub enum Error {
+	/// An error from the tendermint rpc interface
+	#[error("Rpc client error: {0}")]
+	RpcError(String),
+	/// Tendermint rpc error
+	#[error("Tendermint rpc error: {0}")]
+	TendermintRpc(#[from] tendermint_rpc::Error),
+	/// gRPC status returned by the node
+	#[error("Grpc error: {0}")]
+	Grpc(#[from] tonic::Status),
+	/// gRPC transport error
+	#[error("Grpc transport error: {0}")]
+	GrpcTransport(#[from] tonic::transport::Error),
+	/// Protobuf decoding error
+	#[error("Protobuf decoding error: {0}")]
+	Decode(#[from] prost::DecodeError),
+	/// Abci query returned a non-zero code
+	#[error("Abci query failed with code {code}: {log}")]
+	AbciQuery { code: u32, log: String },
+	/// Transaction was rejected by the chain
+	#[error("Transaction {tx_hash} failed with code {code}: {log}")]
+	TransactionFailed { tx_hash: String, code: u32, log: String },
+	/// Key derivation or signing error
+	#[error("Key error: {0}")]
+	Key(String),
+	/// hex error
+	#[error("Error decoding hex: {0:?}")]
+	Hex(#[from] hex::FromHexError),
+	/// Custom error
+	#[error("{0}")]
+	Custom(String),
+	/// Failed to get client update header
+	#[error("Error constructing a client update header: {0}")]
+	HeaderConstruction(String),
+	/// Errors associated with ics-02 client
+	#[error("Ibc client error: {0}")]
+	IbcClient(#[from] ics02_client::error::Error),
+	/// Ibc channel error
+	#[error("Ibc channel error")]
+	IbcChannel(#[from] ibc::core::ics04_channel::error::Error),
+	/// parse error
+	#[error("Failed to parse block numbers: {0}")]
+	ParseIntError(#[from] ParseIntError),
+	/// Ics-20 errors
+	#[error("Ics-20 error: {0}")]
+	Ics20Error(#[from] ibc::applications::transfer::error::Error),
+	/// Error occured parsing timestamp
+	#[error("Timestamp error: {0}")]
+	ParseTimestamp(#[from] ParseTimestampError),
+}
+
+impl Error {
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Self::Key(_) => ErrorKind::Fatal,
+			Self::TransactionFailed { .. } => ErrorKind::Rejected,
+			_ => ErrorKind::Retryable,
+		}
+	}
+}
+
+impl From<String> for Error {
+	fn from(error: String) -> Self {
+		Self::Custom(error)
+	}
+}
@@ -0,0 +1,82 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversion of tendermint abci events emitted by the ibc-go modules into [`IbcEvent`]s.
+
+use std::str::FromStr;
+
+use ibc::{
+	core::{
+		ics02_client::events::{
+			Attributes as ClientAttributes, ClientMisbehaviour, CreateClient, UpdateClient,
+			UpgradeClient,
+		},
+		ics03_connection::events as connection_events,
+		ics04_channel::events as channel_events,
+		ics24_host::identifier::ClientId,
+	},
+	events::{IbcEvent, IbcEventType},
+	Height,
+};
+use tendermint::abci::Event as AbciEvent;
+
+/// Convert an abci event emitted at `height` into an [`IbcEvent`], returns `None` for events
+/// that are not ibc events.
+pub fn ibc_event_try_from_abci_event(event: &AbciEvent, height: Height) -> Option<IbcEvent> {
+	let mut ibc_event = match event.kind.parse() {
+		Ok(IbcEventType::CreateClient) =>
+			client_attributes_from_abci(event).map(CreateClient).map(IbcEvent::CreateClient),
+		Ok(IbcEventType::UpdateClient) => client_attributes_from_abci(event).map(|attrs| {
+			let mut update = UpdateClient::from(attrs);
+			update.header = event
+				.attributes
+				.iter()
+				.find(|attr| attr.key == "header")
+				.and_then(|attr| hex::decode(&attr.value).ok());
+			IbcEvent::UpdateClient(update)
+		}),
+		Ok(IbcEventType::UpgradeClient) => client_attributes_from_abci(event)
+			.map(UpgradeClient)
+			.map(IbcEvent::UpgradeClient),
+		Ok(IbcEventType::ClientMisbehaviour) => client_attributes_from_abci(event)
+			.map(ClientMisbehaviour)
+			.map(IbcEvent::ClientMisbehaviour),
+		Ok(IbcEventType::OpenInitConnection) |
+		Ok(IbcEventType::OpenTryConnection) |
+		Ok(IbcEventType::OpenAckConnection) |
+		Ok(IbcEventType::OpenConfirmConnection) => connection_events::try_from_tx(event),
+		Ok(_) => channel_events::try_from_tx(event),
+		Err(_) => None,
+	}?;
+	ibc_event.set_height(height);
+	Some(ibc_event)
+}
+
+fn client_attributes_from_abci(event: &AbciEvent) -> Option<ClientAttributes> {
+	let mut attributes = ClientAttributes::default();
+	for attr in &event.attributes {
+		match attr.key.as_str() {
+			"client_id" => attributes.client_id = ClientId::from_str(&attr.value).ok()?,
+			"client_type" => attributes.client_type = attr.value.clone(),
+			"consensus_height" => attributes.consensus_height = parse_height(&attr.value)?,
+			_ => {},
+		}
+	}
+	Some(attributes)
+}
+
+/// ibc-go emits heights formatted as `{revision_number}-{revision_height}`.
+fn parse_height(value: &str) -> Option<Height> {
+	Height::from_str(value).ok()
+}
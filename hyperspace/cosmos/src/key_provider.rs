@@ -0,0 +1,73 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+
+use bech32::{ToBase32, Variant};
+use bip32::{DerivationPath, XPrv};
+use bip39::{Language, Mnemonic, Seed};
+use ibc::signer::Signer;
+use k256::ecdsa::{signature::Signer as _, Signature};
+use primitives::KeyProvider;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use super::{error::Error, CosmosClient};
+
+/// Standard cosmos hd path, see https://github.com/satoshilabs/slips/blob/master/slip-0044.md
+pub const COSMOS_HD_PATH: &str = "m/44'/118'/0'/0/0";
+
+/// A secp256k1 key pair along with its bech32 encoded account address.
+#[derive(Clone)]
+pub struct KeyEntry {
+	/// Compressed secp256k1 public key
+	pub public_key: Vec<u8>,
+	/// Extended private key
+	pub private_key: XPrv,
+	/// Bech32 encoded account address
+	pub account: String,
+	/// Raw account address
+	pub address: Vec<u8>,
+}
+
+impl KeyEntry {
+	/// Derive the relayer key from a BIP-39 mnemonic using the standard cosmos hd path.
+	pub fn from_mnemonic(mnemonic: &str, account_prefix: &str) -> Result<Self, Error> {
+		let mnemonic = Mnemonic::from_phrase(mnemonic, Language::English)
+			.map_err(|e| Error::Key(format!("Invalid mnemonic: {:?}", e)))?;
+		let seed = Seed::new(&mnemonic, "");
+		let path = DerivationPath::from_str(COSMOS_HD_PATH)
+			.map_err(|e| Error::Key(format!("Invalid derivation path: {:?}", e)))?;
+		let private_key = XPrv::derive_from_path(seed.as_bytes(), &path)
+			.map_err(|e| Error::Key(format!("Failed to derive private key: {:?}", e)))?;
+		let public_key = private_key.public_key().to_bytes().to_vec();
+		let address = Ripemd160::digest(Sha256::digest(&public_key)).to_vec();
+		let account = bech32::encode(account_prefix, address.to_base32(), Variant::Bech32)
+			.map_err(|e| Error::Key(format!("Failed to encode address: {:?}", e)))?;
+
+		Ok(Self { public_key, private_key, account, address })
+	}
+
+	/// Sign the given message, returns the 64 byte compact signature expected by the cosmos-sdk.
+	pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+		let signature: Signature = self.private_key.private_key().sign(message);
+		signature.as_ref().to_vec()
+	}
+}
+
+impl KeyProvider for CosmosClient {
+	fn account_id(&self) -> Signer {
+		Signer::from_str(&self.keybase.account).expect("Account Id should be valid")
+	}
+}
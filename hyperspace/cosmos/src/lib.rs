@@ -11,3 +11,329 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+#![allow(clippy::all)]
+
+pub mod chain;
+pub mod error;
+pub mod events;
+pub mod key_provider;
+pub mod light_client_sync;
+pub mod provider;
+#[cfg(feature = "testing")]
+pub mod test_provider;
+
+use std::{
+	str::FromStr,
+	sync::{Arc, Mutex},
+};
+
+use error::Error;
+use ibc::{
+	applications::transfer::PrefixedCoin,
+	core::{
+		ics23_commitment::commitment::CommitmentPrefix,
+		ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+	},
+	Height,
+};
+use ibc_proto::{
+	cosmos::auth::v1beta1::{
+		query_client::QueryClient as AuthQueryClient, BaseAccount, QueryAccountRequest,
+	},
+	google::protobuf::Any,
+};
+use ics07_tendermint::client_message::Header;
+use key_provider::KeyEntry;
+use primitives::keystore;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tendermint::block::Height as TmHeight;
+use tendermint_rpc::{endpoint::abci_query::AbciQuery, Client, HttpClient, Paging, Url};
+
+/// Default gas limit used when simulation of a transaction is not possible.
+pub const DEFAULT_GAS_LIMIT: u64 = 900_000;
+/// Maximum number of bytes a transaction may contain, as enforced by the default tendermint
+/// mempool configuration.
+pub const DEFAULT_MAX_TX_SIZE: u64 = 1024 * 1024;
+
+/// Implements the [`crate::Chain`] trait for cosmos-sdk based chains.
+/// This is responsible for:
+/// 1. Tracking a tendermint light client on a counter-party chain, advancing this light
+/// client state as new blocks are committed.
+/// 2. Submitting new IBC messages to this chain as signed transactions.
+#[derive(Clone)]
+pub struct CosmosClient {
+	/// Chain name
+	pub name: String,
+	/// Chain id
+	pub chain_id: ChainId,
+	/// Tendermint rpc client
+	pub rpc_client: HttpClient,
+	/// Tendermint rpc url
+	pub rpc_url: Url,
+	/// Rpc clients of the nodes the fisherman checks the headers submitted to the counterparty
+	/// against
+	pub trusted_rpc_clients: Vec<HttpClient>,
+	/// Cosmos-sdk gRPC url
+	pub grpc_url: String,
+	/// Tendermint websocket url, used for event subscriptions
+	pub websocket_url: Url,
+	/// Light client id on counterparty chain
+	pub client_id: Option<ClientId>,
+	/// Connection Id
+	pub connection_id: Option<ConnectionId>,
+	/// ICS-23 provable store commitment prefix
+	pub commitment_prefix: CommitmentPrefix,
+	/// Name of the key store used for proof queries, usually `ibc`
+	pub store_prefix: String,
+	/// Bech32 prefix for account addresses
+	pub account_prefix: String,
+	/// Denomination used for paying transaction fees
+	pub fee_denom: String,
+	/// Amount of `fee_denom` paid per transaction
+	pub fee_amount: String,
+	/// Gas limit used for transactions
+	pub gas_limit: u64,
+	/// Maximum size of a transaction in bytes
+	pub max_tx_size: u64,
+	/// Relayer key
+	pub keybase: KeyEntry,
+	/// Channels cleared for packet relay
+	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+	/// Minimum fees that must be escrowed for a packet sent by this chain before it's relayed
+	pub min_relay_fees: Vec<PrefixedCoin>,
+	/// Height of a planned upgrade of this chain, kept until the counterparty's light client has
+	/// been upgraded since the plan is cleared once the upgrade is applied
+	pub upgrade_height: Arc<Mutex<Option<u64>>>,
+}
+
+/// config options for [`CosmosClient`]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CosmosClientConfig {
+	/// Chain name
+	pub name: String,
+	/// Tendermint rpc url
+	pub rpc_url: String,
+	/// Cosmos-sdk gRPC url
+	pub grpc_url: String,
+	/// Tendermint websocket url
+	pub websocket_url: String,
+	/// Chain id
+	pub chain_id: String,
+	/// Light client id on counterparty chain
+	pub client_id: Option<ClientId>,
+	/// Connection Id
+	pub connection_id: Option<ConnectionId>,
+	/// Bech32 prefix for account addresses, eg `cosmos`
+	pub account_prefix: String,
+	/// Denomination used for paying transaction fees
+	pub fee_denom: String,
+	/// Amount of `fee_denom` paid per transaction
+	pub fee_amount: String,
+	/// Gas limit used for transactions
+	pub gas_limit: Option<u64>,
+	/// Store prefix, usually `ibc`
+	pub store_prefix: String,
+	/// Maximum size of a transaction in bytes
+	pub max_tx_size: Option<u64>,
+	/// BIP-39 mnemonic of the relayer account, prefer `key_name` outside of development
+	#[serde(default)]
+	pub mnemonic: Option<String>,
+	/// Name of the secp256k1 key in the hyperspace keystore of the relayer account
+	#[serde(default)]
+	pub key_name: Option<String>,
+	/// Directory of the keystore `key_name` is read from, as passed to `hyperspace keys
+	/// --keystore`. Defaults to `$HYPERSPACE_KEYSTORE` or `~/.hyperspace/keys`.
+	#[serde(default)]
+	pub keystore_path: Option<String>,
+	/// Channels cleared for packet relay
+	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+	/// Minimum fees that must be escrowed for a packet sent by this chain before it's relayed,
+	/// packets are relayed regardless of their fees if empty
+	#[serde(default)]
+	pub min_relay_fees: Vec<PrefixedCoin>,
+	/// Tendermint rpc urls of trusted nodes, the fisherman checks the headers submitted to the
+	/// counterparty against them. The node at `rpc_url` is used if empty
+	#[serde(default)]
+	pub trusted_rpc_urls: Vec<String>,
+}
+
+impl CosmosClient {
+	/// Initializes a [`CosmosClient`] given a [`CosmosClientConfig`]
+	pub async fn new(config: CosmosClientConfig) -> Result<Self, Error> {
+		let rpc_url = Url::from_str(&config.rpc_url)
+			.map_err(|e| Error::from(format!("Invalid rpc url {}: {:?}", config.rpc_url, e)))?;
+		let websocket_url = Url::from_str(&config.websocket_url).map_err(|e| {
+			Error::from(format!("Invalid websocket url {}: {:?}", config.websocket_url, e))
+		})?;
+		let rpc_client =
+			HttpClient::new(rpc_url.clone()).map_err(|e| Error::RpcError(format!("{:?}", e)))?;
+		let trusted_rpc_clients = config
+			.trusted_rpc_urls
+			.iter()
+			.map(|url| {
+				let url = Url::from_str(url)
+					.map_err(|e| Error::from(format!("Invalid rpc url {}: {:?}", url, e)))?;
+				HttpClient::new(url).map_err(|e| Error::RpcError(format!("{:?}", e)))
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+		let chain_id = ChainId::from(config.chain_id);
+		let commitment_prefix = CommitmentPrefix::try_from(config.store_prefix.as_bytes().to_vec())
+			.map_err(|e| Error::from(format!("Invalid store prefix {:?}", e)))?;
+		let mnemonic = keystore::resolve_secret(
+			config.keystore_path.as_deref(),
+			config.key_name.as_deref(),
+			config.mnemonic.as_deref(),
+			keystore::KeyType::Secp256k1,
+		)
+		.map_err(|e| Error::Key(e.to_string()))?;
+		let keybase = KeyEntry::from_mnemonic(&mnemonic, &config.account_prefix)?;
+
+		Ok(Self {
+			name: config.name,
+			chain_id,
+			rpc_client,
+			rpc_url,
+			trusted_rpc_clients,
+			grpc_url: config.grpc_url,
+			websocket_url,
+			client_id: config.client_id,
+			connection_id: config.connection_id,
+			commitment_prefix,
+			store_prefix: config.store_prefix,
+			account_prefix: config.account_prefix,
+			fee_denom: config.fee_denom,
+			fee_amount: config.fee_amount,
+			gas_limit: config.gas_limit.unwrap_or(DEFAULT_GAS_LIMIT),
+			max_tx_size: config.max_tx_size.unwrap_or(DEFAULT_MAX_TX_SIZE),
+			keybase,
+			channel_whitelist: config.channel_whitelist,
+			min_relay_fees: config.min_relay_fees,
+			upgrade_height: Arc::new(Mutex::new(None)),
+		})
+	}
+
+	pub fn client_id(&self) -> ClientId {
+		self.client_id.as_ref().expect("Client Id should be defined").clone()
+	}
+
+	pub fn set_client_id(&mut self, client_id: ClientId) {
+		self.client_id = Some(client_id)
+	}
+
+	/// Revision number of this chain, derived from the chain id
+	pub fn revision_number(&self) -> u64 {
+		self.chain_id.version()
+	}
+
+	/// Bech32 encoded address of a cosmos-sdk module account, e.g. the `gov` module which is the
+	/// authority of governance-gated messages.
+	pub fn module_address(&self, module: &str) -> Result<String, Error> {
+		use bech32::{ToBase32, Variant};
+		use sha2::{Digest, Sha256};
+		let address = Sha256::digest(module.as_bytes())[..20].to_vec();
+		bech32::encode(&self.account_prefix, address.to_base32(), Variant::Bech32)
+			.map_err(|e| Error::Key(format!("Failed to encode address: {:?}", e)))
+	}
+
+	/// Convert a tendermint block height to an ibc [`Height`]
+	pub fn to_ibc_height(&self, height: TmHeight) -> Height {
+		Height::new(self.revision_number(), height.value())
+	}
+
+	/// Query the on-chain account of the relayer, this is needed for the account number and
+	/// sequence used when signing transactions.
+	pub async fn query_account(&self) -> Result<BaseAccount, Error> {
+		let mut client = AuthQueryClient::connect(self.grpc_url.clone())
+			.await
+			.map_err(|e| Error::from(format!("Failed to connect to grpc endpoint: {:?}", e)))?;
+		let request =
+			tonic::Request::new(QueryAccountRequest { address: self.keybase.account.clone() });
+		let response = client.account(request).await?.into_inner();
+		let account = response
+			.account
+			.ok_or_else(|| Error::from(format!("Account {} not found", self.keybase.account)))?;
+		let account = BaseAccount::decode(&*account.value)?;
+		Ok(account)
+	}
+
+	/// Perform an abci query at the given height, optionally with a proof.
+	pub async fn query_path(
+		&self,
+		path: Vec<u8>,
+		height: Height,
+		prove: bool,
+	) -> Result<AbciQuery, Error> {
+		self.query_store_path(&self.store_prefix, path, height, prove).await
+	}
+
+	/// Perform an abci query of a key in the given store at the given height, optionally with a
+	/// proof.
+	pub async fn query_store_path(
+		&self,
+		store: &str,
+		path: Vec<u8>,
+		height: Height,
+		prove: bool,
+	) -> Result<AbciQuery, Error> {
+		// Tendermint stores the state root for block `n` in block `n + 1`, the query height is
+		// therefore one less than the height we intend to prove against.
+		let query_height = TmHeight::try_from(height.revision_height.saturating_sub(1))
+			.map_err(|e| Error::from(format!("Invalid height {}: {:?}", height, e)))?;
+		let response = self
+			.rpc_client
+			.abci_query(Some(format!("store/{}/key", store)), path, Some(query_height), prove)
+			.await?;
+
+		if !response.code.is_ok() {
+			return Err(Error::AbciQuery {
+				code: response.code.value(),
+				log: response.log.to_string(),
+			})
+		}
+
+		Ok(response)
+	}
+
+	/// Construct a tendermint [`Header`] for the block at `target_height`, which can be verified
+	/// by a light client that trusts the block at `trusted_height`.
+	pub async fn construct_tendermint_header(
+		&self,
+		trusted_height: Height,
+		target_height: Height,
+	) -> Result<Header, Error> {
+		fetch_tendermint_header(&self.rpc_client, trusted_height, target_height).await
+	}
+}
+
+/// Construct a tendermint [`Header`] for the block at `target_height` from the given node, which
+/// can be verified by a light client that trusts the block at `trusted_height`.
+pub async fn fetch_tendermint_header(
+	rpc_client: &HttpClient,
+	trusted_height: Height,
+	target_height: Height,
+) -> Result<Header, Error> {
+	let target = TmHeight::try_from(target_height.revision_height)
+		.map_err(|e| Error::from(format!("Invalid height {}: {:?}", target_height, e)))?;
+	// The validator set that signed the trusted header's successor is the set the light
+	// client will use to verify the new header.
+	let trusted = TmHeight::try_from(trusted_height.revision_height + 1)
+		.map_err(|e| Error::from(format!("Invalid height {}: {:?}", trusted_height, e)))?;
+
+	let signed_header = rpc_client.commit(target).await?.signed_header;
+	let validators = rpc_client.validators(target, Paging::All).await?.validators;
+	let trusted_validators = rpc_client.validators(trusted, Paging::All).await?.validators;
+
+	let proposer = signed_header.header.proposer_address;
+	let validator_set = tendermint::validator::Set::with_proposer(validators, proposer)
+		.map_err(|e| Error::HeaderConstruction(format!("Invalid validator set: {:?}", e)))?;
+	let trusted_validator_set = tendermint::validator::Set::without_proposer(trusted_validators);
+
+	Ok(Header { signed_header, validator_set, trusted_height, trusted_validator_set })
+}
+
+/// Encode a message as a protobuf [`Any`].
+pub(crate) fn to_any<M: Message>(type_url: &str, msg: &M) -> Any {
+	Any { type_url: type_url.to_string(), value: msg.encode_to_vec() }
+}
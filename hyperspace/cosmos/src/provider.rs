@@ -0,0 +1,1130 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{error::Error, events::ibc_event_try_from_abci_event, CosmosClient};
+use futures::{Stream, StreamExt};
+use ibc::{
+	applications::{
+		fee::fee::PacketFee,
+		transfer::{Amount, PrefixedCoin, PrefixedDenom},
+	},
+	core::{
+		ics02_client::{
+			client_state::{ClientState as _, ClientType},
+			msgs::{update_client::MsgUpdateAnyClient, upgrade_client::MsgUpgradeAnyClient},
+			trust_threshold::TrustThreshold,
+		},
+		ics04_channel::packet::Sequence,
+		ics23_commitment::{commitment::CommitmentPrefix, specs::ProofSpecs},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			path::{
+				AcksPath, ChannelEndsPath, ChannelUpgradeErrorsPath, ChannelUpgradesPath,
+				ClientConsensusStatePath, ClientStatePath, CommitmentsPath, ConnectionsPath,
+				ReceiptsPath, SeqRecvsPath,
+			},
+		},
+	},
+	events::IbcEvent,
+	timestamp::Timestamp,
+	tx_msg::Msg,
+	Height,
+};
+use ibc_proto::{
+	cosmos::{
+		bank::v1beta1::{query_client::QueryClient as BankQueryClient, QueryAllBalancesRequest},
+		base::query::v1beta1::{PageRequest, PageResponse},
+		staking::v1beta1::{query_client::QueryClient as StakingQueryClient, QueryParamsRequest},
+		upgrade::v1beta1::{
+			query_client::QueryClient as UpgradeQueryClient, QueryCurrentPlanRequest,
+		},
+	},
+	google::protobuf::Any,
+	ibc::{
+		applications::fee::v1::{
+			query_client::QueryClient as FeeQueryClient, QueryIncentivizedPacketRequest,
+		},
+		core::{
+			channel::v1::{
+				query_client::QueryClient as ChannelQueryClient, Channel, ErrorReceipt, PacketId,
+				QueryChannelResponse, QueryChannelsRequest, QueryChannelsResponse,
+				QueryConnectionChannelsRequest, QueryNextSequenceReceiveResponse,
+				QueryPacketAcknowledgementResponse, QueryPacketAcknowledgementsRequest,
+				QueryPacketCommitmentResponse, QueryPacketCommitmentsRequest,
+				QueryPacketReceiptResponse, QueryUnreceivedAcksRequest,
+				QueryUnreceivedPacketsRequest, QueryUpgradeErrorResponse, QueryUpgradeResponse,
+				Upgrade,
+			},
+			client::v1::{
+				query_client::QueryClient as ClientQueryClient, QueryClientStateResponse,
+				QueryClientStatesRequest, QueryConsensusStateResponse,
+			},
+			commitment::v1::MerkleProof as RawMerkleProof,
+			connection::v1::{
+				query_client::QueryClient as ConnectionQueryClient, ConnectionEnd,
+				IdentifiedConnection, QueryClientConnectionsRequest, QueryConnectionResponse,
+			},
+		},
+	},
+};
+use ibc_rpc::PacketInfo;
+use ics07_tendermint::{
+	client_def::{UPGRADED_CLIENT_KEY, UPGRADED_CONSENSUS_STATE_KEY},
+	client_message::ClientMessage,
+	client_state::ClientState as TendermintClientState,
+	consensus_state::ConsensusState as TendermintConsensusState,
+};
+use pallet_ibc::light_clients::{
+	AnyClientMessage, AnyClientState, AnyConsensusState, HostFunctionsManager,
+};
+use primitives::{mock::LocalClientTypes, Chain, IbcProvider, KeyProvider, UpdateType};
+use prost::Message;
+use std::{future::Future, pin::Pin, str::FromStr, time::Duration};
+use tendermint::{block::Height as TmHeight, Hash};
+use tendermint_proto::Protobuf;
+use tendermint_rpc::{
+	event::EventData,
+	query::{EventType, Query},
+	Client, Order, SubscriptionClient, Url, WebSocketClient,
+};
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Number of transactions fetched per page when searching for transactions.
+const TX_SEARCH_PAGE_SIZE: u8 = 100;
+/// Number of items fetched per page when querying paginated grpc endpoints.
+const GRPC_PAGE_LIMIT: u64 = 100;
+/// Name of the store the `upgrade` module commits upgraded ibc states to.
+const UPGRADE_STORE_KEY: &str = "upgrade";
+/// Key prefix of the upgraded ibc states in the `upgrade` store.
+const UPGRADED_IBC_STATE_KEY: &str = "upgradedIBCState";
+
+/// Finality notifications for tendermint chains, blocks are final as soon as they're committed.
+#[derive(Clone, Debug)]
+pub enum FinalityEvent {
+	Tendermint(tendermint::block::Header),
+}
+
+#[async_trait::async_trait]
+impl IbcProvider for CosmosClient {
+	type FinalityEvent = FinalityEvent;
+	type TransactionId = Hash;
+	type Error = Error;
+
+	async fn query_latest_ibc_events<C>(
+		&mut self,
+		finality_event: Self::FinalityEvent,
+		counterparty: &C,
+	) -> Result<(Vec<Any>, Vec<IbcEvent>, UpdateType), anyhow::Error>
+	where
+		C: Chain,
+	{
+		let FinalityEvent::Tendermint(header) = finality_event;
+		let client_id = self.client_id();
+		let latest_height = counterparty.latest_height_and_timestamp().await?.0;
+		let response = counterparty.query_client_state(latest_height, client_id).await?;
+		let client_state = response.client_state.ok_or_else(|| {
+			Error::Custom("Received an empty client state from counterparty".to_string())
+		})?;
+
+		let client_state = AnyClientState::try_from(client_state)
+			.map_err(|_| Error::Custom("Failed to decode client state".to_string()))?;
+		let client_state = match client_state {
+			AnyClientState::Tendermint(client_state) => client_state,
+			c => Err(Error::Custom(format!("Expected AnyClientState::Tendermint found: {:?}", c)))?,
+		};
+
+		let trusted_height = client_state.latest_height();
+		let target_height = self.to_ibc_height(header.height);
+		if target_height <= trusted_height {
+			return Ok((vec![], vec![], UpdateType::Optional))
+		}
+
+		log::info!(
+			"Fetching events from {} for blocks {}..{}",
+			self.name,
+			trusted_height.revision_height + 1,
+			target_height.revision_height,
+		);
+
+		let query = Query::gte("tx.height", trusted_height.revision_height + 1)
+			.and_lte("tx.height", target_height.revision_height);
+		let events = self.search_ibc_events(query).await?;
+
+		let tendermint_header =
+			self.construct_tendermint_header(trusted_height, target_height).await?;
+
+		// The light client can't skip over blocks where the validator set changes by more than the
+		// trust threshold, so a change in the validator set makes this a mandatory update.
+		let update_type = match header.validators_hash != header.next_validators_hash {
+			true => UpdateType::Mandatory,
+			false => UpdateType::Optional,
+		};
+
+		let update_header = {
+			let msg = MsgUpdateAnyClient::<LocalClientTypes> {
+				client_id: self.client_id(),
+				client_message: AnyClientMessage::Tendermint(ClientMessage::Header(
+					tendermint_header,
+				)),
+				signer: counterparty.account_id(),
+			};
+			let value = msg.encode_vec()?;
+			Any { value, type_url: msg.type_url() }
+		};
+
+		Ok((vec![update_header], events, update_type))
+	}
+
+	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
+		let (tx, rx) = tokio::sync::mpsc::channel(32);
+		let websocket_url = self.websocket_url.clone();
+		let revision_number = self.revision_number();
+		tokio::spawn(async move {
+			let mut delay = IBC_EVENTS_MIN_RECONNECT_DELAY;
+			loop {
+				// `subscribe_ibc_events` only returns on connection errors or once the
+				// subscription has ended, either way we reconnect unless the receiver is gone.
+				match subscribe_ibc_events(&websocket_url, revision_number, &tx).await {
+					Ok(true) => return,
+					Ok(false) => delay = IBC_EVENTS_MIN_RECONNECT_DELAY,
+					Err(err) => log::error!(
+						"Ibc event subscription to {} failed, reconnecting in {:?}: {}",
+						websocket_url,
+						delay,
+						err
+					),
+				}
+				if tx.is_closed() {
+					return
+				}
+				tokio::time::sleep(delay).await;
+				delay = (delay * 2).min(IBC_EVENTS_MAX_RECONNECT_DELAY);
+			}
+		});
+
+		Box::pin(ReceiverStream::new(rx))
+	}
+
+	async fn query_client_consensus(
+		&self,
+		at: Height,
+		client_id: ClientId,
+		consensus_height: Height,
+	) -> Result<QueryConsensusStateResponse, Self::Error> {
+		let path = ClientConsensusStatePath {
+			client_id,
+			epoch: consensus_height.revision_number,
+			height: consensus_height.revision_height,
+		};
+		let (value, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		let consensus_state = if value.is_empty() { None } else { Some(Any::decode(&*value)?) };
+		Ok(QueryConsensusStateResponse { consensus_state, proof, proof_height: Some(at.into()) })
+	}
+
+	async fn query_client_state(
+		&self,
+		at: Height,
+		client_id: ClientId,
+	) -> Result<QueryClientStateResponse, Self::Error> {
+		let path = ClientStatePath(client_id);
+		let (value, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		let client_state = if value.is_empty() { None } else { Some(Any::decode(&*value)?) };
+		Ok(QueryClientStateResponse { client_state, proof, proof_height: Some(at.into()) })
+	}
+
+	async fn query_connection_end(
+		&self,
+		at: Height,
+		connection_id: ConnectionId,
+	) -> Result<QueryConnectionResponse, Self::Error> {
+		let path = ConnectionsPath(connection_id);
+		let (value, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		let connection =
+			if value.is_empty() { None } else { Some(ConnectionEnd::decode(&*value)?) };
+		Ok(QueryConnectionResponse { connection, proof, proof_height: Some(at.into()) })
+	}
+
+	async fn query_channel_end(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryChannelResponse, Self::Error> {
+		let path = ChannelEndsPath(port_id, channel_id);
+		let (value, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		let channel = if value.is_empty() { None } else { Some(Channel::decode(&*value)?) };
+		Ok(QueryChannelResponse { channel, proof, proof_height: Some(at.into()) })
+	}
+
+	async fn query_channel_upgrade(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeResponse, Self::Error> {
+		let path = ChannelUpgradesPath(port_id, channel_id);
+		let (value, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		let upgrade = if value.is_empty() { None } else { Some(Upgrade::decode(&*value)?) };
+		Ok(QueryUpgradeResponse { upgrade, proof, proof_height: Some(at.into()) })
+	}
+
+	async fn query_channel_upgrade_error(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeErrorResponse, Self::Error> {
+		let path = ChannelUpgradeErrorsPath(port_id, channel_id);
+		let (value, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		let error_receipt =
+			if value.is_empty() { None } else { Some(ErrorReceipt::decode(&*value)?) };
+		Ok(QueryUpgradeErrorResponse { error_receipt, proof, proof_height: Some(at.into()) })
+	}
+
+	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+		let key = keys
+			.into_iter()
+			.next()
+			.ok_or_else(|| Error::Custom("Expected at least one key".to_string()))?;
+		let response = self.query_path(key, at, true).await?;
+		proof_ops_to_merkle_proof(response.proof)
+	}
+
+	async fn query_packet_commitment(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketCommitmentResponse, Self::Error> {
+		let path = CommitmentsPath {
+			port_id: port_id.clone(),
+			channel_id: *channel_id,
+			sequence: Sequence::from(seq),
+		};
+		let (commitment, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		Ok(QueryPacketCommitmentResponse { commitment, proof, proof_height: Some(at.into()) })
+	}
+
+	async fn query_packet_acknowledgement(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketAcknowledgementResponse, Self::Error> {
+		let path = AcksPath {
+			port_id: port_id.clone(),
+			channel_id: *channel_id,
+			sequence: Sequence::from(seq),
+		};
+		let (acknowledgement, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		Ok(QueryPacketAcknowledgementResponse {
+			acknowledgement,
+			proof,
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_next_sequence_recv(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+	) -> Result<QueryNextSequenceReceiveResponse, Self::Error> {
+		let path = SeqRecvsPath(port_id.clone(), *channel_id);
+		let (value, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		// ibc-go stores sequences as big endian encoded u64s
+		let next_sequence_receive =
+			<[u8; 8]>::try_from(value.as_slice())
+				.map(u64::from_be_bytes)
+				.map_err(|_| Error::Custom(format!("Invalid next sequence recv: {:?}", value)))?;
+		Ok(QueryNextSequenceReceiveResponse {
+			next_sequence_receive,
+			proof,
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn query_packet_receipt(
+		&self,
+		at: Height,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		seq: u64,
+	) -> Result<QueryPacketReceiptResponse, Self::Error> {
+		let path = ReceiptsPath {
+			port_id: port_id.clone(),
+			channel_id: *channel_id,
+			sequence: Sequence::from(seq),
+		};
+		let (value, proof) = self.query_value_with_proof(path.to_string(), at).await?;
+		Ok(QueryPacketReceiptResponse {
+			received: !value.is_empty(),
+			proof,
+			proof_height: Some(at.into()),
+		})
+	}
+
+	async fn latest_height_and_timestamp(&self) -> Result<(Height, Timestamp), Self::Error> {
+		let status = self.rpc_client.status().await?;
+		let height = self.to_ibc_height(status.sync_info.latest_block_height);
+		let timestamp = Timestamp::from_nanoseconds(
+			status.sync_info.latest_block_time.unix_timestamp_nanos() as u64,
+		)?;
+		Ok((height, timestamp))
+	}
+
+	async fn query_packet_commitments(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		let client = ChannelQueryClient::connect(self.grpc_url.clone()).await?;
+		let commitments = query_all_pages(|pagination| {
+			let mut client = client.clone();
+			let request = with_height(
+				QueryPacketCommitmentsRequest {
+					port_id: port_id.to_string(),
+					channel_id: channel_id.to_string(),
+					pagination: Some(pagination),
+				},
+				at,
+			);
+			async move {
+				let response = client.packet_commitments(request).await?.into_inner();
+				Ok((response.commitments, response.pagination))
+			}
+		})
+		.await?;
+		Ok(commitments.into_iter().map(|packet_state| packet_state.sequence).collect())
+	}
+
+	async fn query_packet_acknowledgements(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		let client = ChannelQueryClient::connect(self.grpc_url.clone()).await?;
+		let acknowledgements = query_all_pages(|pagination| {
+			let mut client = client.clone();
+			let request = with_height(
+				QueryPacketAcknowledgementsRequest {
+					port_id: port_id.to_string(),
+					channel_id: channel_id.to_string(),
+					pagination: Some(pagination),
+					packet_commitment_sequences: vec![],
+				},
+				at,
+			);
+			async move {
+				let response = client.packet_acknowledgements(request).await?.into_inner();
+				Ok((response.acknowledgements, response.pagination))
+			}
+		})
+		.await?;
+		Ok(acknowledgements.into_iter().map(|packet_state| packet_state.sequence).collect())
+	}
+
+	async fn query_unreceived_packets(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		let mut client = ChannelQueryClient::connect(self.grpc_url.clone()).await?;
+		let request = with_height(
+			QueryUnreceivedPacketsRequest {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				packet_commitment_sequences: seqs,
+			},
+			at,
+		);
+		let response = client.unreceived_packets(request).await?.into_inner();
+		Ok(response.sequences)
+	}
+
+	async fn query_unreceived_acknowledgements(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		let mut client = ChannelQueryClient::connect(self.grpc_url.clone()).await?;
+		let request = with_height(
+			QueryUnreceivedAcksRequest {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				packet_ack_sequences: seqs,
+			},
+			at,
+		);
+		let response = client.unreceived_acks(request).await?.into_inner();
+		Ok(response.sequences)
+	}
+
+	fn channel_whitelist(&self) -> Vec<(ChannelId, PortId)> {
+		self.channel_whitelist.clone()
+	}
+
+	fn set_channel_whitelist(&mut self, channel_whitelist: Vec<(ChannelId, PortId)>) {
+		self.channel_whitelist = channel_whitelist;
+	}
+
+	async fn query_packet_fees(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error> {
+		let mut client = FeeQueryClient::connect(self.grpc_url.clone()).await?;
+		let request = tonic::Request::new(QueryIncentivizedPacketRequest {
+			packet_id: Some(PacketId {
+				port_id: port_id.to_string(),
+				channel_id: channel_id.to_string(),
+				sequence,
+			}),
+			query_height: 0,
+		});
+		let response = match client.incentivized_packet(request).await {
+			Ok(response) => response.into_inner(),
+			// No fees were escrowed for the packet
+			Err(status) if status.code() == tonic::Code::NotFound => return Ok(vec![]),
+			Err(status) => return Err(status.into()),
+		};
+		response
+			.incentivized_packet
+			.map(|packet| packet.packet_fees)
+			.unwrap_or_default()
+			.into_iter()
+			.map(|fee| {
+				PacketFee::try_from(fee)
+					.map_err(|e| Error::from(format!("Invalid packet fee: {:?}", e)))
+			})
+			.collect()
+	}
+
+	fn min_relay_fees(&self) -> Vec<PrefixedCoin> {
+		self.min_relay_fees.clone()
+	}
+
+	async fn query_connection_channels(
+		&self,
+		at: Height,
+		connection_id: &ConnectionId,
+	) -> Result<QueryChannelsResponse, Self::Error> {
+		let client = ChannelQueryClient::connect(self.grpc_url.clone()).await?;
+		let channels = query_all_pages(|pagination| {
+			let mut client = client.clone();
+			let request = with_height(
+				QueryConnectionChannelsRequest {
+					connection: connection_id.to_string(),
+					pagination: Some(pagination),
+				},
+				at,
+			);
+			async move {
+				let response = client.connection_channels(request).await?.into_inner();
+				Ok((response.channels, response.pagination))
+			}
+		})
+		.await?;
+		Ok(QueryChannelsResponse { channels, pagination: None, height: Some(at.into()) })
+	}
+
+	async fn query_send_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		let mut packets = vec![];
+		for seq in seqs {
+			let query = Query::eq("send_packet.packet_src_channel", channel_id.to_string())
+				.and_eq("send_packet.packet_src_port", port_id.to_string())
+				.and_eq("send_packet.packet_sequence", seq.to_string());
+			let packet = self.search_ibc_events(query).await?.into_iter().find_map(|ev| match ev {
+				IbcEvent::SendPacket(ref send_packet)
+					if u64::from(send_packet.packet.sequence) == seq =>
+					Some(self.to_packet_info(ev)),
+				_ => None,
+			});
+			if let Some(packet) = packet {
+				packets.push(packet.await?);
+			}
+		}
+		Ok(packets)
+	}
+
+	async fn query_recv_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		let mut packets = vec![];
+		for seq in seqs {
+			let query =
+				Query::eq("write_acknowledgement.packet_dst_channel", channel_id.to_string())
+					.and_eq("write_acknowledgement.packet_dst_port", port_id.to_string())
+					.and_eq("write_acknowledgement.packet_sequence", seq.to_string());
+			let packet = self.search_ibc_events(query).await?.into_iter().find_map(|ev| match ev {
+				IbcEvent::WriteAcknowledgement(ref write_ack)
+					if u64::from(write_ack.packet.sequence) == seq =>
+					Some(self.to_packet_info(ev)),
+				_ => None,
+			});
+			if let Some(packet) = packet {
+				packets.push(packet.await?);
+			}
+		}
+		Ok(packets)
+	}
+
+	fn expected_block_time(&self) -> Duration {
+		// Cosmos chains have an expected block time of 6 seconds
+		Duration::from_secs(6)
+	}
+
+	async fn query_client_update_time_and_height(
+		&self,
+		client_id: ClientId,
+		client_height: Height,
+	) -> Result<(Height, Timestamp), Self::Error> {
+		let query = Query::eq("update_client.client_id", client_id.to_string())
+			.and_eq("update_client.consensus_height", client_height.to_string());
+		let response = self
+			.rpc_client
+			.tx_search(query, false, 1, 1, Order::Ascending)
+			.await?
+			.txs
+			.into_iter()
+			.next()
+			.ok_or_else(|| {
+				Error::Custom(format!(
+					"No update found for client {} at height {}",
+					client_id, client_height
+				))
+			})?;
+		let header = self.rpc_client.commit(response.height).await?.signed_header.header;
+		Ok((
+			self.to_ibc_height(response.height),
+			Timestamp::from_nanoseconds(header.time.unix_timestamp_nanos() as u64)?,
+		))
+	}
+
+	async fn query_host_consensus_state_proof(
+		&self,
+		_height: Height,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		// Tendermint hosts can introspect their own consensus states, no proof is needed.
+		Ok(None)
+	}
+
+	async fn query_ibc_balance(&self) -> Result<Vec<PrefixedCoin>, Self::Error> {
+		let client = BankQueryClient::connect(self.grpc_url.clone()).await?;
+		let balances = query_all_pages(|pagination| {
+			let mut client = client.clone();
+			let request = tonic::Request::new(QueryAllBalancesRequest {
+				address: self.keybase.account.clone(),
+				pagination: Some(pagination),
+			});
+			async move {
+				let response = client.all_balances(request).await?.into_inner();
+				Ok((response.balances, response.pagination))
+			}
+		})
+		.await?;
+		balances
+			.into_iter()
+			.map(|coin| {
+				Ok(PrefixedCoin {
+					denom: PrefixedDenom::from_str(&coin.denom)?,
+					amount: Amount::from_str(&coin.amount)?,
+				})
+			})
+			.collect()
+	}
+
+	fn connection_prefix(&self) -> CommitmentPrefix {
+		self.commitment_prefix.clone()
+	}
+
+	fn client_id(&self) -> ClientId {
+		self.client_id()
+	}
+
+	fn connection_id(&self) -> ConnectionId {
+		self.connection_id.as_ref().expect("Connection id should be defined").clone()
+	}
+
+	fn client_type(&self) -> ClientType {
+		TendermintClientState::<HostFunctionsManager>::client_type()
+	}
+
+	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
+		let height = TmHeight::try_from(block_number)
+			.map_err(|e| Error::from(format!("Invalid height {}: {:?}", block_number, e)))?;
+		let header = self.rpc_client.commit(height).await?.signed_header.header;
+		Ok(header.time.unix_timestamp_nanos() as u64)
+	}
+
+	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
+		let client = ClientQueryClient::connect(self.grpc_url.clone()).await?;
+		let client_states = query_all_pages(|pagination| {
+			let mut client = client.clone();
+			let request =
+				tonic::Request::new(QueryClientStatesRequest { pagination: Some(pagination) });
+			async move {
+				let response = client.client_states(request).await?.into_inner();
+				Ok((response.client_states, response.pagination))
+			}
+		})
+		.await?;
+		client_states
+			.into_iter()
+			.map(|client| {
+				ClientId::from_str(&client.client_id)
+					.map_err(|_| Error::Custom("Invalid client id ".to_string()))
+			})
+			.collect()
+	}
+
+	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
+		let client = ChannelQueryClient::connect(self.grpc_url.clone()).await?;
+		let channels = query_all_pages(|pagination| {
+			let mut client = client.clone();
+			let request =
+				tonic::Request::new(QueryChannelsRequest { pagination: Some(pagination) });
+			async move {
+				let response = client.channels(request).await?.into_inner();
+				Ok((response.channels, response.pagination))
+			}
+		})
+		.await?;
+		channels
+			.into_iter()
+			.map(|identified_chan| {
+				Ok((
+					ChannelId::from_str(&identified_chan.channel_id).map_err(|_| {
+						Error::Custom(format!("Invalid channel id: {}", identified_chan.channel_id))
+					})?,
+					PortId::from_str(&identified_chan.port_id).map_err(|_| {
+						Error::Custom(format!("Invalid port id: {}", identified_chan.port_id))
+					})?,
+				))
+			})
+			.collect::<Result<Vec<_>, _>>()
+	}
+
+	async fn query_connection_using_client(
+		&self,
+		height: u32,
+		client_id: String,
+	) -> Result<Vec<IdentifiedConnection>, Self::Error> {
+		let at = Height::new(self.revision_number(), height as u64);
+		let mut client = ConnectionQueryClient::connect(self.grpc_url.clone()).await?;
+		let request = with_height(QueryClientConnectionsRequest { client_id }, at);
+		let response = client.client_connections(request).await?.into_inner();
+
+		let mut connections = vec![];
+		for connection_id in response.connection_paths {
+			let connection_id = ConnectionId::from_str(&connection_id)
+				.map_err(|_| Error::Custom("Invalid connection id".to_string()))?;
+			let connection = self
+				.query_connection_end(at, connection_id.clone())
+				.await?
+				.connection
+				.ok_or_else(|| Error::Custom(format!("Connection {} not found", connection_id)))?;
+			connections.push(IdentifiedConnection {
+				id: connection_id.to_string(),
+				client_id: connection.client_id,
+				versions: connection.versions,
+				state: connection.state,
+				counterparty: connection.counterparty,
+				delay_period: connection.delay_period,
+			});
+		}
+
+		Ok(connections)
+	}
+
+	async fn is_update_required(
+		&self,
+		_latest_height: u64,
+		_latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error> {
+		// Tendermint light clients can skip over arbitrary blocks as long as the trusting period
+		// hasn't elapsed, no checkpoints are required.
+		Ok(false)
+	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let status = self.rpc_client.status().await?;
+		let latest_height = status.sync_info.latest_block_height;
+		let header = self.rpc_client.commit(latest_height).await?.signed_header.header;
+
+		let mut staking_client = StakingQueryClient::connect(self.grpc_url.clone()).await?;
+		let params = staking_client
+			.params(tonic::Request::new(QueryParamsRequest {}))
+			.await?
+			.into_inner()
+			.params
+			.ok_or_else(|| Error::Custom("Staking params not found".to_string()))?;
+		let unbonding_period = params
+			.unbonding_time
+			.map(|d| Duration::new(d.seconds as u64, d.nanos as u32))
+			.ok_or_else(|| Error::Custom("Unbonding period not found".to_string()))?;
+		// trusting period is 2/3 of the unbonding period, see the ibc-go defaults.
+		let trusting_period = unbonding_period * 2 / 3;
+
+		let client_state = TendermintClientState::<HostFunctionsManager>::new(
+			self.chain_id.clone(),
+			TrustThreshold::ONE_THIRD,
+			trusting_period,
+			unbonding_period,
+			Duration::from_secs(10) + self.expected_block_time(),
+			self.to_ibc_height(latest_height),
+			ProofSpecs::cosmos(),
+			vec![UPGRADE_STORE_KEY.to_string(), UPGRADED_IBC_STATE_KEY.to_string()],
+		)
+		.map_err(|e| Error::Custom(format!("Failed to construct client state: {:?}", e)))?;
+		let consensus_state = TendermintConsensusState::from(header);
+
+		Ok((
+			AnyClientState::Tendermint(client_state),
+			AnyConsensusState::Tendermint(consensus_state),
+		))
+	}
+
+	async fn query_client_upgrade_messages<C>(
+		&self,
+		counterparty: &C,
+	) -> Result<Vec<Any>, anyhow::Error>
+	where
+		C: Chain,
+	{
+		let mut upgrade_client = UpgradeQueryClient::connect(self.grpc_url.clone()).await?;
+		let plan = upgrade_client
+			.current_plan(tonic::Request::new(QueryCurrentPlanRequest {}))
+			.await?
+			.into_inner()
+			.plan;
+		let upgrade_height = {
+			let mut upgrade_height = self.upgrade_height.lock().expect("poisoned lock");
+			if let Some(plan) = plan {
+				if *upgrade_height != Some(plan.height as u64) {
+					log::info!(
+						"Upgrade {} of {} planned at height {}",
+						plan.name,
+						self.name,
+						plan.height
+					);
+					*upgrade_height = Some(plan.height as u64);
+				}
+			}
+			match *upgrade_height {
+				Some(height) => height,
+				None => return Ok(vec![]),
+			}
+		};
+
+		// The chain halts at the upgrade height, the upgraded states can only be proven once the
+		// upgraded chain has committed the block at that height.
+		let latest_height = self.latest_height_and_timestamp().await?.0;
+		if latest_height.revision_height < upgrade_height {
+			return Ok(vec![])
+		}
+
+		let client_id = self.client_id();
+		let counterparty_height = counterparty.latest_height_and_timestamp().await?.0;
+		let response =
+			counterparty.query_client_state(counterparty_height, client_id.clone()).await?;
+		let client_state = response.client_state.ok_or_else(|| {
+			Error::Custom("Received an empty client state from counterparty".to_string())
+		})?;
+		let client_state = AnyClientState::try_from(client_state)
+			.map_err(|_| Error::Custom("Failed to decode client state".to_string()))?;
+		let trusted_height = client_state.latest_height();
+		let upgrade_height = Height::new(trusted_height.revision_number, upgrade_height);
+
+		let (upgraded_client_state, proof_upgrade_client) =
+			self.query_upgraded_state(upgrade_height, UPGRADED_CLIENT_KEY).await?;
+		let upgraded_client_state = AnyClientState::try_from(upgraded_client_state)
+			.map_err(|_| Error::Custom("Failed to decode upgraded client state".to_string()))?;
+		if trusted_height >= upgraded_client_state.latest_height() {
+			log::info!("Light client of {} was upgraded at height {}", self.name, upgrade_height);
+			*self.upgrade_height.lock().expect("poisoned lock") = None;
+			return Ok(vec![])
+		}
+		let (upgraded_consensus_state, proof_upgrade_consensus_state) =
+			self.query_upgraded_state(upgrade_height, UPGRADED_CONSENSUS_STATE_KEY).await?;
+		let upgraded_consensus_state = AnyConsensusState::try_from(upgraded_consensus_state)
+			.map_err(|_| Error::Custom("Failed to decode upgraded consensus state".to_string()))?;
+
+		let mut messages = vec![];
+		// The upgraded states are verified against the consensus state at the upgrade height.
+		if trusted_height < upgrade_height {
+			let header = self.construct_tendermint_header(trusted_height, upgrade_height).await?;
+			let msg = MsgUpdateAnyClient::<LocalClientTypes> {
+				client_id: client_id.clone(),
+				client_message: AnyClientMessage::Tendermint(ClientMessage::Header(header)),
+				signer: counterparty.account_id(),
+			};
+			let value = msg.encode_vec()?;
+			messages.push(Any { value, type_url: msg.type_url() });
+		}
+		let msg = MsgUpgradeAnyClient::<LocalClientTypes> {
+			client_id,
+			client_state: upgraded_client_state,
+			consensus_state: upgraded_consensus_state,
+			proof_upgrade_client,
+			proof_upgrade_consensus_state,
+			signer: counterparty.account_id(),
+		};
+		let value = msg.encode_vec()?;
+		messages.push(Any { value, type_url: msg.type_url() });
+
+		Ok(messages)
+	}
+
+	async fn query_client_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ClientId, Self::Error> {
+		let response = self.rpc_client.tx(tx_id, false).await?;
+		let height = self.to_ibc_height(response.height);
+		response
+			.tx_result
+			.events
+			.iter()
+			.find_map(|event| match ibc_event_try_from_abci_event(event, height) {
+				Some(IbcEvent::CreateClient(create_client)) =>
+					Some(create_client.client_id().clone()),
+				_ => None,
+			})
+			.ok_or_else(|| {
+				Error::Custom(format!("No CreateClient event found in transaction {}", tx_id))
+			})
+	}
+}
+
+impl CosmosClient {
+	/// Query the value stored at the given ics-24 path along with its encoded [`RawMerkleProof`].
+	async fn query_value_with_proof(
+		&self,
+		path: String,
+		at: Height,
+	) -> Result<(Vec<u8>, Vec<u8>), Error> {
+		let response = self.query_path(path.into_bytes(), at, true).await?;
+		let proof = proof_ops_to_merkle_proof(response.proof)?;
+		Ok((response.value, proof))
+	}
+
+	/// Query an upgraded state committed to by the `upgrade` store for the given upgrade height
+	/// along with its encoded [`RawMerkleProof`].
+	async fn query_upgraded_state(
+		&self,
+		upgrade_height: Height,
+		key: &str,
+	) -> Result<(Any, Vec<u8>), Error> {
+		let path = format!("{}/{}/{}", UPGRADED_IBC_STATE_KEY, upgrade_height.revision_height, key);
+		// the upgraded states are written in the block before the upgrade height and proven
+		// against the root committed to by the header at the upgrade height.
+		let response = self
+			.query_store_path(UPGRADE_STORE_KEY, path.into_bytes(), upgrade_height, true)
+			.await?;
+		if response.value.is_empty() {
+			return Err(Error::Custom(format!(
+				"No upgraded state found at {}/{}",
+				upgrade_height, key
+			)))
+		}
+		let proof = proof_ops_to_merkle_proof(response.proof)?;
+		Ok((Any::decode(&*response.value)?, proof))
+	}
+
+	/// Fetch all ibc events emitted by transactions matching the given query.
+	pub async fn search_ibc_events(&self, query: Query) -> Result<Vec<IbcEvent>, Error> {
+		let mut events = vec![];
+		let mut page = 1;
+		loop {
+			let response = self
+				.rpc_client
+				.tx_search(query.clone(), false, page, TX_SEARCH_PAGE_SIZE, Order::Ascending)
+				.await?;
+			let fetched = response.txs.len() as u32;
+			for tx in response.txs {
+				let height = self.to_ibc_height(tx.height);
+				events.extend(
+					tx.tx_result
+						.events
+						.iter()
+						.filter_map(|event| ibc_event_try_from_abci_event(event, height)),
+				);
+			}
+			if fetched < TX_SEARCH_PAGE_SIZE as u32 ||
+				(page * TX_SEARCH_PAGE_SIZE as u32) >= response.total_count
+			{
+				break
+			}
+			page += 1;
+		}
+		Ok(events)
+	}
+
+	/// Converts a `SendPacket` or `WriteAcknowledgement` event to a [`PacketInfo`]. The channel
+	/// order is read from this chain's end of the channel, which is the source end for sent
+	/// packets and the destination end for received ones.
+	async fn to_packet_info(&self, event: IbcEvent) -> Result<PacketInfo, Error> {
+		let (height, packet, ack, channel_id, port_id) = match event {
+			IbcEvent::SendPacket(send_packet) => {
+				let packet = send_packet.packet;
+				let (channel_id, port_id) = (packet.source_channel, packet.source_port.clone());
+				(send_packet.height, packet, None, channel_id, port_id)
+			},
+			IbcEvent::WriteAcknowledgement(write_ack) => {
+				let packet = write_ack.packet;
+				let (channel_id, port_id) =
+					(packet.destination_channel, packet.destination_port.clone());
+				(write_ack.height, packet, Some(write_ack.ack), channel_id, port_id)
+			},
+			event =>
+				return Err(Error::Custom(format!(
+					"Unexpected packet event: {}",
+					event.event_type().as_str()
+				))),
+		};
+		let channel = self.query_channel_end(height, channel_id, port_id).await?.channel;
+		let channel_order = channel
+			.map(|channel| channel.ordering().as_str_name().to_string())
+			.unwrap_or_default();
+		Ok(PacketInfo {
+			height: height.revision_height,
+			sequence: packet.sequence.into(),
+			source_port: packet.source_port.to_string(),
+			source_channel: packet.source_channel.to_string(),
+			destination_port: packet.destination_port.to_string(),
+			destination_channel: packet.destination_channel.to_string(),
+			channel_order,
+			data: packet.data,
+			timeout_height: packet.timeout_height.into(),
+			timeout_timestamp: packet.timeout_timestamp.nanoseconds(),
+			ack,
+		})
+	}
+}
+
+/// Convert tendermint [`ProofOps`](tendermint::merkle::proof::ProofOps) returned by an abci query
+/// to an encoded [`RawMerkleProof`].
+fn proof_ops_to_merkle_proof(
+	proof_ops: Option<tendermint::merkle::proof::ProofOps>,
+) -> Result<Vec<u8>, Error> {
+	let proof_ops =
+		proof_ops.ok_or_else(|| Error::Custom("Abci query returned no proof".to_string()))?;
+	let proofs = proof_ops
+		.ops
+		.into_iter()
+		.map(|op| ibc_proto::cosmos::ics23::v1::CommitmentProof::decode(&*op.data))
+		.collect::<Result<Vec<_>, _>>()?;
+	Ok(RawMerkleProof { proofs }.encode_to_vec())
+}
+
+/// Attach the `x-cosmos-block-height` header, so that the gRPC query is served at the given
+/// height.
+/// Collects the items of every page of a paginated grpc query. `query` sends the request for the
+/// given page and returns its items along with the pagination of the response, pages are
+/// fetched until the response has no next key.
+async fn query_all_pages<T, F, Fut>(mut query: F) -> Result<Vec<T>, Error>
+where
+	F: FnMut(PageRequest) -> Fut,
+	Fut: Future<Output = Result<(Vec<T>, Option<PageResponse>), Error>>,
+{
+	let mut items = vec![];
+	let mut key = vec![];
+	loop {
+		let (page, pagination) = query(PageRequest {
+			key,
+			offset: 0,
+			limit: GRPC_PAGE_LIMIT,
+			count_total: false,
+			reverse: false,
+		})
+		.await?;
+		items.extend(page);
+		match pagination {
+			Some(pagination) if !pagination.next_key.is_empty() => key = pagination.next_key,
+			_ => break,
+		}
+	}
+	Ok(items)
+}
+
+fn with_height<T>(message: T, height: Height) -> tonic::Request<T> {
+	let mut request = tonic::Request::new(message);
+	request.metadata_mut().insert(
+		"x-cosmos-block-height",
+		height
+			.revision_height
+			.to_string()
+			.parse()
+			.expect("height is a valid header value"),
+	);
+	request
+}
+
+const IBC_EVENTS_MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const IBC_EVENTS_MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Streams the ibc events of new transactions into `tx` until the subscription fails or ends.
+/// Returns `true` once the receiving end has been dropped and there is no point in reconnecting.
+async fn subscribe_ibc_events(
+	websocket_url: &Url,
+	revision_number: u64,
+	tx: &tokio::sync::mpsc::Sender<IbcEvent>,
+) -> Result<bool, Error> {
+	let (client, driver) = WebSocketClient::new(websocket_url.clone()).await?;
+	let driver_handle = tokio::spawn(driver.run());
+	let mut subscription = client.subscribe(EventType::Tx.into()).await?;
+
+	let result = async {
+		while let Some(event) = subscription.next().await {
+			let tx_result = match event?.data {
+				EventData::Tx { tx_result } => tx_result,
+				_ => continue,
+			};
+			let height = Height::new(revision_number, tx_result.height as u64);
+			for event in tx_result.result.events.iter() {
+				if let Some(ibc_event) = ibc_event_try_from_abci_event(event, height) {
+					if tx.send(ibc_event).await.is_err() {
+						return Ok(true)
+					}
+				}
+			}
+		}
+		Ok(false)
+	}
+	.await;
+
+	let _ = client.close();
+	let _ = driver_handle.await;
+	result
+}
@@ -0,0 +1,64 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, CosmosClient};
+use futures::{Stream, StreamExt};
+use ibc::{
+	applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin},
+	core::ics24_host::identifier::ChannelId,
+	tx_msg::Msg,
+};
+use primitives::{Chain, TestProvider};
+use std::pin::Pin;
+use tendermint_rpc::{event::EventData, query::EventType, SubscriptionClient, WebSocketClient};
+
+#[async_trait::async_trait]
+impl TestProvider for CosmosClient {
+	async fn send_transfer(&self, transfer: MsgTransfer<PrefixedCoin>) -> Result<(), Self::Error> {
+		self.submit(vec![transfer.to_any()]).await?;
+		Ok(())
+	}
+
+	async fn send_ordered_packet(
+		&self,
+		_channel_id: ChannelId,
+		_timeout: pallet_ibc::Timeout,
+	) -> Result<(), Self::Error> {
+		Err(Error::Custom("Sending ordered packets is not supported on cosmos chains".to_string()))
+	}
+
+	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
+		let (client, driver) = WebSocketClient::new(self.websocket_url.clone())
+			.await
+			.expect("Failed to connect to tendermint websocket");
+		tokio::spawn(driver.run());
+		let subscription = client
+			.subscribe(EventType::NewBlock.into())
+			.await
+			.expect("Failed to subscribe to new blocks");
+
+		let stream = subscription.filter_map(move |event| {
+			// keep the client alive for as long as the subscription is.
+			let _client = &client;
+			let height = match event.map(|event| event.data) {
+				Ok(EventData::NewBlock { block: Some(block), .. }) =>
+					Some(block.header.height.value()),
+				_ => None,
+			};
+			futures::future::ready(height)
+		});
+
+		Box::pin(stream)
+	}
+}
@@ -0,0 +1,267 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for [`CosmosClient`] against a mocked tendermint rpc server on localhost.
+
+use hyperspace_cosmos::{CosmosClient, CosmosClientConfig};
+use ibc::{core::ics24_host::identifier::ClientId, signer::Signer, Height};
+use ics07_tendermint::client_message::Header;
+use primitives::KeyProvider;
+use std::{str::FromStr, sync::Arc};
+use tendermint::validator::Set;
+use tendermint_rpc::endpoint::{commit, validators};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::{TcpListener, TcpStream},
+};
+
+const MNEMONIC: &str =
+	"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+const HASH: &str = "0F2A6D1C9E1B2A6B7F6A1D3C4E5F60718293A4B5C6D7E8F90A1B2C3D4E5F6071";
+const APP_HASH: &str = "6C1A8E4F2D0B3A5C7E9F1B3D5A7C9E0F2B4D6A8C0E2F4B6D8A0C2E4F6B8D0A2C";
+const FORKED_APP_HASH: &str = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+/// Ed25519 base point, and the address derived from it
+const VALIDATOR_PUB_KEY: &str = "WGZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmY=";
+const VALIDATOR_ADDRESS: &str = "CB05C9FAC26332F9ABC5F4F50B47E39EDAB18BD5";
+
+/// Serves json-rpc requests on a random local port, answering each with the result `respond`
+/// returns for its method. Returns the server's url.
+async fn serve(respond: impl Fn(&str) -> serde_json::Value + Send + Sync + 'static) -> String {
+	let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let addr = listener.local_addr().unwrap();
+	let respond = Arc::new(respond);
+	tokio::spawn(async move {
+		loop {
+			let (mut socket, _) = listener.accept().await.unwrap();
+			let respond = respond.clone();
+			tokio::spawn(async move {
+				let request = read_request(&mut socket).await;
+				let method = serde_json::from_slice::<serde_json::Value>(&request)
+					.ok()
+					.and_then(|request| request["method"].as_str().map(str::to_owned))
+					.unwrap_or_default();
+				let body = serde_json::json!({
+					"jsonrpc": "2.0",
+					"id": "",
+					"result": respond(&method)
+				})
+				.to_string();
+				let response = format!(
+					"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+					body.len(),
+					body
+				);
+				socket.write_all(response.as_bytes()).await.unwrap();
+			});
+		}
+	});
+	format!("http://{}", addr)
+}
+
+/// Reads an http request, returns its body.
+async fn read_request(socket: &mut TcpStream) -> Vec<u8> {
+	let mut request = vec![];
+	let mut buf = [0u8; 8192];
+	loop {
+		let read = socket.read(&mut buf).await.unwrap();
+		if read == 0 {
+			return vec![]
+		}
+		request.extend_from_slice(&buf[..read]);
+		if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+			let headers = String::from_utf8_lossy(&request[..end]).to_lowercase();
+			let length = headers
+				.lines()
+				.find_map(|line| line.strip_prefix("content-length:"))
+				.and_then(|length| length.trim().parse::<usize>().ok())
+				.unwrap_or_default();
+			let body = end + 4;
+			if request.len() >= body + length {
+				return request[body..body + length].to_vec()
+			}
+		}
+	}
+}
+
+/// Serves every request with the given json-rpc result, returns the server's url.
+async fn mock_rpc(result: serde_json::Value) -> String {
+	serve(move |_| result.clone()).await
+}
+
+fn config(rpc_url: String) -> CosmosClientConfig {
+	CosmosClientConfig {
+		name: "cosmos".to_string(),
+		rpc_url,
+		grpc_url: "http://127.0.0.1:9090".to_string(),
+		websocket_url: "ws://127.0.0.1:26657/websocket".to_string(),
+		chain_id: "cosmoshub-1".to_string(),
+		client_id: None,
+		connection_id: None,
+		account_prefix: "cosmos".to_string(),
+		fee_denom: "stake".to_string(),
+		fee_amount: "4000".to_string(),
+		gas_limit: None,
+		store_prefix: "ibc".to_string(),
+		max_tx_size: None,
+		mnemonic: Some(MNEMONIC.to_string()),
+		key_name: None,
+		keystore_path: None,
+		channel_whitelist: vec![],
+		min_relay_fees: vec![],
+		trusted_rpc_urls: vec![],
+	}
+}
+
+fn abci_query_result(code: u32, value: &[u8]) -> serde_json::Value {
+	serde_json::json!({
+		"response": {
+			"code": code,
+			"log": if code == 0 { "" } else { "not found" },
+			"info": "",
+			"index": "0",
+			"key": null,
+			"value": base64::encode(value),
+			"proofOps": null,
+			"height": "41",
+			"codespace": ""
+		}
+	})
+}
+
+#[tokio::test]
+async fn derives_relayer_account_from_mnemonic() {
+	let client = CosmosClient::new(config("http://127.0.0.1:26657".to_string())).await.unwrap();
+	assert_eq!(
+		client.account_id(),
+		Signer::from_str("cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4").unwrap()
+	);
+	assert_eq!(client.revision_number(), 1);
+}
+
+#[tokio::test]
+async fn queries_store_through_abci() {
+	let rpc_url = mock_rpc(abci_query_result(0, b"value")).await;
+	let client = CosmosClient::new(config(rpc_url)).await.unwrap();
+	let response = client
+		.query_path(b"clients/07-tendermint-0/clientState".to_vec(), Height::new(1, 42), false)
+		.await
+		.unwrap();
+	assert_eq!(response.value, b"value".to_vec());
+}
+
+#[tokio::test]
+async fn abci_query_errors_are_surfaced() {
+	let rpc_url = mock_rpc(abci_query_result(1, b"")).await;
+	let client = CosmosClient::new(config(rpc_url)).await.unwrap();
+	let result = client
+		.query_path(b"clients/07-tendermint-0/clientState".to_vec(), Height::new(1, 42), false)
+		.await;
+	assert!(result.is_err());
+}
+
+fn commit_result(app_hash: &str) -> serde_json::Value {
+	let block_id = serde_json::json!({ "hash": HASH, "parts": { "total": 1, "hash": HASH } });
+	serde_json::json!({
+		"signed_header": {
+			"header": {
+				"version": { "block": "11", "app": "0" },
+				"chain_id": "cosmoshub-1",
+				"height": "10",
+				"time": "2022-10-01T00:00:00Z",
+				"last_block_id": block_id.clone(),
+				"last_commit_hash": HASH,
+				"data_hash": HASH,
+				"validators_hash": HASH,
+				"next_validators_hash": HASH,
+				"consensus_hash": HASH,
+				"app_hash": app_hash,
+				"last_results_hash": HASH,
+				"evidence_hash": HASH,
+				"proposer_address": VALIDATOR_ADDRESS
+			},
+			"commit": {
+				"height": "10",
+				"round": 0,
+				"block_id": block_id,
+				"signatures": [{
+					"block_id_flag": 2,
+					"validator_address": VALIDATOR_ADDRESS,
+					"timestamp": "2022-10-01T00:00:00Z",
+					"signature": base64::encode([1u8; 64])
+				}]
+			}
+		},
+		"canonical": true
+	})
+}
+
+fn validators_result() -> serde_json::Value {
+	serde_json::json!({
+		"block_height": "10",
+		"validators": [{
+			"address": VALIDATOR_ADDRESS,
+			"pub_key": { "type": "tendermint/PubKeyEd25519", "value": VALIDATOR_PUB_KEY },
+			"voting_power": "10",
+			"proposer_priority": "0"
+		}],
+		"count": "1",
+		"total": "1"
+	})
+}
+
+/// The header of block 10 as submitted to the light client, trusting block 9.
+fn header(commit: serde_json::Value) -> Header {
+	let signed_header = serde_json::from_value::<commit::Response>(commit).unwrap().signed_header;
+	let validators = serde_json::from_value::<validators::Response>(validators_result())
+		.unwrap()
+		.validators;
+	Header {
+		validator_set: Set::with_proposer(
+			validators.clone(),
+			signed_header.header.proposer_address,
+		)
+		.unwrap(),
+		signed_header,
+		trusted_height: Height::new(1, 9),
+		trusted_validator_set: Set::without_proposer(validators),
+	}
+}
+
+#[tokio::test]
+async fn reports_headers_diverging_from_trusted_nodes() {
+	let trusted_rpc_url = serve(|method| match method {
+		"commit" => commit_result(APP_HASH),
+		"validators" => validators_result(),
+		_ => serde_json::Value::Null,
+	})
+	.await;
+	let mut config = config("http://127.0.0.1:26657".to_string());
+	config.client_id = Some(ClientId::from_str("07-tendermint-0").unwrap());
+	config.trusted_rpc_urls = vec![trusted_rpc_url];
+	let client = CosmosClient::new(config).await.unwrap();
+
+	let honest_header = header(commit_result(APP_HASH));
+	assert!(client.query_misbehaviour(&honest_header).await.unwrap().is_none());
+
+	let forked_header = header(commit_result(FORKED_APP_HASH));
+	let misbehaviour = client
+		.query_misbehaviour(&forked_header)
+		.await
+		.unwrap()
+		.expect("a header diverging from the trusted node is misbehaviour");
+	assert_eq!(misbehaviour.client_id, ClientId::from_str("07-tendermint-0").unwrap());
+	assert_eq!(misbehaviour.header1, forked_header);
+	assert_eq!(misbehaviour.header2, honest_header);
+}
@@ -51,6 +51,7 @@ impl LightClientMetrics {
 	pub fn register(
 		client_id: &ClientId,
 		prefix: &str,
+		const_labels: HashMap<String, String>,
 		registry: &Registry,
 	) -> Result<Self, PrometheusError> {
 		Ok(Self {
@@ -60,6 +61,7 @@ impl LightClientMetrics {
 						&format!("hyperspace_{}_light_client_revision_height", prefix),
 						"The height of the last trusted state",
 					)
+					.const_labels(const_labels.clone())
 					.const_label("client_id", client_id.to_string()),
 				)?,
 				registry,
@@ -70,6 +72,7 @@ impl LightClientMetrics {
 						format!("hyperspace_{}_light_client_revision", prefix),
 						"The revision of the last trusted state",
 					)
+					.const_labels(const_labels.clone())
 					.const_label("client_id", client_id.to_string()),
 				)?,
 				registry,
@@ -80,6 +83,7 @@ impl LightClientMetrics {
 						format!("hyperspace_{}_number_of_received_header_updates", prefix),
 						"Total number of header updates received",
 					)
+					.const_labels(const_labels.clone())
 					.const_label("client_id", client_id.to_string()),
 				)?,
 				registry,
@@ -110,11 +114,15 @@ pub struct Metrics {
 	pub number_of_sent_acknowledgments: Counter<U64>,
 	/// Total number of timed out packets.
 	pub number_of_sent_timeout_packets: Counter<U64>,
+	/// Total number of messages that were dropped because they failed on chain.
+	pub number_of_failed_messages: Counter<U64>,
 
 	/// Number of undelivered packets over time.
 	pub number_of_undelivered_packets: Gauge<U64>,
 	/// Number of undelivered acknowledgements over time.
 	pub number_of_undelivered_acknowledgements: Gauge<U64>,
+	/// Seconds left before the light client of the chain on its counterparty expires.
+	pub light_client_time_to_expiry: Gauge<U64>,
 	/// Gas cost for every sent tx bundle.
 	pub gas_cost_for_sent_tx_bundle: Histogram,
 	/// Transaction length (in bytes) for every sent tx bundle.
@@ -140,73 +148,132 @@ pub struct Metrics {
 
 	/// Metrics prefix.
 	pub prefix: String,
+	/// Constant labels attached to every metric.
+	pub const_labels: HashMap<String, String>,
 }
 
 impl Metrics {
 	pub fn register(prefix: &str, registry: &Registry) -> Result<Self, PrometheusError> {
+		Self::register_with_labels(prefix, HashMap::new(), registry)
+	}
+
+	/// Register the metrics with the given constant labels attached to every metric, e.g. the
+	/// relay path the metrics belong to.
+	pub fn register_with_labels(
+		prefix: &str,
+		const_labels: HashMap<String, String>,
+		registry: &Registry,
+	) -> Result<Self, PrometheusError> {
 		Ok(Self {
 			number_of_received_send_packets: register(
-				Counter::with_opts(Opts::new(
-					&format!("hyperspace_{}_number_of_send_packet_events", prefix),
-					"Total number of 'send packet' events.",
-				))?,
+				Counter::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_send_packet_events", prefix),
+						"Total number of 'send packet' events.",
+					)
+					.const_labels(const_labels.clone()),
+				)?,
 				registry,
 			)?,
 			number_of_received_receive_packets: register(
-				Counter::with_opts(Opts::new(
-					&format!("hyperspace_{}_number_of_receive_packet_events", prefix),
-					"Total number of 'receive packet' events.",
-				))?,
+				Counter::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_receive_packet_events", prefix),
+						"Total number of 'receive packet' events.",
+					)
+					.const_labels(const_labels.clone()),
+				)?,
 				registry,
 			)?,
 			number_of_received_acknowledge_packets: register(
-				Counter::with_opts(Opts::new(
-					&format!("hyperspace_{}_number_of_acknowledge_packet_events", prefix),
-					"Total number of 'acknowledge packet' events.",
-				))?,
+				Counter::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_acknowledge_packet_events", prefix),
+						"Total number of 'acknowledge packet' events.",
+					)
+					.const_labels(const_labels.clone()),
+				)?,
 				registry,
 			)?,
 			number_of_received_timeouts: register(
-				Counter::with_opts(Opts::new(
-					&format!("hyperspace_{}_number_of_timeout_packet_events", prefix),
-					"Total number of 'timeout packet' events.",
-				))?,
+				Counter::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_timeout_packet_events", prefix),
+						"Total number of 'timeout packet' events.",
+					)
+					.const_labels(const_labels.clone()),
+				)?,
 				registry,
 			)?,
 			counterparty_number_of_received_packets: None,
 			counterparty_number_of_received_acknowledgments: None,
 			number_of_sent_packets: register(
-				Counter::new(
-					&format!("hyperspace_{}_number_of_sent_packets", prefix),
-					"Total number of sent packets",
+				Counter::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_sent_packets", prefix),
+						"Total number of sent packets",
+					)
+					.const_labels(const_labels.clone()),
 				)?,
 				registry,
 			)?,
 			number_of_sent_acknowledgments: register(
-				Counter::new(
-					&format!("hyperspace_{}_number_of_sent_acknowledgments", prefix),
-					"Total number of sent acknowledgments",
+				Counter::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_sent_acknowledgments", prefix),
+						"Total number of sent acknowledgments",
+					)
+					.const_labels(const_labels.clone()),
 				)?,
 				registry,
 			)?,
 			number_of_sent_timeout_packets: register(
-				Counter::new(
-					&format!("hyperspace_{}_number_of_timed_out_packets", prefix),
-					"Total number of timed out packets",
+				Counter::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_timed_out_packets", prefix),
+						"Total number of timed out packets",
+					)
+					.const_labels(const_labels.clone()),
+				)?,
+				registry,
+			)?,
+			number_of_failed_messages: register(
+				Counter::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_failed_messages", prefix),
+						"Total number of messages that failed on chain",
+					)
+					.const_labels(const_labels.clone()),
 				)?,
 				registry,
 			)?,
 			number_of_undelivered_packets: register(
-				Gauge::new(
-					&format!("hyperspace_{}_number_of_undelivered_packets", prefix),
-					"Number of undelivered packets over time",
+				Gauge::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_undelivered_packets", prefix),
+						"Number of undelivered packets over time",
+					)
+					.const_labels(const_labels.clone()),
 				)?,
 				registry,
 			)?,
 			number_of_undelivered_acknowledgements: register(
-				Gauge::new(
-					&format!("hyperspace_{}_number_of_undelivered_acknowledgements", prefix),
-					"Number of undelivered acknowledgements over time",
+				Gauge::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_number_of_undelivered_acknowledgements", prefix),
+						"Number of undelivered acknowledgements over time",
+					)
+					.const_labels(const_labels.clone()),
+				)?,
+				registry,
+			)?,
+			light_client_time_to_expiry: register(
+				Gauge::with_opts(
+					Opts::new(
+						&format!("hyperspace_{}_light_client_time_to_expiry", prefix),
+						"Seconds left before the light client expires on the counterparty",
+					)
+					.const_labels(const_labels.clone()),
 				)?,
 				registry,
 			)?,
@@ -216,6 +283,7 @@ impl Metrics {
 						&format!("hyperspace_{}_gas_cost_for_sent_tx_bundle", prefix),
 						"Gas cost for every sent tx bundle",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
@@ -226,6 +294,7 @@ impl Metrics {
 						&format!("hyperspace_{}_transaction_length_for_sent_tx_bundle", prefix),
 						"Transaction length for every sent tx bundle",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
@@ -237,6 +306,7 @@ impl Metrics {
 						&format!("hyperspace_{}_send_packet_event_time", prefix),
 						"Time it takes to process a 'send packet' event",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
@@ -247,6 +317,7 @@ impl Metrics {
 						&format!("hyperspace_{}_receive_packet_event_time", prefix),
 						"Time it takes to process a 'receive packet' event",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
@@ -257,6 +328,7 @@ impl Metrics {
 						&format!("hyperspace_{}_acknowledge_packet_event_time", prefix),
 						"Time it takes to process a 'acknowledge packet' event",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
@@ -267,6 +339,7 @@ impl Metrics {
 						&format!("hyperspace_{}_sent_packet_time", prefix),
 						"Time it takes to send and receive a packet",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
@@ -277,6 +350,7 @@ impl Metrics {
 						&format!("hyperspace_{}_sent_acknowledgment_time", prefix),
 						"Time it takes to send and receive an acknowledgment",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
@@ -287,6 +361,7 @@ impl Metrics {
 						&format!("hyperspace_{}_sent_timeout_packet_time", prefix),
 						"Time it takes to send and receive a timeout packet",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
@@ -297,11 +372,13 @@ impl Metrics {
 						&format!("hyperspace_{}_sent_update_client_time", prefix),
 						"Average time between client updates",
 					)
+					.const_labels(const_labels.clone())
 					.buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0, 1000000.0]),
 				)?,
 				registry,
 			)?,
 			prefix: prefix.to_string(),
+			const_labels,
 		})
 	}
 
@@ -343,8 +420,12 @@ impl Metrics {
 				Ok(())
 			},
 			None => {
-				let light_client_metrics =
-					LightClientMetrics::register(client_id, &self.prefix, registry)?;
+				let light_client_metrics = LightClientMetrics::register(
+					client_id,
+					&self.prefix,
+					self.const_labels.clone(),
+					registry,
+				)?;
 				self.light_client_height.insert(client_id.clone(), light_client_metrics);
 				Ok(())
 			},
@@ -29,7 +29,7 @@ use std::{
 	collections::HashMap,
 	ops::DerefMut,
 	sync::{Arc, Mutex},
-	time::Instant,
+	time::{Duration, Instant},
 };
 
 #[derive(Eq, PartialEq, Hash)]
@@ -51,6 +51,7 @@ impl From<Packet> for PacketId {
 
 pub type PacketMap = Arc<Mutex<HashMap<PacketId, Instant>>>;
 
+#[derive(Clone)]
 pub struct MetricsHandler {
 	registry: Registry,
 	metrics: Metrics,
@@ -185,6 +186,14 @@ impl MetricsHandler {
 		}
 	}
 
+	pub fn handle_client_expiry(&self, time_left: Duration) {
+		self.metrics.light_client_time_to_expiry.set(time_left.as_secs());
+	}
+
+	pub fn handle_failed_messages(&self, count: u64) {
+		self.metrics.number_of_failed_messages.inc_by(count);
+	}
+
 	pub async fn handle_transaction_costs(&self, batch_weight: u64, messages: &[Any]) {
 		let batch_size = messages.iter().map(|x| x.value.len()).sum::<usize>();
 		self.metrics.gas_cost_for_sent_tx_bundle.observe(batch_weight as f64);
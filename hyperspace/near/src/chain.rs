@@ -12,72 +12,168 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::pin::Pin;
+use std::{
+	pin::Pin,
+	time::{Duration, Instant},
+};
 
 use futures::Stream;
-use ibc::core::ics03_connection::msgs::{conn_open_ack, conn_open_init};
+use ibc::core::ics02_client::events::UpdateClient;
 use ibc_proto::google::protobuf::Any;
-use near_primitives::{hash::CryptoHash, transaction::Transaction};
-use primitives::{Chain, IbcProvider};
+use near_primitives::views::FinalExecutionStatus;
+use pallet_ibc::light_clients::AnyClientMessage;
+use primitives::{store::TransactionStatus, Chain, IbcProvider, MisbehaviourHandler};
+use prost::Message;
+use serde::Serialize;
 use tokio_stream::wrappers::ReceiverStream;
 
-use super::error::Error;
-use crate::Client;
+use super::{error::Error, provider::FinalityEvent, CryptoHash, NearClient, BLOCK_TIME};
+
+/// Rough amount of gas the IBC contract burns to handle a single message, NEAR has no way to
+/// dry-run a transaction.
+const GAS_PER_MESSAGE: u64 = 20_000_000_000_000;
+
+/// How long we wait for a broadcasted transaction to be executed
+const TX_COMMIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Arguments of the IBC contract's `deliver` method
+#[derive(Serialize)]
+struct DeliverArgs {
+	/// Hex encoded protobuf [`Any`] messages
+	messages: Vec<String>,
+}
+
+/// Parses a transaction hash returned by [`Chain::broadcast`].
+fn parse_tx_hash(tx_hash: &str) -> Result<CryptoHash, Error> {
+	tx_hash
+		.parse()
+		.map_err(|e| Error::from(format!("Invalid transaction hash {}: {}", tx_hash, e)))
+}
 
 #[async_trait::async_trait]
-impl Chain for Client {
+impl Chain for NearClient {
+	fn name(&self) -> &str {
+		&*self.name
+	}
+
+	fn block_max_weight(&self) -> u64 {
+		self.max_gas
+	}
+
+	async fn estimate_weight(&self, messages: Vec<Any>) -> Result<u64, Self::Error> {
+		Ok(messages.len() as u64 * GAS_PER_MESSAGE)
+	}
+
 	async fn finality_notifications(
 		&self,
 	) -> Pin<Box<dyn Stream<Item = <Self as IbcProvider>::FinalityEvent> + Send + Sync>> {
-		let stream = self.indexer.streamer();
-		Box::pin(ReceiverStream::new(stream))
+		let (tx, rx) = tokio::sync::mpsc::channel(32);
+		let client = self.clone();
+
+		// the rpc has no subscriptions, poll for new final blocks instead.
+		tokio::spawn(async move {
+			let mut last_height = 0;
+			loop {
+				tokio::time::sleep(BLOCK_TIME).await;
+				let header = match client.latest_final_block().await {
+					Ok(header) => header,
+					Err(err) => {
+						log::error!("Failed to fetch latest final block: {}", err);
+						continue
+					},
+				};
+				if header.height <= last_height {
+					continue
+				}
+				last_height = header.height;
+				if tx.send(FinalityEvent::Near(header)).await.is_err() {
+					break
+				}
+			}
+		});
+
+		Box::pin(ReceiverStream::new(rx))
 	}
 
-	async fn submit(&self, mut messages: Vec<Any>) -> Result<(), Error> {
-		let update_client_message = messages.remove(0);
-		let mut permissioned_messages = vec![];
-		let mut non_permissioned_messages = vec![];
+	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Error> {
+		let args = DeliverArgs {
+			messages: messages.iter().map(|msg| hex::encode(msg.encode_to_vec())).collect(),
+		};
+		let outcome = self.call_contract("deliver", serde_json::to_vec(&args)?).await?;
+		Ok(outcome.transaction.hash)
+	}
+
+	async fn broadcast(&self, messages: Vec<Any>) -> Result<String, Error> {
+		let args = DeliverArgs {
+			messages: messages.iter().map(|msg| hex::encode(msg.encode_to_vec())).collect(),
+		};
+		let tx_hash = self.broadcast_contract_call("deliver", serde_json::to_vec(&args)?).await?;
+		Ok(tx_hash.to_string())
+	}
 
-		for msg in messages {
-			if matches!(msg.type_url.as_str(), conn_open_init::TYPE_URL | conn_open_ack::TYPE_URL) {
-				permissioned_messages.push(msg)
-			} else {
-				non_permissioned_messages.push(msg)
+	async fn wait_for_transaction(&self, tx_hash: &str) -> Result<(), Error> {
+		let hash = parse_tx_hash(tx_hash)?;
+		let now = Instant::now();
+		loop {
+			match self.transaction_outcome(hash).await {
+				Ok(outcome) => match outcome.status {
+					FinalExecutionStatus::SuccessValue(_) => return Ok(()),
+					FinalExecutionStatus::Failure(err) =>
+						return Err(Error::TransactionFailed {
+							tx_hash: tx_hash.to_string(),
+							error: err.to_string(),
+						}),
+					FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started => {},
+				},
+				Err(err) if now.elapsed() > TX_COMMIT_TIMEOUT =>
+					return Err(Error::from(format!(
+						"Timeout while waiting for transaction {}: {}",
+						tx_hash, err
+					))),
+				Err(_) => {},
 			}
+			tokio::time::sleep(BLOCK_TIME).await;
 		}
+	}
 
-		let mut messages = vec![update_client_message];
-		if !non_permissioned_messages.is_empty() {
-			messages.extend(non_permissioned_messages.into_iter())
+	async fn query_transaction_status(&self, tx_hash: &str) -> Result<TransactionStatus, Error> {
+		match self.transaction_outcome(parse_tx_hash(tx_hash)?).await {
+			Ok(outcome) => Ok(match outcome.status {
+				FinalExecutionStatus::SuccessValue(_) => TransactionStatus::Included,
+				FinalExecutionStatus::Failure(err) => TransactionStatus::Failed(err.to_string()),
+				FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started =>
+					TransactionStatus::Unknown,
+			}),
+			// the node reports unknown transactions as an error
+			Err(err) => {
+				log::debug!(target: "hyperspace", "Transaction {} not found: {}", tx_hash, err);
+				Ok(TransactionStatus::Unknown)
+			},
 		}
+	}
 
-		let transaction = Transaction::new(
-			self.signer.clone(),
-			self.public_key(),
-			self.contract_id.clone(),
-			0,
-			CryptoHash::default(),
-		);
-
-		let gas = 1_000_000_000_000;
-		let deliver_tx = transaction.clone().function_call(
-			"deliver".to_owned(),
-			serde_json::to_vec(&messages)?,
-			gas,
-			0,
-		);
-		// TODO: handle intermediate receipts
-		let _ = self.send_transaction(deliver_tx).await?;
-
-		if !permissioned_messages.is_empty() {
-			let deliver_permissioned_tx = transaction.clone().function_call(
-				"deliver_permissioned".to_owned(),
-				serde_json::to_vec(&permissioned_messages)?,
-				gas,
-				0,
-			);
-			let _ = self.send_transaction(deliver_permissioned_tx).await?;
-		}
+	async fn query_client_message(&self, update: UpdateClient) -> Result<AnyClientMessage, Error> {
+		let header =
+			update.header.ok_or_else(|| Error::from("No client message found".to_owned()))?;
+		let any = Any::decode(&*header)
+			.map_err(|e| Error::from(format!("Failed to decode Any: {:?}", e)))?;
+		AnyClientMessage::try_from(any)
+			.map_err(|e| Error::from(format!("Failed to decode client message: {:?}", e)))
+	}
+
+	async fn reconnect(&mut self) -> Result<(), Error> {
+		// the json rpc client connects on every request
+		Ok(())
+	}
+}
+
+#[async_trait::async_trait]
+impl MisbehaviourHandler for NearClient {
+	async fn check_for_misbehaviour<C: Chain>(
+		&self,
+		_counterparty: &C,
+		_client_message: AnyClientMessage,
+	) -> Result<(), anyhow::Error> {
 		Ok(())
 	}
 }
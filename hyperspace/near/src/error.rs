@@ -12,13 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ibc::core::ics02_client;
-
-use near_jsonrpc_client::errors::JsonRpcError;
+use ibc::{core::ics02_client, timestamp::ParseTimestampError};
+use near_jsonrpc_client::{
+	errors::JsonRpcError, methods::broadcast_tx_async::RpcBroadcastTxAsyncError,
+};
 use near_jsonrpc_primitives::types::{
-	blocks::RpcBlockError, query::RpcQueryError, transactions::RpcTransactionError,
-	validator::RpcValidatorError,
+	blocks::RpcBlockError, light_client::RpcLightClientNextBlockError, query::RpcQueryError,
+	transactions::RpcTransactionError, validator::RpcValidatorError,
 };
+use primitives::error::ErrorKind;
 use std::io;
 use thiserror::Error;
 
@@ -30,9 +32,13 @@ pub enum RpcError {
 	#[error("{0}")]
 	Transaction(#[from] JsonRpcError<RpcTransactionError>),
 	#[error("{0}")]
+	Broadcast(#[from] JsonRpcError<RpcBroadcastTxAsyncError>),
+	#[error("{0}")]
 	Block(#[from] JsonRpcError<RpcBlockError>),
 	#[error("{0}")]
 	Validator(#[from] JsonRpcError<RpcValidatorError>),
+	#[error("{0}")]
+	LightClient(#[from] JsonRpcError<RpcLightClientNextBlockError>),
 }
 
 /// Error definition for the NEAR client
@@ -47,29 +53,40 @@ pub enum Error {
 	/// RPC error
 	#[error("RPC error: {0}")]
 	Rpc(#[from] RpcError),
-	/// Update pallet name in call definition
-	#[error("Pallet '{0}' not found in metadata, update static definition of call")]
-	PalletNotFound(&'static str),
+	/// Transaction failed during execution
+	#[error("Transaction {tx_hash} failed: {error}")]
+	TransactionFailed { tx_hash: String, error: String },
+	/// Key parsing or signing error
+	#[error("Key error: {0}")]
+	Key(String),
 	/// hex error
 	#[error("Error decoding hex: {0:?}")]
 	Hex(#[from] hex::FromHexError),
 	/// Custom error
 	#[error("{0}")]
 	Custom(String),
+	/// Ibc channel error
 	#[error("Ibc channel error")]
 	IbcChannel(#[from] ibc::core::ics04_channel::error::Error),
-	/// Error querying packets
-	#[error("Could not retrieve packets from {channel_id}/{port_id} for sequences {:?}", .sequences)]
-	QueryPackets { channel_id: String, port_id: String, sequences: Vec<u64>, err: String },
-	/// Failed to rehydrate client state
-	#[error("Error decoding some value: {0}")]
-	ClientStateRehydration(String),
 	/// Failed to get client update header from finality notification
 	#[error("Error constructing a client update header: {0}")]
 	HeaderConstruction(String),
 	/// Errors associated with ics-02 client
 	#[error("Ibc client error: {0}")]
 	IbcClient(#[from] ics02_client::error::Error),
+	/// Error occured parsing timestamp
+	#[error("Timestamp error: {0}")]
+	ParseTimestamp(#[from] ParseTimestampError),
+}
+
+impl Error {
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Self::Key(_) => ErrorKind::Fatal,
+			Self::TransactionFailed { .. } => ErrorKind::Rejected,
+			_ => ErrorKind::Retryable,
+		}
+	}
 }
 
 impl From<String> for Error {
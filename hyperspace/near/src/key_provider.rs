@@ -12,12 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::Client;
+use super::NearClient;
 use primitives::KeyProvider;
 use std::str::FromStr;
 
-impl KeyProvider for Client {
+impl KeyProvider for NearClient {
 	fn account_id(&self) -> ibc::signer::Signer {
-		ibc::signer::Signer::from_str(self.signer.as_str()).expect("Account Id should be valid")
+		ibc::signer::Signer::from_str(self.signer.account_id.as_str())
+			.expect("Account Id should be valid")
 	}
 }
@@ -14,203 +14,343 @@
 
 #![allow(clippy::all)]
 
-use crate::error::Error;
-use ibc::core::{ics04_channel::packet::Packet, ics24_host::identifier::ClientId};
-use near_crypto::{ED25519PublicKey, KeyType, Secp256K1PublicKey};
-use near_indexer::{Indexer, InitConfigArgs};
+pub mod chain;
+pub mod error;
+pub mod key_provider;
+pub mod light_client_sync;
+pub mod provider;
+#[cfg(feature = "testing")]
+pub mod test_provider;
+
+use std::{str::FromStr, time::Duration};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use error::Error;
+use ibc::{
+	applications::transfer::PrefixedCoin,
+	core::{
+		ics23_commitment::commitment::CommitmentPrefix,
+		ics24_host::identifier::{ChainId, ChannelId, ClientId, ConnectionId, PortId},
+	},
+};
+use ics13_near::types::{
+	LightClientBlockView, PublicKey, ValidatorStakeView, ValidatorStakeViewV1,
+};
+use near_crypto::{InMemorySigner, SecretKey};
 use near_jsonrpc_client::{
-	methods::broadcast_tx_commit::{RpcBroadcastTxCommitRequest, RpcBroadcastTxCommitResponse},
+	methods::{
+		block::RpcBlockRequest,
+		broadcast_tx_async::RpcBroadcastTxAsyncRequest,
+		broadcast_tx_commit::RpcBroadcastTxCommitRequest,
+		next_light_client_block::RpcLightClientNextBlockRequest,
+		query::RpcQueryRequest,
+		tx::{RpcTransactionStatusRequest, TransactionInfo},
+		validators::RpcValidatorRequest,
+	},
 	JsonRpcClient,
 };
-use near_jsonrpc_primitives::types::query::{QueryResponseKind, RpcQueryRequest};
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
 use near_primitives::{
-	transaction::{SignedTransaction, Transaction},
-	types::{AccountId, Finality},
+	transaction::{Action, FunctionCallAction, SignedTransaction, Transaction},
+	types::{AccountId, BlockId, BlockReference, EpochReference, Finality, FunctionArgs},
+	views::{BlockHeaderView, FinalExecutionOutcomeView, FinalExecutionStatus, QueryRequest},
 };
-use sp_core::crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId};
-use sp_keystore::SyncCryptoStorePtr;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-mod chain;
-mod error;
-mod key_provider;
-pub mod provider;
+pub use near_primitives::hash::CryptoHash;
+
+/// Gas attached to a single call to the IBC contract, this is the maximum amount of gas a NEAR
+/// transaction may burn.
+pub const DEFAULT_MAX_GAS: u64 = 300_000_000_000_000;
 
 /// Implements the [`crate::Chain`] trait for NEAR.
-pub struct Client {
+/// This is responsible for:
+/// 1. Tracking a NEAR light client on a counter-party chain, advancing this light
+/// client state as new blocks are finalized.
+/// 2. Submitting new IBC messages to the IBC contract on NEAR.
+#[derive(Clone)]
+pub struct NearClient {
+	/// Chain name
+	pub name: String,
+	/// Chain id
+	pub chain_id: ChainId,
 	/// Near rpc client
 	pub rpc_client: JsonRpcClient,
 	/// Light client id on counterparty chain
 	pub client_id: Option<ClientId>,
-	/// Core contract id
+	/// Connection Id
+	pub connection_id: Option<ConnectionId>,
+	/// Account id of the IBC contract
 	pub contract_id: AccountId,
-	/// Near's latest finalized height
-	pub latest_near_height: Option<u32>,
 	/// Commitment prefix
-	pub commitment_prefix: Vec<u8>,
-	/// Sent packet sequence cache
-	pub packet_cache: Vec<Packet>,
-	/// Indexer that provides a stream of finalized messages from Near
-	pub indexer: Indexer,
-	/// Reference to keystore
-	pub key_store: SyncCryptoStorePtr,
-	/// 4 byte Key type id
-	pub key_type_id: KeyTypeId,
-	/// Signer's account id on Near
-	pub signer: AccountId,
+	pub commitment_prefix: CommitmentPrefix,
+	/// Signer of the relayer's transactions
+	pub signer: InMemorySigner,
+	/// Gas attached to calls to the IBC contract
+	pub max_gas: u64,
+	/// Channels cleared for packet relay
+	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+	/// Minimum fees that must be escrowed for a packet sent by this chain before it's relayed
+	pub min_relay_fees: Vec<PrefixedCoin>,
 }
 
-/// config options for [`Client`]
-pub struct ClientConfig {
+/// config options for [`NearClient`]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NearClientConfig {
+	/// Chain name
+	pub name: String,
 	/// rpc url for Near node
 	pub rpc_url: String,
+	/// Chain id, eg `testnet`
+	pub chain_id: String,
 	/// Light client id on counterparty chain
 	pub client_id: Option<ClientId>,
-	/// Core contract id
-	pub contract_id: AccountId,
+	/// Connection Id
+	pub connection_id: Option<ConnectionId>,
+	/// Account id of the IBC contract
+	pub contract_id: String,
 	/// Commitment prefix
-	pub commitment_prefix: Vec<u8>,
-	/// Reference to keystore
-	pub key_store: SyncCryptoStorePtr,
-	/// 4 byte Key type id
-	pub key_type_id: KeyTypeId,
-	/// Signer's account id on Near
-	pub signer: AccountId,
-	/// Indexer configuration
-	pub indexer_config: near_indexer::IndexerConfig,
+	pub commitment_prefix: String,
+	/// Relayer's account id on Near
+	pub account_id: String,
+	/// Secret key of the relayer's access key, eg `ed25519:...`
+	pub private_key: String,
+	/// Gas attached to calls to the IBC contract
+	pub max_gas: Option<u64>,
+	/// Channels cleared for packet relay
+	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+	/// Minimum fees that must be escrowed for a packet sent by this chain before it's relayed,
+	/// packets are relayed regardless of their fees if empty
+	#[serde(default)]
+	pub min_relay_fees: Vec<PrefixedCoin>,
 }
 
-impl Client {
-	/// Initializes configurations for indexer with a default config.
-	pub fn init_configs_default() -> anyhow::Result<()> {
-		let home_dir = near_indexer::get_default_home();
-		near_indexer::indexer_init_configs(
-			&home_dir,
-			InitConfigArgs {
-				chain_id: None,
-				account_id: None,
-				test_seed: None,
-				num_shards: 1,
-				fast: false,
-				genesis: None,
-				download_genesis: false,
-				download_genesis_url: None,
-				download_config: false,
-				download_config_url: None,
-				boot_nodes: None,
-				max_gas_burnt_view: None,
+impl NearClient {
+	/// Initializes a [`NearClient`] given a [`NearClientConfig`]
+	pub async fn new(config: NearClientConfig) -> Result<Self, Error> {
+		let rpc_client = JsonRpcClient::connect(&config.rpc_url);
+		let contract_id = AccountId::from_str(&config.contract_id)
+			.map_err(|e| Error::from(format!("Invalid contract id {}: {e}", config.contract_id)))?;
+		let account_id = AccountId::from_str(&config.account_id)
+			.map_err(|e| Error::from(format!("Invalid account id {}: {e}", config.account_id)))?;
+		let secret_key = SecretKey::from_str(&config.private_key)
+			.map_err(|e| Error::Key(format!("Invalid private key: {e}")))?;
+		let signer = InMemorySigner::from_secret_key(account_id, secret_key);
+		let commitment_prefix =
+			CommitmentPrefix::try_from(config.commitment_prefix.into_bytes())
+				.map_err(|e| Error::from(format!("Invalid commitment prefix: {e:?}")))?;
+
+		Ok(Self {
+			name: config.name,
+			chain_id: ChainId::from_string(&config.chain_id),
+			rpc_client,
+			client_id: config.client_id,
+			connection_id: config.connection_id,
+			contract_id,
+			commitment_prefix,
+			signer,
+			max_gas: config.max_gas.unwrap_or(DEFAULT_MAX_GAS),
+			channel_whitelist: config.channel_whitelist,
+			min_relay_fees: config.min_relay_fees,
+		})
+	}
+
+	pub fn client_id(&self) -> ClientId {
+		self.client_id.as_ref().expect("Client Id should be defined").clone()
+	}
+
+	pub fn set_client_id(&mut self, client_id: ClientId) {
+		self.client_id = Some(client_id)
+	}
+
+	/// Call a view function of the IBC contract at the given block height, arguments and the
+	/// result are json encoded.
+	pub async fn query_contract<T: Serialize, R: DeserializeOwned>(
+		&self,
+		at: Option<u64>,
+		method: &str,
+		args: &T,
+	) -> Result<R, Error> {
+		let block_reference = match at {
+			Some(height) => BlockReference::BlockId(BlockId::Height(height)),
+			None => BlockReference::Finality(Finality::Final),
+		};
+		let request = RpcQueryRequest {
+			block_reference,
+			request: QueryRequest::CallFunction {
+				account_id: self.contract_id.clone(),
+				method_name: method.to_string(),
+				args: FunctionArgs::from(serde_json::to_vec(args)?),
 			},
-		)
+		};
+		match self.rpc_client.call(request).await?.kind {
+			QueryResponseKind::CallResult(result) => Ok(serde_json::from_slice(&result.result)?),
+			kind => Err(Error::Custom(format!(
+				"Unexpected response to call of {method} on {}: {kind:?}",
+				self.contract_id
+			))),
+		}
 	}
 
-	pub fn new(config: ClientConfig) -> Self {
-		openssl_probe::init_ssl_cert_env_vars();
-		let indexer = Indexer::new(config.indexer_config).expect("failed to crate indexer");
-		let rpc_client = JsonRpcClient::connect(&config.rpc_url);
+	/// Sign and send a function call to the IBC contract, waits for the transaction's receipts to
+	/// be executed.
+	pub async fn call_contract(
+		&self,
+		method: &str,
+		args: Vec<u8>,
+	) -> Result<FinalExecutionOutcomeView, Error> {
+		let signed_transaction = self.sign_contract_call(method, args).await?;
+		let outcome =
+			self.rpc_client.call(RpcBroadcastTxCommitRequest { signed_transaction }).await?;
 
-		Self {
-			rpc_client,
-			client_id: None,
-			contract_id: config.contract_id,
-			latest_near_height: None,
-			commitment_prefix: vec![],
-			packet_cache: vec![],
-			indexer,
-			key_store: config.key_store,
-			signer: config.signer,
-			key_type_id: config.key_type_id,
+		if let FinalExecutionStatus::Failure(err) = &outcome.status {
+			return Err(Error::TransactionFailed {
+				tx_hash: outcome.transaction.hash.to_string(),
+				error: err.to_string(),
+			})
 		}
+
+		Ok(outcome)
+	}
+
+	/// Sign and send a function call to the IBC contract without waiting for it to be executed,
+	/// returns the hash of the transaction.
+	pub async fn broadcast_contract_call(
+		&self,
+		method: &str,
+		args: Vec<u8>,
+	) -> Result<CryptoHash, Error> {
+		let signed_transaction = self.sign_contract_call(method, args).await?;
+		Ok(self.rpc_client.call(RpcBroadcastTxAsyncRequest { signed_transaction }).await?)
+	}
+
+	/// Returns the outcome of a transaction sent by the relayer's account.
+	pub async fn transaction_outcome(
+		&self,
+		tx_hash: CryptoHash,
+	) -> Result<FinalExecutionOutcomeView, Error> {
+		Ok(self
+			.rpc_client
+			.call(RpcTransactionStatusRequest {
+				transaction_info: TransactionInfo::TransactionId {
+					hash: tx_hash,
+					account_id: self.signer.account_id.clone(),
+				},
+			})
+			.await?)
 	}
 
-	pub async fn send_transaction(
+	/// Builds and signs a function call to the IBC contract with the next nonce of the relayer's
+	/// access key.
+	async fn sign_contract_call(
 		&self,
-		prepopulated_unsigned_transaction: Transaction,
-	) -> Result<RpcBroadcastTxCommitResponse, Error> {
-		let public_key = self.public_key();
-		let online_signer_access_key_response = self
+		method: &str,
+		args: Vec<u8>,
+	) -> Result<SignedTransaction, Error> {
+		let access_key = self
 			.rpc_client
 			.call(RpcQueryRequest {
-				block_reference: Finality::Final.into(),
-				request: near_primitives::views::QueryRequest::ViewAccessKey {
-					account_id: prepopulated_unsigned_transaction.signer_id.clone(),
-					public_key: public_key.clone(),
+				block_reference: BlockReference::Finality(Finality::Final),
+				request: QueryRequest::ViewAccessKey {
+					account_id: self.signer.account_id.clone(),
+					public_key: self.signer.public_key.clone(),
 				},
 			})
 			.await?;
-		let current_nonce = if let QueryResponseKind::AccessKey(online_signer_access_key) =
-			online_signer_access_key_response.kind
-		{
-			online_signer_access_key.nonce
-		} else {
-			unreachable!("we've requested the access key");
+		let nonce = match access_key.kind {
+			QueryResponseKind::AccessKey(access_key) => access_key.nonce,
+			kind => Err(Error::Custom(format!("Expected an access key, found: {kind:?}")))?,
 		};
-		let unsigned_transaction = Transaction {
-			public_key,
-			block_hash: online_signer_access_key_response.block_hash,
-			nonce: current_nonce + 1,
-			..prepopulated_unsigned_transaction
+
+		let transaction = Transaction {
+			signer_id: self.signer.account_id.clone(),
+			public_key: self.signer.public_key.clone(),
+			nonce: nonce + 1,
+			receiver_id: self.contract_id.clone(),
+			block_hash: access_key.block_hash,
+			actions: vec![Action::FunctionCall(FunctionCallAction {
+				method_name: method.to_string(),
+				args,
+				gas: self.max_gas,
+				deposit: 0,
+			})],
 		};
-		let signature = self.sign(unsigned_transaction.get_hash_and_size().0.as_ref());
-		let signed_transaction = SignedTransaction::new(signature, unsigned_transaction);
-		// TODO: retry loop for sending transaction
-		let transaction_info = self
-			.rpc_client
-			.call(RpcBroadcastTxCommitRequest { signed_transaction: signed_transaction.clone() })
-			.await?;
-		Ok(transaction_info)
+		Ok(transaction.sign(&self.signer))
 	}
 
-	pub fn client_id(&self) -> ClientId {
-		self.client_id.as_ref().expect("Client Id should be defined").clone()
+	/// Returns the header of the latest final block.
+	pub async fn latest_final_block(&self) -> Result<BlockHeaderView, Error> {
+		let block = self
+			.rpc_client
+			.call(RpcBlockRequest { block_reference: BlockReference::Finality(Finality::Final) })
+			.await?;
+		Ok(block.header)
 	}
 
-	pub fn set_client_id(&mut self, client_id: ClientId) {
-		self.client_id = Some(client_id)
+	/// Returns the header of the block at the given height.
+	pub async fn block_at(&self, height: u64) -> Result<BlockHeaderView, Error> {
+		let block = self
+			.rpc_client
+			.call(RpcBlockRequest {
+				block_reference: BlockReference::BlockId(BlockId::Height(height)),
+			})
+			.await?;
+		Ok(block.header)
 	}
 
-	/// Returns the first found public key in the keystore
-	pub fn keystore_key(&self) -> CryptoTypePublicPair {
-		use sp_keystore::SyncCryptoStore;
-		let keys =
-			SyncCryptoStore::keys(&*self.key_store, self.key_type_id).expect("failed to load key");
-		keys.first().expect("no keys found").clone()
+	/// Fetch the next [`LightClientBlockView`] that the light client can be updated to, given the
+	/// hash of the light client's latest block. Returns `None` if there's no newer final block.
+	pub async fn next_light_client_block(
+		&self,
+		last_block_hash: ics13_near::types::CryptoHash,
+	) -> Result<Option<LightClientBlockView>, Error> {
+		let block = self
+			.rpc_client
+			.call(RpcLightClientNextBlockRequest { last_block_hash: CryptoHash(last_block_hash.0) })
+			.await?;
+		block.map(|block| convert_borsh(&block)).transpose()
 	}
 
-	pub fn public_key(&self) -> near_crypto::PublicKey {
-		let key = self.keystore_key();
-		let near_key_type = sp_to_near_key_type(key.0);
-		match near_key_type {
-			KeyType::ED25519 => {
-				let public_key = ED25519PublicKey::try_from(key.1.as_slice())
-					.expect("invalid ed25519 public key");
-				near_crypto::PublicKey::from(public_key)
-			},
-			KeyType::SECP256K1 => {
-				let public_key = Secp256K1PublicKey::try_from(key.1.as_slice())
-					.expect("invalid secp256k1 public key");
-				near_crypto::PublicKey::from(public_key)
-			},
-		}
+	/// Fetch the block producers of the given epoch
+	pub async fn epoch_validators(
+		&self,
+		epoch_id: ics13_near::types::CryptoHash,
+	) -> Result<Vec<ValidatorStakeView>, Error> {
+		let validators = self
+			.rpc_client
+			.call(RpcValidatorRequest {
+				epoch_reference: EpochReference::EpochId(near_primitives::types::EpochId(
+					CryptoHash(epoch_id.0),
+				)),
+			})
+			.await?;
+		validators
+			.current_validators
+			.into_iter()
+			.map(|validator| {
+				let public_key = PublicKey::try_from(validator.public_key.key_data())
+					.map_err(|_| Error::Key("Only ed25519 block producers are supported".into()))?;
+				Ok(ValidatorStakeView::V1(ValidatorStakeViewV1 {
+					account_id: validator.account_id.to_string(),
+					public_key,
+					stake: validator.stake,
+				}))
+			})
+			.collect()
 	}
 
-	pub fn sign(&self, msg: &[u8]) -> near_crypto::Signature {
-		use sp_keystore::SyncCryptoStore;
-		let key = self.keystore_key();
-		let near_key_type = sp_to_near_key_type(key.0);
-		let sig_data = SyncCryptoStore::sign_with(&*self.key_store, self.key_type_id, &key, msg)
-			.ok()
-			.flatten()
-			.expect("failed to sign");
-		near_crypto::Signature::from_parts(near_key_type, &sig_data)
-			.expect("failed to parse signature")
+	/// The hash of the light client block, as computed by the light client.
+	pub fn block_hash(block: &LightClientBlockView) -> ics13_near::types::CryptoHash {
+		block.current_block_hash::<pallet_ibc::light_clients::HostFunctionsManager>()
 	}
 }
 
-fn sp_to_near_key_type(sp_key_type: CryptoTypeId) -> KeyType {
-	match sp_key_type {
-		sp_core::ed25519::CRYPTO_ID => KeyType::ED25519,
-		sp_core::ecdsa::CRYPTO_ID => KeyType::SECP256K1,
-		key_type => {
-			panic!("unsupported key type: {:?}", key_type);
-		},
-	}
+/// The light client mirrors the borsh encoding of the NEAR rpc views, so conversion between
+/// them is done through a borsh roundtrip.
+fn convert_borsh<A: BorshSerialize, B: BorshDeserialize>(value: &A) -> Result<B, Error> {
+	let bytes = value.try_to_vec()?;
+	Ok(B::try_from_slice(&bytes)?)
 }
+
+/// Duration between NEAR blocks.
+pub(crate) const BLOCK_TIME: Duration = Duration::from_secs(1);
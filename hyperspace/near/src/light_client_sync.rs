@@ -0,0 +1,86 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::{
+	core::ics02_client::msgs::update_client::MsgUpdateAnyClient, events::IbcEvent, tx_msg::Msg,
+};
+use ibc_proto::google::protobuf::Any;
+use ics13_near::header::{NearClientMessage, NearHeader};
+use pallet_ibc::light_clients::AnyClientMessage;
+use primitives::{mock::LocalClientTypes, Chain, KeyProvider, LightClientSync};
+
+use super::{error::Error, NearClient};
+
+#[async_trait::async_trait]
+impl LightClientSync for NearClient {
+	async fn is_synced<C: Chain>(&self, counterparty: &C) -> Result<bool, anyhow::Error> {
+		// The light client can only verify blocks produced by the block producers of its current
+		// and next epoch, so it's synced as long as the chain hasn't moved past the next epoch.
+		let client_state = self.query_client_state_on_counterparty(counterparty).await?;
+		let latest = self.latest_final_block().await?;
+		let head = &client_state.get_head().inner_lite;
+		Ok(latest.epoch_id.0 == head.epoch_id.0 || latest.epoch_id.0 == head.next_epoch_id.0)
+	}
+
+	async fn fetch_mandatory_updates<C: Chain>(
+		&self,
+		counterparty: &C,
+	) -> Result<(Vec<Any>, Vec<IbcEvent>), anyhow::Error> {
+		let client_state = self.query_client_state_on_counterparty(counterparty).await?;
+		let latest = self.latest_final_block().await?;
+
+		// Walk the chain one light client block at a time, each of these carries the block
+		// producers of the following epoch.
+		let mut messages = vec![];
+		let mut events = vec![];
+		let mut head = client_state.get_head().clone();
+		loop {
+			let head_epoch = head.inner_lite.epoch_id.0;
+			if latest.epoch_id.0 == head_epoch ||
+				latest.epoch_id.0 == head.inner_lite.next_epoch_id.0
+			{
+				break
+			}
+			let block = self
+				.next_light_client_block(NearClient::block_hash(&head))
+				.await?
+				.ok_or_else(|| {
+					Error::HeaderConstruction("No light client block found while syncing".into())
+				})?;
+			if block.get_height() <= head.get_height() {
+				break
+			}
+
+			events.extend(
+				self.query_ibc_events(
+					head.get_height().revision_height + 1,
+					block.get_height().revision_height,
+				)
+				.await?,
+			);
+			let msg = MsgUpdateAnyClient::<LocalClientTypes> {
+				client_id: self.client_id(),
+				client_message: AnyClientMessage::Near(NearClientMessage::Header(NearHeader::new(
+					block.clone(),
+				))),
+				signer: counterparty.account_id(),
+			};
+			let value = msg.encode_vec()?;
+			messages.push(Any { value, type_url: msg.type_url() });
+			head = block;
+		}
+
+		Ok((messages, events))
+	}
+}
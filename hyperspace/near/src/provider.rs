@@ -12,127 +12,156 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#[cfg(feature = "testing")]
-use actix::dev::Stream;
-#[cfg(feature = "testing")]
-use std::pin::Pin;
-
-use super::error::Error;
-use crate::Client;
+use super::{error::Error, NearClient, BLOCK_TIME};
+use futures::Stream;
 use ibc::{
+	applications::{fee::fee::PacketFee, transfer::PrefixedCoin},
 	core::{
-		ics02_client::{
-			client_consensus::AnyConsensusState, client_state::AnyClientState, header::AnyHeader,
-		},
-		ics04_channel::packet::Packet,
+		ics02_client::{client_state::ClientType, msgs::update_client::MsgUpdateAnyClient},
 		ics23_commitment::commitment::CommitmentPrefix,
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 	},
 	events::IbcEvent,
+	timestamp::Timestamp,
+	tx_msg::Msg,
 	Height,
 };
-use ibc_proto::ibc::core::{
-	channel::v1::{
-		Packet as RawPacket, QueryChannelResponse, QueryNextSequenceReceiveResponse,
-		QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
-		QueryPacketReceiptResponse,
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::core::{
+		channel::v1::{
+			QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
+			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
+			QueryPacketReceiptResponse, QueryUpgradeErrorResponse, QueryUpgradeResponse,
+		},
+		client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
+		connection::v1::{IdentifiedConnection, QueryConnectionResponse},
 	},
-	client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
-	connection::v1::QueryConnectionResponse,
 };
-use near_indexer::StreamerMessage;
-use near_jsonrpc_primitives::types::{
-	blocks::RpcBlockRequest,
-	query::{QueryResponseKind, RpcQueryRequest},
-	validator::RpcValidatorRequest,
+use ibc_rpc::PacketInfo;
+use ics13_near::{
+	client_state::NearClientState,
+	consensus_state::ConsensusState,
+	header::{NearClientMessage, NearHeader},
 };
-use near_primitives::{
-	types::{BlockId, BlockReference, EpochReference, Finality, FunctionArgs},
-	views::QueryRequest,
+use near_jsonrpc_client::methods::tx::{RpcTransactionStatusRequest, TransactionInfo};
+use near_primitives::{hash::CryptoHash, views::BlockHeaderView};
+use pallet_ibc::light_clients::{
+	AnyClientMessage, AnyClientState, AnyConsensusState, HostFunctionsManager,
 };
-use near_sdk::BlockHeight;
-use primitives::{Chain, IbcProvider, UpdateType};
-use serde::{de::DeserializeOwned, Serialize};
+use primitives::{mock::LocalClientTypes, Chain, IbcProvider, KeyProvider, UpdateType};
+use std::{pin::Pin, time::Duration};
+use tendermint_proto::Protobuf;
+use tokio_stream::wrappers::ReceiverStream;
 
-impl Client {
-	fn make_contract_query_at<T: Serialize>(
-		&self,
-		at: BlockHeight,
-		method: impl ToString,
-		args: &T,
-	) -> Result<RpcQueryRequest, <Self as IbcProvider>::Error> {
-		Ok(RpcQueryRequest {
-			block_reference: BlockReference::BlockId(BlockId::Height(at)),
-			request: QueryRequest::CallFunction {
-				account_id: self.contract_id.clone(),
-				method_name: method.to_string(),
-				args: FunctionArgs::from(serde_json::to_vec(args)?),
-			},
-		})
-	}
-
-	fn make_contract_query_at_final<T: Serialize>(
-		&self,
-		method: impl ToString,
-		args: &T,
-	) -> Result<RpcQueryRequest, <Self as IbcProvider>::Error> {
-		Ok(RpcQueryRequest {
-			block_reference: BlockReference::Finality(Finality::Final),
-			request: QueryRequest::CallFunction {
-				account_id: self.contract_id.clone(),
-				method_name: method.to_string(),
-				args: FunctionArgs::from(serde_json::to_vec(args)?),
-			},
-		})
-	}
-
-	async fn send_query<R: DeserializeOwned>(
-		&self,
-		query: RpcQueryRequest,
-	) -> Result<R, <Self as IbcProvider>::Error> {
-		self.rpc_client
-			.call(query)
-			.await
-			.map_err(|e| e.into())
-			.and_then(|resp| match resp.kind {
-				QueryResponseKind::CallResult(res) =>
-					serde_json::from_slice(&res.result).map_err(|e| e.into()),
-				_ => unreachable!(),
-			})
-	}
+/// Finality notifications for NEAR, emitted for every new final block.
+#[derive(Clone, Debug)]
+pub enum FinalityEvent {
+	Near(BlockHeaderView),
 }
 
 #[async_trait::async_trait]
-impl IbcProvider for Client {
-	type IbcEvent = Result<Vec<IbcEvent>, String>;
-	type FinalityEvent = StreamerMessage;
+impl IbcProvider for NearClient {
+	type FinalityEvent = FinalityEvent;
+	type TransactionId = CryptoHash;
 	type Error = Error;
 
-	async fn client_update_header<C>(
+	async fn query_latest_ibc_events<C>(
 		&mut self,
-		_finality_event: Self::FinalityEvent,
-		_counterparty: &C,
-	) -> Result<(AnyHeader, AnyClientState, UpdateType), Self::Error>
+		finality_event: Self::FinalityEvent,
+		counterparty: &C,
+	) -> Result<(Vec<Any>, Vec<IbcEvent>, UpdateType), anyhow::Error>
 	where
 		C: Chain,
-		Self::Error: From<C::Error>,
 	{
-		unimplemented!()
-	}
+		let FinalityEvent::Near(block_header) = finality_event;
+		let client_state = self.query_client_state_on_counterparty(counterparty).await?;
+		let trusted_height = client_state.latest_height();
+		if block_header.height <= trusted_height.revision_height {
+			return Ok((vec![], vec![], UpdateType::Optional))
+		}
 
-	async fn query_latest_ibc_events(
-		&mut self,
-		_header: &AnyHeader,
-		_client_state: &AnyClientState,
-	) -> Result<Vec<IbcEvent>, Self::Error> {
-		unimplemented!()
+		let light_client_block = self
+			.next_light_client_block(NearClient::block_hash(client_state.get_head()))
+			.await?
+			.ok_or_else(|| {
+				Error::HeaderConstruction(format!(
+					"No light client block found after {}",
+					trusted_height
+				))
+			})?;
+		let target_height = light_client_block.get_height();
+		if target_height <= trusted_height {
+			return Ok((vec![], vec![], UpdateType::Optional))
+		}
+
+		log::info!(
+			"Fetching events from {} for blocks {}..{}",
+			self.name,
+			trusted_height.revision_height + 1,
+			target_height.revision_height,
+		);
+		let events = self
+			.query_ibc_events(trusted_height.revision_height + 1, target_height.revision_height)
+			.await?;
+
+		// The light client can't skip over epochs, blocks that carry the next epoch's block
+		// producers must be relayed.
+		let update_type = match light_client_block.next_bps.is_some() {
+			true => UpdateType::Mandatory,
+			false => UpdateType::Optional,
+		};
+
+		let update_header = {
+			let msg = MsgUpdateAnyClient::<LocalClientTypes> {
+				client_id: self.client_id(),
+				client_message: AnyClientMessage::Near(NearClientMessage::Header(NearHeader::new(
+					light_client_block,
+				))),
+				signer: counterparty.account_id(),
+			};
+			let value = msg.encode_vec()?;
+			Any { value, type_url: msg.type_url() }
+		};
+
+		Ok((vec![update_header], events, update_type))
 	}
 
-	async fn host_consensus_state(
-		&self,
-		_height: Height,
-	) -> Result<AnyConsensusState, Self::Error> {
-		unimplemented!()
+	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + 'static>> {
+		let (tx, rx) = tokio::sync::mpsc::channel(32);
+		let client = self.clone();
+		tokio::spawn(async move {
+			let mut last_height = None;
+			loop {
+				tokio::time::sleep(BLOCK_TIME).await;
+				let latest_height = match client.latest_final_block().await {
+					Ok(header) => header.height,
+					Err(err) => {
+						log::error!("Failed to fetch latest final block: {}", err);
+						continue
+					},
+				};
+				let from = last_height.map(|height: u64| height + 1).unwrap_or(latest_height);
+				if from > latest_height {
+					continue
+				}
+				let events = match client.query_ibc_events(from, latest_height).await {
+					Ok(events) => events,
+					Err(err) => {
+						log::error!("Failed to fetch ibc events: {}", err);
+						continue
+					},
+				};
+				last_height = Some(latest_height);
+				for event in events {
+					if tx.send(event).await.is_err() {
+						return
+					}
+				}
+			}
+		});
+
+		Box::pin(ReceiverStream::new(rx))
 	}
 
 	async fn query_client_consensus(
@@ -141,10 +170,9 @@ impl IbcProvider for Client {
 		client_id: ClientId,
 		consensus_height: Height,
 	) -> Result<QueryConsensusStateResponse, Self::Error> {
-		let args = (consensus_height, client_id, false);
-		let query =
-			self.make_contract_query_at(at.revision_height, "query_client_consensus_state", &args)?;
-		self.send_query(query).await
+		let args = (client_id, consensus_height);
+		self.query_contract(Some(at.revision_height), "query_client_consensus_state", &args)
+			.await
 	}
 
 	async fn query_client_state(
@@ -153,8 +181,7 @@ impl IbcProvider for Client {
 		client_id: ClientId,
 	) -> Result<QueryClientStateResponse, Self::Error> {
 		let args = (client_id,);
-		let query = self.make_contract_query_at(at.revision_height, "query_client_state", &args)?;
-		self.send_query(query).await
+		self.query_contract(Some(at.revision_height), "query_client_state", &args).await
 	}
 
 	async fn query_connection_end(
@@ -163,8 +190,7 @@ impl IbcProvider for Client {
 		connection_id: ConnectionId,
 	) -> Result<QueryConnectionResponse, Self::Error> {
 		let args = (connection_id,);
-		let query = self.make_contract_query_at(at.revision_height, "query_connection", &args)?;
-		self.send_query(query).await
+		self.query_contract(Some(at.revision_height), "query_connection", &args).await
 	}
 
 	async fn query_channel_end(
@@ -174,37 +200,34 @@ impl IbcProvider for Client {
 		port_id: PortId,
 	) -> Result<QueryChannelResponse, Self::Error> {
 		let args = (channel_id, port_id);
-		let query = self.make_contract_query_at(at.revision_height, "query_channel", &args)?;
-		self.send_query(query).await
+		self.query_contract(Some(at.revision_height), "query_channel", &args).await
 	}
 
-	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
-		let args = (keys,);
-		let query = self.make_contract_query_at(at.revision_height, "query_proof", &args)?;
-		self.send_query(query).await
+	async fn query_channel_upgrade(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeResponse, Self::Error> {
+		let args = (channel_id, port_id);
+		self.query_contract(Some(at.revision_height), "query_channel_upgrade", &args)
+			.await
 	}
 
-	async fn query_packets(
+	async fn query_channel_upgrade_error(
 		&self,
-		_at: Height,
-		port_id: &PortId,
-		channel_id: &ChannelId,
-		seqs: Vec<u64>,
-	) -> Result<Vec<Packet>, Self::Error> {
-		let args = (port_id, channel_id, &seqs);
-		let query = self.make_contract_query_at_final("query_packets", &args)?;
-		let packets: Vec<RawPacket> =
-			self.send_query(query).await.map_err(|e| Error::QueryPackets {
-				channel_id: channel_id.to_string(),
-				port_id: port_id.to_string(),
-				sequences: seqs,
-				err: e.to_string(),
-			})?;
-		let packets = packets
-			.into_iter()
-			.map(|raw_packet| raw_packet.try_into())
-			.collect::<Result<Vec<Packet>, _>>()?;
-		Ok(packets)
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeErrorResponse, Self::Error> {
+		let args = (channel_id, port_id);
+		self.query_contract(Some(at.revision_height), "query_channel_upgrade_error", &args)
+			.await
+	}
+
+	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
+		let args = (keys,);
+		self.query_contract(Some(at.revision_height), "query_proof", &args).await
 	}
 
 	async fn query_packet_commitment(
@@ -215,9 +238,8 @@ impl IbcProvider for Client {
 		seq: u64,
 	) -> Result<QueryPacketCommitmentResponse, Self::Error> {
 		let args = (port_id, channel_id, seq);
-		let query =
-			self.make_contract_query_at(at.revision_height, "query_packet_commitment", &args)?;
-		self.send_query(query).await
+		self.query_contract(Some(at.revision_height), "query_packet_commitment", &args)
+			.await
 	}
 
 	async fn query_packet_acknowledgement(
@@ -228,9 +250,8 @@ impl IbcProvider for Client {
 		seq: u64,
 	) -> Result<QueryPacketAcknowledgementResponse, Self::Error> {
 		let args = (port_id, channel_id, seq);
-		let query =
-			self.make_contract_query_at(at.revision_height, "query_packet_acknowledgement", &args)?;
-		self.send_query(query).await
+		self.query_contract(Some(at.revision_height), "query_packet_acknowledgement", &args)
+			.await
 	}
 
 	async fn query_next_sequence_recv(
@@ -240,9 +261,8 @@ impl IbcProvider for Client {
 		channel_id: &ChannelId,
 	) -> Result<QueryNextSequenceReceiveResponse, Self::Error> {
 		let args = (port_id, channel_id);
-		let query =
-			self.make_contract_query_at(at.revision_height, "query_next_seq_recv", &args)?;
-		self.send_query(query).await
+		self.query_contract(Some(at.revision_height), "query_next_seq_recv", &args)
+			.await
 	}
 
 	async fn query_packet_receipt(
@@ -253,44 +273,278 @@ impl IbcProvider for Client {
 		seq: u64,
 	) -> Result<QueryPacketReceiptResponse, Self::Error> {
 		let args = (port_id, channel_id, seq);
-		let query =
-			self.make_contract_query_at(at.revision_height, "query_packet_receipt", &args)?;
-		self.send_query(query).await
+		self.query_contract(Some(at.revision_height), "query_packet_receipt", &args)
+			.await
 	}
 
-	async fn latest_height_and_timestamp(&self) -> Result<Height, Self::Error> {
-		let finalized_block = self
-			.rpc_client
-			.call(RpcBlockRequest { block_reference: BlockReference::Finality(Finality::Final) })
-			.await?;
-		let validator_response = self
-			.rpc_client
-			.call(RpcValidatorRequest {
-				epoch_reference: EpochReference::BlockId(BlockId::Height(
-					finalized_block.header.height,
-				)),
-			})
-			.await?;
-		if validator_response.epoch_start_height > finalized_block.header.height {
-			return Err(Error::Custom(
-				"validator epoch height is greater than finalized header epoch height".to_string(),
-			))
-		}
-		let epoch = validator_response.epoch_height;
-		let latest_height = finalized_block.header.height;
-		Ok(Height::new(epoch, latest_height.into()))
+	async fn latest_height_and_timestamp(&self) -> Result<(Height, Timestamp), Self::Error> {
+		let header = self.latest_final_block().await?;
+		Ok((Height::new(0, header.height), Timestamp::from_nanoseconds(header.timestamp_nanosec)?))
+	}
+
+	async fn query_packet_commitments(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		let args = (channel_id, port_id);
+		self.query_contract(Some(at.revision_height), "query_packet_commitments", &args)
+			.await
+	}
+
+	async fn query_packet_acknowledgements(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<Vec<u64>, Self::Error> {
+		let args = (channel_id, port_id);
+		self.query_contract(Some(at.revision_height), "query_packet_acknowledgements", &args)
+			.await
+	}
+
+	async fn query_unreceived_packets(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		let args = (channel_id, port_id, seqs);
+		self.query_contract(Some(at.revision_height), "query_unreceived_packets", &args)
+			.await
+	}
+
+	async fn query_unreceived_acknowledgements(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<u64>, Self::Error> {
+		let args = (channel_id, port_id, seqs);
+		self.query_contract(Some(at.revision_height), "query_unreceived_acknowledgements", &args)
+			.await
+	}
+
+	fn channel_whitelist(&self) -> Vec<(ChannelId, PortId)> {
+		self.channel_whitelist.clone()
+	}
+
+	fn set_channel_whitelist(&mut self, channel_whitelist: Vec<(ChannelId, PortId)>) {
+		self.channel_whitelist = channel_whitelist;
+	}
+
+	async fn query_packet_fees(
+		&self,
+		_port_id: PortId,
+		_channel_id: ChannelId,
+		_sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error> {
+		// The IBC contract doesn't support the fee middleware, no fees are ever escrowed.
+		Ok(vec![])
+	}
+
+	fn min_relay_fees(&self) -> Vec<PrefixedCoin> {
+		self.min_relay_fees.clone()
+	}
+
+	async fn query_connection_channels(
+		&self,
+		at: Height,
+		connection_id: &ConnectionId,
+	) -> Result<QueryChannelsResponse, Self::Error> {
+		let args = (connection_id,);
+		self.query_contract(Some(at.revision_height), "query_connection_channels", &args)
+			.await
+	}
+
+	async fn query_send_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		let args = (channel_id, port_id, seqs);
+		self.query_contract(None, "query_send_packets", &args).await
+	}
+
+	async fn query_recv_packets(
+		&self,
+		channel_id: ChannelId,
+		port_id: PortId,
+		seqs: Vec<u64>,
+	) -> Result<Vec<PacketInfo>, Self::Error> {
+		let args = (channel_id, port_id, seqs);
+		self.query_contract(None, "query_recv_packets", &args).await
+	}
+
+	fn expected_block_time(&self) -> Duration {
+		BLOCK_TIME
+	}
+
+	async fn query_client_update_time_and_height(
+		&self,
+		client_id: ClientId,
+		client_height: Height,
+	) -> Result<(Height, Timestamp), Self::Error> {
+		let args = (client_id, client_height);
+		let (height, timestamp): (u64, u64) =
+			self.query_contract(None, "query_client_update_time_and_height", &args).await?;
+		Ok((Height::new(0, height), Timestamp::from_nanoseconds(timestamp)?))
+	}
+
+	async fn query_host_consensus_state_proof(
+		&self,
+		_height: Height,
+	) -> Result<Option<Vec<u8>>, Self::Error> {
+		// the IBC contract can introspect NEAR's own consensus states, no proof is needed.
+		Ok(None)
+	}
+
+	async fn query_ibc_balance(&self) -> Result<Vec<PrefixedCoin>, Self::Error> {
+		let args = (self.signer.account_id.to_string(),);
+		self.query_contract(None, "query_ibc_balance", &args).await
 	}
 
 	fn connection_prefix(&self) -> CommitmentPrefix {
-		CommitmentPrefix::try_from(self.commitment_prefix.clone()).expect("Should not fail")
+		self.commitment_prefix.clone()
 	}
 
 	fn client_id(&self) -> ClientId {
 		self.client_id()
 	}
 
-	#[cfg(feature = "testing")]
-	async fn ibc_events(&self) -> Pin<Box<dyn Stream<Item = IbcEvent> + Send + Sync>> {
-		todo!()
+	fn connection_id(&self) -> ConnectionId {
+		self.connection_id.as_ref().expect("Connection id should be defined").clone()
+	}
+
+	fn client_type(&self) -> ClientType {
+		NearClientState::<HostFunctionsManager>::client_type()
+	}
+
+	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
+		Ok(self.block_at(block_number).await?.timestamp_nanosec)
+	}
+
+	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
+		self.query_contract(None, "query_clients", &()).await
+	}
+
+	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
+		self.query_contract(None, "query_channels", &()).await
+	}
+
+	async fn query_connection_using_client(
+		&self,
+		height: u32,
+		client_id: String,
+	) -> Result<Vec<IdentifiedConnection>, Self::Error> {
+		let args = (client_id,);
+		self.query_contract(Some(height as u64), "query_connection_using_client", &args)
+			.await
+	}
+
+	async fn is_update_required(
+		&self,
+		latest_height: u64,
+		latest_client_height_on_counterparty: u64,
+	) -> Result<bool, Self::Error> {
+		// the light client only knows the block producers of the current and the next epoch, it
+		// must be updated before the chain moves on to the epoch after.
+		let latest = self.block_at(latest_height).await?;
+		let client_head = self.block_at(latest_client_height_on_counterparty).await?;
+		Ok(latest.epoch_id != client_head.epoch_id && latest.epoch_id != client_head.next_epoch_id)
+	}
+
+	async fn initialize_client_state(
+		&self,
+	) -> Result<(AnyClientState, AnyConsensusState), Self::Error> {
+		let header = self.latest_final_block().await?;
+		let head = self
+			.next_light_client_block(ics13_near::types::CryptoHash(header.prev_hash.0))
+			.await?
+			.ok_or_else(|| Error::Custom("No light client block available".to_string()))?;
+
+		let current_validators = self.epoch_validators(head.inner_lite.epoch_id).await?;
+		let next_validators = match head.next_bps.clone() {
+			Some(next_bps) => next_bps,
+			None => self.epoch_validators(head.inner_lite.next_epoch_id).await?,
+		};
+		let consensus_state = ConsensusState::from_light_client_block(&head)
+			.map_err(|e| Error::Custom(format!("Failed to construct consensus state: {e}")))?;
+		let client_state = NearClientState::<HostFunctionsManager>::new(
+			self.chain_id.clone(),
+			head,
+			current_validators,
+			next_validators,
+		);
+
+		Ok((AnyClientState::Near(client_state), AnyConsensusState::Near(consensus_state)))
+	}
+
+	async fn query_client_upgrade_messages<C>(
+		&self,
+		_counterparty: &C,
+	) -> Result<Vec<Any>, anyhow::Error>
+	where
+		C: Chain,
+	{
+		// Near protocol upgrades don't require the light client to be upgraded.
+		Ok(vec![])
+	}
+
+	async fn query_client_id_from_tx_hash(
+		&self,
+		tx_id: Self::TransactionId,
+	) -> Result<ClientId, Self::Error> {
+		let outcome = self
+			.rpc_client
+			.call(RpcTransactionStatusRequest {
+				transaction_info: TransactionInfo::TransactionId {
+					hash: tx_id,
+					account_id: self.signer.account_id.clone(),
+				},
+			})
+			.await?;
+		outcome
+			.receipts_outcome
+			.iter()
+			.flat_map(|receipt| receipt.outcome.logs.iter())
+			.find_map(|log| match serde_json::from_str::<IbcEvent>(log) {
+				Ok(IbcEvent::CreateClient(create_client)) =>
+					Some(create_client.client_id().clone()),
+				_ => None,
+			})
+			.ok_or_else(|| {
+				Error::Custom(format!("No CreateClient event found in transaction {}", tx_id))
+			})
+	}
+}
+
+impl NearClient {
+	/// Fetch the ibc events emitted by the IBC contract in the given (inclusive) range of blocks.
+	pub async fn query_ibc_events(&self, from: u64, to: u64) -> Result<Vec<IbcEvent>, Error> {
+		self.query_contract(Some(to), "query_events", &(from, to)).await
+	}
+
+	/// Query NEAR's light client state on the counterparty chain.
+	pub async fn query_client_state_on_counterparty<C: Chain>(
+		&self,
+		counterparty: &C,
+	) -> Result<NearClientState<HostFunctionsManager>, anyhow::Error> {
+		let latest_height = counterparty.latest_height_and_timestamp().await?.0;
+		let response = counterparty.query_client_state(latest_height, self.client_id()).await?;
+		let client_state = response.client_state.ok_or_else(|| {
+			Error::Custom("Received an empty client state from counterparty".to_string())
+		})?;
+
+		let client_state = AnyClientState::try_from(client_state)
+			.map_err(|_| Error::Custom("Failed to decode client state".to_string()))?;
+		match client_state {
+			AnyClientState::Near(client_state) => Ok(client_state),
+			c => Err(Error::Custom(format!("Expected AnyClientState::Near found: {:?}", c)))?,
+		}
 	}
 }
@@ -0,0 +1,64 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, NearClient, BLOCK_TIME};
+use futures::Stream;
+use ibc::{
+	applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin},
+	core::ics24_host::identifier::ChannelId,
+	tx_msg::Msg,
+};
+use primitives::{Chain, TestProvider};
+use std::pin::Pin;
+use tokio_stream::wrappers::ReceiverStream;
+
+#[async_trait::async_trait]
+impl TestProvider for NearClient {
+	async fn send_transfer(&self, transfer: MsgTransfer<PrefixedCoin>) -> Result<(), Self::Error> {
+		self.submit(vec![transfer.to_any()]).await?;
+		Ok(())
+	}
+
+	async fn send_ordered_packet(
+		&self,
+		_channel_id: ChannelId,
+		_timeout: pallet_ibc::Timeout,
+	) -> Result<(), Self::Error> {
+		Err(Error::Custom("Sending ordered packets is not supported on NEAR".to_string()))
+	}
+
+	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
+		let (tx, rx) = tokio::sync::mpsc::channel(32);
+		let client = self.clone();
+		tokio::spawn(async move {
+			let mut last_height = 0;
+			loop {
+				tokio::time::sleep(BLOCK_TIME).await;
+				let height = match client.latest_final_block().await {
+					Ok(header) => header.height,
+					Err(_) => continue,
+				};
+				if height <= last_height {
+					continue
+				}
+				last_height = height;
+				if tx.send(height).await.is_err() {
+					break
+				}
+			}
+		});
+
+		Box::pin(ReceiverStream::new(rx))
+	}
+}
@@ -22,7 +22,7 @@ use futures::{Stream, StreamExt, TryFutureExt};
 use grandpa_light_client_primitives::{FinalityProof, ParachainHeaderProofs};
 use ibc_proto::google::protobuf::Any;
 use sp_runtime::{
-	traits::{IdentifyAccount, One, Verify},
+	traits::{AtLeast32Bit, IdentifyAccount, One, Verify},
 	MultiSignature, MultiSigner,
 };
 use subxt::config::{extrinsic_params::BaseExtrinsicParamsBuilder, ExtrinsicParams};
@@ -40,7 +40,7 @@ type RuntimeDispatchInfo = transaction_payment_runtime_api::RuntimeDispatchInfo<
 
 use transaction_payment_rpc::TransactionPaymentApiClient;
 
-use primitives::{Chain, IbcProvider, MisbehaviourHandler};
+use primitives::{store::TransactionStatus, Chain, IbcProvider, MisbehaviourHandler};
 
 use super::{error::Error, signer::ExtrinsicSigner, ParachainClient};
 use crate::{
@@ -54,6 +54,7 @@ use crate::{
 	utils::MetadataIbcEventWrapper,
 	FinalityProtocol,
 };
+use beefy_light_client_primitives::SignedCommitmentWithProof;
 use finality_grandpa_rpc::GrandpaApiClient;
 use ibc::{
 	core::{
@@ -67,6 +68,10 @@ use ibc::{
 	tx_msg::Msg,
 };
 use ics10_grandpa::client_message::{ClientMessage, Misbehaviour, RelayChainHeader};
+use ics11_beefy::{
+	client_message::ClientMessage as BeefyClientMessage,
+	misbehaviour::Misbehaviour as BeefyMisbehaviour,
+};
 use pallet_ibc::light_clients::AnyClientMessage;
 use primitives::mock::LocalClientTypes;
 use sp_core::{twox_128, H256};
@@ -101,6 +106,7 @@ where
 	sp_core::H256: From<T::Hash>,
 	<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
 		From<BaseExtrinsicParamsBuilder<T, Tip>> + Send + Sync,
+	T::Index: AtLeast32Bit,
 	<T as subxt::Config>::AccountId: Send + Sync,
 	<T as subxt::Config>::Address: Send + Sync,
 {
@@ -128,9 +134,9 @@ where
 
 			let tx_params = BaseExtrinsicParamsBuilder::new()
 				.tip(Tip::new(100_000))
-				.era(Era::Immortal, self.para_client.genesis_hash());
+				.era(Era::Immortal, self.para_client().genesis_hash());
 			let call = api::tx().ibc().deliver(messages);
-			self.para_client
+			self.para_client()
 				.tx()
 				.create_signed(&call, &signer, tx_params.into())
 				.await?
@@ -139,7 +145,7 @@ where
 		};
 		let dispatch_info =
 			TransactionPaymentApiClient::<sp_core::H256, RuntimeDispatchInfo>::query_info(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				extrinsic.into(),
 				None,
 			)
@@ -162,14 +168,22 @@ where
 	) -> Pin<Box<dyn Stream<Item = <Self as IbcProvider>::FinalityEvent> + Send + Sync>> {
 		match self.finality_protocol {
 			FinalityProtocol::Grandpa => {
+				let subscription = match GrandpaApiClient::<
+					JustificationNotification,
+					sp_core::H256,
+					u32,
+				>::subscribe_justifications(&*self.relay_ws_client())
+				.await
+				{
+					Ok(subscription) => subscription,
+					Err(err) => {
+						// the relayer re-subscribes once the stream ends
+						log::error!("Failed to subscribe to grandpa justifications: {:?}", err);
+						return Box::pin(futures::stream::empty())
+					},
+				};
 				let subscription =
-					GrandpaApiClient::<JustificationNotification, sp_core::H256, u32>::subscribe_justifications(
-						&*self.relay_ws_client,
-					)
-						.await
-						.expect("Failed to subscribe to grandpa justifications")
-						.chunks(3)
-						.map(|mut notifs| notifs.remove(notifs.len() - 1)); // skip every 3 finality notifications
+					subscription.chunks(3).map(|mut notifs| notifs.remove(notifs.len() - 1)); // skip every 3 finality notifications
 
 				let stream = subscription.filter_map(|justification_notif| {
 					let encoded_justification = match justification_notif {
@@ -196,11 +210,18 @@ where
 			},
 			FinalityProtocol::Beefy => {
 				let subscription =
-					BeefyApiClient::<JustificationNotification, sp_core::H256>::subscribe_justifications(
-						&*self.relay_ws_client,
+					match BeefyApiClient::<JustificationNotification, sp_core::H256>::subscribe_justifications(
+						&*self.relay_ws_client(),
 					)
-						.await
-						.expect("Failed to subscribe to beefy justifications");
+					.await
+					{
+						Ok(subscription) => subscription,
+						Err(err) => {
+							// the relayer re-subscribes once the stream ends
+							log::error!("Failed to subscribe to beefy justifications: {:?}", err);
+							return Box::pin(futures::stream::empty())
+						},
+					};
 
 				let stream = subscription.filter_map(|commitment_notification| {
 					let encoded_commitment = match commitment_notification {
@@ -239,6 +260,22 @@ where
 		Ok(TransactionId { ext_hash, block_hash })
 	}
 
+	async fn broadcast(&self, messages: Vec<Any>) -> Result<String, Error> {
+		// extrinsics are watched until they're included, there's no way to resume watching one
+		// from its hash.
+		let TransactionId { ext_hash, .. } = self.submit(messages).await?;
+		Ok(hex::encode(ext_hash.encode()))
+	}
+
+	async fn wait_for_transaction(&self, _tx_hash: &str) -> Result<(), Error> {
+		Ok(())
+	}
+
+	async fn query_transaction_status(&self, _tx_hash: &str) -> Result<TransactionStatus, Error> {
+		// looking up an extrinsic by its hash requires an indexer.
+		Ok(TransactionStatus::Unknown)
+	}
+
 	async fn query_client_message(&self, update: UpdateClient) -> Result<AnyClientMessage, Error> {
 		use api::runtime_types::{
 			frame_system::EventRecord,
@@ -256,7 +293,7 @@ where
 		let now = std::time::Instant::now();
 		let block_hash = loop {
 			let maybe_hash = self
-				.para_client
+				.para_client()
 				.rpc()
 				.block_hash(Some(host_height.revision_height.into()))
 				.await?;
@@ -275,7 +312,7 @@ where
 		storage_key.extend(twox_128(b"Events").to_vec());
 
 		let event_bytes = self
-			.para_client
+			.para_client()
 			.rpc()
 			.storage(&*storage_key, Some(block_hash))
 			.await?
@@ -314,7 +351,7 @@ where
 			.ok_or_else(|| Error::from("No update client event found".to_owned()))?;
 
 		let block = self
-			.para_client
+			.para_client()
 			.rpc()
 			.block(Some(block_hash.into()))
 			.await?
@@ -348,6 +385,10 @@ where
 
 		Err(Error::from("No client message found".to_owned()))
 	}
+
+	async fn reconnect(&mut self) -> Result<(), Error> {
+		self.reconnect_rpc_clients().await
+	}
 }
 
 #[async_trait::async_trait]
@@ -385,7 +426,7 @@ where
 					.expect("unknown_headers always contain at least one header; qed");
 
 				let common_ancestor_header = self
-					.relay_client
+					.relay_client()
 					.rpc()
 					.header(Some(base_header.parent_hash.into()))
 					.await?
@@ -396,7 +437,7 @@ where
 				let common_ancestor_block_number = u32::from(common_ancestor_header.number());
 				let encoded =
 					GrandpaApiClient::<JustificationNotification, H256, u32>::prove_finality(
-						&*self.relay_ws_client,
+						&*self.relay_ws_client(),
 						common_ancestor_block_number + 1,
 					)
 					.await?
@@ -416,7 +457,7 @@ where
 				let from_block = (common_ancestor_block_number + 1).min(to_block);
 
 				let trusted_base_header_hash = self
-					.relay_client
+					.relay_client()
 					.rpc()
 					.block_hash(Some(from_block.into()))
 					.await?
@@ -436,17 +477,19 @@ where
 
 					trusted_finality_proof.unknown_headers.clear();
 					for i in from_block..=to_block {
-						let unknown_header_hash =
-							self.relay_client.rpc().block_hash(Some(i.into())).await?.ok_or_else(
-								|| {
-									anyhow!(
-										"No block hash found for block number: {:?}",
-										common_ancestor_block_number
-									)
-								},
-							)?;
+						let unknown_header_hash = self
+							.relay_client()
+							.rpc()
+							.block_hash(Some(i.into()))
+							.await?
+							.ok_or_else(|| {
+								anyhow!(
+									"No block hash found for block number: {:?}",
+									common_ancestor_block_number
+								)
+							})?;
 						let unknown_header = self
-							.relay_client
+							.relay_client()
 							.rpc()
 							.header(Some(unknown_header_hash))
 							.await?
@@ -476,6 +519,61 @@ where
 						.await?;
 				}
 			},
+			AnyClientMessage::Beefy(BeefyClientMessage::Header(header)) => {
+				let mmr_update = match header.mmr_update_proof {
+					Some(mmr_update) => mmr_update,
+					None => return Ok(()),
+				};
+				let first_signed_commitment = SignedCommitmentWithProof {
+					signed_commitment: mmr_update.signed_commitment,
+					authority_proof: mmr_update.authority_proof,
+				};
+				let commitment = &first_signed_commitment.signed_commitment.commitment;
+				// BEEFY justifications are only guaranteed to be stored for blocks that end a
+				// session, other commitments can't be checked against the relay chain.
+				let trusted_signed_commitment = match self
+					.query_beefy_signed_commitment_with_proof(commitment.block_number)
+					.await?
+				{
+					Some(signed_commitment) => signed_commitment,
+					None => return Ok(()),
+				};
+				let trusted_commitment = &trusted_signed_commitment.signed_commitment.commitment;
+
+				if commitment != trusted_commitment {
+					log::warn!(
+						"Found misbehaviour on client {}: {:?} != {:?}",
+						self.client_id
+							.as_ref()
+							.map(|x| x.as_str().to_owned())
+							.unwrap_or_else(|| "{unknown}".to_owned()),
+						commitment,
+						trusted_commitment
+					);
+					if commitment.validator_set_id != trusted_commitment.validator_set_id {
+						log::warn!(
+							"Conflicting commitments were signed by different authority sets, misbehaviour can't be proven"
+						);
+						return Ok(())
+					}
+
+					let misbehaviour = BeefyClientMessage::Misbehaviour(BeefyMisbehaviour {
+						first_signed_commitment,
+						second_signed_commitment: trusted_signed_commitment,
+					});
+
+					counterparty
+						.submit(vec![MsgUpdateAnyClient::<LocalClientTypes>::new(
+							self.client_id(),
+							AnyClientMessage::Beefy(misbehaviour),
+							counterparty.account_id(),
+						)
+						.to_any()])
+						.map_err(|e| anyhow!("Failed to submit misbehaviour report: {:?}", e))
+						.await?;
+				}
+			},
+			// headers of other chains are checked by their own `MisbehaviourHandler`
 			_ => {},
 		}
 		Ok(())
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use ibc::{core::ics02_client, timestamp::ParseTimestampError};
+use primitives::error::ErrorKind;
 use sp_runtime::traits::BlakeTwo256;
 use sp_trie::TrieError;
 use std::num::ParseIntError;
@@ -49,6 +50,12 @@ pub enum Error {
 	/// Custom error
 	#[error("{0}")]
 	Custom(String),
+	/// The extrinsic was included but its dispatch failed
+	#[error("Extrinsic failed: {0}")]
+	ExtrinsicFailed(String),
+	/// Every ibc message in the extrinsic failed
+	#[error("Ibc messages failed: {0:?}")]
+	IbcMessagesFailed(Vec<String>),
 	#[error("Ibc channel error")]
 	IbcChannel(#[from] ibc::core::ics04_channel::error::Error),
 	/// Error querying packets
@@ -82,6 +89,16 @@ pub enum Error {
 	JosnrpseeError(#[from] jsonrpsee::core::Error),
 }
 
+impl Error {
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Self::PalletNotFound(_) | Self::CallNotFound(_) => ErrorKind::Fatal,
+			Self::ExtrinsicFailed(_) | Self::IbcMessagesFailed(_) => ErrorKind::Rejected,
+			_ => ErrorKind::Retryable,
+		}
+	}
+}
+
 impl From<String> for Error {
 	fn from(error: String) -> Self {
 		Self::Custom(error)
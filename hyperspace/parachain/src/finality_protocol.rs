@@ -69,7 +69,7 @@ pub enum FinalityProtocol {
 }
 
 /// Finality event for parachains
-#[derive(Decode, Encode)]
+#[derive(Clone, Decode, Encode)]
 pub enum FinalityEvent {
 	Grandpa(
 		grandpa_light_client_primitives::justification::GrandpaJustification<
@@ -170,14 +170,14 @@ where
 
 	if signed_commitment.commitment.validator_set_id < beefy_client_state.current_authorities.id {
 		log::info!(
-			"Commitment: {:#?}\nClientState: {:#?}",
+			"Commitment: {:?}, ClientState: {:?}",
 			signed_commitment.commitment,
 			beefy_client_state
 		);
 		// If validator set id of signed commitment is less than current validator set
 		// id we have Then commitment is outdated and we skip it.
 		log::warn!(
-				"Skipping outdated commitment \n Received signed commitmment with validator_set_id: {:?}\n Current authority set id: {:?}\n Next authority set id: {:?}\n",
+				"Skipping outdated commitment, received signed commitment with validator_set_id: {:?}, current authority set id: {:?}, next authority set id: {:?}",
 				signed_commitment.commitment.validator_set_id, beefy_client_state.current_authorities.id, beefy_client_state.next_authorities.id
 			);
 		Err(Error::HeaderConstruction("Received an outdated beefy commitment".to_string()))?
@@ -250,7 +250,7 @@ where
 	// block_number => events
 	let events: HashMap<String, Vec<IbcEvent>> =
 		IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_events(
-			&*source.para_ws_client,
+			&*source.para_ws_client(),
 			finalized_block_numbers,
 		)
 		.await?;
@@ -437,7 +437,7 @@ where
 	// block_number => events
 	let events: HashMap<String, Vec<IbcEvent>> =
 		IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_events(
-			&*source.para_ws_client,
+			&*source.para_ws_client(),
 			finalized_block_numbers,
 		)
 		.await?;
@@ -489,7 +489,7 @@ where
 		.await?;
 
 	let target = source
-		.relay_client
+		.relay_client()
 		.rpc()
 		.header(Some(finality_proof.block.into()))
 		.await?
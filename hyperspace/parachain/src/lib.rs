@@ -14,7 +14,12 @@
 
 #![allow(clippy::all)]
 
-use std::{collections::BTreeMap, str::FromStr, sync::Arc, time::Duration};
+use std::{
+	collections::BTreeMap,
+	str::FromStr,
+	sync::{Arc, PoisonError, RwLock},
+	time::Duration,
+};
 
 pub mod chain;
 pub mod config;
@@ -35,9 +40,12 @@ use error::Error;
 use frame_support::Serialize;
 use serde::Deserialize;
 
-use beefy_light_client_primitives::{ClientState, MmrUpdateProof};
+use beefy_light_client_primitives::{ClientState, MmrUpdateProof, SignedCommitmentWithProof};
 use beefy_prover::Prover;
-use ibc::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+use ibc::{
+	applications::transfer::PrefixedCoin,
+	core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+};
 use ics11_beefy::client_message::ParachainHeader;
 use pallet_mmr_primitives::Proof;
 use sp_core::{ecdsa, ed25519, sr25519, Bytes, Pair, H256};
@@ -59,7 +67,7 @@ use ics11_beefy::{
 	client_state::ClientState as BeefyClientState,
 	consensus_state::ConsensusState as BeefyConsensusState,
 };
-use primitives::KeyProvider;
+use primitives::{keystore, KeyProvider};
 
 use crate::{finality_protocol::FinalityProtocol, signer::ExtrinsicSigner};
 use grandpa_light_client_primitives::ParachainHeaderProofs;
@@ -69,7 +77,7 @@ use ics10_grandpa::client_state::ClientState as GrandpaClientState;
 use jsonrpsee_ws_client::WsClientBuilder;
 use pallet_ibc::light_clients::{AnyClientState, AnyConsensusState, HostFunctionsManager};
 use sp_keystore::testing::KeyStore;
-use sp_runtime::traits::One;
+use sp_runtime::traits::{AtLeast32Bit, One};
 use subxt::tx::TxPayload;
 
 /// Implements the [`crate::Chain`] trait for parachains.
@@ -81,14 +89,13 @@ use subxt::tx::TxPayload;
 pub struct ParachainClient<T: config::Config> {
 	/// Chain name
 	pub name: String,
-	/// Relay chain rpc client
-	pub relay_client: subxt::OnlineClient<T>,
-	/// Parachain rpc client
-	pub para_client: subxt::OnlineClient<T>,
-	/// Relay chain ws client
-	pub relay_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
-	/// Parachain ws client
-	pub para_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
+	/// Rpc clients of the relay chain and parachain, shared with every clone of this client so
+	/// that a reconnection is picked up by all of them.
+	pub rpc_clients: Arc<RwLock<RpcClients<T>>>,
+	/// rpc url for relay chain, used to reconnect
+	pub relay_chain_rpc_url: String,
+	/// rpc url for parachain, used to reconnect
+	pub parachain_rpc_url: String,
 	/// Parachain Id
 	pub para_id: u32,
 	/// Light client id on counterparty chain
@@ -109,8 +116,25 @@ pub struct ParachainClient<T: config::Config> {
 	pub max_extrinsic_weight: u64,
 	/// Channels cleared for packet relay
 	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+	/// Minimum fees that must be escrowed for a packet sent by this chain before it's relayed
+	pub min_relay_fees: Vec<PrefixedCoin>,
 	/// Finality protocol to use, eg Beefy, Grandpa
 	pub finality_protocol: FinalityProtocol,
+	/// Next nonce of the relayer account, tracked locally so that several extrinsics can be
+	/// in the transaction pool at once. `None` means it should be fetched from the chain.
+	pub nonce: Arc<tokio::sync::Mutex<Option<T::Index>>>,
+}
+
+/// Connections to the relay chain and parachain rpc endpoints.
+pub struct RpcClients<T: config::Config> {
+	/// Relay chain rpc client
+	pub relay_client: subxt::OnlineClient<T>,
+	/// Parachain rpc client
+	pub para_client: subxt::OnlineClient<T>,
+	/// Relay chain ws client
+	pub relay_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
+	/// Parachain ws client
+	pub para_ws_client: Arc<jsonrpsee_ws_client::WsClient>,
 }
 
 enum KeyType {
@@ -159,12 +183,24 @@ pub struct ParachainClientConfig {
 	pub connection_id: Option<ConnectionId>,
 	/// Commitment prefix
 	pub commitment_prefix: Bytes,
-	/// Raw private key for signing transactions
-	pub private_key: String,
+	/// Raw private key for signing transactions, prefer `key_name` outside of development
+	#[serde(default)]
+	pub private_key: Option<String>,
+	/// Name of the key in the hyperspace keystore used for signing transactions
+	#[serde(default)]
+	pub key_name: Option<String>,
+	/// Directory of the keystore `key_name` is read from, as passed to `hyperspace keys
+	/// --keystore`. Defaults to `$HYPERSPACE_KEYSTORE` or `~/.hyperspace/keys`.
+	#[serde(default)]
+	pub keystore_path: Option<String>,
 	/// used for encoding relayer address.
 	pub ss58_version: u8,
 	/// Channels cleared for packet relay
 	pub channel_whitelist: Vec<(ChannelId, PortId)>,
+	/// Minimum fees that must be escrowed for a packet sent by this chain before it's relayed,
+	/// packets are relayed regardless of their fees if empty
+	#[serde(default)]
+	pub min_relay_fees: Vec<PrefixedCoin>,
 	/// Finality protocol
 	pub finality_protocol: FinalityProtocol,
 	/// Digital signature scheme
@@ -177,47 +213,35 @@ where
 {
 	/// Initializes a [`ParachainClient`] given a [`ParachainConfig`]
 	pub async fn new(config: ParachainClientConfig) -> Result<Self, Error> {
-		let relay_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&config.relay_chain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
-		let para_ws_client = Arc::new(
-			WsClientBuilder::default()
-				.build(&config.parachain_rpc_url)
-				.await
-				.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
-		);
-
-		let para_client = subxt::OnlineClient::from_rpc_client(unsafe {
-			unsafe_cast_to_jsonrpsee_client(&para_ws_client)
-		})
-		.await?;
-
-		let relay_client = subxt::OnlineClient::from_rpc_client(unsafe {
-			unsafe_cast_to_jsonrpsee_client(&relay_ws_client)
-		})
-		.await?;
+		let (relay_ws_client, relay_client) = connect(&config.relay_chain_rpc_url).await?;
+		let (para_ws_client, para_client) = connect(&config.parachain_rpc_url).await?;
 
 		let max_extrinsic_weight = fetch_max_extrinsic_weight(&para_client).await?;
 
 		let key_store: SyncCryptoStorePtr = Arc::new(KeyStore::new());
 		let key_type = KeyType::from_str(&config.key_type)?;
 		let key_type_id = key_type.to_key_type_id();
+		let private_key = keystore::resolve_secret(
+			config.keystore_path.as_deref(),
+			config.key_name.as_deref(),
+			config.private_key.as_deref(),
+			keystore::KeyType::from_str(&config.key_type)
+				.map_err(|e| Error::Custom(e.to_string()))?,
+		)
+		.map_err(|e| Error::Custom(e.to_string()))?;
 
 		let public_key: MultiSigner = match key_type {
-			KeyType::Sr25519 => sr25519::Pair::from_string_with_seed(&config.private_key, None)
+			KeyType::Sr25519 => sr25519::Pair::from_string_with_seed(&private_key, None)
 				.map_err(|_| Error::Custom("invalid key".to_owned()))?
 				.0
 				.public()
 				.into(),
-			KeyType::Ed25519 => ed25519::Pair::from_string_with_seed(&config.private_key, None)
+			KeyType::Ed25519 => ed25519::Pair::from_string_with_seed(&private_key, None)
 				.map_err(|_| Error::Custom("invalid key".to_owned()))?
 				.0
 				.public()
 				.into(),
-			KeyType::Ecdsa => ecdsa::Pair::from_string_with_seed(&config.private_key, None)
+			KeyType::Ecdsa => ecdsa::Pair::from_string_with_seed(&private_key, None)
 				.map_err(|_| Error::Custom("invalid key".to_owned()))?
 				.0
 				.public()
@@ -227,15 +251,19 @@ where
 		SyncCryptoStore::insert_unknown(
 			&*key_store,
 			key_type_id,
-			&*config.private_key,
+			&private_key,
 			public_key.as_ref(),
 		)
 		.unwrap();
 
 		Ok(Self {
 			name: config.name,
-			para_client,
-			relay_client,
+			rpc_clients: Arc::new(RwLock::new(RpcClients {
+				relay_client,
+				para_client,
+				relay_ws_client,
+				para_ws_client,
+			})),
 			para_id: config.para_id,
 			client_id: config.client_id,
 			commitment_prefix: config.commitment_prefix.0,
@@ -244,13 +272,80 @@ where
 			key_store,
 			key_type_id,
 			max_extrinsic_weight,
-			para_ws_client,
-			relay_ws_client,
+			relay_chain_rpc_url: config.relay_chain_rpc_url,
+			parachain_rpc_url: config.parachain_rpc_url,
 			ss58_version: Ss58AddressFormat::from(config.ss58_version),
 			channel_whitelist: config.channel_whitelist,
+			min_relay_fees: config.min_relay_fees,
 			finality_protocol: config.finality_protocol,
+			nonce: Arc::new(tokio::sync::Mutex::new(None)),
 		})
 	}
+
+	/// Replaces the rpc clients of the relay chain and parachain with new connections, e.g. after
+	/// a websocket connection dropped. The new connections are used by every clone of this client.
+	pub async fn reconnect_rpc_clients(&self) -> Result<(), Error> {
+		let (relay_ws_client, relay_client) = connect(&self.relay_chain_rpc_url).await?;
+		let (para_ws_client, para_client) = connect(&self.parachain_rpc_url).await?;
+		*self.rpc_clients.write().unwrap_or_else(PoisonError::into_inner) =
+			RpcClients { relay_client, para_client, relay_ws_client, para_ws_client };
+		// extrinsics in flight may have been dropped with the connection
+		*self.nonce.lock().await = None;
+		Ok(())
+	}
+
+	/// Returns the relay chain rpc client.
+	pub fn relay_client(&self) -> subxt::OnlineClient<T> {
+		self.rpc_clients
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.relay_client
+			.clone()
+	}
+
+	/// Returns the parachain rpc client.
+	pub fn para_client(&self) -> subxt::OnlineClient<T> {
+		self.rpc_clients
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.para_client
+			.clone()
+	}
+
+	/// Returns the relay chain ws client.
+	pub fn relay_ws_client(&self) -> Arc<jsonrpsee_ws_client::WsClient> {
+		self.rpc_clients
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.relay_ws_client
+			.clone()
+	}
+
+	/// Returns the parachain ws client.
+	pub fn para_ws_client(&self) -> Arc<jsonrpsee_ws_client::WsClient> {
+		self.rpc_clients
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.para_ws_client
+			.clone()
+	}
+}
+
+/// Opens a websocket connection to the rpc url, returns it along with a subxt client using it.
+async fn connect<T: config::Config>(
+	url: &str,
+) -> Result<(Arc<jsonrpsee_ws_client::WsClient>, subxt::OnlineClient<T>), Error> {
+	let ws_client = Arc::new(
+		WsClientBuilder::default()
+			.build(url)
+			.await
+			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?,
+	);
+	let client = subxt::OnlineClient::from_rpc_client(unsafe {
+		unsafe_cast_to_jsonrpsee_client(&ws_client)
+	})
+	.await?;
+	Ok((ws_client, client))
 }
 
 impl<T: config::Config + Send + Sync> ParachainClient<T>
@@ -269,12 +364,12 @@ where
 {
 	/// Returns a grandpa proving client.
 	pub fn grandpa_prover(&self) -> GrandpaProver<T> {
-		let relay_ws_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.relay_ws_client) };
-		let para_ws_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.para_ws_client) };
+		let relay_ws_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.relay_ws_client()) };
+		let para_ws_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.para_ws_client()) };
 		GrandpaProver {
-			relay_client: self.relay_client.clone(),
+			relay_client: self.relay_client(),
 			relay_ws_client,
-			para_client: self.para_client.clone(),
+			para_client: self.para_client(),
 			para_ws_client,
 			para_id: self.para_id,
 		}
@@ -292,8 +387,8 @@ where
 		T::BlockNumber: From<u32>,
 	{
 		let client_wrapper = Prover {
-			relay_client: self.relay_client.clone(),
-			para_client: self.para_client.clone(),
+			relay_client: self.relay_client(),
+			para_client: self.para_client(),
 			para_id: self.para_id,
 		};
 
@@ -322,8 +417,8 @@ where
 		T::BlockNumber: Ord + sp_runtime::traits::Zero,
 	{
 		let client_wrapper = Prover {
-			relay_client: self.relay_client.clone(),
-			para_client: self.para_client.clone(),
+			relay_client: self.relay_client(),
+			para_client: self.para_client(),
 			para_id: self.para_id,
 		};
 
@@ -365,8 +460,8 @@ where
 		>,
 	) -> Result<MmrUpdateProof, Error> {
 		let prover = Prover {
-			relay_client: self.relay_client.clone(),
-			para_client: self.para_client.clone(),
+			relay_client: self.relay_client(),
+			para_client: self.para_client(),
 			para_id: self.para_id,
 		};
 
@@ -377,11 +472,52 @@ where
 		Ok(mmr_update)
 	}
 
-	/// Submits the given transaction to the parachain node, waits for it to be included in a block
-	/// and asserts that it was successfully dispatched on-chain.
+	/// Queries the signed commitment in the BEEFY justification of the given relay chain block
+	/// along with the proof of its signatories, if the block has one.
+	pub async fn query_beefy_signed_commitment_with_proof(
+		&self,
+		block_number: u32,
+	) -> Result<Option<SignedCommitmentWithProof>, Error> {
+		let prover = Prover {
+			relay_client: self.relay_client(),
+			para_client: self.para_client(),
+			para_id: self.para_id,
+		};
+
+		let signed_commitment =
+			prover.fetch_signed_commitment_with_proof(block_number).await.map_err(|e| {
+				Error::from(format!("[fetch_signed_commitment_with_proof] Failed due to {:?}", e))
+			})?;
+		Ok(signed_commitment)
+	}
+
+	pub fn client_id(&self) -> ClientId {
+		self.client_id.as_ref().expect("Client Id should be defined").clone()
+	}
+}
+
+impl<T: config::Config + Send + Sync> ParachainClient<T>
+where
+	u32: From<<<T as subxt::Config>::Header as HeaderT>::Number>,
+	Self: KeyProvider,
+	<<T as config::Config>::Signature as Verify>::Signer:
+		From<MultiSigner> + IdentifyAccount<AccountId = T::AccountId>,
+	MultiSigner: From<MultiSigner>,
+	<T as subxt::Config>::Address: From<<T as subxt::Config>::AccountId>,
+	<T as subxt::Config>::Signature: From<MultiSignature> + Send + Sync,
+	H256: From<T::Hash>,
+	T::BlockNumber: From<u32> + Ord + sp_runtime::traits::Zero + One,
+	T::Index: AtLeast32Bit,
+	<T as subxt::Config>::AccountId: Send + Sync,
+	<T as subxt::Config>::Address: Send + Sync,
+{
+	/// Submits the given transaction to the parachain node, waits for it to be finalized and
+	/// asserts that it was successfully dispatched on-chain. Returns an error if every ibc message
+	/// in the transaction failed, failures of individual messages are logged.
 	///
-	/// We retry sending the transaction up to 5 times in the case where the transaction pool might
-	/// reject the transaction because of conflicting nonces.
+	/// Nonces are tracked locally so several transactions can be in the pool at once. We retry
+	/// sending the transaction up to 5 times in the case where the transaction pool might reject
+	/// the transaction because of conflicting nonces, refetching the nonce from the chain.
 	pub async fn submit_call<C: TxPayload>(&self, call: C) -> Result<(T::Hash, T::Hash), Error> {
 		// Try extrinsic submission five times in case of failures
 		let mut count = 0;
@@ -390,7 +526,21 @@ where
 				Err(Error::Custom("Failed to submit extrinsic after 5 tries".to_string()))?
 			}
 
-			let other_params = T::custom_extrinsic_params(&self.para_client).await?;
+			let other_params = T::custom_extrinsic_params(&self.para_client()).await?;
+
+			// the lock is held until the extrinsic is in the pool so nonces are handed out in
+			// order of submission.
+			let mut nonce = self.nonce.lock().await;
+			let current = match *nonce {
+				Some(nonce) => nonce,
+				None => {
+					let account_id = <<T as config::Config>::Signature as Verify>::Signer::from(
+						self.public_key.clone(),
+					)
+					.into_account();
+					self.para_client().rpc().system_account_next_index(&account_id).await?
+				},
+			};
 
 			let res = {
 				let signer = ExtrinsicSigner::<T, Self>::new(
@@ -398,27 +548,59 @@ where
 					self.key_type_id.clone(),
 					self.public_key.clone(),
 				);
-				self.para_client
-					.tx()
-					.sign_and_submit_then_watch(&call, &signer, other_params)
-					.await
+				match self.para_client().tx().create_signed_with_nonce(
+					&call,
+					&signer,
+					current,
+					other_params,
+				) {
+					Ok(extrinsic) => extrinsic.submit_and_watch().await,
+					Err(e) => Err(e),
+				}
 			};
 			match res {
-				Ok(progress) => break progress,
+				Ok(progress) => {
+					*nonce = Some(current + One::one());
+					break progress
+				},
 				Err(e) => {
 					log::warn!("Failed to submit extrinsic: {:?}. Retrying...", e);
+					// the local nonce may be stale, fetch it again on the next try.
+					*nonce = None;
 					count += 1;
 				},
 			}
 		};
 
-		let tx_in_block = progress.wait_for_in_block().await?;
-		tx_in_block.wait_for_success().await?;
-		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash()))
-	}
+		let tx_in_block = progress.wait_for_finalized().await?;
+		let events = tx_in_block.wait_for_success().await.map_err(|e| match e {
+			subxt::Error::Runtime(e) => Error::ExtrinsicFailed(format!("{:?}", e)),
+			e => e.into(),
+		})?;
+
+		let mut succeeded = 0;
+		let mut errors = vec![];
+		for ev in events.find::<api::ibc::events::Events>() {
+			for event in ev?.events {
+				match event {
+					Ok(_) => succeeded += 1,
+					Err(e) => errors.push(ibc_error_message(e)),
+				}
+			}
+		}
+		for error in &errors {
+			log::warn!(
+				target: "hyperspace",
+				"Ibc message failed in extrinsic {:?}: {}",
+				tx_in_block.extrinsic_hash(),
+				error
+			);
+		}
+		if succeeded == 0 && !errors.is_empty() {
+			Err(Error::IbcMessagesFailed(errors))?
+		}
 
-	pub fn client_id(&self) -> ClientId {
-		self.client_id.as_ref().expect("Client Id should be defined").clone()
+		Ok((tx_in_block.extrinsic_hash(), tx_in_block.block_hash()))
 	}
 }
 
@@ -454,11 +636,11 @@ where
 		u32: From<<T as subxt::Config>::BlockNumber>,
 	{
 		use ibc::core::ics24_host::identifier::ChainId;
-		let api = self.relay_client.storage();
-		let para_client_api = self.para_client.storage();
+		let api = self.relay_client().storage();
+		let para_client_api = self.para_client().storage();
 		let client_wrapper = Prover {
-			relay_client: self.relay_client.clone(),
-			para_client: self.para_client.clone(),
+			relay_client: self.relay_client(),
+			para_client: self.para_client(),
 			para_id: self.para_id,
 		};
 		loop {
@@ -468,7 +650,7 @@ where
 
 			let subxt_block_number: subxt::rpc::types::BlockNumber =
 				beefy_state.latest_beefy_height.into();
-			let block_hash = self.relay_client.rpc().block_hash(Some(subxt_block_number)).await?;
+			let block_hash = self.relay_client().rpc().block_hash(Some(subxt_block_number)).await?;
 			let heads_addr = polkadot::api::storage().paras().heads(
 				&polkadot::api::runtime_types::polkadot_parachain::primitives::Id(self.para_id),
 			);
@@ -507,7 +689,7 @@ where
 			}
 			let subxt_block_number: subxt::rpc::types::BlockNumber = block_number.into();
 			let block_hash =
-				self.para_client.rpc().block_hash(Some(subxt_block_number)).await.unwrap();
+				self.para_client().rpc().block_hash(Some(subxt_block_number)).await.unwrap();
 			let timestamp_addr = api::storage().timestamp().now();
 			let unix_timestamp_millis = para_client_api
 				.at(block_hash)
@@ -542,17 +724,17 @@ where
 		u32: From<<T as subxt::Config>::BlockNumber>,
 		<T as subxt::Config>::Hash: From<H256>,
 	{
-		let relay_ws_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.relay_ws_client) };
-		let para_ws_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.para_ws_client) };
+		let relay_ws_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.relay_ws_client()) };
+		let para_ws_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.para_ws_client()) };
 		let prover = GrandpaProver {
-			relay_client: self.relay_client.clone(),
+			relay_client: self.relay_client(),
 			relay_ws_client,
-			para_client: self.para_client.clone(),
+			para_client: self.para_client(),
 			para_ws_client,
 			para_id: self.para_id,
 		};
-		let api = self.relay_client.storage();
-		let para_client_api = self.para_client.storage();
+		let api = self.relay_client().storage();
+		let para_client_api = self.para_client().storage();
 		loop {
 			let light_client_state = prover
 				.initialize_client_state()
@@ -597,7 +779,7 @@ where
 
 			let subxt_block_number: subxt::rpc::types::BlockNumber = block_number.into();
 			let block_hash =
-				self.para_client.rpc().block_hash(Some(subxt_block_number)).await.unwrap();
+				self.para_client().rpc().block_hash(Some(subxt_block_number)).await.unwrap();
 			let timestamp_addr = api::storage().timestamp().now();
 			let unix_timestamp_millis = para_client_api
 				.at(block_hash)
@@ -620,3 +802,17 @@ where
 		}
 	}
 }
+
+/// Decodes the error message of a failed ibc message, as emitted by pallet-ibc.
+fn ibc_error_message(error: api::runtime_types::pallet_ibc::errors::IbcError) -> String {
+	use api::runtime_types::pallet_ibc::errors::IbcError;
+	let (kind, message) = match error {
+		IbcError::Ics02Client { message } => ("ics02", message),
+		IbcError::Ics03Connection { message } => ("ics03", message),
+		IbcError::Ics04Channel { message } => ("ics04", message),
+		IbcError::Ics20FungibleTokenTransfer { message } => ("ics20", message),
+		IbcError::UnknownMessageTypeUrl { message } => ("unknown message type url", message),
+		IbcError::MalformedMessageBytes { message } => ("malformed message", message),
+	};
+	format!("{}: {}", kind, String::from_utf8_lossy(&message))
+}
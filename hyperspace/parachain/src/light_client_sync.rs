@@ -75,11 +75,11 @@ where
 						c
 					)))?,
 				};
-				let latest_hash = self.relay_client.rpc().finalized_head().await?;
+				let latest_hash = self.relay_client().rpc().finalized_head().await?;
 				let finalized_head =
-					self.relay_client.rpc().header(Some(latest_hash)).await?.ok_or_else(|| {
-						Error::Custom(format!("Expected finalized header, found None"))
-					})?;
+					self.relay_client().rpc().header(Some(latest_hash)).await?.ok_or_else(
+						|| Error::Custom(format!("Expected finalized header, found None")),
+					)?;
 				let previous_finalized_height = client_state.latest_relay_height;
 				let session_length = prover.session_length().await?;
 				let (.., session_end_block) =
@@ -117,11 +117,11 @@ where
 						c
 					)))?,
 				};
-				let latest_hash = self.relay_client.rpc().finalized_head().await?;
+				let latest_hash = self.relay_client().rpc().finalized_head().await?;
 				let finalized_head =
-					self.relay_client.rpc().header(Some(latest_hash)).await?.ok_or_else(|| {
-						Error::Custom(format!("Expected finalized header, found None"))
-					})?;
+					self.relay_client().rpc().header(Some(latest_hash)).await?.ok_or_else(
+						|| Error::Custom(format!("Expected finalized header, found None")),
+					)?;
 				let latest_finalized_height = u32::from(finalized_head.number());
 				let (mut messages, mut events, previous_para_height, previous_finalized_height) =
 					self.query_missed_grandpa_updates(
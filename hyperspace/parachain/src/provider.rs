@@ -18,12 +18,15 @@ use crate::{
 	FinalityProtocol, GrandpaClientState,
 };
 use beefy_prover::helpers::fetch_timestamp_extrinsic_with_proof;
-use codec::Encode;
+use codec::{Decode, Encode};
 use finality_grandpa::BlockNumberOps;
 use futures::Stream;
 use grandpa_light_client_primitives::ParachainHeaderProofs;
 use ibc::{
-	applications::transfer::{Amount, PrefixedCoin, PrefixedDenom},
+	applications::{
+		fee::fee::PacketFee,
+		transfer::{Amount, PrefixedCoin, PrefixedDenom},
+	},
 	core::{
 		ics02_client::client_state::ClientType,
 		ics23_commitment::commitment::CommitmentPrefix,
@@ -39,7 +42,7 @@ use ibc_proto::{
 		channel::v1::{
 			QueryChannelResponse, QueryChannelsResponse, QueryNextSequenceReceiveResponse,
 			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
-			QueryPacketReceiptResponse,
+			QueryPacketReceiptResponse, QueryUpgradeErrorResponse, QueryUpgradeResponse,
 		},
 		client::v1::{
 			IdentifiedClientState, QueryClientStateResponse, QueryConsensusStateResponse,
@@ -54,7 +57,7 @@ use pallet_ibc::{
 	HostConsensusProof,
 };
 use primitives::{Chain, IbcProvider, KeyProvider, UpdateType};
-use sp_core::H256;
+use sp_core::{blake2_128, twox_128, H256};
 use sp_runtime::{
 	traits::{IdentifyAccount, One, Verify},
 	MultiSignature, MultiSigner,
@@ -70,8 +73,10 @@ use subxt::config::polkadot::PlainTip as Tip;
 
 #[cfg(feature = "dali")]
 use subxt::config::substrate::AssetTip as Tip;
+use tendermint_proto::Protobuf;
 use tokio_stream::wrappers::ReceiverStream;
 
+#[derive(Debug)]
 pub struct TransactionId<Hash> {
 	pub ext_hash: Hash,
 	pub block_hash: Hash,
@@ -120,8 +125,8 @@ where
 		use futures::StreamExt;
 		use pallet_ibc::events::IbcEvent as RawIbcEvent;
 		let (tx, rx) = tokio::sync::mpsc::channel(32);
-		let event = self.para_client.events();
-		let para_client = self.para_client.clone();
+		let event = self.para_client().events();
+		let para_client = self.para_client();
 		tokio::spawn(async move {
 			let stream = para_client
 				.blocks()
@@ -178,7 +183,7 @@ where
 		consensus_height: Height,
 	) -> Result<QueryConsensusStateResponse, Self::Error> {
 		let res = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_client_consensus_state(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			Some(at.revision_height as u32),
 			client_id.to_string(),
 			consensus_height.revision_height,
@@ -197,7 +202,7 @@ where
 	) -> Result<QueryClientStateResponse, Self::Error> {
 		let response =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_client_state(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				at.revision_height as u32,
 				client_id.to_string(),
 			)
@@ -212,7 +217,7 @@ where
 		connection_id: ConnectionId,
 	) -> Result<QueryConnectionResponse, Self::Error> {
 		let response = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_connection(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			at.revision_height as u32,
 			connection_id.to_string(),
 		)
@@ -228,7 +233,7 @@ where
 		port_id: PortId,
 	) -> Result<QueryChannelResponse, Self::Error> {
 		let response = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_channel(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
@@ -238,9 +243,45 @@ where
 		Ok(response)
 	}
 
+	async fn query_channel_upgrade(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeResponse, Self::Error> {
+		let response =
+			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_channel_upgrade(
+				&*self.para_ws_client(),
+				at.revision_height as u32,
+				channel_id.to_string(),
+				port_id.to_string(),
+			)
+			.await
+			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		Ok(response)
+	}
+
+	async fn query_channel_upgrade_error(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeErrorResponse, Self::Error> {
+		let response =
+			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_channel_upgrade_error(
+				&*self.para_ws_client(),
+				at.revision_height as u32,
+				channel_id.to_string(),
+				port_id.to_string(),
+			)
+			.await
+			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
+		Ok(response)
+	}
+
 	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error> {
 		let proof = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_proof(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			at.revision_height as u32,
 			keys,
 		)
@@ -258,7 +299,7 @@ where
 	) -> Result<QueryPacketCommitmentResponse, Self::Error> {
 		let res =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_packet_commitment(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				at.revision_height as u32,
 				channel_id.to_string(),
 				port_id.to_string(),
@@ -277,7 +318,7 @@ where
 		seq: u64,
 	) -> Result<QueryPacketAcknowledgementResponse, Self::Error> {
 		let res = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_packet_acknowledgement(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
@@ -295,7 +336,7 @@ where
 		channel_id: &ChannelId,
 	) -> Result<QueryNextSequenceReceiveResponse, Self::Error> {
 		let res = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_next_seq_recv(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
@@ -313,7 +354,7 @@ where
 		seq: u64,
 	) -> Result<QueryPacketReceiptResponse, Self::Error> {
 		let res = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_packet_receipt(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
@@ -326,7 +367,7 @@ where
 
 	async fn latest_height_and_timestamp(&self) -> Result<(Height, Timestamp), Self::Error> {
 		let finalized_header = self
-			.para_client
+			.para_client()
 			.rpc()
 			.header(None)
 			.await?
@@ -335,10 +376,11 @@ where
 		let height = Height::new(self.para_id.into(), latest_height.into());
 
 		let subxt_block_number: subxt::rpc::types::BlockNumber = latest_height.into();
-		let block_hash = self.para_client.rpc().block_hash(Some(subxt_block_number)).await.unwrap();
+		let block_hash =
+			self.para_client().rpc().block_hash(Some(subxt_block_number)).await.unwrap();
 		let timestamp_addr = parachain::api::storage().timestamp().now();
 		let unix_timestamp_millis = self
-			.para_client
+			.para_client()
 			.storage()
 			.at(block_hash)
 			.await
@@ -359,7 +401,7 @@ where
 	) -> Result<Vec<u64>, Self::Error> {
 		let res =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_packet_commitments(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				at.revision_height as u32,
 				channel_id.to_string(),
 				port_id.to_string(),
@@ -376,7 +418,7 @@ where
 		port_id: PortId,
 	) -> Result<Vec<u64>, Self::Error> {
 		let res = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_packet_acknowledgements(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
@@ -399,7 +441,7 @@ where
 	) -> Result<Vec<u64>, Self::Error> {
 		let res =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_unreceived_packets(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				at.revision_height as u32,
 				channel_id.to_string(),
 				port_id.to_string(),
@@ -418,7 +460,7 @@ where
 		seqs: Vec<u64>,
 	) -> Result<Vec<u64>, Self::Error> {
 		let res = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_unreceived_acknowledgements(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			at.revision_height as u32,
 			channel_id.to_string(),
 			port_id.to_string(),
@@ -433,6 +475,45 @@ where
 		self.channel_whitelist.clone()
 	}
 
+	fn set_channel_whitelist(&mut self, channel_whitelist: Vec<(ChannelId, PortId)>) {
+		self.channel_whitelist = channel_whitelist;
+	}
+
+	async fn query_packet_fees(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error> {
+		// The fee storage of pallet-ibc isn't part of the generated runtime api, so the storage
+		// key of `FeesInEscrow` is built by hand.
+		let escrow_key =
+			(port_id.as_bytes().to_vec(), channel_id.to_string().as_bytes().to_vec()).encode();
+		let sequence = sequence.encode();
+		let mut storage_key = twox_128(b"Ibc").to_vec();
+		storage_key.extend(twox_128(b"FeesInEscrow").to_vec());
+		storage_key.extend(blake2_128(&escrow_key).to_vec());
+		storage_key.extend(escrow_key);
+		storage_key.extend(blake2_128(&sequence).to_vec());
+		storage_key.extend(sequence);
+
+		let fees = match self.para_client().rpc().storage(&*storage_key, None).await? {
+			Some(data) => Vec::<Vec<u8>>::decode(&mut &*data.0)
+				.map_err(|e| Error::from(format!("Failed to decode packet fees: {:?}", e)))?,
+			None => return Ok(vec![]),
+		};
+		fees.into_iter()
+			.map(|fee| {
+				PacketFee::decode_vec(&fee)
+					.map_err(|e| Error::from(format!("Failed to decode packet fee: {:?}", e)))
+			})
+			.collect()
+	}
+
+	fn min_relay_fees(&self) -> Vec<PrefixedCoin> {
+		self.min_relay_fees.clone()
+	}
+
 	async fn query_connection_channels(
 		&self,
 		at: Height,
@@ -440,7 +521,7 @@ where
 	) -> Result<QueryChannelsResponse, Self::Error> {
 		let response =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_connection_channels(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				at.revision_height as u32,
 				connection_id.to_string(),
 			)
@@ -457,7 +538,7 @@ where
 	) -> Result<Vec<PacketInfo>, Self::Error> {
 		let response =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_send_packets(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				channel_id.to_string(),
 				port_id.to_string(),
 				seqs,
@@ -475,7 +556,7 @@ where
 	) -> Result<Vec<PacketInfo>, Self::Error> {
 		let response =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_recv_packets(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				channel_id.to_string(),
 				port_id.to_string(),
 				seqs,
@@ -496,7 +577,7 @@ where
 		client_height: Height,
 	) -> Result<(Height, Timestamp), Self::Error> {
 		let response = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_client_update_time_and_height(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			client_id.to_string(),
 			client_height.revision_number,
 			client_height.revision_height,
@@ -514,15 +595,15 @@ where
 		&self,
 		height: Height,
 	) -> Result<Option<Vec<u8>>, Self::Error> {
-		let hash = self.para_client.rpc().block_hash(Some(height.revision_height.into())).await?;
+		let hash = self.para_client().rpc().block_hash(Some(height.revision_height.into())).await?;
 		let header = self
-			.para_client
+			.para_client()
 			.rpc()
 			.header(hash)
 			.await?
 			.ok_or_else(|| Error::Custom("Latest height query returned None".to_string()))?;
 		let extrinsic_with_proof =
-			fetch_timestamp_extrinsic_with_proof(&self.para_client, Some(header.hash()))
+			fetch_timestamp_extrinsic_with_proof(&self.para_client(), Some(header.hash()))
 				.await
 				.map_err(Error::BeefyProver)?;
 
@@ -541,7 +622,7 @@ where
 		hex_string.insert_str(0, "0x");
 		let coin: ibc_proto::cosmos::base::v1beta1::Coin =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_balance_with_address(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				hex_string,
 			)
 			.await
@@ -573,10 +654,11 @@ where
 
 	async fn query_timestamp_at(&self, block_number: u64) -> Result<u64, Self::Error> {
 		let subxt_block_number: subxt::rpc::types::BlockNumber = block_number.into();
-		let block_hash = self.para_client.rpc().block_hash(Some(subxt_block_number)).await.unwrap();
+		let block_hash =
+			self.para_client().rpc().block_hash(Some(subxt_block_number)).await.unwrap();
 		let timestamp_addr = parachain::api::storage().timestamp().now();
 		let unix_timestamp_millis = self
-			.para_client
+			.para_client()
 			.storage()
 			.at(block_hash)
 			.await
@@ -592,7 +674,7 @@ where
 	async fn query_clients(&self) -> Result<Vec<ClientId>, Self::Error> {
 		let response: Vec<IdentifiedClientState> =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_clients(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 			)
 			.await
 			.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -607,7 +689,7 @@ where
 
 	async fn query_channels(&self) -> Result<Vec<(ChannelId, PortId)>, Self::Error> {
 		let response = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_channels(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 		)
 		.await
 		.map_err(|e| Error::from(format!("Rpc Error {:?}", e)))?;
@@ -631,7 +713,7 @@ where
 		client_id: String,
 	) -> Result<Vec<IdentifiedConnection>, Self::Error> {
 		let response = IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_connection_using_client(
-			&*self.para_ws_client,
+			&*self.para_ws_client(),
 			height,
 			client_id,
 		)
@@ -674,6 +756,18 @@ where
 		}
 	}
 
+	async fn query_client_upgrade_messages<C>(
+		&self,
+		_counterparty: &C,
+	) -> Result<Vec<Any>, anyhow::Error>
+	where
+		C: Chain,
+	{
+		// Parachain runtime upgrades don't change the consensus of the chain, the light client
+		// never needs to be upgraded.
+		Ok(vec![])
+	}
+
 	async fn query_client_id_from_tx_hash(
 		&self,
 		tx_id: Self::TransactionId,
@@ -682,7 +776,7 @@ where
 		let TransactionId { ext_hash, block_hash } = tx_id;
 		let identified_client_state =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_newly_created_client(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				block_hash.into(),
 				ext_hash.into(),
 			)
@@ -25,7 +25,7 @@ use futures::{Stream, StreamExt};
 use grandpa_light_client_primitives::ParachainHeaderProofs;
 use ibc::{
 	applications::transfer::{msgs::transfer::MsgTransfer, PrefixedCoin},
-	core::ics24_host::identifier::{ChannelId, ClientId, PortId},
+	core::ics24_host::identifier::{ChannelId, ClientId},
 };
 use ibc_rpc::IbcApiClient;
 use jsonrpsee::{core::client::SubscriptionClientT, rpc_params};
@@ -36,7 +36,7 @@ use sp_core::{
 	H256,
 };
 use sp_runtime::{
-	traits::{IdentifyAccount, One, Verify},
+	traits::{AtLeast32Bit, IdentifyAccount, One, Verify},
 	MultiSignature, MultiSigner,
 };
 use std::{collections::BTreeMap, fmt::Display, pin::Pin, str::FromStr};
@@ -66,6 +66,7 @@ where
 	BTreeMap<H256, ParachainHeaderProofs>:
 		From<BTreeMap<<T as subxt::Config>::Hash, ParachainHeaderProofs>>,
 	T::BlockNumber: Ord + sp_runtime::traits::Zero,
+	T::Index: AtLeast32Bit,
 	<T as subxt::Config>::AccountId: Send + Sync,
 	<T as subxt::Config>::Address: Send + Sync,
 {
@@ -83,7 +84,7 @@ where
 		// Query newly created client Id
 		let identified_client_state =
 			IbcApiClient::<u32, H256, <T as config::Config>::AssetId>::query_newly_created_client(
-				&*self.para_ws_client,
+				&*self.para_ws_client(),
 				block_hash.into(),
 				ext_hash.into(),
 			)
@@ -140,10 +141,10 @@ where
 		let ext = api::tx().sudo().sudo(call);
 		// Submit extrinsic to parachain node
 
-		let other_params = T::custom_extrinsic_params(&self.para_client).await?;
+		let other_params = T::custom_extrinsic_params(&self.para_client()).await?;
 
 		let _progress = self
-			.para_client
+			.para_client()
 			.tx()
 			.sign_and_submit_then_watch(&ext, &signer, other_params)
 			.await?
@@ -189,6 +190,7 @@ where
 		From<BTreeMap<<T as subxt::Config>::Hash, ParachainHeaderProofs>>,
 	<T::ExtrinsicParams as ExtrinsicParams<T::Index, T::Hash>>::OtherParams:
 		From<BaseExtrinsicParamsBuilder<T, Tip>> + Send + Sync,
+	T::Index: AtLeast32Bit,
 	<T as subxt::Config>::AccountId: Send + Sync,
 	<T as subxt::Config>::Address: Send + Sync,
 {
@@ -232,7 +234,7 @@ where
 	}
 
 	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>> {
-		let para_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.para_ws_client) };
+		let para_client = unsafe { unsafe_cast_to_jsonrpsee_client(&self.para_ws_client()) };
 		let stream = para_client
 			.subscribe::<T::Header, _>(
 				"chain_subscribeNewHeads",
@@ -249,8 +251,4 @@ where
 
 		Box::pin(Box::new(stream))
 	}
-
-	fn set_channel_whitelist(&mut self, channel_whitelist: Vec<(ChannelId, PortId)>) {
-		self.channel_whitelist = channel_whitelist;
-	}
 }
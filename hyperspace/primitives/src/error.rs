@@ -43,6 +43,12 @@ pub enum Error {
 	HexDecode(#[from] hex::FromHexError),
 	#[error("String from utf-8 error")]
 	StringFromUtf8(#[from] FromUtf8Error),
+	/// State store error
+	#[error("State store error: {0}")]
+	Store(#[from] sled::Error),
+	/// Keystore error
+	#[error("Keystore error: {0}")]
+	Keystore(String),
 }
 
 impl From<String> for Error {
@@ -50,3 +56,25 @@ impl From<String> for Error {
 		Self::Custom(error)
 	}
 }
+
+/// Classification of errors, determines how the relayer reacts to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+	/// Transient error, e.g. an rpc request that failed or timed out. The operation is retried
+	/// after a backoff.
+	Retryable,
+	/// The chain rejected a transaction, the messages in it are dropped and picked up again on
+	/// the next finality event if they're still relevant.
+	Rejected,
+	/// Error the relayer can't recover from, e.g. invalid configuration.
+	Fatal,
+}
+
+impl Error {
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Self::Store(_) | Self::Keystore(_) => ErrorKind::Fatal,
+			_ => ErrorKind::Retryable,
+		}
+	}
+}
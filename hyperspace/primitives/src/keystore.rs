@@ -0,0 +1,420 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encrypted on-disk storage for the relayer's signing keys, so that chain configs can refer to
+//! a key by name instead of embedding the secret.
+
+use std::{
+	fmt::{Display, Formatter},
+	fs,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+
+use chacha20poly1305::{
+	aead::{Aead, KeyInit},
+	Key, XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+
+/// Environment variable overriding the default keystore directory.
+pub const KEYSTORE_ENV: &str = "HYPERSPACE_KEYSTORE";
+/// Environment variable holding the keystore passphrase, it's prompted for if unset.
+pub const PASSPHRASE_ENV: &str = "HYPERSPACE_KEYSTORE_PASSPHRASE";
+
+/// scrypt parameters used to derive the encryption key from the passphrase.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Digital signature scheme of a stored key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+	Sr25519,
+	Ed25519,
+	Ecdsa,
+	/// Used by cosmos chains
+	Secp256k1,
+}
+
+impl FromStr for KeyType {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"sr25519" => Ok(KeyType::Sr25519),
+			"ed25519" => Ok(KeyType::Ed25519),
+			"ecdsa" => Ok(KeyType::Ecdsa),
+			"secp256k1" => Ok(KeyType::Secp256k1),
+			_ => Err(Error::Keystore(format!("Invalid key type {}", s))),
+		}
+	}
+}
+
+impl Display for KeyType {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let name = match self {
+			KeyType::Sr25519 => "sr25519",
+			KeyType::Ed25519 => "ed25519",
+			KeyType::Ecdsa => "ecdsa",
+			KeyType::Secp256k1 => "secp256k1",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+/// Secret encrypted with XChaCha20-Poly1305, using a key derived from the passphrase with scrypt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedSecret {
+	/// Hex encoded scrypt salt
+	salt: String,
+	log_n: u8,
+	r: u32,
+	p: u32,
+	/// Hex encoded nonce
+	nonce: String,
+	/// Hex encoded ciphertext
+	ciphertext: String,
+}
+
+/// A key stored in the keystore, only the secret is encrypted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyFile {
+	/// Name of the key, as referred to by the chain configs
+	pub name: String,
+	pub key_type: KeyType,
+	/// Hex encoded public key
+	pub public_key: String,
+	crypto: EncryptedSecret,
+}
+
+impl KeyFile {
+	/// Decrypts the secret of the key, either a secret uri or a BIP-39 mnemonic.
+	pub fn decrypt(&self, passphrase: &str) -> Result<Zeroizing<String>, Error> {
+		let salt = hex::decode(&self.crypto.salt)?;
+		let nonce = hex::decode(&self.crypto.nonce)?;
+		let ciphertext = hex::decode(&self.crypto.ciphertext)?;
+		let params = scrypt::Params::new(self.crypto.log_n, self.crypto.r, self.crypto.p)
+			.map_err(|e| Error::Keystore(format!("Invalid scrypt params: {:?}", e)))?;
+		let key = derive_key(passphrase, &salt, &params)?;
+		let secret = XChaCha20Poly1305::new(Key::from_slice(&*key))
+			.decrypt(XNonce::from_slice(&nonce), &*ciphertext)
+			.map_err(|_| {
+				Error::Keystore(format!("Failed to decrypt key {}, wrong passphrase?", self.name))
+			})?;
+		let secret = String::from_utf8(secret)?;
+		Ok(Zeroizing::new(secret))
+	}
+}
+
+fn derive_key(
+	passphrase: &str,
+	salt: &[u8],
+	params: &scrypt::Params,
+) -> Result<Zeroizing<[u8; 32]>, Error> {
+	let mut key = Zeroizing::new([0u8; 32]);
+	scrypt::scrypt(passphrase.as_bytes(), salt, params, &mut *key)
+		.map_err(|e| Error::Keystore(format!("Failed to derive encryption key: {:?}", e)))?;
+	Ok(key)
+}
+
+/// A directory of [`KeyFile`]s, one json file per key.
+pub struct KeyStore {
+	path: PathBuf,
+}
+
+impl KeyStore {
+	/// Opens (or creates) the keystore at the given directory.
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+		let path = path.as_ref().to_path_buf();
+		fs::create_dir_all(&path).map_err(|e| {
+			Error::Keystore(format!("Failed to create keystore {}: {}", path.display(), e))
+		})?;
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::PermissionsExt;
+			fs::set_permissions(&path, fs::Permissions::from_mode(0o700)).map_err(|e| {
+				Error::Keystore(format!("Failed to restrict access to {}: {}", path.display(), e))
+			})?;
+		}
+		Ok(Self { path })
+	}
+
+	/// Opens the keystore at `path` if given, otherwise the default keystore.
+	pub fn open_or_default(path: Option<impl AsRef<Path>>) -> Result<Self, Error> {
+		match path {
+			Some(path) => Self::open(path),
+			None => Self::open_default(),
+		}
+	}
+
+	/// Opens the keystore at `$HYPERSPACE_KEYSTORE`, defaults to `~/.hyperspace/keys`.
+	pub fn open_default() -> Result<Self, Error> {
+		let path = match std::env::var(KEYSTORE_ENV) {
+			Ok(path) => PathBuf::from(path),
+			Err(_) => {
+				let home = std::env::var("HOME").map_err(|_| {
+					Error::Keystore(format!("Neither {} nor HOME is set", KEYSTORE_ENV))
+				})?;
+				Path::new(&home).join(".hyperspace").join("keys")
+			},
+		};
+		Self::open(path)
+	}
+
+	fn key_path(&self, name: &str) -> Result<PathBuf, Error> {
+		// names end up in file paths
+		if name.is_empty() ||
+			!name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+		{
+			return Err(Error::Keystore(format!(
+				"Invalid key name {:?}, only alphanumeric characters, '-' and '_' are allowed",
+				name
+			)))
+		}
+		Ok(self.path.join(format!("{}.json", name)))
+	}
+
+	/// Encrypts the secret with the passphrase and stores it under `name`, fails if a key with
+	/// that name already exists.
+	pub fn add(
+		&self,
+		name: &str,
+		key_type: KeyType,
+		public_key: &[u8],
+		secret: &str,
+		passphrase: &str,
+	) -> Result<KeyFile, Error> {
+		let path = self.key_path(name)?;
+		if path.exists() {
+			return Err(Error::Keystore(format!("Key {} already exists", name)))
+		}
+
+		let mut salt = [0u8; 32];
+		OsRng.fill_bytes(&mut salt);
+		let mut nonce = [0u8; 24];
+		OsRng.fill_bytes(&mut nonce);
+		let params = scrypt::Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)
+			.map_err(|e| Error::Keystore(format!("Invalid scrypt params: {:?}", e)))?;
+		let key = derive_key(passphrase, &salt, &params)?;
+		let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&*key))
+			.encrypt(XNonce::from_slice(&nonce), secret.as_bytes())
+			.map_err(|_| Error::Keystore(format!("Failed to encrypt key {}", name)))?;
+
+		let key_file = KeyFile {
+			name: name.to_string(),
+			key_type,
+			public_key: hex::encode(public_key),
+			crypto: EncryptedSecret {
+				salt: hex::encode(salt),
+				log_n: SCRYPT_LOG_N,
+				r: SCRYPT_R,
+				p: SCRYPT_P,
+				nonce: hex::encode(nonce),
+				ciphertext: hex::encode(ciphertext),
+			},
+		};
+		let json = serde_json::to_vec_pretty(&key_file)
+			.map_err(|e| Error::Keystore(format!("Failed to encode key {}: {}", name, e)))?;
+		write_private(&path, &json)
+			.map_err(|e| Error::Keystore(format!("Failed to write key {}: {}", name, e)))?;
+		Ok(key_file)
+	}
+
+	/// Returns the key stored under `name`.
+	pub fn get(&self, name: &str) -> Result<KeyFile, Error> {
+		let path = self.key_path(name)?;
+		let json = fs::read(&path).map_err(|e| {
+			Error::Keystore(format!("Failed to read key {} from {}: {}", name, path.display(), e))
+		})?;
+		serde_json::from_slice(&json)
+			.map_err(|e| Error::Keystore(format!("Invalid key file {}: {}", path.display(), e)))
+	}
+
+	/// Returns all the keys in the keystore, sorted by name.
+	pub fn list(&self) -> Result<Vec<KeyFile>, Error> {
+		let entries = fs::read_dir(&self.path).map_err(|e| {
+			Error::Keystore(format!("Failed to read keystore {}: {}", self.path.display(), e))
+		})?;
+		let mut keys = vec![];
+		for entry in entries {
+			let path = entry
+				.map_err(|e| Error::Keystore(format!("Failed to read keystore entry: {}", e)))?
+				.path();
+			match path.file_stem().and_then(|stem| stem.to_str()) {
+				Some(name) if path.extension().map(|ext| ext == "json").unwrap_or(false) =>
+					keys.push(self.get(name)?),
+				_ => continue,
+			}
+		}
+		keys.sort_by(|a, b| a.name.cmp(&b.name));
+		Ok(keys)
+	}
+
+	/// Removes the key stored under `name`.
+	pub fn delete(&self, name: &str) -> Result<(), Error> {
+		let path = self.key_path(name)?;
+		fs::remove_file(&path)
+			.map_err(|e| Error::Keystore(format!("Failed to delete key {}: {}", name, e)))
+	}
+}
+
+/// Writes the file so that it's only readable by the current user.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+	use std::io::Write;
+	let mut options = fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::OpenOptionsExt;
+		options.mode(0o600);
+	}
+	options.open(path)?.write_all(contents)
+}
+
+/// Reads the keystore passphrase from `$HYPERSPACE_KEYSTORE_PASSPHRASE`, or prompts for it.
+/// A prompted passphrase for a new key must be entered twice.
+pub fn passphrase(confirm: bool) -> Result<Zeroizing<String>, Error> {
+	if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+		return Ok(Zeroizing::new(passphrase))
+	}
+	let passphrase = prompt_secret("Keystore passphrase: ")?;
+	if confirm {
+		let confirmation = prompt_secret("Repeat passphrase: ")?;
+		if passphrase != confirmation {
+			return Err(Error::Keystore("Passphrases don't match".to_string()))
+		}
+	}
+	Ok(passphrase)
+}
+
+/// Reads a secret from the terminal without echoing it.
+pub fn prompt_secret(prompt: &str) -> Result<Zeroizing<String>, Error> {
+	rpassword::prompt_password(prompt)
+		.map(Zeroizing::new)
+		.map_err(|e| Error::Keystore(format!("Failed to read secret: {}", e)))
+}
+
+/// Loads the secret of the key `name` from the keystore at `keystore_path`, or the default
+/// keystore, for a chain that signs with `key_type`.
+pub fn load_secret(
+	keystore_path: Option<&str>,
+	name: &str,
+	key_type: KeyType,
+) -> Result<Zeroizing<String>, Error> {
+	let key_file = KeyStore::open_or_default(keystore_path)?.get(name)?;
+	if key_file.key_type != key_type {
+		return Err(Error::Keystore(format!(
+			"Key {} is a {} key, expected a {} key",
+			name, key_file.key_type, key_type
+		)))
+	}
+	key_file.decrypt(&passphrase(false)?)
+}
+
+/// Returns the secret a chain signs with, either the key `key_name` from the keystore at
+/// `keystore_path` (the default keystore if unset) or the secret embedded in its config.
+pub fn resolve_secret(
+	keystore_path: Option<&str>,
+	key_name: Option<&str>,
+	secret: Option<&str>,
+	key_type: KeyType,
+) -> Result<Zeroizing<String>, Error> {
+	match (key_name, secret) {
+		(Some(name), None) => load_secret(keystore_path, name, key_type),
+		(None, Some(secret)) => Ok(Zeroizing::new(secret.to_string())),
+		(Some(_), Some(_)) =>
+			Err(Error::Keystore("Only one of a key name or a secret can be configured".to_string())),
+		(None, None) =>
+			Err(Error::Keystore("Either a key name or a secret must be configured".to_string())),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn keystore(name: &str) -> (KeyStore, PathBuf) {
+		let path = std::env::temp_dir().join(format!(
+			"hyperspace-keystore-{}-{}",
+			name,
+			std::process::id()
+		));
+		let _ = fs::remove_dir_all(&path);
+		(KeyStore::open(&path).unwrap(), path)
+	}
+
+	#[test]
+	fn keys_are_decrypted_with_their_passphrase_only() {
+		let (keystore, path) = keystore("roundtrip");
+		let secret = "bottom drive obey lake curtain smoke basket hold race lonely fit walk";
+		let added = keystore
+			.add("relayer", KeyType::Sr25519, &[1, 2, 3], secret, "hunter2")
+			.unwrap();
+		assert_eq!(added.public_key, "010203");
+		// the secret isn't stored in the clear
+		let json = fs::read_to_string(path.join("relayer.json")).unwrap();
+		assert!(!json.contains("bottom drive"));
+
+		let key_file = keystore.get("relayer").unwrap();
+		assert_eq!(key_file.key_type, KeyType::Sr25519);
+		assert_eq!(&*key_file.decrypt("hunter2").unwrap(), secret);
+		assert!(matches!(key_file.decrypt("hunter3"), Err(Error::Keystore(_))));
+
+		// keys can't be overwritten
+		assert!(keystore.add("relayer", KeyType::Ed25519, &[4], "//Bob", "hunter2").is_err());
+		keystore.add("other", KeyType::Secp256k1, &[5], "//Bob", "hunter2").unwrap();
+		let names = keystore.list().unwrap().into_iter().map(|key| key.name).collect::<Vec<_>>();
+		assert_eq!(names, vec!["other", "relayer"]);
+
+		keystore.delete("relayer").unwrap();
+		assert!(keystore.get("relayer").is_err());
+		fs::remove_dir_all(&path).unwrap();
+	}
+
+	#[test]
+	fn key_names_must_be_valid_file_names() {
+		let (keystore, path) = keystore("names");
+		assert_eq!(keystore.key_path("relayer-1_a").unwrap(), path.join("relayer-1_a.json"));
+		for name in ["", "../relayer", "/etc/passwd", "relayer.json", "relayer key", "ключ"] {
+			assert!(keystore.key_path(name).is_err(), "{:?}", name);
+			assert!(keystore.get(name).is_err(), "{:?}", name);
+			assert!(keystore.delete(name).is_err(), "{:?}", name);
+		}
+		fs::remove_dir_all(&path).unwrap();
+	}
+
+	#[test]
+	fn secrets_are_resolved_from_the_configured_keystore() {
+		let (keystore, path) = keystore("resolve");
+		keystore.add("cosmos", KeyType::Secp256k1, &[1], "//Alice", "hunter2").unwrap();
+		std::env::set_var(PASSPHRASE_ENV, "hunter2");
+		let path_str = path.to_str();
+		assert_eq!(
+			&*resolve_secret(path_str, Some("cosmos"), None, KeyType::Secp256k1).unwrap(),
+			"//Alice"
+		);
+		// the key type must match the chain's
+		assert!(resolve_secret(path_str, Some("cosmos"), None, KeyType::Sr25519).is_err());
+		assert_eq!(&*resolve_secret(None, None, Some("//Bob"), KeyType::Sr25519).unwrap(), "//Bob");
+		assert!(resolve_secret(path_str, Some("cosmos"), Some("//Bob"), KeyType::Sr25519).is_err());
+		assert!(resolve_secret(path_str, None, None, KeyType::Sr25519).is_err());
+		fs::remove_dir_all(&path).unwrap();
+	}
+}
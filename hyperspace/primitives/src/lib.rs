@@ -23,18 +23,19 @@ use ibc_proto::{
 		channel::v1::{
 			QueryChannelResponse, QueryNextSequenceReceiveResponse,
 			QueryPacketAcknowledgementResponse, QueryPacketCommitmentResponse,
-			QueryPacketReceiptResponse,
+			QueryPacketReceiptResponse, QueryUpgradeErrorResponse, QueryUpgradeResponse,
 		},
 		client::v1::{QueryClientStateResponse, QueryConsensusStateResponse},
 		connection::v1::QueryConnectionResponse,
 	},
 };
 
-use crate::error::Error;
+use crate::{error::Error, store::TransactionStatus};
 #[cfg(feature = "testing")]
 use ibc::applications::transfer::msgs::transfer::MsgTransfer;
 use ibc::{
-	applications::transfer::PrefixedCoin,
+	applications::{fee::fee::PacketFee, transfer::PrefixedCoin},
+	bigint::U256,
 	core::{
 		ics02_client::{
 			client_consensus::ConsensusState as ConsensusStateT,
@@ -61,7 +62,9 @@ use ibc_rpc::PacketInfo;
 use pallet_ibc::light_clients::{AnyClientMessage, AnyClientState, AnyConsensusState};
 
 pub mod error;
+pub mod keystore;
 pub mod mock;
+pub mod store;
 pub mod utils;
 
 pub enum UpdateMessage {
@@ -95,11 +98,12 @@ pub fn apply_prefix(mut commitment_prefix: Vec<u8>, path: String) -> Vec<u8> {
 /// relayed to the counterparty chain.
 #[async_trait::async_trait]
 pub trait IbcProvider {
-	/// Finality event type, passed on to [`Chain::query_latest_ibc_events`]
-	type FinalityEvent;
+	/// Finality event type, passed on to [`Chain::query_latest_ibc_events`], events are cloned
+	/// to be retried when processing them fails.
+	type FinalityEvent: Clone;
 
 	/// A representation of the transaction id for the chain
-	type TransactionId;
+	type TransactionId: std::fmt::Debug;
 
 	/// Error type, just needs to implement standard error trait.
 	type Error: std::error::Error + From<String> + Send + Sync + 'static;
@@ -149,6 +153,22 @@ pub trait IbcProvider {
 		port_id: PortId,
 	) -> Result<QueryChannelResponse, Self::Error>;
 
+	/// Query the upgrade proposed for a channel with proof
+	async fn query_channel_upgrade(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeResponse, Self::Error>;
+
+	/// Query the error receipt of the last aborted upgrade of a channel with proof
+	async fn query_channel_upgrade_error(
+		&self,
+		at: Height,
+		channel_id: ChannelId,
+		port_id: PortId,
+	) -> Result<QueryUpgradeErrorResponse, Self::Error>;
+
 	/// Query proof for provided key path
 	async fn query_proof(&self, at: Height, keys: Vec<Vec<u8>>) -> Result<Vec<u8>, Self::Error>;
 
@@ -213,7 +233,8 @@ pub trait IbcProvider {
 	/// and send the request to this Query/UnreceivedPackets on the **receiving**
 	/// chain. This method should then return the list of packet sequences that
 	/// are yet to be received on the receiving chain.
-	/// NOTE: WORKS ONLY FOR UNORDERED CHANNELS
+	/// NOTE: WORKS ONLY FOR UNORDERED CHANNELS, packets on ordered channels are received in
+	/// sequence, see [`query_undelivered_sequences`].
 	async fn query_unreceived_packets(
 		&self,
 		at: Height,
@@ -235,6 +256,21 @@ pub trait IbcProvider {
 	/// Channel whitelist
 	fn channel_whitelist(&self) -> Vec<(ChannelId, PortId)>;
 
+	/// Set the channel whitelist for the relayer task.
+	fn set_channel_whitelist(&mut self, channel_whitelist: Vec<(ChannelId, PortId)>);
+
+	/// Query the ICS-29 fees escrowed on this chain for relaying a packet it sent.
+	async fn query_packet_fees(
+		&self,
+		port_id: PortId,
+		channel_id: ChannelId,
+		sequence: u64,
+	) -> Result<Vec<PacketFee>, Self::Error>;
+
+	/// Minimum fees that must be escrowed for a packet sent by this chain before it's relayed,
+	/// packets are relayed regardless of their fees if empty.
+	fn min_relay_fees(&self) -> Vec<PrefixedCoin>;
+
 	/// Query all channels for a connection
 	async fn query_connection_channels(
 		&self,
@@ -323,6 +359,15 @@ pub trait IbcProvider {
 		&self,
 	) -> Result<(AnyClientState, AnyConsensusState), Self::Error>;
 
+	/// Messages upgrading the counterparty's light client of this chain once this chain has reached
+	/// the height of a planned upgrade, empty if there's no pending upgrade.
+	async fn query_client_upgrade_messages<T>(
+		&self,
+		counterparty: &T,
+	) -> Result<Vec<Any>, anyhow::Error>
+	where
+		T: Chain;
+
 	/// Should find client id that was created in this transaction
 	async fn query_client_id_from_tx_hash(
 		&self,
@@ -347,9 +392,6 @@ pub trait TestProvider: Chain + Clone + 'static {
 
 	/// Returns a stream that yields chain Block number
 	async fn subscribe_blocks(&self) -> Pin<Box<dyn Stream<Item = u64> + Send + Sync>>;
-
-	/// Set the channel whitelist for the relayer task.
-	fn set_channel_whitelist(&mut self, channel_whitelist: Vec<(ChannelId, PortId)>);
 }
 
 /// Provides an interface for managing key management for signing.
@@ -408,15 +450,35 @@ pub trait Chain:
 	/// Should return the transaction id
 	async fn submit(&self, messages: Vec<Any>) -> Result<Self::TransactionId, Self::Error>;
 
+	/// Signs and broadcasts the messages without waiting for the transaction to be included in a
+	/// block, returns the hash of the transaction. Chains that can't broadcast asynchronously
+	/// wait for the transaction to be included before returning.
+	async fn broadcast(&self, messages: Vec<Any>) -> Result<String, Self::Error>;
+
+	/// Waits for a transaction returned by [`Chain::broadcast`] to be included in a block, fails
+	/// if the transaction failed.
+	async fn wait_for_transaction(&self, tx_hash: &str) -> Result<(), Self::Error>;
+
+	/// Queries the status of a transaction returned by [`Chain::broadcast`], used to resolve the
+	/// submissions that were still pending when the relayer stopped.
+	async fn query_transaction_status(
+		&self,
+		tx_hash: &str,
+	) -> Result<TransactionStatus, Self::Error>;
+
 	/// Returns an [`AnyClientMessage`] for an [`UpdateClient`] event
 	async fn query_client_message(
 		&self,
 		update: UpdateClient,
 	) -> Result<AnyClientMessage, Self::Error>;
+
+	/// Re-establishes the connections to the chain's rpc endpoints, this is called before
+	/// re-subscribing to finality notifications after the stream closed.
+	async fn reconnect(&mut self) -> Result<(), Self::Error>;
 }
 
 /// Returns undelivered packet sequences that have been sent out from
-/// the `source` chain to the `sink` chain
+/// the `source` chain to the `sink` chain, in ascending order
 /// works for both ordered and unordered channels
 pub async fn query_undelivered_sequences(
 	source_height: Height,
@@ -445,24 +507,36 @@ pub async fn query_undelivered_sequences(
 	let counterparty_port_id = channel_end.counterparty().port_id.clone();
 
 	let undelivered_sequences = if channel_end.ordering == Order::Unordered {
-		sink.query_unreceived_packets(
-			sink_height,
-			counterparty_channel_id,
-			counterparty_port_id.clone(),
-			seqs,
-		)
-		.await?
+		let mut seqs = sink
+			.query_unreceived_packets(
+				sink_height,
+				counterparty_channel_id,
+				counterparty_port_id.clone(),
+				seqs,
+			)
+			.await?;
+		seqs.sort_unstable();
+		seqs
 	} else {
 		let next_seq_recv = sink
 			.query_next_sequence_recv(sink_height, &counterparty_port_id, &counterparty_channel_id)
 			.await?
 			.next_sequence_receive;
-		seqs.into_iter().filter(|seq| *seq > next_seq_recv).collect()
+		undelivered_ordered_sequences(seqs, next_seq_recv)
 	};
 
 	Ok(undelivered_sequences)
 }
 
+/// Returns the sequences of the packet commitments `seqs` of an ordered channel that are yet to be
+/// received, in the order they must be received in. Every sequence from the next one to be received
+/// on the counterparty is still undelivered.
+pub fn undelivered_ordered_sequences(mut seqs: Vec<u64>, next_sequence_recv: u64) -> Vec<u64> {
+	seqs.retain(|seq| *seq >= next_sequence_recv);
+	seqs.sort_unstable();
+	seqs
+}
+
 /// Queries the `source` chain for packet acknowledgements that have not been seen by the `sink`
 /// chain.
 pub async fn query_undelivered_acks(
@@ -491,7 +565,7 @@ pub async fn query_undelivered_acks(
 		.ok_or_else(|| Error::Custom("Expected counterparty channel id".to_string()))?;
 	let counterparty_port_id = channel_end.counterparty().port_id.clone();
 
-	let undelivered_acks = sink
+	let mut undelivered_acks = sink
 		.query_unreceived_acknowledgements(
 			sink_height,
 			counterparty_channel_id,
@@ -499,10 +573,39 @@ pub async fn query_undelivered_acks(
 			seqs,
 		)
 		.await?;
+	// acknowledgements on ordered channels must also be delivered in sequence.
+	undelivered_acks.sort_unstable();
 
 	Ok(undelivered_acks)
 }
 
+/// Returns true if the receive and acknowledgement fees escrowed on the source chain for the
+/// packet cover the amount of any of the minimum fees required by the relayer.
+pub async fn is_relay_fee_sufficient(source: &impl Chain, packet: &Packet) -> Result<bool, Error> {
+	let min_relay_fees = source.min_relay_fees();
+	if min_relay_fees.is_empty() {
+		return Ok(true)
+	}
+	let packet_fees = source
+		.query_packet_fees(
+			packet.source_port.clone(),
+			packet.source_channel,
+			packet.sequence.into(),
+		)
+		.await
+		.map_err(|e| Error::Custom(format!("Failed to query packet fees: {:?}", e)))?;
+
+	let sufficient = min_relay_fees.iter().any(|min_fee| {
+		let escrowed = packet_fees
+			.iter()
+			.flat_map(|packet_fee| packet_fee.fee.recv_fee.iter().chain(&packet_fee.fee.ack_fee))
+			.filter(|coin| coin.denom == min_fee.denom)
+			.fold(U256::zero(), |total, coin| total.saturating_add(coin.amount.as_u256()));
+		escrowed >= min_fee.amount.as_u256()
+	});
+	Ok(sufficient)
+}
+
 pub fn packet_info_to_packet(packet_info: &PacketInfo) -> Packet {
 	Packet {
 		sequence: packet_info.sequence.into(),
@@ -0,0 +1,308 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistent relayer state, so that a restarted relayer can resume from where it left off
+//! instead of rescanning both chains.
+
+use std::{
+	collections::HashMap,
+	path::Path,
+	sync::{Arc, Mutex},
+};
+
+use codec::{Decode, Encode};
+use ibc::{
+	core::ics24_host::identifier::{ChannelId, PortId},
+	Height,
+};
+
+use crate::error::Error;
+
+/// A transaction that was submitted to a chain, but hasn't been confirmed yet.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct PendingTransaction {
+	/// Hash of the transaction, as returned by [`crate::Chain::broadcast`].
+	pub tx_id: String,
+	/// Type urls of the messages in the transaction.
+	pub type_urls: Vec<String>,
+	/// Unix timestamp in seconds at which the transaction was submitted.
+	pub submitted_at: u64,
+}
+
+/// Status of a transaction that was broadcast to a chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+	/// The transaction was included in a block and executed successfully.
+	Included,
+	/// The transaction was included in a block, but its execution failed.
+	Failed(String),
+	/// The chain doesn't know the transaction, it may still be in the mempool, may have been
+	/// dropped or the chain can't look up transactions by their hash.
+	Unknown,
+}
+
+/// Storage for the relayer's state, chains are identified by their configured name.
+pub trait StateStore: Send + Sync {
+	/// Returns the height of the last finality event that was fully processed for the chain.
+	fn last_processed_height(&self, chain: &str) -> Result<Option<Height>, Error>;
+
+	/// Records the height of the last finality event that was fully processed for the chain.
+	fn set_last_processed_height(&self, chain: &str, height: Height) -> Result<(), Error>;
+
+	/// Returns the sequences of packets sent on the channel that haven't been delivered yet.
+	fn in_flight_sequences(
+		&self,
+		chain: &str,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+	) -> Result<Vec<u64>, Error>;
+
+	/// Replaces the in-flight packet sequences of the channel.
+	fn set_in_flight_sequences(
+		&self,
+		chain: &str,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+		sequences: Vec<u64>,
+	) -> Result<(), Error>;
+
+	/// Returns the transactions submitted to the chain that haven't been confirmed yet.
+	fn pending_transactions(&self, chain: &str) -> Result<Vec<PendingTransaction>, Error>;
+
+	/// Records a transaction that is about to be submitted to the chain.
+	fn add_pending_transaction(&self, chain: &str, tx: PendingTransaction) -> Result<(), Error>;
+
+	/// Removes a transaction once it's been confirmed, or once it's known to have failed.
+	fn remove_pending_transaction(&self, chain: &str, tx_id: &str) -> Result<(), Error>;
+}
+
+/// A shared handle to a [`StateStore`].
+pub type SharedStateStore = Arc<dyn StateStore>;
+
+fn channel_key(chain: &str, channel_id: &ChannelId, port_id: &PortId) -> String {
+	format!("{}/{}/{}", chain, port_id, channel_id)
+}
+
+fn pending_key(chain: &str, tx_id: &str) -> String {
+	format!("{}/{}", chain, tx_id)
+}
+
+/// [`StateStore`] backed by an embedded sled database.
+#[derive(Clone)]
+pub struct SledStore {
+	heights: sled::Tree,
+	in_flight: sled::Tree,
+	pending: sled::Tree,
+}
+
+impl SledStore {
+	/// Opens (or creates) the database at the given path.
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+		let db = sled::open(path)?;
+		Ok(Self {
+			heights: db.open_tree("last_processed_height")?,
+			in_flight: db.open_tree("in_flight_sequences")?,
+			pending: db.open_tree("pending_transactions")?,
+		})
+	}
+}
+
+impl StateStore for SledStore {
+	fn last_processed_height(&self, chain: &str) -> Result<Option<Height>, Error> {
+		self.heights
+			.get(chain)?
+			.map(|bytes| {
+				let (revision_number, revision_height) = <(u64, u64)>::decode(&mut &*bytes)?;
+				Ok(Height::new(revision_number, revision_height))
+			})
+			.transpose()
+	}
+
+	fn set_last_processed_height(&self, chain: &str, height: Height) -> Result<(), Error> {
+		let value = (height.revision_number, height.revision_height).encode();
+		self.heights.insert(chain, value)?;
+		self.heights.flush()?;
+		Ok(())
+	}
+
+	fn in_flight_sequences(
+		&self,
+		chain: &str,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+	) -> Result<Vec<u64>, Error> {
+		match self.in_flight.get(channel_key(chain, channel_id, port_id))? {
+			Some(bytes) => Ok(Vec::<u64>::decode(&mut &*bytes)?),
+			None => Ok(vec![]),
+		}
+	}
+
+	fn set_in_flight_sequences(
+		&self,
+		chain: &str,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+		sequences: Vec<u64>,
+	) -> Result<(), Error> {
+		self.in_flight
+			.insert(channel_key(chain, channel_id, port_id), sequences.encode())?;
+		self.in_flight.flush()?;
+		Ok(())
+	}
+
+	fn pending_transactions(&self, chain: &str) -> Result<Vec<PendingTransaction>, Error> {
+		self.pending
+			.scan_prefix(format!("{}/", chain))
+			.values()
+			.map(|bytes| Ok(PendingTransaction::decode(&mut &*bytes?)?))
+			.collect()
+	}
+
+	fn add_pending_transaction(&self, chain: &str, tx: PendingTransaction) -> Result<(), Error> {
+		self.pending.insert(pending_key(chain, &tx.tx_id), tx.encode())?;
+		self.pending.flush()?;
+		Ok(())
+	}
+
+	fn remove_pending_transaction(&self, chain: &str, tx_id: &str) -> Result<(), Error> {
+		self.pending.remove(pending_key(chain, tx_id))?;
+		self.pending.flush()?;
+		Ok(())
+	}
+}
+
+/// [`StateStore`] that keeps everything in memory, nothing survives a restart.
+#[derive(Clone, Default)]
+pub struct InMemoryStore {
+	heights: Arc<Mutex<HashMap<String, Height>>>,
+	in_flight: Arc<Mutex<HashMap<String, Vec<u64>>>>,
+	pending: Arc<Mutex<HashMap<String, PendingTransaction>>>,
+}
+
+impl StateStore for InMemoryStore {
+	fn last_processed_height(&self, chain: &str) -> Result<Option<Height>, Error> {
+		Ok(self.heights.lock().unwrap().get(chain).copied())
+	}
+
+	fn set_last_processed_height(&self, chain: &str, height: Height) -> Result<(), Error> {
+		self.heights.lock().unwrap().insert(chain.to_string(), height);
+		Ok(())
+	}
+
+	fn in_flight_sequences(
+		&self,
+		chain: &str,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+	) -> Result<Vec<u64>, Error> {
+		let key = channel_key(chain, channel_id, port_id);
+		Ok(self.in_flight.lock().unwrap().get(&key).cloned().unwrap_or_default())
+	}
+
+	fn set_in_flight_sequences(
+		&self,
+		chain: &str,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+		sequences: Vec<u64>,
+	) -> Result<(), Error> {
+		self.in_flight
+			.lock()
+			.unwrap()
+			.insert(channel_key(chain, channel_id, port_id), sequences);
+		Ok(())
+	}
+
+	fn pending_transactions(&self, chain: &str) -> Result<Vec<PendingTransaction>, Error> {
+		let prefix = format!("{}/", chain);
+		Ok(self
+			.pending
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|(key, _)| key.starts_with(&prefix))
+			.map(|(_, tx)| tx.clone())
+			.collect())
+	}
+
+	fn add_pending_transaction(&self, chain: &str, tx: PendingTransaction) -> Result<(), Error> {
+		self.pending.lock().unwrap().insert(pending_key(chain, &tx.tx_id), tx);
+		Ok(())
+	}
+
+	fn remove_pending_transaction(&self, chain: &str, tx_id: &str) -> Result<(), Error> {
+		self.pending.lock().unwrap().remove(&pending_key(chain, tx_id));
+		Ok(())
+	}
+}
+
+/// Wraps a [`StateStore`] so that the state of different relay paths that share chains is kept
+/// apart, every chain name is prefixed with the path's name.
+#[derive(Clone)]
+pub struct PrefixedStore {
+	prefix: String,
+	inner: SharedStateStore,
+}
+
+impl PrefixedStore {
+	pub fn new(prefix: impl Into<String>, inner: SharedStateStore) -> Self {
+		Self { prefix: prefix.into(), inner }
+	}
+
+	fn key(&self, chain: &str) -> String {
+		format!("{}:{}", self.prefix, chain)
+	}
+}
+
+impl StateStore for PrefixedStore {
+	fn last_processed_height(&self, chain: &str) -> Result<Option<Height>, Error> {
+		self.inner.last_processed_height(&self.key(chain))
+	}
+
+	fn set_last_processed_height(&self, chain: &str, height: Height) -> Result<(), Error> {
+		self.inner.set_last_processed_height(&self.key(chain), height)
+	}
+
+	fn in_flight_sequences(
+		&self,
+		chain: &str,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+	) -> Result<Vec<u64>, Error> {
+		self.inner.in_flight_sequences(&self.key(chain), channel_id, port_id)
+	}
+
+	fn set_in_flight_sequences(
+		&self,
+		chain: &str,
+		channel_id: &ChannelId,
+		port_id: &PortId,
+		sequences: Vec<u64>,
+	) -> Result<(), Error> {
+		self.inner
+			.set_in_flight_sequences(&self.key(chain), channel_id, port_id, sequences)
+	}
+
+	fn pending_transactions(&self, chain: &str) -> Result<Vec<PendingTransaction>, Error> {
+		self.inner.pending_transactions(&self.key(chain))
+	}
+
+	fn add_pending_transaction(&self, chain: &str, tx: PendingTransaction) -> Result<(), Error> {
+		self.inner.add_pending_transaction(&self.key(chain), tx)
+	}
+
+	fn remove_pending_transaction(&self, chain: &str, tx_id: &str) -> Result<(), Error> {
+		self.inner.remove_pending_transaction(&self.key(chain), tx_id)
+	}
+}
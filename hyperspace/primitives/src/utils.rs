@@ -15,6 +15,9 @@
 use crate::{mock::LocalClientTypes, Chain, TestProvider};
 use futures::{future, StreamExt};
 use ibc::{
+	applications::fee::msgs::{
+		register_counterparty_payee::MsgRegisterCounterpartyPayee, register_payee::MsgRegisterPayee,
+	},
 	core::{
 		ics02_client::msgs::create_client::MsgCreateAnyClient,
 		ics03_connection::{connection::Counterparty, msgs::conn_open_init::MsgConnectionOpenInit},
@@ -28,6 +31,7 @@ use ibc::{
 	},
 	events::IbcEvent,
 	protobuf::Protobuf,
+	signer::Signer,
 	tx_msg::Msg,
 };
 use ibc_proto::google::protobuf::Any;
@@ -92,6 +96,28 @@ pub async fn create_clients(
 	Ok((client_id_a_on_b, client_id_b_on_a))
 }
 
+/// Creates a new light client of `chain_b` on `chain_a`, to be used as the substitute when
+/// recovering a frozen or expired client of `chain_b` through governance.
+pub async fn create_substitute_client(
+	chain_a: &impl Chain,
+	chain_b: &impl Chain,
+) -> Result<ClientId, anyhow::Error> {
+	let (client_state, consensus_state) = chain_b.initialize_client_state().await?;
+
+	let msg = MsgCreateAnyClient::<LocalClientTypes> {
+		client_state,
+		consensus_state,
+		signer: chain_a.account_id(),
+	};
+
+	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
+
+	let tx_id = chain_a.submit(vec![msg]).await?;
+	let client_id = chain_a.query_client_id_from_tx_hash(tx_id).await?;
+
+	Ok(client_id)
+}
+
 /// Completes the connection handshake process
 /// The relayer process must be running before this function is executed
 pub async fn create_connection(
@@ -187,3 +213,36 @@ pub async fn create_channel(
 
 	Ok((channel_id_a, channel_id_b))
 }
+
+/// Registers the address the relayer is paid its ICS-29 acknowledgement and timeout fees to for
+/// packets sent over the channel of `chain`.
+pub async fn register_payee(
+	chain: &impl Chain,
+	port_id: PortId,
+	channel_id: ChannelId,
+	payee: Signer,
+) -> Result<(), anyhow::Error> {
+	let msg = MsgRegisterPayee { port_id, channel_id, relayer: chain.account_id(), payee };
+	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
+	chain.submit(vec![msg]).await?;
+	Ok(())
+}
+
+/// Registers the address on the counterparty chain the relayer is paid its ICS-29 receive fees to
+/// for packets received over the channel of `chain`.
+pub async fn register_counterparty_payee(
+	chain: &impl Chain,
+	port_id: PortId,
+	channel_id: ChannelId,
+	counterparty_payee: String,
+) -> Result<(), anyhow::Error> {
+	let msg = MsgRegisterCounterpartyPayee {
+		port_id,
+		channel_id,
+		relayer: chain.account_id(),
+		counterparty_payee,
+	};
+	let msg = Any { type_url: msg.type_url(), value: msg.encode_vec()? };
+	chain.submit(vec![msg]).await?;
+	Ok(())
+}
@@ -0,0 +1,84 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the relayer [`StateStore`] implementations.
+
+use hyperspace_primitives::store::{
+	InMemoryStore, PendingTransaction, PrefixedStore, SledStore, StateStore,
+};
+use ibc::{
+	core::ics24_host::identifier::{ChannelId, PortId},
+	Height,
+};
+use std::{str::FromStr, sync::Arc};
+
+fn exercise(store: &dyn StateStore) {
+	assert_eq!(store.last_processed_height("a").unwrap(), None);
+	store.set_last_processed_height("a", Height::new(1, 10)).unwrap();
+	store.set_last_processed_height("a", Height::new(1, 12)).unwrap();
+	assert_eq!(store.last_processed_height("a").unwrap(), Some(Height::new(1, 12)));
+	assert_eq!(store.last_processed_height("b").unwrap(), None);
+
+	let channel_id = ChannelId::new(0);
+	let port_id = PortId::from_str("transfer").unwrap();
+	assert!(store.in_flight_sequences("a", &channel_id, &port_id).unwrap().is_empty());
+	store
+		.set_in_flight_sequences("a", &channel_id, &port_id, vec![1, 2, 5])
+		.unwrap();
+	assert_eq!(store.in_flight_sequences("a", &channel_id, &port_id).unwrap(), vec![1, 2, 5]);
+	assert!(store.in_flight_sequences("b", &channel_id, &port_id).unwrap().is_empty());
+
+	let tx = PendingTransaction {
+		tx_id: "1".to_string(),
+		type_urls: vec!["/ibc.core.channel.v1.MsgRecvPacket".to_string()],
+		submitted_at: 100,
+	};
+	store.add_pending_transaction("a", tx.clone()).unwrap();
+	assert_eq!(store.pending_transactions("a").unwrap(), vec![tx]);
+	assert!(store.pending_transactions("b").unwrap().is_empty());
+	store.remove_pending_transaction("a", "1").unwrap();
+	assert!(store.pending_transactions("a").unwrap().is_empty());
+}
+
+#[test]
+fn in_memory_store_roundtrip() {
+	exercise(&InMemoryStore::default());
+}
+
+#[test]
+fn sled_store_roundtrip_and_persistence() {
+	let path = std::env::temp_dir().join(format!("hyperspace-store-{}", std::process::id()));
+	let _ = std::fs::remove_dir_all(&path);
+	{
+		let store = SledStore::open(&path).unwrap();
+		exercise(&store);
+	}
+
+	// state survives reopening the database
+	let store = SledStore::open(&path).unwrap();
+	assert_eq!(store.last_processed_height("a").unwrap(), Some(Height::new(1, 12)));
+	drop(store);
+	std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn prefixed_stores_are_isolated() {
+	let inner = Arc::new(InMemoryStore::default());
+	let path_a = PrefixedStore::new("path-a", inner.clone());
+	let path_b = PrefixedStore::new("path-b", inner);
+
+	path_a.set_last_processed_height("chain", Height::new(0, 5)).unwrap();
+	assert_eq!(path_b.last_processed_height("chain").unwrap(), None);
+	exercise(&path_b);
+}
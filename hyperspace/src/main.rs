@@ -28,6 +28,7 @@ async fn main() -> Result<()> {
 
 	match &cli.subcommand {
 		Subcommand::Relay(cmd) => cmd.run().await,
+		Subcommand::RelayPaths(cmd) => cmd.run_paths().await,
 		Subcommand::CreateClients(cmd) => {
 			let new_config = cmd.create_clients().await?;
 			let config = cmd.new_config.as_ref().cloned().unwrap_or_else(|| cmd.config.clone());
@@ -50,5 +51,9 @@ async fn main() -> Result<()> {
 				.map_err(|e| anyhow!(e))
 		},
 		Subcommand::Fish(cmd) => cmd.fish().await,
+		Subcommand::RegisterPayee(cmd) => cmd.register_payee().await,
+		Subcommand::RecoverClient(cmd) => cmd.recover_client().await,
+		Subcommand::ClearPackets(cmd) => cmd.clear_packets().await,
+		Subcommand::Keys(cmd) => cmd.run(),
 	}
 }
@@ -19,7 +19,7 @@ use futures::{future, StreamExt};
 use hyperspace_core::send_packet_relay::set_relay_status;
 use hyperspace_primitives::{
 	utils::{create_channel, create_connection, timeout_after, timeout_future},
-	TestProvider,
+	IbcProvider, TestProvider,
 };
 use ibc::{
 	applications::transfer::{msgs::transfer::MsgTransfer, Amount, PrefixedCoin, VERSION},
@@ -62,9 +62,19 @@ where
 	let client_b_clone = chain_b.clone();
 	// Start relayer loop
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
 	// check if an open transfer channel exists
 	let (latest_height, ..) = chain_a.latest_height_and_timestamp().await.unwrap();
@@ -483,9 +493,19 @@ pub async fn ibc_messaging_packet_height_timeout_with_connection_delay<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
 	send_packet_and_assert_height_timeout(chain_a, chain_b, channel_id).await;
 	handle.abort()
@@ -512,9 +532,19 @@ pub async fn ibc_messaging_packet_timestamp_timeout_with_connection_delay<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
 	send_packet_and_assert_timestamp_timeout(chain_a, chain_b, channel_id).await;
 	handle.abort()
@@ -540,9 +570,19 @@ where
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
 	send_packet_with_connection_delay(chain_a, chain_b, channel_id).await;
 	handle.abort()
@@ -567,9 +607,19 @@ where
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
 	send_channel_close_init_and_assert_channel_close_confirm(chain_a, chain_b, channel_id).await;
 	handle.abort()
@@ -594,9 +644,19 @@ where
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
 	send_packet_and_assert_timeout_on_channel_close(chain_a, chain_b, channel_id).await;
 	handle.abort()
@@ -17,7 +17,7 @@ use futures::future;
 use hyperspace_core::send_packet_relay::set_relay_status;
 use hyperspace_primitives::{
 	utils::{create_channel, create_connection},
-	TestProvider,
+	IbcProvider, TestProvider,
 };
 use ibc::{
 	core::{
@@ -51,9 +51,19 @@ where
 	let client_b_clone = chain_b.clone();
 	// Start relayer loop
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
 	// check if an open ping channel exists
 	let (latest_height, ..) = chain_a.latest_height_and_timestamp().await.unwrap();
@@ -168,11 +178,13 @@ async fn send_ordered_packet_and_assert_acknowledgement<A, B>(
 	.await;
 }
 
-/// Send a packet on an ordered channel and assert timeout
+/// Send a packet on an ordered channel, assert timeout and that the timeout closed both ends of
+/// the channel
 async fn send_ordered_packet_and_assert_timeout<A, B>(
 	chain_a: &A,
 	chain_b: &B,
 	channel_id: ChannelId,
+	port_id: PortId,
 ) where
 	A: TestProvider,
 	A::FinalityEvent: Send + Sync,
@@ -217,10 +229,51 @@ async fn send_ordered_packet_and_assert_timeout<A, B>(
 	)
 	.await;
 
+	// the timeout closes the channel on chain a, the relayer should close it on chain b.
+	let close_future = chain_b
+		.ibc_events()
+		.await
+		.skip_while(|ev| future::ready(!matches!(ev, IbcEvent::CloseConfirmChannel(_))))
+		.take(1)
+		.collect::<Vec<_>>();
+
 	set_relay_status(true);
 
 	assert_timeout_packet(chain_a, 130).await;
 	log::info!(target: "hyperspace", "🚀🚀 Timeout packet successfully processed for ordered channel");
+
+	timeout_future(
+		close_future,
+		10 * 60,
+		format!("Didn't see CloseConfirmChannel message on {}", chain_b.name()),
+	)
+	.await;
+
+	let (latest_height, ..) = chain_a.latest_height_and_timestamp().await.unwrap();
+	let channel_end = chain_a
+		.query_channel_end(latest_height, channel_id, port_id)
+		.await
+		.unwrap()
+		.channel
+		.unwrap();
+	let channel_end = ChannelEnd::try_from(channel_end).unwrap();
+	assert_eq!(channel_end.state, State::Closed);
+
+	let counterparty = channel_end.counterparty();
+	let (latest_height, ..) = chain_b.latest_height_and_timestamp().await.unwrap();
+	let counterparty_channel_end = chain_b
+		.query_channel_end(
+			latest_height,
+			counterparty.channel_id.unwrap(),
+			counterparty.port_id.clone(),
+		)
+		.await
+		.unwrap()
+		.channel
+		.unwrap();
+	let counterparty_channel_end = ChannelEnd::try_from(counterparty_channel_end).unwrap();
+	assert_eq!(counterparty_channel_end.state, State::Closed);
+	log::info!(target: "hyperspace", "🚀🚀 Ordered channel successfully closed on both chains");
 }
 
 ///
@@ -252,9 +305,19 @@ pub async fn ibc_messaging_ordered_packet_with_connection_delay<A, B>(
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
 	send_ordered_packet_and_assert_acknowledgement(chain_a, chain_b, channel_id).await;
 	handle.abort()
@@ -285,14 +348,24 @@ pub async fn ibc_messaging_ordered_packet_timeout<A, B>(
 	// Set channel whitelist and restart relayer loop
 	handle.abort();
 	chain_a.set_channel_whitelist(vec![(channel_id, port_id.clone())]);
-	chain_b.set_channel_whitelist(vec![(channel_b, port_id)]);
+	chain_b.set_channel_whitelist(vec![(channel_b, port_id.clone())]);
 	let client_a_clone = chain_a.clone();
 	let client_b_clone = chain_b.clone();
 	let handle = tokio::task::spawn(async move {
-		hyperspace_core::relay(client_a_clone, client_b_clone, None, None, None)
-			.await
-			.unwrap()
+		hyperspace_core::relay(
+			client_a_clone,
+			client_b_clone,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+		.await
+		.unwrap()
 	});
-	send_ordered_packet_and_assert_timeout(chain_a, chain_b, channel_id).await;
+	send_ordered_packet_and_assert_timeout(chain_a, chain_b, channel_id, port_id).await;
 	handle.abort()
 }
@@ -111,8 +111,11 @@ async fn setup_clients() -> (ParachainClient<DefaultConfig>, ParachainClient<Def
 		commitment_prefix: args.connection_prefix_b.as_bytes().to_vec().into(),
 		ss58_version: 42,
 		channel_whitelist: vec![],
+		min_relay_fees: vec![],
 		finality_protocol: FinalityProtocol::Grandpa,
-		private_key: "//Alice".to_string(),
+		private_key: Some("//Alice".to_string()),
+		key_name: None,
+		keystore_path: None,
 		key_type: "sr25519".to_string(),
 	};
 	let config_b = ParachainClientConfig {
@@ -123,9 +126,12 @@ async fn setup_clients() -> (ParachainClient<DefaultConfig>, ParachainClient<Def
 		client_id: None,
 		connection_id: None,
 		commitment_prefix: args.connection_prefix_b.as_bytes().to_vec().into(),
-		private_key: "//Alice".to_string(),
+		private_key: Some("//Alice".to_string()),
+		key_name: None,
+		keystore_path: None,
 		ss58_version: 42,
 		channel_whitelist: vec![],
+		min_relay_fees: vec![],
 		finality_protocol: FinalityProtocol::Grandpa,
 		key_type: "sr25519".to_string(),
 	};
@@ -137,7 +143,7 @@ async fn setup_clients() -> (ParachainClient<DefaultConfig>, ParachainClient<Def
 	log::info!(target: "hyperspace", "Waiting for  block production from parachains");
 	let session_length = chain_a.grandpa_prover().session_length().await.unwrap();
 	let _ = chain_a
-		.relay_client
+		.relay_client()
 		.rpc()
 		.subscribe_finalized_block_headers()
 		.await
@@ -680,6 +680,257 @@ impl State {
 		}
 	}
 
+	fn impl_fn_verify_channel_upgrade(&self) -> proc_macro2::TokenStream {
+		let crate_ = &self.crate_ident;
+		let trait_ = &self.current_impl_trait;
+		let error = &self.current_impl_error;
+		let client_state_trait = &self.client_state_trait;
+		let cases = self.clients.iter().map(|client| {
+			let variant_ident = &client.variant_ident;
+			let attrs = &client.attrs;
+			quote! {
+				#(#attrs)*
+				Self::#variant_ident(client) => {
+					let client_type = #client_state_trait::client_type(client_state).to_owned();
+					let client_state = #crate_::downcast!(
+						client_state => Self::ClientState::#variant_ident
+					)
+					.ok_or_else(|| #error::client_args_type_mismatch(client_type))?;
+
+					#trait_::verify_channel_upgrade(
+						client,
+						ctx,
+						client_id,
+						client_state,
+						height,
+						connection_end,
+						proof,
+						root,
+						port_id,
+						channel_id,
+						upgrade,
+					)
+				}
+			}
+		});
+
+		quote! {
+			fn verify_channel_upgrade<Ctx: #crate_::core::ics26_routing::context::ReaderContext>(
+				&self,
+				ctx: &Ctx,
+				client_id: &#crate_::core::ics24_host::identifier::ClientId,
+				client_state: &Self::ClientState,
+				height: #crate_::core::ics02_client::height::Height,
+				connection_end: &#crate_::core::ics03_connection::connection::ConnectionEnd,
+				proof: &#crate_::core::ics23_commitment::commitment::CommitmentProofBytes,
+				root: &#crate_::core::ics23_commitment::commitment::CommitmentRoot,
+				port_id: &#crate_::core::ics24_host::identifier::PortId,
+				channel_id: &#crate_::core::ics24_host::identifier::ChannelId,
+				upgrade: &#crate_::core::ics04_channel::upgrade::Upgrade,
+			) -> ::core::result::Result<(), #error> {
+				match self {
+					#(#cases)*
+				}
+			}
+		}
+	}
+
+	fn impl_fn_verify_channel_upgrade_error(&self) -> proc_macro2::TokenStream {
+		let crate_ = &self.crate_ident;
+		let trait_ = &self.current_impl_trait;
+		let error = &self.current_impl_error;
+		let client_state_trait = &self.client_state_trait;
+		let cases = self.clients.iter().map(|client| {
+			let variant_ident = &client.variant_ident;
+			let attrs = &client.attrs;
+			quote! {
+				#(#attrs)*
+				Self::#variant_ident(client) => {
+					let client_type = #client_state_trait::client_type(client_state).to_owned();
+					let client_state = #crate_::downcast!(
+						client_state => Self::ClientState::#variant_ident
+					)
+					.ok_or_else(|| #error::client_args_type_mismatch(client_type))?;
+
+					#trait_::verify_channel_upgrade_error(
+						client,
+						ctx,
+						client_id,
+						client_state,
+						height,
+						connection_end,
+						proof,
+						root,
+						port_id,
+						channel_id,
+						error_receipt,
+					)
+				}
+			}
+		});
+
+		quote! {
+			fn verify_channel_upgrade_error<Ctx: #crate_::core::ics26_routing::context::ReaderContext>(
+				&self,
+				ctx: &Ctx,
+				client_id: &#crate_::core::ics24_host::identifier::ClientId,
+				client_state: &Self::ClientState,
+				height: #crate_::core::ics02_client::height::Height,
+				connection_end: &#crate_::core::ics03_connection::connection::ConnectionEnd,
+				proof: &#crate_::core::ics23_commitment::commitment::CommitmentProofBytes,
+				root: &#crate_::core::ics23_commitment::commitment::CommitmentRoot,
+				port_id: &#crate_::core::ics24_host::identifier::PortId,
+				channel_id: &#crate_::core::ics24_host::identifier::ChannelId,
+				error_receipt: &#crate_::core::ics04_channel::upgrade::ErrorReceipt,
+			) -> ::core::result::Result<(), #error> {
+				match self {
+					#(#cases)*
+				}
+			}
+		}
+	}
+
+	fn impl_fn_verify_membership(&self) -> proc_macro2::TokenStream {
+		let crate_ = &self.crate_ident;
+		let trait_ = &self.current_impl_trait;
+		let error = &self.current_impl_error;
+		let client_state_trait = &self.client_state_trait;
+		let cases = self.clients.iter().map(|client| {
+			let variant_ident = &client.variant_ident;
+			let attrs = &client.attrs;
+			quote! {
+				#(#attrs)*
+				Self::#variant_ident(client) => {
+					let client_type = #client_state_trait::client_type(client_state).to_owned();
+					let client_state = #crate_::downcast!(
+						client_state => Self::ClientState::#variant_ident
+					)
+					.ok_or_else(|| #error::client_args_type_mismatch(client_type))?;
+
+					#trait_::verify_membership(
+						client,
+						ctx,
+						client_state,
+						height,
+						prefix,
+						proof,
+						root,
+						path,
+						value,
+					)
+				}
+			}
+		});
+
+		quote! {
+			fn verify_membership<Ctx: #crate_::core::ics26_routing::context::ReaderContext>(
+				&self,
+				ctx: &Ctx,
+				client_state: &Self::ClientState,
+				height: #crate_::core::ics02_client::height::Height,
+				prefix: &#crate_::core::ics23_commitment::commitment::CommitmentPrefix,
+				proof: &#crate_::core::ics23_commitment::commitment::CommitmentProofBytes,
+				root: &#crate_::core::ics23_commitment::commitment::CommitmentRoot,
+				path: #crate_::core::ics24_host::Path,
+				value: ::alloc::vec::Vec<u8>,
+			) -> ::core::result::Result<(), #error> {
+				match self {
+					#(#cases)*
+				}
+			}
+		}
+	}
+
+	fn impl_fn_verify_non_membership(&self) -> proc_macro2::TokenStream {
+		let crate_ = &self.crate_ident;
+		let trait_ = &self.current_impl_trait;
+		let error = &self.current_impl_error;
+		let client_state_trait = &self.client_state_trait;
+		let cases = self.clients.iter().map(|client| {
+			let variant_ident = &client.variant_ident;
+			let attrs = &client.attrs;
+			quote! {
+				#(#attrs)*
+				Self::#variant_ident(client) => {
+					let client_type = #client_state_trait::client_type(client_state).to_owned();
+					let client_state = #crate_::downcast!(
+						client_state => Self::ClientState::#variant_ident
+					)
+					.ok_or_else(|| #error::client_args_type_mismatch(client_type))?;
+
+					#trait_::verify_non_membership(
+						client,
+						ctx,
+						client_state,
+						height,
+						prefix,
+						proof,
+						root,
+						path,
+					)
+				}
+			}
+		});
+
+		quote! {
+			fn verify_non_membership<Ctx: #crate_::core::ics26_routing::context::ReaderContext>(
+				&self,
+				ctx: &Ctx,
+				client_state: &Self::ClientState,
+				height: #crate_::core::ics02_client::height::Height,
+				prefix: &#crate_::core::ics23_commitment::commitment::CommitmentPrefix,
+				proof: &#crate_::core::ics23_commitment::commitment::CommitmentProofBytes,
+				root: &#crate_::core::ics23_commitment::commitment::CommitmentRoot,
+				path: #crate_::core::ics24_host::Path,
+			) -> ::core::result::Result<(), #error> {
+				match self {
+					#(#cases)*
+				}
+			}
+		}
+	}
+
+	fn impl_fn_update_state_on_proof(&self) -> proc_macro2::TokenStream {
+		let crate_ = &self.crate_ident;
+		let trait_ = &self.current_impl_trait;
+		let error = &self.current_impl_error;
+		let client_state_trait = &self.client_state_trait;
+		let cases = self.clients.iter().map(|client| {
+			let variant_ident = &client.variant_ident;
+			let attrs = &client.attrs;
+			quote! {
+				#(#attrs)*
+				Self::#variant_ident(client) => {
+					let client_type = #client_state_trait::client_type(client_state).to_owned();
+					let client_state = #crate_::downcast!(
+						client_state => Self::ClientState::#variant_ident
+					)
+					.ok_or_else(|| #error::client_args_type_mismatch(client_type))?;
+
+					let states = #trait_::update_state_on_proof(client, client_state, proof)?;
+					Ok(states.map(|(client_state, consensus_state)| {
+						(
+							Self::ClientState::#variant_ident(client_state),
+							Self::ConsensusState::#variant_ident(consensus_state),
+						)
+					}))
+				}
+			}
+		});
+
+		quote! {
+			fn update_state_on_proof(
+				&self,
+				client_state: &Self::ClientState,
+				proof: &#crate_::core::ics23_commitment::commitment::CommitmentProofBytes,
+			) -> ::core::result::Result<::core::option::Option<(Self::ClientState, Self::ConsensusState)>, #error> {
+				match self {
+					#(#cases)*
+				}
+			}
+		}
+	}
+
 	pub fn impl_client_def(&mut self) -> proc_macro2::TokenStream {
 		let crate_ = &self.crate_ident;
 		self.current_impl_trait =
@@ -707,6 +958,11 @@ impl State {
 		let fn_verify_packet_acknowledgement = self.impl_fn_verify_packet_acknowledgement();
 		let fn_verify_next_sequence_recv = self.impl_fn_verify_next_sequence_recv();
 		let fn_verify_packet_receipt_absence = self.impl_fn_verify_packet_receipt_absence();
+		let fn_verify_channel_upgrade = self.impl_fn_verify_channel_upgrade();
+		let fn_verify_channel_upgrade_error = self.impl_fn_verify_channel_upgrade_error();
+		let fn_verify_membership = self.impl_fn_verify_membership();
+		let fn_verify_non_membership = self.impl_fn_verify_non_membership();
+		let fn_update_state_on_proof = self.impl_fn_update_state_on_proof();
 
 		quote! {
 			impl #impl_generics #client_def_trait for #this #ty_generics #where_clause {
@@ -727,6 +983,11 @@ impl State {
 				#fn_verify_packet_acknowledgement
 				#fn_verify_next_sequence_recv
 				#fn_verify_packet_receipt_absence
+				#fn_verify_channel_upgrade
+				#fn_verify_channel_upgrade_error
+				#fn_verify_membership
+				#fn_verify_non_membership
+				#fn_update_state_on_proof
 			}
 		}
 	}
@@ -0,0 +1,106 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use subtle_encoding::base64;
+
+use super::error::Error;
+use crate::prelude::*;
+
+/// The acknowledgement written by the fee middleware on fee enabled channels, it wraps the
+/// acknowledgement of the application with the address the forward relayer wants to be paid to on
+/// the source chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IncentivizedAcknowledgement {
+	pub app_acknowledgement: Vec<u8>,
+	pub forward_relayer_address: String,
+	pub underlying_app_success: bool,
+}
+
+/// JSON representation of the acknowledgement, bytes are base64 encoded as in the proto3 JSON
+/// mapping.
+#[derive(Serialize, Deserialize)]
+struct RawIncentivizedAcknowledgement {
+	app_acknowledgement: String,
+	forward_relayer_address: String,
+	underlying_app_success: bool,
+}
+
+impl IncentivizedAcknowledgement {
+	pub fn new(app_acknowledgement: Vec<u8>, forward_relayer_address: String) -> Self {
+		let underlying_app_success = is_app_success(&app_acknowledgement);
+		Self { app_acknowledgement, forward_relayer_address, underlying_app_success }
+	}
+
+	pub fn encode(&self) -> Vec<u8> {
+		let raw = RawIncentivizedAcknowledgement {
+			app_acknowledgement: String::from_utf8(base64::encode(&self.app_acknowledgement))
+				.expect("base64 encoded bytes are valid UTF8"),
+			forward_relayer_address: self.forward_relayer_address.clone(),
+			underlying_app_success: self.underlying_app_success,
+		};
+		serde_json::to_vec(&raw).expect("infallible Serialize impl failed")
+	}
+
+	pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+		let raw = serde_json::from_slice::<RawIncentivizedAcknowledgement>(bytes)
+			.map_err(|_| Error::ack_deserialization())?;
+		Ok(Self {
+			app_acknowledgement: base64::decode(raw.app_acknowledgement.as_bytes())
+				.map_err(|_| Error::ack_deserialization())?,
+			forward_relayer_address: raw.forward_relayer_address,
+			underlying_app_success: raw.underlying_app_success,
+		})
+	}
+}
+
+impl Display for IncentivizedAcknowledgement {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		let ack = self.encode();
+		write!(f, "{}", String::from_utf8_lossy(&ack))
+	}
+}
+
+/// Applications in this repo write JSON acknowledgements that hold an `error` field when the
+/// packet failed, anything else is considered a success.
+fn is_app_success(ack: &[u8]) -> bool {
+	#[derive(Deserialize)]
+	struct ErrorAck {
+		#[allow(dead_code)]
+		error: String,
+	}
+	serde_json::from_slice::<ErrorAck>(ack).is_err()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn incentivized_ack_roundtrip() {
+		let ack =
+			IncentivizedAcknowledgement::new(br#"{"result":"AQ=="}"#.to_vec(), "relayer".into());
+		assert!(ack.underlying_app_success);
+		assert_eq!(IncentivizedAcknowledgement::decode(&ack.encode()).unwrap(), ack);
+	}
+
+	#[test]
+	fn error_ack_is_not_successful() {
+		let ack =
+			IncentivizedAcknowledgement::new(br#"{"error":"failed"}"#.to_vec(), "relayer".into());
+		assert!(!ack.underlying_app_success);
+	}
+}
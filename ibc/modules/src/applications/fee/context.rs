@@ -0,0 +1,92 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{error::Error, fee::PacketFee};
+use crate::{
+	applications::transfer::context::BankKeeper,
+	core::{
+		ics04_channel::{context::ChannelReader, packet::Sequence},
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	prelude::*,
+	signer::Signer,
+};
+
+pub trait FeeReader: ChannelReader {
+	type AccountId: TryFrom<Signer>;
+
+	/// Returns the account holding the fees in escrow.
+	fn get_fee_escrow_address(&self) -> Result<<Self as FeeReader>::AccountId, Error>;
+
+	/// Returns true if the fee middleware is enabled on the channel.
+	fn is_fee_enabled(&self, port_id: &PortId, channel_id: &ChannelId) -> bool;
+
+	/// Returns the fees escrowed for the packet.
+	fn fees_in_escrow(
+		&self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Vec<PacketFee>;
+
+	/// Returns the sequences of the packets sent over the channel that have fees in escrow.
+	fn sequences_with_fees(&self, port_id: &PortId, channel_id: &ChannelId) -> Vec<Sequence>;
+
+	/// Returns the address the relayer registered to be paid to on the channel.
+	fn payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<Signer>;
+
+	/// Returns the address on the counterparty chain the relayer registered to be paid its receive
+	/// fees to.
+	fn counterparty_payee(&self, relayer: &Signer, channel_id: &ChannelId) -> Option<String>;
+}
+
+pub trait FeeKeeper: BankKeeper<AccountId = <Self as FeeKeeper>::AccountId> {
+	type AccountId;
+
+	fn set_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId);
+
+	fn delete_fee_enabled(&mut self, port_id: &PortId, channel_id: &ChannelId);
+
+	fn store_fees_in_escrow(
+		&mut self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		fees: Vec<PacketFee>,
+	);
+
+	fn delete_fees_in_escrow(
+		&mut self,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	);
+
+	fn store_payee(&mut self, relayer: &Signer, channel_id: &ChannelId, payee: &Signer);
+
+	fn store_counterparty_payee(
+		&mut self,
+		relayer: &Signer,
+		channel_id: &ChannelId,
+		counterparty_payee: &str,
+	);
+}
+
+/// Captures all the dependencies the fee middleware requires to escrow and distribute fees.
+pub trait FeeContext:
+	FeeKeeper<AccountId = <Self as FeeContext>::AccountId>
+	+ FeeReader<AccountId = <Self as FeeContext>::AccountId>
+{
+	type AccountId: TryFrom<Signer>;
+}
@@ -0,0 +1,103 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use flex_error::{define_error, TraceError};
+
+use crate::{
+	applications::transfer::error::Error as Ics20Error,
+	core::{
+		ics04_channel::{error as channel_error, packet::Sequence},
+		ics24_host::{
+			error::ValidationError,
+			identifier::{ChannelId, PortId},
+		},
+	},
+	prelude::*,
+	signer::SignerError,
+};
+
+define_error! {
+	#[derive(Debug, PartialEq, Eq)]
+	Error {
+		Ics04Channel
+			[ channel_error::Error ]
+			|_ | { "Ics04 channel error" },
+
+		Ics20Transfer
+			[ Ics20Error ]
+			|_ | { "failed to transfer fee" },
+
+		InvalidPortId
+			{ context: String }
+			[ ValidationError ]
+			| e | { format_args!("invalid port identifier: {0}", e.context) },
+
+		InvalidChannelId
+			{ context: String }
+			[ ValidationError ]
+			| e | { format_args!("invalid channel identifier: {0}", e.context) },
+
+		Signer
+			[ SignerError ]
+			| _ | { "failed to parse signer" },
+
+		ParseAccountFailure
+			| _ | { "failed to parse as AccountId" },
+
+		InvalidCoin
+			{ coin: String }
+			| e | { format_args!("invalid coin: {0}", e.coin) },
+
+		EmptyFee
+			| _ | { "packet fee must not be empty" },
+
+		MissingFee
+			| _ | { "missing fee" },
+
+		MissingPacketId
+			| _ | { "missing packet id" },
+
+		EmptyCounterpartyPayee
+			| _ | { "counterparty payee must not be empty" },
+
+		RelayersNotSupported
+			| _ | { "specifying the relayers of a packet fee is not supported" },
+
+		FeeNotEnabled
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | { format_args!("fee is not enabled on channel {0}/{1}", e.port_id, e.channel_id) },
+
+		PacketNotFound
+			{ port_id: PortId, channel_id: ChannelId, sequence: Sequence }
+			| e | { format_args!("no in-flight packet {2} on channel {0}/{1}", e.port_id, e.channel_id, e.sequence) },
+
+		InvalidVersion
+			{ version: String }
+			| e | { format_args!("expected version '{0}', got '{1}'", super::VERSION, e.version) },
+
+		MetadataDeserialization
+			| _ | { "failed to deserialize channel version metadata" },
+
+		AckDeserialization
+			| _ | { "failed to deserialize incentivized acknowledgement" },
+
+		DecodeRawMsg
+			[ TraceError<tendermint_proto::Error> ]
+			| _ | { "error decoding raw msg" },
+
+		UnknownMsgType
+			{ msg_type: String }
+			| e | { format_args!("unknown msg type: {0}", e.msg_type) },
+	}
+}
@@ -0,0 +1,58 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The version of a fee enabled channel is a JSON encoded [`Metadata`] wrapping the version of the
+//! application, channels with any other version are not incentivised.
+
+use serde::{Deserialize, Serialize};
+
+use super::{error::Error, VERSION};
+use crate::{core::ics04_channel::Version, prelude::*};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+	/// The ICS29 protocol version.
+	pub fee_version: String,
+	/// The version of the wrapped application.
+	pub app_version: String,
+}
+
+impl Metadata {
+	pub fn new(app_version: &Version) -> Self {
+		Self { fee_version: VERSION.to_string(), app_version: app_version.to_string() }
+	}
+
+	/// Returns the metadata if this is the version of a fee enabled channel, versions that aren't
+	/// JSON encoded metadata belong to channels without fees.
+	pub fn from_version(version: &Version) -> Result<Option<Self>, Error> {
+		let metadata = match serde_json::from_str::<Self>(&version.to_string()) {
+			Ok(metadata) => metadata,
+			Err(_) => return Ok(None),
+		};
+		if metadata.fee_version != VERSION {
+			return Err(Error::invalid_version(metadata.fee_version))
+		}
+		Ok(Some(metadata))
+	}
+
+	pub fn to_version(&self) -> Version {
+		Version::new(
+			serde_json::to_string(self).expect("Metadata's infallible Serialize impl failed"),
+		)
+	}
+
+	pub fn app_version(&self) -> Version {
+		Version::new(self.app_version.clone())
+	}
+}
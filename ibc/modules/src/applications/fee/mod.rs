@@ -0,0 +1,31 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ICS29 fee middleware, incentivises relayers by escrowing fees for the packets of the wrapped
+//! application and paying them out to the relayers that deliver the packets.
+
+pub mod acknowledgement;
+pub mod context;
+pub mod error;
+pub mod events;
+pub mod fee;
+pub mod metadata;
+pub mod msgs;
+pub mod relay;
+
+/// Module identifier of the fee middleware, only used for its events.
+pub const MODULE_ID_STR: &str = "feeibc";
+
+/// ICS29 version, negotiated alongside the version of the wrapped application.
+pub const VERSION: &str = "ics29-1";
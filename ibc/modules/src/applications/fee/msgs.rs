@@ -0,0 +1,72 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Messages used to pay fees for packets and to register the addresses relayers are paid to.
+
+use super::error::Error;
+use crate::{prelude::*, signer::Signer};
+use ibc_proto::google::protobuf::Any;
+
+pub mod pay_packet_fee;
+pub mod pay_packet_fee_async;
+pub mod register_counterparty_payee;
+pub mod register_payee;
+
+/// All the messages handled by the fee middleware.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeeMsg {
+	PayPacketFee(pay_packet_fee::MsgPayPacketFee),
+	PayPacketFeeAsync(pay_packet_fee_async::MsgPayPacketFeeAsync),
+	RegisterPayee(register_payee::MsgRegisterPayee),
+	RegisterCounterpartyPayee(register_counterparty_payee::MsgRegisterCounterpartyPayee),
+}
+
+impl FeeMsg {
+	/// Returns true if the type url is the one of a fee message.
+	pub fn is_fee_msg(type_url: &str) -> bool {
+		matches!(
+			type_url,
+			pay_packet_fee::TYPE_URL |
+				pay_packet_fee_async::TYPE_URL |
+				register_payee::TYPE_URL |
+				register_counterparty_payee::TYPE_URL
+		)
+	}
+
+	/// The account that signed the message, it pays the fees or is the relayer registering a
+	/// payee.
+	pub fn signer(&self) -> &Signer {
+		match self {
+			FeeMsg::PayPacketFee(msg) => &msg.signer,
+			FeeMsg::PayPacketFeeAsync(msg) => &msg.packet_fee.refund_address,
+			FeeMsg::RegisterPayee(msg) => &msg.relayer,
+			FeeMsg::RegisterCounterpartyPayee(msg) => &msg.relayer,
+		}
+	}
+}
+
+impl TryFrom<Any> for FeeMsg {
+	type Error = Error;
+
+	fn try_from(raw: Any) -> Result<Self, Self::Error> {
+		match raw.type_url.as_str() {
+			pay_packet_fee::TYPE_URL => Ok(FeeMsg::PayPacketFee(raw.try_into()?)),
+			pay_packet_fee_async::TYPE_URL => Ok(FeeMsg::PayPacketFeeAsync(raw.try_into()?)),
+			register_payee::TYPE_URL => Ok(FeeMsg::RegisterPayee(raw.try_into()?)),
+			register_counterparty_payee::TYPE_URL =>
+				Ok(FeeMsg::RegisterCounterpartyPayee(raw.try_into()?)),
+			_ => Err(Error::unknown_msg_type(raw.type_url)),
+		}
+	}
+}
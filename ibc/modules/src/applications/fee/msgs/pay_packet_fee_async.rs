@@ -0,0 +1,102 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::{
+		applications::fee::v1::MsgPayPacketFeeAsync as RawMsgPayPacketFeeAsync,
+		core::channel::v1::PacketId as RawPacketId,
+	},
+};
+use tendermint_proto::Protobuf;
+
+use crate::{
+	applications::fee::{error::Error, fee::PacketFee},
+	core::{
+		ics04_channel::packet::Sequence,
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	prelude::*,
+	tx_msg::Msg,
+};
+
+pub const TYPE_URL: &str = "/ibc.applications.fee.v1.MsgPayPacketFeeAsync";
+
+/// Escrows a fee for a packet that has already been sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgPayPacketFeeAsync {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub sequence: Sequence,
+	pub packet_fee: PacketFee,
+}
+
+impl Msg for MsgPayPacketFeeAsync {
+	type ValidationError = Error;
+	type Raw = RawMsgPayPacketFeeAsync;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		TYPE_URL.to_string()
+	}
+}
+
+impl TryFrom<RawMsgPayPacketFeeAsync> for MsgPayPacketFeeAsync {
+	type Error = Error;
+
+	fn try_from(raw_msg: RawMsgPayPacketFeeAsync) -> Result<Self, Self::Error> {
+		let packet_id = raw_msg.packet_id.ok_or_else(Error::missing_packet_id)?;
+		Ok(MsgPayPacketFeeAsync {
+			port_id: packet_id
+				.port_id
+				.parse()
+				.map_err(|e| Error::invalid_port_id(packet_id.port_id.clone(), e))?,
+			channel_id: packet_id
+				.channel_id
+				.parse()
+				.map_err(|e| Error::invalid_channel_id(packet_id.channel_id.clone(), e))?,
+			sequence: packet_id.sequence.into(),
+			packet_fee: raw_msg.packet_fee.ok_or_else(Error::missing_fee)?.try_into()?,
+		})
+	}
+}
+
+impl From<MsgPayPacketFeeAsync> for RawMsgPayPacketFeeAsync {
+	fn from(domain_msg: MsgPayPacketFeeAsync) -> Self {
+		RawMsgPayPacketFeeAsync {
+			packet_id: Some(RawPacketId {
+				port_id: domain_msg.port_id.to_string(),
+				channel_id: domain_msg.channel_id.to_string(),
+				sequence: domain_msg.sequence.into(),
+			}),
+			packet_fee: Some(domain_msg.packet_fee.into()),
+		}
+	}
+}
+
+impl Protobuf<RawMsgPayPacketFeeAsync> for MsgPayPacketFeeAsync {}
+
+impl TryFrom<Any> for MsgPayPacketFeeAsync {
+	type Error = Error;
+
+	fn try_from(raw: Any) -> Result<Self, Self::Error> {
+		match raw.type_url.as_str() {
+			TYPE_URL => MsgPayPacketFeeAsync::decode_vec(&raw.value).map_err(Error::decode_raw_msg),
+			_ => Err(Error::unknown_msg_type(raw.type_url)),
+		}
+	}
+}
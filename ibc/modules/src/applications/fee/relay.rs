@@ -0,0 +1,310 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handlers of the fee messages, and the escrow and distribution of fees driven by the packet
+//! callbacks of the middleware.
+
+use super::{
+	acknowledgement::IncentivizedAcknowledgement,
+	context::FeeContext,
+	error::Error,
+	events::{
+		DistributeFeeEvent, IncentivizedPacketEvent, RegisterCounterpartyPayeeEvent,
+		RegisterPayeeEvent,
+	},
+	fee::{Fee, PacketFee},
+	msgs::{
+		pay_packet_fee::MsgPayPacketFee, pay_packet_fee_async::MsgPayPacketFeeAsync,
+		register_counterparty_payee::MsgRegisterCounterpartyPayee,
+		register_payee::MsgRegisterPayee, FeeMsg,
+	},
+};
+use crate::{
+	applications::transfer::PrefixedCoin,
+	core::{
+		ics04_channel::packet::{Packet, Sequence},
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	handler::{HandlerOutput, HandlerOutputBuilder},
+	prelude::*,
+	signer::Signer,
+};
+
+/// Executes a fee message.
+pub fn dispatch<Ctx: FeeContext>(ctx: &mut Ctx, msg: FeeMsg) -> Result<HandlerOutput<()>, Error> {
+	let mut output = HandlerOutputBuilder::new();
+	match msg {
+		FeeMsg::PayPacketFee(msg) => pay_packet_fee(ctx, &mut output, msg)?,
+		FeeMsg::PayPacketFeeAsync(msg) => pay_packet_fee_async(ctx, &mut output, msg)?,
+		FeeMsg::RegisterPayee(msg) => register_payee(ctx, &mut output, msg)?,
+		FeeMsg::RegisterCounterpartyPayee(msg) =>
+			register_counterparty_payee(ctx, &mut output, msg)?,
+	}
+	Ok(output.with_result(()))
+}
+
+/// Escrows the fee for the next packet sent over the channel.
+pub fn pay_packet_fee<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgPayPacketFee,
+) -> Result<(), Error> {
+	let sequence = ctx
+		.get_next_sequence_send(&(msg.source_port_id.clone(), msg.source_channel_id))
+		.map_err(Error::ics04_channel)?;
+	let packet_fee = PacketFee { fee: msg.fee, refund_address: msg.signer, relayers: msg.relayers };
+	escrow_packet_fee(
+		ctx,
+		output,
+		&msg.source_port_id,
+		&msg.source_channel_id,
+		sequence,
+		packet_fee,
+	)
+}
+
+/// Escrows the fee for a packet that was sent but hasn't been acknowledged nor timed out yet.
+pub fn pay_packet_fee_async<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgPayPacketFeeAsync,
+) -> Result<(), Error> {
+	ctx.get_packet_commitment(&(msg.port_id.clone(), msg.channel_id, msg.sequence))
+		.map_err(|_| Error::packet_not_found(msg.port_id.clone(), msg.channel_id, msg.sequence))?;
+	escrow_packet_fee(ctx, output, &msg.port_id, &msg.channel_id, msg.sequence, msg.packet_fee)
+}
+
+fn escrow_packet_fee<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	sequence: Sequence,
+	packet_fee: PacketFee,
+) -> Result<(), Error> {
+	if !ctx.is_fee_enabled(port_id, channel_id) {
+		return Err(Error::fee_not_enabled(port_id.clone(), *channel_id))
+	}
+	packet_fee.validate()?;
+
+	let refund_account = packet_fee
+		.refund_address
+		.clone()
+		.try_into()
+		.map_err(|_| Error::parse_account_failure())?;
+	let escrow_account = ctx.get_fee_escrow_address()?;
+	for coin in packet_fee.fee.total() {
+		ctx.send_coins(&refund_account, &escrow_account, &coin)
+			.map_err(Error::ics20_transfer)?;
+	}
+
+	let mut fees = ctx.fees_in_escrow(port_id, channel_id, sequence);
+	fees.push(packet_fee);
+	let total_fee = fees.iter().fold(Fee::default(), |mut total, packet_fee| {
+		total.recv_fee.extend(packet_fee.fee.recv_fee.iter().cloned());
+		total.ack_fee.extend(packet_fee.fee.ack_fee.iter().cloned());
+		total.timeout_fee.extend(packet_fee.fee.timeout_fee.iter().cloned());
+		total
+	});
+	ctx.store_fees_in_escrow(port_id, channel_id, sequence, fees);
+
+	output.emit(
+		IncentivizedPacketEvent {
+			port_id: port_id.clone(),
+			channel_id: *channel_id,
+			sequence,
+			total_fee,
+		}
+		.into(),
+	);
+
+	Ok(())
+}
+
+pub fn register_payee<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgRegisterPayee,
+) -> Result<(), Error> {
+	if !ctx.is_fee_enabled(&msg.port_id, &msg.channel_id) {
+		return Err(Error::fee_not_enabled(msg.port_id, msg.channel_id))
+	}
+	ctx.store_payee(&msg.relayer, &msg.channel_id, &msg.payee);
+	output.emit(
+		RegisterPayeeEvent { relayer: msg.relayer, payee: msg.payee, channel_id: msg.channel_id }
+			.into(),
+	);
+	Ok(())
+}
+
+pub fn register_counterparty_payee<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgRegisterCounterpartyPayee,
+) -> Result<(), Error> {
+	if !ctx.is_fee_enabled(&msg.port_id, &msg.channel_id) {
+		return Err(Error::fee_not_enabled(msg.port_id, msg.channel_id))
+	}
+	if msg.counterparty_payee.trim().is_empty() {
+		return Err(Error::empty_counterparty_payee())
+	}
+	ctx.store_counterparty_payee(&msg.relayer, &msg.channel_id, &msg.counterparty_payee);
+	output.emit(
+		RegisterCounterpartyPayeeEvent {
+			relayer: msg.relayer,
+			counterparty_payee: msg.counterparty_payee,
+			channel_id: msg.channel_id,
+		}
+		.into(),
+	);
+	Ok(())
+}
+
+/// Wraps the acknowledgement written by the application on fee enabled channels, the forward
+/// relayer address is the counterparty payee registered by the relayer, if any.
+/// Asynchronous acknowledgements are left empty and should be wrapped once they're written.
+pub fn on_recv_packet<Ctx: FeeContext>(
+	ctx: &Ctx,
+	packet: &Packet,
+	app_acknowledgement: Vec<u8>,
+	relayer: &Signer,
+) -> Vec<u8> {
+	if app_acknowledgement.is_empty() ||
+		!ctx.is_fee_enabled(&packet.destination_port, &packet.destination_channel)
+	{
+		return app_acknowledgement
+	}
+	let forward_relayer_address =
+		ctx.counterparty_payee(relayer, &packet.destination_channel).unwrap_or_default();
+	IncentivizedAcknowledgement::new(app_acknowledgement, forward_relayer_address).encode()
+}
+
+/// Pays the receive fee to the forward relayer and the acknowledgement fee to the relayer of the
+/// acknowledgement, the timeout fee is refunded.
+/// Returns the acknowledgement of the application that should be passed on to it.
+pub fn on_acknowledgement_packet<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	packet: &Packet,
+	acknowledgement: &[u8],
+	relayer: &Signer,
+) -> Result<Vec<u8>, Error> {
+	if !ctx.is_fee_enabled(&packet.source_port, &packet.source_channel) {
+		return Ok(acknowledgement.to_vec())
+	}
+	let ack = IncentivizedAcknowledgement::decode(acknowledgement)?;
+
+	let fees = ctx.fees_in_escrow(&packet.source_port, &packet.source_channel, packet.sequence);
+	if !fees.is_empty() {
+		let forward_relayer = ack.forward_relayer_address.parse::<Signer>().ok();
+		let reverse_relayer =
+			ctx.payee(relayer, &packet.source_channel).unwrap_or_else(|| relayer.clone());
+		for PacketFee { fee, refund_address, .. } in fees {
+			distribute_fee(ctx, output, forward_relayer.as_ref(), &refund_address, &fee.recv_fee)?;
+			distribute_fee(ctx, output, Some(&reverse_relayer), &refund_address, &fee.ack_fee)?;
+			distribute_fee(ctx, output, None, &refund_address, &fee.timeout_fee)?;
+		}
+		ctx.delete_fees_in_escrow(&packet.source_port, &packet.source_channel, packet.sequence);
+	}
+
+	Ok(ack.app_acknowledgement)
+}
+
+/// Pays the timeout fee to the relayer of the timeout, the receive and acknowledgement fees are
+/// refunded.
+pub fn on_timeout_packet<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	packet: &Packet,
+	relayer: &Signer,
+) -> Result<(), Error> {
+	if !ctx.is_fee_enabled(&packet.source_port, &packet.source_channel) {
+		return Ok(())
+	}
+
+	let fees = ctx.fees_in_escrow(&packet.source_port, &packet.source_channel, packet.sequence);
+	if !fees.is_empty() {
+		let timeout_relayer =
+			ctx.payee(relayer, &packet.source_channel).unwrap_or_else(|| relayer.clone());
+		for PacketFee { fee, refund_address, .. } in fees {
+			distribute_fee(ctx, output, Some(&timeout_relayer), &refund_address, &fee.timeout_fee)?;
+			distribute_fee(ctx, output, None, &refund_address, &fee.recv_fee)?;
+			distribute_fee(ctx, output, None, &refund_address, &fee.ack_fee)?;
+		}
+		ctx.delete_fees_in_escrow(&packet.source_port, &packet.source_channel, packet.sequence);
+	}
+
+	Ok(())
+}
+
+/// Refunds the fees of all the packets sent over the channel once it's closed, since none of them
+/// can be relayed anymore.
+pub fn refund_fees_on_channel_closure<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+) -> Result<(), Error> {
+	for sequence in ctx.sequences_with_fees(port_id, channel_id) {
+		for PacketFee { fee, refund_address, .. } in
+			ctx.fees_in_escrow(port_id, channel_id, sequence)
+		{
+			distribute_fee(ctx, output, None, &refund_address, &fee.total())?;
+		}
+		ctx.delete_fees_in_escrow(port_id, channel_id, sequence);
+	}
+	ctx.delete_fee_enabled(port_id, channel_id);
+	Ok(())
+}
+
+/// Pays the coins out of escrow to the receiver, coins that can't be paid to the receiver are
+/// refunded instead. Fees that can be neither paid nor refunded stay in escrow.
+fn distribute_fee<Ctx: FeeContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	receiver: Option<&Signer>,
+	refund_address: &Signer,
+	coins: &[PrefixedCoin],
+) -> Result<(), Error> {
+	if coins.is_empty() {
+		return Ok(())
+	}
+	let escrow_account = ctx.get_fee_escrow_address()?;
+	let receiver_account = receiver.and_then(|receiver| receiver.clone().try_into().ok());
+	let refund_account = refund_address.clone().try_into().ok();
+
+	let mut paid = vec![];
+	let mut refunded = vec![];
+	for coin in coins {
+		if let Some(account) = receiver_account.as_ref() {
+			if ctx.send_coins(&escrow_account, account, coin).is_ok() {
+				paid.push(coin.clone());
+				continue
+			}
+		}
+		if let Some(account) = refund_account.as_ref() {
+			if ctx.send_coins(&escrow_account, account, coin).is_ok() {
+				refunded.push(coin.clone());
+			}
+		}
+	}
+
+	if let (Some(receiver), false) = (receiver, paid.is_empty()) {
+		output.emit(DistributeFeeEvent { receiver: receiver.clone(), fee: paid }.into());
+	}
+	if !refunded.is_empty() {
+		output.emit(DistributeFeeEvent { receiver: refund_address.clone(), fee: refunded }.into());
+	}
+	Ok(())
+}
@@ -0,0 +1,66 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::error::Error;
+use crate::prelude::*;
+use core::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use subtle_encoding::base64;
+
+/// A string constant included in error acknowledgements, the error itself is emitted as an event
+/// since error messages are not deterministic.
+/// NOTE: Changing this const is state machine breaking as acknowledgements are written into state
+pub const ACK_ERR_STR: &str = "error handling interchain account packet: see events for details";
+
+/// The channel acknowledgement written by the host, the result holds the base64 encoded output of
+/// the executed transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Acknowledgement {
+	Result(String),
+	Error(String),
+}
+
+impl Acknowledgement {
+	pub fn success(result: &[u8]) -> Self {
+		Self::Result(
+			String::from_utf8(base64::encode(result)).expect("base64 encoded bytes are valid UTF8"),
+		)
+	}
+
+	pub fn from_error(_err: Error) -> Self {
+		Self::Error(ACK_ERR_STR.to_string())
+	}
+
+	pub fn is_successful(&self) -> bool {
+		matches!(self, Self::Result(_))
+	}
+
+	/// Returns the decoded transaction result or the error string.
+	pub fn into_result(self) -> Result<Vec<u8>, String> {
+		match self {
+			Self::Result(r) => base64::decode(r.as_bytes()).map_err(|e| e.to_string()),
+			Self::Error(e) => Err(e),
+		}
+	}
+}
+
+impl Display for Acknowledgement {
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		serde_json::to_string(self)
+			.map_err(|_| core::fmt::Error)
+			.and_then(|s| write!(f, "{}", s))
+	}
+}
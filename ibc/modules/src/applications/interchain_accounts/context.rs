@@ -0,0 +1,84 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc_proto::google::protobuf::Any;
+
+use super::error::Error;
+use crate::{
+	core::{
+		ics04_channel::context::ChannelKeeper,
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::ReaderContext,
+	},
+	prelude::*,
+};
+
+/// Tracks the channel bound to every interchain account. Channels are ordered so a timeout closes
+/// them, the controller can then open a new channel for the same account.
+pub trait IcaContext: ReaderContext {
+	/// Returns the open channel of the controller port on the given connection.
+	fn active_channel(&self, connection_id: &ConnectionId, port_id: &PortId) -> Option<ChannelId>;
+
+	fn set_active_channel(
+		&mut self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+	) -> Result<(), Error>;
+}
+
+/// Captures all the dependencies of the ICS27 host.
+pub trait IcaHostContext: IcaContext {
+	/// Returns true iff the host accepts new accounts and transactions.
+	fn is_host_enabled(&self) -> bool;
+
+	/// Returns the address of the account owned by the controller port on the given connection.
+	fn interchain_account_address(
+		&self,
+		connection_id: &ConnectionId,
+		controller_port_id: &PortId,
+	) -> Option<String>;
+
+	/// Creates a new account for the controller port on the given connection and returns its
+	/// address.
+	fn register_interchain_account(
+		&mut self,
+		connection_id: &ConnectionId,
+		controller_port_id: &PortId,
+	) -> Result<String, Error>;
+
+	/// Executes the messages atomically on behalf of the account, if any of them fails no state
+	/// changes must be committed. Messages that the host doesn't allow must be rejected before
+	/// anything is executed. Returns the result that is written in the acknowledgement.
+	fn execute_tx(
+		&mut self,
+		connection_id: &ConnectionId,
+		controller_port_id: &PortId,
+		messages: Vec<Any>,
+	) -> Result<Vec<u8>, Error>;
+}
+
+/// Captures all the dependencies of the ICS27 controller.
+pub trait IcaControllerContext: IcaContext + ChannelKeeper {
+	/// Returns true iff the controller can register accounts and send transactions.
+	fn is_controller_enabled(&self) -> bool;
+
+	/// Stores the address of the account on the host chain, as returned in `ChanOpenAck`.
+	fn set_interchain_account_address(
+		&mut self,
+		connection_id: &ConnectionId,
+		port_id: &PortId,
+		address: String,
+	) -> Result<(), Error>;
+}
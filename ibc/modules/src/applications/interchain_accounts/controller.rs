@@ -0,0 +1,271 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel and packet callbacks of the ICS27 controller, along with the handlers used to register
+//! interchain accounts and to send transactions to them.
+
+use crate::{
+	applications::interchain_accounts::{
+		acknowledgement::Acknowledgement, context::IcaControllerContext, controller_port_id,
+		counterparty_connection_id, error::Error, events::AckEvent, host_port_id,
+		is_controller_port, metadata::Metadata, packet::InterchainAccountPacketData, single_hop,
+		HOST_PORT_ID_STR,
+	},
+	core::{
+		ics02_client::height::Height,
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, Order, State},
+			handler::send_packet::send_packet,
+			msgs::acknowledgement::Acknowledgement as GenericAcknowledgement,
+			packet::{Packet, Sequence},
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::ModuleOutputBuilder,
+	},
+	handler::{HandlerOutput, HandlerOutputBuilder},
+	prelude::*,
+	signer::Signer,
+	timestamp::Timestamp,
+};
+
+/// Returns the controller port of the owner and the channel end that opens the channel of a new
+/// interchain account when submitted in a `MsgChannelOpenInit`.
+pub fn register_account(
+	ctx: &impl IcaControllerContext,
+	owner: &Signer,
+	connection_id: &ConnectionId,
+) -> Result<(PortId, ChannelEnd), Error> {
+	if !ctx.is_controller_enabled() {
+		return Err(Error::controller_disabled())
+	}
+
+	let port_id = controller_port_id(owner.as_ref())?;
+	ensure_no_open_channel(ctx, connection_id, &port_id)?;
+
+	let host_connection_id = counterparty_connection_id(ctx, connection_id)?;
+	let metadata = Metadata::new(connection_id.clone(), host_connection_id);
+	let channel_end = ChannelEnd::new(
+		State::Init,
+		Order::Ordered,
+		Counterparty::new(host_port_id(), None),
+		vec![connection_id.clone()],
+		metadata.to_version(),
+	);
+
+	Ok((port_id, channel_end))
+}
+
+fn ensure_no_open_channel(
+	ctx: &impl IcaControllerContext,
+	connection_id: &ConnectionId,
+	port_id: &PortId,
+) -> Result<(), Error> {
+	if let Some(channel_id) = ctx.active_channel(connection_id, port_id) {
+		let channel_end =
+			ctx.channel_end(&(port_id.clone(), channel_id)).map_err(Error::ics04_channel)?;
+		if channel_end.is_open() {
+			return Err(Error::active_channel_exists(port_id.clone(), channel_id))
+		}
+	}
+	Ok(())
+}
+
+/// Transaction sent by the owner of an interchain account to the host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgSendTx {
+	pub owner: Signer,
+	pub connection_id: ConnectionId,
+	pub packet_data: InterchainAccountPacketData,
+	/// Height of the host after which the packet times out.
+	/// The timeout is disabled when set to 0.
+	pub timeout_height: Height,
+	/// Timestamp of the host after which the packet times out.
+	/// The timeout is disabled when set to 0.
+	pub timeout_timestamp: Timestamp,
+}
+
+/// Sends the transaction over the active channel of the owner's account and returns the sequence
+/// of the packet.
+/// If this method returns an error, the runtime is expected to rollback all state modifications to
+/// the `Ctx` caused by all messages from the transaction that this `msg` is a part of.
+pub fn send_tx<Ctx: IcaControllerContext>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	msg: MsgSendTx,
+) -> Result<Sequence, Error> {
+	if !ctx.is_controller_enabled() {
+		return Err(Error::controller_disabled())
+	}
+
+	let source_port = controller_port_id(msg.owner.as_ref())?;
+	let not_found =
+		|| Error::active_channel_not_found(msg.connection_id.clone(), source_port.clone());
+	let source_channel =
+		ctx.active_channel(&msg.connection_id, &source_port).ok_or_else(not_found)?;
+	let source_channel_end = ctx
+		.channel_end(&(source_port.clone(), source_channel))
+		.map_err(Error::ics04_channel)?;
+	if !source_channel_end.is_open() {
+		return Err(not_found())
+	}
+
+	let destination_port = source_channel_end.counterparty().port_id().clone();
+	let destination_channel =
+		*source_channel_end.counterparty().channel_id().ok_or_else(not_found)?;
+
+	let sequence = ctx
+		.get_next_sequence_send(&(source_port.clone(), source_channel))
+		.map_err(Error::ics04_channel)?;
+
+	let packet = Packet {
+		sequence,
+		source_port,
+		source_channel,
+		destination_port,
+		destination_channel,
+		data: msg.packet_data.encode(),
+		timeout_height: msg.timeout_height,
+		timeout_timestamp: msg.timeout_timestamp,
+	};
+
+	let HandlerOutput { result, log, events } =
+		send_packet(ctx, packet).map_err(Error::ics04_channel)?;
+
+	ctx.store_packet_result(result).map_err(Error::ics04_channel)?;
+
+	output.merge_output(HandlerOutput::builder().with_log(log).with_events(events).with_result(()));
+
+	output.log(format!(
+		"Interchain account transaction: {} --({} messages)--> {}",
+		msg.owner,
+		msg.packet_data.messages.len(),
+		msg.connection_id
+	));
+
+	Ok(sequence)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_init(
+	ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	order: Order,
+	connection_hops: &[ConnectionId],
+	port_id: &PortId,
+	_channel_id: &ChannelId,
+	counterparty: &Counterparty,
+	version: &Version,
+) -> Result<(), Error> {
+	if !ctx.is_controller_enabled() {
+		return Err(Error::controller_disabled())
+	}
+
+	if order != Order::Ordered {
+		return Err(Error::channel_not_ordered(order))
+	}
+
+	if !is_controller_port(port_id) {
+		return Err(Error::invalid_controller_port(port_id.clone()))
+	}
+
+	if counterparty.port_id().as_str() != HOST_PORT_ID_STR {
+		return Err(Error::invalid_port(counterparty.port_id().clone(), host_port_id()))
+	}
+
+	let connection_id = single_hop(connection_hops)?;
+	let host_connection_id = counterparty_connection_id(&*ctx, connection_id)?;
+	Metadata::from_version(version)?.validate(connection_id, &host_connection_id)?;
+
+	ensure_no_open_channel(&*ctx, connection_id, port_id)
+}
+
+pub fn on_chan_open_ack(
+	ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	counterparty_version: &Version,
+) -> Result<(), Error> {
+	let channel_end =
+		ctx.channel_end(&(port_id.clone(), *channel_id)).map_err(Error::ics04_channel)?;
+	let connection_id = single_hop(channel_end.connection_hops())?;
+	let host_connection_id = counterparty_connection_id(&*ctx, connection_id)?;
+
+	let metadata = Metadata::from_version(counterparty_version)?;
+	metadata.validate(connection_id, &host_connection_id)?;
+	if metadata.address.is_empty() {
+		return Err(Error::empty_address())
+	}
+
+	ctx.set_active_channel(connection_id, port_id, channel_id)?;
+	ctx.set_interchain_account_address(connection_id, port_id, metadata.address)
+}
+
+pub fn on_chan_close_init(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Err(Error::cant_close_channel())
+}
+
+pub fn on_chan_close_confirm(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Ok(())
+}
+
+pub fn on_recv_packet(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+) -> Acknowledgement {
+	Acknowledgement::from_error(Error::cant_receive_packets())
+}
+
+pub fn on_acknowledgement_packet(
+	_ctx: &mut impl IcaControllerContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+	acknowledgement: &GenericAcknowledgement,
+) -> Result<(), Error> {
+	let acknowledgement = serde_json::from_slice::<Acknowledgement>(acknowledgement.as_ref())
+		.map_err(|_| Error::ack_deserialization())?;
+
+	output.emit(
+		AckEvent {
+			port_id: packet.source_port.clone(),
+			sequence: packet.sequence,
+			acknowledgement,
+		}
+		.into(),
+	);
+
+	Ok(())
+}
+
+/// The channel is ordered so core ibc closes it on timeout, the owner has to register the account
+/// again to open a new channel.
+pub fn on_timeout_packet(
+	_ctx: &mut impl IcaControllerContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+) -> Result<(), Error> {
+	Ok(())
+}
@@ -0,0 +1,150 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use flex_error::{define_error, TraceError};
+
+use crate::{
+	core::{
+		ics03_connection::error as connection_error,
+		ics04_channel::{channel::Order, error as channel_error},
+		ics24_host::{
+			error::ValidationError,
+			identifier::{ChannelId, ConnectionId, PortId},
+		},
+	},
+	prelude::*,
+};
+
+define_error! {
+	#[derive(Debug, PartialEq, Eq)]
+	Error {
+		Ics03Connection
+			[ connection_error::Error ]
+			|_ | { "Ics03 connection error" },
+
+		Ics04Channel
+			[ channel_error::Error ]
+			|_ | { "Ics04 channel error" },
+
+		EmptyOwner
+			| _ | { "interchain account owner is empty" },
+
+		InvalidPortId
+			{ context: String }
+			[ ValidationError ]
+			| e | { format_args!("invalid port identifier: {0}", e.context) },
+
+		InvalidPort
+			{ port_id: PortId, exp_port_id: PortId }
+			| e | { format_args!("invalid port: '{0}', expected '{1}'", e.port_id, e.exp_port_id) },
+
+		InvalidControllerPort
+			{ port_id: PortId }
+			| e | { format_args!("port '{0}' is not a controller port", e.port_id) },
+
+		ChannelNotOrdered
+			{ order: Order }
+			| e | { format_args!("expected '{0}' channel, got '{1}'", Order::Ordered, e.order) },
+
+		InvalidConnectionHops
+			{ len: usize }
+			| e | { format_args!("expected a single connection hop, got {0}", e.len) },
+
+		InvalidVersion
+			{ version: String }
+			| e | { format_args!("expected version '{0}', got '{1}'", super::VERSION, e.version) },
+
+		UnsupportedEncoding
+			{ encoding: String }
+			| e | { format_args!("unsupported encoding: {0}", e.encoding) },
+
+		UnsupportedTxType
+			{ tx_type: String }
+			| e | { format_args!("unsupported transaction type: {0}", e.tx_type) },
+
+		ConnectionMismatch
+			{ expected: ConnectionId, actual: ConnectionId }
+			| e | { format_args!("expected connection '{0}', got '{1}'", e.expected, e.actual) },
+
+		CounterpartyConnectionNotFound
+			{ connection_id: ConnectionId }
+			| e | { format_args!("counterparty of connection {0} has no connection id", e.connection_id) },
+
+		MetadataDeserialization
+			| _ | { "failed to deserialize channel version metadata" },
+
+		EmptyAddress
+			| _ | { "interchain account address is empty" },
+
+		ActiveChannelExists
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | { format_args!("an active channel {1} already exists for port {0}", e.port_id, e.channel_id) },
+
+		ActiveChannelNotFound
+			{ connection_id: ConnectionId, port_id: PortId }
+			| e | { format_args!("no active channel for port {1} on connection {0}", e.connection_id, e.port_id) },
+
+		InactiveChannel
+			{ channel_id: ChannelId, port_id: PortId }
+			| e | { format_args!("channel {0} is not the active channel of port {1}", e.channel_id, e.port_id) },
+
+		MessageNotAllowed
+			{ message: String }
+			| e | { format_args!("message {0} is not allowed by the host", e.message) },
+
+		AccountNotFound
+			{ connection_id: ConnectionId, port_id: PortId }
+			| e | { format_args!("no interchain account registered for port {1} on connection {0}", e.connection_id, e.port_id) },
+
+		InvalidPacketType
+			{ packet_type: String }
+			| e | { format_args!("invalid packet type: {0}", e.packet_type) },
+
+		PacketDataDeserialization
+			| _ | { "failed to deserialize packet data" },
+
+		DecodeCosmosTx
+			[ TraceError<prost::DecodeError> ]
+			| _ | { "error decoding cosmos tx" },
+
+		AckDeserialization
+			| _ | { "failed to deserialize acknowledgement" },
+
+		HostDisabled
+			| _ | { "host submodule is disabled" },
+
+		ControllerDisabled
+			| _ | { "controller submodule is disabled" },
+
+		CantCloseChannel
+			| _ | { "user cannot close an interchain account channel" },
+
+		CantOpenChannel
+			| _ | { "the channel handshake step is not allowed on this side" },
+
+		CantReceivePackets
+			| _ | { "controller does not accept packets" },
+
+		CantSendPackets
+			| _ | { "host does not send packets" },
+
+		ExecuteTx
+			{ reason: String }
+			| e | { format_args!("failed to execute interchain account transaction: {}", e.reason) },
+
+		ImplementationSpecific
+			{ reason: String }
+			| e | { format_args!("implementation specific error: {}", e.reason) },
+	}
+}
@@ -0,0 +1,99 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	applications::interchain_accounts::{
+		acknowledgement::Acknowledgement, CONTROLLER_MODULE_ID_STR, HOST_MODULE_ID_STR,
+	},
+	core::{
+		ics04_channel::packet::Sequence,
+		ics24_host::identifier::{ConnectionId, PortId},
+	},
+	events::{IbcEvent, ModuleEvent},
+	prelude::*,
+};
+
+const EVENT_TYPE_PACKET: &str = "ics27_packet";
+const EVENT_TYPE_REGISTER: &str = "register_interchain_account";
+
+/// Emitted by the host once a packet has been executed.
+pub struct HostPacketEvent {
+	pub controller_port_id: PortId,
+	pub success: bool,
+	/// The reason of the failure, if any.
+	pub error: Option<String>,
+}
+
+impl From<HostPacketEvent> for IbcEvent {
+	fn from(ev: HostPacketEvent) -> Self {
+		let HostPacketEvent { controller_port_id, success, error } = ev;
+		let mut event = ModuleEvent {
+			kind: EVENT_TYPE_PACKET.to_string(),
+			module_name: HOST_MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("controller_port_id", controller_port_id).into(),
+				("success", success).into(),
+			],
+		};
+		if let Some(error) = error {
+			event.attributes.push(("error", error).into());
+		}
+		IbcEvent::AppModule(event)
+	}
+}
+
+/// Emitted by the host when an interchain account is registered.
+pub struct RegisterEvent {
+	pub controller_port_id: PortId,
+	pub connection_id: ConnectionId,
+	pub address: String,
+}
+
+impl From<RegisterEvent> for IbcEvent {
+	fn from(ev: RegisterEvent) -> Self {
+		let RegisterEvent { controller_port_id, connection_id, address } = ev;
+		IbcEvent::AppModule(ModuleEvent {
+			kind: EVENT_TYPE_REGISTER.to_string(),
+			module_name: HOST_MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("controller_port_id", controller_port_id).into(),
+				("connection_id", connection_id).into(),
+				("address", address).into(),
+			],
+		})
+	}
+}
+
+/// Emitted by the controller when the host acknowledged a packet.
+pub struct AckEvent {
+	pub port_id: PortId,
+	pub sequence: Sequence,
+	pub acknowledgement: Acknowledgement,
+}
+
+impl From<AckEvent> for IbcEvent {
+	fn from(ev: AckEvent) -> Self {
+		let AckEvent { port_id, sequence, acknowledgement } = ev;
+		let attr_label = if acknowledgement.is_successful() { "success" } else { "error" };
+		IbcEvent::AppModule(ModuleEvent {
+			kind: EVENT_TYPE_PACKET.to_string(),
+			module_name: CONTROLLER_MODULE_ID_STR.parse().expect("invalid ModuleId"),
+			attributes: vec![
+				("port_id", port_id).into(),
+				("sequence", sequence).into(),
+				(attr_label, acknowledgement).into(),
+			],
+		})
+	}
+}
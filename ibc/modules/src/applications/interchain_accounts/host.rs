@@ -0,0 +1,194 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel and packet callbacks of the ICS27 host, the host accepts channels opened by controllers
+//! and executes the transactions it receives on behalf of the interchain accounts.
+
+use crate::{
+	applications::interchain_accounts::{
+		acknowledgement::Acknowledgement,
+		context::IcaHostContext,
+		counterparty_connection_id,
+		error::Error,
+		events::{HostPacketEvent, RegisterEvent},
+		host_port_id, is_controller_port,
+		metadata::Metadata,
+		packet::InterchainAccountPacketData,
+		single_hop, HOST_PORT_ID_STR,
+	},
+	core::{
+		ics04_channel::{
+			channel::{Counterparty, Order},
+			packet::Packet,
+			Version,
+		},
+		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
+		ics26_routing::context::ModuleOutputBuilder,
+	},
+	prelude::*,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn on_chan_open_try(
+	ctx: &mut impl IcaHostContext,
+	output: &mut ModuleOutputBuilder,
+	order: Order,
+	connection_hops: &[ConnectionId],
+	port_id: &PortId,
+	_channel_id: &ChannelId,
+	counterparty: &Counterparty,
+	_version: &Version,
+	counterparty_version: &Version,
+) -> Result<Version, Error> {
+	if !ctx.is_host_enabled() {
+		return Err(Error::host_disabled())
+	}
+
+	if order != Order::Ordered {
+		return Err(Error::channel_not_ordered(order))
+	}
+
+	if port_id.as_str() != HOST_PORT_ID_STR {
+		return Err(Error::invalid_port(port_id.clone(), host_port_id()))
+	}
+
+	let controller_port_id = counterparty.port_id();
+	if !is_controller_port(controller_port_id) {
+		return Err(Error::invalid_controller_port(controller_port_id.clone()))
+	}
+
+	let connection_id = single_hop(connection_hops)?;
+	let controller_connection_id = counterparty_connection_id(&*ctx, connection_id)?;
+	let mut metadata = Metadata::from_version(counterparty_version)?;
+	metadata.validate(&controller_connection_id, connection_id)?;
+
+	// a new channel can only be opened for an account once the previous one has been closed.
+	if let Some(channel_id) = ctx.active_channel(connection_id, controller_port_id) {
+		let channel_end =
+			ctx.channel_end(&(port_id.clone(), channel_id)).map_err(Error::ics04_channel)?;
+		if channel_end.is_open() {
+			return Err(Error::active_channel_exists(controller_port_id.clone(), channel_id))
+		}
+	}
+
+	metadata.address = match ctx.interchain_account_address(connection_id, controller_port_id) {
+		Some(address) => address,
+		None => {
+			let address = ctx.register_interchain_account(connection_id, controller_port_id)?;
+			output.emit(
+				RegisterEvent {
+					controller_port_id: controller_port_id.clone(),
+					connection_id: connection_id.clone(),
+					address: address.clone(),
+				}
+				.into(),
+			);
+			address
+		},
+	};
+
+	Ok(metadata.to_version())
+}
+
+pub fn on_chan_open_confirm(
+	ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+) -> Result<(), Error> {
+	let channel_end =
+		ctx.channel_end(&(port_id.clone(), *channel_id)).map_err(Error::ics04_channel)?;
+	let connection_id = single_hop(channel_end.connection_hops())?;
+	ctx.set_active_channel(connection_id, channel_end.counterparty().port_id(), channel_id)
+}
+
+pub fn on_chan_close_init(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Err(Error::cant_close_channel())
+}
+
+pub fn on_chan_close_confirm(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+) -> Result<(), Error> {
+	Ok(())
+}
+
+pub fn on_recv_packet(
+	ctx: &mut impl IcaHostContext,
+	output: &mut ModuleOutputBuilder,
+	packet: &Packet,
+) -> Acknowledgement {
+	let (ack, error) = match process_recv_packet(ctx, packet) {
+		Ok(result) => (Acknowledgement::success(&result), None),
+		Err(e) => {
+			let reason = e.to_string();
+			(Acknowledgement::from_error(e), Some(reason))
+		},
+	};
+
+	output.emit(
+		HostPacketEvent {
+			controller_port_id: packet.source_port.clone(),
+			success: ack.is_successful(),
+			error,
+		}
+		.into(),
+	);
+
+	ack
+}
+
+fn process_recv_packet(ctx: &mut impl IcaHostContext, packet: &Packet) -> Result<Vec<u8>, Error> {
+	if !ctx.is_host_enabled() {
+		return Err(Error::host_disabled())
+	}
+
+	let data = InterchainAccountPacketData::decode(&packet.data)?;
+	let channel_end = ctx
+		.channel_end(&(packet.destination_port.clone(), packet.destination_channel))
+		.map_err(Error::ics04_channel)?;
+	let connection_id = single_hop(channel_end.connection_hops())?;
+	// transactions are only accepted on the channel the account is currently bound to.
+	if ctx.active_channel(connection_id, &packet.source_port) != Some(packet.destination_channel) {
+		return Err(Error::inactive_channel(packet.destination_channel, packet.source_port.clone()))
+	}
+	if ctx.interchain_account_address(connection_id, &packet.source_port).is_none() {
+		return Err(Error::account_not_found(connection_id.clone(), packet.source_port.clone()))
+	}
+
+	ctx.execute_tx(connection_id, &packet.source_port, data.messages)
+}
+
+pub fn on_acknowledgement_packet(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+) -> Result<(), Error> {
+	Err(Error::cant_send_packets())
+}
+
+pub fn on_timeout_packet(
+	_ctx: &mut impl IcaHostContext,
+	_output: &mut ModuleOutputBuilder,
+	_packet: &Packet,
+) -> Result<(), Error> {
+	Err(Error::cant_send_packets())
+}
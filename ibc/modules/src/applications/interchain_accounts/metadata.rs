@@ -0,0 +1,97 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The version of an interchain account channel is a JSON encoded [`Metadata`], it's negotiated
+//! during the channel handshake and carries the address of the account back to the controller.
+
+use serde::{Deserialize, Serialize};
+
+use super::{error::Error, ENCODING_PROTOBUF, TX_TYPE_SDK_MULTI_MSG, VERSION};
+use crate::{
+	core::{ics04_channel::Version, ics24_host::identifier::ConnectionId},
+	prelude::*,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+	/// The ICS27 protocol version.
+	pub version: String,
+	/// Connection identifier on the controller chain.
+	pub controller_connection_id: ConnectionId,
+	/// Connection identifier on the host chain.
+	pub host_connection_id: ConnectionId,
+	/// Address of the interchain account on the host chain, set by the host in `ChanOpenTry`.
+	#[serde(default)]
+	pub address: String,
+	/// Encoding of the messages in the packet data.
+	pub encoding: String,
+	/// Type of transactions sent over the channel.
+	pub tx_type: String,
+}
+
+impl Metadata {
+	/// Returns the metadata proposed by a controller in `ChanOpenInit`.
+	pub fn new(controller_connection_id: ConnectionId, host_connection_id: ConnectionId) -> Self {
+		Self {
+			version: VERSION.to_string(),
+			controller_connection_id,
+			host_connection_id,
+			address: String::new(),
+			encoding: ENCODING_PROTOBUF.to_string(),
+			tx_type: TX_TYPE_SDK_MULTI_MSG.to_string(),
+		}
+	}
+
+	pub fn from_version(version: &Version) -> Result<Self, Error> {
+		serde_json::from_str(&version.to_string()).map_err(|_| Error::metadata_deserialization())
+	}
+
+	pub fn to_version(&self) -> Version {
+		Version::new(
+			serde_json::to_string(self).expect("Metadata's infallible Serialize impl failed"),
+		)
+	}
+
+	/// Checks the version, encoding and transaction type, and that the metadata was proposed for
+	/// the given pair of connections.
+	pub fn validate(
+		&self,
+		controller_connection_id: &ConnectionId,
+		host_connection_id: &ConnectionId,
+	) -> Result<(), Error> {
+		if self.version != VERSION {
+			return Err(Error::invalid_version(self.version.clone()))
+		}
+		if self.encoding != ENCODING_PROTOBUF {
+			return Err(Error::unsupported_encoding(self.encoding.clone()))
+		}
+		if self.tx_type != TX_TYPE_SDK_MULTI_MSG {
+			return Err(Error::unsupported_tx_type(self.tx_type.clone()))
+		}
+		if &self.controller_connection_id != controller_connection_id {
+			return Err(Error::connection_mismatch(
+				controller_connection_id.clone(),
+				self.controller_connection_id.clone(),
+			))
+		}
+		if &self.host_connection_id != host_connection_id {
+			return Err(Error::connection_mismatch(
+				host_connection_id.clone(),
+				self.host_connection_id.clone(),
+			))
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,96 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ICS 27: Interchain Accounts allow a controller chain to register an account on a host chain and
+//! to execute transactions on the host chain through that account. Every interchain account is
+//! bound to an ordered channel between the controller port of its owner and the host port.
+pub mod acknowledgement;
+pub mod context;
+pub mod controller;
+pub mod error;
+pub mod events;
+pub mod host;
+pub mod metadata;
+pub mod packet;
+
+use crate::{
+	core::{
+		ics03_connection::context::ConnectionReader,
+		ics24_host::identifier::{ConnectionId, PortId},
+	},
+	prelude::*,
+};
+use error::Error;
+
+/// Module identifier for the ICS27 host application.
+pub const HOST_MODULE_ID_STR: &str = "icahost";
+
+/// Module identifier for the ICS27 controller application.
+pub const CONTROLLER_MODULE_ID_STR: &str = "icacontroller";
+
+/// The port identifier that the ICS27 host binds with.
+pub const HOST_PORT_ID_STR: &str = "icahost";
+
+/// Prefix of the controller port identifiers, the owner of the account is appended to it.
+pub const CONTROLLER_PORT_PREFIX: &str = "icacontroller-";
+
+/// ICS27 application current version.
+pub const VERSION: &str = "ics27-1";
+
+/// Protobuf encoding of the messages in a transaction, the only encoding currently supported.
+pub const ENCODING_PROTOBUF: &str = "proto3";
+
+/// Transactions are a list of messages executed atomically.
+pub const TX_TYPE_SDK_MULTI_MSG: &str = "sdk_multi_msg";
+
+/// Returns the controller port for the given account owner.
+pub fn controller_port_id(owner: &str) -> Result<PortId, Error> {
+	if owner.trim().is_empty() {
+		return Err(Error::empty_owner())
+	}
+	format!("{}{}", CONTROLLER_PORT_PREFIX, owner)
+		.parse()
+		.map_err(|e| Error::invalid_port_id(owner.to_string(), e))
+}
+
+/// Returns true if the port is a controller port.
+pub fn is_controller_port(port_id: &PortId) -> bool {
+	port_id.as_str().starts_with(CONTROLLER_PORT_PREFIX)
+}
+
+/// Returns the port the host binds with.
+pub fn host_port_id() -> PortId {
+	HOST_PORT_ID_STR.parse().expect("host port id is valid")
+}
+
+/// Interchain account channels are bound to a single connection.
+pub(crate) fn single_hop(connection_hops: &[ConnectionId]) -> Result<&ConnectionId, Error> {
+	match connection_hops {
+		[connection_id] => Ok(connection_id),
+		_ => Err(Error::invalid_connection_hops(connection_hops.len())),
+	}
+}
+
+/// Returns the identifier of the connection on the other chain.
+pub(crate) fn counterparty_connection_id(
+	ctx: &impl ConnectionReader,
+	connection_id: &ConnectionId,
+) -> Result<ConnectionId, Error> {
+	let connection_end = ctx.connection_end(connection_id).map_err(Error::ics03_connection)?;
+	connection_end
+		.counterparty()
+		.connection_id()
+		.cloned()
+		.ok_or_else(|| Error::counterparty_connection_not_found(connection_id.clone()))
+}
@@ -0,0 +1,92 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::applications::interchain_accounts::v1::{CosmosTx, Type},
+};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use subtle_encoding::base64;
+
+use super::error::Error;
+use crate::prelude::*;
+
+/// Packet data sent from a controller to a host, in the JSON encoding used by ibc-go.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterchainAccountPacketData {
+	/// Messages executed atomically by the interchain account on the host chain.
+	pub messages: Vec<Any>,
+	pub memo: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawPacketData {
+	#[serde(rename = "type")]
+	packet_type: String,
+	data: String,
+	#[serde(default)]
+	memo: String,
+}
+
+impl InterchainAccountPacketData {
+	pub fn new(messages: Vec<Any>, memo: String) -> Self {
+		Self { messages, memo }
+	}
+
+	pub fn encode(&self) -> Vec<u8> {
+		let tx = CosmosTx { messages: self.messages.clone() };
+		let raw = RawPacketData {
+			packet_type: Type::ExecuteTx.as_str_name().to_string(),
+			data: String::from_utf8(base64::encode(tx.encode_to_vec()))
+				.expect("base64 encoded bytes are valid UTF8"),
+			memo: self.memo.clone(),
+		};
+		serde_json::to_vec(&raw).expect("RawPacketData's infallible Serialize impl failed")
+	}
+
+	pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+		let raw = serde_json::from_slice::<RawPacketData>(bytes)
+			.map_err(|_| Error::packet_data_deserialization())?;
+		if Type::from_str_name(&raw.packet_type) != Some(Type::ExecuteTx) {
+			return Err(Error::invalid_packet_type(raw.packet_type))
+		}
+		let data = base64::decode(raw.data.as_bytes())
+			.map_err(|_| Error::packet_data_deserialization())?;
+		let tx = CosmosTx::decode(data.as_slice()).map_err(Error::decode_cosmos_tx)?;
+		Ok(Self { messages: tx.messages, memo: raw.memo })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn packet_data_roundtrip() {
+		let data = InterchainAccountPacketData::new(
+			vec![Any { type_url: "/pallet_ibc.RuntimeCall".to_string(), value: vec![0, 1, 2] }],
+			"memo".to_string(),
+		);
+		let encoded = data.encode();
+		assert!(String::from_utf8(encoded.clone()).unwrap().contains("TYPE_EXECUTE_TX"));
+		assert_eq!(InterchainAccountPacketData::decode(&encoded).unwrap(), data);
+	}
+
+	#[test]
+	fn rejects_unspecified_packet_type() {
+		let raw = br#"{"type":"TYPE_UNSPECIFIED","data":"","memo":""}"#;
+		assert!(InterchainAccountPacketData::decode(raw).is_err());
+	}
+}
@@ -19,7 +19,7 @@ use crate::{
 	applications::transfer::{
 		acknowledgement::Acknowledgement,
 		events::{AckEvent, AckStatusEvent, RecvEvent, TimeoutEvent},
-		packet::PacketData,
+		packet::decode_packet_data,
 		relay::{
 			on_ack_packet::process_ack_packet, on_recv_packet::process_recv_packet,
 			on_timeout_packet::process_timeout_packet,
@@ -157,7 +157,7 @@ fn validate_transfer_channel_params(
 		return Err(Ics20Error::invalid_port(port_id.clone(), bound_port))
 	}
 
-	if version != &Version::ics20() {
+	if version != &Version::ics20() && version != &Version::ics20_v2() {
 		return Err(Ics20Error::invalid_version(version.clone()))
 	}
 
@@ -165,7 +165,7 @@ fn validate_transfer_channel_params(
 }
 
 fn validate_counterparty_version(counterparty_version: &Version) -> Result<(), Ics20Error> {
-	if counterparty_version == &Version::ics20() {
+	if counterparty_version == &Version::ics20() || counterparty_version == &Version::ics20_v2() {
 		Ok(())
 	} else {
 		Err(Ics20Error::invalid_counterparty_version(counterparty_version.clone()))
@@ -200,7 +200,7 @@ pub fn on_chan_open_try(
 ) -> Result<Version, Ics20Error> {
 	validate_transfer_channel_params(ctx, order, port_id, channel_id, version)?;
 	validate_counterparty_version(counterparty_version)?;
-	Ok(Version::ics20())
+	Ok(counterparty_version.clone())
 }
 
 pub fn on_chan_open_ack(
@@ -223,6 +223,45 @@ pub fn on_chan_open_confirm(
 	Ok(())
 }
 
+/// Transfer channels can be upgraded as long as they keep the ICS20 ordering and one of its
+/// versions, e.g. to move them to another connection or to switch them to `ics20-2`.
+pub fn on_chan_upgrade_init(
+	ctx: &mut impl Ics20Context,
+	_output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	order: Order,
+	_connection_hops: &[ConnectionId],
+	version: &Version,
+) -> Result<Version, Ics20Error> {
+	validate_transfer_channel_params(ctx, order, port_id, channel_id, version)?;
+	Ok(version.clone())
+}
+
+pub fn on_chan_upgrade_try(
+	ctx: &mut impl Ics20Context,
+	_output: &mut ModuleOutputBuilder,
+	port_id: &PortId,
+	channel_id: &ChannelId,
+	order: Order,
+	_connection_hops: &[ConnectionId],
+	counterparty_version: &Version,
+) -> Result<Version, Ics20Error> {
+	validate_counterparty_version(counterparty_version)?;
+	validate_transfer_channel_params(ctx, order, port_id, channel_id, counterparty_version)?;
+	Ok(counterparty_version.clone())
+}
+
+pub fn on_chan_upgrade_ack(
+	_ctx: &mut impl Ics20Context,
+	_output: &mut ModuleOutputBuilder,
+	_port_id: &PortId,
+	_channel_id: &ChannelId,
+	counterparty_version: &Version,
+) -> Result<(), Ics20Error> {
+	validate_counterparty_version(counterparty_version)
+}
+
 pub fn on_chan_close_init(
 	_ctx: &mut impl Ics20Context,
 	_output: &mut ModuleOutputBuilder,
@@ -247,9 +286,13 @@ pub fn on_recv_packet<Ctx: 'static + Ics20Context>(
 	packet: &Packet,
 	_relayer: &Signer,
 ) -> Acknowledgement {
-	let data = match serde_json::from_slice::<PacketData>(&packet.data) {
+	let data = match ctx
+		.channel_end(&(packet.destination_port.clone(), packet.destination_channel))
+		.map_err(Ics20Error::ics04_channel)
+		.and_then(|channel_end| decode_packet_data(channel_end.version(), &packet.data))
+	{
 		Ok(data) => data,
-		Err(_) => return Acknowledgement::from_error(Ics20Error::packet_data_deserialization()),
+		Err(e) => return Acknowledgement::from_error(e),
 	};
 
 	let ack = match process_recv_packet(ctx, output, packet, data.clone()) {
@@ -257,13 +300,15 @@ pub fn on_recv_packet<Ctx: 'static + Ics20Context>(
 		Err(e) => Acknowledgement::from_error(e),
 	};
 
-	let recv_event = RecvEvent {
-		receiver: data.receiver,
-		denom: data.token.denom,
-		amount: data.token.amount,
-		success: ack.is_successful(),
-	};
-	output.emit(recv_event.into());
+	for token in data.tokens {
+		let recv_event = RecvEvent {
+			receiver: data.receiver.clone(),
+			denom: token.denom,
+			amount: token.amount,
+			success: ack.is_successful(),
+		};
+		output.emit(recv_event.into());
+	}
 
 	ack
 }
@@ -275,21 +320,25 @@ pub fn on_acknowledgement_packet(
 	acknowledgement: &GenericAcknowledgement,
 	_relayer: &Signer,
 ) -> Result<(), Ics20Error> {
-	let data = serde_json::from_slice::<PacketData>(&packet.data)
-		.map_err(|_| Ics20Error::packet_data_deserialization())?;
+	let channel_end = ctx
+		.channel_end(&(packet.source_port.clone(), packet.source_channel))
+		.map_err(Ics20Error::ics04_channel)?;
+	let data = decode_packet_data(channel_end.version(), &packet.data)?;
 
 	let acknowledgement = serde_json::from_slice::<Acknowledgement>(acknowledgement.as_ref())
 		.map_err(|_| Ics20Error::ack_deserialization())?;
 
 	process_ack_packet(ctx, packet, &data, &acknowledgement)?;
 
-	let ack_event = AckEvent {
-		receiver: data.receiver,
-		denom: data.token.denom,
-		amount: data.token.amount,
-		acknowledgement: acknowledgement.clone(),
-	};
-	output.emit(ack_event.into());
+	for token in data.tokens {
+		let ack_event = AckEvent {
+			receiver: data.receiver.clone(),
+			denom: token.denom,
+			amount: token.amount,
+			acknowledgement: acknowledgement.clone(),
+		};
+		output.emit(ack_event.into());
+	}
 	output.emit(AckStatusEvent { acknowledgement }.into());
 
 	Ok(())
@@ -301,17 +350,21 @@ pub fn on_timeout_packet(
 	packet: &Packet,
 	_relayer: &Signer,
 ) -> Result<(), Ics20Error> {
-	let data = serde_json::from_slice::<PacketData>(&packet.data)
-		.map_err(|_| Ics20Error::packet_data_deserialization())?;
+	let channel_end = ctx
+		.channel_end(&(packet.source_port.clone(), packet.source_channel))
+		.map_err(Ics20Error::ics04_channel)?;
+	let data = decode_packet_data(channel_end.version(), &packet.data)?;
 
 	process_timeout_packet(ctx, packet, &data)?;
 
-	let timeout_event = TimeoutEvent {
-		refund_receiver: data.sender,
-		refund_denom: data.token.denom,
-		refund_amount: data.token.amount,
-	};
-	output.emit(timeout_event.into());
+	for token in data.tokens {
+		let timeout_event = TimeoutEvent {
+			refund_receiver: data.sender.clone(),
+			refund_denom: token.denom,
+			refund_amount: token.amount,
+		};
+		output.emit(timeout_event.into());
+	}
 
 	Ok(())
 }
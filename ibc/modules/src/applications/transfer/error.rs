@@ -116,11 +116,25 @@ define_error! {
 
 		InvalidVersion
 			{ version: Version }
-			| e | { format_args!("expected version '{0}', got '{1}'", Version::ics20(), e.version) },
+			| e | { format_args!("expected version '{0}' or '{1}', got '{2}'", Version::ics20(), Version::ics20_v2(), e.version) },
 
 		InvalidCounterpartyVersion
 			{ version: Version }
-			| e | { format_args!("expected counterparty version '{0}', got '{1}'", Version::ics20(), e.version) },
+			| e | { format_args!("expected counterparty version '{0}' or '{1}', got '{2}'", Version::ics20(), Version::ics20_v2(), e.version) },
+
+		EmptyTokens
+			| _ | { "packet doesn't transfer any tokens" },
+
+		InvalidForwarding
+			{ reason: String }
+			| e | { format_args!("invalid forwarding path: {0}", e.reason) },
+
+		ForwardingUnsupported
+			| _ | { "forwarded tokens must be received by the forwarding middleware" },
+
+		IncompatiblePacketData
+			{ version: Version }
+			| e | { format_args!("packet data can't be sent over a channel of version '{0}', only a single token can be transferred without forwarding", e.version) },
 
 		CantCloseChannel
 			| _ | { "channel cannot be closed" },
@@ -35,3 +35,10 @@ pub const PORT_ID_STR: &str = "transfer";
 
 /// ICS20 application current version.
 pub const VERSION: &str = "ics20-1";
+
+/// Version of the ICS20 application that transfers several tokens in a packet and can forward
+/// them through other chains.
+pub const VERSION_V2: &str = "ics20-2";
+
+/// Maximum number of hops tokens can be forwarded through before reaching the receiver.
+pub const MAX_FORWARDING_HOPS: usize = 8;
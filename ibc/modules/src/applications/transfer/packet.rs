@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use alloc::string::{String, ToString};
-use core::{convert::TryFrom, str::FromStr};
+use core::str::FromStr;
 
 use ibc_proto::ibc::applications::transfer::v2::FungibleTokenPacketData as RawPacketData;
 use serde::{Deserialize, Serialize};
 
-use super::{error::Error, Amount, PrefixedCoin, PrefixedDenom};
-use crate::signer::Signer;
+use super::{error::Error, Amount, PrefixedCoin, PrefixedDenom, MAX_FORWARDING_HOPS};
+use crate::{
+	core::{
+		ics04_channel::Version,
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	prelude::*,
+	signer::Signer,
+};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PacketData {
@@ -57,3 +63,113 @@ impl From<PacketData> for RawPacketData {
 		}
 	}
 }
+
+/// Channel the tokens of a packet are forwarded over.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hop {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+}
+
+/// Hops the tokens of a packet are forwarded through once received, the last hop delivers them to
+/// the receiver of the packet.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Forwarding {
+	pub hops: Vec<Hop>,
+	/// Memo of the transfer over the last hop
+	pub destination_memo: String,
+}
+
+/// Data of `ics20-2` packets, which transfer several tokens at once and can forward them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PacketDataV2 {
+	pub tokens: Vec<PrefixedCoin>,
+	pub sender: Signer,
+	pub receiver: Signer,
+	pub memo: String,
+	#[serde(default)]
+	pub forwarding: Forwarding,
+}
+
+impl PacketDataV2 {
+	pub fn validate_basic(&self) -> Result<(), Error> {
+		if self.tokens.is_empty() {
+			return Err(Error::empty_tokens())
+		}
+		if self.forwarding.hops.len() > MAX_FORWARDING_HOPS {
+			return Err(Error::invalid_forwarding(format!(
+				"{} hops exceed the maximum of {}",
+				self.forwarding.hops.len(),
+				MAX_FORWARDING_HOPS
+			)))
+		}
+		if self.forwarding.hops.is_empty() && !self.forwarding.destination_memo.is_empty() {
+			return Err(Error::invalid_forwarding("destination memo without hops".to_string()))
+		}
+		if self.is_forwarded() && !self.memo.is_empty() {
+			return Err(Error::invalid_forwarding(
+				"the memo of forwarded packets must be set as the destination memo".to_string(),
+			))
+		}
+		Ok(())
+	}
+
+	/// Returns true if the tokens are forwarded to another chain once received.
+	pub fn is_forwarded(&self) -> bool {
+		!self.forwarding.hops.is_empty()
+	}
+}
+
+impl From<PacketData> for PacketDataV2 {
+	fn from(data: PacketData) -> Self {
+		Self {
+			tokens: vec![data.token],
+			sender: data.sender,
+			receiver: data.receiver,
+			memo: data.memo,
+			forwarding: Forwarding::default(),
+		}
+	}
+}
+
+impl TryFrom<PacketDataV2> for PacketData {
+	type Error = Error;
+
+	fn try_from(mut data: PacketDataV2) -> Result<Self, Self::Error> {
+		if data.tokens.len() != 1 || data.is_forwarded() {
+			return Err(Error::incompatible_packet_data(Version::ics20()))
+		}
+		Ok(Self {
+			token: data.tokens.remove(0),
+			sender: data.sender,
+			receiver: data.receiver,
+			memo: data.memo,
+		})
+	}
+}
+
+/// Decodes the data of a packet sent over a transfer channel of the given version, `ics20-1`
+/// packets are converted to `ics20-2` ones.
+pub fn decode_packet_data(version: &Version, data: &[u8]) -> Result<PacketDataV2, Error> {
+	let data = if version == &Version::ics20_v2() {
+		serde_json::from_slice::<PacketDataV2>(data)
+			.map_err(|_| Error::packet_data_deserialization())?
+	} else {
+		serde_json::from_slice::<PacketData>(data)
+			.map_err(|_| Error::packet_data_deserialization())?
+			.into()
+	};
+	data.validate_basic()?;
+	Ok(data)
+}
+
+/// Encodes packet data to be sent over a transfer channel of the given version.
+pub fn encode_packet_data(version: &Version, data: PacketDataV2) -> Result<Vec<u8>, Error> {
+	data.validate_basic()?;
+	let data = if version == &Version::ics20_v2() {
+		serde_json::to_vec(&data)
+	} else {
+		serde_json::to_vec(&PacketData::try_from(data)?)
+	};
+	Ok(data.expect("PacketData's infallible Serialize impl failed"))
+}
@@ -15,7 +15,7 @@
 use crate::{
 	applications::transfer::{
 		acknowledgement::Acknowledgement, context::Ics20Context, error::Error as Ics20Error,
-		packet::PacketData, relay::refund_packet_token,
+		packet::PacketDataV2, relay::refund_packet_tokens,
 	},
 	core::ics04_channel::packet::Packet,
 };
@@ -23,11 +23,11 @@ use crate::{
 pub fn process_ack_packet(
 	ctx: &mut impl Ics20Context,
 	packet: &Packet,
-	data: &PacketData,
+	data: &PacketDataV2,
 	ack: &Acknowledgement,
 ) -> Result<(), Ics20Error> {
 	if !ack.is_successful() {
-		refund_packet_token(ctx, packet, data)?;
+		refund_packet_tokens(ctx, packet, data)?;
 	}
 	Ok(())
 }
@@ -14,8 +14,8 @@
 
 use crate::{
 	applications::transfer::{
-		context::Ics20Context, error::Error as Ics20Error, packet::PacketData,
-		relay::refund_packet_token,
+		context::Ics20Context, error::Error as Ics20Error, packet::PacketDataV2,
+		relay::refund_packet_tokens,
 	},
 	core::ics04_channel::packet::Packet,
 };
@@ -23,7 +23,7 @@ use crate::{
 pub fn process_timeout_packet(
 	ctx: &mut impl Ics20Context,
 	packet: &Packet,
-	data: &PacketData,
+	data: &PacketDataV2,
 ) -> Result<(), Ics20Error> {
-	refund_packet_token(ctx, packet, data)
+	refund_packet_tokens(ctx, packet, data)
 }
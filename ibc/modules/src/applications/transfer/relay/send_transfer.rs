@@ -14,12 +14,22 @@
 
 use crate::{
 	applications::transfer::{
-		context::Ics20Context, error::Error, events::TransferEvent, is_sender_chain_source,
-		msgs::transfer::MsgTransfer, packet::PacketData, Coin, PrefixedCoin,
+		context::Ics20Context,
+		error::Error,
+		events::TransferEvent,
+		is_sender_chain_source,
+		msgs::transfer::MsgTransfer,
+		packet::{encode_packet_data, Forwarding, PacketDataV2},
+		PrefixedCoin,
+	},
+	core::{
+		ics02_client::height::Height,
+		ics04_channel::{handler::send_packet::send_packet, packet::Packet},
+		ics24_host::identifier::{ChannelId, PortId},
 	},
-	core::ics04_channel::{handler::send_packet::send_packet, packet::Packet},
 	handler::{HandlerOutput, HandlerOutputBuilder},
 	prelude::*,
+	timestamp::Timestamp,
 };
 
 /// This function handles the transfer sending logic.
@@ -33,73 +43,105 @@ pub fn send_transfer<Ctx, C>(
 where
 	Ctx: Ics20Context,
 	C: TryInto<PrefixedCoin>,
+{
+	let token = msg.token.try_into().map_err(|_| Error::invalid_token())?;
+	let data = PacketDataV2 {
+		tokens: vec![token],
+		sender: msg.sender,
+		receiver: msg.receiver,
+		memo: msg.memo,
+		forwarding: Forwarding::default(),
+	};
+	send_tokens(
+		ctx,
+		output,
+		msg.source_port,
+		msg.source_channel,
+		data,
+		msg.timeout_height,
+		msg.timeout_timestamp,
+	)
+}
+
+/// Escrows or burns every token of `data` and sends them in a single packet, which is encoded
+/// according to the version of the source channel.
+/// If this method returns an error, the runtime is expected to rollback all state modifications to
+/// the `Ctx` caused by all messages from the transaction that this transfer is a part of.
+pub fn send_tokens<Ctx>(
+	ctx: &mut Ctx,
+	output: &mut HandlerOutputBuilder<()>,
+	source_port: PortId,
+	source_channel: ChannelId,
+	data: PacketDataV2,
+	timeout_height: Height,
+	timeout_timestamp: Timestamp,
+) -> Result<(), Error>
+where
+	Ctx: Ics20Context,
 {
 	if !ctx.is_send_enabled() {
 		return Err(Error::send_disabled())
 	}
 
 	let source_channel_end = ctx
-		.channel_end(&(msg.source_port.clone(), msg.source_channel))
+		.channel_end(&(source_port.clone(), source_channel))
 		.map_err(Error::ics04_channel)?;
 
 	let destination_port = source_channel_end.counterparty().port_id().clone();
-	let destination_channel = *source_channel_end.counterparty().channel_id().ok_or_else(|| {
-		Error::destination_channel_not_found(msg.source_port.clone(), msg.source_channel)
-	})?;
+	let destination_channel = *source_channel_end
+		.counterparty()
+		.channel_id()
+		.ok_or_else(|| Error::destination_channel_not_found(source_port.clone(), source_channel))?;
 
 	// get the next sequence
 	let sequence = ctx
-		.get_next_sequence_send(&(msg.source_port.clone(), msg.source_channel))
+		.get_next_sequence_send(&(source_port.clone(), source_channel))
 		.map_err(Error::ics04_channel)?;
 
-	let token = msg.token.try_into().map_err(|_| Error::invalid_token())?;
-	let denom = token.denom.clone();
-	let coin = Coin { denom: denom.clone(), amount: token.amount };
-
-	let sender = msg.sender.clone().try_into().map_err(|_| Error::parse_account_failure())?;
+	let sender = data.sender.clone().try_into().map_err(|_| Error::parse_account_failure())?;
 
-	if is_sender_chain_source(msg.source_port.clone(), msg.source_channel, &denom) {
-		let escrow_address =
-			ctx.get_channel_escrow_address(&msg.source_port, msg.source_channel)?;
-		ctx.send_coins(&sender, &escrow_address, &coin)?;
-	} else {
-		ctx.burn_coins(&sender, &coin)?;
+	for coin in &data.tokens {
+		if is_sender_chain_source(source_port.clone(), source_channel, &coin.denom) {
+			let escrow_address = ctx.get_channel_escrow_address(&source_port, source_channel)?;
+			ctx.send_coins(&sender, &escrow_address, coin)?;
+		} else {
+			ctx.burn_coins(&sender, coin)?;
+		}
 	}
 
-	let data = {
-		let data = PacketData {
-			token: coin,
-			sender: msg.sender.clone(),
-			receiver: msg.receiver.clone(),
-			memo: msg.memo,
-		};
-		serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
-	};
+	let log = format!(
+		"IBC fungible token transfer: {} --({})--> {}",
+		data.sender,
+		data.tokens.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+		data.receiver
+	);
+	let transfer_event =
+		TransferEvent { sender: data.sender.clone(), receiver: data.receiver.clone() };
 
 	let packet = Packet {
 		sequence,
-		source_port: msg.source_port,
-		source_channel: msg.source_channel,
+		source_port,
+		source_channel,
 		destination_port,
 		destination_channel,
-		data,
-		timeout_height: msg.timeout_height,
-		timeout_timestamp: msg.timeout_timestamp,
+		data: encode_packet_data(source_channel_end.version(), data)?,
+		timeout_height,
+		timeout_timestamp,
 	};
 
-	let HandlerOutput { result, log, events } =
+	let HandlerOutput { result, log: packet_log, events } =
 		send_packet(ctx, packet).map_err(Error::ics04_channel)?;
 
 	ctx.store_packet_result(result).map_err(Error::ics04_channel)?;
 
-	output.merge_output(HandlerOutput::builder().with_log(log).with_events(events).with_result(()));
-
-	output.log(format!(
-		"IBC fungible token transfer: {} --({})--> {}",
-		msg.sender, token, msg.receiver
-	));
+	output.merge_output(
+		HandlerOutput::builder()
+			.with_log(packet_log)
+			.with_events(events)
+			.with_result(()),
+	);
 
-	let transfer_event = TransferEvent { sender: msg.sender, receiver: msg.receiver };
+	output.log(log);
 	output.emit(transfer_event.into());
 
 	Ok(())
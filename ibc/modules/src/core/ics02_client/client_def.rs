@@ -22,9 +22,13 @@ use crate::{
 			channel::ChannelEnd,
 			commitment::{AcknowledgementCommitment, PacketCommitment},
 			packet::Sequence,
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
-		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			Path,
+		},
 		ics26_routing::context::ReaderContext,
 	},
 	prelude::*,
@@ -228,4 +232,84 @@ pub trait ClientDef: Clone {
 		channel_id: &ChannelId,
 		sequence: Sequence,
 	) -> Result<(), Error>;
+
+	/// Verify a `proof` that the counterparty stores `upgrade` as the in-progress upgrade of the
+	/// given channel.
+	#[allow(clippy::too_many_arguments)]
+	fn verify_channel_upgrade<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		upgrade: &Upgrade,
+	) -> Result<(), Error>;
+
+	/// Verify a `proof` that the counterparty wrote `error_receipt` after aborting an upgrade of
+	/// the given channel.
+	#[allow(clippy::too_many_arguments)]
+	fn verify_channel_upgrade_error<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), Error>;
+
+	/// Verify a `proof` that `value` is stored under `path` in the state of the chain tracked by
+	/// the client. Used for the proofs of the chains along the path of a multi-hop channel, whose
+	/// store prefix is not known to a connection end of the host.
+	#[allow(clippy::too_many_arguments)]
+	fn verify_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_path: Path,
+		_value: Vec<u8>,
+	) -> Result<(), Error> {
+		Err(Error::implementation_specific("membership proofs are not supported".to_string()))
+	}
+
+	/// Verify a `proof` that nothing is stored under `path` in the state of the chain tracked by
+	/// the client, see [`ClientDef::verify_membership`].
+	#[allow(clippy::too_many_arguments)]
+	fn verify_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		_path: Path,
+	) -> Result<(), Error> {
+		Err(Error::implementation_specific("non-membership proofs are not supported".to_string()))
+	}
+
+	/// Returns the client and consensus state to store once `proof` was verified against
+	/// `client_state`, for clients whose state changes with every proof they verify. Solo
+	/// machines sign each proof at a new sequence, which the proof consumes. `None` leaves the
+	/// client unchanged.
+	fn update_state_on_proof(
+		&self,
+		_client_state: &Self::ClientState,
+		_proof: &CommitmentProofBytes,
+	) -> Result<Option<(Self::ClientState, Self::ConsensusState)>, Error> {
+		Ok(None)
+	}
 }
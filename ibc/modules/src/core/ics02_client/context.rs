@@ -97,6 +97,14 @@ pub trait ClientReader: ClientKeeper {
 		proof: Option<Vec<u8>>,
 	) -> Result<Self::AnyConsensusState, Error>;
 
+	/// Decodes a client state of any client type supported by the host from its protobuf `Any`
+	/// encoding. Used for the client states proven along the path of a multi-hop channel.
+	fn decode_client_state(&self, value: &[u8]) -> Result<Self::AnyClientState, Error>;
+
+	/// Decodes a consensus state of any client type supported by the host from its protobuf
+	/// `Any` encoding. Used for the consensus states proven along the path of a multi-hop channel.
+	fn decode_consensus_state(&self, value: &[u8]) -> Result<Self::AnyConsensusState, Error>;
+
 	/// Returns a natural number, counting how many clients have been created thus far.
 	/// The value of this counter should increase only via method
 	/// `ClientKeeper::increase_client_counter`.
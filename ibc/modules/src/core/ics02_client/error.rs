@@ -40,6 +40,10 @@ define_error! {
 			{ client_type: String }
 			| e | { format_args!("unknown client type: {0}", e.client_type) },
 
+		ClientCreatedByHost
+			{ client_type: String }
+			| e | { format_args!("clients of type {0} can only be created by the host", e.client_type) },
+
 		ClientIdentifierConstructor
 			{ client_type: String, counter: u64 }
 			[ ValidationError ]
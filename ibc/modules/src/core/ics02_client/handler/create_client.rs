@@ -25,6 +25,7 @@ use crate::{
 			context::ClientTypes, error::Error, events::Attributes, handler::ClientResult,
 			height::Height, msgs::create_client::MsgCreateAnyClient,
 		},
+		ics09_localhost::client_state::ClientState as LocalhostClientState,
 		ics24_host::identifier::ClientId,
 	},
 	events::IbcEvent,
@@ -53,9 +54,14 @@ where
 {
 	let mut output = HandlerOutput::builder();
 
+	// The localhost client is created once by the host itself
+	let client_type = msg.client_state.client_type();
+	if client_type == LocalhostClientState::client_type() {
+		return Err(Error::client_created_by_host(client_type))
+	}
+
 	// Construct this client's identifier
 	let id_counter = ctx.client_counter()?;
-	let client_type = msg.client_state.client_type();
 	let client_id = ClientId::new(&client_type, id_counter)
 		.map_err(|e| Error::client_identifier_constructor(client_type.to_owned(), id_counter, e))?;
 
@@ -89,15 +95,22 @@ mod tests {
 		core::{
 			ics02_client::{
 				context::ClientReader,
+				error::ErrorDetail,
 				handler::{dispatch, ClientResult},
 				msgs::{create_client::MsgCreateAnyClient, ClientMsg},
 			},
+			ics09_localhost::{
+				client_state::ClientState as LocalhostClientState,
+				consensus_state::ConsensusState as LocalhostConsensusState,
+			},
 			ics24_host::identifier::ClientId,
 		},
 		events::IbcEvent,
 		handler::HandlerOutput,
 		mock::{
-			client_state::{MockClientState, MockConsensusState},
+			client_state::{
+				AnyClientState, AnyConsensusState, MockClientState, MockConsensusState,
+			},
 			context::{MockClientTypes, MockContext},
 			header::MockHeader,
 		},
@@ -149,6 +162,23 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_create_localhost_client_rejected() {
+		let ctx = MockContext::<MockClientTypes>::default();
+		let msg = MsgCreateAnyClient::new(
+			AnyClientState::Localhost(LocalhostClientState::new(Height::new(0, 42))),
+			AnyConsensusState::Localhost(LocalhostConsensusState::new(ctx.host_timestamp())),
+			get_dummy_account_id(),
+		)
+		.unwrap();
+
+		let output = dispatch(&ctx, ClientMsg::CreateClient(msg));
+		assert!(matches!(
+			output.as_ref().map_err(|e| e.detail()),
+			Err(ErrorDetail::ClientCreatedByHost(_))
+		));
+	}
+
 	#[test]
 	fn test_create_client_ok_multiple() {
 		let existing_client_id = ClientId::default();
@@ -28,6 +28,7 @@ use crate::{
 			height::Height,
 			msgs::update_client::MsgUpdateAnyClient,
 		},
+		ics23_commitment::commitment::CommitmentProofBytes,
 		ics24_host::identifier::ClientId,
 		ics26_routing::context::ReaderContext,
 	},
@@ -152,6 +153,50 @@ where
 	Ok(output.with_result(result))
 }
 
+/// Returns the state of client `client_id` the next proof of a message is verified against: the
+/// state the previous proofs of the message left the client in, if any, otherwise the stored one.
+pub fn client_state_for_proof<Ctx>(
+	ctx: &Ctx,
+	client_id: &ClientId,
+	client_update: &Option<Result<Ctx>>,
+) -> core::result::Result<Ctx::AnyClientState, Error>
+where
+	Ctx: ReaderContext,
+{
+	match client_update {
+		Some(update) if update.client_id == *client_id => Ok(update.client_state.clone()),
+		_ => ctx.client_state(client_id),
+	}
+}
+
+/// Lets the client `client_id` consume a `proof` that was just verified against `client_state`,
+/// see [`ClientDef::update_state_on_proof`]. The new state of the client is recorded in
+/// `client_update`, which the handler returns with the result of the message so that it's stored
+/// along with it.
+pub fn update_on_proof<Ctx>(
+	ctx: &Ctx,
+	client_id: &ClientId,
+	client_state: &Ctx::AnyClientState,
+	proof: &CommitmentProofBytes,
+	client_update: &mut Option<Result<Ctx>>,
+) -> core::result::Result<(), Error>
+where
+	Ctx: ReaderContext,
+{
+	if let Some((client_state, consensus_state)) =
+		client_state.client_def().update_state_on_proof(client_state, proof)?
+	{
+		*client_update = Some(Result {
+			client_id: client_id.clone(),
+			client_state,
+			consensus_state: Some(ConsensusUpdateResult::Single(consensus_state)),
+			processed_time: ctx.host_timestamp(),
+			processed_height: ctx.host_height(),
+		});
+	}
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	use core::str::FromStr;
@@ -219,6 +264,7 @@ mod tests {
 									AnyClientMessage::Mock(client_msg) => {
 										client_msg.height()
 									},
+									_ => unreachable!(),
 								})
 								.with_timestamp(timestamp)
 								.into()
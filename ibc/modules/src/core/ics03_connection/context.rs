@@ -18,6 +18,7 @@
 
 use crate::{
 	core::{
+		ics02_client::context::ClientTypes,
 		ics03_connection::{
 			connection::ConnectionEnd,
 			error::Error,
@@ -72,7 +73,10 @@ pub trait ConnectionReader {
 /// A context supplying all the necessary write-only dependencies (i.e., storage writing facility)
 /// for processing any `ConnectionMsg`.
 pub trait ConnectionKeeper {
-	fn store_connection_result(&mut self, result: ConnectionResult) -> Result<(), Error> {
+	fn store_connection_result<C: ClientTypes>(
+		&mut self,
+		result: ConnectionResult<C>,
+	) -> Result<(), Error> {
 		self.store_connection(result.connection_id.clone(), &result.connection_end)?;
 
 		// If we generated an identifier, increase the counter & associate this new identifier
@@ -39,7 +39,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: MsgConnectionOpenAck<Ctx>,
-) -> HandlerResult<ConnectionResult, Error> {
+) -> HandlerResult<ConnectionResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	// Check the client's (consensus state) proof height if it consensus proof is provided
@@ -104,8 +104,10 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	ctx.validate_self_client(&client_state).map_err(Error::ics02_client)?;
 
+	let mut client_update = None;
 	verify_connection_proof::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&conn_end,
 		&expected_conn,
@@ -115,6 +117,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	verify_client_proof::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&conn_end,
 		client_state,
@@ -124,6 +127,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	verify_consensus_proof::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&conn_end,
 		&consensus_proof,
@@ -144,6 +148,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		connection_id: msg.connection_id,
 		connection_id_state: ConnectionIdState::Reused,
 		connection_end: conn_end,
+		client_update,
 	};
 
 	output.emit(IbcEvent::OpenAckConnection(event_attributes.into()));
@@ -309,7 +314,7 @@ mod tests {
 					assert!(!proto_output.events.is_empty()); // Some events must exist.
 
 					// The object in the output is a ConnectionEnd, should have OPEN state.
-					let res: ConnectionResult = proto_output.result;
+					let res: ConnectionResult<_> = proto_output.result;
 					assert_eq!(res.connection_end.state().clone(), State::Open);
 
 					// assert that counterparty connection id is correct
@@ -33,7 +33,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: MsgConnectionOpenConfirm,
-) -> HandlerResult<ConnectionResult, Error> {
+) -> HandlerResult<ConnectionResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	// Validate the connection end.
@@ -59,8 +59,10 @@ pub(crate) fn process<Ctx: ReaderContext>(
 	);
 
 	// 2. Pass the details to the verification function.
+	let mut client_update = None;
 	verify_connection_proof::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&conn_end,
 		&expected_conn,
@@ -85,6 +87,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		connection_id: msg.connection_id,
 		connection_id_state: ConnectionIdState::Reused,
 		connection_end: conn_end,
+		client_update,
 	};
 
 	output.emit(IbcEvent::OpenConfirmConnection(event_attributes.into()));
@@ -197,7 +200,7 @@ mod tests {
 					assert!(!proto_output.events.is_empty()); // Some events must exist.
 
 					// The object in the output is a ConnectionEnd, should have OPEN state.
-					let res: ConnectionResult = proto_output.result;
+					let res: ConnectionResult<_> = proto_output.result;
 					assert_eq!(res.connection_end.state().clone(), State::Open);
 
 					for e in proto_output.events.iter() {
@@ -40,7 +40,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: MsgConnectionOpenTry<Ctx>,
-) -> HandlerResult<ConnectionResult, Error> {
+) -> HandlerResult<ConnectionResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let minimum_delay_period = ctx.minimum_delay_period();
@@ -98,8 +98,10 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	ctx.validate_self_client(&client_state).map_err(Error::ics02_client)?;
 
+	let mut client_update = None;
 	verify_connection_proof::<_>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&new_connection_end,
 		&expected_conn,
@@ -109,6 +111,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	verify_client_proof::<_>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&new_connection_end,
 		client_state,
@@ -118,6 +121,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	verify_consensus_proof::<_>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&new_connection_end,
 		&consensus_proof,
@@ -148,6 +152,7 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		connection_id: conn_id,
 		connection_id_state: ConnectionIdState::Generated,
 		connection_end: new_connection_end,
+		client_update,
 	};
 
 	output.emit(IbcEvent::OpenTryConnection(event_attributes.into()));
@@ -278,7 +283,7 @@ mod tests {
 					assert!(!proto_output.events.is_empty()); // Some events must exist.
 
 					// The object in the output is a ConnectionEnd, should have TryOpen state.
-					let res: ConnectionResult = proto_output.result;
+					let res: ConnectionResult<_> = proto_output.result;
 					assert_eq!(res.connection_end.state().clone(), State::TryOpen);
 
 					for e in proto_output.events.iter() {
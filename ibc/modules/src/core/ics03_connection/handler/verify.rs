@@ -15,7 +15,10 @@
 //! ICS3 verification functions, common across all four handlers of ICS3.
 
 use crate::core::ics02_client::{
-	client_consensus::ConsensusState, client_def::ClientDef, client_state::ClientState,
+	client_consensus::ConsensusState,
+	client_def::ClientDef,
+	client_state::ClientState,
+	handler::update_client::{self, client_state_for_proof, update_on_proof},
 };
 
 use crate::{
@@ -34,6 +37,7 @@ use alloc::vec::Vec;
 /// which created this proof). This object must match the state of `expected_conn`.
 pub fn verify_connection_proof<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
 	height: Height,
 	connection_end: &ConnectionEnd,
 	expected_conn: &ConnectionEnd,
@@ -41,7 +45,8 @@ pub fn verify_connection_proof<Ctx: ReaderContext>(
 	proof: &CommitmentProofBytes,
 ) -> Result<(), Error> {
 	// Fetch the client state (IBC client on the local/host chain).
-	let client_state = ctx.client_state(connection_end.client_id()).map_err(Error::ics02_client)?;
+	let client_state = client_state_for_proof(ctx, connection_end.client_id(), client_update)
+		.map_err(Error::ics02_client)?;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
@@ -75,7 +80,11 @@ pub fn verify_connection_proof<Ctx: ReaderContext>(
 			connection_id,
 			expected_conn,
 		)
-		.map_err(Error::verify_connection_state)
+		.map_err(Error::verify_connection_state)?;
+
+	update_on_proof(ctx, connection_end.client_id(), &client_state, proof, client_update)
+		.map_err(Error::ics02_client)?;
+	Ok(())
 }
 
 /// Verifies the client `proof` from a connection handshake message, typically from a
@@ -87,6 +96,7 @@ pub fn verify_connection_proof<Ctx: ReaderContext>(
 /// `proof` is correct.
 pub fn verify_client_proof<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
 	height: Height,
 	connection_end: &ConnectionEnd,
 	expected_client_state: Ctx::AnyClientState,
@@ -94,7 +104,8 @@ pub fn verify_client_proof<Ctx: ReaderContext>(
 	proof: &CommitmentProofBytes,
 ) -> Result<(), Error> {
 	// Fetch the local client state (IBC client running on the host chain).
-	let client_state = ctx.client_state(connection_end.client_id()).map_err(Error::ics02_client)?;
+	let client_state = client_state_for_proof(ctx, connection_end.client_id(), client_update)
+		.map_err(Error::ics02_client)?;
 
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(connection_end.client_id().clone()))
@@ -119,18 +130,24 @@ pub fn verify_client_proof<Ctx: ReaderContext>(
 		)
 		.map_err(|e| {
 			Error::client_state_verification_failure(connection_end.client_id().clone(), e)
-		})
+		})?;
+
+	update_on_proof(ctx, connection_end.client_id(), &client_state, proof, client_update)
+		.map_err(Error::ics02_client)?;
+	Ok(())
 }
 
 pub fn verify_consensus_proof<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
 	height: Height,
 	connection_end: &ConnectionEnd,
 	proof: &ConsensusProof,
 	host_consensus_state_proof: Vec<u8>,
 ) -> Result<(), Error> {
 	// Fetch the client state (IBC client on the local chain).
-	let client_state = ctx.client_state(connection_end.client_id()).map_err(Error::ics02_client)?;
+	let client_state = client_state_for_proof(ctx, connection_end.client_id(), client_update)
+		.map_err(Error::ics02_client)?;
 
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(connection_end.client_id().clone()))
@@ -167,6 +184,8 @@ pub fn verify_consensus_proof<Ctx: ReaderContext>(
 		)
 		.map_err(|e| Error::consensus_state_verification_failure(proof.height(), e))?;
 
+	update_on_proof(ctx, connection_end.client_id(), &client_state, proof.proof(), client_update)
+		.map_err(Error::ics02_client)?;
 	Ok(())
 }
 
@@ -27,7 +27,7 @@ use ibc_proto::ibc::core::channel::v1::{
 use crate::{
 	core::{
 		ics02_client::height::Height,
-		ics04_channel::{error::Error, packet::Sequence, Version},
+		ics04_channel::{error::Error, packet::Sequence, upgrade::UpgradeFields, Version},
 		ics24_host::identifier::{ChannelId, ConnectionId, PortId},
 	},
 	events::WithBlockDataType,
@@ -58,6 +58,7 @@ impl TryFrom<RawIdentifiedChannel> for IdentifiedChannelEnd {
 			counterparty: value.counterparty,
 			connection_hops: value.connection_hops,
 			version: value.version,
+			upgrade_sequence: value.upgrade_sequence,
 		};
 
 		Ok(IdentifiedChannelEnd {
@@ -83,6 +84,7 @@ impl From<IdentifiedChannelEnd> for RawIdentifiedChannel {
 			version: value.channel_end.version.to_string(),
 			port_id: value.port_id.to_string(),
 			channel_id: value.channel_id.to_string(),
+			upgrade_sequence: value.channel_end.upgrade_sequence,
 		}
 	}
 }
@@ -94,6 +96,8 @@ pub struct ChannelEnd {
 	pub remote: Counterparty,
 	pub connection_hops: Vec<ConnectionId>,
 	pub version: Version,
+	/// Latest upgrade attempt performed on this channel, `0` if it was never upgraded.
+	pub upgrade_sequence: u64,
 }
 
 impl Default for ChannelEnd {
@@ -104,6 +108,7 @@ impl Default for ChannelEnd {
 			remote: Counterparty::default(),
 			connection_hops: Vec::new(),
 			version: Version::default(),
+			upgrade_sequence: 0,
 		}
 	}
 }
@@ -131,7 +136,11 @@ impl TryFrom<RawChannel> for ChannelEnd {
 
 		let version = value.version.into();
 
-		Ok(ChannelEnd::new(chan_state, chan_ordering, remote, connection_hops, version))
+		let mut channel_end =
+			ChannelEnd::new(chan_state, chan_ordering, remote, connection_hops, version);
+		channel_end.upgrade_sequence = value.upgrade_sequence;
+
+		Ok(channel_end)
 	}
 }
 
@@ -143,6 +152,7 @@ impl From<ChannelEnd> for RawChannel {
 			counterparty: Some(value.counterparty().clone().into()),
 			connection_hops: value.connection_hops.iter().map(|v| v.as_str().to_string()).collect(),
 			version: value.version.to_string(),
+			upgrade_sequence: value.upgrade_sequence,
 		}
 	}
 }
@@ -156,7 +166,7 @@ impl ChannelEnd {
 		connection_hops: Vec<ConnectionId>,
 		version: Version,
 	) -> Self {
-		Self { state, ordering, remote, connection_hops, version }
+		Self { state, ordering, remote, connection_hops, version, upgrade_sequence: 0 }
 	}
 
 	/// Updates the ChannelEnd to assume a new State 's'.
@@ -172,6 +182,17 @@ impl ChannelEnd {
 		self.remote.channel_id = Some(c);
 	}
 
+	pub fn set_upgrade_sequence(&mut self, sequence: u64) {
+		self.upgrade_sequence = sequence;
+	}
+
+	/// Applies the fields negotiated by a channel upgrade to this end.
+	pub fn apply_upgrade_fields(&mut self, fields: &UpgradeFields) {
+		self.ordering = fields.ordering;
+		self.connection_hops = fields.connection_hops.clone();
+		self.version = fields.version.clone();
+	}
+
 	/// Returns `true` if this `ChannelEnd` is in state [`State::Open`].
 	pub fn is_open(&self) -> bool {
 		self.state_matches(&State::Open)
@@ -197,9 +218,18 @@ impl ChannelEnd {
 		&self.version
 	}
 
+	pub fn upgrade_sequence(&self) -> u64 {
+		self.upgrade_sequence
+	}
+
+	/// Returns `true` if this `ChannelEnd` is flushing in-flight packets as part of an upgrade.
+	pub fn is_flushing(&self) -> bool {
+		matches!(self.state, State::Flushing | State::FlushComplete)
+	}
+
 	pub fn validate_basic(&self) -> Result<(), Error> {
-		if self.connection_hops.len() != 1 {
-			return Err(Error::invalid_connection_hops_length(1, self.connection_hops.len()))
+		if self.connection_hops.is_empty() {
+			return Err(Error::invalid_connection_hops_length(1, 0))
 		}
 		self.counterparty().validate_basic()
 	}
@@ -331,6 +361,8 @@ pub enum State {
 	TryOpen = 2,
 	Open = 3,
 	Closed = 4,
+	Flushing = 5,
+	FlushComplete = 6,
 }
 
 impl State {
@@ -341,6 +373,8 @@ impl State {
 			Self::TryOpen => "TRYOPEN",
 			Self::Open => "OPEN",
 			Self::Closed => "CLOSED",
+			Self::Flushing => "FLUSHING",
+			Self::FlushComplete => "FLUSHCOMPLETE",
 		}
 	}
 
@@ -351,6 +385,8 @@ impl State {
 			2 => Ok(Self::TryOpen),
 			3 => Ok(Self::Open),
 			4 => Ok(Self::Closed),
+			5 => Ok(Self::Flushing),
+			6 => Ok(Self::FlushComplete),
 			_ => Err(Error::unknown_state(s)),
 		}
 	}
@@ -421,6 +457,7 @@ pub mod test_util {
 			counterparty: Some(get_dummy_raw_counterparty()),
 			connection_hops: vec![ConnectionId::default().to_string()],
 			version: "ics20".to_string(), // The version is not validated.
+			upgrade_sequence: 0,
 		}
 	}
 }
@@ -19,13 +19,17 @@ use num_traits::float::FloatCore;
 
 use crate::{
 	core::{
+		ics02_client::context::ClientTypes,
 		ics04_channel::{
 			channel::ChannelEnd,
 			commitment::{AcknowledgementCommitment, PacketCommitment},
 			error::Error,
-			handler::{recv_packet::RecvPacketResult, ChannelIdState, ChannelResult},
+			handler::{
+				recv_packet::RecvPacketResult, ChannelIdState, ChannelResult, ChannelUpgradeResult,
+			},
 			msgs::acknowledgement::Acknowledgement,
 			packet::Receipt,
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
 	},
@@ -70,6 +74,30 @@ pub trait ChannelReader {
 		key: &(PortId, ChannelId, Sequence),
 	) -> Result<AcknowledgementCommitment, Error>;
 
+	/// Returns the upgrade proposed for the given channel while an upgrade handshake is in
+	/// progress.
+	fn channel_upgrade(&self, port_channel_id: &(PortId, ChannelId)) -> Result<Upgrade, Error>;
+
+	/// Returns the upgrade of the counterparty channel, stored once this end started flushing.
+	fn counterparty_upgrade(&self, port_channel_id: &(PortId, ChannelId))
+		-> Result<Upgrade, Error>;
+
+	/// Returns the receipt of the last aborted upgrade of the given channel.
+	fn upgrade_error_receipt(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, Error>;
+
+	/// Returns the number of packets sent on the given channel whose commitments have not been
+	/// deleted yet, i.e. that were neither acknowledged nor timed out.
+	fn inflight_packet_count(&self, port_channel_id: &(PortId, ChannelId)) -> Result<u64, Error>;
+
+	/// Returns the time the counterparty is given to flush its in-flight packets once this end
+	/// accepted a channel upgrade.
+	fn upgrade_timeout(&self) -> Duration {
+		Duration::from_secs(600)
+	}
+
 	fn packet_commitment(
 		&self,
 		packet_data: Vec<u8>,
@@ -119,7 +147,10 @@ pub trait ChannelReader {
 /// A context supplying all the necessary write-only dependencies (i.e., storage writing facility)
 /// for processing any `ChannelMsg`.
 pub trait ChannelKeeper: ChannelReader {
-	fn store_channel_result(&mut self, result: ChannelResult) -> Result<(), Error> {
+	fn store_channel_result<C: ClientTypes>(
+		&mut self,
+		result: ChannelResult<C>,
+	) -> Result<(), Error> {
 		// The handler processed this channel & some modifications occurred, store the new end.
 		self.store_channel((result.port_id.clone(), result.channel_id), &result.channel_end)?;
 
@@ -137,13 +168,36 @@ pub trait ChannelKeeper: ChannelReader {
 			// Initialize send, recv, and ack sequence numbers.
 			self.store_next_sequence_send((result.port_id.clone(), result.channel_id), 1.into())?;
 			self.store_next_sequence_recv((result.port_id.clone(), result.channel_id), 1.into())?;
-			self.store_next_sequence_ack((result.port_id, result.channel_id), 1.into())?;
+			self.store_next_sequence_ack((result.port_id.clone(), result.channel_id), 1.into())?;
+		}
+
+		let port_channel_id = (result.port_id, result.channel_id);
+		match result.upgrade {
+			Some(ChannelUpgradeResult::Proposed { upgrade, counterparty_upgrade }) => {
+				self.store_channel_upgrade(port_channel_id.clone(), &upgrade)?;
+				if let Some(counterparty_upgrade) = counterparty_upgrade {
+					self.store_counterparty_upgrade(port_channel_id, &counterparty_upgrade)?;
+				}
+			},
+			Some(ChannelUpgradeResult::Cleared) => {
+				self.delete_channel_upgrade(port_channel_id.clone())?;
+				self.delete_counterparty_upgrade(port_channel_id)?;
+			},
+			Some(ChannelUpgradeResult::Aborted(error_receipt)) => {
+				self.delete_channel_upgrade(port_channel_id.clone())?;
+				self.delete_counterparty_upgrade(port_channel_id.clone())?;
+				self.store_upgrade_error_receipt(port_channel_id, &error_receipt)?;
+			},
+			None => {},
 		}
 
 		Ok(())
 	}
 
-	fn store_packet_result(&mut self, general_result: PacketResult) -> Result<(), Error> {
+	fn store_packet_result<C: ClientTypes>(
+		&mut self,
+		general_result: PacketResult<C>,
+	) -> Result<(), Error> {
 		match general_result {
 			PacketResult::Send(res) => {
 				self.store_next_sequence_send(
@@ -159,11 +213,20 @@ pub trait ChannelKeeper: ChannelReader {
 				self.store_send_packet((res.port_id.clone(), res.channel_id, res.seq), res.packet)?;
 			},
 			PacketResult::Recv(res) => match res {
-				RecvPacketResult::Ordered { port_id, channel_id, next_seq_recv, packet } => {
+				RecvPacketResult::Ordered {
+					port_id, channel_id, next_seq_recv, packet, ..
+				} => {
 					self.store_next_sequence_recv((port_id.clone(), channel_id), next_seq_recv)?;
 					self.store_recv_packet((port_id, channel_id, packet.sequence), packet)?
 				},
-				RecvPacketResult::Unordered { port_id, channel_id, sequence, receipt, packet } => {
+				RecvPacketResult::Unordered {
+					port_id,
+					channel_id,
+					sequence,
+					receipt,
+					packet,
+					..
+				} => {
 					self.store_packet_receipt((port_id.clone(), channel_id, sequence), receipt)?;
 					self.store_recv_packet((port_id, channel_id, packet.sequence), packet)?
 				},
@@ -186,6 +249,10 @@ pub trait ChannelKeeper: ChannelReader {
 					//Ordered Channel
 					self.store_next_sequence_ack((res.port_id.clone(), res.channel_id), s)?;
 				}
+				if let Some(c) = res.channel {
+					// Channel finished flushing its in-flight packets
+					self.store_channel((res.port_id.clone(), res.channel_id), &c)?;
+				}
 
 				// Delete packet commitment since packet has been aknowledged
 				self.delete_packet_commitment((res.port_id.clone(), res.channel_id, res.seq))?;
@@ -260,6 +327,35 @@ pub trait ChannelKeeper: ChannelReader {
 		channel_end: &ChannelEnd,
 	) -> Result<(), Error>;
 
+	/// Stores the upgrade proposed for the channel, replacing any previous one.
+	fn store_channel_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: &Upgrade,
+	) -> Result<(), Error>;
+
+	fn delete_channel_upgrade(&mut self, port_channel_id: (PortId, ChannelId))
+		-> Result<(), Error>;
+
+	fn store_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: &Upgrade,
+	) -> Result<(), Error>;
+
+	fn delete_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+	) -> Result<(), Error>;
+
+	/// Stores the receipt of an aborted upgrade at a provable path, so the counterparty can
+	/// cancel its side of the handshake.
+	fn store_upgrade_error_receipt(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), Error>;
+
 	fn store_next_sequence_send(
 		&mut self,
 		port_channel_id: (PortId, ChannelId),
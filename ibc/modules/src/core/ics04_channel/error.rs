@@ -27,7 +27,7 @@ use crate::{
 	prelude::*,
 	proofs::ProofError,
 	signer::SignerError,
-	timestamp::Timestamp,
+	timestamp::{Timestamp, TimestampOverflowError},
 	Height,
 };
 
@@ -371,6 +371,121 @@ define_error! {
 					e.client_id, e.height)
 			},
 
+		MissingUpgrade
+			| _ | { "missing channel upgrade" },
+
+		MissingUpgradeFields
+			| _ | { "missing channel upgrade fields" },
+
+		MissingErrorReceipt
+			| _ | { "missing channel upgrade error receipt" },
+
+		UpgradeNotFound
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | {
+				format_args!(
+					"no upgrade in progress for the channel end ({0}, {1})",
+					e.port_id, e.channel_id)
+			},
+
+		UpgradeInProgress
+			{ port_id: PortId, channel_id: ChannelId }
+			| e | {
+				format_args!(
+					"an upgrade is already in progress for the channel end ({0}, {1})",
+					e.port_id, e.channel_id)
+			},
+
+		InvalidUpgradeFields
+			{ reason: String }
+			| e | {
+				format_args!(
+					"invalid channel upgrade fields: {0}",
+					e.reason)
+			},
+
+		InvalidUpgradeSequence
+			{ expected: u64, actual: u64 }
+			| e | {
+				format_args!(
+					"invalid upgrade sequence: expected {0}; actual {1}",
+					e.expected, e.actual)
+			},
+
+		IncompatibleCounterpartyUpgrade
+			{ reason: String }
+			| e | {
+				format_args!(
+					"counterparty upgrade is incompatible: {0}",
+					e.reason)
+			},
+
+		UpgradeTimeoutNotReached
+			| _ | { "channel upgrade timeout has not been reached" },
+
+		UpgradeTimedOut
+			| _ | { "channel upgrade timeout has elapsed" },
+
+		InvalidUpgradeTimeout
+			| _ | { "channel upgrade timeout height and timestamp cannot both be 0" },
+
+		PacketSentAfterUpgrade
+			{ sequence: Sequence, next_sequence_send: Sequence }
+			| e | {
+				format_args!(
+					"packet sequence {0} was sent after the counterparty started flushing at sequence {1}",
+					e.sequence, e.next_sequence_send)
+			},
+
+		VerifyChannelUpgradeFailed
+			[ client_error::Error ]
+			| _ | {
+				"Error verifying channel upgrade"
+			},
+
+		InvalidMultihopProof
+			{ reason: String }
+			| e | {
+				format_args!(
+					"invalid multi-hop proof: {0}",
+					e.reason)
+			},
+
+		VerifyMultihopFailed
+			{ hop: ConnectionId }
+			[ client_error::Error ]
+			| e | {
+				format_args!(
+					"Error verifying the state of connection hop {0}",
+					e.hop)
+			},
+
+		NotEnoughTimeElapsed
+			{
+				current_time: Timestamp,
+				earliest_time: Timestamp,
+			}
+			| e | {
+				format_args!(
+					"not enough time elapsed, current timestamp {0} is still less than earliest acceptable timestamp {1}",
+					e.current_time, e.earliest_time)
+			},
+
+		NotEnoughBlocksElapsed
+			{
+				current_height: Height,
+				earliest_height: Height,
+			}
+			| e | {
+				format_args!(
+					"not enough blocks elapsed, current height {0} is still less than earliest acceptable height {1}",
+					e.current_height, e.earliest_height)
+			},
+
+		TimestampOverflow
+			[ TimestampOverflowError ]
+			| _ | { "timestamp overflowed" },
+
 		RouteNotFound
 			| _ | { "route not found" },
 
@@ -16,12 +16,15 @@
 
 use crate::{
 	core::{
+		ics02_client::{context::ClientTypes, handler::update_client},
+		ics03_connection::connection::{ConnectionEnd, State as ConnectionState},
 		ics04_channel::{
-			channel::ChannelEnd,
+			channel::{ChannelEnd, Counterparty, State},
 			error::Error,
-			events::WriteAcknowledgement,
+			events::{UpgradeAttributes, UpgradeError, WriteAcknowledgement},
 			msgs::{ChannelMsg, PacketMsg},
 			packet::PacketResult,
+			upgrade::{ErrorReceipt, Upgrade, UpgradeTimeout},
 		},
 		ics24_host::identifier::{ChannelId, PortId},
 		ics26_routing::context::{
@@ -30,8 +33,9 @@ use crate::{
 	},
 	events::IbcEvent,
 	handler::{HandlerOutput, HandlerOutputBuilder},
+	Height,
 };
-use alloc::string::ToString;
+use alloc::{format, string::ToString};
 use core::fmt::Debug;
 
 pub mod acknowledgement;
@@ -41,6 +45,13 @@ pub mod chan_open_ack;
 pub mod chan_open_confirm;
 pub mod chan_open_init;
 pub mod chan_open_try;
+pub mod chan_upgrade_ack;
+pub mod chan_upgrade_cancel;
+pub mod chan_upgrade_confirm;
+pub mod chan_upgrade_init;
+pub mod chan_upgrade_open;
+pub mod chan_upgrade_timeout;
+pub mod chan_upgrade_try;
 pub mod recv_packet;
 pub mod send_packet;
 pub mod timeout;
@@ -59,12 +70,133 @@ pub enum ChannelIdState {
 	Reused,
 }
 
+/// Defines how the stored upgrade of a channel changes as a result of an upgrade handshake
+/// message.
 #[derive(Clone, Debug)]
-pub struct ChannelResult {
+pub enum ChannelUpgradeResult {
+	/// The upgrade of this end, and possibly the one of the counterparty, must be (re)stored.
+	Proposed { upgrade: Upgrade, counterparty_upgrade: Option<Upgrade> },
+
+	/// The upgrade completed or was cancelled, so all the upgrade state must be removed.
+	Cleared,
+
+	/// The upgrade was aborted on this end. The upgrade state must be removed and the error
+	/// receipt written so that the counterparty can cancel its side of the handshake.
+	Aborted(ErrorReceipt),
+}
+
+#[derive(Clone, Debug)]
+pub struct ChannelResult<C: ClientTypes> {
 	pub port_id: PortId,
 	pub channel_id: ChannelId,
 	pub channel_id_state: ChannelIdState,
 	pub channel_end: ChannelEnd,
+	pub upgrade: Option<ChannelUpgradeResult>,
+	/// The state the client of the channel was left in by the proofs of the message, for clients
+	/// whose state changes with every proof they verify.
+	pub client_update: Option<update_client::Result<C>>,
+}
+
+impl<C: ClientTypes> ChannelResult<C> {
+	fn proposed_upgrade_mut(&mut self) -> Option<&mut Upgrade> {
+		match &mut self.upgrade {
+			Some(ChannelUpgradeResult::Proposed { upgrade, .. }) => Some(upgrade),
+			_ => None,
+		}
+	}
+}
+
+/// Returns the open connection the given channel end is built on.
+pub(crate) fn open_channel_connection<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	channel_end: &ChannelEnd,
+) -> Result<ConnectionEnd, Error> {
+	if channel_end.connection_hops().len() != 1 {
+		return Err(Error::invalid_connection_hops_length(1, channel_end.connection_hops().len()))
+	}
+
+	let conn = ctx
+		.connection_end(&channel_end.connection_hops()[0])
+		.map_err(Error::ics03_connection)?;
+
+	if !conn.state_matches(&ConnectionState::Open) {
+		return Err(Error::connection_not_open(channel_end.connection_hops()[0].clone()))
+	}
+
+	Ok(conn)
+}
+
+/// Builds the channel end the counterparty of `channel_end` is expected to store while the
+/// upgrade handshake is in progress, i.e. before any upgrade field has been applied.
+pub(crate) fn expected_counterparty_channel(
+	port_id: &PortId,
+	channel_id: ChannelId,
+	channel_end: &ChannelEnd,
+	connection_end: &ConnectionEnd,
+	state: State,
+	upgrade_sequence: u64,
+) -> Result<ChannelEnd, Error> {
+	let counterparty_connection_id =
+		connection_end.counterparty().connection_id().ok_or_else(|| {
+			Error::undefined_connection_counterparty(channel_end.connection_hops()[0].clone())
+		})?;
+
+	let mut expected = ChannelEnd::new(
+		state,
+		*channel_end.ordering(),
+		Counterparty::new(port_id.clone(), Some(channel_id)),
+		vec![counterparty_connection_id.clone()],
+		channel_end.version().clone(),
+	);
+	expected.set_upgrade_sequence(upgrade_sequence);
+	Ok(expected)
+}
+
+/// Returns the deadline given to the counterparty for completing an upgrade once this end
+/// started flushing.
+pub(crate) fn flushing_upgrade_timeout<Ctx: ReaderContext>(
+	ctx: &Ctx,
+) -> Result<UpgradeTimeout, Error> {
+	let timestamp = (ctx.host_timestamp() + ctx.upgrade_timeout())
+		.map_err(|_| Error::invalid_upgrade_timeout())?;
+	UpgradeTimeout::new(Height::zero(), timestamp)
+}
+
+/// Aborts the upgrade handshake of a channel: the channel end is restored to OPEN and an error
+/// receipt is written for the given upgrade sequence, which allows the counterparty to cancel its
+/// side of the handshake.
+pub(crate) fn abort_upgrade<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	output: &mut HandlerOutputBuilder<ChannelResult<Ctx>>,
+	port_id: &PortId,
+	channel_id: ChannelId,
+	mut channel_end: ChannelEnd,
+	sequence: u64,
+	error: Error,
+	client_update: Option<update_client::Result<Ctx>>,
+) -> ChannelResult<Ctx> {
+	output.log(format!("channel upgrade aborted: {}", error));
+
+	channel_end.set_state(State::Open);
+
+	output.emit(IbcEvent::UpgradeErrorChannel(UpgradeError::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		port_id.clone(),
+		channel_id,
+		&channel_end,
+	))));
+
+	ChannelResult {
+		port_id: port_id.clone(),
+		channel_id,
+		channel_id_state: ChannelIdState::Reused,
+		channel_end,
+		upgrade: Some(ChannelUpgradeResult::Aborted(ErrorReceipt::new(
+			sequence,
+			error.to_string(),
+		))),
+		client_update,
+	}
 }
 
 pub fn channel_validate<Ctx>(ctx: &Ctx, msg: &ChannelMsg) -> Result<ModuleId, Error>
@@ -79,12 +211,12 @@ where
 	}
 }
 
-/// General entry point for processing any type of message related to the ICS4 channel open and
-/// channel close handshake protocols.
+/// General entry point for processing any type of message related to the ICS4 channel open,
+/// channel close and channel upgrade handshake protocols.
 pub fn channel_dispatch<Ctx>(
 	ctx: &Ctx,
 	msg: &ChannelMsg,
-) -> Result<(HandlerOutputBuilder<()>, ChannelResult), Error>
+) -> Result<(HandlerOutputBuilder<()>, ChannelResult<Ctx>), Error>
 where
 	Ctx: ReaderContext,
 {
@@ -95,6 +227,13 @@ where
 		ChannelMsg::ChannelOpenConfirm(msg) => chan_open_confirm::process::<_>(ctx, msg),
 		ChannelMsg::ChannelCloseInit(msg) => chan_close_init::process(ctx, msg),
 		ChannelMsg::ChannelCloseConfirm(msg) => chan_close_confirm::process::<_>(ctx, msg),
+		ChannelMsg::ChannelUpgradeInit(msg) => chan_upgrade_init::process(ctx, msg),
+		ChannelMsg::ChannelUpgradeTry(msg) => chan_upgrade_try::process::<_>(ctx, msg),
+		ChannelMsg::ChannelUpgradeAck(msg) => chan_upgrade_ack::process::<_>(ctx, msg),
+		ChannelMsg::ChannelUpgradeConfirm(msg) => chan_upgrade_confirm::process::<_>(ctx, msg),
+		ChannelMsg::ChannelUpgradeOpen(msg) => chan_upgrade_open::process::<_>(ctx, msg),
+		ChannelMsg::ChannelUpgradeTimeout(msg) => chan_upgrade_timeout::process::<_>(ctx, msg),
+		ChannelMsg::ChannelUpgradeCancel(msg) => chan_upgrade_cancel::process::<_>(ctx, msg),
 	}?;
 	let HandlerOutput { result, log, events } = output;
 	let builder = HandlerOutput::builder().with_log(log).with_events(events);
@@ -105,9 +244,9 @@ pub fn channel_callback<Ctx>(
 	ctx: &mut Ctx,
 	module_id: &ModuleId,
 	msg: &ChannelMsg,
-	mut result: ChannelResult,
+	mut result: ChannelResult<Ctx>,
 	module_output: &mut ModuleOutputBuilder,
-) -> Result<ChannelResult, Error>
+) -> Result<ChannelResult<Ctx>, Error>
 where
 	Ctx: Ics26Context,
 {
@@ -171,6 +310,60 @@ where
 			&result.channel_id,
 			&msg.signer,
 		)?,
+		ChannelMsg::ChannelUpgradeInit(msg) => {
+			let version = cb.on_chan_upgrade_init(
+				&ctx_clone,
+				module_output,
+				&msg.port_id,
+				&msg.channel_id,
+				msg.fields.ordering,
+				&msg.fields.connection_hops,
+				&msg.fields.version,
+			)?;
+			if let Some(upgrade) = result.proposed_upgrade_mut() {
+				upgrade.fields.version = version;
+			}
+		},
+		ChannelMsg::ChannelUpgradeTry(msg) => {
+			// The handshake was aborted and an error receipt written, the module is not involved.
+			if let Some(upgrade) = result.proposed_upgrade_mut() {
+				let version = cb.on_chan_upgrade_try(
+					&ctx_clone,
+					module_output,
+					&msg.port_id,
+					&msg.channel_id,
+					upgrade.fields.ordering,
+					&upgrade.fields.connection_hops,
+					&msg.counterparty_upgrade_fields.version,
+				)?;
+				upgrade.fields.version = version;
+			}
+		},
+		ChannelMsg::ChannelUpgradeAck(msg) =>
+			if result.proposed_upgrade_mut().is_some() {
+				cb.on_chan_upgrade_ack(
+					&ctx_clone,
+					module_output,
+					&msg.port_id,
+					&msg.channel_id,
+					&msg.counterparty_upgrade.fields.version,
+				)?
+			},
+		ChannelMsg::ChannelUpgradeConfirm(_) | ChannelMsg::ChannelUpgradeOpen(_) => {
+			// Confirm only completes the upgrade if the counterparty already finished flushing.
+			if matches!(result.upgrade, Some(ChannelUpgradeResult::Cleared)) {
+				cb.on_chan_upgrade_open(
+					&ctx_clone,
+					module_output,
+					&result.port_id,
+					&result.channel_id,
+					result.channel_end.ordering,
+					&result.channel_end.connection_hops,
+					&result.channel_end.version,
+				)?
+			}
+		},
+		ChannelMsg::ChannelUpgradeTimeout(_) | ChannelMsg::ChannelUpgradeCancel(_) => {},
 	}
 	Ok(result)
 }
@@ -202,7 +395,7 @@ where
 pub fn packet_dispatch<Ctx>(
 	ctx: &Ctx,
 	msg: &PacketMsg,
-) -> Result<(HandlerOutputBuilder<()>, PacketResult), Error>
+) -> Result<(HandlerOutputBuilder<()>, PacketResult<Ctx>), Error>
 where
 	Ctx: ReaderContext,
 {
@@ -236,8 +429,10 @@ where
 			let ack = cb
 				.on_recv_packet(&ctx_clone, module_output, &mut packet, &msg.signer)
 				.map_err(|e| Error::app_module(e.to_string()))?;
+			// NOTE: an empty acknowledgement means the module will write it asynchronously once it
+			// is known, e.g. after a packet it forwarded has been acknowledged
 			if ack.as_ref().is_empty() {
-				return Err(Error::invalid_acknowledgement())
+				return Ok(())
 			}
 			// NOTE: IBC app modules or middlewares might have written the acknowledgement
 			// synchronously on the OnRecvPacket callback so we only write the acknowledgement if it
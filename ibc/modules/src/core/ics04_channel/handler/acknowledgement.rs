@@ -14,36 +14,39 @@
 
 use crate::{
 	core::{
+		ics02_client::{context::ClientTypes, handler::update_client},
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
-			channel::{Counterparty, Order, State},
+			channel::{ChannelEnd, Counterparty, Order, State},
 			error::Error,
-			events::AcknowledgePacket,
+			events::{AcknowledgePacket, FlushComplete, UpgradeAttributes},
 			handler::verify::verify_packet_acknowledgement_proofs,
 			msgs::acknowledgement::MsgAcknowledgement,
-			packet::{PacketResult, Sequence},
+			packet::{Packet, PacketResult, Sequence},
 		},
 		ics24_host::identifier::{ChannelId, PortId},
 		ics26_routing::context::ReaderContext,
 	},
 	events::IbcEvent,
-	handler::{HandlerOutput, HandlerResult},
+	handler::{HandlerOutput, HandlerOutputBuilder, HandlerResult},
 	prelude::*,
 };
 use core::fmt::Debug;
 
 #[derive(Clone, Debug)]
-pub struct AckPacketResult {
+pub struct AckPacketResult<C: ClientTypes> {
 	pub port_id: PortId,
 	pub channel_id: ChannelId,
 	pub seq: Sequence,
 	pub seq_number: Option<Sequence>,
+	pub channel: Option<ChannelEnd>,
+	pub client_update: Option<update_client::Result<C>>,
 }
 
 pub fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgAcknowledgement,
-) -> HandlerResult<PacketResult, Error> {
+) -> HandlerResult<PacketResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let packet = &msg.packet;
@@ -51,7 +54,7 @@ pub fn process<Ctx: ReaderContext>(
 	let source_channel_end =
 		ctx.channel_end(&(packet.source_port.clone(), packet.source_channel))?;
 
-	if !source_channel_end.state_matches(&State::Open) {
+	if !matches!(source_channel_end.state, State::Open | State::Flushing) {
 		return Err(Error::channel_closed(packet.source_channel))
 	}
 
@@ -90,15 +93,20 @@ pub fn process<Ctx: ReaderContext>(
 	}
 
 	// Verify the acknowledgement proof
+	let mut client_update = None;
 	verify_packet_acknowledgement_proofs::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
+		&source_channel_end,
 		packet,
 		msg.acknowledgement.clone(),
 		&connection_end,
 		&msg.proofs,
 	)?;
 
+	let channel = flush_channel(ctx, &mut output, packet, source_channel_end.clone())?;
+
 	let result = if source_channel_end.order_matches(&Order::Ordered) {
 		let next_seq_ack =
 			ctx.get_next_sequence_ack(&(packet.source_port.clone(), packet.source_channel))?;
@@ -112,6 +120,8 @@ pub fn process<Ctx: ReaderContext>(
 			channel_id: packet.source_channel,
 			seq: packet.sequence,
 			seq_number: Some(next_seq_ack.increment()),
+			channel,
+			client_update,
 		})
 	} else {
 		PacketResult::Ack(AckPacketResult {
@@ -119,6 +129,8 @@ pub fn process<Ctx: ReaderContext>(
 			channel_id: packet.source_channel,
 			seq: packet.sequence,
 			seq_number: None,
+			channel,
+			client_update,
 		})
 	};
 
@@ -132,6 +144,35 @@ pub fn process<Ctx: ReaderContext>(
 	Ok(output.with_result(result))
 }
 
+/// Moves a FLUSHING channel to FLUSHCOMPLETE once the given packet, the last one in flight, is
+/// acknowledged or timed out. Returns the updated channel end if the state changed.
+pub(crate) fn flush_channel<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	output: &mut HandlerOutputBuilder<PacketResult<Ctx>>,
+	packet: &Packet,
+	mut channel_end: ChannelEnd,
+) -> Result<Option<ChannelEnd>, Error> {
+	if !channel_end.state_matches(&State::Flushing) {
+		return Ok(None)
+	}
+
+	// The commitment of the given packet is only deleted when the result is stored.
+	let port_channel_id = (packet.source_port.clone(), packet.source_channel);
+	if ctx.inflight_packet_count(&port_channel_id)? > 1 {
+		return Ok(None)
+	}
+
+	channel_end.set_state(State::FlushComplete);
+	output.emit(IbcEvent::FlushCompleteChannel(FlushComplete::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		packet.source_port.clone(),
+		packet.source_channel,
+		&channel_end,
+	))));
+
+	Ok(Some(channel_end))
+}
+
 #[cfg(test)]
 mod tests {
 	use test_log::test;
@@ -33,7 +33,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgChannelCloseInit,
-) -> HandlerResult<ChannelResult, Error> {
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	// Unwrap the old channel end and validate it against the message.
@@ -45,8 +45,8 @@ pub(crate) fn process<Ctx: ReaderContext>(
 	}
 
 	// An OPEN IBC connection running on the local (host) chain should exist.
-	if channel_end.connection_hops().len() != 1 {
-		return Err(Error::invalid_connection_hops_length(1, channel_end.connection_hops().len()))
+	if channel_end.connection_hops().is_empty() {
+		return Err(Error::invalid_connection_hops_length(1, 0))
 	}
 
 	let conn = ctx
@@ -76,6 +76,8 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		channel_id: msg.channel_id,
 		channel_id_state: ChannelIdState::Reused,
 		channel_end,
+		upgrade: None,
+		client_update: None,
 	};
 
 	output.emit(IbcEvent::CloseInitChannel(
@@ -21,7 +21,10 @@ use crate::{
 			channel::{ChannelEnd, Counterparty, State},
 			error::Error,
 			events::Attributes,
-			handler::{verify::verify_channel_proofs, ChannelIdState, ChannelResult},
+			handler::{
+				verify::{counterparty_connection_hops, verify_channel_proofs},
+				ChannelIdState, ChannelResult,
+			},
 			msgs::chan_open_ack::MsgChannelOpenAck,
 		},
 		ics26_routing::context::ReaderContext,
@@ -34,7 +37,7 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgChannelOpenAck,
-) -> HandlerResult<ChannelResult, Error> {
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	// Unwrap the old channel end and validate it against the message.
@@ -47,8 +50,8 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	// An OPEN IBC connection running on the local (host) chain should exist.
 
-	if channel_end.connection_hops().len() != 1 {
-		return Err(Error::invalid_connection_hops_length(1, channel_end.connection_hops().len()))
+	if channel_end.connection_hops().is_empty() {
+		return Err(Error::invalid_connection_hops_length(1, 0))
 	}
 
 	let conn = ctx
@@ -64,12 +67,11 @@ pub(crate) fn process<Ctx: ReaderContext>(
 
 	let expected_counterparty = Counterparty::new(msg.port_id.clone(), Some(msg.channel_id));
 
-	let counterparty = conn.counterparty();
-	let ccid = counterparty.connection_id().ok_or_else(|| {
-		Error::undefined_connection_counterparty(channel_end.connection_hops()[0].clone())
-	})?;
-
-	let expected_connection_hops = vec![ccid.clone()];
+	let expected_connection_hops = counterparty_connection_hops(
+		channel_end.connection_hops(),
+		&conn,
+		msg.proofs.object_proof(),
+	)?;
 
 	let expected_channel_end = ChannelEnd::new(
 		State::TryOpen,
@@ -83,8 +85,10 @@ pub(crate) fn process<Ctx: ReaderContext>(
 	channel_end.set_counterparty_channel_id(msg.counterparty_channel_id);
 
 	//2. Verify proofs
+	let mut client_update = None;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&channel_end,
 		&conn,
@@ -112,6 +116,8 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		channel_id: msg.channel_id,
 		channel_id_state: ChannelIdState::Reused,
 		channel_end,
+		upgrade: None,
+		client_update,
 	};
 
 	output.emit(IbcEvent::OpenAckChannel(
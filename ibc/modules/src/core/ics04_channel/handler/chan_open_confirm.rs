@@ -21,7 +21,10 @@ use crate::{
 			channel::{ChannelEnd, Counterparty, State},
 			error::Error,
 			events::Attributes,
-			handler::{verify::verify_channel_proofs, ChannelIdState, ChannelResult},
+			handler::{
+				verify::{counterparty_connection_hops, verify_channel_proofs},
+				ChannelIdState, ChannelResult,
+			},
 			msgs::chan_open_confirm::MsgChannelOpenConfirm,
 		},
 		ics26_routing::context::ReaderContext,
@@ -34,7 +37,7 @@ use crate::{
 pub(crate) fn process<Ctx>(
 	ctx: &Ctx,
 	msg: &MsgChannelOpenConfirm,
-) -> HandlerResult<ChannelResult, Error>
+) -> HandlerResult<ChannelResult<Ctx>, Error>
 where
 	Ctx: ReaderContext,
 {
@@ -49,8 +52,8 @@ where
 	}
 
 	// An OPEN IBC connection running on the local (host) chain should exist.
-	if channel_end.connection_hops().len() != 1 {
-		return Err(Error::invalid_connection_hops_length(1, channel_end.connection_hops().len()))
+	if channel_end.connection_hops().is_empty() {
+		return Err(Error::invalid_connection_hops_length(1, 0))
 	}
 
 	let conn = ctx
@@ -66,12 +69,11 @@ where
 
 	let expected_counterparty = Counterparty::new(msg.port_id.clone(), Some(msg.channel_id));
 
-	let connection_counterparty = conn.counterparty();
-	let ccid = connection_counterparty.connection_id().ok_or_else(|| {
-		Error::undefined_connection_counterparty(channel_end.connection_hops()[0].clone())
-	})?;
-
-	let expected_connection_hops = vec![ccid.clone()];
+	let expected_connection_hops = counterparty_connection_hops(
+		channel_end.connection_hops(),
+		&conn,
+		msg.proofs.object_proof(),
+	)?;
 
 	let expected_channel_end = ChannelEnd::new(
 		State::Open,
@@ -81,8 +83,10 @@ where
 		channel_end.version().clone(),
 	);
 	//2. Verify proofs
+	let mut client_update = None;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&channel_end,
 		&conn,
@@ -110,6 +114,8 @@ where
 		channel_id: msg.channel_id,
 		channel_id_state: ChannelIdState::Reused,
 		channel_end,
+		upgrade: None,
+		client_update,
 	};
 
 	output.emit(IbcEvent::OpenConfirmChannel(
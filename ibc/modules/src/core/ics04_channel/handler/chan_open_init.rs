@@ -34,11 +34,11 @@ use crate::{
 pub(crate) fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgChannelOpenInit,
-) -> HandlerResult<ChannelResult, Error> {
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
-	if msg.channel.connection_hops().len() != 1 {
-		return Err(Error::invalid_connection_hops_length(1, msg.channel.connection_hops().len()))
+	if msg.channel.connection_hops().is_empty() {
+		return Err(Error::invalid_connection_hops_length(1, 0))
 	}
 
 	// An IBC connection running on the local (host) chain should exist.
@@ -86,6 +86,8 @@ pub(crate) fn process<Ctx: ReaderContext>(
 		channel_id: chan_id,
 		channel_end: new_channel_end,
 		channel_id_state: ChannelIdState::Generated,
+		upgrade: None,
+		client_update: None,
 	};
 
 	output.emit(IbcEvent::OpenInitChannel(
@@ -21,7 +21,10 @@ use crate::{
 			channel::{ChannelEnd, Counterparty, State},
 			error::Error,
 			events::Attributes,
-			handler::{verify::verify_channel_proofs, ChannelIdState, ChannelResult},
+			handler::{
+				verify::{counterparty_connection_hops, verify_channel_proofs},
+				ChannelIdState, ChannelResult,
+			},
 			msgs::chan_open_try::MsgChannelOpenTry,
 		},
 		ics24_host::identifier::ChannelId,
@@ -35,7 +38,7 @@ use crate::{
 pub(crate) fn process<Ctx>(
 	ctx: &Ctx,
 	msg: &MsgChannelOpenTry,
-) -> HandlerResult<ChannelResult, Error>
+) -> HandlerResult<ChannelResult<Ctx>, Error>
 where
 	Ctx: ReaderContext,
 {
@@ -61,8 +64,8 @@ where
 	};
 
 	// An IBC connection running on the local (host) chain should exist.
-	if msg.channel.connection_hops().len() != 1 {
-		return Err(Error::invalid_connection_hops_length(1, msg.channel.connection_hops().len()))
+	if msg.channel.connection_hops().is_empty() {
+		return Err(Error::invalid_connection_hops_length(1, 0))
 	}
 
 	let conn = ctx
@@ -88,11 +91,11 @@ where
 	//      the port should be identical with the port we're using; the channel id should not be set
 	//      since the counterparty cannot know yet which ID did we choose.
 	let expected_counterparty = Counterparty::new(msg.port_id.clone(), None);
-	let counterparty = conn.counterparty();
-	let ccid = counterparty.connection_id().ok_or_else(|| {
-		Error::undefined_connection_counterparty(msg.channel.connection_hops()[0].clone())
-	})?;
-	let expected_connection_hops = vec![ccid.clone()];
+	let expected_connection_hops = counterparty_connection_hops(
+		msg.channel.connection_hops(),
+		&conn,
+		msg.proofs.object_proof(),
+	)?;
 
 	// The other party should be storing a channel end in this configuration.
 	let expected_channel_end = ChannelEnd::new(
@@ -104,8 +107,10 @@ where
 	);
 
 	// 2. Actual proofs are verified now.
+	let mut client_update = None;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&new_channel_end,
 		&conn,
@@ -132,6 +137,8 @@ where
 		channel_id_state: ChannelIdState::Generated,
 		channel_id,
 		channel_end: new_channel_end,
+		upgrade: None,
+		client_update,
 	};
 
 	output.emit(IbcEvent::OpenTryChannel(
@@ -149,7 +156,10 @@ mod tests {
 
 	use crate::{
 		core::{
-			ics02_client::{context::ClientReader, error as ics02_error},
+			ics02_client::{
+				client_consensus::ConsensusState, client_state::ClientState, context::ClientReader,
+				error as ics02_error,
+			},
 			ics03_connection::{
 				connection::{
 					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
@@ -168,17 +178,30 @@ mod tests {
 					},
 					ChannelMsg,
 				},
+				multihop::{MultihopProof, MultihopProofs},
+			},
+			ics24_host::{
+				identifier::{ChannelId, ClientId, ConnectionId},
+				path::{
+					ChannelEndsPath, ClientConsensusStatePath, ClientStatePath, ConnectionsPath,
+					Path,
+				},
 			},
-			ics24_host::identifier::{ChannelId, ClientId, ConnectionId},
 		},
 		events::IbcEvent,
 		mock::{
-			client_state::MockClientState,
+			client_state::{
+				AnyClientState, AnyConsensusState, MockClientState, MockConsensusState,
+			},
 			context::{MockClientTypes, MockContext},
+			header::MockHeader,
 		},
+		proofs::Proofs,
+		test_utils::get_dummy_proof,
 		timestamp::ZERO_DURATION,
 		Height,
 	};
+	use tendermint_proto::Protobuf;
 
 	#[test]
 	fn chan_open_try_msg_processing() {
@@ -220,6 +243,67 @@ mod tests {
 		// This message does not assume a channel should already be initialized.
 		let msg_vanilla = msg.clone();
 
+		// A message for a multi-hop channel, which reaches the counterparty through the chain at
+		// the other end of connection `conn_id`.
+		let hop_conn_id = ConnectionId::new(7);
+		let hop_client_id = ClientId::new(&MockClientState::client_type(), 7).unwrap();
+		let hop_conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			hop_client_id.clone(),
+			ConnectionCounterparty::new(
+				client_id.clone(),
+				Some(ConnectionId::new(3)),
+				conn_end.counterparty().prefix().clone(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let hop_client_state =
+			AnyClientState::Mock(MockClientState::new(MockHeader::new(Height::new(0, 5)).into()));
+		let hop_consensus_state =
+			AnyConsensusState::Mock(MockConsensusState::new(MockHeader::new(Height::new(0, 5))));
+		let hop_proof = |value: Vec<u8>, path: Path| MultihopProof {
+			proof: get_dummy_proof().try_into().unwrap(),
+			value,
+			prefixed_key: vec!["ibc".to_string(), path.to_string()],
+		};
+		let multihop_proofs = MultihopProofs {
+			key_proof: hop_proof(
+				vec![],
+				ChannelEndsPath(msg.channel.counterparty().port_id().clone(), ChannelId::default())
+					.into(),
+			),
+			connection_proofs: vec![hop_proof(
+				hop_conn_end.encode_vec().unwrap(),
+				ConnectionsPath(hop_conn_id.clone()).into(),
+			)],
+			consensus_proofs: vec![hop_proof(
+				hop_consensus_state.encode_to_vec().unwrap(),
+				ClientConsensusStatePath { client_id: hop_client_id.clone(), epoch: 0, height: 5 }
+					.into(),
+			)],
+			client_state_proofs: vec![hop_proof(
+				hop_client_state.encode_to_vec().unwrap(),
+				ClientStatePath(hop_client_id).into(),
+			)],
+		};
+		let multihop_msg = |proofs: MultihopProofs| {
+			let mut msg = msg_vanilla.clone();
+			msg.channel.connection_hops = vec![conn_id.clone(), hop_conn_id.clone()];
+			msg.proofs = Proofs::new(
+				proofs.encode_vec().unwrap().try_into().unwrap(),
+				None,
+				None,
+				None,
+				Height::new(0, proof_height),
+			)
+			.unwrap();
+			msg
+		};
+		let msg_multihop = multihop_msg(multihop_proofs.clone());
+		let msg_multihop_missing_hop =
+			multihop_msg(MultihopProofs { consensus_proofs: vec![], ..multihop_proofs });
+
 		// A preloaded channel end that resides in the context. This is constructed so as to be
 		// consistent with the incoming ChanOpenTry message `msg`.
 		let correct_chan_end = ChannelEnd::new(
@@ -293,12 +377,38 @@ mod tests {
 				name: "Processing is successful against an empty context (no preexisting channel)"
 					.to_string(),
 				ctx: context
+					.clone()
 					.with_client(&client_id, Height::new(0, proof_height))
-					.with_connection(conn_id, conn_end),
+					.with_connection(conn_id.clone(), conn_end.clone()),
 				msg: ChannelMsg::ChannelOpenTry(msg_vanilla),
 				want_pass: true,
 				match_error: Box::new(|_| {}),
 			},
+			Test {
+				name: "Processing is successful on a multi-hop channel".to_string(),
+				ctx: context
+					.clone()
+					.with_client(&client_id, Height::new(0, proof_height))
+					.with_connection(conn_id.clone(), conn_end.clone()),
+				msg: ChannelMsg::ChannelOpenTry(msg_multihop),
+				want_pass: true,
+				match_error: Box::new(|_| {}),
+			},
+			Test {
+				name: "Processing fails b/c the multi-hop proof misses a consensus state proof"
+					.to_string(),
+				ctx: context
+					.with_client(&client_id, Height::new(0, proof_height))
+					.with_connection(conn_id, conn_end),
+				msg: ChannelMsg::ChannelOpenTry(msg_multihop_missing_hop),
+				want_pass: false,
+				match_error: Box::new(|e| match e {
+					error::ErrorDetail::InvalidMultihopProof(_) => {},
+					_ => {
+						panic!("Expected InvalidMultihopProof, instead got {}", e)
+					},
+				}),
+			},
 		]
 		.into_iter()
 		.collect();
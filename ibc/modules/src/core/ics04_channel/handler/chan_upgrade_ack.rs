@@ -0,0 +1,317 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeAck`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::State,
+			error::Error,
+			events::{UpgradeAck, UpgradeAttributes},
+			handler::{
+				abort_upgrade,
+				chan_upgrade_try::check_upgrade_compatibility,
+				expected_counterparty_channel, flushing_upgrade_timeout, open_channel_connection,
+				verify::{verify_channel_proofs, verify_channel_upgrade_proofs},
+				ChannelIdState, ChannelResult, ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_ack::MsgChannelUpgradeAck,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	events::IbcEvent,
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeAck,
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	// The channel is FLUSHING already if both ends proposed an upgrade (crossing hellos).
+	if !matches!(channel_end.state, State::Open | State::Flushing) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	let mut upgrade = ctx
+		.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id))?;
+
+	let conn = open_channel_connection(ctx, &channel_end)?;
+
+	// Verify that the counterparty is flushing the packets for the same upgrade.
+	let expected_channel_end = expected_counterparty_channel(
+		&msg.port_id,
+		msg.channel_id,
+		&channel_end,
+		&conn,
+		State::Flushing,
+		channel_end.upgrade_sequence(),
+	)?;
+
+	let mut client_update = None;
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&expected_channel_end,
+		msg.proofs.object_proof(),
+	)?;
+
+	let upgrade_proof = msg.proofs.other_proof().as_ref().ok_or_else(Error::missing_upgrade)?;
+
+	verify_channel_upgrade_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&msg.counterparty_upgrade,
+		upgrade_proof,
+	)?;
+
+	let sequence = channel_end.upgrade_sequence();
+
+	if channel_end.state_matches(&State::Open) {
+		let proposed_conn = ctx
+			.connection_end(&upgrade.fields.connection_hops[0])
+			.map_err(Error::ics03_connection)?;
+
+		let compatibility = check_upgrade_compatibility(
+			&upgrade.fields,
+			&msg.counterparty_upgrade.fields,
+			&proposed_conn,
+		)
+		.and_then(|_| {
+			if upgrade.fields.version != msg.counterparty_upgrade.fields.version {
+				return Err(Error::incompatible_counterparty_upgrade(format!(
+					"expected version {}, got {}",
+					upgrade.fields.version, msg.counterparty_upgrade.fields.version
+				)))
+			}
+			Ok(())
+		});
+
+		if let Err(error) = compatibility {
+			let result = abort_upgrade(
+				ctx,
+				&mut output,
+				&msg.port_id,
+				msg.channel_id,
+				channel_end,
+				sequence,
+				error,
+				client_update,
+			);
+			return Ok(output.with_result(result))
+		}
+
+		upgrade.timeout = flushing_upgrade_timeout(ctx)?;
+		upgrade.next_sequence_send = ctx.get_next_sequence_send(&port_channel_id)?;
+		channel_end.set_state(State::Flushing);
+	}
+
+	// The counterparty already gave up on this upgrade, there is no point in flushing for it.
+	if msg
+		.counterparty_upgrade
+		.timeout
+		.has_expired(ctx.host_height(), &ctx.host_timestamp())
+	{
+		let result = abort_upgrade(
+			ctx,
+			&mut output,
+			&msg.port_id,
+			msg.channel_id,
+			channel_end,
+			sequence,
+			Error::upgrade_timed_out(),
+			client_update,
+		);
+		return Ok(output.with_result(result))
+	}
+
+	if ctx.inflight_packet_count(&port_channel_id)? == 0 {
+		channel_end.set_state(State::FlushComplete);
+	}
+
+	output.log("success: channel upgrade ack");
+
+	output.emit(IbcEvent::UpgradeAckChannel(UpgradeAck::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		msg.port_id.clone(),
+		msg.channel_id,
+		&channel_end,
+	))));
+
+	let result = ChannelResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_id_state: ChannelIdState::Reused,
+		channel_end,
+		upgrade: Some(ChannelUpgradeResult::Proposed {
+			upgrade,
+			counterparty_upgrade: Some(msg.counterparty_upgrade.clone()),
+		}),
+		client_update,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				msgs::test_util::get_dummy_raw_counterparty,
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				handler::{channel_dispatch, ChannelUpgradeResult},
+				msgs::{
+					chan_upgrade_ack::{
+						test_util::get_dummy_raw_msg_chan_upgrade_ack, MsgChannelUpgradeAck,
+					},
+					ChannelMsg,
+				},
+				upgrade::{Upgrade, UpgradeTimeout},
+				Version,
+			},
+			ics24_host::identifier::{ClientId, ConnectionId},
+		},
+		events::IbcEvent,
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+		timestamp::ZERO_DURATION,
+		Height,
+	};
+
+	#[test]
+	fn chan_upgrade_ack_msg_processing() {
+		struct Test {
+			name: String,
+			ctx: MockContext<MockClientTypes>,
+			msg: ChannelMsg,
+			want_pass: bool,
+		}
+
+		let proof_height = 10;
+		let msg = MsgChannelUpgradeAck::try_from(get_dummy_raw_msg_chan_upgrade_ack(proof_height))
+			.unwrap();
+
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+
+		let mut chan_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+		chan_end.set_upgrade_sequence(1);
+
+		// The upgrade proposed by this end in `ChanUpgradeInit`.
+		let upgrade =
+			Upgrade::new(msg.counterparty_upgrade.fields.clone(), UpgradeTimeout::none(), 0.into());
+
+		let context = MockContext::default()
+			.with_client(&ClientId::default(), Height::new(0, proof_height))
+			.with_connection(ConnectionId::default(), conn_end)
+			.with_channel(msg.port_id.clone(), msg.channel_id, chan_end.clone())
+			.with_send_sequence(msg.port_id.clone(), msg.channel_id, 1.into());
+
+		let tests: Vec<Test> = vec![
+			Test {
+				name: "Processing fails because no upgrade is in progress".to_string(),
+				ctx: context.clone(),
+				msg: ChannelMsg::ChannelUpgradeAck(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the channel already flushed its packets"
+					.to_string(),
+				ctx: context
+					.clone()
+					.with_channel(
+						msg.port_id.clone(),
+						msg.channel_id,
+						ChannelEnd { state: State::FlushComplete, ..chan_end },
+					)
+					.with_channel_upgrade(msg.port_id.clone(), msg.channel_id, upgrade.clone()),
+				msg: ChannelMsg::ChannelUpgradeAck(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Good parameters".to_string(),
+				ctx: context.with_channel_upgrade(msg.port_id.clone(), msg.channel_id, upgrade),
+				msg: ChannelMsg::ChannelUpgradeAck(msg),
+				want_pass: true,
+			},
+		]
+		.into_iter()
+		.collect();
+
+		for test in tests {
+			let res = channel_dispatch(&test.ctx, &test.msg);
+			match res {
+				Ok((proto_output, res)) => {
+					assert!(
+						test.want_pass,
+						"chan_upgrade_ack: test passed but was supposed to fail for test: {}",
+						test.name,
+					);
+					// No packets are in flight, the channel is flushed right away.
+					assert_eq!(res.channel_end.state, State::FlushComplete);
+					assert!(matches!(
+						res.upgrade,
+						Some(ChannelUpgradeResult::Proposed { counterparty_upgrade: Some(_), .. })
+					));
+
+					let proto_output = proto_output.with_result(());
+					assert!(!proto_output.events.is_empty());
+					for e in proto_output.events.iter() {
+						assert!(matches!(e, &IbcEvent::UpgradeAckChannel(_)));
+					}
+				},
+				Err(e) => {
+					assert!(
+						!test.want_pass,
+						"chan_upgrade_ack: did not pass test: {}, \nparams {:?} {:?} error: {:?}",
+						test.name,
+						test.msg,
+						test.ctx.clone(),
+						e,
+					);
+				},
+			}
+		}
+	}
+}
@@ -0,0 +1,249 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeCancel`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::State,
+			error::Error,
+			events::{UpgradeAttributes, UpgradeCancel},
+			handler::{
+				open_channel_connection, verify::verify_channel_upgrade_error_proofs,
+				ChannelIdState, ChannelResult, ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_cancel::MsgChannelUpgradeCancel,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	events::IbcEvent,
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeCancel,
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	ctx.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id))?;
+
+	// Once this end is done flushing the counterparty can only move the upgrade forward, or let
+	// it time out.
+	if channel_end.state_matches(&State::FlushComplete) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	// An error receipt for an older upgrade cannot cancel the current one.
+	if msg.error_receipt.sequence < channel_end.upgrade_sequence() {
+		return Err(Error::invalid_upgrade_sequence(
+			channel_end.upgrade_sequence(),
+			msg.error_receipt.sequence,
+		))
+	}
+
+	let conn = open_channel_connection(ctx, &channel_end)?;
+
+	let mut client_update = None;
+	verify_channel_upgrade_error_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&msg.error_receipt,
+		msg.proofs.object_proof(),
+	)?;
+
+	// Restore the channel end and sync the upgrade sequence with the counterparty.
+	channel_end.set_state(State::Open);
+	channel_end.set_upgrade_sequence(msg.error_receipt.sequence);
+
+	output.log("success: channel upgrade cancel");
+
+	output.emit(IbcEvent::UpgradeCancelChannel(UpgradeCancel::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		msg.port_id.clone(),
+		msg.channel_id,
+		&channel_end,
+	))));
+
+	let result = ChannelResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_id_state: ChannelIdState::Reused,
+		channel_end,
+		upgrade: Some(ChannelUpgradeResult::Cleared),
+		client_update,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				msgs::test_util::get_dummy_raw_counterparty,
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				handler::{channel_dispatch, ChannelUpgradeResult},
+				msgs::{
+					chan_upgrade_cancel::{
+						test_util::get_dummy_raw_msg_chan_upgrade_cancel, MsgChannelUpgradeCancel,
+					},
+					ChannelMsg,
+				},
+				upgrade::{test_util::get_dummy_raw_upgrade, Upgrade},
+				Version,
+			},
+			ics24_host::identifier::{ClientId, ConnectionId},
+		},
+		events::IbcEvent,
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+		timestamp::ZERO_DURATION,
+		Height,
+	};
+
+	#[test]
+	fn chan_upgrade_cancel_msg_processing() {
+		struct Test {
+			name: String,
+			ctx: MockContext<MockClientTypes>,
+			msg: ChannelMsg,
+			want_pass: bool,
+		}
+
+		let proof_height = 10;
+		let msg =
+			MsgChannelUpgradeCancel::try_from(get_dummy_raw_msg_chan_upgrade_cancel(proof_height))
+				.unwrap();
+
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+
+		let mut chan_end = ChannelEnd::new(
+			State::Flushing,
+			Order::Ordered,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+		chan_end.set_upgrade_sequence(1);
+
+		// This end is already past the upgrade the error receipt was written for.
+		let mut newer_chan_end = chan_end.clone();
+		newer_chan_end.set_upgrade_sequence(2);
+
+		let upgrade = Upgrade::try_from(get_dummy_raw_upgrade(proof_height + 100)).unwrap();
+
+		let context = MockContext::default()
+			.with_client(&ClientId::default(), Height::new(0, proof_height))
+			.with_connection(ConnectionId::default(), conn_end)
+			.with_channel(msg.port_id.clone(), msg.channel_id, chan_end.clone());
+		let upgrade_context =
+			context
+				.clone()
+				.with_channel_upgrade(msg.port_id.clone(), msg.channel_id, upgrade);
+
+		let tests: Vec<Test> = vec![
+			Test {
+				name: "Processing fails because no upgrade is in progress".to_string(),
+				ctx: context,
+				msg: ChannelMsg::ChannelUpgradeCancel(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the channel already flushed its packets"
+					.to_string(),
+				ctx: upgrade_context.clone().with_channel(
+					msg.port_id.clone(),
+					msg.channel_id,
+					ChannelEnd { state: State::FlushComplete, ..chan_end },
+				),
+				msg: ChannelMsg::ChannelUpgradeCancel(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the error receipt is for an older upgrade"
+					.to_string(),
+				ctx: upgrade_context.clone().with_channel(
+					msg.port_id.clone(),
+					msg.channel_id,
+					newer_chan_end,
+				),
+				msg: ChannelMsg::ChannelUpgradeCancel(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Good parameters".to_string(),
+				ctx: upgrade_context,
+				msg: ChannelMsg::ChannelUpgradeCancel(msg),
+				want_pass: true,
+			},
+		]
+		.into_iter()
+		.collect();
+
+		for test in tests {
+			let res = channel_dispatch(&test.ctx, &test.msg);
+			match res {
+				Ok((proto_output, res)) => {
+					assert!(
+						test.want_pass,
+						"chan_upgrade_cancel: test passed but was supposed to fail for test: {}",
+						test.name,
+					);
+					// The channel is restored to its parameters before the upgrade.
+					assert_eq!(res.channel_end.state, State::Open);
+					assert_eq!(res.channel_end.ordering, Order::Ordered);
+					assert!(matches!(res.upgrade, Some(ChannelUpgradeResult::Cleared)));
+
+					let proto_output = proto_output.with_result(());
+					assert!(!proto_output.events.is_empty());
+					for e in proto_output.events.iter() {
+						assert!(matches!(e, &IbcEvent::UpgradeCancelChannel(_)));
+					}
+				},
+				Err(e) => {
+					assert!(
+						!test.want_pass,
+						"chan_upgrade_cancel: did not pass test: {}, \nparams {:?} {:?} error: {:?}",
+						test.name,
+						test.msg,
+						test.ctx.clone(),
+						e,
+					);
+				},
+			}
+		}
+	}
+}
@@ -0,0 +1,306 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeConfirm`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::State,
+			error::Error,
+			events::{UpgradeAttributes, UpgradeConfirm, UpgradeOpen},
+			handler::{
+				abort_upgrade, expected_counterparty_channel, open_channel_connection,
+				verify::{verify_channel_proofs, verify_channel_upgrade_proofs},
+				ChannelIdState, ChannelResult, ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_confirm::MsgChannelUpgradeConfirm,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	events::IbcEvent,
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeConfirm,
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	if !channel_end.state_matches(&State::Flushing) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	let upgrade = ctx
+		.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id))?;
+
+	let conn = open_channel_connection(ctx, &channel_end)?;
+
+	// Verify that the counterparty accepted the same upgrade and started flushing too.
+	let expected_channel_end = expected_counterparty_channel(
+		&msg.port_id,
+		msg.channel_id,
+		&channel_end,
+		&conn,
+		msg.counterparty_channel_state,
+		channel_end.upgrade_sequence(),
+	)?;
+
+	let mut client_update = None;
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&expected_channel_end,
+		msg.proofs.object_proof(),
+	)?;
+
+	let upgrade_proof = msg.proofs.other_proof().as_ref().ok_or_else(Error::missing_upgrade)?;
+
+	verify_channel_upgrade_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&msg.counterparty_upgrade,
+		upgrade_proof,
+	)?;
+
+	if msg
+		.counterparty_upgrade
+		.timeout
+		.has_expired(ctx.host_height(), &ctx.host_timestamp())
+	{
+		let sequence = channel_end.upgrade_sequence();
+		let result = abort_upgrade(
+			ctx,
+			&mut output,
+			&msg.port_id,
+			msg.channel_id,
+			channel_end,
+			sequence,
+			Error::upgrade_timed_out(),
+			client_update,
+		);
+		return Ok(output.with_result(result))
+	}
+
+	if ctx.inflight_packet_count(&port_channel_id)? == 0 {
+		channel_end.set_state(State::FlushComplete);
+	}
+
+	output.log("success: channel upgrade confirm");
+
+	output.emit(IbcEvent::UpgradeConfirmChannel(UpgradeConfirm::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		msg.port_id.clone(),
+		msg.channel_id,
+		&channel_end,
+	))));
+
+	// Both ends are done flushing, the upgrade can be applied right away.
+	let upgrade_result = if channel_end.state_matches(&State::FlushComplete) &&
+		msg.counterparty_channel_state == State::FlushComplete
+	{
+		channel_end.apply_upgrade_fields(&upgrade.fields);
+		channel_end.set_state(State::Open);
+
+		output.emit(IbcEvent::UpgradeOpenChannel(UpgradeOpen::from(UpgradeAttributes::new(
+			ctx.host_height(),
+			msg.port_id.clone(),
+			msg.channel_id,
+			&channel_end,
+		))));
+
+		ChannelUpgradeResult::Cleared
+	} else {
+		ChannelUpgradeResult::Proposed {
+			upgrade,
+			counterparty_upgrade: Some(msg.counterparty_upgrade.clone()),
+		}
+	};
+
+	let result = ChannelResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_id_state: ChannelIdState::Reused,
+		channel_end,
+		upgrade: Some(upgrade_result),
+		client_update,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				msgs::test_util::get_dummy_raw_counterparty,
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				handler::{channel_dispatch, ChannelUpgradeResult},
+				msgs::{
+					chan_upgrade_confirm::{
+						test_util::get_dummy_raw_msg_chan_upgrade_confirm, MsgChannelUpgradeConfirm,
+					},
+					ChannelMsg,
+				},
+				Version,
+			},
+			ics24_host::identifier::{ClientId, ConnectionId},
+		},
+		events::IbcEvent,
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+		timestamp::ZERO_DURATION,
+		Height,
+	};
+
+	#[test]
+	fn chan_upgrade_confirm_msg_processing() {
+		struct Test {
+			name: String,
+			ctx: MockContext<MockClientTypes>,
+			msg: ChannelMsg,
+			want_pass: bool,
+		}
+
+		let proof_height = 10;
+		let msg = MsgChannelUpgradeConfirm::try_from(get_dummy_raw_msg_chan_upgrade_confirm(
+			proof_height,
+		))
+		.unwrap();
+
+		let mut flushed_msg = msg.clone();
+		flushed_msg.counterparty_channel_state = State::FlushComplete;
+
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+
+		let mut chan_end = ChannelEnd::new(
+			State::Flushing,
+			Order::Ordered,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+		chan_end.set_upgrade_sequence(1);
+
+		let context = MockContext::default()
+			.with_client(&ClientId::default(), Height::new(0, proof_height))
+			.with_connection(ConnectionId::default(), conn_end)
+			.with_channel(msg.port_id.clone(), msg.channel_id, chan_end.clone());
+		let upgrade_context = context.clone().with_channel_upgrade(
+			msg.port_id.clone(),
+			msg.channel_id,
+			msg.counterparty_upgrade.clone(),
+		);
+
+		let tests: Vec<Test> = vec![
+			Test {
+				name: "Processing fails because no upgrade is in progress".to_string(),
+				ctx: context,
+				msg: ChannelMsg::ChannelUpgradeConfirm(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the channel is not flushing".to_string(),
+				ctx: upgrade_context.clone().with_channel(
+					msg.port_id.clone(),
+					msg.channel_id,
+					ChannelEnd { state: State::Open, ..chan_end },
+				),
+				msg: ChannelMsg::ChannelUpgradeConfirm(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Good parameters, the counterparty is still flushing".to_string(),
+				ctx: upgrade_context.clone(),
+				msg: ChannelMsg::ChannelUpgradeConfirm(msg),
+				want_pass: true,
+			},
+			Test {
+				name: "Good parameters, both ends are flushed".to_string(),
+				ctx: upgrade_context,
+				msg: ChannelMsg::ChannelUpgradeConfirm(flushed_msg),
+				want_pass: true,
+			},
+		]
+		.into_iter()
+		.collect();
+
+		for test in tests {
+			let res = channel_dispatch(&test.ctx, &test.msg);
+			match res {
+				Ok((proto_output, res)) => {
+					assert!(
+						test.want_pass,
+						"chan_upgrade_confirm: test passed but was supposed to fail for test: {}",
+						test.name,
+					);
+					let proto_output = proto_output.with_result(());
+					assert!(matches!(
+						proto_output.events.first(),
+						Some(&IbcEvent::UpgradeConfirmChannel(_))
+					));
+
+					match res.upgrade {
+						// The upgrade was applied, the channel is open again.
+						Some(ChannelUpgradeResult::Cleared) => {
+							assert_eq!(res.channel_end.state, State::Open);
+							assert_eq!(res.channel_end.ordering, Order::Unordered);
+							assert!(matches!(
+								proto_output.events.last(),
+								Some(&IbcEvent::UpgradeOpenChannel(_))
+							));
+						},
+						Some(ChannelUpgradeResult::Proposed { .. }) =>
+							assert_eq!(res.channel_end.state, State::FlushComplete),
+						_ => panic!("chan_upgrade_confirm: unexpected upgrade result"),
+					}
+				},
+				Err(e) => {
+					assert!(
+						!test.want_pass,
+						"chan_upgrade_confirm: did not pass test: {}, \nparams {:?} {:?} error: {:?}",
+						test.name,
+						test.msg,
+						test.ctx.clone(),
+						e,
+					);
+				},
+			}
+		}
+	}
+}
@@ -0,0 +1,227 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeInit`.
+
+use crate::{
+	core::{
+		ics03_connection::connection::State as ConnectionState,
+		ics04_channel::{
+			channel::State,
+			error::Error,
+			events::{UpgradeAttributes, UpgradeInit},
+			handler::{ChannelIdState, ChannelResult, ChannelUpgradeResult},
+			msgs::chan_upgrade_init::MsgChannelUpgradeInit,
+			upgrade::{Upgrade, UpgradeTimeout},
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	events::IbcEvent,
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeInit,
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	// Only an open channel which is not being upgraded already can be upgraded.
+	if !channel_end.state_matches(&State::Open) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	if ctx.channel_upgrade(&port_channel_id).is_ok() {
+		return Err(Error::upgrade_in_progress(msg.port_id.clone(), msg.channel_id))
+	}
+
+	msg.fields.validate_basic()?;
+	if msg.fields.ordering == channel_end.ordering &&
+		msg.fields.connection_hops == channel_end.connection_hops &&
+		msg.fields.version == channel_end.version
+	{
+		return Err(Error::invalid_upgrade_fields(
+			"upgrade fields are identical to the current channel end".to_string(),
+		))
+	}
+
+	// The channel can only be moved to an OPEN connection.
+	let conn = ctx
+		.connection_end(&msg.fields.connection_hops[0])
+		.map_err(Error::ics03_connection)?;
+
+	if !conn.state_matches(&ConnectionState::Open) {
+		return Err(Error::connection_not_open(msg.fields.connection_hops[0].clone()))
+	}
+
+	channel_end.set_upgrade_sequence(channel_end.upgrade_sequence() + 1);
+
+	// The timeout and the next send sequence are only known once this end starts flushing, the
+	// counterparty verifies the proposed upgrade against its fields alone.
+	let upgrade = Upgrade::new(msg.fields.clone(), UpgradeTimeout::none(), 0.into());
+
+	output.log("success: channel upgrade init");
+
+	output.emit(IbcEvent::UpgradeInitChannel(UpgradeInit::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		msg.port_id.clone(),
+		msg.channel_id,
+		&channel_end,
+	))));
+
+	let result = ChannelResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_id_state: ChannelIdState::Reused,
+		channel_end,
+		upgrade: Some(ChannelUpgradeResult::Proposed { upgrade, counterparty_upgrade: None }),
+		client_update: None,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				msgs::test_util::get_dummy_raw_counterparty,
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				handler::{channel_dispatch, ChannelUpgradeResult},
+				msgs::{
+					chan_upgrade_init::{
+						test_util::get_dummy_raw_msg_chan_upgrade_init, MsgChannelUpgradeInit,
+					},
+					ChannelMsg,
+				},
+				Version,
+			},
+			ics24_host::identifier::{ClientId, ConnectionId},
+		},
+		events::IbcEvent,
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+		timestamp::ZERO_DURATION,
+	};
+
+	#[test]
+	fn chan_upgrade_init_msg_processing() {
+		struct Test {
+			name: String,
+			ctx: MockContext<MockClientTypes>,
+			msg: ChannelMsg,
+			want_pass: bool,
+		}
+
+		let msg = MsgChannelUpgradeInit::try_from(get_dummy_raw_msg_chan_upgrade_init()).unwrap();
+
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+
+		let chan_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+
+		// The dummy message only changes the ordering of the channel.
+		let mut unchanged_msg = msg.clone();
+		unchanged_msg.fields.ordering = Order::Ordered;
+
+		let context = MockContext::default()
+			.with_connection(ConnectionId::default(), conn_end)
+			.with_channel(msg.port_id.clone(), msg.channel_id, chan_end.clone());
+
+		let tests: Vec<Test> = vec![
+			Test {
+				name: "Processing fails because the channel does not exist".to_string(),
+				ctx: MockContext::default(),
+				msg: ChannelMsg::ChannelUpgradeInit(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the channel is not open".to_string(),
+				ctx: context.clone().with_channel(
+					msg.port_id.clone(),
+					msg.channel_id,
+					ChannelEnd { state: State::TryOpen, ..chan_end },
+				),
+				msg: ChannelMsg::ChannelUpgradeInit(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the upgrade does not change anything".to_string(),
+				ctx: context.clone(),
+				msg: ChannelMsg::ChannelUpgradeInit(unchanged_msg),
+				want_pass: false,
+			},
+			Test {
+				name: "Good parameters".to_string(),
+				ctx: context,
+				msg: ChannelMsg::ChannelUpgradeInit(msg),
+				want_pass: true,
+			},
+		]
+		.into_iter()
+		.collect();
+
+		for test in tests {
+			let res = channel_dispatch(&test.ctx, &test.msg);
+			match res {
+				Ok((proto_output, res)) => {
+					assert!(
+						test.want_pass,
+						"chan_upgrade_init: test passed but was supposed to fail for test: {}",
+						test.name,
+					);
+					assert_eq!(res.channel_end.upgrade_sequence(), 1);
+					assert!(matches!(res.upgrade, Some(ChannelUpgradeResult::Proposed { .. })));
+
+					let proto_output = proto_output.with_result(());
+					assert!(!proto_output.events.is_empty());
+					for e in proto_output.events.iter() {
+						assert!(matches!(e, &IbcEvent::UpgradeInitChannel(_)));
+					}
+				},
+				Err(e) => {
+					assert!(
+						!test.want_pass,
+						"chan_upgrade_init: did not pass test: {}, \nparams {:?} {:?} error: {:?}",
+						test.name,
+						test.msg,
+						test.ctx.clone(),
+						e,
+					);
+				},
+			}
+		}
+	}
+}
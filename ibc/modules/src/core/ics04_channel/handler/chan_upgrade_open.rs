@@ -0,0 +1,285 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeOpen`.
+
+use crate::{
+	core::{
+		ics04_channel::{
+			channel::{ChannelEnd, Counterparty, State},
+			error::Error,
+			events::{UpgradeAttributes, UpgradeOpen},
+			handler::{
+				expected_counterparty_channel, open_channel_connection,
+				verify::verify_channel_proofs, ChannelIdState, ChannelResult, ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_open::MsgChannelUpgradeOpen,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	events::IbcEvent,
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeOpen,
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	if !channel_end.state_matches(&State::FlushComplete) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	let upgrade = ctx
+		.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id))?;
+
+	let conn = open_channel_connection(ctx, &channel_end)?;
+
+	let expected_channel_end = match msg.counterparty_channel_state {
+		// The counterparty is done flushing too, but did not apply the upgrade yet.
+		State::FlushComplete => expected_counterparty_channel(
+			&msg.port_id,
+			msg.channel_id,
+			&channel_end,
+			&conn,
+			State::FlushComplete,
+			channel_end.upgrade_sequence(),
+		)?,
+		// The counterparty already applied the upgrade, so its channel end is built on the
+		// counterparty of the connection the channel is moved to.
+		State::Open => {
+			if msg.counterparty_upgrade_sequence < channel_end.upgrade_sequence() {
+				return Err(Error::invalid_upgrade_sequence(
+					channel_end.upgrade_sequence(),
+					msg.counterparty_upgrade_sequence,
+				))
+			}
+
+			let proposed_conn = ctx
+				.connection_end(&upgrade.fields.connection_hops[0])
+				.map_err(Error::ics03_connection)?;
+			let counterparty_connection_id =
+				proposed_conn.counterparty().connection_id().ok_or_else(|| {
+					Error::undefined_connection_counterparty(
+						upgrade.fields.connection_hops[0].clone(),
+					)
+				})?;
+
+			let mut expected = ChannelEnd::new(
+				State::Open,
+				upgrade.fields.ordering,
+				Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+				vec![counterparty_connection_id.clone()],
+				upgrade.fields.version.clone(),
+			);
+			expected.set_upgrade_sequence(msg.counterparty_upgrade_sequence);
+			expected
+		},
+		state => return Err(Error::invalid_channel_state(msg.channel_id, state)),
+	};
+
+	let mut client_update = None;
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&expected_channel_end,
+		msg.proofs.object_proof(),
+	)?;
+
+	channel_end.apply_upgrade_fields(&upgrade.fields);
+	channel_end.set_state(State::Open);
+
+	output.log("success: channel upgrade open");
+
+	output.emit(IbcEvent::UpgradeOpenChannel(UpgradeOpen::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		msg.port_id.clone(),
+		msg.channel_id,
+		&channel_end,
+	))));
+
+	let result = ChannelResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_id_state: ChannelIdState::Reused,
+		channel_end,
+		upgrade: Some(ChannelUpgradeResult::Cleared),
+		client_update,
+	};
+
+	Ok(output.with_result(result))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				msgs::test_util::get_dummy_raw_counterparty,
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				handler::{channel_dispatch, ChannelUpgradeResult},
+				msgs::{
+					chan_upgrade_open::{
+						test_util::get_dummy_raw_msg_chan_upgrade_open, MsgChannelUpgradeOpen,
+					},
+					ChannelMsg,
+				},
+				upgrade::{test_util::get_dummy_raw_upgrade, Upgrade},
+				Version,
+			},
+			ics24_host::identifier::{ClientId, ConnectionId},
+		},
+		events::IbcEvent,
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+		timestamp::ZERO_DURATION,
+		Height,
+	};
+
+	#[test]
+	fn chan_upgrade_open_msg_processing() {
+		struct Test {
+			name: String,
+			ctx: MockContext<MockClientTypes>,
+			msg: ChannelMsg,
+			want_pass: bool,
+		}
+
+		let proof_height = 10;
+		let msg =
+			MsgChannelUpgradeOpen::try_from(get_dummy_raw_msg_chan_upgrade_open(proof_height))
+				.unwrap();
+
+		// The counterparty already opened the upgraded channel.
+		let mut opened_msg = msg.clone();
+		opened_msg.counterparty_channel_state = State::Open;
+
+		let mut outdated_msg = opened_msg.clone();
+		outdated_msg.counterparty_upgrade_sequence = 0;
+
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+
+		let mut chan_end = ChannelEnd::new(
+			State::FlushComplete,
+			Order::Ordered,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+		chan_end.set_upgrade_sequence(1);
+
+		let upgrade = Upgrade::try_from(get_dummy_raw_upgrade(proof_height + 100)).unwrap();
+
+		let context = MockContext::default()
+			.with_client(&ClientId::default(), Height::new(0, proof_height))
+			.with_connection(ConnectionId::default(), conn_end)
+			.with_channel(msg.port_id.clone(), msg.channel_id, chan_end.clone());
+		let upgrade_context =
+			context
+				.clone()
+				.with_channel_upgrade(msg.port_id.clone(), msg.channel_id, upgrade);
+
+		let tests: Vec<Test> = vec![
+			Test {
+				name: "Processing fails because no upgrade is in progress".to_string(),
+				ctx: context,
+				msg: ChannelMsg::ChannelUpgradeOpen(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the channel is still flushing".to_string(),
+				ctx: upgrade_context.clone().with_channel(
+					msg.port_id.clone(),
+					msg.channel_id,
+					ChannelEnd { state: State::Flushing, ..chan_end },
+				),
+				msg: ChannelMsg::ChannelUpgradeOpen(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the counterparty opened an older upgrade"
+					.to_string(),
+				ctx: upgrade_context.clone(),
+				msg: ChannelMsg::ChannelUpgradeOpen(outdated_msg),
+				want_pass: false,
+			},
+			Test {
+				name: "Good parameters, the counterparty is flushed".to_string(),
+				ctx: upgrade_context.clone(),
+				msg: ChannelMsg::ChannelUpgradeOpen(msg),
+				want_pass: true,
+			},
+			Test {
+				name: "Good parameters, the counterparty is open".to_string(),
+				ctx: upgrade_context,
+				msg: ChannelMsg::ChannelUpgradeOpen(opened_msg),
+				want_pass: true,
+			},
+		]
+		.into_iter()
+		.collect();
+
+		for test in tests {
+			let res = channel_dispatch(&test.ctx, &test.msg);
+			match res {
+				Ok((proto_output, res)) => {
+					assert!(
+						test.want_pass,
+						"chan_upgrade_open: test passed but was supposed to fail for test: {}",
+						test.name,
+					);
+					assert_eq!(res.channel_end.state, State::Open);
+					assert_eq!(res.channel_end.ordering, Order::Unordered);
+					assert!(matches!(res.upgrade, Some(ChannelUpgradeResult::Cleared)));
+
+					let proto_output = proto_output.with_result(());
+					assert!(!proto_output.events.is_empty());
+					for e in proto_output.events.iter() {
+						assert!(matches!(e, &IbcEvent::UpgradeOpenChannel(_)));
+					}
+				},
+				Err(e) => {
+					assert!(
+						!test.want_pass,
+						"chan_upgrade_open: did not pass test: {}, \nparams {:?} {:?} error: {:?}",
+						test.name,
+						test.msg,
+						test.ctx.clone(),
+						e,
+					);
+				},
+			}
+		}
+	}
+}
@@ -0,0 +1,113 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeTimeout`.
+
+use crate::{
+	core::{
+		ics02_client::client_consensus::ConsensusState,
+		ics04_channel::{
+			channel::State,
+			error::Error,
+			events::{UpgradeAttributes, UpgradeTimeout},
+			handler::{
+				abort_upgrade, open_channel_connection, verify::verify_channel_proofs,
+				ChannelResult,
+			},
+			msgs::chan_upgrade_timeout::MsgChannelUpgradeTimeout,
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	events::IbcEvent,
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeTimeout,
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let channel_end = ctx.channel_end(&port_channel_id)?;
+
+	if !channel_end.is_flushing() {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	let upgrade = ctx
+		.channel_upgrade(&port_channel_id)
+		.map_err(|_| Error::upgrade_not_found(msg.port_id.clone(), msg.channel_id))?;
+
+	let conn = open_channel_connection(ctx, &channel_end)?;
+
+	// The proof must have been produced by the counterparty after the upgrade timed out.
+	let proof_height = msg.proofs.height();
+	let proof_timestamp = ctx
+		.consensus_state(conn.client_id(), proof_height)
+		.map_err(|_| Error::error_invalid_consensus_state())?
+		.timestamp();
+
+	if !upgrade.timeout.has_expired(proof_height, &proof_timestamp) {
+		return Err(Error::upgrade_timeout_not_reached())
+	}
+
+	// The counterparty must not have moved on with the upgrade.
+	let counterparty_channel = &msg.counterparty_channel;
+	match counterparty_channel.state {
+		State::Open =>
+			if counterparty_channel.upgrade_sequence() >= channel_end.upgrade_sequence() {
+				return Err(Error::invalid_upgrade_sequence(
+					channel_end.upgrade_sequence(),
+					counterparty_channel.upgrade_sequence(),
+				))
+			},
+		State::Flushing => {},
+		state => return Err(Error::invalid_channel_state(msg.channel_id, state)),
+	}
+
+	let mut client_update = None;
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		proof_height,
+		&channel_end,
+		&conn,
+		counterparty_channel,
+		msg.proofs.object_proof(),
+	)?;
+
+	output.log("success: channel upgrade timeout");
+
+	output.emit(IbcEvent::UpgradeTimeoutChannel(UpgradeTimeout::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		msg.port_id.clone(),
+		msg.channel_id,
+		&channel_end,
+	))));
+
+	let sequence = channel_end.upgrade_sequence();
+	let result = abort_upgrade(
+		ctx,
+		&mut output,
+		&msg.port_id,
+		msg.channel_id,
+		channel_end,
+		sequence,
+		Error::upgrade_timed_out(),
+		client_update,
+	);
+
+	Ok(output.with_result(result))
+}
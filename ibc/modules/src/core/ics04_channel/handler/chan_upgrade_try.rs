@@ -0,0 +1,407 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Protocol logic specific to ICS4 messages of type `MsgChannelUpgradeTry`.
+
+use crate::{
+	core::{
+		ics03_connection::connection::{ConnectionEnd, State as ConnectionState},
+		ics04_channel::{
+			channel::State,
+			error::Error,
+			events::{UpgradeAttributes, UpgradeTry},
+			handler::{
+				abort_upgrade, expected_counterparty_channel, flushing_upgrade_timeout,
+				open_channel_connection,
+				verify::{verify_channel_proofs, verify_channel_upgrade_proofs},
+				ChannelIdState, ChannelResult, ChannelUpgradeResult,
+			},
+			msgs::chan_upgrade_try::MsgChannelUpgradeTry,
+			upgrade::{Upgrade, UpgradeFields, UpgradeTimeout},
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	events::IbcEvent,
+	handler::{HandlerOutput, HandlerResult},
+	prelude::*,
+};
+
+pub(crate) fn process<Ctx: ReaderContext>(
+	ctx: &Ctx,
+	msg: &MsgChannelUpgradeTry,
+) -> HandlerResult<ChannelResult<Ctx>, Error> {
+	let mut output = HandlerOutput::builder();
+
+	let port_channel_id = (msg.port_id.clone(), msg.channel_id);
+	let mut channel_end = ctx.channel_end(&port_channel_id)?;
+
+	if !channel_end.state_matches(&State::Open) {
+		return Err(Error::invalid_channel_state(msg.channel_id, channel_end.state))
+	}
+
+	let conn = open_channel_connection(ctx, &channel_end)?;
+
+	// The channel can only be moved to an OPEN connection.
+	let proposed_conn = ctx
+		.connection_end(&msg.proposed_upgrade_connection_hops[0])
+		.map_err(Error::ics03_connection)?;
+
+	if !proposed_conn.state_matches(&ConnectionState::Open) {
+		return Err(Error::connection_not_open(msg.proposed_upgrade_connection_hops[0].clone()))
+	}
+
+	let mut upgrade = match ctx.channel_upgrade(&port_channel_id) {
+		// Both ends proposed an upgrade (crossing hellos), our proposal must move the channel to
+		// the same connection.
+		Ok(upgrade) => {
+			if upgrade.fields.connection_hops != msg.proposed_upgrade_connection_hops {
+				return Err(Error::invalid_upgrade_fields(
+					"proposed connection hops do not match the upgrade in progress".to_string(),
+				))
+			}
+			upgrade
+		},
+		// Otherwise the upgrade proposed by the counterparty is initialised on this end too.
+		Err(_) => {
+			channel_end.set_upgrade_sequence(channel_end.upgrade_sequence() + 1);
+			Upgrade::new(
+				UpgradeFields::new(
+					msg.counterparty_upgrade_fields.ordering,
+					msg.proposed_upgrade_connection_hops.clone(),
+					msg.counterparty_upgrade_fields.version.clone(),
+				),
+				UpgradeTimeout::none(),
+				0.into(),
+			)
+		},
+	};
+
+	// Verify that the counterparty channel is still OPEN and has proposed the upgrade.
+	let expected_channel_end = expected_counterparty_channel(
+		&msg.port_id,
+		msg.channel_id,
+		&channel_end,
+		&conn,
+		State::Open,
+		msg.counterparty_upgrade_sequence,
+	)?;
+
+	let mut client_update = None;
+	verify_channel_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&expected_channel_end,
+		msg.proofs.object_proof(),
+	)?;
+
+	let counterparty_upgrade =
+		Upgrade::new(msg.counterparty_upgrade_fields.clone(), UpgradeTimeout::none(), 0.into());
+	let upgrade_proof = msg.proofs.other_proof().as_ref().ok_or_else(Error::missing_upgrade)?;
+
+	verify_channel_upgrade_proofs::<Ctx>(
+		ctx,
+		&mut client_update,
+		msg.proofs.height(),
+		&channel_end,
+		&conn,
+		&counterparty_upgrade,
+		upgrade_proof,
+	)?;
+
+	// The counterparty is in an older upgrade, force it to abort and catch up with ours.
+	if msg.counterparty_upgrade_sequence < channel_end.upgrade_sequence() {
+		let sequence = channel_end.upgrade_sequence() - 1;
+		let error = Error::invalid_upgrade_sequence(
+			channel_end.upgrade_sequence(),
+			msg.counterparty_upgrade_sequence,
+		);
+		let result = abort_upgrade(
+			ctx,
+			&mut output,
+			&msg.port_id,
+			msg.channel_id,
+			channel_end,
+			sequence,
+			error,
+			client_update,
+		);
+		return Ok(output.with_result(result))
+	}
+	channel_end.set_upgrade_sequence(msg.counterparty_upgrade_sequence);
+
+	if let Err(error) = check_upgrade_compatibility(
+		&upgrade.fields,
+		&msg.counterparty_upgrade_fields,
+		&proposed_conn,
+	) {
+		let sequence = channel_end.upgrade_sequence();
+		let result = abort_upgrade(
+			ctx,
+			&mut output,
+			&msg.port_id,
+			msg.channel_id,
+			channel_end,
+			sequence,
+			error,
+			client_update,
+		);
+		return Ok(output.with_result(result))
+	}
+
+	// Start flushing the packets in flight, the counterparty has until the timeout to follow.
+	upgrade.timeout = flushing_upgrade_timeout(ctx)?;
+	upgrade.next_sequence_send = ctx.get_next_sequence_send(&port_channel_id)?;
+	channel_end.set_state(State::Flushing);
+
+	output.log("success: channel upgrade try");
+
+	output.emit(IbcEvent::UpgradeTryChannel(UpgradeTry::from(UpgradeAttributes::new(
+		ctx.host_height(),
+		msg.port_id.clone(),
+		msg.channel_id,
+		&channel_end,
+	))));
+
+	let result = ChannelResult {
+		port_id: msg.port_id.clone(),
+		channel_id: msg.channel_id,
+		channel_id_state: ChannelIdState::Reused,
+		channel_end,
+		upgrade: Some(ChannelUpgradeResult::Proposed { upgrade, counterparty_upgrade: None }),
+		client_update,
+	};
+
+	Ok(output.with_result(result))
+}
+
+/// Checks that the upgrades proposed by both ends agree on the ordering of the channel and on the
+/// connection it is moved to.
+pub(crate) fn check_upgrade_compatibility(
+	fields: &UpgradeFields,
+	counterparty_fields: &UpgradeFields,
+	proposed_conn: &ConnectionEnd,
+) -> Result<(), Error> {
+	if fields.ordering != counterparty_fields.ordering {
+		return Err(Error::incompatible_counterparty_upgrade(format!(
+			"expected ordering {}, got {}",
+			fields.ordering, counterparty_fields.ordering
+		)))
+	}
+
+	let counterparty_hop = proposed_conn.counterparty().connection_id();
+	if counterparty_hop != counterparty_fields.connection_hops.first() {
+		return Err(Error::incompatible_counterparty_upgrade(format!(
+			"expected connection hops {:?}, got {:?}",
+			counterparty_hop, counterparty_fields.connection_hops
+		)))
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		core::{
+			ics03_connection::{
+				connection::{
+					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+				},
+				msgs::test_util::get_dummy_raw_counterparty,
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{ChannelEnd, Counterparty, Order, State},
+				handler::{channel_dispatch, ChannelUpgradeResult},
+				msgs::{
+					chan_upgrade_try::{
+						test_util::get_dummy_raw_msg_chan_upgrade_try, MsgChannelUpgradeTry,
+					},
+					ChannelMsg,
+				},
+				Version,
+			},
+			ics24_host::identifier::{ClientId, ConnectionId},
+		},
+		events::IbcEvent,
+		mock::context::{MockClientTypes, MockContext},
+		prelude::*,
+		timestamp::ZERO_DURATION,
+		Height,
+	};
+
+	#[test]
+	fn chan_upgrade_try_msg_processing() {
+		struct Test {
+			name: String,
+			ctx: MockContext<MockClientTypes>,
+			msg: ChannelMsg,
+			want_pass: bool,
+		}
+
+		let proof_height = 10;
+		let msg = MsgChannelUpgradeTry::try_from(get_dummy_raw_msg_chan_upgrade_try(proof_height))
+			.unwrap();
+
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+
+		let chan_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+
+		let context = MockContext::default()
+			.with_client(&ClientId::default(), Height::new(0, proof_height))
+			.with_connection(ConnectionId::default(), conn_end.clone())
+			.with_channel(msg.port_id.clone(), msg.channel_id, chan_end.clone())
+			.with_send_sequence(msg.port_id.clone(), msg.channel_id, 1.into());
+
+		let tests: Vec<Test> = vec![
+			Test {
+				name: "Processing fails because the channel does not exist".to_string(),
+				ctx: MockContext::default()
+					.with_client(&ClientId::default(), Height::new(0, proof_height))
+					.with_connection(ConnectionId::default(), conn_end.clone()),
+				msg: ChannelMsg::ChannelUpgradeTry(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the channel is already flushing".to_string(),
+				ctx: context.clone().with_channel(
+					msg.port_id.clone(),
+					msg.channel_id,
+					ChannelEnd { state: State::Flushing, ..chan_end.clone() },
+				),
+				msg: ChannelMsg::ChannelUpgradeTry(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Processing fails because the proposed connection is not open".to_string(),
+				ctx: context.clone().with_connection(
+					ConnectionId::default(),
+					ConnectionEnd::new(
+						ConnectionState::Init,
+						ClientId::default(),
+						ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+						get_compatible_versions(),
+						ZERO_DURATION,
+					),
+				),
+				msg: ChannelMsg::ChannelUpgradeTry(msg.clone()),
+				want_pass: false,
+			},
+			Test {
+				name: "Good parameters".to_string(),
+				ctx: context,
+				msg: ChannelMsg::ChannelUpgradeTry(msg),
+				want_pass: true,
+			},
+		]
+		.into_iter()
+		.collect();
+
+		for test in tests {
+			let res = channel_dispatch(&test.ctx, &test.msg);
+			match res {
+				Ok((proto_output, res)) => {
+					assert!(
+						test.want_pass,
+						"chan_upgrade_try: test passed but was supposed to fail for test: {}",
+						test.name,
+					);
+					assert_eq!(res.channel_end.state, State::Flushing);
+					assert_eq!(res.channel_end.upgrade_sequence(), 1);
+					match res.upgrade {
+						Some(ChannelUpgradeResult::Proposed { upgrade, .. }) =>
+							assert!(upgrade.timeout.is_set()),
+						_ => panic!("chan_upgrade_try: expected the upgrade to be proposed"),
+					}
+
+					let proto_output = proto_output.with_result(());
+					assert!(!proto_output.events.is_empty());
+					for e in proto_output.events.iter() {
+						assert!(matches!(e, &IbcEvent::UpgradeTryChannel(_)));
+					}
+				},
+				Err(e) => {
+					assert!(
+						!test.want_pass,
+						"chan_upgrade_try: did not pass test: {}, \nparams {:?} {:?} error: {:?}",
+						test.name,
+						test.msg,
+						test.ctx.clone(),
+						e,
+					);
+				},
+			}
+		}
+	}
+
+	#[test]
+	fn chan_upgrade_try_aborts_outdated_counterparty_upgrade() {
+		let proof_height = 10;
+		let msg = MsgChannelUpgradeTry::try_from(get_dummy_raw_msg_chan_upgrade_try(proof_height))
+			.unwrap();
+
+		let conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::try_from(get_dummy_raw_counterparty()).unwrap(),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+
+		// This end already went through upgrades the counterparty doesn't know about.
+		let mut chan_end = ChannelEnd::new(
+			State::Open,
+			Order::Ordered,
+			Counterparty::new(msg.port_id.clone(), Some(msg.channel_id)),
+			vec![ConnectionId::default()],
+			Version::ics20(),
+		);
+		chan_end.set_upgrade_sequence(3);
+
+		let context = MockContext::default()
+			.with_client(&ClientId::default(), Height::new(0, proof_height))
+			.with_connection(ConnectionId::default(), conn_end)
+			.with_channel(msg.port_id.clone(), msg.channel_id, chan_end)
+			.with_send_sequence(msg.port_id.clone(), msg.channel_id, 1.into());
+
+		let (proto_output, res) =
+			channel_dispatch(&context, &ChannelMsg::ChannelUpgradeTry(msg)).unwrap();
+
+		assert_eq!(res.channel_end.state, State::Open);
+		match res.upgrade {
+			Some(ChannelUpgradeResult::Aborted(receipt)) => assert_eq!(receipt.sequence, 3),
+			_ => panic!("chan_upgrade_try: expected the upgrade to be aborted"),
+		}
+
+		let proto_output = proto_output.with_result(());
+		assert!(proto_output
+			.events
+			.iter()
+			.all(|e| matches!(e, &IbcEvent::UpgradeErrorChannel(_))));
+	}
+}
@@ -14,6 +14,7 @@
 
 use crate::{
 	core::{
+		ics02_client::{context::ClientTypes, handler::update_client},
 		ics03_connection::connection::State as ConnectionState,
 		ics04_channel::{
 			channel::{Counterparty, Order, State},
@@ -35,7 +36,7 @@ use crate::{
 use core::fmt::Debug;
 
 #[derive(Clone, Debug)]
-pub enum RecvPacketResult {
+pub enum RecvPacketResult<C: ClientTypes> {
 	NoOp,
 	Unordered {
 		port_id: PortId,
@@ -43,19 +44,21 @@ pub enum RecvPacketResult {
 		sequence: Sequence,
 		receipt: Receipt,
 		packet: Packet,
+		client_update: Option<update_client::Result<C>>,
 	},
 	Ordered {
 		port_id: PortId,
 		channel_id: ChannelId,
 		next_seq_recv: Sequence,
 		packet: Packet,
+		client_update: Option<update_client::Result<C>>,
 	},
 }
 
 pub fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgRecvPacket,
-) -> HandlerResult<PacketResult, Error> {
+) -> HandlerResult<PacketResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let packet = &msg.packet;
@@ -63,10 +66,26 @@ pub fn process<Ctx: ReaderContext>(
 	let dest_channel_end =
 		ctx.channel_end(&(packet.destination_port.clone(), packet.destination_channel))?;
 
-	if !dest_channel_end.state_matches(&State::Open) {
+	// Packets keep being received while the channel is being upgraded.
+	if !dest_channel_end.state_matches(&State::Open) && !dest_channel_end.is_flushing() {
 		return Err(Error::invalid_channel_state(packet.source_channel, dest_channel_end.state))
 	}
 
+	// Only the packets sent before the counterparty started flushing are delivered during the
+	// upgrade.
+	if dest_channel_end.is_flushing() {
+		if let Ok(counterparty_upgrade) =
+			ctx.counterparty_upgrade(&(packet.destination_port.clone(), packet.destination_channel))
+		{
+			if packet.sequence >= counterparty_upgrade.next_sequence_send {
+				return Err(Error::packet_sent_after_upgrade(
+					packet.sequence,
+					counterparty_upgrade.next_sequence_send,
+				))
+			}
+		}
+	}
+
 	let counterparty = Counterparty::new(packet.source_port.clone(), Some(packet.source_channel));
 
 	if !dest_channel_end.counterparty_matches(&counterparty) {
@@ -94,9 +113,12 @@ pub fn process<Ctx: ReaderContext>(
 		return Err(Error::low_packet_timestamp())
 	}
 
+	let mut client_update = None;
 	verify_packet_recv_proofs::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
+		&dest_channel_end,
 		packet,
 		&connection_end,
 		&msg.proofs,
@@ -123,6 +145,7 @@ pub fn process<Ctx: ReaderContext>(
 			channel_id: packet.destination_channel,
 			next_seq_recv: next_seq_recv.increment(),
 			packet: packet.clone(),
+			client_update,
 		})
 	} else {
 		let packet_rec = ctx.get_packet_receipt(&(
@@ -147,6 +170,7 @@ pub fn process<Ctx: ReaderContext>(
 					sequence: packet.sequence,
 					receipt: Receipt::Ok,
 					packet: packet.clone(),
+					client_update,
 				})
 			},
 			Err(e) => return Err(Error::implementation_specific(e.to_string())),
@@ -44,7 +44,7 @@ pub struct SendPacketResult {
 pub fn send_packet<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	packet: Packet,
-) -> HandlerResult<PacketResult, Error> {
+) -> HandlerResult<PacketResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let source_channel_end =
@@ -54,6 +54,11 @@ pub fn send_packet<Ctx: ReaderContext>(
 		return Err(Error::channel_closed(packet.source_channel))
 	}
 
+	// No new packets can be sent while the in-flight ones are flushed for an upgrade.
+	if source_channel_end.is_flushing() {
+		return Err(Error::invalid_channel_state(packet.source_channel, source_channel_end.state))
+	}
+
 	let counterparty =
 		Counterparty::new(packet.destination_port.clone(), Some(packet.destination_channel));
 
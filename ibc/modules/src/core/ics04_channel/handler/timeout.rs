@@ -14,12 +14,20 @@
 
 use crate::{
 	core::{
-		ics02_client::client_consensus::ConsensusState,
+		ics02_client::{
+			client_consensus::ConsensusState, context::ClientTypes, handler::update_client,
+		},
 		ics04_channel::{
 			channel::{ChannelEnd, Counterparty, Order, State},
 			error::Error,
 			events::TimeoutPacket,
-			handler::verify::{verify_next_sequence_recv, verify_packet_receipt_absence},
+			handler::{
+				acknowledgement::flush_channel,
+				verify::{
+					multihop_counterparty_consensus_state, verify_next_sequence_recv,
+					verify_packet_receipt_absence,
+				},
+			},
 			msgs::timeout::MsgTimeout,
 			packet::{PacketResult, Sequence},
 		},
@@ -33,14 +41,15 @@ use crate::{
 use core::fmt::Debug;
 
 #[derive(Clone, Debug)]
-pub struct TimeoutPacketResult {
+pub struct TimeoutPacketResult<C: ClientTypes> {
 	pub port_id: PortId,
 	pub channel_id: ChannelId,
 	pub seq: Sequence,
 	pub channel: Option<ChannelEnd>,
+	pub client_update: Option<update_client::Result<C>>,
 }
 
-pub fn process<Ctx>(ctx: &Ctx, msg: &MsgTimeout) -> HandlerResult<PacketResult, Error>
+pub fn process<Ctx>(ctx: &Ctx, msg: &MsgTimeout) -> HandlerResult<PacketResult<Ctx>, Error>
 where
 	Ctx: ReaderContext,
 {
@@ -51,7 +60,7 @@ where
 	let mut source_channel_end =
 		ctx.channel_end(&(packet.source_port.clone(), packet.source_channel))?;
 
-	if !source_channel_end.state_matches(&State::Open) {
+	if !matches!(source_channel_end.state, State::Open | State::Flushing) {
 		return Err(Error::channel_closed(packet.source_channel))
 	}
 
@@ -71,12 +80,18 @@ where
 
 	let client_id = connection_end.client_id().clone();
 
-	// check that timeout height or timeout timestamp has passed on the other end
-	let proof_height = msg.proofs.height();
-
-	let consensus_state = ctx
-		.consensus_state(&client_id, proof_height)
-		.map_err(|_| Error::error_invalid_consensus_state())?;
+	// check that timeout height or timeout timestamp has passed on the other end. The proof
+	// height of a multi-hop channel is a height of the first hop, the height and timestamp of the
+	// other end are those of the consensus state proven for the last hop.
+	let (proof_height, consensus_state) = if source_channel_end.connection_hops().len() > 1 {
+		multihop_counterparty_consensus_state(ctx, msg.proofs.object_proof())?
+	} else {
+		let proof_height = msg.proofs.height();
+		let consensus_state = ctx
+			.consensus_state(&client_id, proof_height)
+			.map_err(|_| Error::error_invalid_consensus_state())?;
+		(proof_height, consensus_state)
+	};
 
 	let proof_timestamp = consensus_state.timestamp();
 
@@ -102,13 +117,16 @@ where
 		return Err(Error::incorrect_packet_commitment(packet.sequence))
 	}
 
+	let mut client_update = None;
 	let result = if source_channel_end.order_matches(&Order::Ordered) {
 		if packet.sequence < msg.next_sequence_recv {
 			return Err(Error::invalid_packet_sequence(packet.sequence, msg.next_sequence_recv))
 		}
 		verify_next_sequence_recv::<Ctx>(
 			ctx,
+			&mut client_update,
 			msg.proofs.height(),
+			&source_channel_end,
 			&connection_end,
 			packet.clone(),
 			msg.next_sequence_recv,
@@ -121,21 +139,27 @@ where
 			channel_id: packet.source_channel,
 			seq: packet.sequence,
 			channel: Some(source_channel_end),
+			client_update,
 		})
 	} else {
 		verify_packet_receipt_absence::<Ctx>(
 			ctx,
+			&mut client_update,
 			msg.proofs.height(),
+			&source_channel_end,
 			&connection_end,
 			packet.clone(),
 			&msg.proofs,
 		)?;
 
+		let channel = flush_channel(ctx, &mut output, packet, source_channel_end)?;
+
 		PacketResult::Timeout(TimeoutPacketResult {
 			port_id: packet.source_port.clone(),
 			channel_id: packet.source_channel,
 			seq: packet.sequence,
-			channel: None,
+			channel,
+			client_update,
 		})
 	};
 
@@ -155,7 +179,12 @@ mod tests {
 
 	use crate::{
 		core::{
-			ics02_client::{context::ClientReader, height::Height},
+			ics02_client::{
+				client_consensus::ConsensusState,
+				client_state::ClientState,
+				context::{ClientKeeper, ClientReader},
+				height::Height,
+			},
 			ics03_connection::{
 				connection::{
 					ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
@@ -165,17 +194,33 @@ mod tests {
 			ics04_channel::{
 				channel::{ChannelEnd, Counterparty, Order, State},
 				context::ChannelReader,
+				error::ErrorDetail,
 				handler::timeout::process,
 				msgs::timeout::{test_util::get_dummy_raw_msg_timeout, MsgTimeout},
+				multihop::{MultihopProof, MultihopProofs},
 				Version,
 			},
-			ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			ics24_host::{
+				identifier::{ChannelId, ClientId, ConnectionId, PortId},
+				path::{
+					ClientConsensusStatePath, ClientStatePath, ConnectionsPath, Path, ReceiptsPath,
+				},
+			},
 		},
 		events::IbcEvent,
-		mock::context::{MockClientTypes, MockContext},
+		mock::{
+			client_state::{
+				AnyClientState, AnyConsensusState, MockClientState, MockConsensusState,
+			},
+			context::{MockClientTypes, MockContext},
+			header::MockHeader,
+		},
 		prelude::*,
+		proofs::Proofs,
+		test_utils::get_dummy_proof,
 		timestamp::ZERO_DURATION,
 	};
+	use tendermint_proto::Protobuf;
 
 	#[test]
 	fn timeout_packet_processing() {
@@ -341,4 +386,136 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn timeout_packet_on_multihop_channel() {
+		// The channel reaches its counterparty through the chain at the other end of the default
+		// connection, whose client of the counterparty is `hop_client_id`.
+		let hop_conn_id = ConnectionId::new(7);
+		let hop_client_id = ClientId::new(&MockClientState::client_type(), 7).unwrap();
+		let first_hop_height = Height::new(0, 12);
+
+		let msg = MsgTimeout::try_from(get_dummy_raw_msg_timeout(10, 0)).unwrap();
+		let packet = msg.packet.clone();
+
+		let connection_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			ClientId::default(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::default()),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let hop_conn_end = ConnectionEnd::new(
+			ConnectionState::Open,
+			hop_client_id.clone(),
+			ConnectionCounterparty::new(
+				ClientId::default(),
+				Some(ConnectionId::new(3)),
+				Default::default(),
+			),
+			get_compatible_versions(),
+			ZERO_DURATION,
+		);
+		let channel_end = ChannelEnd::new(
+			State::Open,
+			Order::Unordered,
+			Counterparty::new(packet.destination_port.clone(), Some(packet.destination_channel)),
+			vec![ConnectionId::default(), hop_conn_id.clone()],
+			Version::ics20(),
+		);
+
+		let mut context = MockContext::<MockClientTypes>::default()
+			.with_client(&ClientId::default(), first_hop_height)
+			.with_connection(ConnectionId::default(), connection_end)
+			.with_channel(packet.source_port.clone(), packet.source_channel, channel_end);
+		let commitment = context.packet_commitment(
+			packet.data.clone(),
+			packet.timeout_height,
+			packet.timeout_timestamp,
+		);
+		let host_timestamp = context.host_timestamp();
+		let host_height = context.host_height();
+		context
+			.store_update_time(ClientId::default(), first_hop_height, host_timestamp)
+			.unwrap();
+		context
+			.store_update_height(ClientId::default(), first_hop_height, host_height)
+			.unwrap();
+		let context = context.with_packet_commitment(
+			packet.source_port.clone(),
+			packet.source_channel,
+			packet.sequence,
+			commitment,
+		);
+
+		// The first hop is past the timeout height of the packet, the proofs carry the state of
+		// the destination at `destination_height`.
+		let msg_with_destination_at = |destination_height: Height| {
+			let hop_proof = |value: Vec<u8>, path: Path| MultihopProof {
+				proof: get_dummy_proof().try_into().unwrap(),
+				value,
+				prefixed_key: vec!["ibc".to_string(), path.to_string()],
+			};
+			let header = MockHeader::new(destination_height);
+			let proofs = MultihopProofs {
+				key_proof: hop_proof(
+					vec![],
+					ReceiptsPath {
+						port_id: packet.destination_port.clone(),
+						channel_id: packet.destination_channel,
+						sequence: packet.sequence,
+					}
+					.into(),
+				),
+				connection_proofs: vec![hop_proof(
+					hop_conn_end.encode_vec().unwrap(),
+					ConnectionsPath(hop_conn_id.clone()).into(),
+				)],
+				consensus_proofs: vec![hop_proof(
+					AnyConsensusState::Mock(MockConsensusState::new(header))
+						.encode_to_vec()
+						.unwrap(),
+					ClientConsensusStatePath {
+						client_id: hop_client_id.clone(),
+						epoch: destination_height.revision_number,
+						height: destination_height.revision_height,
+					}
+					.into(),
+				)],
+				client_state_proofs: vec![hop_proof(
+					AnyClientState::Mock(MockClientState::new(header.into()))
+						.encode_to_vec()
+						.unwrap(),
+					ClientStatePath(hop_client_id.clone()).into(),
+				)],
+			};
+			let mut msg = msg.clone();
+			msg.proofs = Proofs::new(
+				proofs.encode_vec().unwrap().try_into().unwrap(),
+				None,
+				None,
+				None,
+				first_hop_height,
+			)
+			.unwrap();
+			msg
+		};
+
+		let res = process(&context, &msg_with_destination_at(Height::new(0, 5)));
+		assert!(
+			matches!(
+				res.as_ref().map_err(|e| e.detail()),
+				Err(ErrorDetail::PacketTimeoutNotReached(_))
+			),
+			"expected the timeout to be checked against the destination height, got {:?}",
+			res.map(|_| ())
+		);
+
+		let res = process(&context, &msg_with_destination_at(Height::new(0, 10)));
+		assert!(res.is_ok(), "timeout at the destination height failed: {:?}", res.err());
+	}
 }
@@ -21,7 +21,8 @@ use crate::{
 			handler::{
 				timeout::TimeoutPacketResult,
 				verify::{
-					verify_channel_proofs, verify_next_sequence_recv, verify_packet_receipt_absence,
+					counterparty_connection_hops, verify_channel_proofs, verify_next_sequence_recv,
+					verify_packet_receipt_absence,
 				},
 			},
 			msgs::timeout_on_close::MsgTimeoutOnClose,
@@ -37,7 +38,7 @@ use crate::{
 pub fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	msg: &MsgTimeoutOnClose,
-) -> HandlerResult<PacketResult, Error> {
+) -> HandlerResult<PacketResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let packet = &msg.packet;
@@ -75,12 +76,17 @@ pub fn process<Ctx: ReaderContext>(
 	let expected_counterparty =
 		Counterparty::new(packet.source_port.clone(), Some(packet.source_channel));
 
-	let counterparty = connection_end.counterparty();
-	let ccid = counterparty.connection_id().ok_or_else(|| {
-		Error::undefined_connection_counterparty(source_channel_end.connection_hops()[0].clone())
-	})?;
+	let channel_proof = msg
+		.proofs
+		.other_proof()
+		.as_ref()
+		.ok_or_else(|| Error::missing_channel_proof())?;
 
-	let expected_connection_hops = vec![ccid.clone()];
+	let expected_connection_hops = counterparty_connection_hops(
+		source_channel_end.connection_hops(),
+		&connection_end,
+		channel_proof,
+	)?;
 
 	let expected_channel_end = ChannelEnd::new(
 		State::Closed,
@@ -90,16 +96,15 @@ pub fn process<Ctx: ReaderContext>(
 		source_channel_end.version().clone(),
 	);
 
+	let mut client_update = None;
 	verify_channel_proofs::<Ctx>(
 		ctx,
+		&mut client_update,
 		msg.proofs.height(),
 		&source_channel_end,
 		&connection_end,
 		&expected_channel_end,
-		msg.proofs
-			.other_proof()
-			.as_ref()
-			.ok_or_else(|| Error::missing_channel_proof())?,
+		channel_proof,
 	)?;
 
 	let result = if source_channel_end.order_matches(&Order::Ordered) {
@@ -108,7 +113,9 @@ pub fn process<Ctx: ReaderContext>(
 		}
 		verify_next_sequence_recv::<Ctx>(
 			ctx,
+			&mut client_update,
 			msg.proofs.height(),
+			&source_channel_end,
 			&connection_end,
 			packet.clone(),
 			msg.next_sequence_recv,
@@ -120,11 +127,14 @@ pub fn process<Ctx: ReaderContext>(
 			channel_id: packet.source_channel,
 			seq: packet.sequence,
 			channel: Some(source_channel_end),
+			client_update,
 		})
 	} else {
 		verify_packet_receipt_absence::<Ctx>(
 			ctx,
+			&mut client_update,
 			msg.proofs.height(),
+			&source_channel_end,
 			&connection_end,
 			packet.clone(),
 			&msg.proofs,
@@ -135,6 +145,7 @@ pub fn process<Ctx: ReaderContext>(
 			channel_id: packet.source_channel,
 			seq: packet.sequence,
 			channel: None,
+			client_update,
 		})
 	};
 
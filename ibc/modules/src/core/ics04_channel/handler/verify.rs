@@ -15,26 +15,359 @@
 use crate::{
 	core::{
 		ics02_client::{
-			client_consensus::ConsensusState, client_def::ClientDef, client_state::ClientState,
+			client_consensus::ConsensusState,
+			client_def::ClientDef,
+			client_state::ClientState,
+			error::Error as Ics02Error,
+			handler::update_client::{self, client_state_for_proof, update_on_proof},
 		},
-		ics03_connection::connection::ConnectionEnd,
+		ics03_connection::connection::{ConnectionEnd, State as ConnectionState},
 		ics04_channel::{
 			channel::ChannelEnd,
 			error::Error,
 			msgs::acknowledgement::Acknowledgement,
+			multihop::MultihopProofs,
 			packet::{Packet, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
+		},
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+		ics24_host::{
+			identifier::{ClientId, ConnectionId},
+			path::{
+				AcksPath, ChannelEndsPath, ChannelUpgradeErrorsPath, ChannelUpgradesPath,
+				CommitmentsPath, ReceiptsPath, SeqRecvsPath,
+			},
+			Path,
 		},
-		ics23_commitment::commitment::CommitmentProofBytes,
 		ics26_routing::context::ReaderContext,
 	},
 	prelude::*,
 	proofs::Proofs,
 	Height,
 };
+use core::time::Duration;
+use prost::Message;
+use tendermint_proto::Protobuf;
+
+/// The counterparty of a multi-hop channel, as proven through the chains on the channel path.
+struct MultihopCounterparty<Ctx: ReaderContext> {
+	/// Client state the last intermediate chain stores for the counterparty.
+	client_state: Ctx::AnyClientState,
+	/// Height of the counterparty consensus state the proof of the object is checked against.
+	height: Height,
+	/// Commitment root of that consensus state.
+	root: CommitmentRoot,
+	/// Store prefix of the counterparty.
+	prefix: CommitmentPrefix,
+	/// Proof of the object in the counterparty state.
+	proof: CommitmentProofBytes,
+	/// The longest delay period of the connections on the channel path.
+	delay_period: Duration,
+}
+
+impl<Ctx: ReaderContext> MultihopCounterparty<Ctx> {
+	fn verify_membership(
+		&self,
+		ctx: &Ctx,
+		path: impl Into<Path>,
+		value: Vec<u8>,
+	) -> Result<(), Ics02Error> {
+		self.client_state.client_def().verify_membership(
+			ctx,
+			&self.client_state,
+			self.height,
+			&self.prefix,
+			&self.proof,
+			&self.root,
+			path.into(),
+			value,
+		)
+	}
+
+	fn verify_non_membership(&self, ctx: &Ctx, path: impl Into<Path>) -> Result<(), Ics02Error> {
+		self.client_state.client_def().verify_non_membership(
+			ctx,
+			&self.client_state,
+			self.height,
+			&self.prefix,
+			&self.proof,
+			&self.root,
+			path.into(),
+		)
+	}
+
+	/// Packet proofs are only accepted once the delay period of every connection on the path
+	/// passed since the client of the first hop was updated to `height`.
+	fn verify_delay_passed(
+		&self,
+		ctx: &Ctx,
+		client_id: &ClientId,
+		height: Height,
+	) -> Result<(), Error> {
+		let processed_time = ctx
+			.client_update_time(client_id, height)
+			.map_err(|_| Error::processed_time_not_found(client_id.clone(), height))?;
+		let processed_height = ctx
+			.client_update_height(client_id, height)
+			.map_err(|_| Error::processed_height_not_found(client_id.clone(), height))?;
+
+		let current_time = ctx.host_timestamp();
+		let earliest_time =
+			(processed_time + self.delay_period).map_err(Error::timestamp_overflow)?;
+		if !(current_time == earliest_time || current_time.after(&earliest_time)) {
+			return Err(Error::not_enough_time_elapsed(current_time, earliest_time))
+		}
+
+		let current_height = ctx.host_height();
+		let earliest_height = processed_height.add(ctx.block_delay(self.delay_period));
+		if current_height < earliest_height {
+			return Err(Error::not_enough_blocks_elapsed(current_height, earliest_height))
+		}
+
+		Ok(())
+	}
+}
+
+/// Verifies the proofs of the chains on the path of a multi-hop channel, from the consensus
+/// state of the first hop at `height` to the consensus state the last intermediate chain stores
+/// for the counterparty. Every intermediate chain proves its connection end to the next chain
+/// and the client and consensus states it stores for it; the proofs of the next chain are then
+/// verified with that client, so each hop may use its own client type. Only the proofs of the
+/// first hop are verified by a client of the host, which consumes them.
+fn verify_multihop_counterparty<Ctx>(
+	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
+	client_state: &Ctx::AnyClientState,
+	height: Height,
+	connection_hops: &[ConnectionId],
+	connection_end: &ConnectionEnd,
+	proof: &CommitmentProofBytes,
+) -> Result<MultihopCounterparty<Ctx>, Error>
+where
+	Ctx: ReaderContext,
+{
+	let proofs = MultihopProofs::from_proof(proof)?;
+	let intermediate_hops = connection_hops.len() - 1;
+	if proofs.connection_proofs.len() != intermediate_hops ||
+		proofs.client_state_proofs.len() != intermediate_hops ||
+		proofs.consensus_proofs.len() != intermediate_hops
+	{
+		return Err(Error::invalid_multihop_proof(format!(
+			"expected {} connection, client state and consensus proofs, got {}, {} and {}",
+			intermediate_hops,
+			proofs.connection_proofs.len(),
+			proofs.client_state_proofs.len(),
+			proofs.consensus_proofs.len()
+		)))
+	}
+
+	let mut hop_client_id = connection_end.client_id().clone();
+	let mut hop_client_state = client_state.clone();
+	let mut hop_height = height;
+	let mut hop_consensus_state = ctx
+		.consensus_state(&hop_client_id, height)
+		.map_err(|_| Error::error_invalid_consensus_state())?;
+	let mut hop_connection_end = connection_end.clone();
+	let mut delay_period = connection_end.delay_period();
+	// The proofs are ordered from the counterparty, walk them from the first hop.
+	for (hop, (((connection_id, connection_proof), client_state_proof), consensus_proof)) in
+		connection_hops[1..]
+			.iter()
+			.zip(proofs.connection_proofs.iter().rev())
+			.zip(proofs.client_state_proofs.iter().rev())
+			.zip(proofs.consensus_proofs.iter().rev())
+			.enumerate()
+	{
+		let first_hop = hop == 0;
+		let prefix = hop_connection_end.counterparty().prefix().clone();
+		let client_def = hop_client_state.client_def();
+		let verify_failed = |e| Error::verify_multihop_failed(connection_id.clone(), e);
+
+		let next_connection_end = connection_proof.connection_end()?;
+		if !next_connection_end.state_matches(&ConnectionState::Open) {
+			return Err(Error::connection_not_open(connection_id.clone()))
+		}
+		client_def
+			.verify_connection_state(
+				ctx,
+				&hop_client_id,
+				&hop_client_state,
+				hop_height,
+				&prefix,
+				&connection_proof.proof,
+				hop_consensus_state.root(),
+				connection_id,
+				&next_connection_end,
+			)
+			.map_err(verify_failed)?;
+		if first_hop {
+			consume_proof(
+				ctx,
+				client_update,
+				&hop_client_id,
+				&mut hop_client_state,
+				&connection_proof.proof,
+			)?;
+		}
+
+		let next_client_id = next_connection_end.client_id();
+		let next_client_state = ctx
+			.decode_client_state(&client_state_proof.value)
+			.map_err(Error::ics02_client)?;
+		if next_client_state.is_frozen() {
+			return Err(Error::frozen_client(next_client_id.clone()))
+		}
+		client_def
+			.verify_client_full_state(
+				ctx,
+				&hop_client_state,
+				hop_height,
+				&prefix,
+				&client_state_proof.proof,
+				hop_consensus_state.root(),
+				next_client_id,
+				&next_client_state,
+			)
+			.map_err(verify_failed)?;
+		if first_hop {
+			consume_proof(
+				ctx,
+				client_update,
+				&hop_client_id,
+				&mut hop_client_state,
+				&client_state_proof.proof,
+			)?;
+		}
+
+		let consensus_height = consensus_proof.consensus_height(next_client_id)?;
+		let next_consensus_state = ctx
+			.decode_consensus_state(&consensus_proof.value)
+			.map_err(Error::ics02_client)?;
+		client_def
+			.verify_client_consensus_state(
+				ctx,
+				&hop_client_state,
+				hop_height,
+				&prefix,
+				&consensus_proof.proof,
+				hop_consensus_state.root(),
+				next_client_id,
+				consensus_height,
+				&next_consensus_state,
+			)
+			.map_err(verify_failed)?;
+		if first_hop {
+			consume_proof(
+				ctx,
+				client_update,
+				&hop_client_id,
+				&mut hop_client_state,
+				&consensus_proof.proof,
+			)?;
+		}
+
+		delay_period = delay_period.max(next_connection_end.delay_period());
+		hop_client_id = next_client_id.clone();
+		hop_client_state = next_client_state;
+		hop_height = consensus_height;
+		hop_consensus_state = next_consensus_state;
+		hop_connection_end = next_connection_end;
+	}
+
+	Ok(MultihopCounterparty {
+		client_state: hop_client_state,
+		height: hop_height,
+		root: hop_consensus_state.root().clone(),
+		prefix: hop_connection_end.counterparty().prefix().clone(),
+		proof: proofs.key_proof.proof,
+		delay_period,
+	})
+}
+
+/// Returns the height and the consensus state of the counterparty of a multi-hop channel, as
+/// stored by the last intermediate chain on the path. They are read from the consensus proof of
+/// that chain, which `proof` is verified against with the other proofs of the message.
+pub fn multihop_counterparty_consensus_state<Ctx>(
+	ctx: &Ctx,
+	proof: &CommitmentProofBytes,
+) -> Result<(Height, Ctx::AnyConsensusState), Error>
+where
+	Ctx: ReaderContext,
+{
+	let proofs = MultihopProofs::from_proof(proof)?;
+	let (connection_proof, consensus_proof) = proofs
+		.connection_proofs
+		.first()
+		.zip(proofs.consensus_proofs.first())
+		.ok_or_else(|| Error::invalid_multihop_proof("missing intermediate chains".to_string()))?;
+	let client_id = connection_proof.connection_end()?.client_id().clone();
+	let height = consensus_proof.consensus_height(&client_id)?;
+	let consensus_state = ctx
+		.decode_consensus_state(&consensus_proof.value)
+		.map_err(Error::ics02_client)?;
+	Ok((height, consensus_state))
+}
+
+/// Lets the client consume a proof verified against `client_state` and moves `client_state` to
+/// the state the next proof of the message must be verified against.
+fn consume_proof<Ctx>(
+	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
+	client_id: &ClientId,
+	client_state: &mut Ctx::AnyClientState,
+	proof: &CommitmentProofBytes,
+) -> Result<(), Error>
+where
+	Ctx: ReaderContext,
+{
+	update_on_proof(ctx, client_id, client_state, proof, client_update)
+		.map_err(Error::ics02_client)?;
+	*client_state =
+		client_state_for_proof(ctx, client_id, client_update).map_err(Error::ics02_client)?;
+	Ok(())
+}
+
+/// Returns the connection hops the counterparty end of a channel is expected to store. For a
+/// multi-hop channel they are read from the connection ends bundled in `proof`, which are
+/// verified along with the channel end.
+pub fn counterparty_connection_hops(
+	connection_hops: &[ConnectionId],
+	connection_end: &ConnectionEnd,
+	proof: &CommitmentProofBytes,
+) -> Result<Vec<ConnectionId>, Error> {
+	if connection_hops.len() <= 1 {
+		let counterparty_connection_id =
+			connection_end.counterparty().connection_id().ok_or_else(|| {
+				Error::undefined_connection_counterparty(
+					connection_hops.first().cloned().unwrap_or_default(),
+				)
+			})?;
+		return Ok(vec![counterparty_connection_id.clone()])
+	}
+
+	MultihopProofs::from_proof(proof)?.counterparty_connection_hops(connection_end)
+}
+
+/// Returns the commitment root the proofs of a single-hop channel are verified against, that of
+/// the consensus state of the client of the connection at `height`.
+fn counterparty_root<Ctx>(
+	ctx: &Ctx,
+	connection_end: &ConnectionEnd,
+	height: Height,
+) -> Result<CommitmentRoot, Error>
+where
+	Ctx: ReaderContext,
+{
+	let consensus_state = ctx
+		.consensus_state(connection_end.client_id(), height)
+		.map_err(|_| Error::error_invalid_consensus_state())?;
+	Ok(consensus_state.root().clone())
+}
 
 /// Entry point for verifying all proofs bundled in any ICS4 message for channel protocols.
 pub fn verify_channel_proofs<Ctx>(
 	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
 	height: Height,
 	channel_end: &ChannelEnd,
 	connection_end: &ConnectionEnd,
@@ -47,21 +380,41 @@ where
 	// This is the client which will perform proof verification.
 	let client_id = connection_end.client_id().clone();
 
-	let client_state = ctx.client_state(&client_id).map_err(Error::ics02_client)?;
+	let client_state =
+		client_state_for_proof(ctx, &client_id, client_update).map_err(Error::ics02_client)?;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(client_id))
 	}
 
-	let consensus_state = ctx
-		.consensus_state(&client_id, height)
-		.map_err(|_| Error::error_invalid_consensus_state())?;
+	// A counterparty channel id of None in not possible, and is checked by validate_basic in msg.
+	let port_id = channel_end.counterparty().port_id();
+	let channel_id =
+		channel_end.counterparty().channel_id().ok_or_else(Error::missing_channel_id)?;
+
+	if channel_end.connection_hops().len() > 1 {
+		let counterparty = verify_multihop_counterparty(
+			ctx,
+			client_update,
+			&client_state,
+			height,
+			channel_end.connection_hops(),
+			connection_end,
+			proof,
+		)?;
+		let value = expected_chan
+			.encode_vec()
+			.map_err(|e| Error::verify_channel_failed(Ics02Error::encode(e)))?;
+		return counterparty
+			.verify_membership(ctx, ChannelEndsPath(port_id.clone(), *channel_id), value)
+			.map_err(Error::verify_channel_failed)
+	}
 
+	let root = counterparty_root(ctx, connection_end, height)?;
 	let client_def = client_state.client_def();
 
 	// Verify the proof for the channel state against the expected channel end.
-	// A counterparty channel id of None in not possible, and is checked by validate_basic in msg.
 	client_def
 		.verify_channel_state(
 			ctx,
@@ -69,43 +422,65 @@ where
 			&client_state,
 			height,
 			connection_end.counterparty().prefix(),
-			&proof,
-			consensus_state.root(),
-			channel_end.counterparty().port_id(),
-			channel_end
-				.counterparty()
-				.channel_id()
-				.ok_or_else(|| Error::missing_channel_id())?,
+			proof,
+			&root,
+			port_id,
+			channel_id,
 			expected_chan,
 		)
-		.map_err(Error::verify_channel_failed)
+		.map_err(Error::verify_channel_failed)?;
+
+	update_on_proof(ctx, &client_id, &client_state, proof, client_update)
+		.map_err(Error::ics02_client)?;
+	Ok(())
 }
 
 /// Entry point for verifying all proofs bundled in a ICS4 packet recv. message.
 pub fn verify_packet_recv_proofs<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
 	height: Height,
+	channel_end: &ChannelEnd,
 	packet: &Packet,
 	connection_end: &ConnectionEnd,
 	proofs: &Proofs,
 ) -> Result<(), Error> {
 	let client_id = connection_end.client_id();
-	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	let client_state =
+		client_state_for_proof(ctx, client_id, client_update).map_err(Error::ics02_client)?;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(client_id.clone()))
 	}
 
-	let consensus_state = ctx
-		.consensus_state(client_id, proofs.height())
-		.map_err(|_| Error::error_invalid_consensus_state())?;
-
-	let client_def = client_state.client_def();
-
 	let commitment =
 		ctx.packet_commitment(packet.data.clone(), packet.timeout_height, packet.timeout_timestamp);
 
+	if channel_end.connection_hops().len() > 1 {
+		let counterparty = verify_multihop_counterparty(
+			ctx,
+			client_update,
+			&client_state,
+			proofs.height(),
+			channel_end.connection_hops(),
+			connection_end,
+			proofs.object_proof(),
+		)?;
+		counterparty.verify_delay_passed(ctx, client_id, proofs.height())?;
+		let path = CommitmentsPath {
+			port_id: packet.source_port.clone(),
+			channel_id: packet.source_channel,
+			sequence: packet.sequence,
+		};
+		return counterparty
+			.verify_membership(ctx, path, commitment.into_vec())
+			.map_err(|e| Error::packet_verification_failed(packet.sequence, e))
+	}
+
+	let root = counterparty_root(ctx, connection_end, proofs.height())?;
+	let client_def = client_state.client_def();
+
 	// Verify the proof for the packet against the chain store.
 	client_def
 		.verify_packet_data(
@@ -115,7 +490,7 @@ pub fn verify_packet_recv_proofs<Ctx: ReaderContext>(
 			height,
 			connection_end,
 			proofs.object_proof(),
-			consensus_state.root(),
+			&root,
 			&packet.source_port,
 			&packet.source_channel,
 			packet.sequence,
@@ -123,32 +498,55 @@ pub fn verify_packet_recv_proofs<Ctx: ReaderContext>(
 		)
 		.map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
 
+	update_on_proof(ctx, client_id, &client_state, proofs.object_proof(), client_update)
+		.map_err(Error::ics02_client)?;
 	Ok(())
 }
 
 /// Entry point for verifying all proofs bundled in an ICS4 packet ack message.
 pub fn verify_packet_acknowledgement_proofs<Ctx: ReaderContext>(
 	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
 	height: Height,
+	channel_end: &ChannelEnd,
 	packet: &Packet,
 	acknowledgement: Acknowledgement,
 	connection_end: &ConnectionEnd,
 	proofs: &Proofs,
 ) -> Result<(), Error> {
 	let client_id = connection_end.client_id();
-	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	let client_state =
+		client_state_for_proof(ctx, client_id, client_update).map_err(Error::ics02_client)?;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(client_id.clone()))
 	}
 
-	let consensus_state = ctx
-		.consensus_state(client_id, proofs.height())
-		.map_err(|_| Error::error_invalid_consensus_state())?;
-
 	let ack_commitment = ctx.ack_commitment(acknowledgement);
 
+	if channel_end.connection_hops().len() > 1 {
+		let counterparty = verify_multihop_counterparty(
+			ctx,
+			client_update,
+			&client_state,
+			proofs.height(),
+			channel_end.connection_hops(),
+			connection_end,
+			proofs.object_proof(),
+		)?;
+		counterparty.verify_delay_passed(ctx, client_id, proofs.height())?;
+		let path = AcksPath {
+			port_id: packet.destination_port.clone(),
+			channel_id: packet.destination_channel,
+			sequence: packet.sequence,
+		};
+		return counterparty
+			.verify_membership(ctx, path, ack_commitment.into_vec())
+			.map_err(|e| Error::packet_verification_failed(packet.sequence, e))
+	}
+
+	let root = counterparty_root(ctx, connection_end, proofs.height())?;
 	let client_def = client_state.client_def();
 
 	// Verify the proof for the packet against the chain store.
@@ -160,7 +558,7 @@ pub fn verify_packet_acknowledgement_proofs<Ctx: ReaderContext>(
 			height,
 			connection_end,
 			proofs.object_proof(),
-			consensus_state.root(),
+			&root,
 			&packet.destination_port,
 			&packet.destination_channel,
 			packet.sequence,
@@ -168,13 +566,17 @@ pub fn verify_packet_acknowledgement_proofs<Ctx: ReaderContext>(
 		)
 		.map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
 
+	update_on_proof(ctx, client_id, &client_state, proofs.object_proof(), client_update)
+		.map_err(Error::ics02_client)?;
 	Ok(())
 }
 
 /// Entry point for verifying all timeout proofs.
 pub fn verify_next_sequence_recv<Ctx>(
 	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
 	height: Height,
+	channel_end: &ChannelEnd,
 	connection_end: &ConnectionEnd,
 	packet: Packet,
 	seq: Sequence,
@@ -184,17 +586,34 @@ where
 	Ctx: ReaderContext,
 {
 	let client_id = connection_end.client_id();
-	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	let client_state =
+		client_state_for_proof(ctx, client_id, client_update).map_err(Error::ics02_client)?;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(client_id.clone()))
 	}
 
-	let consensus_state = ctx
-		.consensus_state(client_id, proofs.height())
-		.map_err(|_| Error::error_invalid_consensus_state())?;
+	if channel_end.connection_hops().len() > 1 {
+		let counterparty = verify_multihop_counterparty(
+			ctx,
+			client_update,
+			&client_state,
+			proofs.height(),
+			channel_end.connection_hops(),
+			connection_end,
+			proofs.object_proof(),
+		)?;
+		counterparty.verify_delay_passed(ctx, client_id, proofs.height())?;
+		let mut seq_bytes = Vec::new();
+		u64::from(seq).encode(&mut seq_bytes).expect("buffer size too small");
+		let path = SeqRecvsPath(packet.destination_port.clone(), packet.destination_channel);
+		return counterparty
+			.verify_membership(ctx, path, seq_bytes)
+			.map_err(|e| Error::packet_verification_failed(seq, e))
+	}
 
+	let root = counterparty_root(ctx, connection_end, proofs.height())?;
 	let client_def = client_state.client_def();
 
 	// Verify the proof for the packet against the chain store.
@@ -206,19 +625,23 @@ where
 			height,
 			connection_end,
 			proofs.object_proof(),
-			consensus_state.root(),
+			&root,
 			&packet.destination_port,
 			&packet.destination_channel,
 			packet.sequence,
 		)
 		.map_err(|e| Error::packet_verification_failed(seq, e))?;
 
+	update_on_proof(ctx, client_id, &client_state, proofs.object_proof(), client_update)
+		.map_err(Error::ics02_client)?;
 	Ok(())
 }
 
 pub fn verify_packet_receipt_absence<Ctx>(
 	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
 	height: Height,
+	channel_end: &ChannelEnd,
 	connection_end: &ConnectionEnd,
 	packet: Packet,
 	proofs: &Proofs,
@@ -227,17 +650,36 @@ where
 	Ctx: ReaderContext,
 {
 	let client_id = connection_end.client_id();
-	let client_state = ctx.client_state(client_id).map_err(Error::ics02_client)?;
+	let client_state =
+		client_state_for_proof(ctx, client_id, client_update).map_err(Error::ics02_client)?;
 
 	// The client must not be frozen.
 	if client_state.is_frozen() {
 		return Err(Error::frozen_client(client_id.clone()))
 	}
 
-	let consensus_state = ctx
-		.consensus_state(client_id, proofs.height())
-		.map_err(|_| Error::error_invalid_consensus_state())?;
+	if channel_end.connection_hops().len() > 1 {
+		let counterparty = verify_multihop_counterparty(
+			ctx,
+			client_update,
+			&client_state,
+			proofs.height(),
+			channel_end.connection_hops(),
+			connection_end,
+			proofs.object_proof(),
+		)?;
+		counterparty.verify_delay_passed(ctx, client_id, proofs.height())?;
+		let path = ReceiptsPath {
+			port_id: packet.destination_port.clone(),
+			channel_id: packet.destination_channel,
+			sequence: packet.sequence,
+		};
+		return counterparty
+			.verify_non_membership(ctx, path)
+			.map_err(|e| Error::packet_verification_failed(packet.sequence, e))
+	}
 
+	let root = counterparty_root(ctx, connection_end, proofs.height())?;
 	let client_def = client_state.client_def();
 
 	// Verify the proof for the packet against the chain store.
@@ -249,12 +691,149 @@ where
 			height,
 			connection_end,
 			proofs.object_proof(),
-			consensus_state.root(),
+			&root,
 			&packet.destination_port,
 			&packet.destination_channel,
 			packet.sequence,
 		)
 		.map_err(|e| Error::packet_verification_failed(packet.sequence, e))?;
 
+	update_on_proof(ctx, client_id, &client_state, proofs.object_proof(), client_update)
+		.map_err(Error::ics02_client)?;
+	Ok(())
+}
+
+/// Entry point for verifying the proof of the upgrade stored on the counterparty channel end.
+pub fn verify_channel_upgrade_proofs<Ctx>(
+	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
+	height: Height,
+	channel_end: &ChannelEnd,
+	connection_end: &ConnectionEnd,
+	upgrade: &Upgrade,
+	proof: &CommitmentProofBytes,
+) -> Result<(), Error>
+where
+	Ctx: ReaderContext,
+{
+	let client_id = connection_end.client_id();
+	let client_state =
+		client_state_for_proof(ctx, client_id, client_update).map_err(Error::ics02_client)?;
+
+	// The client must not be frozen.
+	if client_state.is_frozen() {
+		return Err(Error::frozen_client(client_id.clone()))
+	}
+
+	let port_id = channel_end.counterparty().port_id();
+	let channel_id =
+		channel_end.counterparty().channel_id().ok_or_else(Error::missing_channel_id)?;
+
+	if channel_end.connection_hops().len() > 1 {
+		let counterparty = verify_multihop_counterparty(
+			ctx,
+			client_update,
+			&client_state,
+			height,
+			channel_end.connection_hops(),
+			connection_end,
+			proof,
+		)?;
+		let value = upgrade
+			.encode_vec()
+			.map_err(|e| Error::verify_channel_upgrade_failed(Ics02Error::encode(e)))?;
+		return counterparty
+			.verify_membership(ctx, ChannelUpgradesPath(port_id.clone(), *channel_id), value)
+			.map_err(Error::verify_channel_upgrade_failed)
+	}
+
+	let root = counterparty_root(ctx, connection_end, height)?;
+	let client_def = client_state.client_def();
+
+	client_def
+		.verify_channel_upgrade(
+			ctx,
+			client_id,
+			&client_state,
+			height,
+			connection_end,
+			proof,
+			&root,
+			port_id,
+			channel_id,
+			upgrade,
+		)
+		.map_err(Error::verify_channel_upgrade_failed)?;
+
+	update_on_proof(ctx, client_id, &client_state, proof, client_update)
+		.map_err(Error::ics02_client)?;
+	Ok(())
+}
+
+/// Entry point for verifying the proof of an error receipt written by the counterparty channel
+/// end when it aborted an upgrade.
+pub fn verify_channel_upgrade_error_proofs<Ctx>(
+	ctx: &Ctx,
+	client_update: &mut Option<update_client::Result<Ctx>>,
+	height: Height,
+	channel_end: &ChannelEnd,
+	connection_end: &ConnectionEnd,
+	error_receipt: &ErrorReceipt,
+	proof: &CommitmentProofBytes,
+) -> Result<(), Error>
+where
+	Ctx: ReaderContext,
+{
+	let client_id = connection_end.client_id();
+	let client_state =
+		client_state_for_proof(ctx, client_id, client_update).map_err(Error::ics02_client)?;
+
+	// The client must not be frozen.
+	if client_state.is_frozen() {
+		return Err(Error::frozen_client(client_id.clone()))
+	}
+
+	let port_id = channel_end.counterparty().port_id();
+	let channel_id =
+		channel_end.counterparty().channel_id().ok_or_else(Error::missing_channel_id)?;
+
+	if channel_end.connection_hops().len() > 1 {
+		let counterparty = verify_multihop_counterparty(
+			ctx,
+			client_update,
+			&client_state,
+			height,
+			channel_end.connection_hops(),
+			connection_end,
+			proof,
+		)?;
+		let value = error_receipt
+			.encode_vec()
+			.map_err(|e| Error::verify_channel_upgrade_failed(Ics02Error::encode(e)))?;
+		return counterparty
+			.verify_membership(ctx, ChannelUpgradeErrorsPath(port_id.clone(), *channel_id), value)
+			.map_err(Error::verify_channel_upgrade_failed)
+	}
+
+	let root = counterparty_root(ctx, connection_end, height)?;
+	let client_def = client_state.client_def();
+
+	client_def
+		.verify_channel_upgrade_error(
+			ctx,
+			client_id,
+			&client_state,
+			height,
+			connection_end,
+			proof,
+			&root,
+			port_id,
+			channel_id,
+			error_receipt,
+		)
+		.map_err(Error::verify_channel_upgrade_failed)?;
+
+	update_on_proof(ctx, client_id, &client_state, proof, client_update)
+		.map_err(Error::ics02_client)?;
 	Ok(())
 }
@@ -40,13 +40,13 @@ pub fn process<Ctx: ReaderContext>(
 	ctx: &Ctx,
 	packet: Packet,
 	ack: Vec<u8>,
-) -> HandlerResult<PacketResult, Error> {
+) -> HandlerResult<PacketResult<Ctx>, Error> {
 	let mut output = HandlerOutput::builder();
 
 	let dest_channel_end =
 		ctx.channel_end(&(packet.destination_port.clone(), packet.destination_channel))?;
 
-	if !dest_channel_end.state_matches(&State::Open) {
+	if !dest_channel_end.state_matches(&State::Open) && !dest_channel_end.is_flushing() {
 		return Err(Error::invalid_channel_state(packet.source_channel, dest_channel_end.state))
 	}
 
@@ -22,7 +22,9 @@ pub mod events;
 
 pub mod handler;
 pub mod msgs;
+pub mod multihop;
 pub mod packet;
+pub mod upgrade;
 
 pub mod commitment;
 mod version;
@@ -0,0 +1,220 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::prelude::*;
+
+use tendermint_proto::Protobuf;
+
+use ibc_proto::ibc::core::channel::v1::MsgChannelUpgradeAck as RawMsgChannelUpgradeAck;
+
+use crate::{
+	core::{
+		ics04_channel::{error::Error, upgrade::Upgrade},
+		ics24_host::identifier::{ChannelId, PortId},
+	},
+	proofs::Proofs,
+	signer::Signer,
+	tx_msg::Msg,
+};
+
+pub const TYPE_URL: &str = "/ibc.core.channel.v1.MsgChannelUpgradeAck";
+
+///
+/// Message definition for the third step in the channel upgrade handshake (`ChanUpgradeAck`
+/// datagram). The object proof proves the counterparty channel end, the other proof proves the
+/// counterparty upgrade.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MsgChannelUpgradeAck {
+	pub port_id: PortId,
+	pub channel_id: ChannelId,
+	pub counterparty_upgrade: Upgrade,
+	pub proofs: Proofs,
+	pub signer: Signer,
+}
+
+impl Msg for MsgChannelUpgradeAck {
+	type ValidationError = Error;
+	type Raw = RawMsgChannelUpgradeAck;
+
+	fn route(&self) -> String {
+		crate::keys::ROUTER_KEY.to_string()
+	}
+
+	fn type_url(&self) -> String {
+		TYPE_URL.to_string()
+	}
+}
+
+impl Protobuf<RawMsgChannelUpgradeAck> for MsgChannelUpgradeAck {}
+
+impl TryFrom<RawMsgChannelUpgradeAck> for MsgChannelUpgradeAck {
+	type Error = Error;
+
+	fn try_from(raw_msg: RawMsgChannelUpgradeAck) -> Result<Self, Self::Error> {
+		let proofs = Proofs::new(
+			raw_msg.proof_channel.try_into().map_err(Error::invalid_proof)?,
+			None,
+			None,
+			Some(raw_msg.proof_upgrade.try_into().map_err(Error::invalid_proof)?),
+			raw_msg.proof_height.ok_or_else(Error::missing_height)?.into(),
+		)
+		.map_err(Error::invalid_proof)?;
+
+		let counterparty_upgrade: Upgrade =
+			raw_msg.counterparty_upgrade.ok_or_else(Error::missing_upgrade)?.try_into()?;
+		counterparty_upgrade.validate_basic()?;
+
+		Ok(MsgChannelUpgradeAck {
+			port_id: raw_msg.port_id.parse().map_err(Error::identifier)?,
+			channel_id: raw_msg.channel_id.parse().map_err(Error::identifier)?,
+			counterparty_upgrade,
+			proofs,
+			signer: raw_msg.signer.parse().map_err(Error::signer)?,
+		})
+	}
+}
+
+impl From<MsgChannelUpgradeAck> for RawMsgChannelUpgradeAck {
+	fn from(domain_msg: MsgChannelUpgradeAck) -> Self {
+		RawMsgChannelUpgradeAck {
+			port_id: domain_msg.port_id.to_string(),
+			channel_id: domain_msg.channel_id.to_string(),
+			counterparty_upgrade: Some(domain_msg.counterparty_upgrade.into()),
+			proof_channel: domain_msg.proofs.object_proof().clone().into(),
+			proof_upgrade: domain_msg
+				.proofs
+				.other_proof()
+				.clone()
+				.map_or_else(Vec::new, |v| v.into()),
+			proof_height: Some(domain_msg.proofs.height().into()),
+			signer: domain_msg.signer.to_string(),
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test_util {
+	use crate::prelude::*;
+	use ibc_proto::ibc::core::{
+		channel::v1::MsgChannelUpgradeAck as RawMsgChannelUpgradeAck, client::v1::Height,
+	};
+
+	use crate::{
+		core::{
+			ics04_channel::upgrade::test_util::get_dummy_raw_upgrade,
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		test_utils::{get_dummy_bech32_account, get_dummy_proof},
+	};
+
+	/// Returns a dummy `RawMsgChannelUpgradeAck`, for testing only!
+	pub fn get_dummy_raw_msg_chan_upgrade_ack(proof_height: u64) -> RawMsgChannelUpgradeAck {
+		RawMsgChannelUpgradeAck {
+			port_id: PortId::default().to_string(),
+			channel_id: ChannelId::default().to_string(),
+			counterparty_upgrade: Some(get_dummy_raw_upgrade(proof_height + 100)),
+			proof_channel: get_dummy_proof(),
+			proof_upgrade: get_dummy_proof(),
+			proof_height: Some(Height { revision_number: 0, revision_height: proof_height }),
+			signer: get_dummy_bech32_account(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::prelude::*;
+
+	use test_log::test;
+
+	use ibc_proto::ibc::core::channel::v1::{
+		MsgChannelUpgradeAck as RawMsgChannelUpgradeAck, Timeout as RawTimeout,
+		Upgrade as RawUpgrade,
+	};
+
+	use crate::core::ics04_channel::{
+		msgs::chan_upgrade_ack::{
+			test_util::get_dummy_raw_msg_chan_upgrade_ack, MsgChannelUpgradeAck,
+		},
+		upgrade::test_util::get_dummy_raw_upgrade,
+	};
+
+	#[test]
+	fn parse_channel_upgrade_ack_msg() {
+		struct Test {
+			name: String,
+			raw: RawMsgChannelUpgradeAck,
+			want_pass: bool,
+		}
+
+		let proof_height = 10;
+		let default_raw_msg = get_dummy_raw_msg_chan_upgrade_ack(proof_height);
+
+		let tests: Vec<Test> = vec![
+			Test {
+				name: "Good parameters".to_string(),
+				raw: default_raw_msg.clone(),
+				want_pass: true,
+			},
+			Test {
+				name: "Missing counterparty upgrade".to_string(),
+				raw: RawMsgChannelUpgradeAck {
+					counterparty_upgrade: None,
+					..default_raw_msg.clone()
+				},
+				want_pass: false,
+			},
+			Test {
+				name: "Counterparty upgrade without timeout".to_string(),
+				raw: RawMsgChannelUpgradeAck {
+					counterparty_upgrade: Some(RawUpgrade {
+						timeout: Some(RawTimeout { height: None, timestamp: 0 }),
+						..get_dummy_raw_upgrade(proof_height)
+					}),
+					..default_raw_msg.clone()
+				},
+				want_pass: false,
+			},
+			Test {
+				name: "Empty channel proof".to_string(),
+				raw: RawMsgChannelUpgradeAck { proof_channel: vec![], ..default_raw_msg },
+				want_pass: false,
+			},
+		]
+		.into_iter()
+		.collect();
+
+		for test in tests {
+			let msg = MsgChannelUpgradeAck::try_from(test.raw.clone());
+
+			assert_eq!(
+				test.want_pass,
+				msg.is_ok(),
+				"MsgChanUpgradeAck::try_from failed for test {}, \nmsg {:?} with error {:?}",
+				test.name,
+				test.raw,
+				msg.err(),
+			);
+		}
+	}
+
+	#[test]
+	fn to_and_from() {
+		let raw = get_dummy_raw_msg_chan_upgrade_ack(12);
+		let msg = MsgChannelUpgradeAck::try_from(raw.clone()).unwrap();
+		let raw_back = RawMsgChannelUpgradeAck::from(msg.clone());
+		let msg_back = MsgChannelUpgradeAck::try_from(raw_back.clone()).unwrap();
+		assert_eq!(raw, raw_back);
+		assert_eq!(msg, msg_back);
+	}
+}
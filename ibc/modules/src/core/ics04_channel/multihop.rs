@@ -0,0 +1,190 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Proofs for channels whose connection hops span several chains (ICS-33 multi-hop channels).
+//!
+//! The client of the first connection hop only tracks the adjacent chain. A proof of the
+//! counterparty state is therefore bundled with the proofs needed to walk the channel path: for
+//! every intermediate chain, the connection end to the next chain and the client and consensus
+//! states it stores for that chain. The proofs are ordered from the counterparty towards the
+//! verifying chain, i.e. the last entries are proven against the client of the first hop.
+
+use crate::prelude::*;
+
+use core::str::FromStr;
+
+use ibc_proto::ibc::core::{
+	channel::v1::{MsgMultihopProofs as RawMsgMultihopProofs, MultihopProof as RawMultihopProof},
+	commitment::v1::MerklePath,
+};
+use tendermint_proto::Protobuf;
+
+use crate::core::{
+	ics02_client::height::Height,
+	ics03_connection::connection::ConnectionEnd,
+	ics04_channel::error::Error,
+	ics23_commitment::commitment::CommitmentProofBytes,
+	ics24_host::{
+		identifier::{ClientId, ConnectionId},
+		path::Path,
+	},
+};
+
+/// A proof that `value` is stored under `prefixed_key` in the state of one chain on the path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultihopProof {
+	pub proof: CommitmentProofBytes,
+	pub value: Vec<u8>,
+	/// Store prefix followed by the ICS-24 path of the value.
+	pub prefixed_key: Vec<String>,
+}
+
+impl MultihopProof {
+	/// The ICS-24 path the value is stored under, without the store prefix.
+	pub fn path(&self) -> Result<Path, Error> {
+		let key = self
+			.prefixed_key
+			.last()
+			.ok_or_else(|| Error::invalid_multihop_proof("empty key".to_string()))?;
+		Path::from_str(key).map_err(|e| Error::invalid_multihop_proof(e.to_string()))
+	}
+
+	/// Decodes the proven value as a connection end.
+	pub fn connection_end(&self) -> Result<ConnectionEnd, Error> {
+		ConnectionEnd::decode_vec(&self.value)
+			.map_err(|e| Error::invalid_multihop_proof(e.to_string()))
+	}
+
+	/// Returns the height of the consensus state proven for `client_id`.
+	pub fn consensus_height(&self, client_id: &ClientId) -> Result<Height, Error> {
+		match self.path()? {
+			Path::ClientConsensusState(path) if path.client_id == *client_id =>
+				Ok(Height::new(path.epoch, path.height)),
+			path => Err(Error::invalid_multihop_proof(format!(
+				"expected a consensus state of client {}, got {}",
+				client_id, path
+			))),
+		}
+	}
+}
+
+impl Protobuf<RawMultihopProof> for MultihopProof {}
+
+impl TryFrom<RawMultihopProof> for MultihopProof {
+	type Error = Error;
+
+	fn try_from(value: RawMultihopProof) -> Result<Self, Self::Error> {
+		Ok(MultihopProof {
+			proof: value.proof.try_into().map_err(Error::invalid_proof)?,
+			value: value.value,
+			prefixed_key: value.prefixed_key.map(|key| key.key_path).unwrap_or_default(),
+		})
+	}
+}
+
+impl From<MultihopProof> for RawMultihopProof {
+	fn from(value: MultihopProof) -> Self {
+		RawMultihopProof {
+			proof: value.proof.into(),
+			value: value.value,
+			prefixed_key: Some(MerklePath { key_path: value.prefixed_key }),
+		}
+	}
+}
+
+/// The proofs carried by a message on a multi-hop channel, encoded in place of the object proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultihopProofs {
+	/// Proof of the object itself, in the state of the counterparty.
+	pub key_proof: MultihopProof,
+	/// Proofs of the connection ends of the intermediate chains.
+	pub connection_proofs: Vec<MultihopProof>,
+	/// Proofs of the consensus states stored by the intermediate chains.
+	pub consensus_proofs: Vec<MultihopProof>,
+	/// Proofs of the client states stored by the intermediate chains, which verify the proofs of
+	/// the next chain on the path.
+	pub client_state_proofs: Vec<MultihopProof>,
+}
+
+impl MultihopProofs {
+	/// Decodes the proofs bundled in the object proof of a message.
+	pub fn from_proof(proof: &CommitmentProofBytes) -> Result<Self, Error> {
+		Self::decode_vec(proof.as_bytes()).map_err(|e| Error::invalid_multihop_proof(e.to_string()))
+	}
+
+	/// Returns the connection hops of the counterparty channel end, which are the counterparty
+	/// connections of the hops of the local end, in reverse order. The connection ends of the
+	/// intermediate chains are taken as is, they still need to be verified.
+	pub fn counterparty_connection_hops(
+		&self,
+		connection_end: &ConnectionEnd,
+	) -> Result<Vec<ConnectionId>, Error> {
+		let mut hops = Vec::with_capacity(self.connection_proofs.len() + 1);
+		let mut connection_end = connection_end.clone();
+		for proof in self.connection_proofs.iter().rev() {
+			let next_connection_end = proof.connection_end()?;
+			hops.push(counterparty_connection_id(&connection_end)?);
+			connection_end = next_connection_end;
+		}
+		hops.push(counterparty_connection_id(&connection_end)?);
+		hops.reverse();
+		Ok(hops)
+	}
+}
+
+fn counterparty_connection_id(connection_end: &ConnectionEnd) -> Result<ConnectionId, Error> {
+	connection_end.counterparty().connection_id().cloned().ok_or_else(|| {
+		Error::invalid_multihop_proof("connection hop without a counterparty".to_string())
+	})
+}
+
+impl Protobuf<RawMsgMultihopProofs> for MultihopProofs {}
+
+impl TryFrom<RawMsgMultihopProofs> for MultihopProofs {
+	type Error = Error;
+
+	fn try_from(value: RawMsgMultihopProofs) -> Result<Self, Self::Error> {
+		Ok(MultihopProofs {
+			key_proof: value
+				.key_proof
+				.ok_or_else(|| Error::invalid_multihop_proof("missing key proof".to_string()))?
+				.try_into()?,
+			connection_proofs: value
+				.connection_proofs
+				.into_iter()
+				.map(MultihopProof::try_from)
+				.collect::<Result<_, _>>()?,
+			consensus_proofs: value
+				.consensus_proofs
+				.into_iter()
+				.map(MultihopProof::try_from)
+				.collect::<Result<_, _>>()?,
+			client_state_proofs: value
+				.client_state_proofs
+				.into_iter()
+				.map(MultihopProof::try_from)
+				.collect::<Result<_, _>>()?,
+		})
+	}
+}
+
+impl From<MultihopProofs> for RawMsgMultihopProofs {
+	fn from(value: MultihopProofs) -> Self {
+		RawMsgMultihopProofs {
+			key_proof: Some(value.key_proof.into()),
+			connection_proofs: value.connection_proofs.into_iter().map(Into::into).collect(),
+			consensus_proofs: value.consensus_proofs.into_iter().map(Into::into).collect(),
+			client_state_proofs: value.client_state_proofs.into_iter().map(Into::into).collect(),
+		}
+	}
+}
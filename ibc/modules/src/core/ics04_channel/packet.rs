@@ -22,6 +22,7 @@ use ibc_proto::ibc::core::channel::v1::Packet as RawPacket;
 
 use crate::{
 	core::{
+		ics02_client::{context::ClientTypes, handler::update_client},
 		ics04_channel::error::Error,
 		ics24_host::identifier::{ChannelId, PortId},
 	},
@@ -46,12 +47,28 @@ pub enum PacketMsgType {
 }
 
 #[derive(Clone, Debug)]
-pub enum PacketResult {
+pub enum PacketResult<C: ClientTypes> {
 	Send(SendPacketResult),
-	Recv(RecvPacketResult),
+	Recv(RecvPacketResult<C>),
 	WriteAck(WriteAckPacketResult),
-	Ack(AckPacketResult),
-	Timeout(TimeoutPacketResult),
+	Ack(AckPacketResult<C>),
+	Timeout(TimeoutPacketResult<C>),
+}
+
+impl<C: ClientTypes> PacketResult<C> {
+	/// Takes the state the client of the channel was left in by the proofs of the message, for
+	/// clients whose state changes with every proof they verify.
+	pub fn take_client_update(&mut self) -> Option<update_client::Result<C>> {
+		match self {
+			PacketResult::Recv(
+				RecvPacketResult::Unordered { client_update, .. } |
+				RecvPacketResult::Ordered { client_update, .. },
+			) |
+			PacketResult::Ack(AckPacketResult { client_update, .. }) |
+			PacketResult::Timeout(TimeoutPacketResult { client_update, .. }) => client_update.take(),
+			_ => None,
+		}
+	}
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,252 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Types describing an in-progress channel upgrade (ICS-04 channel upgradability).
+
+use crate::prelude::*;
+
+use core::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tendermint_proto::Protobuf;
+
+use ibc_proto::ibc::core::channel::v1::{
+	ErrorReceipt as RawErrorReceipt, Timeout as RawTimeout, Upgrade as RawUpgrade,
+	UpgradeFields as RawUpgradeFields,
+};
+
+use crate::{
+	core::{
+		ics02_client::height::Height,
+		ics04_channel::{channel::Order, error::Error, packet::Sequence, Version},
+		ics24_host::identifier::ConnectionId,
+	},
+	timestamp::{Expiry, Timestamp},
+};
+
+/// The fields of a channel end which may be changed by an upgrade.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeFields {
+	pub ordering: Order,
+	pub connection_hops: Vec<ConnectionId>,
+	pub version: Version,
+}
+
+impl UpgradeFields {
+	pub fn new(ordering: Order, connection_hops: Vec<ConnectionId>, version: Version) -> Self {
+		Self { ordering, connection_hops, version }
+	}
+
+	pub fn validate_basic(&self) -> Result<(), Error> {
+		if self.connection_hops.len() != 1 {
+			return Err(Error::invalid_connection_hops_length(1, self.connection_hops.len()))
+		}
+		if self.version.to_string().is_empty() {
+			return Err(Error::invalid_upgrade_fields("version cannot be empty".to_string()))
+		}
+		Ok(())
+	}
+}
+
+impl Protobuf<RawUpgradeFields> for UpgradeFields {}
+
+impl TryFrom<RawUpgradeFields> for UpgradeFields {
+	type Error = Error;
+
+	fn try_from(value: RawUpgradeFields) -> Result<Self, Self::Error> {
+		let ordering = Order::from_i32(value.ordering)?;
+		let connection_hops = value
+			.connection_hops
+			.into_iter()
+			.map(|conn_id| ConnectionId::from_str(conn_id.as_str()))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(Error::identifier)?;
+
+		Ok(UpgradeFields::new(ordering, connection_hops, value.version.into()))
+	}
+}
+
+impl From<UpgradeFields> for RawUpgradeFields {
+	fn from(value: UpgradeFields) -> Self {
+		RawUpgradeFields {
+			ordering: value.ordering as i32,
+			connection_hops: value.connection_hops.iter().map(|v| v.as_str().to_string()).collect(),
+			version: value.version.to_string(),
+		}
+	}
+}
+
+/// Deadline after which an upgrade attempt can be timed out on the counterparty chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeTimeout {
+	pub height: Height,
+	pub timestamp: Timestamp,
+}
+
+impl UpgradeTimeout {
+	pub fn new(height: Height, timestamp: Timestamp) -> Result<Self, Error> {
+		if height.is_zero() && timestamp == Timestamp::none() {
+			return Err(Error::invalid_upgrade_timeout())
+		}
+		Ok(Self { height, timestamp })
+	}
+
+	/// The timeout of an upgrade that was only proposed. It is set once the end starts flushing.
+	pub fn none() -> Self {
+		Self { height: Height::zero(), timestamp: Timestamp::none() }
+	}
+
+	pub fn is_set(&self) -> bool {
+		!self.height.is_zero() || self.timestamp != Timestamp::none()
+	}
+
+	/// Returns `true` if the timeout has elapsed on a chain at the given height and time.
+	pub fn has_expired(&self, chain_height: Height, chain_timestamp: &Timestamp) -> bool {
+		(!self.height.is_zero() && self.height <= chain_height) ||
+			(self.timestamp != Timestamp::none() &&
+				chain_timestamp.check_expiry(&self.timestamp) == Expiry::Expired)
+	}
+}
+
+impl Protobuf<RawTimeout> for UpgradeTimeout {}
+
+impl TryFrom<RawTimeout> for UpgradeTimeout {
+	type Error = Error;
+
+	fn try_from(value: RawTimeout) -> Result<Self, Self::Error> {
+		let height = value.height.map(Height::from).unwrap_or_else(Height::zero);
+		let timestamp = Timestamp::from_nanoseconds(value.timestamp)
+			.map_err(Error::invalid_packet_timestamp)?;
+		Ok(UpgradeTimeout { height, timestamp })
+	}
+}
+
+impl From<UpgradeTimeout> for RawTimeout {
+	fn from(value: UpgradeTimeout) -> Self {
+		RawTimeout { height: Some(value.height.into()), timestamp: value.timestamp.nanoseconds() }
+	}
+}
+
+/// An upgrade proposed for a channel end, stored under the channel upgrade path
+/// until the handshake completes or is aborted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Upgrade {
+	pub fields: UpgradeFields,
+	pub timeout: UpgradeTimeout,
+	/// Next send sequence of the proposing end when the upgrade was proposed, the counterparty
+	/// uses it to know when all in-flight packets have been flushed.
+	pub next_sequence_send: Sequence,
+}
+
+impl Upgrade {
+	pub fn new(
+		fields: UpgradeFields,
+		timeout: UpgradeTimeout,
+		next_sequence_send: Sequence,
+	) -> Self {
+		Self { fields, timeout, next_sequence_send }
+	}
+
+	/// Validates an upgrade of an end that started flushing, which must carry a timeout.
+	pub fn validate_basic(&self) -> Result<(), Error> {
+		self.fields.validate_basic()?;
+		if !self.timeout.is_set() {
+			return Err(Error::invalid_upgrade_timeout())
+		}
+		Ok(())
+	}
+}
+
+impl Protobuf<RawUpgrade> for Upgrade {}
+
+impl TryFrom<RawUpgrade> for Upgrade {
+	type Error = Error;
+
+	fn try_from(value: RawUpgrade) -> Result<Self, Self::Error> {
+		Ok(Upgrade {
+			fields: value.fields.ok_or_else(Error::missing_upgrade_fields)?.try_into()?,
+			timeout: value.timeout.ok_or_else(Error::invalid_upgrade_timeout)?.try_into()?,
+			next_sequence_send: value.next_sequence_send.into(),
+		})
+	}
+}
+
+impl From<Upgrade> for RawUpgrade {
+	fn from(value: Upgrade) -> Self {
+		RawUpgrade {
+			fields: Some(value.fields.into()),
+			timeout: Some(value.timeout.into()),
+			next_sequence_send: value.next_sequence_send.into(),
+		}
+	}
+}
+
+/// Written when an upgrade attempt is aborted, so the counterparty can prove the failure
+/// and cancel its side of the handshake.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorReceipt {
+	pub sequence: u64,
+	pub message: String,
+}
+
+impl ErrorReceipt {
+	pub fn new(sequence: u64, message: String) -> Self {
+		Self { sequence, message }
+	}
+}
+
+impl Protobuf<RawErrorReceipt> for ErrorReceipt {}
+
+impl From<RawErrorReceipt> for ErrorReceipt {
+	fn from(value: RawErrorReceipt) -> Self {
+		ErrorReceipt { sequence: value.sequence, message: value.message }
+	}
+}
+
+impl From<ErrorReceipt> for RawErrorReceipt {
+	fn from(value: ErrorReceipt) -> Self {
+		RawErrorReceipt { sequence: value.sequence, message: value.message }
+	}
+}
+
+#[cfg(test)]
+pub mod test_util {
+	use crate::{core::ics24_host::identifier::ConnectionId, prelude::*};
+	use ibc_proto::ibc::core::{
+		channel::v1::{
+			Timeout as RawTimeout, Upgrade as RawUpgrade, UpgradeFields as RawUpgradeFields,
+		},
+		client::v1::Height as RawHeight,
+	};
+
+	/// Returns dummy `RawUpgradeFields`, for testing only!
+	pub fn get_dummy_raw_upgrade_fields() -> RawUpgradeFields {
+		RawUpgradeFields {
+			ordering: 1,
+			connection_hops: vec![ConnectionId::default().to_string()],
+			version: "ics20-1".to_string(),
+		}
+	}
+
+	/// Returns a dummy `RawUpgrade`, for testing only!
+	pub fn get_dummy_raw_upgrade(timeout_height: u64) -> RawUpgrade {
+		RawUpgrade {
+			fields: Some(get_dummy_raw_upgrade_fields()),
+			timeout: Some(RawTimeout {
+				height: Some(RawHeight { revision_number: 0, revision_height: timeout_height }),
+				timestamp: 0,
+			}),
+			next_sequence_send: 1,
+		}
+	}
+}
@@ -0,0 +1,319 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::prelude::*;
+
+use core::fmt::Display;
+
+use crate::{
+	core::{
+		ics02_client::{
+			client_def::{ClientDef, ConsensusUpdateResult},
+			error::Error as Ics02Error,
+		},
+		ics03_connection::connection::ConnectionEnd,
+		ics04_channel::{
+			channel::ChannelEnd,
+			commitment::{AcknowledgementCommitment, PacketCommitment},
+			packet::Sequence,
+			upgrade::{ErrorReceipt, Upgrade},
+		},
+		ics09_localhost::{
+			client_message::ClientMessage, client_state::ClientState,
+			consensus_state::ConsensusState, error::Error,
+		},
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			path::{
+				AcksPath, ChannelEndsPath, ChannelUpgradeErrorsPath, ChannelUpgradesPath,
+				ClientConsensusStatePath, ClientStatePath, CommitmentsPath, ConnectionsPath,
+				ReceiptsPath, SeqRecvsPath,
+			},
+		},
+		ics26_routing::context::ReaderContext,
+	},
+	Height,
+};
+
+/// Verifies the state of the counterparty by reading it from the store of the host, so the
+/// proofs, proof heights and commitment roots passed in are ignored.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct LocalhostClient;
+
+impl ClientDef for LocalhostClient {
+	type ClientMessage = ClientMessage;
+	type ClientState = ClientState;
+	type ConsensusState = ConsensusState;
+
+	fn verify_client_message<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		_client_state: Self::ClientState,
+		_client_message: Self::ClientMessage,
+	) -> Result<(), Ics02Error> {
+		Ok(())
+	}
+
+	fn update_state<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		_client_message: Self::ClientMessage,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		let consensus_state = ConsensusState::new(ctx.host_timestamp());
+		let cs = Ctx::AnyConsensusState::wrap(&consensus_state).ok_or_else(|| {
+			Ics02Error::unknown_consensus_state_type("Ctx::AnyConsensusState".to_string())
+		})?;
+		Ok((client_state.with_height(ctx.host_height()), ConsensusUpdateResult::Single(cs)))
+	}
+
+	fn update_state_on_misbehaviour(
+		&self,
+		_client_state: Self::ClientState,
+		_client_message: Self::ClientMessage,
+	) -> Result<Self::ClientState, Ics02Error> {
+		Err(Error::unsupported("misbehaviour".to_string()).into())
+	}
+
+	fn check_for_misbehaviour<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		_client_state: Self::ClientState,
+		_client_message: Self::ClientMessage,
+	) -> Result<bool, Ics02Error> {
+		Ok(false)
+	}
+
+	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		_old_client_state: &Self::ClientState,
+		_upgrade_client_state: &Self::ClientState,
+		_upgrade_consensus_state: &Self::ConsensusState,
+		_proof_upgrade_client: Vec<u8>,
+		_proof_upgrade_consensus_state: Vec<u8>,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		Err(Error::unsupported("upgrades".to_string()).into())
+	}
+
+	fn verify_client_consensus_state<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		client_id: &ClientId,
+		consensus_height: Height,
+		expected_consensus_state: &Ctx::AnyConsensusState,
+	) -> Result<(), Ics02Error> {
+		let path = ClientConsensusStatePath {
+			client_id: client_id.clone(),
+			epoch: consensus_height.revision_number,
+			height: consensus_height.revision_height,
+		};
+		verify_value(
+			path,
+			ctx.consensus_state(client_id, consensus_height),
+			expected_consensus_state,
+		)
+	}
+
+	fn verify_connection_state<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		connection_id: &ConnectionId,
+		expected_connection_end: &ConnectionEnd,
+	) -> Result<(), Ics02Error> {
+		let path = ConnectionsPath(connection_id.clone());
+		verify_value(path, ctx.connection_end(connection_id), expected_connection_end)
+	}
+
+	fn verify_channel_state<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		expected_channel_end: &ChannelEnd,
+	) -> Result<(), Ics02Error> {
+		let path = ChannelEndsPath(port_id.clone(), *channel_id);
+		verify_value(path, ctx.channel_end(&(port_id.clone(), *channel_id)), expected_channel_end)
+	}
+
+	fn verify_client_full_state<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_prefix: &CommitmentPrefix,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		client_id: &ClientId,
+		expected_client_state: &Ctx::AnyClientState,
+	) -> Result<(), Ics02Error> {
+		let path = ClientStatePath(client_id.clone());
+		verify_value(path, ctx.client_state(client_id), expected_client_state)
+	}
+
+	fn verify_packet_data<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_connection_end: &ConnectionEnd,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		commitment: PacketCommitment,
+	) -> Result<(), Ics02Error> {
+		let path = CommitmentsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		verify_value(
+			path,
+			ctx.get_packet_commitment(&(port_id.clone(), *channel_id, sequence)),
+			&commitment,
+		)
+	}
+
+	fn verify_packet_acknowledgement<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_connection_end: &ConnectionEnd,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		ack_commitment: AcknowledgementCommitment,
+	) -> Result<(), Ics02Error> {
+		let path = AcksPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		verify_value(
+			path,
+			ctx.get_packet_acknowledgement(&(port_id.clone(), *channel_id, sequence)),
+			&ack_commitment,
+		)
+	}
+
+	fn verify_next_sequence_recv<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_connection_end: &ConnectionEnd,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		let path = SeqRecvsPath(port_id.clone(), *channel_id);
+		verify_value(path, ctx.get_next_sequence_recv(&(port_id.clone(), *channel_id)), &sequence)
+	}
+
+	fn verify_packet_receipt_absence<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_connection_end: &ConnectionEnd,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		let path = ReceiptsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		if ctx.get_packet_receipt(&(port_id.clone(), *channel_id, sequence)).is_ok() {
+			return Err(Error::value_exists(path.to_string()).into())
+		}
+		Ok(())
+	}
+
+	fn verify_channel_upgrade<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_connection_end: &ConnectionEnd,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		upgrade: &Upgrade,
+	) -> Result<(), Ics02Error> {
+		let path = ChannelUpgradesPath(port_id.clone(), *channel_id);
+		verify_value(path, ctx.channel_upgrade(&(port_id.clone(), *channel_id)), upgrade)
+	}
+
+	fn verify_channel_upgrade_error<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		_client_state: &Self::ClientState,
+		_height: Height,
+		_connection_end: &ConnectionEnd,
+		_proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), Ics02Error> {
+		let path = ChannelUpgradeErrorsPath(port_id.clone(), *channel_id);
+		verify_value(
+			path,
+			ctx.upgrade_error_receipt(&(port_id.clone(), *channel_id)),
+			error_receipt,
+		)
+	}
+}
+
+/// Checks that the value the host stores at `path` is the `expected` one.
+fn verify_value<P, T, E>(path: P, stored: Result<T, E>, expected: &T) -> Result<(), Ics02Error>
+where
+	P: Display,
+	T: PartialEq,
+	E: Display,
+{
+	let stored = stored.map_err(|e| Error::missing_value(path.to_string(), e.to_string()))?;
+	if &stored != expected {
+		return Err(Error::value_mismatch(path.to_string()).into())
+	}
+	Ok(())
+}
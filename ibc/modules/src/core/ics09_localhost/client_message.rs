@@ -0,0 +1,53 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::prelude::*;
+
+use ibc_proto::google::protobuf::Any;
+use serde::{Deserialize, Serialize};
+use tendermint_proto::Protobuf;
+
+use crate::core::{ics02_client, ics09_localhost::error::Error};
+
+pub const LOCALHOST_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.localhost.v2.ClientMessage";
+
+/// Moves the localhost client to the current height of the host, it carries no data since
+/// everything is read from the host itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientMessage;
+
+impl ics02_client::client_message::ClientMessage for ClientMessage {
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<Any> for ClientMessage {}
+
+impl TryFrom<Any> for ClientMessage {
+	type Error = Error;
+
+	fn try_from(any: Any) -> Result<Self, Self::Error> {
+		match &*any.type_url {
+			LOCALHOST_CLIENT_MESSAGE_TYPE_URL => Ok(ClientMessage),
+			_ => Err(Error::unsupported(format!("client message {}", any.type_url))),
+		}
+	}
+}
+
+impl From<ClientMessage> for Any {
+	fn from(_: ClientMessage) -> Self {
+		Any { type_url: LOCALHOST_CLIENT_MESSAGE_TYPE_URL.to_string(), value: vec![] }
+	}
+}
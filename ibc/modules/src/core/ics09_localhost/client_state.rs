@@ -0,0 +1,117 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::prelude::*;
+
+use core::time::Duration;
+
+use ibc_proto::ibc::lightclients::localhost::v2::ClientState as RawClientState;
+use serde::{Deserialize, Serialize};
+use tendermint_proto::Protobuf;
+
+use crate::{
+	core::{
+		ics02_client::{self, client_state::ClientType},
+		ics09_localhost::{client_def::LocalhostClient, error::Error},
+		ics24_host::identifier::ChainId,
+	},
+	Height,
+};
+
+pub const LOCALHOST_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.localhost.v2.ClientState";
+
+/// The localhost client only tracks the latest height of the host, its state is read directly
+/// from the host's store.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientState {
+	pub latest_height: Height,
+}
+
+impl Protobuf<RawClientState> for ClientState {}
+
+impl ClientState {
+	pub fn new(latest_height: Height) -> Self {
+		Self { latest_height }
+	}
+
+	pub fn client_type() -> ClientType {
+		"09-localhost".to_string()
+	}
+
+	pub fn latest_height(&self) -> Height {
+		self.latest_height
+	}
+
+	pub fn with_height(self, latest_height: Height) -> Self {
+		Self { latest_height }
+	}
+}
+
+impl ics02_client::client_state::ClientState for ClientState {
+	type UpgradeOptions = ();
+	type ClientDef = LocalhostClient;
+
+	fn chain_id(&self) -> ChainId {
+		ChainId::new("localhost".to_string(), self.latest_height.revision_number)
+	}
+
+	fn client_def(&self) -> Self::ClientDef {
+		LocalhostClient::default()
+	}
+
+	fn client_type(&self) -> ClientType {
+		Self::client_type()
+	}
+
+	fn latest_height(&self) -> Height {
+		self.latest_height()
+	}
+
+	fn frozen_height(&self) -> Option<Height> {
+		// the host can't misbehave towards itself
+		None
+	}
+
+	fn upgrade(
+		self,
+		upgrade_height: Height,
+		_upgrade_options: Self::UpgradeOptions,
+		_chain_id: ChainId,
+	) -> Self {
+		self.with_height(upgrade_height)
+	}
+
+	fn expired(&self, _elapsed: Duration) -> bool {
+		false
+	}
+
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		self.encode_vec()
+	}
+}
+
+impl TryFrom<RawClientState> for ClientState {
+	type Error = Error;
+
+	fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
+		let latest_height = raw.latest_height.ok_or_else(Error::missing_latest_height)?.into();
+		Ok(Self { latest_height })
+	}
+}
+
+impl From<ClientState> for RawClientState {
+	fn from(value: ClientState) -> Self {
+		RawClientState { latest_height: Some(value.latest_height.into()) }
+	}
+}
@@ -0,0 +1,85 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::prelude::*;
+
+use core::convert::Infallible;
+
+use serde::Serialize;
+use tendermint_proto::Protobuf;
+
+use crate::{
+	core::{
+		ics02_client, ics09_localhost::error::Error, ics23_commitment::commitment::CommitmentRoot,
+	},
+	timestamp::Timestamp,
+};
+
+pub const LOCALHOST_CONSENSUS_STATE_TYPE_URL: &str =
+	"/ibc.lightclients.localhost.v2.ConsensusState";
+
+/// The localhost v2 protos don't define a consensus state, it only records the time of the host
+/// at the height the client was updated to.
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct RawConsensusState {
+	#[prost(uint64, tag = "1")]
+	pub timestamp: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ConsensusState {
+	pub timestamp: Timestamp,
+	/// Proofs are checked against the host's store, the root is always empty
+	pub root: CommitmentRoot,
+}
+
+impl ConsensusState {
+	pub fn new(timestamp: Timestamp) -> Self {
+		Self { timestamp, root: CommitmentRoot::from_bytes(&[]) }
+	}
+}
+
+impl ics02_client::client_consensus::ConsensusState for ConsensusState {
+	type Error = Infallible;
+
+	fn root(&self) -> &CommitmentRoot {
+		&self.root
+	}
+
+	fn timestamp(&self) -> Timestamp {
+		self.timestamp
+	}
+
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<RawConsensusState> for ConsensusState {}
+
+impl TryFrom<RawConsensusState> for ConsensusState {
+	type Error = Error;
+
+	fn try_from(raw: RawConsensusState) -> Result<Self, Self::Error> {
+		let timestamp = Timestamp::from_nanoseconds(raw.timestamp)
+			.map_err(|e| Error::invalid_timestamp(e.to_string()))?;
+		Ok(Self::new(timestamp))
+	}
+}
+
+impl From<ConsensusState> for RawConsensusState {
+	fn from(value: ConsensusState) -> Self {
+		RawConsensusState { timestamp: value.timestamp.nanoseconds() }
+	}
+}
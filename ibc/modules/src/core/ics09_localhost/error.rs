@@ -0,0 +1,65 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	core::{
+		ics02_client::error::{self as client_error, Error as Ics02Error},
+		ics03_connection::error as connection_error,
+		ics09_localhost::client_state::ClientState,
+	},
+	prelude::*,
+};
+use flex_error::define_error;
+
+define_error! {
+	#[derive(Debug, PartialEq, Eq)]
+	Error {
+		Ics02Client
+			[ client_error::Error ]
+			| _ | { "ics02 client error" },
+
+		Ics03Connection
+			[ connection_error::Error ]
+			| _ | { "ics03 connection error" },
+
+		MissingLatestHeight
+			|_| { "missing latest height" },
+
+		InvalidTimestamp
+			{ reason: String }
+			|e| { format_args!("invalid timestamp: {}", e.reason) },
+
+		MissingValue
+			{ path: String, reason: String }
+			|e| { format_args!("no value is stored at {}: {}", e.path, e.reason) },
+
+		ValueMismatch
+			{ path: String }
+			|e| { format_args!("the value stored at {} doesn't match the expected value", e.path) },
+
+		ValueExists
+			{ path: String }
+			|e| { format_args!("a value is stored at {}", e.path) },
+
+		Unsupported
+			{ reason: String }
+			|e| { format_args!("not supported by the localhost client: {}", e.reason) },
+	}
+}
+
+impl From<Error> for Ics02Error {
+	fn from(e: Error) -> Self {
+		Ics02Error::client_error(ClientState::client_type(), e.to_string())
+	}
+}
@@ -0,0 +1,218 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ICS 09: Localhost client, which lets modules of the host open connections and channels to
+//! each other. Instead of verifying proofs it reads the counterparty state from the host's own
+//! store, so packets between local modules don't need a relayer to travel through another chain.
+//!
+//! The host creates the client and the `connection-localhost` connection with [`initialize`],
+//! and the client must be updated to the height of the proofs that are submitted.
+
+pub mod client_def;
+pub mod client_message;
+pub mod client_state;
+pub mod consensus_state;
+pub mod error;
+
+use core::{str::FromStr, time::Duration};
+
+use crate::core::{
+	ics03_connection::{
+		connection::{ConnectionEnd, Counterparty, State},
+		context::ConnectionKeeper,
+		version::get_compatible_versions,
+	},
+	ics09_localhost::{client_state::ClientState, consensus_state::ConsensusState, error::Error},
+	ics24_host::identifier::{ClientId, ConnectionId},
+	ics26_routing::context::ReaderContext,
+};
+
+/// Identifier of the single localhost client of the host.
+pub const LOCALHOST_CLIENT_ID: &str = "09-localhost";
+/// Identifier of the connection of the localhost client to itself.
+pub const LOCALHOST_CONNECTION_ID: &str = "connection-localhost";
+
+pub fn client_id() -> ClientId {
+	ClientId::from_str(LOCALHOST_CLIENT_ID).expect("statically defined and valid, qed")
+}
+
+pub fn connection_id() -> ConnectionId {
+	ConnectionId::from_str(LOCALHOST_CONNECTION_ID).expect("statically defined and valid, qed")
+}
+
+/// Creates the localhost client at the current height of the host, together with the open
+/// `connection-localhost` connection whose counterparty is itself. Does nothing if the client
+/// already exists.
+pub fn initialize<Ctx>(ctx: &mut Ctx) -> Result<(), Error>
+where
+	Ctx: ReaderContext + ConnectionKeeper,
+{
+	let client_id = client_id();
+	if ctx.client_state(&client_id).is_ok() {
+		return Ok(())
+	}
+
+	let height = ctx.host_height();
+	let timestamp = ctx.host_timestamp();
+	let client_state = Ctx::AnyClientState::wrap(&ClientState::new(height))
+		.ok_or_else(|| Error::unsupported("client state of the host".to_string()))?;
+	let consensus_state = Ctx::AnyConsensusState::wrap(&ConsensusState::new(timestamp))
+		.ok_or_else(|| Error::unsupported("consensus state of the host".to_string()))?;
+	ctx.store_client_type(client_id.clone(), ClientState::client_type())
+		.map_err(Error::ics02_client)?;
+	ctx.store_client_state(client_id.clone(), client_state)
+		.map_err(Error::ics02_client)?;
+	ctx.store_consensus_state(client_id.clone(), height, consensus_state)
+		.map_err(Error::ics02_client)?;
+	ctx.store_update_time(client_id.clone(), height, timestamp)
+		.map_err(Error::ics02_client)?;
+	ctx.store_update_height(client_id.clone(), height, height)
+		.map_err(Error::ics02_client)?;
+
+	let connection_id = connection_id();
+	let counterparty =
+		Counterparty::new(client_id.clone(), Some(connection_id.clone()), ctx.commitment_prefix());
+	let connection_end = ConnectionEnd::new(
+		State::Open,
+		client_id.clone(),
+		counterparty,
+		get_compatible_versions(),
+		Duration::ZERO,
+	);
+	ctx.store_connection(connection_id.clone(), &connection_end)
+		.map_err(Error::ics03_connection)?;
+	ctx.store_connection_to_client(connection_id, &client_id)
+		.map_err(Error::ics03_connection)?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use test_log::test;
+
+	use crate::{
+		core::{
+			ics02_client::{
+				client_def::ClientDef,
+				context::{ClientKeeper, ClientReader},
+				handler::{dispatch, ClientResult::Update},
+				msgs::{update_client::MsgUpdateAnyClient, ClientMsg},
+			},
+			ics03_connection::context::ConnectionReader,
+			ics04_channel::{context::ChannelKeeper, upgrade::ErrorReceipt},
+			ics09_localhost::{client_def::LocalhostClient, client_message::ClientMessage},
+			ics23_commitment::commitment::{CommitmentProofBytes, CommitmentRoot},
+			ics24_host::identifier::{ChannelId, PortId},
+		},
+		mock::{
+			client_state::AnyClientState,
+			context::{MockClientTypes, MockContext},
+			header::AnyClientMessage,
+		},
+		prelude::*,
+		test_utils::get_dummy_account_id,
+	};
+
+	#[test]
+	fn localhost_connection_is_verified_against_the_host_store() {
+		let mut ctx = MockContext::<MockClientTypes>::default();
+		initialize(&mut ctx).unwrap();
+		// the client is only created once
+		initialize(&mut ctx).unwrap();
+
+		let connection_end = ctx.connection_end(&connection_id()).unwrap();
+		assert_eq!(connection_end.state(), &State::Open);
+		assert_eq!(connection_end.client_id(), &client_id());
+		assert_eq!(connection_end.counterparty().connection_id(), Some(&connection_id()));
+
+		let client_state = ClientState::new(ctx.host_height());
+		let proof = CommitmentProofBytes::try_from(vec![0]).unwrap();
+		let verify = |expected: &ConnectionEnd| {
+			LocalhostClient.verify_connection_state(
+				&ctx,
+				&client_id(),
+				&client_state,
+				ctx.host_height(),
+				&ctx.commitment_prefix(),
+				&proof,
+				&CommitmentRoot::from_bytes(&[]),
+				&connection_id(),
+				expected,
+			)
+		};
+		verify(&connection_end).unwrap();
+
+		let mut other_connection_end = connection_end.clone();
+		other_connection_end.set_state(State::Init);
+		assert!(verify(&other_connection_end).is_err());
+	}
+
+	#[test]
+	fn localhost_upgrade_error_is_verified_against_the_host_store() {
+		let mut ctx = MockContext::<MockClientTypes>::default();
+		initialize(&mut ctx).unwrap();
+		let port_id = PortId::transfer();
+		let error_receipt = ErrorReceipt::new(1, "upgrade aborted".to_string());
+		ctx.store_upgrade_error_receipt((port_id.clone(), ChannelId::new(0)), &error_receipt)
+			.unwrap();
+
+		let connection_end = ctx.connection_end(&connection_id()).unwrap();
+		let client_state = ClientState::new(ctx.host_height());
+		let proof = CommitmentProofBytes::try_from(vec![0]).unwrap();
+		let verify = |channel_id: ChannelId, expected: &ErrorReceipt| {
+			LocalhostClient.verify_channel_upgrade_error(
+				&ctx,
+				&client_id(),
+				&client_state,
+				ctx.host_height(),
+				&connection_end,
+				&proof,
+				&CommitmentRoot::from_bytes(&[]),
+				&port_id,
+				&channel_id,
+				expected,
+			)
+		};
+		verify(ChannelId::new(0), &error_receipt).unwrap();
+		assert!(verify(ChannelId::new(0), &ErrorReceipt::new(2, "upgrade aborted".to_string()))
+			.is_err());
+		assert!(verify(ChannelId::new(1), &error_receipt).is_err());
+	}
+
+	#[test]
+	fn localhost_client_is_updated_to_the_host_height() {
+		let mut ctx = MockContext::<MockClientTypes>::default();
+		initialize(&mut ctx).unwrap();
+		ctx.advance_host_chain_height();
+
+		let msg = MsgUpdateAnyClient {
+			client_id: client_id(),
+			client_message: AnyClientMessage::Localhost(ClientMessage),
+			signer: get_dummy_account_id(),
+		};
+		let output = dispatch(&ctx, ClientMsg::UpdateClient(msg)).unwrap();
+		match &output.result {
+			Update(result) => assert_eq!(
+				result.client_state,
+				AnyClientState::Localhost(ClientState::new(ctx.host_height()))
+			),
+			_ => panic!("update handler result has incorrect type"),
+		}
+
+		ctx.store_client_result(output.result).unwrap();
+		assert!(ctx.consensus_state(&client_id(), ctx.host_height()).is_ok());
+	}
+}
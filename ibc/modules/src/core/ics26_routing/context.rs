@@ -180,7 +180,67 @@ pub trait Module: Send + Sync + AsAnyMut {
 		Ok(())
 	}
 
-	/// Modules can choose to write acknowledgement to storage in this callback
+	/// Called when this chain proposes an upgrade of the channel. Returns the version the module
+	/// agrees to upgrade to. Modules have to opt in to channel upgrades, so the default
+	/// implementation rejects the upgrade.
+	#[allow(clippy::too_many_arguments)]
+	fn on_chan_upgrade_init(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_version: &Version,
+	) -> Result<Version, Error> {
+		Err(Error::app_module("channel upgrades are not supported by this module".to_string()))
+	}
+
+	/// Called when the counterparty proposed an upgrade of the channel. Returns the version the
+	/// module agrees to upgrade to, which must be compatible with `counterparty_version`.
+	#[allow(clippy::too_many_arguments)]
+	fn on_chan_upgrade_try(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_counterparty_version: &Version,
+	) -> Result<Version, Error> {
+		Err(Error::app_module("channel upgrades are not supported by this module".to_string()))
+	}
+
+	fn on_chan_upgrade_ack(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_counterparty_version: &Version,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+
+	/// Called once the upgrade has been applied to the channel end and the channel is open again.
+	#[allow(clippy::too_many_arguments)]
+	fn on_chan_upgrade_open(
+		&mut self,
+		_ctx: &dyn ModuleCallbackContext,
+		_output: &mut ModuleOutputBuilder,
+		_port_id: &PortId,
+		_channel_id: &ChannelId,
+		_order: Order,
+		_connection_hops: &[ConnectionId],
+		_version: &Version,
+	) -> Result<(), Error> {
+		Ok(())
+	}
+
+	/// Modules can choose to write acknowledgement to storage in this callback, an empty
+	/// acknowledgement defers writing it until the module is able to
 	fn on_recv_packet(
 		&self,
 		_ctx: &dyn ModuleCallbackContext,
@@ -191,6 +251,19 @@ pub trait Module: Send + Sync + AsAnyMut {
 		Ok(GenericAcknowledgement::from_bytes(Default::default()))
 	}
 
+	/// Called when a module writes the acknowledgement it deferred in `on_recv_packet`, so that
+	/// middlewares can wrap the acknowledgement as they would have in `on_recv_packet`. Returns
+	/// the acknowledgement to write.
+	fn on_write_acknowledgement(
+		&self,
+		_ctx: &dyn ModuleCallbackContext,
+		_packet: &Packet,
+		acknowledgement: GenericAcknowledgement,
+		_relayer: &Signer,
+	) -> Result<GenericAcknowledgement, Error> {
+		Ok(acknowledgement)
+	}
+
 	fn on_acknowledgement_packet(
 		&mut self,
 		_ctx: &dyn ModuleCallbackContext,
@@ -16,7 +16,7 @@ use crate::{
 	core::{
 		ics02_client::{
 			context::{ClientKeeper, ClientTypes},
-			handler::dispatch as ics2_msg_dispatcher,
+			handler::{dispatch as ics2_msg_dispatcher, ClientResult},
 		},
 		ics03_connection::handler::dispatch as ics3_msg_dispatcher,
 		ics04_channel::{
@@ -100,10 +100,14 @@ where
 		},
 
 		Ics3Msg(msg) => {
-			let handler_output =
+			let mut handler_output =
 				ics3_msg_dispatcher::<_>(ctx, msg).map_err(Error::ics03_connection)?;
 
 			// Apply any results to the host chain store.
+			if let Some(client_update) = handler_output.result.client_update.take() {
+				ctx.store_client_result(ClientResult::Update(client_update))
+					.map_err(Error::ics02_client)?;
+			}
 			ctx.store_connection_result(handler_output.result)
 				.map_err(Error::ics03_connection)?;
 
@@ -122,9 +126,13 @@ where
 			let cb_result =
 				ics4_callback(ctx, &module_id, &msg, channel_result, &mut module_output);
 			handler_builder.merge(module_output);
-			let channel_result = cb_result.map_err(Error::ics04_channel)?;
+			let mut channel_result = cb_result.map_err(Error::ics04_channel)?;
 
 			// Apply any results to the host chain store.
+			if let Some(client_update) = channel_result.client_update.take() {
+				ctx.store_client_result(ClientResult::Update(client_update))
+					.map_err(Error::ics02_client)?;
+			}
 			ctx.store_channel_result(channel_result).map_err(Error::ics04_channel)?;
 
 			handler_builder.with_result(())
@@ -132,7 +140,7 @@ where
 
 		Ics4PacketMsg(msg) => {
 			let module_id = get_module_for_packet_msg(ctx, &msg).map_err(Error::ics04_channel)?;
-			let (mut handler_builder, packet_result) =
+			let (mut handler_builder, mut packet_result) =
 				ics4_packet_msg_dispatcher::<_>(ctx, &msg).map_err(Error::ics04_channel)?;
 
 			if matches!(packet_result, PacketResult::Recv(RecvPacketResult::NoOp)) {
@@ -145,6 +153,10 @@ where
 			cb_result.map_err(Error::ics04_channel)?;
 
 			// Apply any results to the host chain store.
+			if let Some(client_update) = packet_result.take_client_update() {
+				ctx.store_client_result(ClientResult::Update(client_update))
+					.map_err(Error::ics02_client)?;
+			}
 			ctx.store_packet_result(packet_result).map_err(Error::ics04_channel)?;
 
 			handler_builder.with_result(())
@@ -29,8 +29,9 @@ use crate::core::{
 	},
 	ics04_channel::msgs::{
 		acknowledgement, chan_close_confirm, chan_close_init, chan_open_ack, chan_open_confirm,
-		chan_open_init, chan_open_try, recv_packet, timeout, timeout_on_close, ChannelMsg,
-		PacketMsg,
+		chan_open_init, chan_open_try, chan_upgrade_ack, chan_upgrade_cancel, chan_upgrade_confirm,
+		chan_upgrade_init, chan_upgrade_open, chan_upgrade_timeout, chan_upgrade_try, recv_packet,
+		timeout, timeout_on_close, ChannelMsg, PacketMsg,
 	},
 	ics26_routing::error::Error,
 };
@@ -156,6 +157,46 @@ where
 						.map_err(Error::malformed_message_bytes)?;
 				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelCloseConfirm(domain_msg)))
 			},
+			chan_upgrade_init::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_init::MsgChannelUpgradeInit::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelUpgradeInit(domain_msg)))
+			},
+			chan_upgrade_try::TYPE_URL => {
+				let domain_msg = chan_upgrade_try::MsgChannelUpgradeTry::decode_vec(&any_msg.value)
+					.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelUpgradeTry(domain_msg)))
+			},
+			chan_upgrade_ack::TYPE_URL => {
+				let domain_msg = chan_upgrade_ack::MsgChannelUpgradeAck::decode_vec(&any_msg.value)
+					.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelUpgradeAck(domain_msg)))
+			},
+			chan_upgrade_confirm::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_confirm::MsgChannelUpgradeConfirm::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelUpgradeConfirm(domain_msg)))
+			},
+			chan_upgrade_open::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_open::MsgChannelUpgradeOpen::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelUpgradeOpen(domain_msg)))
+			},
+			chan_upgrade_timeout::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_timeout::MsgChannelUpgradeTimeout::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelUpgradeTimeout(domain_msg)))
+			},
+			chan_upgrade_cancel::TYPE_URL => {
+				let domain_msg =
+					chan_upgrade_cancel::MsgChannelUpgradeCancel::decode_vec(&any_msg.value)
+						.map_err(Error::malformed_message_bytes)?;
+				Ok(Ics26Envelope::Ics4ChannelMsg(ChannelMsg::ChannelUpgradeCancel(domain_msg)))
+			},
 			// ICS04 packet messages
 			recv_packet::TYPE_URL => {
 				let domain_msg = recv_packet::MsgRecvPacket::decode_vec(&any_msg.value)
@@ -19,6 +19,7 @@ pub mod ics02_client;
 pub mod ics03_connection;
 pub mod ics04_channel;
 pub mod ics05_port;
+pub mod ics09_localhost;
 pub mod ics23_commitment;
 pub mod ics24_host;
 pub mod ics26_routing;
@@ -30,6 +30,12 @@ use crate::{
 			context::ClientTypes,
 			error::{Error as Ics02Error, Error},
 		},
+		ics09_localhost::{
+			client_state::{ClientState as LocalhostClientState, LOCALHOST_CLIENT_STATE_TYPE_URL},
+			consensus_state::{
+				ConsensusState as LocalhostConsensusState, LOCALHOST_CONSENSUS_STATE_TYPE_URL,
+			},
+		},
 		ics23_commitment::commitment::CommitmentRoot,
 		ics24_host::identifier::ChainId,
 	},
@@ -66,6 +72,7 @@ pub struct MockClientRecord<C: ClientTypes> {
 #[serde(tag = "type")]
 pub enum AnyUpgradeOptions {
 	Mock(()),
+	Localhost(()),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, ClientState, Protobuf)]
@@ -73,6 +80,8 @@ pub enum AnyUpgradeOptions {
 pub enum AnyClientState {
 	#[ibc(proto_url = "MOCK_CLIENT_STATE_TYPE_URL")]
 	Mock(MockClientState),
+	#[ibc(proto_url = "LOCALHOST_CLIENT_STATE_TYPE_URL")]
+	Localhost(LocalhostClientState),
 }
 
 /// A mock of a client state. For an example of a real structure that this mocks, you can see
@@ -201,6 +210,8 @@ pub const MOCK_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.mock.ConsensusState";
 pub enum AnyConsensusState {
 	#[ibc(proto_url = "MOCK_CONSENSUS_STATE_TYPE_URL")]
 	Mock(MockConsensusState),
+	#[ibc(proto_url = "LOCALHOST_CONSENSUS_STATE_TYPE_URL")]
+	Localhost(LocalhostConsensusState),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
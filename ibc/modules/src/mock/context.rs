@@ -49,6 +49,7 @@ use crate::{
 			error::Error as Ics04Error,
 			msgs::acknowledgement::Acknowledgement,
 			packet::{Receipt, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics05_port::{
 			context::PortReader,
@@ -314,6 +315,35 @@ where
 		self
 	}
 
+	/// Associates an in-progress upgrade to the given channel of this context.
+	pub fn with_channel_upgrade(
+		self,
+		port_id: PortId,
+		chan_id: ChannelId,
+		upgrade: Upgrade,
+	) -> Self {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.channel_upgrades
+			.insert((port_id, chan_id), upgrade);
+		self
+	}
+
+	pub fn with_counterparty_upgrade(
+		self,
+		port_id: PortId,
+		chan_id: ChannelId,
+		upgrade: Upgrade,
+	) -> Self {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.counterparty_upgrades
+			.insert((port_id, chan_id), upgrade);
+		self
+	}
+
 	pub fn with_router(self, router: MockRouter) -> Self {
 		Self { router, ..self }
 	}
@@ -542,6 +572,15 @@ pub struct MockIbcStore<C: HostBlockType> {
 
 	// Used by unordered channel
 	pub packet_receipt: BTreeMap<(PortId, ChannelId, Sequence), Receipt>,
+
+	/// Upgrades proposed for channels with an upgrade handshake in progress.
+	pub channel_upgrades: BTreeMap<(PortId, ChannelId), Upgrade>,
+
+	/// Counterparty upgrades, stored once the channel started flushing.
+	pub counterparty_upgrades: BTreeMap<(PortId, ChannelId), Upgrade>,
+
+	/// Receipts of the aborted channel upgrades.
+	pub upgrade_error_receipts: BTreeMap<(PortId, ChannelId), ErrorReceipt>,
 }
 
 #[derive(Default)]
@@ -704,6 +743,57 @@ impl<C: HostBlockType> ChannelReader for MockContext<C> {
 		}
 	}
 
+	fn channel_upgrade(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, Ics04Error> {
+		match self.ibc_store.lock().unwrap().channel_upgrades.get(port_channel_id) {
+			Some(upgrade) => Ok(upgrade.clone()),
+			None =>
+				Err(Ics04Error::upgrade_not_found(port_channel_id.0.clone(), port_channel_id.1)),
+		}
+	}
+
+	fn counterparty_upgrade(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, Ics04Error> {
+		match self.ibc_store.lock().unwrap().counterparty_upgrades.get(port_channel_id) {
+			Some(upgrade) => Ok(upgrade.clone()),
+			None =>
+				Err(Ics04Error::upgrade_not_found(port_channel_id.0.clone(), port_channel_id.1)),
+		}
+	}
+
+	fn upgrade_error_receipt(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, Ics04Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.upgrade_error_receipts
+			.get(port_channel_id)
+			.cloned()
+			.ok_or_else(Ics04Error::missing_error_receipt)
+	}
+
+	fn inflight_packet_count(
+		&self,
+		port_channel_id: &(PortId, ChannelId),
+	) -> Result<u64, Ics04Error> {
+		Ok(self
+			.ibc_store
+			.lock()
+			.unwrap()
+			.packet_commitment
+			.keys()
+			.filter(|(port_id, channel_id, _)| {
+				port_id == &port_channel_id.0 && channel_id == &port_channel_id.1
+			})
+			.count() as u64)
+	}
+
 	fn hash(&self, value: Vec<u8>) -> Vec<u8> {
 		sha2::Sha256::digest(value).to_vec()
 	}
@@ -837,6 +927,61 @@ impl<C: HostBlockType> ChannelKeeper for MockContext<C> {
 		Ok(())
 	}
 
+	fn store_channel_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: &Upgrade,
+	) -> Result<(), Ics04Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.channel_upgrades
+			.insert(port_channel_id, upgrade.clone());
+		Ok(())
+	}
+
+	fn delete_channel_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+	) -> Result<(), Ics04Error> {
+		self.ibc_store.lock().unwrap().channel_upgrades.remove(&port_channel_id);
+		Ok(())
+	}
+
+	fn store_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		upgrade: &Upgrade,
+	) -> Result<(), Ics04Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.counterparty_upgrades
+			.insert(port_channel_id, upgrade.clone());
+		Ok(())
+	}
+
+	fn delete_counterparty_upgrade(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+	) -> Result<(), Ics04Error> {
+		self.ibc_store.lock().unwrap().counterparty_upgrades.remove(&port_channel_id);
+		Ok(())
+	}
+
+	fn store_upgrade_error_receipt(
+		&mut self,
+		port_channel_id: (PortId, ChannelId),
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), Ics04Error> {
+		self.ibc_store
+			.lock()
+			.unwrap()
+			.upgrade_error_receipts
+			.insert(port_channel_id, error_receipt.clone());
+		Ok(())
+	}
+
 	fn increase_channel_counter(&mut self) {
 		self.ibc_store.lock().unwrap().channel_ids_counter += 1;
 	}
@@ -1065,6 +1210,16 @@ where
 		}
 	}
 
+	fn decode_client_state(&self, value: &[u8]) -> Result<C::AnyClientState, Ics02Error> {
+		let any = <Any as prost::Message>::decode(value).map_err(Ics02Error::decode)?;
+		C::AnyClientState::try_from(any)
+	}
+
+	fn decode_consensus_state(&self, value: &[u8]) -> Result<C::AnyConsensusState, Ics02Error> {
+		let any = <Any as prost::Message>::decode(value).map_err(Ics02Error::decode)?;
+		C::AnyConsensusState::try_from(any)
+	}
+
 	fn client_counter(&self) -> Result<u64, Ics02Error> {
 		Ok(self.ibc_store.lock().unwrap().client_ids_counter)
 	}
@@ -19,7 +19,12 @@ use tendermint_proto::Protobuf;
 use ibc_proto::ibc::mock::Header as RawMockHeader;
 
 use crate::{
-	core::ics02_client::{client_message::ClientMessage, error::Error},
+	core::{
+		ics02_client::{client_message::ClientMessage, error::Error},
+		ics09_localhost::client_message::{
+			ClientMessage as LocalhostClientMessage, LOCALHOST_CLIENT_MESSAGE_TYPE_URL,
+		},
+	},
 	mock::{
 		client_state::{AnyConsensusState, MockConsensusState},
 		host::{HostBlock, MockHostBlock},
@@ -36,6 +41,8 @@ pub const MOCK_HEADER_TYPE_URL: &str = "/ibc.mock.Header";
 pub enum AnyClientMessage {
 	#[ibc(proto_url = "MOCK_HEADER_TYPE_URL")]
 	Mock(MockClientMessage),
+	#[ibc(proto_url = "LOCALHOST_CLIENT_MESSAGE_TYPE_URL")]
+	Localhost(LocalhostClientMessage),
 }
 
 impl From<MockHostBlock> for AnyClientMessage {
@@ -92,6 +99,7 @@ impl TryFrom<Any> for AnyClientMessage {
 				MockMisbehaviour::decode_vec(&value.value)
 					.map_err(Error::decode_raw_misbehaviour)?,
 			))),
+			LOCALHOST_CLIENT_MESSAGE_TYPE_URL => Ok(Self::Localhost(LocalhostClientMessage)),
 			_ => Err(Error::unknown_consensus_state_type(value.type_url)),
 		}
 	}
@@ -108,6 +116,7 @@ impl From<AnyClientMessage> for Any {
 				type_url: MOCK_MISBEHAVIOUR_TYPE_URL.to_string(),
 				value: misbehaviour.encode_vec().unwrap(),
 			},
+			AnyClientMessage::Localhost(msg) => msg.into(),
 		}
 	}
 }
@@ -38,6 +38,7 @@ use crate::{
 			context::{ChannelKeeper, ChannelReader},
 			error::Error,
 			packet::{Receipt, Sequence},
+			upgrade::{ErrorReceipt, Upgrade},
 			Version,
 		},
 		ics05_port::{context::PortReader, error::Error as PortError},
@@ -348,6 +349,44 @@ impl<C: HostBlockType> ChannelKeeper for DummyTransferModule<C> {
 		unimplemented!()
 	}
 
+	fn store_channel_upgrade(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+		_upgrade: &Upgrade,
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
+	fn delete_channel_upgrade(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
+	fn store_counterparty_upgrade(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+		_upgrade: &Upgrade,
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
+	fn delete_counterparty_upgrade(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
+	fn store_upgrade_error_receipt(
+		&mut self,
+		_port_channel_id: (PortId, ChannelId),
+		_error_receipt: &ErrorReceipt,
+	) -> Result<(), Error> {
+		unimplemented!()
+	}
+
 	fn increase_channel_counter(&mut self) {
 		unimplemented!()
 	}
@@ -476,6 +515,14 @@ impl<C: HostBlockType> ClientReader for DummyTransferModule<C> {
 		unimplemented!()
 	}
 
+	fn decode_client_state(&self, _value: &[u8]) -> Result<Self::AnyClientState, Ics02Error> {
+		unimplemented!()
+	}
+
+	fn decode_consensus_state(&self, _value: &[u8]) -> Result<Self::AnyConsensusState, Ics02Error> {
+		unimplemented!()
+	}
+
 	fn consensus_state(
 		&self,
 		client_id: &ClientId,
@@ -577,6 +624,28 @@ impl<C: HostBlockType> ChannelReader for DummyTransferModule<C> {
 		unimplemented!()
 	}
 
+	fn channel_upgrade(&self, _port_channel_id: &(PortId, ChannelId)) -> Result<Upgrade, Error> {
+		unimplemented!()
+	}
+
+	fn counterparty_upgrade(
+		&self,
+		_port_channel_id: &(PortId, ChannelId),
+	) -> Result<Upgrade, Error> {
+		unimplemented!()
+	}
+
+	fn upgrade_error_receipt(
+		&self,
+		_port_channel_id: &(PortId, ChannelId),
+	) -> Result<ErrorReceipt, Error> {
+		unimplemented!()
+	}
+
+	fn inflight_packet_count(&self, _port_channel_id: &(PortId, ChannelId)) -> Result<u64, Error> {
+		unimplemented!()
+	}
+
 	fn hash(&self, value: Vec<u8>) -> Vec<u8> {
 		use sha2::Digest;
 
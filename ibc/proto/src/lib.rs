@@ -149,6 +149,11 @@ pub mod ibc {
 		pub use super::applications::*;
 	}
 	pub mod applications {
+		pub mod fee {
+			pub mod v1 {
+				include_proto!("ibc.applications.fee.v1.rs");
+			}
+		}
 		pub mod transfer {
 			pub mod v1 {
 				include_proto!("ibc.applications.transfer.v1.rs");
@@ -201,6 +206,11 @@ pub mod ibc {
 		}
 	}
 	pub mod lightclients {
+		pub mod localhost {
+			pub mod v2 {
+				include_proto!("ibc.lightclients.localhost.v2.rs");
+			}
+		}
 		pub mod solomachine {
 			pub mod v1 {
 				include_proto!("ibc.lightclients.solomachine.v3.rs");
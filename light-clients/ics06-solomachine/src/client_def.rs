@@ -0,0 +1,524 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::{fmt::Debug, marker::PhantomData};
+
+use ibc::core::{
+	ics02_client::{
+		client_def::{ClientDef, ConsensusUpdateResult},
+		error::Error as Ics02Error,
+	},
+	ics03_connection::connection::ConnectionEnd,
+	ics04_channel::{
+		channel::ChannelEnd,
+		commitment::{AcknowledgementCommitment, PacketCommitment},
+		packet::Sequence,
+		upgrade::{ErrorReceipt, Upgrade},
+	},
+	ics23_commitment::{
+		commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+		merkle::apply_prefix,
+	},
+	ics24_host::{
+		identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		path::{
+			AcksPath, ChannelEndsPath, ChannelUpgradeErrorsPath, ChannelUpgradesPath,
+			ClientConsensusStatePath, ClientStatePath, CommitmentsPath, ConnectionsPath,
+			ReceiptsPath, SeqRecvsPath,
+		},
+		Path,
+	},
+	ics26_routing::context::ReaderContext,
+};
+use ibc_proto::{
+	cosmos::tx::signing::v1beta1::signature_descriptor::{
+		data::{Single, Sum},
+		Data as SignatureData,
+	},
+	ibc::lightclients::solomachine::v1::{HeaderData, SignBytes, TimestampedSignatureData},
+};
+use prost::Message;
+use tendermint_proto::Protobuf;
+
+use crate::{
+	client_message::{ClientMessage, Header, Misbehaviour, SignatureAndData},
+	client_state::ClientState,
+	consensus_state::{ConsensusState, PublicKey},
+	error::Error,
+	HostFunctionsProvider,
+};
+use ibc::{prelude::*, Height};
+
+/// Path the solo machine signs over when it submits a header.
+pub const SENTINEL_HEADER_PATH: &str = "solomachine:header";
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct SolomachineClient<H>(PhantomData<H>);
+
+impl<H> ClientDef for SolomachineClient<H>
+where
+	H: HostFunctionsProvider,
+{
+	type ClientMessage = ClientMessage;
+	type ClientState = ClientState<H>;
+	type ConsensusState = ConsensusState;
+
+	fn verify_client_message<Ctx>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		message: Self::ClientMessage,
+	) -> Result<(), Ics02Error>
+	where
+		Ctx: ReaderContext,
+	{
+		let consensus_state = &client_state.consensus_state;
+		match message {
+			ClientMessage::Header(header) => {
+				if header.timestamp < consensus_state.timestamp {
+					return Err(Error::timestamp_too_old(
+						header.timestamp,
+						consensus_state.timestamp,
+					)
+					.into())
+				}
+				let sign_bytes = header_sign_bytes(&client_state, &header);
+				verify_signature::<H>(&consensus_state.public_key, &sign_bytes, &header.signature)?;
+			},
+			ClientMessage::Misbehaviour(misbehaviour) => {
+				let Misbehaviour { sequence, signature_one, signature_two } = misbehaviour;
+				if signature_one.path == signature_two.path &&
+					signature_one.data == signature_two.data
+				{
+					return Err(Error::invalid_misbehaviour(
+						"both signatures are over the same data".to_string(),
+					)
+					.into())
+				}
+				verify_signature_and_data::<H>(consensus_state, sequence, signature_one)?;
+				verify_signature_and_data::<H>(consensus_state, sequence, signature_two)?;
+			},
+		};
+
+		Ok(())
+	}
+
+	fn update_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		let header = match client_message {
+			ClientMessage::Header(header) => header,
+			_ => unreachable!("02-client will check for Header before calling update_state; qed"),
+		};
+		let client_state = client_state.with_header(header);
+		let cs = Ctx::AnyConsensusState::wrap(&client_state.consensus_state).ok_or_else(|| {
+			Ics02Error::unknown_consensus_state_type("Ctx::AnyConsensusState".to_string())
+		})?;
+		Ok((client_state, ConsensusUpdateResult::Single(cs)))
+	}
+
+	fn update_state_on_misbehaviour(
+		&self,
+		client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
+	) -> Result<Self::ClientState, Ics02Error> {
+		match client_message {
+			ClientMessage::Misbehaviour(_) => {},
+			_ => unreachable!(
+				"02-client will check for misbehaviour before calling update_state_on_misbehaviour; qed"
+			),
+		};
+		Ok(client_state.frozen())
+	}
+
+	fn check_for_misbehaviour<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		_client_state: Self::ClientState,
+		message: Self::ClientMessage,
+	) -> Result<bool, Ics02Error> {
+		// a verified misbehaviour is always a double signature, headers and proofs can't conflict
+		// since every one of them consumes the sequence it was signed for
+		Ok(matches!(message, ClientMessage::Misbehaviour(_)))
+	}
+
+	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: ClientId,
+		_old_client_state: &Self::ClientState,
+		_upgrade_client_state: &Self::ClientState,
+		_upgrade_consensus_state: &Self::ConsensusState,
+		_proof_upgrade_client: Vec<u8>,
+		_proof_upgrade_consensus_state: Vec<u8>,
+	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
+		Err(Error::upgrade_not_supported().into())
+	}
+
+	fn verify_client_consensus_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		client_id: &ClientId,
+		consensus_height: Height,
+		expected_consensus_state: &Ctx::AnyConsensusState,
+	) -> Result<(), Ics02Error> {
+		let path = ClientConsensusStatePath {
+			client_id: client_id.clone(),
+			epoch: consensus_height.revision_number,
+			height: consensus_height.revision_height,
+		};
+		let value = expected_consensus_state.encode_to_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H, _>(client_state, height, prefix, proof, path, value)
+	}
+
+	fn verify_connection_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		connection_id: &ConnectionId,
+		expected_connection_end: &ConnectionEnd,
+	) -> Result<(), Ics02Error> {
+		let path = ConnectionsPath(connection_id.clone());
+		let value = expected_connection_end.encode_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H, _>(client_state, height, prefix, proof, path, value)
+	}
+
+	fn verify_channel_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		expected_channel_end: &ChannelEnd,
+	) -> Result<(), Ics02Error> {
+		let path = ChannelEndsPath(port_id.clone(), *channel_id);
+		let value = expected_channel_end.encode_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H, _>(client_state, height, prefix, proof, path, value)
+	}
+
+	fn verify_client_full_state<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		client_id: &ClientId,
+		expected_client_state: &Ctx::AnyClientState,
+	) -> Result<(), Ics02Error> {
+		let path = ClientStatePath(client_id.clone());
+		let value = expected_client_state.encode_to_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H, _>(client_state, height, prefix, proof, path, value)
+	}
+
+	fn verify_packet_data<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		commitment: PacketCommitment,
+	) -> Result<(), Ics02Error> {
+		let commitment_path =
+			CommitmentsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		verify_membership::<H, _>(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			commitment_path,
+			commitment.into_vec(),
+		)
+	}
+
+	fn verify_packet_acknowledgement<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		ack_commitment: AcknowledgementCommitment,
+	) -> Result<(), Ics02Error> {
+		let ack_path = AcksPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		verify_membership::<H, _>(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			ack_path,
+			ack_commitment.into_vec(),
+		)
+	}
+
+	fn verify_next_sequence_recv<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		let mut seq_bytes = Vec::new();
+		u64::from(sequence).encode(&mut seq_bytes).expect("buffer size too small");
+
+		let seq_path = SeqRecvsPath(port_id.clone(), *channel_id);
+		verify_membership::<H, _>(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			seq_path,
+			seq_bytes,
+		)
+	}
+
+	fn verify_packet_receipt_absence<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Ics02Error> {
+		let receipt_path =
+			ReceiptsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		// absence is proven by a signature over empty data
+		verify_membership::<H, _>(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			receipt_path,
+			vec![],
+		)
+	}
+
+	fn verify_channel_upgrade<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		upgrade: &Upgrade,
+	) -> Result<(), Ics02Error> {
+		let path = ChannelUpgradesPath(port_id.clone(), *channel_id);
+		let value = upgrade.encode_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H, _>(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			path,
+			value,
+		)
+	}
+
+	fn verify_channel_upgrade_error<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		_root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), Ics02Error> {
+		let path = ChannelUpgradeErrorsPath(port_id.clone(), *channel_id);
+		let value = error_receipt.encode_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H, _>(
+			client_state,
+			height,
+			connection_end.counterparty().prefix(),
+			proof,
+			path,
+			value,
+		)
+	}
+
+	fn update_state_on_proof(
+		&self,
+		client_state: &Self::ClientState,
+		proof: &CommitmentProofBytes,
+	) -> Result<Option<(Self::ClientState, Self::ConsensusState)>, Ics02Error> {
+		// the proof was signed at the current sequence, which must not be signed at again
+		let proof = TimestampedSignatureData::decode(proof.as_bytes())
+			.map_err(|e| Error::decode(e.to_string()))?;
+		let client_state = client_state.clone().with_proof(proof.timestamp);
+		let consensus_state = client_state.consensus_state.clone();
+		Ok(Some((client_state, consensus_state)))
+	}
+}
+
+/// Bytes the solo machine signs to rotate to the key and diversifier of `header`.
+pub fn header_sign_bytes<H: Clone>(client_state: &ClientState<H>, header: &Header) -> Vec<u8> {
+	let data = HeaderData {
+		new_pub_key: Some(header.new_public_key.clone().into()),
+		new_diversifier: header.new_diversifier.clone(),
+	};
+	SignBytes {
+		sequence: client_state.sequence,
+		timestamp: header.timestamp,
+		diversifier: client_state.consensus_state.diversifier.clone(),
+		path: SENTINEL_HEADER_PATH.as_bytes().to_vec(),
+		data: data.encode_to_vec(),
+	}
+	.encode_to_vec()
+}
+
+/// Bytes the solo machine signs to prove that `value` is stored at `path` of its state.
+pub fn proof_sign_bytes<P: Into<Path>>(
+	sequence: u64,
+	timestamp: u64,
+	diversifier: String,
+	prefix: &CommitmentPrefix,
+	path: P,
+	value: Vec<u8>,
+) -> Vec<u8> {
+	let merkle_path = apply_prefix(prefix, vec![path.into().to_string()]);
+	SignBytes { sequence, timestamp, diversifier, path: merkle_path.encode_to_vec(), data: value }
+		.encode_to_vec()
+}
+
+fn verify_membership<H, P>(
+	client_state: &ClientState<H>,
+	height: Height,
+	prefix: &CommitmentPrefix,
+	proof: &CommitmentProofBytes,
+	path: P,
+	value: Vec<u8>,
+) -> Result<(), Ics02Error>
+where
+	P: Into<Path>,
+	H: HostFunctionsProvider,
+{
+	client_state.verify_height(height)?;
+
+	let proof = TimestampedSignatureData::decode(proof.as_bytes())
+		.map_err(|e| Error::decode(e.to_string()))?;
+	let consensus_state = &client_state.consensus_state;
+	if proof.timestamp < consensus_state.timestamp {
+		return Err(Error::timestamp_too_old(proof.timestamp, consensus_state.timestamp).into())
+	}
+
+	let sign_bytes = proof_sign_bytes(
+		client_state.sequence,
+		proof.timestamp,
+		consensus_state.diversifier.clone(),
+		prefix,
+		path,
+		value,
+	);
+	verify_signature::<H>(&consensus_state.public_key, &sign_bytes, &proof.signature_data)
+		.map_err(|e| e.into())
+}
+
+fn verify_signature_and_data<H: HostFunctionsProvider>(
+	consensus_state: &ConsensusState,
+	sequence: u64,
+	signature_and_data: SignatureAndData,
+) -> Result<(), Error> {
+	if signature_and_data.timestamp < consensus_state.timestamp {
+		return Err(Error::timestamp_too_old(
+			signature_and_data.timestamp,
+			consensus_state.timestamp,
+		))
+	}
+	let sign_bytes = SignBytes {
+		sequence,
+		timestamp: signature_and_data.timestamp,
+		diversifier: consensus_state.diversifier.clone(),
+		path: signature_and_data.path,
+		data: signature_and_data.data,
+	}
+	.encode_to_vec();
+	verify_signature::<H>(&consensus_state.public_key, &sign_bytes, &signature_and_data.signature)
+}
+
+/// Verifies a protobuf encoded `SignatureDescriptor.Data` over `msg`, only single signatures
+/// are supported.
+fn verify_signature<H: HostFunctionsProvider>(
+	public_key: &PublicKey,
+	msg: &[u8],
+	signature_data: &[u8],
+) -> Result<(), Error> {
+	let signature_data =
+		SignatureData::decode(signature_data).map_err(|e| Error::decode(e.to_string()))?;
+	let signature = match signature_data.sum {
+		Some(Sum::Single(Single { signature, .. })) => signature,
+		_ => return Err(Error::unsupported_signature()),
+	};
+	let signature: &[u8; 64] =
+		signature.as_slice().try_into().map_err(|_| Error::invalid_signature())?;
+
+	let valid = match public_key {
+		PublicKey::Ed25519(key) => H::ed25519_verify(signature, msg, key),
+		PublicKey::Secp256k1(key) => H::secp256k1_verify(signature, msg, key),
+	};
+	if !valid {
+		return Err(Error::invalid_signature())
+	}
+	Ok(())
+}
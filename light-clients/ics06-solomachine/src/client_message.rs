@@ -0,0 +1,186 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::prelude::*;
+
+use ibc_proto::{
+	google::protobuf::Any,
+	ibc::lightclients::solomachine::v1::{
+		Header as RawHeader, Misbehaviour as RawMisbehaviour,
+		SignatureAndData as RawSignatureAndData,
+	},
+};
+use tendermint_proto::Protobuf;
+
+use crate::{consensus_state::PublicKey, error::Error};
+use ibc::{core::ics02_client, Height};
+
+pub const SOLOMACHINE_CLIENT_MESSAGE_TYPE_URL: &str =
+	"/ibc.lightclients.solomachine.v3.ClientMessage";
+pub const SOLOMACHINE_HEADER_TYPE_URL: &str = "/ibc.lightclients.solomachine.v3.Header";
+pub const SOLOMACHINE_MISBEHAVIOUR_TYPE_URL: &str = "/ibc.lightclients.solomachine.v3.Misbehaviour";
+
+/// Rotates the public key and diversifier of the solo machine, signed with the current key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+	pub timestamp: u64,
+	pub signature: Vec<u8>,
+	pub new_public_key: PublicKey,
+	pub new_diversifier: String,
+}
+
+/// A signature of the solo machine over `data` at `path`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureAndData {
+	pub signature: Vec<u8>,
+	pub path: Vec<u8>,
+	pub data: Vec<u8>,
+	pub timestamp: u64,
+}
+
+/// Two different messages signed by the solo machine for the same sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Misbehaviour {
+	pub sequence: u64,
+	pub signature_one: SignatureAndData,
+	pub signature_two: SignatureAndData,
+}
+
+impl Misbehaviour {
+	pub fn height(&self) -> Height {
+		Height::new(0, self.sequence)
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientMessage {
+	Header(Header),
+	Misbehaviour(Misbehaviour),
+}
+
+impl ics02_client::client_message::ClientMessage for ClientMessage {
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<Any> for ClientMessage {}
+
+impl TryFrom<Any> for ClientMessage {
+	type Error = Error;
+
+	fn try_from(any: Any) -> Result<Self, Self::Error> {
+		let msg = match &*any.type_url {
+			SOLOMACHINE_HEADER_TYPE_URL => Self::Header(
+				Header::decode(&*any.value).map_err(|e| Error::decode(format!("{e:?}")))?,
+			),
+			SOLOMACHINE_MISBEHAVIOUR_TYPE_URL => Self::Misbehaviour(
+				Misbehaviour::decode(&*any.value).map_err(|e| Error::decode(format!("{e:?}")))?,
+			),
+			_ => Err(Error::decode(format!("Unknown type: {}", any.type_url)))?,
+		};
+
+		Ok(msg)
+	}
+}
+
+impl From<ClientMessage> for Any {
+	fn from(msg: ClientMessage) -> Self {
+		match msg {
+			ClientMessage::Header(header) => Any {
+				value: header.encode_vec().expect("failed to encode ClientMessage.header"),
+				type_url: SOLOMACHINE_HEADER_TYPE_URL.to_string(),
+			},
+			ClientMessage::Misbehaviour(misbehaviour) => Any {
+				value: misbehaviour
+					.encode_vec()
+					.expect("failed to encode ClientMessage.misbehaviour"),
+				type_url: SOLOMACHINE_MISBEHAVIOUR_TYPE_URL.to_string(),
+			},
+		}
+	}
+}
+
+impl Protobuf<RawHeader> for Header {}
+
+impl TryFrom<RawHeader> for Header {
+	type Error = Error;
+
+	fn try_from(raw: RawHeader) -> Result<Self, Self::Error> {
+		Ok(Self {
+			timestamp: raw.timestamp,
+			signature: raw.signature,
+			new_public_key: raw.new_public_key.ok_or_else(Error::missing_public_key)?.try_into()?,
+			new_diversifier: raw.new_diversifier,
+		})
+	}
+}
+
+impl From<Header> for RawHeader {
+	fn from(value: Header) -> Self {
+		RawHeader {
+			timestamp: value.timestamp,
+			signature: value.signature,
+			new_public_key: Some(value.new_public_key.into()),
+			new_diversifier: value.new_diversifier,
+		}
+	}
+}
+
+impl From<RawSignatureAndData> for SignatureAndData {
+	fn from(raw: RawSignatureAndData) -> Self {
+		Self { signature: raw.signature, path: raw.path, data: raw.data, timestamp: raw.timestamp }
+	}
+}
+
+impl From<SignatureAndData> for RawSignatureAndData {
+	fn from(value: SignatureAndData) -> Self {
+		RawSignatureAndData {
+			signature: value.signature,
+			path: value.path,
+			data: value.data,
+			timestamp: value.timestamp,
+		}
+	}
+}
+
+impl Protobuf<RawMisbehaviour> for Misbehaviour {}
+
+impl TryFrom<RawMisbehaviour> for Misbehaviour {
+	type Error = Error;
+
+	fn try_from(raw: RawMisbehaviour) -> Result<Self, Self::Error> {
+		Ok(Self {
+			sequence: raw.sequence,
+			signature_one: raw
+				.signature_one
+				.ok_or_else(|| Error::invalid_misbehaviour("missing signature one".into()))?
+				.into(),
+			signature_two: raw
+				.signature_two
+				.ok_or_else(|| Error::invalid_misbehaviour("missing signature two".into()))?
+				.into(),
+		})
+	}
+}
+
+impl From<Misbehaviour> for RawMisbehaviour {
+	fn from(value: Misbehaviour) -> Self {
+		RawMisbehaviour {
+			sequence: value.sequence,
+			signature_one: Some(value.signature_one.into()),
+			signature_two: Some(value.signature_two.into()),
+		}
+	}
+}
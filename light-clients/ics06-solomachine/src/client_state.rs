@@ -0,0 +1,180 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::prelude::*;
+
+use core::{marker::PhantomData, time::Duration};
+
+use ibc_proto::ibc::lightclients::solomachine::v1::ClientState as RawClientState;
+use tendermint_proto::Protobuf;
+
+use crate::{
+	client_def::SolomachineClient, client_message::Header, consensus_state::ConsensusState,
+	error::Error, HostFunctionsProvider,
+};
+use ibc::{
+	core::{ics02_client::client_state::ClientType, ics24_host::identifier::ChainId},
+	Height,
+};
+
+pub const SOLOMACHINE_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.solomachine.v3.ClientState";
+
+/// Solo machine clients track a single signer. Every header and every proof is a signature
+/// over the current `sequence`, which doubles as the height of the client. A header or a verified
+/// proof consumes the sequence, so that no two honest signatures share one.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ClientState<H> {
+	/// Sequence the next header or proof must be signed at
+	pub sequence: u64,
+	/// Set once misbehaviour has been submitted for the solo machine
+	pub is_frozen: bool,
+	/// The key and diversifier currently used by the solo machine
+	pub consensus_state: ConsensusState,
+	pub _phantom: PhantomData<H>,
+}
+
+impl<H: Clone> Protobuf<RawClientState> for ClientState<H> {}
+
+impl<H: Clone> ClientState<H> {
+	pub fn new(sequence: u64, consensus_state: ConsensusState) -> Self {
+		Self { sequence, is_frozen: false, consensus_state, _phantom: PhantomData }
+	}
+
+	pub fn latest_height(&self) -> Height {
+		Height::new(0, self.sequence)
+	}
+
+	/// Rotates the solo machine to the key and diversifier of the header and consumes the
+	/// current sequence.
+	pub fn with_header(self, header: Header) -> Self {
+		ClientState {
+			sequence: self.sequence + 1,
+			consensus_state: ConsensusState::new(
+				header.new_public_key,
+				header.new_diversifier,
+				header.timestamp,
+			),
+			..self
+		}
+	}
+
+	/// Consumes the current sequence for a proof the solo machine signed at `timestamp`.
+	pub fn with_proof(self, timestamp: u64) -> Self {
+		ClientState {
+			sequence: self.sequence + 1,
+			consensus_state: ConsensusState { timestamp, ..self.consensus_state },
+			..self
+		}
+	}
+
+	pub fn frozen(self) -> Self {
+		Self { is_frozen: true, ..self }
+	}
+
+	pub fn chain_id(&self) -> ChainId {
+		ChainId::new("solomachine".to_string(), 0)
+	}
+
+	pub fn client_type() -> ClientType {
+		"06-solomachine".to_string()
+	}
+
+	pub fn frozen_height(&self) -> Option<Height> {
+		self.is_frozen.then(|| self.latest_height())
+	}
+
+	/// Check that the client is unfrozen and that `height` is a sequence the solo machine has
+	/// reached. The proofs of a message share its proof height while each of them is signed at
+	/// the sequence the previous one moved the client to.
+	pub fn verify_height(&self, height: Height) -> Result<(), Error> {
+		if self.is_frozen {
+			return Err(Error::client_frozen())
+		}
+		if height.revision_number != 0 || height.revision_height > self.sequence {
+			return Err(Error::invalid_proof_height(height, self.sequence))
+		}
+		Ok(())
+	}
+}
+
+impl<H> ibc::core::ics02_client::client_state::ClientState for ClientState<H>
+where
+	H: HostFunctionsProvider,
+{
+	type UpgradeOptions = ();
+	type ClientDef = SolomachineClient<H>;
+
+	fn chain_id(&self) -> ChainId {
+		self.chain_id()
+	}
+
+	fn client_def(&self) -> Self::ClientDef {
+		SolomachineClient::default()
+	}
+
+	fn client_type(&self) -> ClientType {
+		Self::client_type()
+	}
+
+	fn latest_height(&self) -> Height {
+		self.latest_height()
+	}
+
+	fn frozen_height(&self) -> Option<Height> {
+		self.frozen_height()
+	}
+
+	fn upgrade(
+		self,
+		_upgrade_height: Height,
+		_upgrade_options: Self::UpgradeOptions,
+		_chain_id: ChainId,
+	) -> Self {
+		self
+	}
+
+	fn expired(&self, _elapsed: Duration) -> bool {
+		// solo machines have no trusting period
+		false
+	}
+
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		self.encode_vec()
+	}
+}
+
+impl<H> TryFrom<RawClientState> for ClientState<H> {
+	type Error = Error;
+
+	fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
+		let consensus_state =
+			raw.consensus_state.ok_or_else(Error::missing_consensus_state)?.try_into()?;
+		Ok(Self {
+			sequence: raw.sequence,
+			is_frozen: raw.is_frozen,
+			consensus_state,
+			_phantom: PhantomData,
+		})
+	}
+}
+
+impl<H> From<ClientState<H>> for RawClientState {
+	fn from(value: ClientState<H>) -> Self {
+		RawClientState {
+			sequence: value.sequence,
+			is_frozen: value.is_frozen,
+			consensus_state: Some(value.consensus_state.into()),
+		}
+	}
+}
@@ -0,0 +1,126 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::prelude::*;
+
+use core::convert::Infallible;
+
+use ibc_proto::{
+	google::protobuf::Any, ibc::lightclients::solomachine::v1::ConsensusState as RawConsensusState,
+};
+use prost::Message;
+use tendermint_proto::Protobuf;
+
+use crate::error::Error;
+use ibc::{core::ics23_commitment::commitment::CommitmentRoot, timestamp::Timestamp};
+
+pub const SOLOMACHINE_CONSENSUS_STATE_TYPE_URL: &str =
+	"/ibc.lightclients.solomachine.v3.ConsensusState";
+pub const ED25519_PUBLIC_KEY_TYPE_URL: &str = "/cosmos.crypto.ed25519.PubKey";
+pub const SECP256K1_PUBLIC_KEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+
+/// Public key of a solo machine, encoded like the cosmos-sdk keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublicKey {
+	Ed25519(Vec<u8>),
+	Secp256k1(Vec<u8>),
+}
+
+/// Both `cosmos.crypto.ed25519.PubKey` and `cosmos.crypto.secp256k1.PubKey` hold the key bytes.
+#[derive(Clone, PartialEq, Message)]
+struct RawPublicKey {
+	#[prost(bytes = "vec", tag = "1")]
+	key: Vec<u8>,
+}
+
+impl TryFrom<Any> for PublicKey {
+	type Error = Error;
+
+	fn try_from(any: Any) -> Result<Self, Self::Error> {
+		let key = RawPublicKey::decode(any.value.as_slice())
+			.map_err(|e| Error::decode(e.to_string()))?
+			.key;
+		match any.type_url.as_str() {
+			ED25519_PUBLIC_KEY_TYPE_URL => Ok(Self::Ed25519(key)),
+			SECP256K1_PUBLIC_KEY_TYPE_URL => Ok(Self::Secp256k1(key)),
+			_ => Err(Error::unsupported_public_key(any.type_url)),
+		}
+	}
+}
+
+impl From<PublicKey> for Any {
+	fn from(public_key: PublicKey) -> Self {
+		let (type_url, key) = match public_key {
+			PublicKey::Ed25519(key) => (ED25519_PUBLIC_KEY_TYPE_URL, key),
+			PublicKey::Secp256k1(key) => (SECP256K1_PUBLIC_KEY_TYPE_URL, key),
+		};
+		Any { type_url: type_url.to_string(), value: RawPublicKey { key }.encode_to_vec() }
+	}
+}
+
+/// The public key and diversifier the solo machine signs with from `timestamp` on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusState {
+	pub public_key: PublicKey,
+	/// Arbitrary string the solo machine signs over, which lets it use the same key for
+	/// several clients
+	pub diversifier: String,
+	/// Timestamp of the solo machine in nanoseconds
+	pub timestamp: u64,
+	/// Solo machines don't commit to their state, the root is always empty
+	pub root: CommitmentRoot,
+}
+
+impl ConsensusState {
+	pub fn new(public_key: PublicKey, diversifier: String, timestamp: u64) -> Self {
+		Self { public_key, diversifier, timestamp, root: CommitmentRoot::from_bytes(&[]) }
+	}
+}
+
+impl ibc::core::ics02_client::client_consensus::ConsensusState for ConsensusState {
+	type Error = Infallible;
+
+	fn root(&self) -> &CommitmentRoot {
+		&self.root
+	}
+
+	fn timestamp(&self) -> Timestamp {
+		Timestamp::from_nanoseconds(self.timestamp).expect("timestamp is a valid u64; qed")
+	}
+
+	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<RawConsensusState> for ConsensusState {}
+
+impl TryFrom<RawConsensusState> for ConsensusState {
+	type Error = Error;
+
+	fn try_from(raw: RawConsensusState) -> Result<Self, Self::Error> {
+		let public_key = raw.public_key.ok_or_else(Error::missing_public_key)?.try_into()?;
+		Ok(Self::new(public_key, raw.diversifier, raw.timestamp))
+	}
+}
+
+impl From<ConsensusState> for RawConsensusState {
+	fn from(value: ConsensusState) -> Self {
+		RawConsensusState {
+			public_key: Some(value.public_key.into()),
+			diversifier: value.diversifier,
+			timestamp: value.timestamp,
+		}
+	}
+}
@@ -0,0 +1,79 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ibc::prelude::*;
+
+use flex_error::define_error;
+
+use crate::client_state::ClientState;
+use ibc::{core::ics02_client::error::Error as Ics02Error, Height};
+
+define_error! {
+	#[derive(Debug, PartialEq, Eq)]
+	Error {
+		Decode
+			{ reason: String }
+			|e| { format_args!("decoding error: {}", e.reason) },
+
+		MissingConsensusState
+			|_| { "missing consensus state" },
+
+		MissingPublicKey
+			|_| { "missing public key" },
+
+		UnsupportedPublicKey
+			{ type_url: String }
+			|e| { format_args!("unsupported public key type: {}", e.type_url) },
+
+		UnsupportedSignature
+			|_| { "only single signatures are supported" },
+
+		InvalidSignature
+			|_| { "invalid signature" },
+
+		ClientFrozen
+			|_| { "solo machine client is frozen" },
+
+		InvalidProofHeight
+			{ height: Height, sequence: u64 }
+			|e| {
+				format_args!(
+					"proof height {} is past the sequence {} of the solo machine",
+					e.height, e.sequence
+				)
+			},
+
+		TimestampTooOld
+			{ timestamp: u64, consensus_timestamp: u64 }
+			|e| {
+				format_args!(
+					"timestamp {} is older than the consensus state timestamp {}",
+					e.timestamp, e.consensus_timestamp
+				)
+			},
+
+		InvalidMisbehaviour
+			{ reason: String }
+			|e| { format_args!("invalid misbehaviour: {}", e.reason) },
+
+		UpgradeNotSupported
+			|_| { "solo machine clients can't be upgraded" },
+	}
+}
+
+impl From<Error> for Ics02Error {
+	fn from(e: Error) -> Self {
+		Ics02Error::client_error(ClientState::<()>::client_type().to_owned(), e.to_string())
+	}
+}
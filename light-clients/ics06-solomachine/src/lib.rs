@@ -0,0 +1,41 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::all)]
+
+//! ICS 06: Solo Machine Client implements a client verification algorithm for machines, e.g.
+//! phones, browsers or custodial services, which sign the state they commit to with a single
+//! public key instead of running a consensus algorithm.
+
+extern crate alloc;
+
+use core::fmt::Debug;
+
+pub mod client_def;
+pub mod client_message;
+pub mod client_state;
+pub mod consensus_state;
+pub mod error;
+#[cfg(test)]
+mod tests;
+
+/// Host functions that allow the light client verify the signatures of the solo machine in native.
+pub trait HostFunctionsProvider: Debug + Clone + Send + Sync + Default + Eq + 'static {
+	/// Verifies an ed25519 `signature` of `msg` by the public key `pubkey`.
+	fn ed25519_verify(signature: &[u8; 64], msg: &[u8], pubkey: &[u8]) -> bool;
+
+	/// Verifies a secp256k1 `signature` of the sha256 hash of `msg` by the compressed public key
+	/// `pubkey`, as produced by cosmos-sdk keys.
+	fn secp256k1_verify(signature: &[u8; 64], msg: &[u8], pubkey: &[u8]) -> bool;
+}
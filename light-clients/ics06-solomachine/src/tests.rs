@@ -0,0 +1,339 @@
+// Copyright 2022 ComposableFi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+	client_def::{header_sign_bytes, proof_sign_bytes, SolomachineClient},
+	client_message::{ClientMessage, Header, Misbehaviour, SignatureAndData},
+	client_state::ClientState,
+	consensus_state::{ConsensusState, PublicKey},
+	HostFunctionsProvider,
+};
+use ibc::{
+	core::{
+		ics02_client::client_def::ClientDef,
+		ics03_connection::connection::ConnectionEnd,
+		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
+		ics24_host::{
+			identifier::{ClientId, ConnectionId},
+			path::ConnectionsPath,
+		},
+	},
+	mock::context::{MockClientTypes, MockContext},
+	prelude::*,
+	Height,
+};
+use ibc_proto::{
+	cosmos::tx::signing::v1beta1::signature_descriptor::{
+		data::{Single, Sum},
+		Data as SignatureData,
+	},
+	ibc::lightclients::solomachine::v1::{SignBytes, TimestampedSignatureData},
+};
+use prost::Message;
+use sp_core::{crypto::ByteArray, ed25519, Pair};
+use tendermint_proto::Protobuf;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct HostFunctions;
+
+impl HostFunctionsProvider for HostFunctions {
+	fn ed25519_verify(signature: &[u8; 64], msg: &[u8], pubkey: &[u8]) -> bool {
+		match ed25519::Public::from_slice(pubkey) {
+			Ok(pubkey) =>
+				ed25519::Pair::verify(&ed25519::Signature::from_raw(*signature), msg, &pubkey),
+			Err(_) => false,
+		}
+	}
+
+	fn secp256k1_verify(_signature: &[u8; 64], _msg: &[u8], _pubkey: &[u8]) -> bool {
+		unimplemented!("the tests only use ed25519 keys")
+	}
+}
+
+const DIVERSIFIER: &str = "diversifier";
+const TIMESTAMP: u64 = 1_000_000_000;
+
+fn sign(pair: &ed25519::Pair, msg: &[u8]) -> Vec<u8> {
+	SignatureData {
+		sum: Some(Sum::Single(Single { mode: 0, signature: pair.sign(msg).0.to_vec() })),
+	}
+	.encode_to_vec()
+}
+
+fn client_state(pair: &ed25519::Pair, sequence: u64) -> ClientState<HostFunctions> {
+	let public_key = PublicKey::Ed25519(pair.public().to_raw_vec());
+	ClientState::new(sequence, ConsensusState::new(public_key, DIVERSIFIER.to_string(), TIMESTAMP))
+}
+
+fn signed_header(
+	client_state: &ClientState<HostFunctions>,
+	signer: &ed25519::Pair,
+	new_pair: &ed25519::Pair,
+) -> Header {
+	let mut header = Header {
+		timestamp: TIMESTAMP + 1,
+		signature: vec![],
+		new_public_key: PublicKey::Ed25519(new_pair.public().to_raw_vec()),
+		new_diversifier: "new diversifier".to_string(),
+	};
+	header.signature = sign(signer, &header_sign_bytes(client_state, &header));
+	header
+}
+
+#[test]
+fn header_rotates_the_public_key() {
+	let ctx = MockContext::<MockClientTypes>::default();
+	let client_id = ClientId::new(&ClientState::<()>::client_type(), 0).unwrap();
+	let client = SolomachineClient::<HostFunctions>::default();
+	let pair = ed25519::Pair::from_seed(&[1; 32]);
+	let new_pair = ed25519::Pair::from_seed(&[2; 32]);
+	let client_state = client_state(&pair, 1);
+
+	let header = signed_header(&client_state, &pair, &new_pair);
+	client
+		.verify_client_message(
+			&ctx,
+			client_id.clone(),
+			client_state.clone(),
+			ClientMessage::Header(header.clone()),
+		)
+		.unwrap();
+	assert!(!client
+		.check_for_misbehaviour(
+			&ctx,
+			client_id.clone(),
+			client_state.clone(),
+			ClientMessage::Header(header.clone()),
+		)
+		.unwrap());
+
+	let new_client_state = client_state.clone().with_header(header.clone());
+	assert_eq!(new_client_state.sequence, 2);
+	assert_eq!(new_client_state.consensus_state.public_key, header.new_public_key);
+	assert_eq!(new_client_state.consensus_state.diversifier, header.new_diversifier);
+	assert_eq!(new_client_state.consensus_state.timestamp, header.timestamp);
+
+	// headers must be signed by the current key
+	let forged_header = signed_header(&client_state, &new_pair, &new_pair);
+	assert!(client
+		.verify_client_message(
+			&ctx,
+			client_id.clone(),
+			client_state.clone(),
+			ClientMessage::Header(forged_header),
+		)
+		.is_err());
+
+	// and can't be replayed once the sequence moved on
+	assert!(client
+		.verify_client_message(&ctx, client_id, new_client_state, ClientMessage::Header(header),)
+		.is_err());
+}
+
+#[test]
+fn verifies_signed_state() {
+	let ctx = MockContext::<MockClientTypes>::default();
+	let client = SolomachineClient::<HostFunctions>::default();
+	let pair = ed25519::Pair::from_seed(&[1; 32]);
+	let client_state = client_state(&pair, 5);
+	let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+	let root = CommitmentRoot::from_bytes(&[]);
+	let connection_id = ConnectionId::new(0);
+	let connection_end = ConnectionEnd::default();
+
+	let sign_bytes = proof_sign_bytes(
+		client_state.sequence,
+		TIMESTAMP,
+		DIVERSIFIER.to_string(),
+		&prefix,
+		ConnectionsPath(connection_id.clone()),
+		connection_end.encode_vec().unwrap(),
+	);
+	let proof = CommitmentProofBytes::try_from(
+		TimestampedSignatureData { signature_data: sign(&pair, &sign_bytes), timestamp: TIMESTAMP }
+			.encode_to_vec(),
+	)
+	.unwrap();
+
+	client
+		.verify_connection_state(
+			&ctx,
+			&ClientId::default(),
+			&client_state,
+			client_state.latest_height(),
+			&prefix,
+			&proof,
+			&root,
+			&connection_id,
+			&connection_end,
+		)
+		.unwrap();
+
+	// the proof is only valid for the current sequence
+	assert!(client
+		.verify_connection_state(
+			&ctx,
+			&ClientId::default(),
+			&client_state,
+			Height::new(0, client_state.sequence + 1),
+			&prefix,
+			&proof,
+			&root,
+			&connection_id,
+			&connection_end,
+		)
+		.is_err());
+
+	// and for the signed value
+	assert!(client
+		.verify_connection_state(
+			&ctx,
+			&ClientId::default(),
+			&client_state,
+			client_state.latest_height(),
+			&prefix,
+			&proof,
+			&root,
+			&ConnectionId::new(1),
+			&connection_end,
+		)
+		.is_err());
+}
+
+#[test]
+fn double_signing_freezes_the_client() {
+	let ctx = MockContext::<MockClientTypes>::default();
+	let client_id = ClientId::new(&ClientState::<()>::client_type(), 0).unwrap();
+	let client = SolomachineClient::<HostFunctions>::default();
+	let pair = ed25519::Pair::from_seed(&[1; 32]);
+	let client_state = client_state(&pair, 3);
+
+	let signature_and_data = |data: &[u8]| {
+		let sign_bytes = SignBytes {
+			sequence: client_state.sequence,
+			timestamp: TIMESTAMP,
+			diversifier: DIVERSIFIER.to_string(),
+			path: b"path".to_vec(),
+			data: data.to_vec(),
+		}
+		.encode_to_vec();
+		SignatureAndData {
+			signature: sign(&pair, &sign_bytes),
+			path: b"path".to_vec(),
+			data: data.to_vec(),
+			timestamp: TIMESTAMP,
+		}
+	};
+
+	let misbehaviour = ClientMessage::Misbehaviour(Misbehaviour {
+		sequence: client_state.sequence,
+		signature_one: signature_and_data(b"one"),
+		signature_two: signature_and_data(b"two"),
+	});
+	client
+		.verify_client_message(&ctx, client_id.clone(), client_state.clone(), misbehaviour.clone())
+		.unwrap();
+	assert!(client
+		.check_for_misbehaviour(&ctx, client_id.clone(), client_state.clone(), misbehaviour.clone())
+		.unwrap());
+	let frozen_client_state =
+		client.update_state_on_misbehaviour(client_state.clone(), misbehaviour).unwrap();
+	assert!(frozen_client_state.is_frozen);
+	assert_eq!(frozen_client_state.frozen_height(), Some(client_state.latest_height()));
+
+	// signing the same data twice isn't misbehaviour
+	let misbehaviour = ClientMessage::Misbehaviour(Misbehaviour {
+		sequence: client_state.sequence,
+		signature_one: signature_and_data(b"one"),
+		signature_two: signature_and_data(b"one"),
+	});
+	assert!(client
+		.verify_client_message(&ctx, client_id, client_state, misbehaviour)
+		.is_err());
+}
+
+#[test]
+fn verified_proofs_consume_the_sequence() {
+	let ctx = MockContext::<MockClientTypes>::default();
+	let client_id = ClientId::new(&ClientState::<()>::client_type(), 0).unwrap();
+	let client = SolomachineClient::<HostFunctions>::default();
+	let pair = ed25519::Pair::from_seed(&[1; 32]);
+	let client_state = client_state(&pair, 5);
+	// the proofs of a message share its proof height
+	let height = client_state.latest_height();
+	let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+	let root = CommitmentRoot::from_bytes(&[]);
+	let connection_end = ConnectionEnd::default();
+
+	let prove = |client_state: &ClientState<HostFunctions>, connection_id: &ConnectionId| {
+		let sign_bytes = proof_sign_bytes(
+			client_state.sequence,
+			TIMESTAMP,
+			DIVERSIFIER.to_string(),
+			&prefix,
+			ConnectionsPath(connection_id.clone()),
+			connection_end.encode_vec().unwrap(),
+		);
+		let signature = sign(&pair, &sign_bytes);
+		let proof = CommitmentProofBytes::try_from(
+			TimestampedSignatureData { signature_data: signature.clone(), timestamp: TIMESTAMP }
+				.encode_to_vec(),
+		)
+		.unwrap();
+		let SignBytes { path, data, .. } = SignBytes::decode(sign_bytes.as_slice()).unwrap();
+		(proof, SignatureAndData { signature, path, data, timestamp: TIMESTAMP })
+	};
+	let verify = |client_state: &ClientState<HostFunctions>,
+	              connection_id: &ConnectionId,
+	              proof: &CommitmentProofBytes| {
+		client.verify_connection_state(
+			&ctx,
+			&client_id,
+			client_state,
+			height,
+			&prefix,
+			proof,
+			&root,
+			connection_id,
+			&connection_end,
+		)
+	};
+
+	let (proof_one, signature_one) = prove(&client_state, &ConnectionId::new(0));
+	verify(&client_state, &ConnectionId::new(0), &proof_one).unwrap();
+	let (client_state, consensus_state) =
+		client.update_state_on_proof(&client_state, &proof_one).unwrap().unwrap();
+	assert_eq!(client_state.sequence, 6);
+	assert_eq!(consensus_state, client_state.consensus_state);
+
+	// a verified proof can't be replayed
+	assert!(verify(&client_state, &ConnectionId::new(0), &proof_one).is_err());
+
+	let (proof_two, signature_two) = prove(&client_state, &ConnectionId::new(1));
+	verify(&client_state, &ConnectionId::new(1), &proof_two).unwrap();
+	let (client_state, _) =
+		client.update_state_on_proof(&client_state, &proof_two).unwrap().unwrap();
+
+	// so two honest proofs never share a sequence and can't be passed off as misbehaviour
+	for sequence in [5, 6] {
+		let misbehaviour = ClientMessage::Misbehaviour(Misbehaviour {
+			sequence,
+			signature_one: signature_one.clone(),
+			signature_two: signature_two.clone(),
+		});
+		assert!(client
+			.verify_client_message(&ctx, client_id.clone(), client_state.clone(), misbehaviour)
+			.is_err());
+	}
+}
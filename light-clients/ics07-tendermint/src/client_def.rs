@@ -26,6 +26,7 @@ use ibc::core::{
 		channel::ChannelEnd,
 		commitment::{AcknowledgementCommitment, PacketCommitment},
 		packet::Sequence,
+		upgrade::{ErrorReceipt, Upgrade},
 	},
 	ics23_commitment::{
 		commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
@@ -34,14 +35,15 @@ use ibc::core::{
 	ics24_host::{
 		identifier::{ChannelId, ClientId, ConnectionId, PortId},
 		path::{
-			AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath, CommitmentsPath,
-			ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+			AcksPath, ChannelEndsPath, ChannelUpgradeErrorsPath, ChannelUpgradesPath,
+			ClientConsensusStatePath, ClientStatePath, CommitmentsPath, ConnectionsPath,
+			ReceiptsPath, SeqRecvsPath,
 		},
 		Path,
 	},
 	ics26_routing::context::ReaderContext,
 };
-use ibc_proto::ibc::core::commitment::v1::MerkleProof as RawMerkleProof;
+use ibc_proto::ibc::core::commitment::v1::{MerklePath, MerkleProof as RawMerkleProof};
 use prost::Message;
 use tendermint_light_client_verifier::{
 	types::{TrustedBlockState, UntrustedBlockState},
@@ -58,6 +60,14 @@ use crate::{
 };
 use ibc::{prelude::*, timestamp::Timestamp, Height};
 
+/// Key of the upgraded client state in the upgrade store of the counterparty.
+pub const UPGRADED_CLIENT_KEY: &str = "upgradedClient";
+/// Key of the upgraded consensus state in the upgrade store of the counterparty.
+pub const UPGRADED_CONSENSUS_STATE_KEY: &str = "upgradedConsState";
+/// Root of the consensus state of an upgraded client, until it's updated with a header of the
+/// upgraded chain.
+pub const SENTINEL_ROOT: &str = "sentinel_root";
+
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct TendermintClient<H>(PhantomData<H>);
 
@@ -323,16 +333,108 @@ where
 
 	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
-		_client_id: ClientId,
-		_old_client_state: &Self::ClientState,
-		_upgrade_client_state: &Self::ClientState,
-		_upgrade_consensus_state: &Self::ConsensusState,
-		_proof_upgrade_client: Vec<u8>,
-		_proof_upgrade_consensus_state: Vec<u8>,
+		ctx: &Ctx,
+		client_id: ClientId,
+		old_client_state: &Self::ClientState,
+		upgrade_client_state: &Self::ClientState,
+		upgrade_consensus_state: &Self::ConsensusState,
+		proof_upgrade_client: Vec<u8>,
+		proof_upgrade_consensus_state: Vec<u8>,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Ics02Error> {
-		// TODO:
-		Err(Ics02Error::implementation_specific("Not implemented".to_string()))
+		if old_client_state.upgrade_path.is_empty() {
+			return Err(Error::missing_upgrade_path().into())
+		}
+
+		if old_client_state.frozen_height.is_some() {
+			return Err(Ics02Error::client_frozen(client_id.clone()))
+		}
+
+		if upgrade_client_state.latest_height().revision_height == 0 {
+			return Err(Error::validation(
+				"upgraded client state latest height must be greater than zero".to_string(),
+			)
+			.into())
+		}
+
+		let latest_height = old_client_state.latest_height();
+		if upgrade_client_state.latest_height() <= latest_height {
+			return Err(Ics02Error::low_upgrade_height(
+				upgrade_client_state.latest_height(),
+				latest_height,
+			))
+		}
+
+		// The upgraded states are committed to by the consensus state at the latest height of the
+		// client, which must still be within the trusting period.
+		let consensus_state = ctx
+			.consensus_state(&client_id, latest_height)?
+			.downcast::<ConsensusState>()
+			.ok_or_else(|| {
+				Ics02Error::client_args_type_mismatch(ClientState::<()>::client_type().to_owned())
+			})?;
+		let elapsed = ctx
+			.host_timestamp()
+			.duration_since(&consensus_state.timestamp.into())
+			.unwrap_or_default();
+		if old_client_state.expired(elapsed) {
+			return Err(Error::client_expired(latest_height).into())
+		}
+
+		let client_state_value = Ctx::AnyClientState::wrap(upgrade_client_state)
+			.ok_or_else(|| {
+				Ics02Error::unknown_client_state_type("Ctx::AnyClientState".to_string())
+			})?
+			.encode_to_vec()
+			.map_err(Ics02Error::encode)?;
+		verify_upgrade_membership(
+			old_client_state,
+			&consensus_state.root,
+			&proof_upgrade_client,
+			latest_height,
+			UPGRADED_CLIENT_KEY,
+			client_state_value,
+		)?;
+
+		let consensus_state_value = Ctx::AnyConsensusState::wrap(upgrade_consensus_state)
+			.ok_or_else(|| {
+				Ics02Error::unknown_consensus_state_type("Ctx::AnyConsensusState".to_string())
+			})?
+			.encode_to_vec()
+			.map_err(Ics02Error::encode)?;
+		verify_upgrade_membership(
+			old_client_state,
+			&consensus_state.root,
+			&proof_upgrade_consensus_state,
+			latest_height,
+			UPGRADED_CONSENSUS_STATE_KEY,
+			consensus_state_value,
+		)?;
+
+		// The chain specified fields are taken from the upgraded client state, while the fields
+		// chosen by the relayer are kept. The combination goes through the same validation as a
+		// newly created client, the trusting period must stay below the new unbonding period.
+		let new_client_state = ClientState::new(
+			upgrade_client_state.chain_id.clone(),
+			old_client_state.trust_level,
+			old_client_state.trusting_period,
+			upgrade_client_state.unbonding_period,
+			old_client_state.max_clock_drift,
+			upgrade_client_state.latest_height,
+			upgrade_client_state.proof_specs.clone(),
+			upgrade_client_state.upgrade_path.clone(),
+		)?;
+		// The root of the upgraded consensus state is only known after the first update of the
+		// upgraded client, a sentinel value is stored until then.
+		let new_consensus_state = ConsensusState::new(
+			CommitmentRoot::from_bytes(SENTINEL_ROOT.as_bytes()),
+			upgrade_consensus_state.timestamp,
+			upgrade_consensus_state.next_validators_hash,
+		);
+		let cs = Ctx::AnyConsensusState::wrap(&new_consensus_state).ok_or_else(|| {
+			Ics02Error::unknown_consensus_state_type("Ctx::AnyConsensusState".to_string())
+		})?;
+
+		Ok((new_client_state, ConsensusUpdateResult::Single(cs)))
 	}
 
 	fn verify_client_consensus_state<Ctx: ReaderContext>(
@@ -530,6 +632,89 @@ where
 			receipt_path,
 		)
 	}
+
+	fn verify_channel_upgrade<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		upgrade: &Upgrade,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+
+		let path = ChannelUpgradesPath(port_id.clone(), *channel_id);
+		let value = upgrade.encode_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H, _>(
+			client_state,
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			path,
+			value,
+		)
+	}
+
+	fn verify_channel_upgrade_error<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+
+		let path = ChannelUpgradeErrorsPath(port_id.clone(), *channel_id);
+		let value = error_receipt.encode_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H, _>(
+			client_state,
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			path,
+			value,
+		)
+	}
+
+	fn verify_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+		value: Vec<u8>,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_membership::<H, _>(client_state, prefix, proof, root, path, value)
+	}
+
+	fn verify_non_membership<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		path: Path,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		verify_non_membership::<H, _>(client_state, prefix, proof, root, path)
+	}
 }
 
 fn verify_membership<H, P>(
@@ -554,6 +739,38 @@ where
 		.map_err(|e| Error::ics23_error(e).into())
 }
 
+/// Verifies the membership of an upgraded state in the `upgrade` store of the counterparty, the
+/// last key of the upgrade path is suffixed with the height the upgrade was planned at and the key
+/// of the state, e.g. `upgradedIBCState/{height}/upgradedClient`.
+fn verify_upgrade_membership<H>(
+	client_state: &ClientState<H>,
+	root: &CommitmentRoot,
+	proof: &[u8],
+	last_height: Height,
+	key: &str,
+	value: Vec<u8>,
+) -> Result<(), Ics02Error>
+where
+	H: ics23::HostFunctionsProvider,
+{
+	let mut key_path = client_state.upgrade_path.clone();
+	let last_key = key_path.pop().ok_or_else(Error::missing_upgrade_path)?;
+	key_path.push(format!("{}/{}/{}", last_key, last_height.revision_height, key));
+	let merkle_proof: MerkleProof<H> = RawMerkleProof::decode(proof)
+		.map_err(|e| Ics02Error::implementation_specific(e.to_string()))?
+		.into();
+
+	merkle_proof
+		.verify_membership(
+			&client_state.proof_specs,
+			root.clone().into(),
+			MerklePath { key_path },
+			value,
+			0,
+		)
+		.map_err(|e| Error::ics23_error(e).into())
+}
+
 fn verify_non_membership<H, P>(
 	client_state: &ClientState<H>,
 	prefix: &CommitmentPrefix,
@@ -604,3 +821,525 @@ fn verify_delay_passed<Ctx: ReaderContext>(
 	)
 	.map_err(|e| e.into())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{AnyClientState, AnyConsensusState, Crypto, MockClientTypes};
+	use core::time::Duration;
+	use ibc::{
+		core::{
+			ics02_client::{
+				client_consensus::ConsensusState as _,
+				client_state::ClientState as _,
+				context::{ClientKeeper, ClientReader},
+				trust_threshold::TrustThreshold,
+			},
+			ics03_connection::{
+				connection::{Counterparty as ConnectionCounterparty, State as ConnectionState},
+				version::get_compatible_versions,
+			},
+			ics04_channel::{
+				channel::{Counterparty as ChannelCounterparty, Order, State as ChannelState},
+				error::ErrorDetail as ChannelErrorDetail,
+				handler::verify::verify_channel_proofs,
+				multihop::{MultihopProof, MultihopProofs},
+				Version,
+			},
+			ics23_commitment::specs::ProofSpecs,
+			ics24_host::identifier::ChainId,
+		},
+		mock::{
+			client_state::{MockClientState, MockConsensusState},
+			context::MockContext,
+			header::MockHeader,
+		},
+		prelude::*,
+		timestamp::ZERO_DURATION,
+		Height,
+	};
+	use ibc_proto::cosmos::ics23::v1::CommitmentProof as RawCommitmentProof;
+	use ics23::{
+		calculate_existence_root, commitment_proof::Proof, CommitmentProof, ExistenceProof, HashOp,
+		InnerOp, LeafOp, LengthOp,
+	};
+	use tendermint::Hash;
+
+	const UPGRADE_PATH: [&str; 2] = ["upgrade", "upgradedIBCState"];
+
+	fn client_state(
+		chain_id: ChainId,
+		trusting_period: Duration,
+		unbonding_period: Duration,
+		latest_height: Height,
+	) -> ClientState<Crypto> {
+		ClientState::new(
+			chain_id,
+			TrustThreshold::ONE_THIRD,
+			trusting_period,
+			unbonding_period,
+			Duration::from_secs(3),
+			latest_height,
+			ProofSpecs::default(),
+			UPGRADE_PATH.iter().map(|key| key.to_string()).collect(),
+		)
+		.unwrap()
+	}
+
+	fn leaf(key: &[u8], value: Vec<u8>, prefix: Vec<u8>) -> ExistenceProof {
+		ExistenceProof {
+			key: key.to_vec(),
+			value,
+			leaf: Some(LeafOp {
+				hash: HashOp::Sha256.into(),
+				prehash_key: HashOp::NoHash.into(),
+				prehash_value: HashOp::Sha256.into(),
+				length: LengthOp::VarProto.into(),
+				prefix,
+			}),
+			path: vec![],
+		}
+	}
+
+	fn root(proof: &ExistenceProof) -> Vec<u8> {
+		calculate_existence_root::<Crypto>(proof).unwrap()
+	}
+
+	fn encode_proof(proofs: Vec<ExistenceProof>) -> Vec<u8> {
+		let proofs = proofs
+			.into_iter()
+			.map(|proof| {
+				let proof = CommitmentProof { proof: Some(Proof::Exist(proof)) };
+				RawCommitmentProof::decode(&*proof.encode_to_vec()).unwrap()
+			})
+			.collect();
+		RawMerkleProof { proofs }.encode_to_vec()
+	}
+
+	/// Commits to the upgraded states in a two leaves iavl `upgrade` store under a tendermint
+	/// multistore, returns the app hash with the proofs of the client and consensus states.
+	fn upgrade_proofs(
+		client_state: &ClientState<Crypto>,
+		consensus_state: &ConsensusState,
+		last_height: Height,
+	) -> (CommitmentRoot, Vec<u8>, Vec<u8>) {
+		let key =
+			|name: &str| format!("{}/{}/{}", UPGRADE_PATH[1], last_height.revision_height, name);
+		let client_value = AnyClientState::wrap(client_state).unwrap().encode_to_vec().unwrap();
+		let consensus_value =
+			AnyConsensusState::wrap(consensus_state).unwrap().encode_to_vec().unwrap();
+
+		// iavl leaves are at height 0 with size 1, their parent at height 1 with size 2.
+		let mut client_leaf =
+			leaf(key(UPGRADED_CLIENT_KEY).as_bytes(), client_value, vec![0, 2, 2]);
+		let mut consensus_leaf =
+			leaf(key(UPGRADED_CONSENSUS_STATE_KEY).as_bytes(), consensus_value, vec![0, 2, 2]);
+		let (client_hash, consensus_hash) = (root(&client_leaf), root(&consensus_leaf));
+		client_leaf.path.push(InnerOp {
+			hash: HashOp::Sha256.into(),
+			prefix: vec![2, 4, 2, 32],
+			suffix: [&[32u8][..], &consensus_hash].concat(),
+		});
+		consensus_leaf.path.push(InnerOp {
+			hash: HashOp::Sha256.into(),
+			prefix: [&[2u8, 4, 2, 32][..], &client_hash, &[32]].concat(),
+			suffix: vec![],
+		});
+
+		let store = leaf(UPGRADE_PATH[0].as_bytes(), root(&client_leaf), vec![0]);
+		(
+			CommitmentRoot::from_bytes(&root(&store)),
+			encode_proof(vec![client_leaf, store.clone()]),
+			encode_proof(vec![consensus_leaf, store]),
+		)
+	}
+
+	struct Upgrade {
+		ctx: MockContext<MockClientTypes>,
+		client_id: ClientId,
+		old_client_state: ClientState<Crypto>,
+		client_state: ClientState<Crypto>,
+		consensus_state: ConsensusState,
+		proof_client: Vec<u8>,
+		proof_consensus_state: Vec<u8>,
+	}
+
+	impl Upgrade {
+		/// Commits to `client_state` as the upgrade of a client on `chain-0` at height 10.
+		fn new(client_state: ClientState<Crypto>) -> Self {
+			let mut ctx = MockContext::<MockClientTypes>::default();
+			let client_id = ClientId::new(&ClientState::<()>::client_type(), 0).unwrap();
+			let old_client_state = client_state_at(Height::new(0, 10));
+			let timestamp = ctx.host_timestamp().into_tm_time().unwrap();
+			let consensus_state = ConsensusState::new(
+				CommitmentRoot::from_bytes(&[1; 32]),
+				timestamp,
+				Hash::Sha256([2; 32]),
+			);
+
+			let (root, proof_client, proof_consensus_state) =
+				upgrade_proofs(&client_state, &consensus_state, old_client_state.latest_height);
+			ctx.store_consensus_state(
+				client_id.clone(),
+				old_client_state.latest_height,
+				AnyConsensusState::Tendermint(ConsensusState::new(
+					root,
+					timestamp,
+					Hash::Sha256([3; 32]),
+				)),
+			)
+			.unwrap();
+
+			Self {
+				ctx,
+				client_id,
+				old_client_state,
+				client_state,
+				consensus_state,
+				proof_client,
+				proof_consensus_state,
+			}
+		}
+
+		fn verify(
+			&self,
+		) -> Result<
+			(ClientState<Crypto>, ConsensusUpdateResult<MockContext<MockClientTypes>>),
+			Ics02Error,
+		> {
+			TendermintClient::<Crypto>::default().verify_upgrade_and_update_state(
+				&self.ctx,
+				self.client_id.clone(),
+				&self.old_client_state,
+				&self.client_state,
+				&self.consensus_state,
+				self.proof_client.clone(),
+				self.proof_consensus_state.clone(),
+			)
+		}
+	}
+
+	fn client_state_at(latest_height: Height) -> ClientState<Crypto> {
+		client_state(
+			ChainId::new("chain".to_string(), latest_height.revision_number),
+			Duration::from_secs(64000),
+			Duration::from_secs(128000),
+			latest_height,
+		)
+	}
+
+	#[test]
+	fn upgrade_verifies_proofs_and_keeps_relayer_fields() {
+		let upgraded = client_state(
+			ChainId::new("chain".to_string(), 1),
+			Duration::from_secs(1),
+			Duration::from_secs(256000),
+			Height::new(1, 1),
+		);
+		let upgrade = Upgrade::new(upgraded);
+
+		let (client_state, consensus_update) = upgrade.verify().unwrap();
+		assert_eq!(client_state.chain_id, upgrade.client_state.chain_id);
+		assert_eq!(client_state.latest_height, Height::new(1, 1));
+		assert_eq!(client_state.unbonding_period, Duration::from_secs(256000));
+		assert_eq!(client_state.trusting_period, upgrade.old_client_state.trusting_period);
+		assert_eq!(client_state.frozen_height, None);
+		match consensus_update {
+			ConsensusUpdateResult::Single(AnyConsensusState::Tendermint(consensus_state)) => {
+				assert_eq!(consensus_state.root.as_bytes(), SENTINEL_ROOT.as_bytes());
+				assert_eq!(
+					consensus_state.next_validators_hash,
+					upgrade.consensus_state.next_validators_hash
+				);
+			},
+			update => panic!("unexpected consensus update: {:?}", update),
+		}
+	}
+
+	#[test]
+	fn upgrade_rejects_invalid_proofs() {
+		let upgraded = client_state_at(Height::new(1, 1));
+
+		// the committed client state differs from the submitted one.
+		let mut upgrade = Upgrade::new(upgraded.clone());
+		upgrade.client_state.unbonding_period = Duration::from_secs(256000);
+		assert!(upgrade.verify().is_err());
+
+		// the proofs are swapped.
+		let mut upgrade = Upgrade::new(upgraded.clone());
+		core::mem::swap(&mut upgrade.proof_client, &mut upgrade.proof_consensus_state);
+		assert!(upgrade.verify().is_err());
+
+		// the proof isn't a merkle proof.
+		let mut upgrade = Upgrade::new(upgraded);
+		upgrade.proof_client = vec![1, 2, 3];
+		assert!(upgrade.verify().is_err());
+	}
+
+	#[test]
+	fn upgrade_rejects_invalid_client_states() {
+		let upgrade = Upgrade::new(client_state_at(Height::new(1, 1)));
+		assert!(upgrade.verify().is_ok());
+
+		let mut frozen = Upgrade::new(client_state_at(Height::new(1, 1)));
+		frozen.old_client_state =
+			frozen.old_client_state.with_frozen_height(Height::new(0, 5)).unwrap();
+		assert!(frozen.verify().is_err());
+
+		for height in [Height::new(1, 0), Height::new(0, 10), Height::new(0, 9)] {
+			let mut upgraded = client_state_at(Height::new(1, 1));
+			upgraded.latest_height = height;
+			assert!(Upgrade::new(upgraded).verify().is_err(), "upgrade to {}", height);
+		}
+
+		// the relayer's trusting period must stay below the new unbonding period.
+		let mut upgraded = client_state_at(Height::new(1, 1));
+		upgraded.unbonding_period = Duration::from_secs(64000);
+		assert!(Upgrade::new(upgraded).verify().is_err());
+	}
+
+	/// Commits to `entries` in a balanced iavl `ibc` store under a tendermint multistore, returns
+	/// the app hash with the proof of every entry.
+	fn ibc_store_proofs(entries: Vec<(String, Vec<u8>)>) -> (CommitmentRoot, Vec<Vec<u8>>) {
+		let count = entries.len();
+		let mut leaves: Vec<_> = entries
+			.into_iter()
+			.map(|(key, value)| leaf(key.as_bytes(), value, vec![0, 2, 2]))
+			.collect();
+		while leaves.len() < 2 || !leaves.len().is_power_of_two() {
+			let key = format!("padding/{}", leaves.len());
+			leaves.push(leaf(key.as_bytes(), vec![0], vec![0, 2, 2]));
+		}
+
+		// the inner nodes above subtrees of `width` leaves, their prefix holds the zigzag encoded
+		// height, size and version of the node followed by the length of the left hash.
+		let (mut width, mut height) = (1, 1);
+		while width < leaves.len() {
+			let prefix = vec![height * 2, width as u8 * 4, 2, 32];
+			for start in (0..leaves.len()).step_by(width * 2) {
+				let (left, right) = (root(&leaves[start]), root(&leaves[start + width]));
+				for proof in &mut leaves[start..start + width] {
+					proof.path.push(InnerOp {
+						hash: HashOp::Sha256.into(),
+						prefix: prefix.clone(),
+						suffix: [&[32u8][..], &right].concat(),
+					});
+				}
+				for proof in &mut leaves[start + width..start + width * 2] {
+					proof.path.push(InnerOp {
+						hash: HashOp::Sha256.into(),
+						prefix: [&prefix[..], &left, &[32]].concat(),
+						suffix: vec![],
+					});
+				}
+			}
+			width *= 2;
+			height += 1;
+		}
+
+		let store = leaf(b"ibc", root(&leaves[0]), vec![0]);
+		let proofs = leaves
+			.into_iter()
+			.take(count)
+			.map(|leaf| encode_proof(vec![leaf, store.clone()]))
+			.collect();
+		(CommitmentRoot::from_bytes(&root(&store)), proofs)
+	}
+
+	/// A channel from the host (chain A) to chain C through chain B. The host has a tendermint
+	/// client of B, B has a tendermint client of C.
+	struct MultihopChannel {
+		ctx: MockContext<MockClientTypes>,
+		proof_height: Height,
+		channel_end: ChannelEnd,
+		connection_end: ConnectionEnd,
+		counterparty_channel_end: ChannelEnd,
+		proofs: MultihopProofs,
+	}
+
+	impl MultihopChannel {
+		fn new() -> Self {
+			let prefix = CommitmentPrefix::try_from(b"ibc".to_vec()).unwrap();
+			let port_id = PortId::transfer();
+			let connection_end = |client_id: ClientId, counterparty_connection_id| {
+				ConnectionEnd::new(
+					ConnectionState::Open,
+					client_id.clone(),
+					ConnectionCounterparty::new(
+						client_id,
+						Some(ConnectionId::new(counterparty_connection_id)),
+						prefix.clone(),
+					),
+					get_compatible_versions(),
+					ZERO_DURATION,
+				)
+			};
+			let hop_proof = |proof: Vec<u8>, value: Vec<u8>, path: Path| MultihopProof {
+				proof: proof.try_into().unwrap(),
+				value,
+				prefixed_key: vec!["ibc".to_string(), path.to_string()],
+			};
+
+			let mut ctx = MockContext::<MockClientTypes>::default();
+			let timestamp = ctx.host_timestamp().into_tm_time().unwrap();
+
+			// chain C stores the counterparty channel end.
+			let counterparty_channel_end = ChannelEnd::new(
+				ChannelState::Init,
+				Order::Unordered,
+				ChannelCounterparty::new(port_id.clone(), None),
+				vec![ConnectionId::new(2), ConnectionId::new(3)],
+				Version::ics20(),
+			);
+			let channel_path = ChannelEndsPath(port_id.clone(), ChannelId::new(0));
+			let (c_root, c_proofs) = ibc_store_proofs(vec![(
+				channel_path.to_string(),
+				counterparty_channel_end.encode_vec().unwrap(),
+			)]);
+
+			// chain B stores its connection to C with the client and consensus state of C.
+			let b_client_id = ClientId::new(&ClientState::<()>::client_type(), 1).unwrap();
+			let b_connection_id = ConnectionId::new(1);
+			let b_connection_end = connection_end(b_client_id.clone(), 2);
+			let c_height = Height::new(0, 20);
+			let c_client_state = AnyClientState::Tendermint(client_state_at(c_height));
+			let c_consensus_state = AnyConsensusState::Tendermint(ConsensusState::new(
+				c_root,
+				timestamp,
+				Hash::Sha256([4; 32]),
+			));
+			let connection_path = ConnectionsPath(b_connection_id.clone());
+			let client_state_path = ClientStatePath(b_client_id.clone());
+			let consensus_path = ClientConsensusStatePath {
+				client_id: b_client_id,
+				epoch: c_height.revision_number,
+				height: c_height.revision_height,
+			};
+			let (b_root, b_proofs) = ibc_store_proofs(vec![
+				(connection_path.to_string(), b_connection_end.encode_vec().unwrap()),
+				(client_state_path.to_string(), c_client_state.encode_to_vec().unwrap()),
+				(consensus_path.to_string(), c_consensus_state.encode_to_vec().unwrap()),
+			]);
+
+			// the host tracks B at the proof height.
+			let a_client_id = ClientId::new(&ClientState::<()>::client_type(), 0).unwrap();
+			let proof_height = Height::new(0, 10);
+			ctx.store_client_state(
+				a_client_id.clone(),
+				AnyClientState::Tendermint(client_state_at(proof_height)),
+			)
+			.unwrap();
+			ctx.store_consensus_state(
+				a_client_id.clone(),
+				proof_height,
+				AnyConsensusState::Tendermint(ConsensusState::new(
+					b_root,
+					timestamp,
+					Hash::Sha256([3; 32]),
+				)),
+			)
+			.unwrap();
+
+			let channel_end = ChannelEnd::new(
+				ChannelState::Init,
+				Order::Unordered,
+				ChannelCounterparty::new(port_id, Some(ChannelId::new(0))),
+				vec![ConnectionId::new(0), b_connection_id],
+				Version::ics20(),
+			);
+			let proofs = MultihopProofs {
+				key_proof: hop_proof(c_proofs[0].clone(), vec![], channel_path.into()),
+				connection_proofs: vec![hop_proof(
+					b_proofs[0].clone(),
+					b_connection_end.encode_vec().unwrap(),
+					connection_path.into(),
+				)],
+				client_state_proofs: vec![hop_proof(
+					b_proofs[1].clone(),
+					c_client_state.encode_to_vec().unwrap(),
+					client_state_path.into(),
+				)],
+				consensus_proofs: vec![hop_proof(
+					b_proofs[2].clone(),
+					c_consensus_state.encode_to_vec().unwrap(),
+					consensus_path.into(),
+				)],
+			};
+
+			Self {
+				ctx,
+				proof_height,
+				channel_end,
+				connection_end: connection_end(a_client_id, 0),
+				counterparty_channel_end,
+				proofs,
+			}
+		}
+
+		fn verify(&self) -> Result<(), ibc::core::ics04_channel::error::Error> {
+			verify_channel_proofs(
+				&self.ctx,
+				&mut None,
+				self.proof_height,
+				&self.channel_end,
+				&self.connection_end,
+				&self.counterparty_channel_end,
+				&self.proofs.encode_vec().unwrap().try_into().unwrap(),
+			)
+		}
+	}
+
+	#[test]
+	fn multihop_proofs_are_verified_with_the_client_of_each_hop() {
+		let channel = MultihopChannel::new();
+		channel.verify().unwrap();
+
+		// the proven object differs from the expected one.
+		let mut tampered = MultihopChannel::new();
+		tampered.counterparty_channel_end.ordering = Order::Ordered;
+		assert!(tampered.verify().is_err());
+	}
+
+	#[test]
+	fn multihop_rejects_forged_intermediate_proofs() {
+		let is_hop_failure = |channel: &MultihopChannel| {
+			matches!(
+				channel.verify().as_ref().map_err(|e| e.detail()),
+				Err(ChannelErrorDetail::VerifyMultihopFailed(_))
+			)
+		};
+
+		// a consensus state of C that B doesn't store, committing to a forged channel end.
+		let mut forged = MultihopChannel::new();
+		forged.counterparty_channel_end.ordering = Order::Ordered;
+		let channel_path = ChannelEndsPath(PortId::transfer(), ChannelId::new(0)).to_string();
+		let (forged_root, forged_proofs) = ibc_store_proofs(vec![(
+			channel_path,
+			forged.counterparty_channel_end.encode_vec().unwrap(),
+		)]);
+		let timestamp = forged.ctx.host_timestamp().into_tm_time().unwrap();
+		let consensus_state = ConsensusState::new(forged_root, timestamp, Hash::Sha256([4; 32]));
+		forged.proofs.consensus_proofs[0].value =
+			AnyConsensusState::Tendermint(consensus_state).encode_to_vec().unwrap();
+		forged.proofs.key_proof.proof = forged_proofs[0].clone().try_into().unwrap();
+		assert!(is_hop_failure(&forged));
+
+		// a client of C that accepts any proof in place of the one stored by B.
+		let mut forged = MultihopChannel::new();
+		let mock_header = MockHeader::new(Height::new(0, 20));
+		let client_state = AnyClientState::Mock(MockClientState::new(mock_header.into()));
+		let consensus_state = AnyConsensusState::Mock(MockConsensusState::new(mock_header));
+		forged.proofs.client_state_proofs[0].value = client_state.encode_to_vec().unwrap();
+		forged.proofs.consensus_proofs[0].value = consensus_state.encode_to_vec().unwrap();
+		assert!(is_hop_failure(&forged));
+
+		// the proofs of B are swapped.
+		let mut forged = MultihopChannel::new();
+		let proofs = &mut forged.proofs;
+		core::mem::swap(
+			&mut proofs.client_state_proofs[0].proof,
+			&mut proofs.consensus_proofs[0].proof,
+		);
+		assert!(is_hop_failure(&forged));
+	}
+}
@@ -287,6 +287,15 @@ define_error! {
 			| e | {
 				format_args!("the client is frozen: frozen_height={0} target_height={1}", e.frozen_height, e.target_height)
 			},
+
+		MissingUpgradePath
+			| _ | { "cannot upgrade client, no upgrade path set" },
+
+		ClientExpired
+			{ latest_height: Height }
+			| e | {
+				format_args!("cannot upgrade an expired client, the consensus state at height {0} is outside of the trusting period", e.latest_height)
+			},
 	}
 }
 
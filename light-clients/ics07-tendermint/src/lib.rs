@@ -59,6 +59,7 @@ pub trait HostFunctionsProvider:
 	+ Sync
 	+ Default
 	+ Eq
+	+ 'static
 {
 }
 
@@ -39,13 +39,15 @@ use ibc::{
 			channel::ChannelEnd,
 			commitment::{AcknowledgementCommitment, PacketCommitment},
 			packet::Sequence,
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
 		ics24_host::{
 			identifier::{ChannelId, ClientId, ConnectionId, PortId},
 			path::{
-				AcksPath, ChannelEndsPath, ClientConsensusStatePath, ClientStatePath,
-				CommitmentsPath, ConnectionsPath, ReceiptsPath, SeqRecvsPath,
+				AcksPath, ChannelEndsPath, ChannelUpgradeErrorsPath, ChannelUpgradesPath,
+				ClientConsensusStatePath, ClientStatePath, CommitmentsPath, ConnectionsPath,
+				ReceiptsPath, SeqRecvsPath,
 			},
 		},
 		ics26_routing::context::ReaderContext,
@@ -129,7 +131,20 @@ where
 					.map_err(Error::from)?
 				}
 			},
-			ClientMessage::Misbehaviour(_) => unimplemented!(),
+			ClientMessage::Misbehaviour(misbehaviour) => {
+				let light_client_state = LightClientState {
+					latest_beefy_height: client_state.latest_beefy_height,
+					mmr_root_hash: client_state.mmr_root_hash,
+					current_authorities: client_state.authority.clone(),
+					next_authorities: client_state.next_authority_set.clone(),
+				};
+				beefy_client::verify_equivocation::<H>(
+					&light_client_state,
+					&misbehaviour.first_signed_commitment,
+					&misbehaviour.second_signed_commitment,
+				)
+				.map_err(Error::from)?
+			},
 		}
 		Ok(())
 	}
@@ -229,9 +244,8 @@ where
 					}
 				}
 			},
-			// todo: Beefy protocol hasn't yet defined it's equivocation protocol
-			// blocked on https://github.com/paritytech/grandpa-bridge-gadget/issues/101
-			ClientMessage::Misbehaviour(_) => {},
+			// conflicting commitments have already been verified in `verify_client_message`
+			ClientMessage::Misbehaviour(_) => return Ok(true),
 		}
 
 		Ok(false)
@@ -452,4 +466,58 @@ where
 		.map_err(Error::Anyhow)?;
 		Ok(())
 	}
+
+	fn verify_channel_upgrade<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		upgrade: &Upgrade,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		let path = ChannelUpgradesPath(port_id.clone(), *channel_id);
+		let value = upgrade.encode_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H::BlakeTwo256, _>(
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			path,
+			value,
+		)
+		.map_err(Error::Anyhow)?;
+		Ok(())
+	}
+
+	fn verify_channel_upgrade_error<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), Ics02Error> {
+		client_state.verify_height(height)?;
+		let path = ChannelUpgradeErrorsPath(port_id.clone(), *channel_id);
+		let value = error_receipt.encode_vec().map_err(Ics02Error::encode)?;
+		verify_membership::<H::BlakeTwo256, _>(
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			path,
+			value,
+		)
+		.map_err(Error::Anyhow)?;
+		Ok(())
+	}
 }
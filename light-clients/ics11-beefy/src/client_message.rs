@@ -17,6 +17,7 @@ use tendermint_proto::Protobuf;
 
 use crate::{
 	error::Error,
+	misbehaviour::Misbehaviour,
 	proto::{
 		client_message, BeefyAuthoritySet as RawBeefyAuthoritySet, BeefyMmrLeaf as RawBeefyMmrLeaf,
 		BeefyMmrLeafPartial as RawBeefyMmrLeafPartial, ClientMessage as RawClientMessage,
@@ -60,7 +61,7 @@ pub enum ClientMessage {
 	/// Header variant for updating the client
 	Header(BeefyHeader),
 	/// Misbehaviour variant for freezing the client.
-	Misbehaviour(()),
+	Misbehaviour(Misbehaviour),
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -320,7 +321,15 @@ impl TryFrom<RawClientMessage> for ClientMessage {
 
 				ClientMessage::Header(BeefyHeader { headers_with_proof, mmr_update_proof })
 			},
-			client_message::Message::Misbehaviour(_) => ClientMessage::Misbehaviour(()),
+			client_message::Message::Misbehaviour(raw_misbehaviour) =>
+				ClientMessage::Misbehaviour(Misbehaviour {
+					first_signed_commitment: Decode::decode(
+						&mut &*raw_misbehaviour.first_signed_commitment,
+					)?,
+					second_signed_commitment: Decode::decode(
+						&mut &*raw_misbehaviour.second_signed_commitment,
+					)?,
+				}),
 		};
 
 		Ok(message)
@@ -462,8 +471,11 @@ impl From<ClientMessage> for RawClientMessage {
 					},
 				})),
 			},
-			ClientMessage::Misbehaviour(_) => RawClientMessage {
-				message: Some(client_message::Message::Misbehaviour(RawMisbehaviour {})),
+			ClientMessage::Misbehaviour(misbehaviour) => RawClientMessage {
+				message: Some(client_message::Message::Misbehaviour(RawMisbehaviour {
+					first_signed_commitment: misbehaviour.first_signed_commitment.encode(),
+					second_signed_commitment: misbehaviour.second_signed_commitment.encode(),
+				})),
 			},
 		}
 	}
@@ -12,3 +12,16 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+use beefy_light_client_primitives::SignedCommitmentWithProof;
+
+/// Misbehaviour type for BEEFY. If both signed commitments are valid, that is they're for the same
+/// block and were signed by a super majority of the same authority set, then the authority set has
+/// equivocated.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Misbehaviour {
+	/// first signed commitment along with the proof of its signatories
+	pub first_signed_commitment: SignedCommitmentWithProof,
+	/// second signed commitment along with the proof of its signatories
+	pub second_signed_commitment: SignedCommitmentWithProof,
+}
@@ -18,15 +18,18 @@ use super::{
 	consensus_state::ConsensusState,
 	error::Error as NearError,
 	header::NearHeader,
+	trie,
 	types::{ApprovalInner, CryptoHash, LightClientBlockView},
 };
 use crate::header::NearClientMessage;
-use borsh::BorshSerialize;
-use core::fmt::Debug;
+use borsh::{BorshDeserialize, BorshSerialize};
+use core::{fmt::Debug, marker::PhantomData};
 use ibc::{
 	core::{
 		ics02_client::{
+			client_consensus::ConsensusState as _,
 			client_def::{ClientDef, ConsensusUpdateResult},
+			client_state::ClientState as _,
 			error::Error,
 		},
 		ics03_connection::connection::ConnectionEnd,
@@ -34,19 +37,41 @@ use ibc::{
 			channel::ChannelEnd,
 			commitment::{AcknowledgementCommitment, PacketCommitment},
 			packet::Sequence,
+			upgrade::{ErrorReceipt, Upgrade},
 		},
 		ics23_commitment::commitment::{CommitmentPrefix, CommitmentProofBytes, CommitmentRoot},
-		ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId},
+		ics24_host::{
+			identifier::{ChannelId, ClientId, ConnectionId, PortId},
+			path::{
+				AcksPath, ChannelEndsPath, ChannelUpgradeErrorsPath, ChannelUpgradesPath,
+				ClientConsensusStatePath, ClientStatePath, CommitmentsPath, ConnectionsPath,
+				ReceiptsPath, SeqRecvsPath,
+			},
+			Path,
+		},
 		ics26_routing::context::ReaderContext,
 	},
 	prelude::*,
 	Height,
 };
 use ics23::HostFunctionsProvider;
-use std::marker::PhantomData;
+use light_client_common::verify_delay_passed;
+use tendermint_proto::Protobuf;
+
+const CLIENT_STATE_UPGRADE_PATH: &[u8] = b"client-state-upgrade-path";
+const CONSENSUS_STATE_UPGRADE_PATH: &[u8] = b"consensus-state-upgrade-path";
 
 pub trait HostFunctionsTrait:
-	HostFunctions + HostFunctionsProvider + Clone + Debug + PartialEq + Eq + Default + Send + Sync
+	HostFunctions
+	+ HostFunctionsProvider
+	+ Clone
+	+ Debug
+	+ PartialEq
+	+ Eq
+	+ Default
+	+ Send
+	+ Sync
+	+ 'static
 {
 }
 
@@ -65,23 +90,6 @@ pub trait HostFunctions: Clone + Send + Sync + Default {
 	/// Recover the ED25519 pubkey that produced this signature, given a arbitrarily sized message
 	fn ed25519_verify(signature: &[u8; 64], msg: &[u8], pubkey: &[u8]) -> bool;
 
-	/// This function should verify membership in a trie proof using sp_state_machine's
-	/// read_child_proof_check
-	fn verify_membership_trie_proof(
-		root: &[u8; 32],
-		proof: &[Vec<u8>],
-		key: &[u8],
-		value: &[u8],
-	) -> Result<(), Error>;
-
-	/// This function should verify non membership in a trie proof using sp_state_machine's
-	/// read_child_proof_check
-	fn verify_non_membership_trie_proof(
-		root: &[u8; 32],
-		proof: &[Vec<u8>],
-		key: &[u8],
-	) -> Result<(), Error>;
-
 	/// This function should verify membership in a trie proof using parity's sp-trie package
 	/// with a BlakeTwo256 Hasher
 	fn verify_timestamp_extrinsic(
@@ -160,28 +168,40 @@ impl<H: HostFunctionsTrait> ClientDef for NearClient<H> {
 		&self,
 		_ctx: &Ctx,
 		_client_id: ClientId,
-		_client_state: Self::ClientState,
-		_client_message: Self::ClientMessage,
+		mut client_state: Self::ClientState,
+		client_message: Self::ClientMessage,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Error> {
-		// 1. create new client state from this header, return that.
-		// 2. as well as all the neccessary consensus states.
-		//
-		//
-		// []--[]--[]--[]--[]--[]--[]--[]--[]--[]
-		// 11  12  13  14  15  16  17  18  19  20 <- block merkle root
-		// ^                                    ^
-		// |    <-------consensus states----->  |
-		// current state                       new state
-
-		todo!()
+		let NearClientMessage::Header(header) = client_message;
+		let block = header.inner;
+		let consensus_state = ConsensusState::from_light_client_block(&block)?;
+
+		// the block has been validated to be in either the current or the next epoch, if it's the
+		// latter then the next epoch's block producers become the current ones.
+		if block.inner_lite.epoch_id == client_state.next_epoch {
+			client_state.current_epoch = client_state.next_epoch;
+			client_state.current_validators = core::mem::take(&mut client_state.next_validators);
+		}
+
+		// the last block of an epoch carries the block producers of the following epoch.
+		if let Some(next_bps) = block.next_bps.clone() {
+			client_state.next_epoch = block.inner_lite.next_epoch_id;
+			client_state.next_validators = next_bps;
+		}
+
+		client_state.head = block;
+
+		let wrapped = Ctx::AnyConsensusState::wrap(&consensus_state)
+			.expect("AnyConsenusState is type checked; qed");
+		Ok((client_state, ConsensusUpdateResult::Single(wrapped)))
 	}
 
 	fn update_state_on_misbehaviour(
 		&self,
-		_client_state: Self::ClientState,
+		mut client_state: Self::ClientState,
 		_client_message: Self::ClientMessage,
 	) -> Result<Self::ClientState, Error> {
-		todo!()
+		client_state.frozen_height = Some(client_state.head.get_height());
+		Ok(client_state)
 	}
 
 	fn check_for_misbehaviour<Ctx: ReaderContext>(
@@ -196,30 +216,77 @@ impl<H: HostFunctionsTrait> ClientDef for NearClient<H> {
 
 	fn verify_upgrade_and_update_state<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
-		_client_id: ClientId,
-		_old_client_state: &Self::ClientState,
-		_upgrade_client_state: &Self::ClientState,
-		_upgrade_consensus_state: &Self::ConsensusState,
-		_proof_upgrade_client: Vec<u8>,
-		_proof_upgrade_consensus_state: Vec<u8>,
+		ctx: &Ctx,
+		client_id: ClientId,
+		old_client_state: &Self::ClientState,
+		upgrade_client_state: &Self::ClientState,
+		upgrade_consensus_state: &Self::ConsensusState,
+		proof_upgrade_client: Vec<u8>,
+		proof_upgrade_consensus_state: Vec<u8>,
 	) -> Result<(Self::ClientState, ConsensusUpdateResult<Ctx>), Error> {
-		todo!()
+		let height = old_client_state.latest_height();
+		let consensus_state = ctx
+			.consensus_state(&client_id, height)?
+			.downcast::<Self::ConsensusState>()
+			.ok_or_else(|| {
+				Error::client_error(
+					NearClientState::<H>::client_type(),
+					format!("Wrong consensus state type stored for NEAR client with {client_id} at {height}"),
+				)
+			})?;
+		let root = consensus_state.root();
+
+		let encoded_client_state = Ctx::AnyClientState::wrap(upgrade_client_state)
+			.expect("AnyClientState is type-checked; qed")
+			.encode_to_vec()
+			.map_err(Error::encode)?;
+		verify_trie_proof::<H>(
+			root,
+			&proof_upgrade_client,
+			CLIENT_STATE_UPGRADE_PATH,
+			Some(&encoded_client_state[..]),
+		)?;
+
+		let encoded_consensus_state = Ctx::AnyConsensusState::wrap(upgrade_consensus_state)
+			.expect("AnyConsensusState is type-checked; qed")
+			.encode_to_vec()
+			.map_err(Error::encode)?;
+		verify_trie_proof::<H>(
+			root,
+			&proof_upgrade_consensus_state,
+			CONSENSUS_STATE_UPGRADE_PATH,
+			Some(&encoded_consensus_state[..]),
+		)?;
+
+		Ok((
+			upgrade_client_state.clone(),
+			ConsensusUpdateResult::Single(
+				Ctx::AnyConsensusState::wrap(upgrade_consensus_state)
+					.expect("AnyConsensusState is type-checked; qed"),
+			),
+		))
 	}
 
 	fn verify_client_consensus_state<Ctx: ReaderContext>(
 		&self,
 		_ctx: &Ctx,
-		_client_state: &Self::ClientState,
-		_height: Height,
-		_prefix: &CommitmentPrefix,
-		_proof: &CommitmentProofBytes,
-		_root: &CommitmentRoot,
-		_client_id: &ClientId,
-		_consensus_height: Height,
-		_expected_consensus_state: &Ctx::AnyConsensusState,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		client_id: &ClientId,
+		consensus_height: Height,
+		expected_consensus_state: &Ctx::AnyConsensusState,
 	) -> Result<(), Error> {
-		todo!()
+		client_state.verify_height(height)?;
+		let path = ClientConsensusStatePath {
+			client_id: client_id.clone(),
+			epoch: consensus_height.revision_number,
+			height: consensus_height.revision_height,
+		};
+		let value = expected_consensus_state.encode_to_vec().map_err(Error::encode)?;
+		verify_membership::<H, _>(prefix, proof, root, path, value)
 	}
 
 	// Consensus state will be verified in the verification functions  before these are called
@@ -227,112 +294,260 @@ impl<H: HostFunctionsTrait> ClientDef for NearClient<H> {
 		&self,
 		_ctx: &Ctx,
 		_client_id: &ClientId,
-		_client_state: &Self::ClientState,
-		_height: Height,
-		_prefix: &CommitmentPrefix,
-		_proof: &CommitmentProofBytes,
-		_root: &CommitmentRoot,
-		_connection_id: &ConnectionId,
-		_expected_connection_end: &ConnectionEnd,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		connection_id: &ConnectionId,
+		expected_connection_end: &ConnectionEnd,
 	) -> Result<(), Error> {
-		todo!()
+		client_state.verify_height(height)?;
+		let path = ConnectionsPath(connection_id.clone());
+		let value = expected_connection_end.encode_vec().map_err(Error::encode)?;
+		verify_membership::<H, _>(prefix, proof, root, path, value)
 	}
 
 	fn verify_channel_state<Ctx: ReaderContext>(
 		&self,
 		_ctx: &Ctx,
 		_client_id: &ClientId,
-		_client_state: &Self::ClientState,
-		_height: Height,
-		_prefix: &CommitmentPrefix,
-		_proof: &CommitmentProofBytes,
-		_root: &CommitmentRoot,
-		_port_id: &PortId,
-		_channel_id: &ChannelId,
-		_expected_channel_end: &ChannelEnd,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		expected_channel_end: &ChannelEnd,
 	) -> Result<(), Error> {
-		todo!()
+		client_state.verify_height(height)?;
+		let path = ChannelEndsPath(port_id.clone(), *channel_id);
+		let value = expected_channel_end.encode_vec().map_err(Error::encode)?;
+		verify_membership::<H, _>(prefix, proof, root, path, value)
 	}
 
 	fn verify_client_full_state<Ctx: ReaderContext>(
 		&self,
 		_ctx: &Ctx,
-		_client_state: &Self::ClientState,
-		_height: Height,
-		_prefix: &CommitmentPrefix,
-		_proof: &CommitmentProofBytes,
-		_root: &CommitmentRoot,
-		_client_id: &ClientId,
-		_expected_client_state: &Ctx::AnyClientState,
+		client_state: &Self::ClientState,
+		height: Height,
+		prefix: &CommitmentPrefix,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		client_id: &ClientId,
+		expected_client_state: &Ctx::AnyClientState,
 	) -> Result<(), Error> {
-		todo!()
+		client_state.verify_height(height)?;
+		let path = ClientStatePath(client_id.clone());
+		let value = expected_client_state.encode_to_vec().map_err(Error::encode)?;
+		verify_membership::<H, _>(prefix, proof, root, path, value)
 	}
 
 	fn verify_packet_data<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
+		ctx: &Ctx,
 		_client_id: &ClientId,
-		_client_state: &Self::ClientState,
-		_height: Height,
-		_connection_end: &ConnectionEnd,
-		_proof: &CommitmentProofBytes,
-		_root: &CommitmentRoot,
-		_port_id: &PortId,
-		_channel_id: &ChannelId,
-		_sequence: Sequence,
-		_commitment: PacketCommitment,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		commitment: PacketCommitment,
 	) -> Result<(), Error> {
-		todo!()
+		client_state.verify_height(height)?;
+		verify_delay_passed::<H, _>(ctx, height, connection_end)
+			.map_err(|e| Error::implementation_specific(e.to_string()))?;
+
+		let commitment_path =
+			CommitmentsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		verify_membership::<H, _>(
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			commitment_path,
+			commitment.into_vec(),
+		)
 	}
 
 	fn verify_packet_acknowledgement<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
+		ctx: &Ctx,
 		_client_id: &ClientId,
-		_client_state: &Self::ClientState,
-		_height: Height,
-		_connection_end: &ConnectionEnd,
-		_proof: &CommitmentProofBytes,
-		_root: &CommitmentRoot,
-		_port_id: &PortId,
-		_channel_id: &ChannelId,
-		_sequence: Sequence,
-		_ack: AcknowledgementCommitment,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+		ack: AcknowledgementCommitment,
 	) -> Result<(), Error> {
-		todo!()
+		client_state.verify_height(height)?;
+		verify_delay_passed::<H, _>(ctx, height, connection_end)
+			.map_err(|e| Error::implementation_specific(e.to_string()))?;
+
+		let ack_path = AcksPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		verify_membership::<H, _>(
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			ack_path,
+			ack.into_vec(),
+		)
 	}
 
 	fn verify_next_sequence_recv<Ctx: ReaderContext>(
 		&self,
-		_ctx: &Ctx,
+		ctx: &Ctx,
 		_client_id: &ClientId,
-		_client_state: &Self::ClientState,
-		_height: Height,
-		_connection_end: &ConnectionEnd,
-		_proof: &CommitmentProofBytes,
-		_root: &CommitmentRoot,
-		_port_id: &PortId,
-		_channel_id: &ChannelId,
-		_sequence: Sequence,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
 	) -> Result<(), Error> {
-		todo!()
+		client_state.verify_height(height)?;
+		verify_delay_passed::<H, _>(ctx, height, connection_end)
+			.map_err(|e| Error::implementation_specific(e.to_string()))?;
+
+		let seq_bytes = u64::from(sequence)
+			.try_to_vec()
+			.map_err(|_| Error::from(NearError::serialization_error()))?;
+		let seq_path = SeqRecvsPath(port_id.clone(), *channel_id);
+		verify_membership::<H, _>(
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			seq_path,
+			seq_bytes,
+		)
 	}
 
 	fn verify_packet_receipt_absence<Ctx: ReaderContext>(
+		&self,
+		ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		sequence: Sequence,
+	) -> Result<(), Error> {
+		client_state.verify_height(height)?;
+		verify_delay_passed::<H, _>(ctx, height, connection_end)
+			.map_err(|e| Error::implementation_specific(e.to_string()))?;
+
+		let receipt_path =
+			ReceiptsPath { port_id: port_id.clone(), channel_id: *channel_id, sequence };
+		verify_non_membership::<H, _>(
+			connection_end.counterparty().prefix(),
+			proof,
+			root,
+			receipt_path,
+		)
+	}
+
+	fn verify_channel_upgrade<Ctx: ReaderContext>(
 		&self,
 		_ctx: &Ctx,
 		_client_id: &ClientId,
-		_client_state: &Self::ClientState,
-		_height: Height,
-		_connection_end: &ConnectionEnd,
-		_proof: &CommitmentProofBytes,
-		_root: &CommitmentRoot,
-		_port_id: &PortId,
-		_channel_id: &ChannelId,
-		_sequence: Sequence,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		upgrade: &Upgrade,
 	) -> Result<(), Error> {
-		todo!()
+		client_state.verify_height(height)?;
+		let path = ChannelUpgradesPath(port_id.clone(), *channel_id);
+		let value = upgrade.encode_vec().map_err(Error::encode)?;
+		verify_membership::<H, _>(connection_end.counterparty().prefix(), proof, root, path, value)
 	}
+
+	fn verify_channel_upgrade_error<Ctx: ReaderContext>(
+		&self,
+		_ctx: &Ctx,
+		_client_id: &ClientId,
+		client_state: &Self::ClientState,
+		height: Height,
+		connection_end: &ConnectionEnd,
+		proof: &CommitmentProofBytes,
+		root: &CommitmentRoot,
+		port_id: &PortId,
+		channel_id: &ChannelId,
+		error_receipt: &ErrorReceipt,
+	) -> Result<(), Error> {
+		client_state.verify_height(height)?;
+		let path = ChannelUpgradeErrorsPath(port_id.clone(), *channel_id);
+		let value = error_receipt.encode_vec().map_err(Error::encode)?;
+		verify_membership::<H, _>(connection_end.counterparty().prefix(), proof, root, path, value)
+	}
+}
+
+/// Membership proof verification of an ics-24 path against the NEAR state root.
+fn verify_membership<H: HostFunctions, P: Into<Path>>(
+	prefix: &CommitmentPrefix,
+	proof: &CommitmentProofBytes,
+	root: &CommitmentRoot,
+	path: P,
+	value: Vec<u8>,
+) -> Result<(), Error> {
+	let path: Path = path.into();
+	let key = apply_prefix(prefix, path);
+	verify_trie_proof::<H>(root, proof.as_bytes(), &key, Some(&value[..]))
+}
+
+/// Non-membership proof verification of an ics-24 path against the NEAR state root.
+fn verify_non_membership<H: HostFunctions, P: Into<Path>>(
+	prefix: &CommitmentPrefix,
+	proof: &CommitmentProofBytes,
+	root: &CommitmentRoot,
+	path: P,
+) -> Result<(), Error> {
+	let path: Path = path.into();
+	let key = apply_prefix(prefix, path);
+	verify_trie_proof::<H>(root, proof.as_bytes(), &key, None)
+}
+
+/// Verifies a borsh encoded list of NEAR trie nodes against the given state root. When `value` is
+/// `None` the proof is checked for the absence of `key`.
+fn verify_trie_proof<H: HostFunctions>(
+	root: &CommitmentRoot,
+	proof: &[u8],
+	key: &[u8],
+	value: Option<&[u8]>,
+) -> Result<(), Error> {
+	let root = CryptoHash::try_from(root.as_bytes()).map_err(|_| {
+		Error::from(NearError::invalid_proof(format!(
+			"invalid commitment root length: {}",
+			root.as_bytes().len()
+		)))
+	})?;
+	let nodes = Vec::<Vec<u8>>::try_from_slice(proof).map_err(|e| {
+		Error::from(NearError::invalid_proof(format!("failed to decode proof nodes: {e}")))
+	})?;
+	trie::verify_state_proof::<H>(&root, &nodes, key, value).map_err(Into::into)
+}
+
+/// The commitment prefix of a NEAR chain is the trie key prefix of the IBC contract's storage: the
+/// contract data column, the contract's account id and the `,` separator.
+fn apply_prefix(prefix: &CommitmentPrefix, path: Path) -> Vec<u8> {
+	let mut key = prefix.as_bytes().to_vec();
+	key.extend(path.to_string().as_bytes());
+	key
 }
 
 /// validates a light block that's contained on the `NearHeader` based on the current
@@ -405,7 +620,7 @@ where
 		let validator_public_key = &bp_stake_view.public_key;
 		let data = H::sha256_digest(&approval_message);
 		let signature = maybe_signature.as_ref().unwrap();
-		if H::ed25519_verify(signature.get_inner(), &data, validator_public_key.get_inner()) {
+		if !H::ed25519_verify(signature.get_inner(), &data, validator_public_key.get_inner()) {
 			return Err(NearError::invalid_signature().into())
 		}
 	}
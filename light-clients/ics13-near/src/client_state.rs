@@ -14,7 +14,13 @@
 // limitations under the License.
 
 use super::types::{CryptoHash, LightClientBlockView, ValidatorStakeView};
-use crate::client_def::{HostFunctionsTrait, NearClient};
+use crate::{
+	client_def::{HostFunctionsTrait, NearClient},
+	error::Error,
+	proto::ClientState as RawClientState,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use core::{marker::PhantomData, time::Duration};
 use ibc::{
 	core::{
 		ics02_client::client_state::{ClientState, ClientType},
@@ -24,24 +30,51 @@ use ibc::{
 	Height,
 };
 use serde::{Deserialize, Serialize};
-use std::{marker::PhantomData, time::Duration};
 use tendermint_proto::Protobuf;
 
+/// Protobuf type url for NEAR ClientState
+pub const NEAR_CLIENT_STATE_TYPE_URL: &str = "/ibc.lightclients.near.v1.ClientState";
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct NearClientState<H> {
-	chain_id: ChainId,
-	head: LightClientBlockView,
-	current_epoch: CryptoHash,
-	next_epoch: CryptoHash,
-	current_validators: Vec<ValidatorStakeView>,
-	next_validators: Vec<ValidatorStakeView>,
-	_phantom: PhantomData<H>,
+	pub chain_id: ChainId,
+	/// Latest verified block
+	pub head: LightClientBlockView,
+	/// Epoch of the latest verified block
+	pub current_epoch: CryptoHash,
+	/// Epoch following the current epoch
+	pub next_epoch: CryptoHash,
+	/// Block producers of the current epoch
+	pub current_validators: Vec<ValidatorStakeView>,
+	/// Block producers of the next epoch
+	pub next_validators: Vec<ValidatorStakeView>,
+	/// Block height when the client was frozen due to a misbehaviour
+	pub frozen_height: Option<Height>,
+	pub _phantom: PhantomData<H>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NearUpgradeOptions {}
 
-impl<H: HostFunctionsTrait> NearClientState<H> {
+impl<H> NearClientState<H> {
+	pub fn new(
+		chain_id: ChainId,
+		head: LightClientBlockView,
+		current_validators: Vec<ValidatorStakeView>,
+		next_validators: Vec<ValidatorStakeView>,
+	) -> Self {
+		Self {
+			chain_id,
+			current_epoch: head.inner_lite.epoch_id,
+			next_epoch: head.inner_lite.next_epoch_id,
+			head,
+			current_validators,
+			next_validators,
+			frozen_height: None,
+			_phantom: PhantomData,
+		}
+	}
+
 	pub fn get_validators_by_epoch(
 		&self,
 		epoch_id: &CryptoHash,
@@ -58,6 +91,35 @@ impl<H: HostFunctionsTrait> NearClientState<H> {
 	pub fn get_head(&self) -> &LightClientBlockView {
 		&self.head
 	}
+
+	pub fn latest_height(&self) -> Height {
+		self.head.get_height()
+	}
+
+	/// Verify that the client is at a sufficient height and unfrozen at the given height
+	pub fn verify_height(&self, height: Height) -> Result<(), Error> {
+		let latest_height = self.head.get_height();
+		if latest_height < height {
+			return Err(Error::insufficient_height(latest_height, height))
+		}
+
+		match self.frozen_height {
+			Some(frozen_height) if frozen_height <= height =>
+				Err(Error::client_frozen(frozen_height)),
+			_ => Ok(()),
+		}
+	}
+
+	pub fn with_frozen_height(self, h: Height) -> Result<Self, Error> {
+		if h == Height::zero() {
+			return Err(Error::zero_frozen_height())
+		}
+		Ok(Self { frozen_height: Some(h), ..self })
+	}
+
+	pub fn client_type() -> ClientType {
+		"13-near".to_string()
+	}
 }
 
 impl<H: HostFunctionsTrait> ClientState for NearClientState<H> {
@@ -77,49 +139,98 @@ impl<H: HostFunctionsTrait> ClientState for NearClientState<H> {
 	}
 
 	fn latest_height(&self) -> Height {
-		self.head.get_height()
+		self.latest_height()
 	}
 
 	fn frozen_height(&self) -> Option<Height> {
-		// TODO: validate this
-		Some(self.head.get_height())
+		self.frozen_height
 	}
 
 	fn upgrade(
-		self,
+		mut self,
 		_upgrade_height: Height,
 		_upgrade_options: Self::UpgradeOptions,
-		_chain_id: ChainId,
+		chain_id: ChainId,
 	) -> Self {
-		// TODO: validate this -- not sure how to process the given parameters in this case
+		// the validator sets are carried over from the upgraded client state, only the
+		// chain-specified parameters are reset here.
+		self.frozen_height = None;
+		self.chain_id = chain_id;
 		self
 	}
 
 	fn expired(&self, _elapsed: Duration) -> bool {
-		todo!()
+		// the client tracks validator sets epoch by epoch, so it can't expire. Updates that skip
+		// more than one epoch are rejected in `validate_light_block`.
+		false
 	}
 
 	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
-		todo!("implement encoding")
+		self.encode_vec()
 	}
 }
 
-impl<H> NearClientState<H> {
-	pub fn client_type() -> ClientType {
-		"13-near".to_string()
+impl<H: Clone> Protobuf<RawClientState> for NearClientState<H> {}
+
+impl<H> TryFrom<RawClientState> for NearClientState<H> {
+	type Error = Error;
+
+	fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
+		let decode_validators = |validators: Vec<Vec<u8>>| {
+			validators
+				.into_iter()
+				.map(|bytes| {
+					ValidatorStakeView::try_from_slice(&bytes)
+						.map_err(|e| Error::decode(format!("invalid validator stake: {e}")))
+				})
+				.collect::<Result<Vec<_>, Error>>()
+		};
+		let decode_hash = |bytes: Vec<u8>| {
+			CryptoHash::try_from(bytes.as_slice())
+				.map_err(|e| Error::decode(format!("invalid epoch id: {e:?}")))
+		};
+
+		let chain_id = ChainId::from_string(&raw.chain_id);
+		let head = LightClientBlockView::try_from_slice(&raw.head)
+			.map_err(|e| Error::decode(format!("invalid head: {e}")))?;
+
+		Ok(Self {
+			frozen_height: raw.frozen_height.map(|height| Height::new(0, height)),
+			chain_id,
+			head,
+			current_epoch: decode_hash(raw.current_epoch)?,
+			next_epoch: decode_hash(raw.next_epoch)?,
+			current_validators: decode_validators(raw.current_validators)?,
+			next_validators: decode_validators(raw.next_validators)?,
+			_phantom: PhantomData,
+		})
 	}
 }
 
-impl<H: HostFunctionsTrait> Protobuf<()> for NearClientState<H> {}
-
-impl<H: HostFunctionsTrait> From<NearClientState<H>> for () {
-	fn from(_: NearClientState<H>) -> Self {
-		todo!()
-	}
-}
-
-impl<H: HostFunctionsTrait> From<()> for NearClientState<H> {
-	fn from(_: ()) -> Self {
-		todo!()
+impl<H> From<NearClientState<H>> for RawClientState {
+	fn from(client_state: NearClientState<H>) -> Self {
+		let encode_validators = |validators: Vec<ValidatorStakeView>| {
+			validators
+				.iter()
+				.map(|validator| {
+					validator.try_to_vec().expect("borsh serialization into a vec can't fail")
+				})
+				.collect()
+		};
+
+		RawClientState {
+			chain_id: client_state.chain_id.to_string(),
+			head: client_state
+				.head
+				.try_to_vec()
+				.expect("borsh serialization into a vec can't fail"),
+			current_epoch: client_state.current_epoch.0.to_vec(),
+			next_epoch: client_state.next_epoch.0.to_vec(),
+			current_validators: encode_validators(client_state.current_validators),
+			next_validators: encode_validators(client_state.next_validators),
+			frozen_height: client_state
+				.frozen_height
+				.map(|frozen_height| frozen_height.revision_height),
+		}
 	}
 }
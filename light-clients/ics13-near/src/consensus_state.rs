@@ -13,20 +13,40 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::error::Error;
+use super::{error::Error, types::LightClientBlockView};
+use crate::proto::ConsensusState as RawConsensusState;
 use ibc::{
 	core::{
 		ics02_client::client_consensus::{self},
 		ics23_commitment::commitment::CommitmentRoot,
 	},
+	prelude::*,
 	timestamp::Timestamp,
 };
 use serde::Serialize;
 use tendermint_proto::Protobuf;
 
+/// Protobuf type url for NEAR ConsensusState
+pub const NEAR_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.near.v1.ConsensusState";
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct ConsensusState {
-	commitment_root: CommitmentRoot,
+	pub commitment_root: CommitmentRoot,
+	pub timestamp: Timestamp,
+}
+
+impl ConsensusState {
+	pub fn new(root: Vec<u8>, timestamp: Timestamp) -> Self {
+		Self { commitment_root: root.into(), timestamp }
+	}
+
+	/// The commitment root is the state root that the block commits to, since NEAR executes
+	/// chunks one block late, this is the state after the previous block.
+	pub fn from_light_client_block(block: &LightClientBlockView) -> Result<Self, Error> {
+		let timestamp = Timestamp::from_nanoseconds(block.inner_lite.timestamp_nanosec)
+			.map_err(|e| Error::decode(format!("invalid block timestamp: {e}")))?;
+		Ok(Self::new(block.inner_lite.prev_state_root.0.to_vec(), timestamp))
+	}
 }
 
 impl client_consensus::ConsensusState for ConsensusState {
@@ -37,24 +57,31 @@ impl client_consensus::ConsensusState for ConsensusState {
 	}
 
 	fn timestamp(&self) -> Timestamp {
-		todo!()
+		self.timestamp
 	}
 
 	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
-		todo!("Encode to vec")
+		self.encode_vec()
 	}
 }
 
-impl Protobuf<()> for ConsensusState {}
+impl Protobuf<RawConsensusState> for ConsensusState {}
+
+impl TryFrom<RawConsensusState> for ConsensusState {
+	type Error = Error;
 
-impl From<ConsensusState> for () {
-	fn from(_: ConsensusState) -> Self {
-		todo!()
+	fn try_from(raw: RawConsensusState) -> Result<Self, Self::Error> {
+		let timestamp = Timestamp::from_nanoseconds(raw.timestamp)
+			.map_err(|e| Error::decode(format!("invalid consensus state timestamp: {e}")))?;
+		Ok(Self::new(raw.root, timestamp))
 	}
 }
 
-impl From<()> for ConsensusState {
-	fn from(_: ()) -> Self {
-		todo!()
+impl From<ConsensusState> for RawConsensusState {
+	fn from(value: ConsensusState) -> Self {
+		RawConsensusState {
+			root: value.commitment_root.into_vec(),
+			timestamp: value.timestamp.nanoseconds(),
+		}
 	}
 }
@@ -15,8 +15,9 @@
 
 use super::types::CryptoHash;
 use crate::client_state::NearClientState;
+use alloc::string::String;
 use flex_error::define_error;
-use ibc::core::ics02_client::error::Error as Ics02Error;
+use ibc::{core::ics02_client::error::Error as Ics02Error, Height};
 
 define_error! {
 	#[derive(Debug, PartialEq, Eq)]
@@ -44,6 +45,30 @@ define_error! {
 		| _ | { format_args!(
 			"unavailable block producers")
 		},
+		InsufficientHeight
+		{ latest_height: Height, target_height: Height }
+		| e | { format_args!(
+			"insufficient height, known height: {}, given height: {}", e.latest_height, e.target_height)
+		},
+		ClientFrozen
+		{ frozen_height: Height }
+		| e | { format_args!(
+			"client has been frozen at height {}", e.frozen_height)
+		},
+		ZeroFrozenHeight
+		| _ | { format_args!(
+			"client state frozen height must be greater than zero")
+		},
+		InvalidProof
+		{ reason: String }
+		| e | { format_args!(
+			"invalid proof: {}", e.reason)
+		},
+		Decode
+		{ reason: String }
+		| e | { format_args!(
+			"decoding error: {}", e.reason)
+		},
 	}
 }
 
@@ -13,41 +13,58 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ibc::{core::ics02_client::client_message::ClientMessage, Height};
+use borsh::{BorshDeserialize, BorshSerialize};
+use ibc::{core::ics02_client::client_message::ClientMessage, prelude::*, Height};
 use tendermint_proto::Protobuf;
 
 use super::types::LightClientBlockView;
+use crate::{
+	error::Error,
+	proto::{client_message, ClientMessage as RawClientMessage, Header as RawHeader},
+};
+
+/// Protobuf type url for NEAR ClientMessage
+pub const NEAR_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.near.v1.ClientMessage";
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct NearHeader {
-	inner: LightClientBlockView,
+	pub inner: LightClientBlockView,
 }
 
 impl NearHeader {
-	pub fn get_light_client_block_view(&self) -> &LightClientBlockView {
-		&self.inner
+	pub fn new(inner: LightClientBlockView) -> Self {
+		Self { inner }
 	}
 
-	pub fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
-		unimplemented!()
+	pub fn get_light_client_block_view(&self) -> &LightClientBlockView {
+		&self.inner
 	}
 
 	pub fn height(&self) -> Height {
-		todo!()
+		self.inner.get_height()
 	}
 }
 
-impl Protobuf<()> for NearHeader {}
+impl Protobuf<RawHeader> for NearHeader {}
+
+impl TryFrom<RawHeader> for NearHeader {
+	type Error = Error;
 
-impl From<NearHeader> for () {
-	fn from(_: NearHeader) -> Self {
-		todo!()
+	fn try_from(raw: RawHeader) -> Result<Self, Self::Error> {
+		let inner = LightClientBlockView::try_from_slice(&raw.light_client_block)
+			.map_err(|e| Error::decode(format!("invalid light client block: {e}")))?;
+		Ok(Self { inner })
 	}
 }
 
-impl From<()> for NearHeader {
-	fn from(_: ()) -> Self {
-		todo!()
+impl From<NearHeader> for RawHeader {
+	fn from(header: NearHeader) -> Self {
+		RawHeader {
+			light_client_block: header
+				.inner
+				.try_to_vec()
+				.expect("borsh serialization into a vec can't fail"),
+		}
 	}
 }
 
@@ -58,6 +75,32 @@ pub enum NearClientMessage {
 
 impl ClientMessage for NearClientMessage {
 	fn encode_to_vec(&self) -> Result<Vec<u8>, tendermint_proto::Error> {
-		todo!()
+		self.encode_vec()
+	}
+}
+
+impl Protobuf<RawClientMessage> for NearClientMessage {}
+
+impl TryFrom<RawClientMessage> for NearClientMessage {
+	type Error = Error;
+
+	fn try_from(raw: RawClientMessage) -> Result<Self, Self::Error> {
+		let message = match raw
+			.message
+			.ok_or_else(|| Error::decode("client message is empty".to_string()))?
+		{
+			client_message::Message::Header(raw_header) =>
+				NearClientMessage::Header(raw_header.try_into()?),
+		};
+		Ok(message)
+	}
+}
+
+impl From<NearClientMessage> for RawClientMessage {
+	fn from(client_message: NearClientMessage) -> Self {
+		match client_message {
+			NearClientMessage::Header(header) =>
+				RawClientMessage { message: Some(client_message::Message::Header(header.into())) },
+		}
 	}
 }
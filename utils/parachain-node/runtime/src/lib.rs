@@ -722,6 +722,7 @@ impl pallet_ibc::Config for Runtime {
 	type Whitelist = AllowAll;
 	type MemoMessage = MemoMessage;
 	type HandleMemo = ();
+	type RuntimeCall = RuntimeCall;
 }
 
 pub struct AllowAll {}
@@ -952,6 +953,14 @@ impl_runtime_apis! {
 			Ibc::channel(channel_id, port_id).ok()
 		}
 
+		fn channel_upgrade(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryChannelUpgradeResponse> {
+			Ibc::channel_upgrade(channel_id, port_id).ok()
+		}
+
+		fn channel_upgrade_error(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::QueryUpgradeErrorResponse> {
+			Ibc::channel_upgrade_error(channel_id, port_id).ok()
+		}
+
 		fn channel_client(channel_id: Vec<u8>, port_id: Vec<u8>) -> Option<ibc_primitives::IdentifiedClientState> {
 			Ibc::channel_client(channel_id, port_id).ok()
 		}